@@ -0,0 +1,28 @@
+//! Emits a `cef_api_at_least = "<major>"` cfg for each CEF API milestone up to the version of the
+//! sys bindings, see the `compat` module.
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=DEP_CEF_VERSION_MAJOR");
+    println!("cargo:rerun-if-env-changed=DEP_CEF_API_MILESTONES");
+    let major: u32 = std::env::var("DEP_CEF_VERSION_MAJOR")
+        .ok()
+        .and_then(|major| major.parse().ok())
+        .expect("The sys crate didn't report the CEF version");
+    let milestones: Vec<u32> = std::env::var("DEP_CEF_API_MILESTONES")
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|milestone| milestone.parse().ok())
+        .collect();
+
+    let values: Vec<_> = milestones
+        .iter()
+        .map(|milestone| format!("\"{milestone}\""))
+        .collect();
+    println!(
+        "cargo:rustc-check-cfg=cfg(cef_api_at_least, values({}))",
+        values.join(", ")
+    );
+    for milestone in milestones.iter().filter(|&&milestone| milestone <= major) {
+        println!("cargo:rustc-cfg=cef_api_at_least=\"{milestone}\"");
+    }
+}
@@ -75,6 +75,27 @@ impl Rc for DemoClient {
 }
 
 impl ImplClient for DemoClient {
+    fn get_context_menu_handler(&self) -> Option<ContextMenuHandler> {
+        Some(
+            context_menu::ContextMenuHandlerBuilder::new()
+                .spellcheck_suggestions(true)
+                .build(),
+        )
+    }
+
+    fn get_display_handler(&self) -> Option<DisplayHandler> {
+        Some(
+            display::DisplayHandlerBuilder::new()
+                .on_address_change(|browser, frame, url| {
+                    if frame.is_main() {
+                        let security_state = browser.security_state();
+                        println!("{url}: {security_state:?}");
+                    }
+                })
+                .build(),
+        )
+    }
+
     fn get_raw(&self) -> *mut cef_sys::_cef_client_t {
         self.0 as *mut cef_sys::_cef_client_t
     }
@@ -126,9 +147,9 @@ impl Rc for DemoWindowDelegate {
 impl ImplViewDelegate for DemoWindowDelegate {
     fn on_child_view_changed(
         &self,
-        _view: Option<&mut impl ImplView>,
+        _view: Option<&impl ImplView>,
         _added: ::std::os::raw::c_int,
-        _child: Option<&mut impl ImplView>,
+        _child: Option<&impl ImplView>,
     ) {
         // view.as_panel().map(|x| x.as_window().map(|w| w.close()));
     }
@@ -141,19 +162,19 @@ impl ImplViewDelegate for DemoWindowDelegate {
 impl ImplPanelDelegate for DemoWindowDelegate {}
 
 impl ImplWindowDelegate for DemoWindowDelegate {
-    fn on_window_created(&self, window: Option<&mut impl ImplWindow>) {
+    fn on_window_created(&self, window: Option<&impl ImplWindow>) {
         if let Some(window) = window {
-            let mut view = self.browser_view.clone();
-            window.add_child_view(Some(&mut view));
+            let view = self.browser_view.clone();
+            window.add_child_view(Some(&view));
             window.show();
         }
     }
 
-    fn can_close(&self, _window: Option<&mut impl ImplWindow>) -> ::std::os::raw::c_int {
+    fn can_close(&self, _window: Option<&impl ImplWindow>) -> ::std::os::raw::c_int {
         1
     }
 
-    fn on_window_destroyed(&self, _window: Option<&mut impl ImplWindow>) {
+    fn on_window_destroyed(&self, _window: Option<&impl ImplWindow>) {
         quit_message_loop();
     }
 }
@@ -161,36 +182,36 @@ impl ImplWindowDelegate for DemoWindowDelegate {
 fn main() {
     let args = Args::new(std::env::args());
     // dbg!(&args);
-    let mut app = DemoApp::new();
+    let app = DemoApp::new();
     dbg!(initialize(
         Some(args.as_main_args()),
         Some(&Default::default()),
-        Some(&mut app),
+        Some(&app),
         std::ptr::null_mut()
     ));
     {
         dbg!(execute_process(
             Some(args.as_main_args()),
-            Some(&mut app),
+            Some(&app),
             std::ptr::null_mut()
         ));
 
         // let window_info = WindowInfo::new();
-        let mut client = DemoClient::new();
+        let client = DemoClient::new();
         let url = CefString::from(&CefStringUtf8::from("https://www.google.com"));
 
         let browser_view = browser_view_create(
-            Some(&mut client),
+            Some(&client),
             Some(&url),
             Some(&Default::default()),
-            Option::<&mut DictionaryValue>::None,
-            Option::<&mut RequestContext>::None,
-            Option::<&mut BrowserViewDelegate>::None,
+            Option::<&DictionaryValue>::None,
+            Option::<&RequestContext>::None,
+            Option::<&BrowserViewDelegate>::None,
         )
         .expect("Failed to create browser view");
-        let mut delegate = DemoWindowDelegate::new(browser_view);
+        let delegate = DemoWindowDelegate::new(browser_view);
 
-        let x = window_create_top_level(Some(&mut delegate)).expect("Failed to create window");
+        let x = window_create_top_level(Some(&delegate)).expect("Failed to create window");
         // dbg!(cef::create_browser(
         //     window_info,
         //     Some(client),
@@ -0,0 +1,58 @@
+//! Shows a page fullscreen with the kiosk preset, which blocks popups, dialogs, context menus and
+//! navigations to other origins: `cargo run --example kiosk -- https://example.com`.
+
+use cef::{
+    app::AppBuilder,
+    args::Args,
+    browser::BrowserOptions,
+    presets::{kiosk, KioskConfig},
+    *,
+};
+
+fn main() {
+    let args = Args::new(std::env::args());
+    if execute_process(
+        Some(args.as_main_args()),
+        Option::<&App>::None,
+        std::ptr::null_mut(),
+    ) >= 0
+    {
+        return;
+    }
+    let url = std::env::args()
+        .skip(1)
+        .find(|arg| !arg.starts_with("--"))
+        .unwrap_or_else(|| "https://example.com".to_string());
+
+    let mut config = KioskConfig::new(url.as_str());
+    config.hide_cursor = true;
+    let app = AppBuilder::new().switches(config.switches()).build();
+    let (settings, mut browser_settings, client) = kiosk(config);
+    initialize(
+        Some(args.as_main_args()),
+        Some(&settings),
+        Some(&app),
+        std::ptr::null_mut(),
+    );
+    {
+        let client = client.build();
+        let mut window_info = WindowInfo::default();
+        BrowserOptions::default()
+            .apply(&mut window_info, &mut browser_settings)
+            .expect("Invalid browser options");
+        let url = CefString::from(&CefStringUtf8::from(url.as_str()));
+        browser_host_create_browser_sync(
+            Some(&window_info),
+            Some(&client),
+            Some(&url),
+            Some(&browser_settings),
+            Option::<&DictionaryValue>::None,
+            Option::<&RequestContext>::None,
+        )
+        .expect("Failed to create browser");
+
+        run_message_loop();
+    }
+
+    shutdown();
+}
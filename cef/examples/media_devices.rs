@@ -0,0 +1,120 @@
+//! Lists the media devices and opens the camera whose label contains the first argument, without
+//! a permission prompt: `cargo run --example media_devices -- "Integrated Camera"`.
+
+use std::{
+    future::Future,
+    pin::pin,
+    task::{Context, Poll, Waker},
+    time::{Duration, Instant},
+};
+
+use cef::{
+    args::Args,
+    browser::{create_browser_sync, BrowserOptions},
+    client::ClientBuilder,
+    media_devices::{MediaAccessGrant, MediaDeviceInfo, MediaDeviceKind},
+    *,
+};
+
+const ORIGIN: &str = "https://example.com";
+
+/// Run the message loop until `future` resolves.
+fn wait<T>(future: impl Future<Output = T>) -> T {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(value) = future.as_mut().poll(&mut cx) {
+            return value;
+        }
+        do_message_loop_work();
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+fn pump(duration: Duration) {
+    let start = Instant::now();
+    while start.elapsed() < duration {
+        do_message_loop_work();
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+fn main() {
+    let args = Args::new(std::env::args());
+    if execute_process(
+        Some(args.as_main_args()),
+        Option::<&App>::None,
+        std::ptr::null_mut(),
+    ) >= 0
+    {
+        return;
+    }
+    let label = std::env::args()
+        .skip(1)
+        .find(|arg| !arg.starts_with("--"))
+        .unwrap_or_else(|| "Camera".to_string());
+
+    initialize(
+        Some(args.as_main_args()),
+        Some(&Default::default()),
+        Option::<&App>::None,
+        std::ptr::null_mut(),
+    );
+    {
+        let client = ClientBuilder::new()
+            .permission_handler(
+                MediaAccessGrant::new()
+                    .device(MediaDeviceKind::VideoInput)
+                    .origin(ORIGIN)
+                    .build(),
+            )
+            .build();
+        let browser = create_browser_sync(
+            &BrowserOptions::default(),
+            WindowInfo::default(),
+            &client,
+            ORIGIN,
+            None,
+        )
+        .expect("Failed to create browser");
+        pump(Duration::from_secs(3));
+
+        let host = browser.get_host().expect("Failed to get browser host");
+        let frame = browser.get_main_frame().expect("Failed to get main frame");
+        // Labels stay empty until the page was granted a camera once.
+        let _ = frame.execute_script(
+            "navigator.mediaDevices.getUserMedia({video: true}) \
+                .then(stream => stream.getTracks().forEach(track => track.stop()))",
+            ORIGIN,
+            0,
+        );
+        pump(Duration::from_secs(1));
+
+        let devices = wait(host.enumerate_media_devices()).expect("Failed to list devices");
+        for device in &devices {
+            println!("{:?}: {} ({})", device.kind, device.label, device.device_id);
+        }
+
+        match MediaDeviceInfo::find_by_label(&devices, MediaDeviceKind::VideoInput, &label) {
+            Some(camera) => {
+                println!("Opening {}", camera.label);
+                let script = format!(
+                    "navigator.mediaDevices.getUserMedia({}).then(stream => {{ \
+                        const video = document.createElement('video'); \
+                        video.autoplay = true; \
+                        video.srcObject = stream; \
+                        document.body.replaceChildren(video); \
+                    }})",
+                    camera.constraints()
+                );
+                let _ = frame.execute_script(&script, ORIGIN, 0);
+                pump(Duration::from_secs(10));
+            }
+            None => println!("No camera matches {label:?}"),
+        }
+        host.close_browser(1);
+        pump(Duration::from_secs(1));
+    }
+
+    shutdown();
+}
@@ -0,0 +1,59 @@
+use std::time::{Duration, Instant};
+
+use cef::{args::Args, media_router::MediaObserver, *};
+
+struct LoggingObserver;
+
+impl MediaObserver for LoggingObserver {
+    fn on_sinks(&self, sinks: Vec<MediaSink>) {
+        println!("{} sink(s)", sinks.len());
+        for sink in sinks {
+            println!("  {} ({}): {:?}", sink.name(), sink.id(), sink.icon_type());
+        }
+    }
+
+    fn on_routes(&self, routes: Vec<MediaRoute>) {
+        println!("{} route(s)", routes.len());
+    }
+
+    fn on_route_state_changed(&self, _route: MediaRoute, state: MediaRouteConnectionState) {
+        println!("Route state changed: {state:?}");
+    }
+
+    fn on_route_message_received(&self, _route: MediaRoute, message: &[u8]) {
+        println!("Route message: {}", String::from_utf8_lossy(message));
+    }
+}
+
+fn main() {
+    let args = Args::new(std::env::args());
+    if execute_process(
+        Some(args.as_main_args()),
+        Option::<&App>::None,
+        std::ptr::null_mut(),
+    ) >= 0
+    {
+        return;
+    }
+
+    initialize(
+        Some(args.as_main_args()),
+        Some(&Default::default()),
+        Option::<&App>::None,
+        std::ptr::null_mut(),
+    );
+    {
+        let router = MediaRouter::get_global(None).expect("Failed to get media router");
+        let _registration = router.observe(LoggingObserver);
+        router.notify_current_sinks();
+
+        // Log discovery for a while, even when there are no devices on the network.
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_secs(10) {
+            do_message_loop_work();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    shutdown();
+}
@@ -0,0 +1,224 @@
+//! Opens the popups of a windowless browser as tabs of a winit window, each with a frame stream
+//! of its own: `cargo run --example osr_popups -- https://example.com`.
+//!
+//! The window title lists the tabs with the number of frames each one painted. Press a digit to
+//! switch to a tab, and Escape to close the active popup. Only the active tab paints, the frames
+//! aren't drawn, see [cef::osr::FrameStream] for those.
+
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use cef::{
+    app::AppBuilder,
+    args::Args,
+    browser::{create_browser_sync, BrowserOptions},
+    client::ClientBuilder,
+    life_span::{OsrPopups, PopupAction, PopupRequest},
+    osr::{FrameStream, FrameStreamBuilder},
+    *,
+};
+use winit::{
+    application::ApplicationHandler,
+    event::{ElementState, WindowEvent},
+    event_loop::{ActiveEventLoop, ControlFlow, EventLoop},
+    keyboard::{Key, NamedKey},
+    window::{Window, WindowId},
+};
+
+struct Tab {
+    title: String,
+    host: BrowserHost,
+    frames: FrameStream,
+    painted: usize,
+}
+
+type Tabs = Rc<RefCell<Vec<Tab>>>;
+
+struct PopupTabs {
+    url: String,
+    window: Option<Window>,
+    tabs: Tabs,
+    active: usize,
+    // Keeps the popups routed into their tabs.
+    _popups: Option<OsrPopups>,
+}
+
+impl PopupTabs {
+    fn activate(&mut self, index: usize) {
+        let tabs = self.tabs.borrow();
+        if index >= tabs.len() {
+            return;
+        }
+        for (i, tab) in tabs.iter().enumerate() {
+            tab.host.osr().was_hidden(i != index);
+        }
+        self.active = index;
+    }
+
+    fn update_title(&self) {
+        let Some(window) = &self.window else {
+            return;
+        };
+        let tabs = self.tabs.borrow();
+        let titles: Vec<_> = tabs
+            .iter()
+            .enumerate()
+            .map(|(i, tab)| {
+                let marker = if i == self.active { "*" } else { "" };
+                format!("{}{marker} {} ({})", i + 1, tab.title, tab.painted)
+            })
+            .collect();
+        window.set_title(&titles.join(" | "));
+    }
+}
+
+impl ApplicationHandler for PopupTabs {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.window.is_some() {
+            return;
+        }
+        let window = event_loop
+            .create_window(Window::default_attributes().with_title("osr_popups"))
+            .expect("Failed to create window");
+        let size = window.inner_size().to_logical::<i32>(window.scale_factor());
+
+        let popups = OsrPopups::new({
+            let tabs = self.tabs.clone();
+            move |request: &PopupRequest| {
+                if !request.user_gesture {
+                    return PopupAction::Block;
+                }
+                let tabs = tabs.clone();
+                let title = request.target_url.clone();
+                PopupAction::open_as_osr(move |browser, frames| {
+                    let host = browser.get_host().expect("Failed to get browser host");
+                    let mut tabs = tabs.borrow_mut();
+                    // Paint in the background until the tab is activated.
+                    host.osr().was_hidden(true);
+                    tabs.push(Tab {
+                        title,
+                        host,
+                        frames,
+                        painted: 0,
+                    });
+                })
+            }
+        });
+        let frames = FrameStreamBuilder::new(size.width, size.height).build();
+        let client = ClientBuilder::new()
+            .render_handler(popups.render_handler(frames.render_handler()))
+            .push_life_span_handler(popups.life_span_handler())
+            .build();
+        let options = BrowserOptions {
+            windowless: true,
+            ..Default::default()
+        };
+        let browser =
+            create_browser_sync(&options, WindowInfo::default(), &client, &self.url, None)
+                .expect("Failed to create browser");
+        self.tabs.borrow_mut().push(Tab {
+            title: self.url.clone(),
+            host: browser.get_host().expect("Failed to get browser host"),
+            frames,
+            painted: 0,
+        });
+        self._popups = Some(popups);
+        self.window = Some(window);
+    }
+
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, _id: WindowId, event: WindowEvent) {
+        match event {
+            WindowEvent::CloseRequested => {
+                for tab in self.tabs.borrow().iter() {
+                    tab.host.close_browser(1);
+                }
+                event_loop.exit();
+            }
+            WindowEvent::Resized(size) => {
+                let Some(window) = &self.window else {
+                    return;
+                };
+                let size = size.to_logical::<i32>(window.scale_factor());
+                for tab in self.tabs.borrow().iter() {
+                    tab.frames.resize(size.width, size.height);
+                }
+            }
+            WindowEvent::KeyboardInput { event, .. } if event.state == ElementState::Pressed => {
+                match event.logical_key {
+                    Key::Character(digit) => {
+                        if let Some(digit) = digit.parse::<usize>().ok().filter(|&d| d > 0) {
+                            self.activate(digit - 1);
+                        }
+                    }
+                    Key::Named(NamedKey::Escape) if self.active > 0 => {
+                        // The stream of the popup ends when it closed.
+                        let tab = self.tabs.borrow_mut().remove(self.active);
+                        tab.host.close_browser(1);
+                        self.activate(self.active - 1);
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        do_message_loop_work();
+        for tab in self.tabs.borrow_mut().iter_mut() {
+            if tab.frames.latest().is_some() {
+                tab.painted += 1;
+            }
+        }
+        self.update_title();
+        event_loop.set_control_flow(ControlFlow::WaitUntil(
+            Instant::now() + Duration::from_millis(10),
+        ));
+    }
+}
+
+fn main() {
+    let args = Args::new(std::env::args());
+    if execute_process(
+        Some(args.as_main_args()),
+        Option::<&App>::None,
+        std::ptr::null_mut(),
+    ) >= 0
+    {
+        return;
+    }
+    let url = std::env::args()
+        .skip(1)
+        .find(|arg| !arg.starts_with("--"))
+        .unwrap_or_else(|| "https://example.com".to_string());
+
+    let app = AppBuilder::new().build();
+    let settings = Settings {
+        windowless_rendering_enabled: 1,
+        ..Default::default()
+    };
+    initialize(
+        Some(args.as_main_args()),
+        Some(&settings),
+        Some(&app),
+        std::ptr::null_mut(),
+    );
+    {
+        let event_loop = EventLoop::new().expect("Failed to create event loop");
+        let mut popup_tabs = PopupTabs {
+            url,
+            window: None,
+            tabs: Default::default(),
+            active: 0,
+            _popups: None,
+        };
+        event_loop
+            .run_app(&mut popup_tabs)
+            .expect("Failed to run event loop");
+    }
+
+    shutdown();
+}
@@ -0,0 +1,105 @@
+//! Prints the memory metrics of a browser and the browser processes every 5 seconds:
+//! `cargo run --example task_manager -- https://example.com`.
+
+use std::{
+    future::Future,
+    pin::pin,
+    task::{Context, Poll, Waker},
+    time::{Duration, Instant},
+};
+
+use cef::{
+    args::Args,
+    browser::{create_browser_sync, BrowserOptions},
+    client::ClientBuilder,
+    process::child_processes,
+    *,
+};
+
+const INTERVAL: Duration = Duration::from_secs(5);
+
+/// Run the message loop until `future` resolves.
+fn wait<T>(future: impl Future<Output = T>) -> T {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(value) = future.as_mut().poll(&mut cx) {
+            return value;
+        }
+        do_message_loop_work();
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+fn pump(duration: Duration) {
+    let start = Instant::now();
+    while start.elapsed() < duration {
+        do_message_loop_work();
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+fn main() {
+    let args = Args::new(std::env::args());
+    if execute_process(
+        Some(args.as_main_args()),
+        Option::<&App>::None,
+        std::ptr::null_mut(),
+    ) >= 0
+    {
+        return;
+    }
+    let url = std::env::args()
+        .skip(1)
+        .find(|arg| !arg.starts_with("--"))
+        .unwrap_or_else(|| "https://example.com".to_string());
+
+    initialize(
+        Some(args.as_main_args()),
+        Some(&Default::default()),
+        Option::<&App>::None,
+        std::ptr::null_mut(),
+    );
+    {
+        let client = ClientBuilder::new().build();
+        let browser = create_browser_sync(
+            &BrowserOptions::default(),
+            WindowInfo::default(),
+            &client,
+            &url,
+            None,
+        )
+        .expect("Failed to create browser");
+        let host = browser.get_host().expect("Failed to get browser host");
+
+        for _ in 0..12 {
+            pump(INTERVAL);
+
+            match wait(browser.renderer_memory_usage()) {
+                Ok(metrics) => println!(
+                    "JS heap {} / {} KiB, {} documents, {} nodes",
+                    metrics.js_heap_used_bytes / 1024,
+                    metrics.js_heap_total_bytes / 1024,
+                    metrics.documents,
+                    metrics.nodes
+                ),
+                Err(err) => println!("Memory metrics: {err}"),
+            }
+            match wait(child_processes(&host)) {
+                Ok(processes) => {
+                    for process in processes {
+                        println!(
+                            "  {:>8} {:<12} {:?}",
+                            process.id, process.title, process.cpu_time
+                        );
+                    }
+                }
+                Err(err) => println!("  Processes: {err}"),
+            }
+        }
+        host.close_browser(1);
+        pump(Duration::from_secs(1));
+    }
+
+    shutdown();
+}
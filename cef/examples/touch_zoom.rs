@@ -0,0 +1,191 @@
+//! Sends the touches and pinch gestures of a winit window to a windowless browser, and prints the
+//! visual viewport after each pinch: `cargo run --example touch_zoom -- https://example.com`.
+//!
+//! Pass `--zoom-level` to change the zoom level instead of sending touch events. The frames of
+//! the browser aren't drawn, see [cef::osr::FrameStream] for those.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+    time::{Duration, Instant},
+};
+
+use cef::{
+    app::AppBuilder,
+    args::Args,
+    browser::{create_browser_sync, BrowserOptions},
+    client::ClientBuilder,
+    devtools::DevToolsError,
+    osr::{FrameStream, FrameStreamBuilder},
+    switches::SwitchSet,
+    touch::{self, PinchMode, TouchPhase, TouchZoomController, VisualViewport},
+    *,
+};
+use winit::{
+    application::ApplicationHandler,
+    dpi::LogicalPosition,
+    event::{self, WindowEvent},
+    event_loop::{ActiveEventLoop, ControlFlow, EventLoop},
+    window::{Window, WindowId},
+};
+
+type ViewportFuture = Pin<Box<dyn Future<Output = Result<VisualViewport, DevToolsError>>>>;
+
+struct TouchBrowser {
+    host: BrowserHost,
+    // Keeps the render handler of the browser painting.
+    _frames: FrameStream,
+    controller: TouchZoomController,
+}
+
+struct TouchZoom {
+    url: String,
+    mode: PinchMode,
+    window: Option<Window>,
+    browser: Option<TouchBrowser>,
+    cursor: LogicalPosition<f32>,
+    viewport: Option<ViewportFuture>,
+}
+
+fn phase(phase: event::TouchPhase) -> TouchPhase {
+    match phase {
+        event::TouchPhase::Started => TouchPhase::Started,
+        event::TouchPhase::Moved => TouchPhase::Moved,
+        event::TouchPhase::Ended => TouchPhase::Ended,
+        event::TouchPhase::Cancelled => TouchPhase::Cancelled,
+    }
+}
+
+impl ApplicationHandler for TouchZoom {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.window.is_some() {
+            return;
+        }
+        let window = event_loop
+            .create_window(Window::default_attributes().with_title("touch_zoom"))
+            .expect("Failed to create window");
+        let size = window.inner_size().to_logical::<i32>(window.scale_factor());
+
+        let frames = FrameStreamBuilder::new(size.width, size.height).build();
+        let client = ClientBuilder::new()
+            .render_handler(frames.render_handler())
+            .build();
+        let options = BrowserOptions {
+            windowless: true,
+            ..Default::default()
+        };
+        let browser =
+            create_browser_sync(&options, WindowInfo::default(), &client, &self.url, None)
+                .expect("Failed to create browser");
+        let host = browser.get_host().expect("Failed to get browser host");
+        self.browser = Some(TouchBrowser {
+            controller: TouchZoomController::new(&host, self.mode),
+            host,
+            _frames: frames,
+        });
+        self.window = Some(window);
+    }
+
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, _id: WindowId, event: WindowEvent) {
+        let (Some(window), Some(browser)) = (&self.window, &mut self.browser) else {
+            return;
+        };
+        let scale_factor = window.scale_factor();
+        match event {
+            WindowEvent::CloseRequested => {
+                browser.host.close_browser(1);
+                event_loop.exit();
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor = position.to_logical(scale_factor);
+            }
+            WindowEvent::Touch(touch) => {
+                let position = touch.location.to_logical::<f32>(scale_factor);
+                let phase = phase(touch.phase);
+                browser
+                    .controller
+                    .touch(touch.id as i32, phase, position.x, position.y);
+                if phase == TouchPhase::Ended {
+                    self.viewport = Some(Box::pin(touch::visual_viewport(&browser.host)));
+                }
+            }
+            WindowEvent::PinchGesture { delta, phase, .. } => match phase {
+                event::TouchPhase::Started => {
+                    browser.controller.pinch_begin(self.cursor.x, self.cursor.y);
+                }
+                event::TouchPhase::Moved => browser.controller.pinch_update(delta),
+                event::TouchPhase::Ended | event::TouchPhase::Cancelled => {
+                    browser.controller.pinch_end();
+                    self.viewport = Some(Box::pin(touch::visual_viewport(&browser.host)));
+                }
+            },
+            _ => {}
+        }
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        do_message_loop_work();
+        if let Some(viewport) = &mut self.viewport {
+            let mut cx = Context::from_waker(Waker::noop());
+            if let Poll::Ready(viewport) = viewport.as_mut().poll(&mut cx) {
+                println!("{viewport:?}");
+                self.viewport = None;
+            }
+        }
+        event_loop.set_control_flow(ControlFlow::WaitUntil(
+            Instant::now() + Duration::from_millis(10),
+        ));
+    }
+}
+
+fn main() {
+    let args = Args::new(std::env::args());
+    if execute_process(
+        Some(args.as_main_args()),
+        Option::<&App>::None,
+        std::ptr::null_mut(),
+    ) >= 0
+    {
+        return;
+    }
+    let url = std::env::args()
+        .skip(1)
+        .find(|arg| !arg.starts_with("--"))
+        .unwrap_or_else(|| "https://example.com".to_string());
+    let mode = if std::env::args().any(|arg| arg == "--zoom-level") {
+        PinchMode::ZoomLevel
+    } else {
+        PinchMode::TouchEvents
+    };
+
+    let app = AppBuilder::new()
+        .switches(SwitchSet::new().raw("touch-events", Some("enabled")))
+        .build();
+    let settings = Settings {
+        windowless_rendering_enabled: 1,
+        ..Default::default()
+    };
+    initialize(
+        Some(args.as_main_args()),
+        Some(&settings),
+        Some(&app),
+        std::ptr::null_mut(),
+    );
+    {
+        let event_loop = EventLoop::new().expect("Failed to create event loop");
+        let mut touch_zoom = TouchZoom {
+            url,
+            mode,
+            window: None,
+            browser: None,
+            cursor: LogicalPosition::new(0.0, 0.0),
+            viewport: None,
+        };
+        event_loop
+            .run_app(&mut touch_zoom)
+            .expect("Failed to run event loop");
+    }
+
+    shutdown();
+}
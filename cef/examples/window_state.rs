@@ -0,0 +1,163 @@
+//! Restores the placement of a Views window from `window_state.json` in the current directory,
+//! and saves it when the window closes: `cargo run --example window_state --features serde`.
+
+use std::{
+    fs,
+    sync::{Arc, Mutex},
+};
+
+use cef::{
+    app::AppBuilder, args::Args, client::ClientBuilder, rc::*, window_state::WindowState, *,
+};
+
+const STATE_FILE: &str = "window_state.json";
+
+fn load() -> Option<WindowState> {
+    let json = fs::read_to_string(STATE_FILE).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+fn save(state: &WindowState) {
+    let json = serde_json::to_string_pretty(state).expect("Failed to serialize window state");
+    if let Err(err) = fs::write(STATE_FILE, json) {
+        eprintln!("Failed to save {STATE_FILE}: {err}");
+    }
+}
+
+struct StateWindowDelegate {
+    base: *mut RcImpl<cef_sys::_cef_window_delegate_t, Self>,
+    browser_view: BrowserView,
+    /// The placement of the window before it was last maximized or minimized.
+    state: Arc<Mutex<Option<WindowState>>>,
+}
+
+impl StateWindowDelegate {
+    fn new(browser_view: BrowserView) -> WindowDelegate {
+        WindowDelegate::new(Self {
+            base: std::ptr::null_mut(),
+            browser_view,
+            state: Default::default(),
+        })
+    }
+}
+
+impl WrapWindowDelegate for StateWindowDelegate {
+    fn wrap_rc(&mut self, object: *mut RcImpl<cef_sys::_cef_window_delegate_t, Self>) {
+        self.base = object;
+    }
+}
+
+impl Clone for StateWindowDelegate {
+    fn clone(&self) -> Self {
+        unsafe {
+            let rc_impl = &mut *self.base;
+            rc_impl.interface.add_ref();
+        }
+
+        Self {
+            base: self.base,
+            browser_view: self.browser_view.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl Rc for StateWindowDelegate {
+    fn as_base(&self) -> &cef_sys::cef_base_ref_counted_t {
+        unsafe {
+            let base = &*self.base;
+            std::mem::transmute(&base.cef_object)
+        }
+    }
+}
+
+impl ImplViewDelegate for StateWindowDelegate {
+    fn get_raw(&self) -> *mut cef_sys::_cef_view_delegate_t {
+        self.base as *mut cef_sys::_cef_view_delegate_t
+    }
+}
+
+impl ImplPanelDelegate for StateWindowDelegate {}
+
+impl ImplWindowDelegate for StateWindowDelegate {
+    fn on_window_created(&self, window: Option<&impl ImplWindow>) {
+        let Some(window) = window else {
+            return;
+        };
+        window.add_child_view(Some(&self.browser_view));
+        match load() {
+            Some(state) => state.apply(window),
+            None => window.center_window(Some(&Size {
+                width: 1024,
+                height: 768,
+            })),
+        }
+        window.show();
+    }
+
+    fn on_window_bounds_changed(
+        &self,
+        window: Option<&impl ImplWindow>,
+        _new_bounds: Option<&Rect>,
+    ) {
+        let Some(window) = window else {
+            return;
+        };
+        // Keep the bounds to restore to when the window is no longer maximized.
+        if window.is_maximized() == 0 && window.is_minimized() == 0 {
+            *self.state.lock().unwrap() = Some(WindowState::capture(window));
+        }
+    }
+
+    fn can_close(&self, window: Option<&impl ImplWindow>) -> ::std::os::raw::c_int {
+        if let Some(window) = window {
+            let state = self
+                .state
+                .lock()
+                .unwrap()
+                .unwrap_or_else(|| WindowState::capture(window));
+            save(&WindowState {
+                maximized: window.is_maximized() != 0,
+                ..state
+            });
+        }
+        1
+    }
+
+    fn on_window_destroyed(&self, _window: Option<&impl ImplWindow>) {
+        quit_message_loop();
+    }
+}
+
+fn main() {
+    let args = Args::new(std::env::args());
+    let app = AppBuilder::new().build();
+    if execute_process(Some(args.as_main_args()), Some(&app), std::ptr::null_mut()) >= 0 {
+        return;
+    }
+    initialize(
+        Some(args.as_main_args()),
+        Some(&Default::default()),
+        Some(&app),
+        std::ptr::null_mut(),
+    );
+    {
+        let client = ClientBuilder::new().build();
+        let url = CefString::from(&CefStringUtf8::from("https://example.com"));
+        let browser_view = browser_view_create(
+            Some(&client),
+            Some(&url),
+            Some(&Default::default()),
+            Option::<&DictionaryValue>::None,
+            Option::<&RequestContext>::None,
+            Option::<&BrowserViewDelegate>::None,
+        )
+        .expect("Failed to create browser view");
+        let delegate = StateWindowDelegate::new(browser_view);
+        let _window = window_create_top_level(Some(&delegate)).expect("Failed to create window");
+
+        run_message_loop();
+    }
+
+    shutdown();
+}
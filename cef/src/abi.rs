@@ -0,0 +1,63 @@
+//! ABI module
+//!
+//! The bindings only work with a `libcef` whose structs have the layout of the headers they were
+//! generated from. [assert_cef_abi_compatible] checks the struct sizes of [crate::sys] at compile
+//! time, and [check_runtime_abi] checks the loaded `libcef` at runtime.
+
+use std::{ffi::CStr, os::raw::c_int};
+
+use crate::{api_hash, bindings::CEF_API_HASH_UNIVERSAL, context};
+
+/// The `entry` of `cef_api_hash` for the hash of the platform independent API.
+const API_HASH_UNIVERSAL: c_int = 1;
+
+/// Fail to compile if the size of a CEF struct in [crate::sys] differs from the headers the
+/// bindings were generated from, e.g. after regenerating only one of them. The bindings check
+/// this for themselves, so use it in crates which use [crate::sys] directly.
+///
+/// ```ignore
+/// cef::abi::assert_cef_abi_compatible!();
+/// ```
+#[macro_export]
+macro_rules! assert_cef_abi_compatible {
+    () => {
+        $crate::__assert_cef_struct_sizes!();
+    };
+}
+
+pub use crate::assert_cef_abi_compatible;
+
+assert_cef_abi_compatible!();
+
+/// `true` if the loaded `libcef` has the API hash of the headers the bindings were generated
+/// from. Bindings generated without `cef_api_hash.h` compare the CEF version instead.
+pub fn check_runtime_abi() -> bool {
+    match CEF_API_HASH_UNIVERSAL {
+        Some(expected) => runtime_api_hash().is_some_and(|found| found == expected),
+        None => context::runtime_version() == context::binding_version(),
+    }
+}
+
+/// The hash of the platform independent API of the loaded `libcef`.
+fn runtime_api_hash() -> Option<String> {
+    let hash = api_hash(API_HASH_UNIVERSAL);
+    if hash.is_null() {
+        return None;
+    }
+    unsafe { CStr::from_ptr(hash) }
+        .to_str()
+        .ok()
+        .map(String::from)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_check_runtime_abi() {
+        let expected = CEF_API_HASH_UNIVERSAL.expect("bindings generated without cef_api_hash.h");
+        assert_eq!(runtime_api_hash().as_deref(), Some(expected));
+        assert!(check_runtime_abi());
+    }
+}
@@ -0,0 +1,90 @@
+//! App module
+
+use cef_sys::_cef_app_t;
+
+use crate::{
+    rc::{wrap_rc, RcImpl},
+    switches::SwitchSet,
+    *,
+};
+
+/// Builder of an [App] which applies a [SwitchSet] and returns the handlers it was given.
+///
+/// Pass the built app to [crate::context::Context::initialize] and [execute_process].
+#[derive(Clone, Default)]
+pub struct AppBuilder {
+    switches: Option<SwitchSet>,
+    browser_process_handler: Option<BrowserProcessHandler>,
+    render_process_handler: Option<RenderProcessHandler>,
+}
+
+impl AppBuilder {
+    /// Create a builder which leaves the command line untouched and returns no handlers.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Apply `switches` to the command line of the browser process, or of every process with
+    /// [SwitchSet::all_processes].
+    pub fn switches(mut self, switches: SwitchSet) -> Self {
+        self.switches = Some(switches);
+        self
+    }
+
+    /// Return `handler` from [ImplApp::get_browser_process_handler].
+    pub fn browser_process_handler(mut self, handler: BrowserProcessHandler) -> Self {
+        self.browser_process_handler = Some(handler);
+        self
+    }
+
+    /// Return `handler` from [ImplApp::get_render_process_handler].
+    pub fn render_process_handler(mut self, handler: RenderProcessHandler) -> Self {
+        self.render_process_handler = Some(handler);
+        self
+    }
+
+    /// Build the [App].
+    pub fn build(self) -> App {
+        App::new(BuiltApp {
+            object: std::ptr::null_mut(),
+            builder: self,
+        })
+    }
+}
+
+struct BuiltApp {
+    object: *mut RcImpl<_cef_app_t, Self>,
+    builder: AppBuilder,
+}
+
+wrap_rc!(BuiltApp, WrapApp, _cef_app_t, builder);
+
+impl ImplApp for BuiltApp {
+    fn on_before_command_line_processing(
+        &self,
+        process_type: Option<&CefStringUtf16>,
+        command_line: Option<&impl ImplCommandLine>,
+    ) {
+        let (Some(switches), Some(command_line)) = (&self.builder.switches, command_line) else {
+            return;
+        };
+        let process_type = process_type
+            .map(|process_type| process_type.to_string())
+            .unwrap_or_default();
+        if switches.applies_to(&process_type) {
+            switches.apply(command_line);
+        }
+    }
+
+    fn get_browser_process_handler(&self) -> Option<BrowserProcessHandler> {
+        self.builder.browser_process_handler.clone()
+    }
+
+    fn get_render_process_handler(&self) -> Option<RenderProcessHandler> {
+        self.builder.render_process_handler.clone()
+    }
+
+    fn get_raw(&self) -> *mut _cef_app_t {
+        self.object as *mut _cef_app_t
+    }
+}
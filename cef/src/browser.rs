@@ -0,0 +1,685 @@
+//! Browser module
+//!
+//! [BrowserOptions] collects the creation-time settings which are split between [WindowInfo],
+//! [BrowserSettings] and the `extra_info` for the render process, and checks that they fit together
+//! before the browser is created.
+//! [BrowserMap] and [SyncBrowserMap] keep application state per browser.
+
+use std::{
+    cell::{Ref, RefCell},
+    collections::HashMap,
+    fmt,
+    ops::Deref,
+    rc::Rc,
+    sync::{Arc, Mutex},
+};
+
+use cef_sys::{_cef_client_t, _cef_life_span_handler_t};
+
+use crate::{
+    browser_host_create_browser_sync,
+    compat::RuntimeStyle,
+    context::{self, is_running},
+    rc::{wrap_rc, RcImpl},
+    render::RendererConfig,
+    thread::is_ui_thread,
+    Browser, BrowserSettings, CefString, CefStringUtf8, Client, DictionaryValue, ImplBrowser,
+    ImplBrowserHost, ImplClient, ImplLifeSpanHandler, LifeSpanHandler, RequestContext, WindowInfo,
+};
+
+/// The background painted before and behind the page.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Background {
+    /// Use [crate::Settings::background_color], which is opaque white unless it was set.
+    #[default]
+    Default,
+    /// An opaque `0xRRGGBB` color. The alpha component is ignored.
+    Color(u32),
+    /// Let the content behind the browser show through. Needs windowless rendering or a Views
+    /// window with an alpha channel.
+    Transparent,
+}
+
+/// Creation-time options of a browser, applied to [WindowInfo] and [BrowserSettings] with
+/// [BrowserOptions::apply].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BrowserOptions {
+    pub background: Background,
+    /// Render off-screen through a [crate::RenderHandler] instead of a native window.
+    pub windowless: bool,
+    /// Maximum rate of [crate::ImplRenderHandler::on_paint] calls for windowless browsers, from
+    /// 1 to 60. CEF uses 30 if `None`.
+    pub windowless_frame_rate: Option<u32>,
+    /// The browser is hosted in a [crate::BrowserView] whose window has an alpha channel.
+    pub views_with_alpha: bool,
+    /// [RuntimeStyle::Default] lets CEF resolve it to Alloy style for windowless browsers and
+    /// to Chrome style otherwise. Ignored before CEF 125, see [crate::compat].
+    pub runtime_style: RuntimeStyle,
+    /// Sent to the render process in the `extra_info` of the browser, see
+    /// [crate::render::RenderApp::config_for]. Only used by [create_browser_sync].
+    pub renderer_config: Option<RendererConfig>,
+}
+
+/// A conflicting combination of [BrowserOptions].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BrowserOptionsError {
+    /// [Background::Transparent] without windowless rendering or a Views window with alpha.
+    TransparentNeedsAlpha,
+    /// A windowless frame rate for a browser which has a native window.
+    FrameRateWithoutWindowless,
+    /// A windowless frame rate outside of 1 to 60.
+    FrameRateOutOfRange(u32),
+    /// Windowless browsers only support Alloy style.
+    ChromeStyleWindowless,
+}
+
+impl fmt::Display for BrowserOptionsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TransparentNeedsAlpha => write!(
+                f,
+                "a transparent background needs windowless rendering or a Views window with alpha"
+            ),
+            Self::FrameRateWithoutWindowless => {
+                write!(f, "the windowless frame rate needs windowless rendering")
+            }
+            Self::FrameRateOutOfRange(rate) => {
+                write!(f, "windowless frame rate {rate} is not between 1 and 60")
+            }
+            Self::ChromeStyleWindowless => {
+                write!(f, "windowless browsers don't support Chrome style")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BrowserOptionsError {}
+
+impl BrowserOptions {
+    /// Check that the options fit together.
+    pub fn validate(&self) -> Result<(), BrowserOptionsError> {
+        if self.background == Background::Transparent && !self.windowless && !self.views_with_alpha
+        {
+            return Err(BrowserOptionsError::TransparentNeedsAlpha);
+        }
+        if let Some(rate) = self.windowless_frame_rate {
+            if !self.windowless {
+                return Err(BrowserOptionsError::FrameRateWithoutWindowless);
+            }
+            if !(1..=60).contains(&rate) {
+                return Err(BrowserOptionsError::FrameRateOutOfRange(rate));
+            }
+        }
+        if self.windowless && self.runtime_style == RuntimeStyle::Chrome {
+            return Err(BrowserOptionsError::ChromeStyleWindowless);
+        }
+        Ok(())
+    }
+
+    /// Validate the options and write them to `window_info` and `settings`, leaving their other
+    /// fields untouched.
+    pub fn apply(
+        &self,
+        window_info: &mut WindowInfo,
+        settings: &mut BrowserSettings,
+    ) -> Result<(), BrowserOptionsError> {
+        self.validate()?;
+
+        window_info.windowless_rendering_enabled = self.windowless as _;
+        self.runtime_style.apply(window_info);
+
+        settings.background_color = match self.background {
+            Background::Default => 0,
+            Background::Color(color) => 0xFF00_0000 | color,
+            Background::Transparent => 0,
+        };
+        if let Some(rate) = self.windowless_frame_rate {
+            settings.windowless_frame_rate = rate as _;
+        }
+        Ok(())
+    }
+}
+
+/// Why [create_browser_sync] failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CreateBrowserError {
+    /// The [BrowserOptions] conflict.
+    Options(BrowserOptionsError),
+    /// CEF is not running, see [crate::context::is_running].
+    NotInitialized,
+    /// Not called on the UI thread.
+    WrongThread,
+    /// CEF returned no browser, e.g. because of invalid [WindowInfo] or during shutdown.
+    Failed,
+}
+
+impl fmt::Display for CreateBrowserError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Options(error) => error.fmt(f),
+            Self::NotInitialized => write!(f, "CEF is not initialized"),
+            Self::WrongThread => write!(f, "browsers must be created on the UI thread"),
+            Self::Failed => write!(f, "CEF failed to create the browser"),
+        }
+    }
+}
+
+impl std::error::Error for CreateBrowserError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Options(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<BrowserOptionsError> for CreateBrowserError {
+    fn from(value: BrowserOptionsError) -> Self {
+        Self::Options(value)
+    }
+}
+
+/// Create a browser with `options` applied to `window_info` and the default [BrowserSettings].
+///
+/// Must be called on the UI thread.
+pub fn create_browser_sync(
+    options: &BrowserOptions,
+    mut window_info: WindowInfo,
+    client: &impl ImplClient,
+    url: &str,
+    request_context: Option<&RequestContext>,
+) -> Result<Browser, CreateBrowserError> {
+    let mut settings = BrowserSettings::default();
+    options.apply(&mut window_info, &mut settings)?;
+    let extra_info = options
+        .renderer_config
+        .as_ref()
+        .and_then(RendererConfig::to_extra_info);
+    create_browser_with(
+        &window_info,
+        &settings,
+        client,
+        url,
+        extra_info.as_ref(),
+        request_context,
+    )
+}
+
+/// [create_browser_sync] with `settings` as they are, for browsers created without
+/// [BrowserOptions].
+pub(crate) fn create_browser_with(
+    window_info: &WindowInfo,
+    settings: &BrowserSettings,
+    client: &impl ImplClient,
+    url: &str,
+    extra_info: Option<&DictionaryValue>,
+    request_context: Option<&RequestContext>,
+) -> Result<Browser, CreateBrowserError> {
+    if !is_ui_thread() {
+        return Err(if is_running() {
+            CreateBrowserError::WrongThread
+        } else {
+            CreateBrowserError::NotInitialized
+        });
+    }
+    let url = CefString::from(&CefStringUtf8::from(url));
+    browser_host_create_browser_sync(
+        Some(window_info),
+        Some(client),
+        Some(&url),
+        Some(settings),
+        extra_info,
+        request_context,
+    )
+    .filter(Browser::is_valid)
+    .inspect(context::register_browser)
+    .ok_or(CreateBrowserError::Failed)
+}
+
+/// The identifier of a [Browser], see [Browser::identifier].
+pub type BrowserId = i32;
+
+impl Browser {
+    /// `false` once the browser is closed, after which its methods do nothing.
+    pub fn is_valid(&self) -> bool {
+        ImplBrowser::is_valid(self) != 0
+    }
+
+    /// The identifier of the browser, unique within the browser process.
+    pub fn identifier(&self) -> BrowserId {
+        self.get_identifier()
+    }
+
+    /// The client the browser was created with, if it is a `C`, e.g. to reach the application
+    /// state it holds. Clients built by [crate::client::ClientBuilder] or implemented in C++
+    /// return `None`.
+    ///
+    /// The browser holds the client until it is closed, so this returns `None` once the browser
+    /// is no longer valid. The returned [TypedClient] holds its own reference to the client.
+    pub fn typed_client<C: ImplClient + 'static>(&self) -> Option<TypedClient<C>> {
+        if !self.is_valid() {
+            return None;
+        }
+        let client = self.get_host()?.get_client()?;
+        // SAFETY: `client` holds a reference to the object, and `TypedClient` keeps it.
+        let interface = unsafe { RcImpl::<_cef_client_t, C>::downcast_ref(client.get_raw())? };
+        let interface = interface as *const C;
+        Some(TypedClient { client, interface })
+    }
+}
+
+/// The Rust client of a [Browser], see [Browser::typed_client]. Dereferences to `C`.
+pub struct TypedClient<C> {
+    client: Client,
+    interface: *const C,
+}
+
+impl<C> TypedClient<C> {
+    /// The [Client] which holds `C`.
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+}
+
+impl<C> Deref for TypedClient<C> {
+    type Target = C;
+
+    fn deref(&self) -> &C {
+        // SAFETY: `interface` is part of the object which `client` holds a reference to.
+        unsafe { &*self.interface }
+    }
+}
+
+/// Application state per browser, for the UI thread.
+///
+/// Clones share the entries. Return [BrowserMap::life_span_handler] from
+/// [ImplClient::get_life_span_handler] to remove the entry of a browser when it closes, or call
+/// [BrowserMap::remove] from your own [ImplLifeSpanHandler::on_before_close].
+pub struct BrowserMap<T> {
+    entries: Rc<RefCell<HashMap<i32, T>>>,
+}
+
+impl<T> Clone for BrowserMap<T> {
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
+        }
+    }
+}
+
+impl<T> Default for BrowserMap<T> {
+    fn default() -> Self {
+        Self {
+            entries: Default::default(),
+        }
+    }
+}
+
+impl<T: 'static> BrowserMap<T> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Set the state of `browser`, returning the previous one.
+    pub fn insert(&self, browser: &Browser, value: T) -> Option<T> {
+        self.entries
+            .borrow_mut()
+            .insert(browser.identifier(), value)
+    }
+
+    /// The state of `browser`. Panics like [RefCell::borrow] if called from [BrowserMap::with].
+    pub fn get(&self, browser: &Browser) -> Option<Ref<'_, T>> {
+        Ref::filter_map(self.entries.borrow(), |entries| {
+            entries.get(&browser.identifier())
+        })
+        .ok()
+    }
+
+    /// Run `callback` with the state of `browser`, `None` if it has none.
+    pub fn with<R>(&self, browser: &Browser, callback: impl FnOnce(&mut T) -> R) -> Option<R> {
+        let mut entries = self.entries.borrow_mut();
+        entries.get_mut(&browser.identifier()).map(callback)
+    }
+
+    pub fn remove(&self, browser: &Browser) -> Option<T> {
+        self.entries.borrow_mut().remove(&browser.identifier())
+    }
+
+    pub fn contains(&self, browser: &Browser) -> bool {
+        self.entries.borrow().contains_key(&browser.identifier())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.borrow().is_empty()
+    }
+
+    /// A [LifeSpanHandler] which removes the state of each browser in
+    /// [ImplLifeSpanHandler::on_before_close].
+    pub fn life_span_handler(&self) -> LifeSpanHandler {
+        let entries = self.entries.clone();
+        BrowserMapCleanup::handler(Rc::new(move |id| {
+            entries.borrow_mut().remove(&id);
+        }))
+    }
+}
+
+/// Application state per browser, for the multi-threaded message loop.
+///
+/// Like [BrowserMap], but the entries are behind a [Mutex], so clones can be sent to other
+/// threads.
+pub struct SyncBrowserMap<T> {
+    entries: Arc<Mutex<HashMap<i32, T>>>,
+}
+
+impl<T> Clone for SyncBrowserMap<T> {
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
+        }
+    }
+}
+
+impl<T> Default for SyncBrowserMap<T> {
+    fn default() -> Self {
+        Self {
+            entries: Default::default(),
+        }
+    }
+}
+
+impl<T: Send + 'static> SyncBrowserMap<T> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Set the state of `browser`, returning the previous one.
+    pub fn insert(&self, browser: &Browser, value: T) -> Option<T> {
+        self.lock().insert(browser.identifier(), value)
+    }
+
+    /// A copy of the state of `browser`.
+    pub fn get(&self, browser: &Browser) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.lock().get(&browser.identifier()).cloned()
+    }
+
+    /// Run `callback` with the state of `browser`, `None` if it has none. The map is locked
+    /// until `callback` returns.
+    pub fn with<R>(&self, browser: &Browser, callback: impl FnOnce(&mut T) -> R) -> Option<R> {
+        self.lock().get_mut(&browser.identifier()).map(callback)
+    }
+
+    pub fn remove(&self, browser: &Browser) -> Option<T> {
+        self.lock().remove(&browser.identifier())
+    }
+
+    pub fn contains(&self, browser: &Browser) -> bool {
+        self.lock().contains_key(&browser.identifier())
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// A [LifeSpanHandler] which removes the state of each browser in
+    /// [ImplLifeSpanHandler::on_before_close].
+    pub fn life_span_handler(&self) -> LifeSpanHandler {
+        let map = self.clone();
+        BrowserMapCleanup::handler(Rc::new(move |id| {
+            map.lock().remove(&id);
+        }))
+    }
+
+    /// A panic while locked leaves the entries usable, so keep using them.
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<i32, T>> {
+        self.entries.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+type RemoveEntry = Rc<dyn Fn(i32)>;
+
+struct BrowserMapCleanup {
+    object: *mut RcImpl<_cef_life_span_handler_t, Self>,
+    remove: RemoveEntry,
+}
+
+wrap_rc!(
+    BrowserMapCleanup,
+    WrapLifeSpanHandler,
+    _cef_life_span_handler_t,
+    remove
+);
+
+impl BrowserMapCleanup {
+    fn handler(remove: RemoveEntry) -> LifeSpanHandler {
+        LifeSpanHandler::new(Self {
+            object: std::ptr::null_mut(),
+            remove,
+        })
+    }
+}
+
+impl ImplLifeSpanHandler for BrowserMapCleanup {
+    fn on_before_close(&self, browser: Option<&impl ImplBrowser>) {
+        if let Some(browser) = browser {
+            (self.remove)(browser.get_identifier());
+        }
+    }
+
+    fn get_raw(&self) -> *mut _cef_life_span_handler_t {
+        self.object as *mut _
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use cef_sys::{_cef_browser_settings_t, _cef_window_info_t};
+
+    use super::*;
+    use crate::{
+        client::ClientBuilder,
+        fixtures::{FakeBrowser, FakeHost},
+        rc::Rc,
+        Client,
+    };
+
+    fn apply(options: &BrowserOptions) -> (_cef_window_info_t, _cef_browser_settings_t) {
+        let mut window_info = WindowInfo::default();
+        let mut settings = BrowserSettings::default();
+        options.apply(&mut window_info, &mut settings).unwrap();
+        (window_info.into(), settings.into())
+    }
+
+    #[test]
+    fn test_windowless_transparent() {
+        let (window_info, settings) = apply(&BrowserOptions {
+            background: Background::Transparent,
+            windowless: true,
+            windowless_frame_rate: Some(60),
+            ..Default::default()
+        });
+        assert_eq!(window_info.windowless_rendering_enabled, 1);
+        #[cfg(cef_api_at_least = "125")]
+        assert_eq!(
+            window_info.runtime_style,
+            cef_sys::cef_runtime_style_t::CEF_RUNTIME_STYLE_DEFAULT
+        );
+        assert_eq!(settings.background_color, 0);
+        assert_eq!(settings.windowless_frame_rate, 60);
+        assert_eq!(
+            settings.size,
+            std::mem::size_of::<_cef_browser_settings_t>()
+        );
+    }
+
+    #[test]
+    fn test_windowed_color() {
+        let (window_info, settings) = apply(&BrowserOptions {
+            background: Background::Color(0x336699),
+            runtime_style: RuntimeStyle::Alloy,
+            ..Default::default()
+        });
+        assert_eq!(window_info.windowless_rendering_enabled, 0);
+        #[cfg(cef_api_at_least = "125")]
+        assert_eq!(
+            window_info.runtime_style,
+            cef_sys::cef_runtime_style_t::CEF_RUNTIME_STYLE_ALLOY
+        );
+        assert_eq!(settings.background_color, 0xFF336699);
+        assert_eq!(settings.windowless_frame_rate, 0);
+    }
+
+    #[test]
+    fn test_conflicting_options() {
+        let windowed_transparent = BrowserOptions {
+            background: Background::Transparent,
+            ..Default::default()
+        };
+        assert_eq!(
+            windowed_transparent.validate(),
+            Err(BrowserOptionsError::TransparentNeedsAlpha)
+        );
+        assert_eq!(
+            BrowserOptions {
+                views_with_alpha: true,
+                ..windowed_transparent
+            }
+            .validate(),
+            Ok(())
+        );
+        assert_eq!(
+            BrowserOptions {
+                windowless_frame_rate: Some(30),
+                ..Default::default()
+            }
+            .validate(),
+            Err(BrowserOptionsError::FrameRateWithoutWindowless)
+        );
+        assert_eq!(
+            BrowserOptions {
+                windowless: true,
+                windowless_frame_rate: Some(120),
+                ..Default::default()
+            }
+            .validate(),
+            Err(BrowserOptionsError::FrameRateOutOfRange(120))
+        );
+        assert_eq!(
+            BrowserOptions {
+                windowless: true,
+                runtime_style: RuntimeStyle::Chrome,
+                ..Default::default()
+            }
+            .validate(),
+            Err(BrowserOptionsError::ChromeStyleWindowless)
+        );
+    }
+
+    #[test]
+    fn test_browser_map() {
+        let map = BrowserMap::new();
+        let (first, second) = (FakeBrowser::new(1).build(), FakeBrowser::new(2).build());
+        assert_eq!(map.insert(&first, vec!["a"]), None);
+        assert_eq!(map.with(&first, |tabs| tabs.push("b")), Some(()));
+        assert_eq!(map.with(&second, |tabs| tabs.push("c")), None);
+        assert_eq!(*map.get(&first).unwrap(), ["a", "b"]);
+        assert!(map.get(&second).is_none());
+
+        let handler = map.life_span_handler();
+        handler.on_before_close(Some(&second));
+        assert_eq!(map.len(), 1);
+        handler.on_before_close(Some(&first));
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn test_sync_browser_map() {
+        let map = SyncBrowserMap::new();
+        let first = FakeBrowser::new(1).build();
+        map.insert(&first, 1);
+        let other = map.clone();
+        std::thread::spawn(move || {
+            other
+                .entries
+                .lock()
+                .unwrap()
+                .entry(1)
+                .and_modify(|value| *value += 1);
+        })
+        .join()
+        .unwrap();
+        assert_eq!(map.get(&first), Some(2));
+        map.life_span_handler().on_before_close(Some(&first));
+        assert!(!map.contains(&first));
+    }
+
+    #[test]
+    fn test_create_browser_error() {
+        let error = CreateBrowserError::from(BrowserOptionsError::FrameRateOutOfRange(0));
+        assert_eq!(
+            error.to_string(),
+            "windowless frame rate 0 is not between 1 and 60"
+        );
+        assert!(std::error::Error::source(&error).is_some());
+        assert!(std::error::Error::source(&CreateBrowserError::Failed).is_none());
+    }
+
+    struct MyClient {
+        object: *mut RcImpl<cef_sys::_cef_client_t, Self>,
+        name: String,
+    }
+
+    impl ImplClient for MyClient {
+        fn get_raw(&self) -> *mut cef_sys::_cef_client_t {
+            self.object as *mut _
+        }
+    }
+
+    wrap_rc!(MyClient, WrapClient, cef_sys::_cef_client_t, name);
+
+    struct OtherClient {
+        object: *mut RcImpl<cef_sys::_cef_client_t, Self>,
+    }
+
+    impl ImplClient for OtherClient {
+        fn get_raw(&self) -> *mut cef_sys::_cef_client_t {
+            self.object as *mut _
+        }
+    }
+
+    wrap_rc!(OtherClient, WrapClient, cef_sys::_cef_client_t);
+
+    fn browser_with(client: Client) -> Browser {
+        FakeBrowser::new(1)
+            .valid(|| true)
+            .host(FakeHost::new().client(client).build())
+            .build()
+    }
+
+    #[test]
+    fn test_typed_client() {
+        let browser = browser_with(Client::new(MyClient {
+            object: std::ptr::null_mut(),
+            name: "kiosk".to_string(),
+        }));
+        let client = browser.typed_client::<MyClient>().unwrap();
+        assert_eq!(client.name, "kiosk");
+        assert!(client.has_at_least_one_ref());
+        assert!(browser.typed_client::<OtherClient>().is_none());
+        drop(browser);
+        assert_eq!(client.name, "kiosk");
+
+        let browser = browser_with(ClientBuilder::new().build());
+        assert!(browser.typed_client::<MyClient>().is_none());
+    }
+}
@@ -0,0 +1,170 @@
+//! Cache module
+//!
+//! In-memory operation for tests and private browsing, and clearing the cache at runtime.
+//!
+//! [SettingsBuilder::incognito] and [RequestContextSettings::incognito] leave the cache path
+//! empty, which keeps cookies, storage and the HTTP cache in memory, and turn off persisting
+//! session cookies, which CEF ignores without a cache path anyway. See
+//! [crate::request_context::SessionRequestContext] for in-memory request contexts next to the
+//! global one.
+//!
+//! ## What still touches the disk
+//!
+//! The root cache path is Chromium's user data directory, which exists even without a cache
+//! path. If it is empty, CEF uses a platform specific default, e.g. `~/.config/cef_user_data` on
+//! Linux. Chromium keeps data there which isn't part of any request context:
+//!
+//! - The GPU shader caches, `GPUCache`, `GrShaderCache` and `ShaderCache`. Turn them off with
+//!   [crate::switches::SwitchSet::disable_gpu_shader_disk_cache], or relocate them with
+//!   [SettingsBuilder::root_cache_path], e.g. to a temporary directory deleted after shutdown.
+//! - Components such as Widevine, unless
+//!   [crate::switches::SwitchSet::disable_component_update] is set.
+//! - The log file, see [Settings::log_file], and crash dumps if crash reporting is configured.
+
+use std::{future::Future, path::Path};
+
+use cef_sys::cef_thread_id_t;
+
+use crate::{
+    context, thread, Browser, CefString, CefStringUtf8, ImplBrowser, ImplBrowserHost,
+    ImplRequestContext, RequestContext, RequestContextSettings, Settings,
+};
+
+impl Settings {
+    /// Build [Settings] whose cache options fit together.
+    pub fn builder() -> SettingsBuilder {
+        SettingsBuilder::default()
+    }
+}
+
+/// Builds [Settings], see [Settings::builder].
+#[derive(Clone, Default)]
+pub struct SettingsBuilder {
+    settings: Settings,
+    incognito: bool,
+}
+
+impl From<Settings> for SettingsBuilder {
+    /// Start from `settings`, e.g. those of [crate::presets::kiosk].
+    fn from(settings: Settings) -> Self {
+        Self {
+            settings,
+            incognito: false,
+        }
+    }
+}
+
+impl SettingsBuilder {
+    /// Keep cookies, storage and the HTTP cache in memory. Overrides
+    /// [SettingsBuilder::cache_path] and [SettingsBuilder::persist_session_cookies], whether
+    /// they are called before or after it. See the [module documentation](self) for the data
+    /// which still reaches the disk.
+    pub fn incognito(mut self) -> Self {
+        self.incognito = true;
+        self
+    }
+
+    /// Keep the data of the global request context in `path`, which must be the root cache path
+    /// or a directory under it.
+    pub fn cache_path(mut self, path: &Path) -> Self {
+        self.settings.cache_path = cef_path(path);
+        self
+    }
+
+    /// The user data directory of Chromium, which also holds the GPU shader caches.
+    pub fn root_cache_path(mut self, path: &Path) -> Self {
+        self.settings.root_cache_path = cef_path(path);
+        self
+    }
+
+    /// Keep session cookies, which expire when the browser closes, in the cookie store.
+    pub fn persist_session_cookies(mut self, persist: bool) -> Self {
+        self.settings.persist_session_cookies = persist.into();
+        self
+    }
+
+    pub fn build(self) -> Settings {
+        let mut settings = self.settings;
+        if self.incognito {
+            settings.cache_path = Default::default();
+            settings.persist_session_cookies = 0;
+        }
+        settings
+    }
+}
+
+impl RequestContextSettings {
+    /// Settings of a request context which keeps its cookies, storage and HTTP cache in memory,
+    /// apart from every other request context.
+    pub fn incognito() -> Self {
+        Self {
+            cache_path: Default::default(),
+            persist_session_cookies: 0,
+            ..Default::default()
+        }
+    }
+}
+
+impl RequestContext {
+    /// Clear the HTTP cache of this context, and close its connections so no response is reused
+    /// from them.
+    ///
+    /// Runs the DevTools method `Network.clearBrowserCache` in a browser of this context which
+    /// was created through this crate, e.g. by [crate::browser::create_browser_sync]. Without
+    /// such a browser, only the connections are closed. Must be called on the UI thread, and
+    /// panics otherwise.
+    pub fn clear_cache(&self) -> impl Future<Output = ()> {
+        thread::assert_on_thread(cef_thread_id_t::TID_UI);
+        let cleared = context::browsers()
+            .iter()
+            .filter_map(Browser::get_host)
+            .find(|host| {
+                host.get_request_context()
+                    .is_some_and(|context| context.is_same(Some(self)) != 0)
+            })
+            .map(|host| host.execute_dev_tools("Network.clearBrowserCache", None));
+        let connections = self.close_all_connections();
+
+        async move {
+            if let Some(cleared) = cleared {
+                // The connections are closed either way.
+                let _ = cleared.await;
+            }
+            connections.await;
+        }
+    }
+}
+
+fn cef_path(path: &Path) -> CefString {
+    CefString::from(&CefStringUtf8::from(path.to_string_lossy().as_ref()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_incognito_overrides_cache_options() {
+        let root = Path::new("/tmp/cef-root");
+        let settings = Settings::builder()
+            .cache_path(&root.join("profile"))
+            .incognito()
+            .root_cache_path(root)
+            .persist_session_cookies(true)
+            .build();
+        assert!(settings.cache_path.to_string().is_empty());
+        assert_eq!(settings.root_cache_path.to_string(), "/tmp/cef-root");
+        assert_eq!(settings.persist_session_cookies, 0);
+
+        let settings = Settings::builder()
+            .cache_path(&root.join("profile"))
+            .persist_session_cookies(true)
+            .build();
+        assert_eq!(settings.cache_path.to_string(), "/tmp/cef-root/profile");
+        assert_eq!(settings.persist_session_cookies, 1);
+
+        let settings = RequestContextSettings::incognito();
+        assert!(settings.cache_path.to_string().is_empty());
+        assert_eq!(settings.persist_session_cookies, 0);
+    }
+}
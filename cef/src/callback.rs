@@ -0,0 +1,279 @@
+//! Callback module
+//!
+//! Closure-backed implementations of the one-shot callback interfaces which CEF uses to report
+//! the completion of asynchronous operations.
+
+use std::{
+    os::raw::c_int,
+    sync::{Arc, Mutex},
+};
+
+use cef_sys::{
+    _cef_completion_callback_t, _cef_delete_cookies_callback_t, _cef_media_route_create_callback_t,
+    _cef_media_sink_device_info_callback_t, _cef_pdf_print_callback_t, _cef_resolve_callback_t,
+    _cef_task_t,
+};
+
+use crate::{
+    rc::{wrap_rc, RcImpl},
+    *,
+};
+
+type Once<F> = Arc<Mutex<Option<Box<F>>>>;
+
+type MediaRouteCreateFn = dyn FnOnce(MediaRouteCreateResult, String, Option<MediaRoute>) + Send;
+
+type MediaSinkDeviceInfoFn = dyn FnOnce(Option<&MediaSinkDeviceInfo>) + Send;
+
+fn take<F: ?Sized>(callback: &Once<F>) -> Option<Box<F>> {
+    callback
+        .lock()
+        .ok()
+        .and_then(|mut callback| callback.take())
+}
+
+/// Create a [CompletionCallback] which runs `callback` once the operation completes.
+pub fn completion_callback(callback: impl FnOnce() + Send + 'static) -> CompletionCallback {
+    CompletionCallback::new(FnCompletionCallback {
+        object: std::ptr::null_mut(),
+        callback: Arc::new(Mutex::new(Some(Box::new(callback)))),
+    })
+}
+
+struct FnCompletionCallback {
+    object: *mut RcImpl<_cef_completion_callback_t, Self>,
+    callback: Once<dyn FnOnce() + Send>,
+}
+
+wrap_rc!(
+    FnCompletionCallback,
+    WrapCompletionCallback,
+    _cef_completion_callback_t,
+    callback
+);
+
+impl ImplCompletionCallback for FnCompletionCallback {
+    fn on_complete(&self) {
+        if let Some(callback) = take(&self.callback) {
+            callback();
+        }
+    }
+
+    fn get_raw(&self) -> *mut _cef_completion_callback_t {
+        self.object as *mut _cef_completion_callback_t
+    }
+}
+
+/// Create a [Task] which runs `callback` once, e.g. to pass to [post_task].
+pub fn task(callback: impl FnOnce() + Send + 'static) -> Task {
+    Task::new(FnTask {
+        object: std::ptr::null_mut(),
+        callback: Arc::new(Mutex::new(Some(Box::new(callback)))),
+    })
+}
+
+struct FnTask {
+    object: *mut RcImpl<_cef_task_t, Self>,
+    callback: Once<dyn FnOnce() + Send>,
+}
+
+wrap_rc!(FnTask, WrapTask, _cef_task_t, callback);
+
+impl ImplTask for FnTask {
+    fn execute(&self) {
+        if let Some(callback) = take(&self.callback) {
+            callback();
+        }
+    }
+
+    fn get_raw(&self) -> *mut _cef_task_t {
+        self.object as *mut _cef_task_t
+    }
+}
+
+/// Create a [DeleteCookiesCallback] which runs `callback` with the number of deleted cookies.
+pub fn delete_cookies_callback(
+    callback: impl FnOnce(usize) + Send + 'static,
+) -> DeleteCookiesCallback {
+    DeleteCookiesCallback::new(FnDeleteCookiesCallback {
+        object: std::ptr::null_mut(),
+        callback: Arc::new(Mutex::new(Some(Box::new(callback)))),
+    })
+}
+
+struct FnDeleteCookiesCallback {
+    object: *mut RcImpl<_cef_delete_cookies_callback_t, Self>,
+    callback: Once<dyn FnOnce(usize) + Send>,
+}
+
+wrap_rc!(
+    FnDeleteCookiesCallback,
+    WrapDeleteCookiesCallback,
+    _cef_delete_cookies_callback_t,
+    callback
+);
+
+impl ImplDeleteCookiesCallback for FnDeleteCookiesCallback {
+    fn on_complete(&self, num_deleted: c_int) {
+        if let Some(callback) = take(&self.callback) {
+            callback(num_deleted.max(0) as usize);
+        }
+    }
+
+    fn get_raw(&self) -> *mut _cef_delete_cookies_callback_t {
+        self.object as *mut _cef_delete_cookies_callback_t
+    }
+}
+
+/// Create a [ResolveCallback] which runs `callback` with the result code and resolved IP
+/// addresses.
+pub fn resolve_callback(
+    callback: impl FnOnce(Errorcode, Vec<String>) + Send + 'static,
+) -> ResolveCallback {
+    ResolveCallback::new(FnResolveCallback {
+        object: std::ptr::null_mut(),
+        callback: Arc::new(Mutex::new(Some(Box::new(callback)))),
+    })
+}
+
+struct FnResolveCallback {
+    object: *mut RcImpl<_cef_resolve_callback_t, Self>,
+    callback: Once<dyn FnOnce(Errorcode, Vec<String>) + Send>,
+}
+
+wrap_rc!(
+    FnResolveCallback,
+    WrapResolveCallback,
+    _cef_resolve_callback_t,
+    callback
+);
+
+impl ImplResolveCallback for FnResolveCallback {
+    fn on_resolve_completed(&self, result: Errorcode, resolved_ips: Option<&mut CefStringList>) {
+        if let Some(callback) = take(&self.callback) {
+            let resolved_ips = resolved_ips
+                .map(|resolved_ips| resolved_ips.to_vec())
+                .unwrap_or_default();
+            callback(result, resolved_ips);
+        }
+    }
+
+    fn get_raw(&self) -> *mut _cef_resolve_callback_t {
+        self.object as *mut _cef_resolve_callback_t
+    }
+}
+
+/// Create a [MediaRouteCreateCallback] which runs `callback` with the result code, the error
+/// message and the created route.
+pub fn media_route_create_callback(
+    callback: impl FnOnce(MediaRouteCreateResult, String, Option<MediaRoute>) + Send + 'static,
+) -> MediaRouteCreateCallback {
+    MediaRouteCreateCallback::new(FnMediaRouteCreateCallback {
+        object: std::ptr::null_mut(),
+        callback: Arc::new(Mutex::new(Some(Box::new(callback)))),
+    })
+}
+
+struct FnMediaRouteCreateCallback {
+    object: *mut RcImpl<_cef_media_route_create_callback_t, Self>,
+    callback: Once<MediaRouteCreateFn>,
+}
+
+wrap_rc!(
+    FnMediaRouteCreateCallback,
+    WrapMediaRouteCreateCallback,
+    _cef_media_route_create_callback_t,
+    callback
+);
+
+impl ImplMediaRouteCreateCallback for FnMediaRouteCreateCallback {
+    fn on_media_route_create_finished(
+        &self,
+        result: MediaRouteCreateResult,
+        error: Option<&CefStringUtf16>,
+        route: Option<&impl ImplMediaRoute>,
+    ) {
+        if let Some(callback) = take(&self.callback) {
+            let error = error.map(|error| error.to_string()).unwrap_or_default();
+            callback(
+                result,
+                error,
+                route.map(|route| MediaRoute::from_impl(route)),
+            );
+        }
+    }
+
+    fn get_raw(&self) -> *mut _cef_media_route_create_callback_t {
+        self.object as *mut _cef_media_route_create_callback_t
+    }
+}
+
+/// Create a [MediaSinkDeviceInfoCallback] which runs `callback` with the device info of a sink.
+pub fn media_sink_device_info_callback(
+    callback: impl FnOnce(Option<&MediaSinkDeviceInfo>) + Send + 'static,
+) -> MediaSinkDeviceInfoCallback {
+    MediaSinkDeviceInfoCallback::new(FnMediaSinkDeviceInfoCallback {
+        object: std::ptr::null_mut(),
+        callback: Arc::new(Mutex::new(Some(Box::new(callback)))),
+    })
+}
+
+struct FnMediaSinkDeviceInfoCallback {
+    object: *mut RcImpl<_cef_media_sink_device_info_callback_t, Self>,
+    callback: Once<MediaSinkDeviceInfoFn>,
+}
+
+wrap_rc!(
+    FnMediaSinkDeviceInfoCallback,
+    WrapMediaSinkDeviceInfoCallback,
+    _cef_media_sink_device_info_callback_t,
+    callback
+);
+
+impl ImplMediaSinkDeviceInfoCallback for FnMediaSinkDeviceInfoCallback {
+    fn on_media_sink_device_info(&self, device_info: Option<&MediaSinkDeviceInfo>) {
+        if let Some(callback) = take(&self.callback) {
+            callback(device_info);
+        }
+    }
+
+    fn get_raw(&self) -> *mut _cef_media_sink_device_info_callback_t {
+        self.object as *mut _cef_media_sink_device_info_callback_t
+    }
+}
+
+/// Create a [PdfPrintCallback] which runs `callback` with the path of the PDF file and whether
+/// printing succeeded.
+pub fn pdf_print_callback(
+    callback: impl FnOnce(String, bool) + Send + 'static,
+) -> PdfPrintCallback {
+    PdfPrintCallback::new(FnPdfPrintCallback {
+        object: std::ptr::null_mut(),
+        callback: Arc::new(Mutex::new(Some(Box::new(callback)))),
+    })
+}
+
+struct FnPdfPrintCallback {
+    object: *mut RcImpl<_cef_pdf_print_callback_t, Self>,
+    callback: Once<dyn FnOnce(String, bool) + Send>,
+}
+
+wrap_rc!(
+    FnPdfPrintCallback,
+    WrapPdfPrintCallback,
+    _cef_pdf_print_callback_t,
+    callback
+);
+
+impl ImplPdfPrintCallback for FnPdfPrintCallback {
+    fn on_pdf_print_finished(&self, path: Option<&CefStringUtf16>, ok: c_int) {
+        if let Some(callback) = take(&self.callback) {
+            let path = path.map(|path| path.to_string()).unwrap_or_default();
+            callback(path, ok != 0);
+        }
+    }
+
+    fn get_raw(&self) -> *mut _cef_pdf_print_callback_t {
+        self.object as *mut _cef_pdf_print_callback_t
+    }
+}
@@ -0,0 +1,328 @@
+//! Handler chain module
+//!
+//! A [crate::Client] returns a single handler of each kind. [HandlerChain] combines several
+//! handlers of the same kind into one, so a ready-made handler like
+//! [crate::stats::ResourceStats::request_handler] can be used together with your own. The chain
+//! forwards every callback to its handlers in the order they were pushed:
+//!
+//! - Methods which return nothing are forwarded to every handler.
+//! - Methods which return a value stop at the first handler which handles the callback, i.e.
+//!   returns something other than the default value (`0`, `None` or an empty size), and return
+//!   that. If no handler does, the chain returns the default value.
+//! - Getters which return nothing but fill in out-parameters, e.g.
+//!   [crate::ImplRenderHandler::get_view_rect], stop at the first handler which sets one to
+//!   something other than the default value.
+//!
+//! The `ImplFoo` traits of the chains are generated for every handler returned by
+//! [crate::ImplClient], and [crate::client::ClientBuilder] chains the handlers pushed to the
+//! same slot.
+//!
+//! ```ignore
+//! let display_handler: DisplayHandler = HandlerChain::new()
+//!     .push(tracing_display_handler)
+//!     .push(my_display_handler)
+//!     .into();
+//! ```
+
+use std::os::raw::c_int;
+
+use cef_sys::cef_base_ref_counted_t;
+
+use crate::{rc::Rc, Rect, Size};
+
+/// Forwards the callbacks of a handler to an ordered list of handlers of the same kind.
+///
+/// Convert the chain into the handler, e.g. [crate::DisplayHandler], with [Into].
+pub struct HandlerChain<T> {
+    object: *mut cef_base_ref_counted_t,
+    handlers: Vec<T>,
+}
+
+impl<T> HandlerChain<T> {
+    /// Create an empty chain, which handles nothing.
+    pub fn new() -> Self {
+        Self {
+            object: std::ptr::null_mut(),
+            handlers: Vec::new(),
+        }
+    }
+
+    /// Add `handler` to the end of the chain.
+    pub fn push(mut self, handler: T) -> Self {
+        self.handlers.push(handler);
+        self
+    }
+
+    /// The handlers in the order the callbacks are forwarded to them.
+    pub fn handlers(&self) -> &[T] {
+        &self.handlers
+    }
+
+    pub(crate) fn set_object<U>(&mut self, object: *mut U) {
+        self.object = object.cast();
+    }
+
+    pub(crate) fn get_object<U>(&self) -> *mut U {
+        self.object.cast()
+    }
+}
+
+impl<T> Default for HandlerChain<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> From<Vec<T>> for HandlerChain<T> {
+    fn from(handlers: Vec<T>) -> Self {
+        Self {
+            object: std::ptr::null_mut(),
+            handlers,
+        }
+    }
+}
+
+impl<T> FromIterator<T> for HandlerChain<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        iter.into_iter().collect::<Vec<_>>().into()
+    }
+}
+
+impl<T: Clone> Clone for HandlerChain<T> {
+    fn clone(&self) -> Self {
+        if !self.object.is_null() {
+            unsafe { Rc::add_ref(self) };
+        }
+
+        Self {
+            object: self.object,
+            handlers: self.handlers.clone(),
+        }
+    }
+}
+
+/// The base of a chain which isn't converted into a handler yet, whose methods do nothing.
+static UNCONVERTED: cef_base_ref_counted_t = cef_base_ref_counted_t {
+    size: 0,
+    add_ref: None,
+    release: None,
+    has_one_ref: None,
+    has_at_least_one_ref: None,
+};
+
+impl<T> Rc for HandlerChain<T> {
+    fn as_base(&self) -> &cef_base_ref_counted_t {
+        if self.object.is_null() {
+            return &UNCONVERTED;
+        }
+        unsafe { &*self.object }
+    }
+}
+
+/// The value returned by a handler callback, which ends a [HandlerChain] if it is handled.
+pub trait ChainResult: Default {
+    /// `true` unless this is the default value.
+    fn is_handled(&self) -> bool;
+}
+
+impl ChainResult for c_int {
+    fn is_handled(&self) -> bool {
+        *self != 0
+    }
+}
+
+impl<T> ChainResult for Option<T> {
+    fn is_handled(&self) -> bool {
+        self.is_some()
+    }
+}
+
+impl ChainResult for Size {
+    fn is_handled(&self) -> bool {
+        self.width != 0 || self.height != 0
+    }
+}
+
+impl ChainResult for Rect {
+    fn is_handled(&self) -> bool {
+        self.x != 0 || self.y != 0 || self.width != 0 || self.height != 0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use cef_sys::{_cef_display_handler_t, _cef_render_handler_t};
+
+    use super::*;
+    use crate::{
+        rc::{wrap_rc, RcImpl},
+        Browser, CefString, CefStringUtf8, DisplayHandler, ImplBrowser, ImplDisplayHandler,
+        ImplRenderHandler, LogSeverity, RenderHandler,
+    };
+
+    type Calls = Arc<Mutex<Vec<&'static str>>>;
+
+    struct RecordingHandler {
+        object: *mut RcImpl<_cef_display_handler_t, Self>,
+        name: &'static str,
+        handled: bool,
+        calls: Calls,
+    }
+
+    wrap_rc!(
+        RecordingHandler,
+        WrapDisplayHandler,
+        _cef_display_handler_t,
+        name,
+        handled,
+        calls
+    );
+
+    impl ImplDisplayHandler for RecordingHandler {
+        fn on_title_change(&self, _browser: Option<&impl ImplBrowser>, _title: Option<&CefString>) {
+            self.calls.lock().unwrap().push(self.name);
+        }
+
+        fn on_console_message(
+            &self,
+            _browser: Option<&impl ImplBrowser>,
+            _level: LogSeverity,
+            _message: Option<&CefString>,
+            _source: Option<&CefString>,
+            _line: c_int,
+        ) -> c_int {
+            self.calls.lock().unwrap().push(self.name);
+            self.handled.into()
+        }
+
+        fn get_raw(&self) -> *mut _cef_display_handler_t {
+            self.object as *mut _
+        }
+    }
+
+    fn handler(name: &'static str, handled: bool, calls: &Calls) -> DisplayHandler {
+        DisplayHandler::new(RecordingHandler {
+            object: std::ptr::null_mut(),
+            name,
+            handled,
+            calls: calls.clone(),
+        })
+    }
+
+    #[test]
+    fn test_notification_reaches_every_handler() {
+        let calls = Calls::default();
+        let chain: DisplayHandler = HandlerChain::new()
+            .push(handler("first", true, &calls))
+            .push(handler("second", false, &calls))
+            .push(handler("third", true, &calls))
+            .into();
+        let title = CefString::from(&CefStringUtf8::from("title"));
+        chain.on_title_change(Option::<&Browser>::None, Some(&title));
+        assert_eq!(*calls.lock().unwrap(), ["first", "second", "third"]);
+    }
+
+    #[test]
+    fn test_first_handled_result_wins() {
+        let calls = Calls::default();
+        let chain: DisplayHandler = [
+            handler("first", false, &calls),
+            handler("second", true, &calls),
+            handler("third", true, &calls),
+        ]
+        .into_iter()
+        .collect::<HandlerChain<_>>()
+        .into();
+        let message = CefString::from(&CefStringUtf8::from("message"));
+        let handled = chain.on_console_message(
+            Option::<&Browser>::None,
+            LogSeverity::default(),
+            Some(&message),
+            None,
+            1,
+        );
+        assert_eq!(handled, 1);
+        assert_eq!(*calls.lock().unwrap(), ["first", "second"]);
+
+        calls.lock().unwrap().clear();
+        let chain: DisplayHandler = HandlerChain::new()
+            .push(handler("first", false, &calls))
+            .into();
+        let handled = chain.on_console_message(
+            Option::<&Browser>::None,
+            LogSeverity::default(),
+            Some(&message),
+            None,
+            1,
+        );
+        assert_eq!(handled, 0);
+        assert_eq!(*calls.lock().unwrap(), ["first"]);
+    }
+
+    struct ViewRectHandler {
+        object: *mut RcImpl<_cef_render_handler_t, Self>,
+        name: &'static str,
+        rect: Rect,
+        calls: Calls,
+    }
+
+    wrap_rc!(
+        ViewRectHandler,
+        WrapRenderHandler,
+        _cef_render_handler_t,
+        name,
+        rect,
+        calls
+    );
+
+    impl ImplRenderHandler for ViewRectHandler {
+        fn get_view_rect(&self, _browser: Option<&impl ImplBrowser>, rect: Option<&mut Rect>) {
+            self.calls.lock().unwrap().push(self.name);
+            if let Some(rect) = rect {
+                if self.rect.is_handled() {
+                    *rect = self.rect.clone();
+                }
+            }
+        }
+
+        fn get_raw(&self) -> *mut _cef_render_handler_t {
+            self.object as *mut _
+        }
+    }
+
+    fn view_rect_handler(name: &'static str, width: c_int, calls: &Calls) -> RenderHandler {
+        RenderHandler::new(ViewRectHandler {
+            object: std::ptr::null_mut(),
+            name,
+            rect: Rect {
+                width,
+                height: width,
+                ..Default::default()
+            },
+            calls: calls.clone(),
+        })
+    }
+
+    #[test]
+    fn test_first_handled_out_parameter_wins() {
+        let calls = Calls::default();
+        let chain: RenderHandler = HandlerChain::new()
+            .push(view_rect_handler("first", 0, &calls))
+            .push(view_rect_handler("second", 800, &calls))
+            .push(view_rect_handler("third", 600, &calls))
+            .into();
+        let mut rect = Rect::default();
+        chain.get_view_rect(Option::<&Browser>::None, Some(&mut rect));
+        assert_eq!((rect.width, rect.height), (800, 800));
+        assert_eq!(*calls.lock().unwrap(), ["first", "second"]);
+    }
+
+    #[test]
+    fn test_unconverted_chain_ref_count() {
+        let chain = HandlerChain::<DisplayHandler>::new();
+        assert!(!chain.has_one_ref());
+        assert!(!chain.has_at_least_one_ref());
+    }
+}
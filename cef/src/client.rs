@@ -0,0 +1,161 @@
+//! Client module
+
+use std::os::raw::c_int;
+
+use cef_sys::_cef_client_t;
+
+use crate::{
+    chain::HandlerChain,
+    message::MessageDispatcher,
+    rc::{wrap_rc, RcImpl},
+    *,
+};
+
+macro_rules! client_builder {
+    ($($field:ident, $push:ident: $handler:ident => $getter:ident,)*) => {
+        /// Builder of a [Client] which returns the handlers it was given.
+        ///
+        /// Several handlers pushed to the same slot are combined with a [HandlerChain]. A slot
+        /// without handlers returns null to CEF, which then skips its callbacks entirely. Pass a
+        /// handler builder of this crate instead of its built handler to leave the slot empty when
+        /// the builder [overrides](crate::display::DisplayHandlerBuilder::has_overrides) nothing.
+        ///
+        /// Pass the built client to the browser creation functions.
+        #[derive(Clone, Default)]
+        pub struct ClientBuilder {
+            $($field: Vec<$handler>,)*
+            message_dispatcher: Option<MessageDispatcher>,
+        }
+
+        impl ClientBuilder {
+            $(
+                #[doc = concat!("Return `handler` from [ImplClient::", stringify!($getter), "], replacing the handlers pushed before.")]
+                pub fn $field(mut self, handler: impl Into<Option<$handler>>) -> Self {
+                    self.$field = handler.into().into_iter().collect();
+                    self
+                }
+
+                #[doc = concat!("Add `handler` to the [HandlerChain] returned from [ImplClient::", stringify!($getter), "].")]
+                pub fn $push(mut self, handler: impl Into<Option<$handler>>) -> Self {
+                    self.$field.extend(handler.into());
+                    self
+                }
+            )*
+
+            /// Build the [Client].
+            pub fn build(self) -> Client {
+                Client::new(BuiltClient {
+                    object: std::ptr::null_mut(),
+                    $($field: chain(self.$field),)*
+                    message_dispatcher: self.message_dispatcher,
+                })
+            }
+        }
+
+        struct BuiltClient {
+            object: *mut RcImpl<_cef_client_t, Self>,
+            $($field: Option<$handler>,)*
+            message_dispatcher: Option<MessageDispatcher>,
+        }
+
+        wrap_rc!(BuiltClient, WrapClient, _cef_client_t, $($field,)* message_dispatcher);
+
+        impl ImplClient for BuiltClient {
+            $(
+                fn $getter(&self) -> Option<$handler> {
+                    self.$field.clone()
+                }
+            )*
+
+            fn on_process_message_received(
+                &self,
+                browser: Option<&impl ImplBrowser>,
+                frame: Option<&impl ImplFrame>,
+                source_process: ProcessId,
+                message: Option<&impl ImplProcessMessage>,
+            ) -> c_int {
+                let Some(dispatcher) = &self.message_dispatcher else {
+                    return 0;
+                };
+                dispatcher.dispatch(browser, frame, source_process, message) as c_int
+            }
+
+            fn get_raw(&self) -> *mut _cef_client_t {
+                self.object as *mut _cef_client_t
+            }
+        }
+    };
+}
+
+client_builder! {
+    audio_handler, push_audio_handler: AudioHandler => get_audio_handler,
+    command_handler, push_command_handler: CommandHandler => get_command_handler,
+    context_menu_handler, push_context_menu_handler: ContextMenuHandler => get_context_menu_handler,
+    dialog_handler, push_dialog_handler: DialogHandler => get_dialog_handler,
+    display_handler, push_display_handler: DisplayHandler => get_display_handler,
+    download_handler, push_download_handler: DownloadHandler => get_download_handler,
+    drag_handler, push_drag_handler: DragHandler => get_drag_handler,
+    find_handler, push_find_handler: FindHandler => get_find_handler,
+    focus_handler, push_focus_handler: FocusHandler => get_focus_handler,
+    frame_handler, push_frame_handler: FrameHandler => get_frame_handler,
+    permission_handler, push_permission_handler: PermissionHandler => get_permission_handler,
+    jsdialog_handler, push_jsdialog_handler: JsdialogHandler => get_jsdialog_handler,
+    keyboard_handler, push_keyboard_handler: KeyboardHandler => get_keyboard_handler,
+    life_span_handler, push_life_span_handler: LifeSpanHandler => get_life_span_handler,
+    load_handler, push_load_handler: LoadHandler => get_load_handler,
+    print_handler, push_print_handler: PrintHandler => get_print_handler,
+    render_handler, push_render_handler: RenderHandler => get_render_handler,
+    request_handler, push_request_handler: RequestHandler => get_request_handler,
+}
+
+impl ClientBuilder {
+    /// Create a builder which returns no handlers.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Route the process messages sent to the browser process through `dispatcher`.
+    pub fn message_dispatcher(mut self, dispatcher: MessageDispatcher) -> Self {
+        self.message_dispatcher = Some(dispatcher);
+        self
+    }
+}
+
+/// Return the only handler, or a [HandlerChain] of several.
+fn chain<T: From<HandlerChain<T>>>(mut handlers: Vec<T>) -> Option<T> {
+    match handlers.len() {
+        0 => None,
+        1 => handlers.pop(),
+        _ => Some(HandlerChain::from(handlers).into()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{context_menu::ContextMenuHandlerBuilder, display::DisplayHandlerBuilder};
+
+    #[test]
+    fn test_builders_without_overrides_return_null() {
+        let client = ClientBuilder::new()
+            .display_handler(DisplayHandlerBuilder::new())
+            .context_menu_handler(ContextMenuHandlerBuilder::new())
+            .build();
+        assert!(client.get_display_handler().is_none());
+        assert!(client.get_context_menu_handler().is_none());
+
+        let client = ClientBuilder::new()
+            .display_handler(DisplayHandlerBuilder::new().on_address_change(|_, _, _| {}))
+            .push_display_handler(DisplayHandlerBuilder::new())
+            .context_menu_handler(ContextMenuHandlerBuilder::new().suppress_all_menus())
+            .build();
+        assert!(client.get_display_handler().is_some());
+        assert!(client.get_context_menu_handler().is_some());
+
+        // A built handler is always returned.
+        let client = ClientBuilder::new()
+            .display_handler(DisplayHandlerBuilder::new().build())
+            .build();
+        assert!(client.get_display_handler().is_some());
+    }
+}
@@ -0,0 +1,134 @@
+//! Compatibility module
+//!
+//! The bindings follow the CEF version they were generated from, and some of the signatures this
+//! crate wraps changed between versions. The build script emits a `cef_api_at_least = "<major>"`
+//! cfg for each version in [crate::sys::CEF_API_MILESTONES] up to
+//! [crate::sys::CEF_VERSION_MAJOR], which `update-bindings` writes from the CEF headers, and
+//! the helpers here branch on them. The safe API stays the same across adjacent versions:
+//!
+//! - [FrameId] holds the `i64` frame identifiers before CEF 122 and the string ones since.
+//! - [RuntimeStyle] selects the style of a browser, which CEF only does per browser since 125.
+//!
+//! ```ignore
+//! #[cfg(cef_api_at_least = "125")]
+//! let style = window_info.runtime_style;
+//! ```
+
+pub use crate::frame::FrameId;
+use crate::{Browser, Frame, ImplBrowser, ImplFrame, WindowInfo};
+#[cfg(cef_api_at_least = "122")]
+use crate::{CefString, CefStringList, CefStringUtf8};
+#[cfg(cef_api_at_least = "125")]
+use cef_sys::cef_runtime_style_t;
+
+/// The [FrameId] of `frame`, which is empty if the frame doesn't exist (anymore).
+pub(crate) fn frame_id(frame: &impl ImplFrame) -> FrameId {
+    #[cfg(cef_api_at_least = "122")]
+    let id = frame.get_identifier().map(FrameId::from);
+    // Negative identifiers are invalid.
+    #[cfg(not(cef_api_at_least = "122"))]
+    let id = Some(frame.get_identifier())
+        .filter(|&id| id >= 0)
+        .map(FrameId::from);
+    id.unwrap_or_default()
+}
+
+/// The frame of `browser` with the identifier `id`.
+pub(crate) fn frame_by_id(browser: &Browser, id: &FrameId) -> Option<Frame> {
+    #[cfg(cef_api_at_least = "122")]
+    {
+        let id = CefString::from(&CefStringUtf8::from(id.as_str()));
+        browser.get_frame_by_identifier(Some(&id))
+    }
+    #[cfg(not(cef_api_at_least = "122"))]
+    {
+        browser.get_frame_byident(id.as_str().parse().ok()?)
+    }
+}
+
+/// The identifiers of the frames of `browser`.
+pub(crate) fn frame_ids(browser: &Browser) -> Vec<FrameId> {
+    #[cfg(cef_api_at_least = "122")]
+    {
+        let mut identifiers = CefStringList::new();
+        browser.get_frame_identifiers(Some(&mut identifiers));
+        identifiers.into_iter().map(FrameId::from).collect()
+    }
+    #[cfg(not(cef_api_at_least = "122"))]
+    {
+        let mut count = browser.get_frame_count();
+        let mut identifiers = vec![0i64; count];
+        if let Some(first) = identifiers.first_mut() {
+            browser.get_frame_identifiers(Some(&mut count), Some(first));
+        }
+        identifiers.truncate(count);
+        identifiers.into_iter().map(FrameId::from).collect()
+    }
+}
+
+/// The style of the browser UI, see [crate::browser::BrowserOptions::runtime_style].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum RuntimeStyle {
+    /// Let CEF choose, which is Alloy style for windowless browsers and Chrome style otherwise.
+    #[default]
+    Default,
+    Chrome,
+    Alloy,
+}
+
+impl RuntimeStyle {
+    /// Select this style in `window_info`. Before CEF 125 the style is chosen for every browser
+    /// with [crate::Settings], so this does nothing.
+    pub fn apply(self, window_info: &mut WindowInfo) {
+        #[cfg(cef_api_at_least = "125")]
+        {
+            window_info.runtime_style = self.into();
+        }
+        #[cfg(not(cef_api_at_least = "125"))]
+        let _ = window_info;
+    }
+}
+
+#[cfg(cef_api_at_least = "125")]
+impl From<RuntimeStyle> for crate::RuntimeStyle {
+    fn from(style: RuntimeStyle) -> Self {
+        match style {
+            RuntimeStyle::Default => cef_runtime_style_t::CEF_RUNTIME_STYLE_DEFAULT,
+            RuntimeStyle::Chrome => cef_runtime_style_t::CEF_RUNTIME_STYLE_CHROME,
+            RuntimeStyle::Alloy => cef_runtime_style_t::CEF_RUNTIME_STYLE_ALLOY,
+        }
+        .into()
+    }
+}
+
+#[cfg(cef_api_at_least = "125")]
+impl From<crate::RuntimeStyle> for RuntimeStyle {
+    fn from(style: crate::RuntimeStyle) -> Self {
+        match style.into() {
+            cef_runtime_style_t::CEF_RUNTIME_STYLE_CHROME => Self::Chrome,
+            cef_runtime_style_t::CEF_RUNTIME_STYLE_ALLOY => Self::Alloy,
+            _ => Self::Default,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_runtime_style() {
+        let mut window_info = WindowInfo::default();
+        RuntimeStyle::Alloy.apply(&mut window_info);
+        #[cfg(cef_api_at_least = "125")]
+        for style in [
+            RuntimeStyle::Default,
+            RuntimeStyle::Chrome,
+            RuntimeStyle::Alloy,
+        ] {
+            assert_eq!(RuntimeStyle::from(crate::RuntimeStyle::from(style)), style);
+            style.apply(&mut window_info);
+            assert_eq!(RuntimeStyle::from(window_info.runtime_style), style);
+        }
+    }
+}
@@ -0,0 +1,896 @@
+//! Context module
+//!
+//! [Context::initialize] wraps [initialize] and [shutdown], and explains why CEF failed to start.
+//! Call [preflight] first to catch configuration mistakes which CEF only reports in its log.
+//!
+//! ## Teardown order
+//!
+//! CEF crashes on shutdown when browsers are still open, or when scheme handler factories and
+//! request contexts are released after it. [Context] keeps track of the browsers, request contexts
+//! and scheme handler factories created through this crate, e.g. with
+//! [crate::browser::create_browser_sync], [crate::profile::ProfileManager::create_or_open] and
+//! [crate::scheme::LocalContentBuilder::build], and shuts down in this order:
+//!
+//! 1. Close the browsers which are still open, and wait until they are closed.
+//! 2. Clear the scheme handler factories.
+//! 3. Release the request contexts.
+//! 4. Call [shutdown].
+//!
+//! Everything it has to force is reported as a [DiagnosticCategory::ForcedTeardown], e.g. a
+//! browser which was still open, or a request context which something else still references.
+//! Flush cookies before with [crate::shutdown::CleanShutdown].
+
+use std::{
+    env, fmt,
+    marker::PhantomData,
+    mem::{self, ManuallyDrop},
+    os::raw::c_int,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use cef_sys::cef_resultcode_t;
+
+use crate::{
+    args::Args,
+    clear_scheme_handler_factories,
+    diagnostics::{self, DiagnosticCategory},
+    do_message_loop_work, get_exit_code, initialize,
+    rc::Rc,
+    shutdown, version_info, Browser, ImplApp, ImplBrowser, ImplBrowserHost, ImplRequestContext,
+    RequestContext, Resultcode, Settings,
+};
+
+/// Name of the ICU data file which must ship next to the CEF resources.
+pub const ICU_DATA_FILE: &str = "icudtl.dat";
+
+/// `major.minor.patch` version of CEF these bindings were generated from.
+pub fn binding_version() -> (u32, u32, u32) {
+    (
+        cef_sys::CEF_VERSION_MAJOR,
+        cef_sys::CEF_VERSION_MINOR,
+        cef_sys::CEF_VERSION_PATCH,
+    )
+}
+
+/// `major.minor.patch` version of the loaded `libcef`.
+pub fn runtime_version() -> (u32, u32, u32) {
+    let (major, minor, patch, _) = runtime_version_info();
+    (major, minor, patch)
+}
+
+/// `major.minor.patch` version and commit number of the loaded `libcef`.
+pub fn runtime_version_info() -> (u32, u32, u32, u32) {
+    let entry = |index| version_info(index).max(0) as u32;
+    (entry(0), entry(1), entry(2), entry(3))
+}
+
+/// Check that the loaded `libcef` has the `major.minor.patch` version and commit number
+/// `expected`. A commit number of 0 matches any commit, since the bindings only know the version
+/// they were generated from, see [binding_version].
+pub fn check_version(expected: (u32, u32, u32, u32)) -> Result<(), VersionMismatch> {
+    compare_versions(expected, runtime_version_info())
+}
+
+fn compare_versions(
+    expected: (u32, u32, u32, u32),
+    actual: (u32, u32, u32, u32),
+) -> Result<(), VersionMismatch> {
+    let (major, minor, patch, commit) = expected;
+    if (major, minor, patch) == (actual.0, actual.1, actual.2)
+        && (commit == 0 || commit == actual.3)
+    {
+        Ok(())
+    } else {
+        Err(VersionMismatch { expected, actual })
+    }
+}
+
+/// The loaded `libcef` is not the version which [check_version] expected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VersionMismatch {
+    /// `major.minor.patch` and commit number, which is 0 for any commit.
+    pub expected: (u32, u32, u32, u32),
+    pub actual: (u32, u32, u32, u32),
+}
+
+impl fmt::Display for VersionMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (major, minor, patch, commit) = self.actual;
+        write!(
+            f,
+            "libcef version {major}.{minor}.{patch} (commit {commit}) doesn't match the expected version "
+        )?;
+        let (major, minor, patch, commit) = self.expected;
+        write!(f, "{major}.{minor}.{patch}")?;
+        if commit != 0 {
+            write!(f, " (commit {commit})")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for VersionMismatch {}
+
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Set once a [Context] shut CEF down, which can't be initialized again in the same process.
+static SHUT_DOWN: AtomicBool = AtomicBool::new(false);
+
+/// How long dropping a [Context] waits for the browsers to close before shutting down anyway.
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// `true` while a [Context] is alive, i.e. between a successful [Context::initialize] and its
+/// shutdown. CEF initialized without a [Context] is not detected.
+pub fn is_running() -> bool {
+    RUNNING.load(Ordering::Acquire)
+}
+
+/// A running CEF browser process. Dropping it shuts CEF down, so keep it alive until the message
+/// loop returns and every browser is closed.
+///
+/// Debug builds panic when a wrapper object from before the shutdown is used afterwards, and the
+/// `leak-tracking` feature reports the wrapper objects which are still alive at shutdown.
+pub struct Context {
+    // CEF must be shut down on the thread which initialized it.
+    _not_send: PhantomData<*mut ()>,
+    /// CEF runs the message loop on its own thread, so it must not be pumped while closing.
+    multi_threaded_message_loop: bool,
+}
+
+impl Context {
+    /// Initialize CEF in the browser process. On failure the returned [InitError] carries what
+    /// could be found out about the cause.
+    pub fn initialize(
+        args: &Args,
+        settings: &Settings,
+        app: Option<&impl ImplApp>,
+    ) -> Result<Self, InitError> {
+        if SHUT_DOWN.load(Ordering::Acquire) {
+            return Err(InitError::AlreadyShutDown);
+        }
+        let (major, minor, patch) = binding_version();
+        check_version((major, minor, patch, 0)).map_err(InitError::VersionMismatch)?;
+
+        if initialize(
+            Some(args.as_main_args()),
+            Some(settings),
+            app,
+            std::ptr::null_mut(),
+        ) != 0
+        {
+            RUNNING.store(true, Ordering::Release);
+            return Ok(Self {
+                _not_send: PhantomData,
+                multi_threaded_message_loop: settings.multi_threaded_message_loop != 0,
+            });
+        }
+
+        let exit_code = get_exit_code();
+        let missing_paths = SettingsPaths::from(settings).missing_resources();
+        Err(InitError::from_exit_code(exit_code, missing_paths))
+    }
+
+    /// Tear down like dropping the context, see the [module documentation](self), but only wait
+    /// `timeout` for the browsers to close. After a timeout, CEF is left running, since shutting
+    /// it down with open browsers crashes, so exit the process.
+    ///
+    /// Call it on the thread which initialized CEF, after [crate::run_message_loop] returned.
+    pub fn shutdown_with_timeout(self, timeout: Duration) -> Result<(), ShutdownTimeout> {
+        let context = ManuallyDrop::new(self);
+        let mut teardown = take_teardown();
+        if let Err(timeout) = teardown.close_browsers(Instant::now() + timeout, || context.pump()) {
+            RUNNING.store(false, Ordering::Release);
+            return Err(timeout);
+        }
+        teardown.release();
+        context.shut_down();
+        Ok(())
+    }
+
+    /// Run CEF until the browsers it was told to close are gone.
+    fn pump(&self) {
+        if !self.multi_threaded_message_loop {
+            do_message_loop_work();
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+
+    fn shut_down(&self) {
+        #[cfg(feature = "leak-tracking")]
+        if let Some(report) = crate::rc::leak_report() {
+            eprintln!("{report}");
+        }
+        RUNNING.store(false, Ordering::Release);
+        SHUT_DOWN.store(true, Ordering::Release);
+        shutdown();
+        crate::rc::end_shutdown_epoch();
+    }
+}
+
+impl Drop for Context {
+    fn drop(&mut self) {
+        let mut teardown = take_teardown();
+        if let Err(timeout) =
+            teardown.close_browsers(Instant::now() + CLOSE_TIMEOUT, || self.pump())
+        {
+            diagnostics::report(
+                DiagnosticCategory::ForcedTeardown,
+                format_args!("{timeout}, shutting down anyway"),
+            );
+        }
+        teardown.release();
+        self.shut_down();
+    }
+}
+
+/// `true` once a [Context] shut CEF down.
+pub(crate) fn is_shut_down() -> bool {
+    SHUT_DOWN.load(Ordering::Acquire)
+}
+
+/// [Context::shutdown_with_timeout] gave up waiting for the browsers to close.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ShutdownTimeout {
+    pub open_browsers: usize,
+}
+
+impl fmt::Display for ShutdownTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} browsers were still open at shutdown",
+            self.open_browsers
+        )
+    }
+}
+
+impl std::error::Error for ShutdownTimeout {}
+
+/// What [Context] tears down before shutting CEF down, see the [module documentation](self).
+#[derive(Default)]
+struct Teardown {
+    browsers: Vec<Browser>,
+    request_contexts: Vec<RequestContext>,
+    /// The request contexts with scheme handler factories, `None` for the global factories.
+    scheme_factories: Vec<Option<RequestContext>>,
+}
+
+static TEARDOWN: Mutex<Teardown> = Mutex::new(Teardown {
+    browsers: Vec::new(),
+    request_contexts: Vec::new(),
+    scheme_factories: Vec::new(),
+});
+
+fn take_teardown() -> Teardown {
+    mem::take(&mut *TEARDOWN.lock().unwrap_or_else(|err| err.into_inner()))
+}
+
+fn with_teardown(f: impl FnOnce(&mut Teardown)) {
+    f(&mut TEARDOWN.lock().unwrap_or_else(|err| err.into_inner()));
+}
+
+/// Close `browser` before shutdown if it is still open then.
+pub(crate) fn register_browser(browser: &Browser) {
+    with_teardown(|teardown| {
+        // Forget the browsers closed since, so they can be released.
+        teardown.browsers.retain(Browser::is_valid);
+        teardown.browsers.push(browser.clone());
+    });
+}
+
+/// The registered browsers which are still open.
+pub(crate) fn browsers() -> Vec<Browser> {
+    let mut browsers = vec![];
+    with_teardown(|teardown| {
+        browsers = teardown
+            .browsers
+            .iter()
+            .filter(|browser| browser.is_valid())
+            .cloned()
+            .collect();
+    });
+    browsers
+}
+
+/// Release `context` before shutdown.
+pub(crate) fn register_request_context(context: &RequestContext) {
+    with_teardown(|teardown| {
+        if !teardown
+            .request_contexts
+            .iter()
+            .any(|registered| registered.is_same(Some(context)) != 0)
+        {
+            teardown.request_contexts.push(context.clone());
+        }
+    });
+}
+
+/// Clear the scheme handler factories of `context`, or the global ones, before shutdown.
+pub(crate) fn register_scheme_factories(context: Option<&RequestContext>) {
+    with_teardown(|teardown| {
+        let registered =
+            teardown
+                .scheme_factories
+                .iter()
+                .any(|registered| match (registered, context) {
+                    (Some(registered), Some(context)) => registered.is_same(Some(context)) != 0,
+                    (None, None) => true,
+                    _ => false,
+                });
+        if !registered {
+            teardown.scheme_factories.push(context.cloned());
+        }
+    });
+}
+
+impl Teardown {
+    /// Close the browsers which are still open, calling `pump` until they are gone or until
+    /// `deadline`.
+    fn close_browsers(
+        &mut self,
+        deadline: Instant,
+        mut pump: impl FnMut(),
+    ) -> Result<(), ShutdownTimeout> {
+        self.browsers.retain(Browser::is_valid);
+        for browser in &self.browsers {
+            diagnostics::report(
+                DiagnosticCategory::ForcedTeardown,
+                format_args!("closing browser {} at shutdown", browser.identifier()),
+            );
+            if let Some(host) = browser.get_host() {
+                host.close_browser(1);
+            }
+        }
+        loop {
+            self.browsers.retain(Browser::is_valid);
+            if self.browsers.is_empty() {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Err(ShutdownTimeout {
+                    open_browsers: self.browsers.len(),
+                });
+            }
+            pump();
+        }
+    }
+
+    /// Clear the scheme handler factories, and then release the request contexts.
+    fn release(self) {
+        drop(self.browsers);
+        for context in self.scheme_factories {
+            match context {
+                Some(context) => context.clear_scheme_handler_factories(),
+                None => clear_scheme_handler_factories(),
+            };
+        }
+        for context in self.request_contexts {
+            if !context.has_one_ref() {
+                diagnostics::report(
+                    DiagnosticCategory::ForcedTeardown,
+                    "a request context is still referenced at shutdown",
+                );
+            }
+        }
+    }
+}
+
+/// Why [Context::initialize] failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InitError {
+    /// The loaded `libcef` is not the version these bindings were generated from.
+    VersionMismatch(VersionMismatch),
+    /// Another browser process already uses the cache directory. This is the expected result
+    /// when a second instance passes its command line to the running one and should exit.
+    ProcessSingleton { exit_code: c_int },
+    /// The resources or locales directories configured in [Settings] don't exist.
+    MissingPaths {
+        exit_code: c_int,
+        paths: Vec<PathBuf>,
+    },
+    /// CEF failed for another reason, see its log for details.
+    Failed { exit_code: c_int },
+    /// A [Context] already shut CEF down, which can't be initialized again in the same process.
+    AlreadyShutDown,
+}
+
+impl InitError {
+    fn from_exit_code(exit_code: c_int, paths: Vec<PathBuf>) -> Self {
+        let singleton = matches!(
+            result_code(exit_code),
+            Some(Resultcode::NormalExitProcessNotified | Resultcode::ProfileInUse)
+        );
+        if singleton {
+            Self::ProcessSingleton { exit_code }
+        } else if !paths.is_empty() {
+            Self::MissingPaths { exit_code, paths }
+        } else {
+            Self::Failed { exit_code }
+        }
+    }
+
+    /// The exit code reported by [get_exit_code], if CEF got far enough to set one.
+    pub fn exit_code(&self) -> Option<c_int> {
+        match self {
+            Self::VersionMismatch(_) | Self::AlreadyShutDown => None,
+            Self::ProcessSingleton { exit_code }
+            | Self::MissingPaths { exit_code, .. }
+            | Self::Failed { exit_code } => Some(*exit_code),
+        }
+    }
+
+    /// The typed [InitError::exit_code], if it is a known [Resultcode].
+    pub fn result_code(&self) -> Option<Resultcode> {
+        self.exit_code().and_then(result_code)
+    }
+}
+
+impl fmt::Display for InitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::VersionMismatch(mismatch) => write!(f, "{mismatch}"),
+            Self::ProcessSingleton { exit_code } => write!(
+                f,
+                "another browser process is using the cache directory (exit code {exit_code})"
+            ),
+            Self::MissingPaths { exit_code, paths } => {
+                write!(
+                    f,
+                    "CEF failed to initialize (exit code {exit_code}), missing:"
+                )?;
+                for path in paths {
+                    write!(f, " {}", path.display())?;
+                }
+                Ok(())
+            }
+            Self::Failed { exit_code } => {
+                write!(f, "CEF failed to initialize (exit code {exit_code})")
+            }
+            Self::AlreadyShutDown => write!(
+                f,
+                "CEF was already shut down and can't be initialized again in this process"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for InitError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::VersionMismatch(mismatch) => Some(mismatch),
+            _ => None,
+        }
+    }
+}
+
+/// Map a process exit code to a known [Resultcode].
+fn result_code(exit_code: c_int) -> Option<Resultcode> {
+    use cef_resultcode_t::*;
+
+    let code = match exit_code {
+        0 => CEF_RESULT_CODE_NORMAL_EXIT,
+        1 => CEF_RESULT_CODE_KILLED,
+        2 => CEF_RESULT_CODE_HUNG,
+        3 => CEF_RESULT_CODE_KILLED_BAD_MESSAGE,
+        4 => CEF_RESULT_CODE_GPU_DEAD_ON_ARRIVAL,
+        7 => CEF_RESULT_CODE_MISSING_DATA,
+        13 => CEF_RESULT_CODE_UNSUPPORTED_PARAM,
+        21 => CEF_RESULT_CODE_PROFILE_IN_USE,
+        22 => CEF_RESULT_CODE_PACK_EXTENSION_ERROR,
+        24 => CEF_RESULT_CODE_NORMAL_EXIT_PROCESS_NOTIFIED,
+        31 => CEF_RESULT_CODE_INVALID_SANDBOX_STATE,
+        32 => CEF_RESULT_CODE_CLOUD_POLICY_ENROLLMENT_FAILED,
+        34 => CEF_RESULT_CODE_GPU_EXIT_ON_CONTEXT_LOST,
+        36 => CEF_RESULT_CODE_NORMAL_EXIT_PACK_EXTENSION_SUCCESS,
+        37 => CEF_RESULT_CODE_SYSTEM_RESOURCE_EXHAUSTED,
+        _ => return None,
+    };
+    Some(code.into())
+}
+
+/// A likely configuration mistake found by [preflight].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InitWarning {
+    /// The cache path is relative, so it depends on the working directory of the process.
+    RelativeCachePath(PathBuf),
+    /// The cache path is not inside the root cache path, so CEF will ignore it.
+    CachePathOutsideRoot { cache: PathBuf, root: PathBuf },
+    /// A directory configured in [Settings] doesn't exist.
+    MissingPath(PathBuf),
+    /// [ICU_DATA_FILE] was not found in any of the searched directories.
+    MissingIcuData { searched: Vec<PathBuf> },
+    /// The locales directory has no `.pak` file for the configured locale, so CEF silently falls
+    /// back to [crate::locale::DEFAULT_LOCALE].
+    MissingLocale { locale: String, searched: PathBuf },
+}
+
+impl fmt::Display for InitWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RelativeCachePath(path) => {
+                write!(f, "cache path {} is relative", path.display())
+            }
+            Self::CachePathOutsideRoot { cache, root } => write!(
+                f,
+                "cache path {} is not inside the root cache path {}",
+                cache.display(),
+                root.display()
+            ),
+            Self::MissingPath(path) => write!(f, "{} doesn't exist", path.display()),
+            Self::MissingIcuData { searched } => {
+                write!(f, "{ICU_DATA_FILE} not found in:")?;
+                for path in searched {
+                    write!(f, " {}", path.display())?;
+                }
+                Ok(())
+            }
+            Self::MissingLocale { locale, searched } => write!(
+                f,
+                "{locale}.pak not found in {}, falling back to {}",
+                searched.display(),
+                crate::locale::DEFAULT_LOCALE
+            ),
+        }
+    }
+}
+
+/// Check `settings` for mistakes which make CEF fail to start or misbehave, without
+/// initializing CEF.
+pub fn preflight(settings: &Settings) -> Vec<InitWarning> {
+    let paths = SettingsPaths::from(settings);
+    let mut warnings = paths.cache_warnings();
+    warnings.extend(
+        paths
+            .missing_resources()
+            .into_iter()
+            .map(InitWarning::MissingPath),
+    );
+
+    let searched = paths.icu_data_dirs();
+    if !searched.iter().any(|dir| dir.join(ICU_DATA_FILE).is_file()) {
+        warnings.push(InitWarning::MissingIcuData { searched });
+    }
+    warnings.extend(paths.missing_locale());
+    warnings
+}
+
+/// The paths of [Settings] which [preflight] checks, `None` when left empty.
+#[derive(Clone, Debug, Default)]
+struct SettingsPaths {
+    cache: Option<PathBuf>,
+    root_cache: Option<PathBuf>,
+    resources_dir: Option<PathBuf>,
+    locales_dir: Option<PathBuf>,
+    locale: Option<String>,
+}
+
+impl From<&Settings> for SettingsPaths {
+    fn from(settings: &Settings) -> Self {
+        let path = |value: &crate::CefStringUtf16| {
+            let value = value.to_string();
+            (!value.is_empty()).then(|| PathBuf::from(value))
+        };
+        Self {
+            cache: path(&settings.cache_path),
+            root_cache: path(&settings.root_cache_path),
+            resources_dir: path(&settings.resources_dir_path),
+            locales_dir: path(&settings.locales_dir_path),
+            locale: Some(settings.locale.to_string()).filter(|locale| !locale.is_empty()),
+        }
+    }
+}
+
+impl SettingsPaths {
+    fn cache_warnings(&self) -> Vec<InitWarning> {
+        let mut warnings = vec![];
+        if let Some(cache) = &self.cache {
+            if cache.is_relative() {
+                warnings.push(InitWarning::RelativeCachePath(cache.clone()));
+            }
+            if let Some(root) = &self.root_cache {
+                if !cache.starts_with(root) {
+                    warnings.push(InitWarning::CachePathOutsideRoot {
+                        cache: cache.clone(),
+                        root: root.clone(),
+                    });
+                }
+            }
+        }
+        if let Some(root) = &self.root_cache {
+            if root.is_relative() {
+                warnings.push(InitWarning::RelativeCachePath(root.clone()));
+            }
+        }
+        warnings
+    }
+
+    fn missing_resources(&self) -> Vec<PathBuf> {
+        [&self.resources_dir, &self.locales_dir]
+            .into_iter()
+            .flatten()
+            .filter(|path| !path.is_dir())
+            .cloned()
+            .collect()
+    }
+
+    /// The locales directory defaults to `locales` next to the executable. A missing directory
+    /// is reported by [SettingsPaths::missing_resources] instead.
+    fn missing_locale(&self) -> Option<InitWarning> {
+        let locale = self.locale.as_ref()?;
+        let dir = self.locales_dir.clone().or_else(|| {
+            let exe = env::current_exe().ok()?;
+            Some(exe.parent()?.join("locales"))
+        })?;
+        if !dir.is_dir() {
+            return None;
+        }
+        // Chromium also accepts the pack of the language without the region, e.g. `de` for `de-AT`.
+        let language = locale.split('-').next().unwrap_or_default();
+        let found = [locale.as_str(), language]
+            .into_iter()
+            .any(|name| dir.join(format!("{name}.pak")).is_file());
+        (!found).then(|| InitWarning::MissingLocale {
+            locale: locale.clone(),
+            searched: dir,
+        })
+    }
+
+    /// Directories where CEF or a packaged install usually keeps [ICU_DATA_FILE].
+    fn icu_data_dirs(&self) -> Vec<PathBuf> {
+        let mut dirs: Vec<PathBuf> = self.resources_dir.iter().cloned().collect();
+        if let Some(dir) = env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(Path::to_path_buf))
+        {
+            dirs.push(dir);
+        }
+        if let Some(dir) = env::var_os("CEF_PATH") {
+            dirs.push(dir.into());
+        }
+        if let Some(home) = env::var_os("HOME") {
+            dirs.push(Path::new(&home).join(".local/share/cef"));
+        }
+        if env::var_os("FLATPAK_ID").is_some() {
+            dirs.push("/app/lib/cef".into());
+        }
+        dirs.dedup();
+        dirs
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use cef_sys::{_cef_preference_manager_t, _cef_request_context_t};
+
+    use super::*;
+    use crate::{
+        fixtures::{FakeBrowser, FakeHost},
+        rc::{wrap_rc, RcImpl},
+        App, ImplPreferenceManager,
+    };
+
+    type Steps = Arc<Mutex<Vec<&'static str>>>;
+
+    struct FakeRequestContext {
+        object: *mut RcImpl<_cef_request_context_t, Self>,
+        steps: Steps,
+    }
+
+    impl ImplPreferenceManager for FakeRequestContext {
+        fn get_raw(&self) -> *mut _cef_preference_manager_t {
+            self.object as *mut _
+        }
+    }
+
+    impl ImplRequestContext for FakeRequestContext {
+        fn clear_scheme_handler_factories(&self) -> c_int {
+            self.steps.lock().unwrap().push("clear factories");
+            1
+        }
+
+        fn get_raw(&self) -> *mut _cef_request_context_t {
+            self.object as *mut _
+        }
+    }
+
+    wrap_rc!(
+        FakeRequestContext,
+        WrapRequestContext,
+        _cef_request_context_t,
+        steps
+    );
+
+    fn fake_browser(steps: &Steps, closes: bool) -> Browser {
+        let host = FakeHost::new().on_close({
+            let steps = steps.clone();
+            move || {
+                let mut steps = steps.lock().unwrap();
+                steps.push(if closes { "close" } else { "close ignored" });
+            }
+        });
+        let steps = steps.clone();
+        // The browser is open until its host was told to close it and CEF ran once.
+        FakeBrowser::new(1)
+            .valid(move || {
+                let steps = steps.lock().unwrap();
+                !steps
+                    .iter()
+                    .position(|step| *step == "close")
+                    .is_some_and(|close| steps[close..].contains(&"pump"))
+            })
+            .host(host.build())
+            .build()
+    }
+
+    #[test]
+    fn test_check_version() {
+        let actual = runtime_version_info();
+        assert_eq!(check_version(actual), Ok(()));
+        assert_eq!(check_version((actual.0, actual.1, actual.2, 0)), Ok(()));
+
+        let mismatch = compare_versions((131, 3, 4, 0), (131, 3, 5, 2)).unwrap_err();
+        assert_eq!(mismatch.actual, (131, 3, 5, 2));
+        assert_eq!(
+            mismatch.to_string(),
+            "libcef version 131.3.5 (commit 2) doesn't match the expected version 131.3.4"
+        );
+        assert!(compare_versions((131, 3, 4, 1), (131, 3, 4, 2)).is_err());
+        assert!(compare_versions((131, 3, 4, 2), (131, 3, 4, 2)).is_ok());
+    }
+
+    #[test]
+    fn test_initialize_after_shutdown() {
+        SHUT_DOWN.store(true, Ordering::Release);
+        let args = Args::new(["app".to_string()]);
+        let result = Context::initialize(&args, &Settings::default(), Option::<&App>::None);
+        SHUT_DOWN.store(false, Ordering::Release);
+
+        let error = result.err().unwrap();
+        assert_eq!(error, InitError::AlreadyShutDown);
+        assert_eq!(error.exit_code(), None);
+    }
+
+    #[test]
+    fn test_teardown_order() {
+        let steps = Steps::default();
+        let context = RequestContext::new(FakeRequestContext {
+            object: std::ptr::null_mut(),
+            steps: steps.clone(),
+        });
+        let mut teardown = Teardown {
+            browsers: vec![fake_browser(&steps, true)],
+            request_contexts: vec![context.clone()],
+            scheme_factories: vec![Some(context.clone())],
+        };
+
+        let diagnostics = diagnostics::collect(|| {
+            let pump = || steps.lock().unwrap().push("pump");
+            let deadline = Instant::now() + Duration::from_secs(60);
+            assert_eq!(teardown.close_browsers(deadline, pump), Ok(()));
+            teardown.release();
+        });
+        assert_eq!(*steps.lock().unwrap(), ["close", "pump", "clear factories"]);
+        let categories: Vec<_> = diagnostics.iter().map(|d| d.category).collect();
+        // The browser was still open, and the test still holds the request context.
+        assert_eq!(
+            categories,
+            [
+                DiagnosticCategory::ForcedTeardown,
+                DiagnosticCategory::ForcedTeardown
+            ]
+        );
+        assert!(context.has_one_ref());
+    }
+
+    #[test]
+    fn test_teardown_timeout() {
+        let steps = Steps::default();
+        let mut teardown = Teardown {
+            browsers: vec![fake_browser(&steps, false), fake_browser(&steps, true)],
+            ..Default::default()
+        };
+        diagnostics::collect(|| {
+            let deadline = Instant::now() + Duration::from_millis(20);
+            assert_eq!(
+                teardown.close_browsers(deadline, || thread::sleep(Duration::from_millis(1))),
+                Err(ShutdownTimeout { open_browsers: 2 })
+            );
+        });
+        assert_eq!(*steps.lock().unwrap(), ["close ignored", "close"]);
+    }
+
+    #[test]
+    fn test_init_error_from_exit_code() {
+        assert_eq!(
+            InitError::from_exit_code(24, vec![]),
+            InitError::ProcessSingleton { exit_code: 24 }
+        );
+        assert_eq!(
+            InitError::from_exit_code(7, vec!["/missing".into()]),
+            InitError::MissingPaths {
+                exit_code: 7,
+                paths: vec!["/missing".into()]
+            }
+        );
+        let error = InitError::from_exit_code(1000, vec![]);
+        assert_eq!(error, InitError::Failed { exit_code: 1000 });
+        assert_eq!(error.result_code(), None);
+        assert_eq!(
+            error.to_string(),
+            "CEF failed to initialize (exit code 1000)"
+        );
+    }
+
+    #[test]
+    fn test_cache_warnings() {
+        let paths = SettingsPaths {
+            cache: Some("cache".into()),
+            root_cache: Some("/var/cache/app".into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            paths.cache_warnings(),
+            vec![
+                InitWarning::RelativeCachePath("cache".into()),
+                InitWarning::CachePathOutsideRoot {
+                    cache: "cache".into(),
+                    root: "/var/cache/app".into()
+                },
+            ]
+        );
+
+        let paths = SettingsPaths {
+            cache: Some("/var/cache/app/profile".into()),
+            root_cache: Some("/var/cache/app".into()),
+            ..Default::default()
+        };
+        assert!(paths.cache_warnings().is_empty());
+    }
+
+    #[test]
+    fn test_missing_resources() {
+        let existing = env::temp_dir();
+        let missing = existing.join("cef-rs-missing-resources");
+        let paths = SettingsPaths {
+            resources_dir: Some(existing),
+            locales_dir: Some(missing.clone()),
+            ..Default::default()
+        };
+        assert_eq!(paths.missing_resources(), vec![missing]);
+    }
+
+    #[test]
+    fn test_missing_locale() {
+        let dir = env::temp_dir().join("cef-rs-missing-locale");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("de.pak"), b"").unwrap();
+        let paths = |locale: &str| SettingsPaths {
+            locales_dir: Some(dir.clone()),
+            locale: Some(locale.to_string()),
+            ..Default::default()
+        };
+        assert_eq!(paths("de").missing_locale(), None);
+        assert_eq!(paths("de-AT").missing_locale(), None);
+        assert_eq!(
+            paths("fr").missing_locale(),
+            Some(InitWarning::MissingLocale {
+                locale: "fr".into(),
+                searched: dir.clone()
+            })
+        );
+        let paths = SettingsPaths {
+            locales_dir: Some(dir.join("missing")),
+            locale: Some("fr".into()),
+            ..Default::default()
+        };
+        assert_eq!(paths.missing_locale(), None);
+    }
+}
@@ -0,0 +1,1444 @@
+//! Context menu module
+
+use std::{
+    collections::HashMap,
+    fmt::{self, Debug, Formatter},
+    future::Future,
+    os::raw::c_int,
+    pin::Pin,
+    sync::{Arc, Mutex, Weak},
+    task::{Context, Poll, Wake, Waker},
+};
+
+use cef_sys::{
+    _cef_context_menu_handler_t, cef_context_menu_media_type_t, cef_context_menu_type_flags_t,
+    cef_menu_color_type_t, cef_menu_id_t, cef_thread_id_t,
+};
+
+use crate::{
+    browser::BrowserId,
+    rc::{from_impl, wrap_rc, RcImpl},
+    *,
+};
+
+/// Label of the item which adds the misspelled word to the dictionary.
+pub const ADD_TO_DICTIONARY_LABEL: &str = "Add to dictionary";
+
+/// Label of the disabled item shown when there are no dictionary suggestions.
+pub const NO_SPELLING_SUGGESTIONS_LABEL: &str = "No spelling suggestions";
+
+from_impl!(ContextMenuParams, ImplContextMenuParams);
+from_impl!(RunContextMenuCallback, ImplRunContextMenuCallback);
+from_impl!(MenuModel, ImplMenuModel);
+
+/// The future returned by a [ContextMenuHandlerBuilder::custom] menu, which resolves to the
+/// command id the user selected, or `None` if the menu was dismissed.
+pub type CustomMenuFuture = Pin<Box<dyn Future<Output = Option<i32>> + Send>>;
+
+type CustomMenuFn = dyn Fn(ContextMenuData) -> CustomMenuFuture + Send + Sync;
+
+type SectionFn = dyn Fn(&ContextMenuParams, &MenuModel) + Send + Sync;
+
+/// Builder of a [ContextMenuHandler] with common context menu behaviors.
+///
+/// Return the built handler from [ImplClient::get_context_menu_handler].
+#[derive(Clone, Debug, Default)]
+pub struct ContextMenuHandlerBuilder {
+    suppress: Suppress,
+    spellcheck_suggestions: bool,
+    sections: Vec<Section>,
+    run: RunMenu,
+}
+
+/// What a [Section] is added for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Target {
+    Link,
+    Editable,
+    Selection,
+    Media,
+}
+
+impl Target {
+    fn matches(self, params: &ContextMenuParams) -> bool {
+        match self {
+            Self::Link => params.has_type_flag(cef_context_menu_type_flags_t::CM_TYPEFLAG_LINK),
+            Self::Editable => params.is_editable(),
+            Self::Selection => {
+                params.has_type_flag(cef_context_menu_type_flags_t::CM_TYPEFLAG_SELECTION)
+            }
+            Self::Media => params.media_type() != ContextMenuMediaType::None,
+        }
+    }
+}
+
+/// Items added to the menu when it is opened on a [Target].
+#[derive(Clone)]
+struct Section {
+    target: Target,
+    add: Arc<SectionFn>,
+}
+
+impl Debug for Section {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Section({:?})", self.target)
+    }
+}
+
+/// Which context menus are suppressed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Suppress {
+    #[default]
+    None,
+    All,
+    MediaOnly,
+}
+
+/// Who runs the context menus which aren't suppressed.
+#[derive(Clone, Default)]
+enum RunMenu {
+    /// CEF shows the native menu.
+    #[default]
+    Native,
+    /// Nobody, the menu is cancelled.
+    Suppressed,
+    Custom(Arc<CustomMenuFn>),
+}
+
+impl Debug for RunMenu {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Native => write!(f, "Native"),
+            Self::Suppressed => write!(f, "Suppressed"),
+            Self::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
+
+impl ContextMenuHandlerBuilder {
+    /// Create a builder which leaves the default context menu untouched.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Suppress every context menu by clearing the model before it is shown, and cancel running
+    /// it, so windowless browsers, which can't show the native menu, don't wait for it either.
+    /// Commands are left to the default handling, which has nothing to run once the menu is empty.
+    pub fn suppress_all_menus(mut self) -> Self {
+        self.suppress = Suppress::All;
+        self.run = RunMenu::Suppressed;
+        self
+    }
+
+    /// Render the context menus in the application instead of the native menu, e.g. for
+    /// windowless browsers. `custom` is called on the UI thread with a snapshot of the menu, and
+    /// the command id its future resolves to is run, or the menu is cancelled for `None`.
+    ///
+    /// The future is polled on the UI thread. It is dropped, and the menu cancelled, when CEF
+    /// dismisses the menu or the browser is destroyed first.
+    pub fn custom(
+        mut self,
+        custom: impl Fn(ContextMenuData) -> CustomMenuFuture + Send + Sync + 'static,
+    ) -> Self {
+        self.run = RunMenu::Custom(Arc::new(custom));
+        self
+    }
+
+    /// Suppress the context menu of images, videos and other media, and keep the others.
+    pub fn suppress_for_media_only(mut self) -> Self {
+        self.suppress = Suppress::MediaOnly;
+        self
+    }
+
+    /// Offer dictionary suggestions and an "Add to dictionary" item at the top of the menu when
+    /// it is opened on a misspelled word. CEF runs the default commands for these items.
+    pub fn spellcheck_suggestions(mut self, enabled: bool) -> Self {
+        self.spellcheck_suggestions = enabled;
+        self
+    }
+
+    /// Call `section` to add items to the menu when it is opened on a link, e.g. with
+    /// [ContextMenuParams::link_url]. The sections are called in the order they were added,
+    /// after the spelling suggestions, and not for suppressed menus.
+    pub fn when_link(
+        self,
+        section: impl Fn(&ContextMenuParams, &MenuModel) + Send + Sync + 'static,
+    ) -> Self {
+        self.section(Target::Link, section)
+    }
+
+    /// Like [ContextMenuHandlerBuilder::when_link], for menus opened on an editable field, e.g.
+    /// with [ContextMenuParams::edit_state_flags].
+    pub fn when_editable(
+        self,
+        section: impl Fn(&ContextMenuParams, &MenuModel) + Send + Sync + 'static,
+    ) -> Self {
+        self.section(Target::Editable, section)
+    }
+
+    /// Like [ContextMenuHandlerBuilder::when_link], for menus opened on selected text, e.g. with
+    /// [ContextMenuParams::selection_text].
+    pub fn when_selection(
+        self,
+        section: impl Fn(&ContextMenuParams, &MenuModel) + Send + Sync + 'static,
+    ) -> Self {
+        self.section(Target::Selection, section)
+    }
+
+    /// Like [ContextMenuHandlerBuilder::when_link], for menus opened on an image, video or other
+    /// [ContextMenuMediaType], e.g. with [ContextMenuParams::source_url].
+    pub fn when_media(
+        self,
+        section: impl Fn(&ContextMenuParams, &MenuModel) + Send + Sync + 'static,
+    ) -> Self {
+        self.section(Target::Media, section)
+    }
+
+    fn section(
+        mut self,
+        target: Target,
+        add: impl Fn(&ContextMenuParams, &MenuModel) + Send + Sync + 'static,
+    ) -> Self {
+        self.sections.push(Section {
+            target,
+            add: Arc::new(add),
+        });
+        self
+    }
+
+    /// `false` if the builder leaves the default context menu untouched.
+    pub fn has_overrides(&self) -> bool {
+        self.suppress != Suppress::None
+            || self.spellcheck_suggestions
+            || !self.sections.is_empty()
+            || !matches!(self.run, RunMenu::Native)
+    }
+
+    /// Build the [ContextMenuHandler].
+    pub fn build(self) -> ContextMenuHandler {
+        ContextMenuHandler::new(BuiltContextMenuHandler {
+            object: std::ptr::null_mut(),
+            builder: self,
+            pending: Default::default(),
+        })
+    }
+}
+
+/// The built handler, or `None` without [overrides](ContextMenuHandlerBuilder::has_overrides),
+/// see [crate::client::ClientBuilder::context_menu_handler].
+impl From<ContextMenuHandlerBuilder> for Option<ContextMenuHandler> {
+    fn from(builder: ContextMenuHandlerBuilder) -> Self {
+        builder.has_overrides().then(|| builder.build())
+    }
+}
+
+/// The running [ContextMenuHandlerBuilder::custom] menus by browser identifier.
+type PendingMenus = Arc<Mutex<HashMap<BrowserId, Arc<PendingMenu>>>>;
+
+struct BuiltContextMenuHandler {
+    object: *mut RcImpl<_cef_context_menu_handler_t, Self>,
+    builder: ContextMenuHandlerBuilder,
+    pending: PendingMenus,
+}
+
+wrap_rc!(
+    BuiltContextMenuHandler,
+    WrapContextMenuHandler,
+    _cef_context_menu_handler_t,
+    builder,
+    pending
+);
+
+impl ImplContextMenuHandler for BuiltContextMenuHandler {
+    fn on_before_context_menu(
+        &self,
+        _browser: Option<&impl ImplBrowser>,
+        _frame: Option<&impl ImplFrame>,
+        params: Option<&impl ImplContextMenuParams>,
+        model: Option<&impl ImplMenuModel>,
+    ) {
+        let (Some(params), Some(model)) = (params, model) else {
+            return;
+        };
+
+        let suppress = match self.builder.suppress {
+            Suppress::None => false,
+            Suppress::All => true,
+            Suppress::MediaOnly => params.get_media_type() != ContextMenuMediaType::None,
+        };
+        if suppress {
+            model.clear();
+            return;
+        }
+
+        let params = ContextMenuParams::from_impl(params);
+        if self.builder.spellcheck_suggestions {
+            insert_spellcheck_items(&params, model);
+        }
+        if !self.builder.sections.is_empty() {
+            let model = MenuModel::from_impl(model);
+            for section in &self.builder.sections {
+                if section.target.matches(&params) {
+                    (section.add)(&params, &model);
+                }
+            }
+        }
+    }
+
+    fn run_context_menu(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        _frame: Option<&impl ImplFrame>,
+        params: Option<&impl ImplContextMenuParams>,
+        model: Option<&impl ImplMenuModel>,
+        callback: Option<&impl ImplRunContextMenuCallback>,
+    ) -> c_int {
+        let Some(callback) = callback else {
+            return 0;
+        };
+        let custom = match &self.builder.run {
+            RunMenu::Native => return 0,
+            RunMenu::Suppressed => {
+                callback.cancel();
+                return 1;
+            }
+            RunMenu::Custom(custom) => custom,
+        };
+        let (Some(browser), Some(params), Some(model)) = (browser, params, model) else {
+            return 0;
+        };
+
+        let data = ContextMenuData::new(&ContextMenuParams::from_impl(params), model);
+        let menu = Arc::new(PendingMenu {
+            browser: Browser::from_impl(browser),
+            state: Mutex::new(Some((
+                custom(data),
+                RunContextMenuCallback::from_impl(callback),
+            ))),
+            pending: Arc::downgrade(&self.pending),
+        });
+        let previous = self
+            .pending
+            .lock()
+            .ok()
+            .and_then(|mut pending| pending.insert(browser.get_identifier(), menu.clone()));
+        if let Some(previous) = previous {
+            previous.finish(None);
+        }
+        menu.poll();
+        1
+    }
+
+    fn on_context_menu_dismissed(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        _frame: Option<&impl ImplFrame>,
+    ) {
+        let Some(browser) = browser else {
+            return;
+        };
+        let menu = self
+            .pending
+            .lock()
+            .ok()
+            .and_then(|mut pending| pending.remove(&browser.get_identifier()));
+        if let Some(menu) = menu {
+            menu.finish(None);
+        }
+    }
+
+    fn get_raw(&self) -> *mut _cef_context_menu_handler_t {
+        self.object as *mut _cef_context_menu_handler_t
+    }
+}
+
+/// A snapshot of a context menu and the element it was opened on, passed to a
+/// [ContextMenuHandlerBuilder::custom] menu. It owns all of its data, so it can be sent to the
+/// thread which renders the menu.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContextMenuData {
+    /// The position of the menu in view coordinates.
+    pub x: i32,
+    pub y: i32,
+    pub link_url: String,
+    pub source_url: String,
+    pub page_url: String,
+    pub frame_url: String,
+    pub selection_text: String,
+    pub misspelled_word: String,
+    pub dictionary_suggestions: Vec<String>,
+    pub media_type: cef_context_menu_media_type_t,
+    pub is_editable: bool,
+    /// The visible items of the menu.
+    pub entries: Vec<MenuEntry>,
+}
+
+impl ContextMenuData {
+    fn new(params: &ContextMenuParams, model: &impl ImplMenuModel) -> Self {
+        let coordinates = params.coordinates();
+        Self {
+            x: coordinates.x,
+            y: coordinates.y,
+            link_url: params.link_url(),
+            source_url: params.source_url(),
+            page_url: params.page_url(),
+            frame_url: params.frame_url(),
+            selection_text: params.selection_text(),
+            misspelled_word: params.misspelled_word(),
+            dictionary_suggestions: params.dictionary_suggestions(),
+            media_type: params.get_media_type().into(),
+            is_editable: params.is_editable(),
+            entries: menu_entries(model),
+        }
+    }
+}
+
+/// An item of a [ContextMenuData] menu.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MenuEntry {
+    /// The command id to resolve a [CustomMenuFuture] with when the item is selected.
+    pub command_id: i32,
+    /// The label, where `&` marks the mnemonic character and `&&` is a literal `&`.
+    pub label: String,
+    pub kind: MenuEntryKind,
+    pub enabled: bool,
+    pub checked: bool,
+}
+
+/// The kind of a [MenuEntry].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MenuEntryKind {
+    Command,
+    Check,
+    /// A radio item, of which one per `group_id` is checked.
+    Radio {
+        group_id: i32,
+    },
+    Separator,
+    SubMenu(Vec<MenuEntry>),
+}
+
+fn menu_entries(model: &impl ImplMenuModel) -> Vec<MenuEntry> {
+    (0..model.get_count())
+        .filter(|index| model.is_visible_at(*index) != 0)
+        .filter_map(|index| {
+            let kind = match model.get_type_at(index) {
+                MenuItemType::Command => MenuEntryKind::Command,
+                MenuItemType::Check => MenuEntryKind::Check,
+                MenuItemType::Radio => MenuEntryKind::Radio {
+                    group_id: model.get_group_id_at(index),
+                },
+                MenuItemType::Separator => MenuEntryKind::Separator,
+                MenuItemType::Submenu => MenuEntryKind::SubMenu(
+                    model
+                        .get_sub_menu_at(index)
+                        .map(|sub_menu| menu_entries(&sub_menu))
+                        .unwrap_or_default(),
+                ),
+                _ => return None,
+            };
+            Some(MenuEntry {
+                command_id: model.get_command_id_at(index),
+                label: model
+                    .get_label_at(index)
+                    .map(|label| label.to_string())
+                    .unwrap_or_default(),
+                kind,
+                enabled: model.is_enabled_at(index) != 0,
+                checked: model.is_checked_at(index) != 0,
+            })
+        })
+        .collect()
+}
+
+/// A running [ContextMenuHandlerBuilder::custom] menu, which polls its future on the UI thread and
+/// continues or cancels the [RunContextMenuCallback] exactly once.
+struct PendingMenu {
+    browser: Browser,
+    state: Mutex<Option<(CustomMenuFuture, RunContextMenuCallback)>>,
+    pending: Weak<Mutex<HashMap<BrowserId, Arc<PendingMenu>>>>,
+}
+
+impl PendingMenu {
+    fn poll(self: &Arc<Self>) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        let Some((future, _)) = state.as_mut() else {
+            return;
+        };
+        let command_id = if !self.browser.is_valid() {
+            None
+        } else {
+            let waker = Waker::from(self.clone());
+            match future.as_mut().poll(&mut Context::from_waker(&waker)) {
+                Poll::Ready(command_id) => command_id,
+                Poll::Pending => return,
+            }
+        };
+        drop(state);
+        self.finish(command_id);
+    }
+
+    /// Run `command_id`, or cancel the menu for `None`, unless it already finished.
+    fn finish(&self, command_id: Option<i32>) {
+        let Some((future, callback)) = self.state.lock().ok().and_then(|mut state| state.take())
+        else {
+            return;
+        };
+        drop(future);
+
+        // Leave a newer menu of the same browser alone.
+        if let Some(pending) = self.pending.upgrade() {
+            let id = self.browser.identifier();
+            if let Ok(mut pending) = pending.lock() {
+                if pending
+                    .get(&id)
+                    .is_some_and(|menu| std::ptr::eq(Arc::as_ptr(menu), self))
+                {
+                    pending.remove(&id);
+                }
+            }
+        }
+
+        // CEF dismisses the menu synchronously, so don't hold any lock here.
+        match command_id {
+            Some(command_id) => callback.cont(command_id, Default::default()),
+            None => callback.cancel(),
+        }
+    }
+}
+
+impl Wake for PendingMenu {
+    fn wake(self: Arc<Self>) {
+        thread::post(cef_thread_id_t::TID_UI, move || self.poll());
+    }
+}
+
+impl Drop for PendingMenu {
+    /// The handler was released while the menu was running.
+    fn drop(&mut self) {
+        if let Some((_, callback)) = self.state.get_mut().ok().and_then(|state| state.take()) {
+            callback.cancel();
+        }
+    }
+}
+
+/// Which color of a menu item [MenuModel::set_color] sets. The hovered colors are used for the
+/// highlighted item, which is also the one selected with the keyboard.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MenuColorType {
+    Text,
+    TextHovered,
+    /// The text of the keyboard shortcut.
+    TextAccelerator,
+    TextAcceleratorHovered,
+    Background,
+    BackgroundHovered,
+}
+
+impl From<MenuColorType> for cef_menu_color_type_t {
+    fn from(value: MenuColorType) -> Self {
+        match value {
+            MenuColorType::Text => Self::CEF_MENU_COLOR_TEXT,
+            MenuColorType::TextHovered => Self::CEF_MENU_COLOR_TEXT_HOVERED,
+            MenuColorType::TextAccelerator => Self::CEF_MENU_COLOR_TEXT_ACCELERATOR,
+            MenuColorType::TextAcceleratorHovered => Self::CEF_MENU_COLOR_TEXT_ACCELERATOR_HOVERED,
+            MenuColorType::Background => Self::CEF_MENU_COLOR_BACKGROUND,
+            MenuColorType::BackgroundHovered => Self::CEF_MENU_COLOR_BACKGROUND_HOVERED,
+        }
+    }
+}
+
+impl MenuModel {
+    /// Set the default font of the items without a font of their own, as a font list such as
+    /// `"Arial, Helvetica, Bold Italic 14px"`. An empty `font` restores the system font. Only
+    /// used by Views and windowless context menus.
+    pub fn set_font_list(&self, font: &str) -> bool {
+        let font = CefString::from(&CefStringUtf8::from(font));
+        // Index -1 sets the default of all items.
+        self.set_font_list_at(-1, Some(&font)) != 0
+    }
+
+    /// Set the `0xAARRGGBB` `color` of the item with `command_id`, or `0` to restore the default
+    /// color. Only used by Views and windowless context menus.
+    pub fn set_color(&self, command_id: c_int, color_type: MenuColorType, color: Color) -> bool {
+        let color_type = crate::MenuColorType::from(cef_menu_color_type_t::from(color_type));
+        ImplMenuModel::set_color(self, command_id, color_type, color) != 0
+    }
+}
+
+impl ContextMenuMediaStateFlags {
+    pub fn is_paused(self) -> bool {
+        self.contains(Self::PAUSED)
+    }
+
+    pub fn is_muted(self) -> bool {
+        self.contains(Self::MUTED)
+    }
+
+    pub fn is_looping(self) -> bool {
+        self.contains(Self::LOOP)
+    }
+
+    pub fn has_audio(self) -> bool {
+        self.contains(Self::HAS_AUDIO)
+    }
+
+    pub fn can_save(self) -> bool {
+        self.contains(Self::CAN_SAVE)
+    }
+
+    pub fn can_print(self) -> bool {
+        self.contains(Self::CAN_PRINT)
+    }
+}
+
+impl ContextMenuEditStateFlags {
+    pub fn can_undo(self) -> bool {
+        self.contains(Self::CAN_UNDO)
+    }
+
+    pub fn can_redo(self) -> bool {
+        self.contains(Self::CAN_REDO)
+    }
+
+    pub fn can_cut(self) -> bool {
+        self.contains(Self::CAN_CUT)
+    }
+
+    pub fn can_copy(self) -> bool {
+        self.contains(Self::CAN_COPY)
+    }
+
+    pub fn can_paste(self) -> bool {
+        self.contains(Self::CAN_PASTE)
+    }
+
+    pub fn can_delete(self) -> bool {
+        self.contains(Self::CAN_DELETE)
+    }
+
+    pub fn can_select_all(self) -> bool {
+        self.contains(Self::CAN_SELECT_ALL)
+    }
+}
+
+fn string(value: Option<CefStringUtf16>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+impl ContextMenuParams {
+    /// The position of the menu in view coordinates.
+    pub fn coordinates(&self) -> Point {
+        Point {
+            x: self.get_xcoord(),
+            y: self.get_ycoord(),
+        }
+    }
+
+    /// The URL of the link the menu was opened on, or an empty string.
+    pub fn link_url(&self) -> String {
+        string(self.get_link_url())
+    }
+
+    /// The link URL before Chromium checked whether the page may navigate to it.
+    pub fn unfiltered_link_url(&self) -> String {
+        string(self.get_unfiltered_link_url())
+    }
+
+    /// The source URL of the image, video or other media the menu was opened on, or an empty
+    /// string.
+    pub fn source_url(&self) -> String {
+        string(self.get_source_url())
+    }
+
+    /// The menu was opened on an image which has loaded.
+    pub fn has_image_contents(&self) -> bool {
+        ImplContextMenuParams::has_image_contents(self) != 0
+    }
+
+    /// The URL of the top-level page.
+    pub fn page_url(&self) -> String {
+        string(self.get_page_url())
+    }
+
+    /// The URL of the frame the menu was opened in.
+    pub fn frame_url(&self) -> String {
+        string(self.get_frame_url())
+    }
+
+    /// The kind of element the menu was opened on.
+    pub fn media_type(&self) -> ContextMenuMediaType {
+        self.get_media_type()
+    }
+
+    /// The state of the media the menu was opened on.
+    pub fn media_state_flags(&self) -> ContextMenuMediaStateFlags {
+        self.get_media_state_flags()
+    }
+
+    /// The selected text under the menu, or an empty string.
+    pub fn selection_text(&self) -> String {
+        string(self.get_selection_text())
+    }
+
+    /// The menu was opened on an editable field.
+    pub fn is_editable(&self) -> bool {
+        ImplContextMenuParams::is_editable(self) != 0
+    }
+
+    /// The edit commands which apply to the element under the menu.
+    pub fn edit_state_flags(&self) -> ContextMenuEditStateFlags {
+        self.get_edit_state_flags()
+    }
+
+    /// The menu was opened on selected text, so "Copy" applies.
+    pub fn can_copy(&self) -> bool {
+        self.has_type_flag(cef_context_menu_type_flags_t::CM_TYPEFLAG_SELECTION)
+            && self.has_edit_flag(ContextMenuEditStateFlags::CAN_COPY)
+    }
+
+    /// The menu was opened on an editable field, and the clipboard holds something it accepts.
+    pub fn can_paste(&self) -> bool {
+        self.is_editable() && self.has_edit_flag(ContextMenuEditStateFlags::CAN_PASTE)
+    }
+
+    /// The menu was opened on an editable field which is spell checked.
+    pub fn can_spell_check(&self) -> bool {
+        self.is_editable() && self.is_spell_check_enabled() != 0
+    }
+
+    /// The selection under the menu is inside an editable field, so "Cut" and "Delete" may
+    /// apply.
+    pub fn is_selection_in_editable_area(&self) -> bool {
+        self.has_type_flag(cef_context_menu_type_flags_t::CM_TYPEFLAG_SELECTION)
+            && self.has_type_flag(cef_context_menu_type_flags_t::CM_TYPEFLAG_EDITABLE)
+    }
+
+    /// The menu was opened on selected text of a password field.
+    ///
+    /// CEF doesn't report the input type, so this is inferred from Chromium never allowing to
+    /// copy, cut or spell check a password. Without a selection, a password field can't be told
+    /// apart from other unchecked fields, and this returns `false`.
+    pub fn is_password_field(&self) -> bool {
+        self.is_selection_in_editable_area()
+            && self.is_spell_check_enabled() == 0
+            && !self.has_edit_flag(ContextMenuEditStateFlags::CAN_COPY)
+            && !self.has_edit_flag(ContextMenuEditStateFlags::CAN_CUT)
+    }
+
+    fn has_type_flag(&self, flag: cef_context_menu_type_flags_t) -> bool {
+        self.get_type_flags().intersects(flag.into())
+    }
+
+    fn has_edit_flag(&self, flag: ContextMenuEditStateFlags) -> bool {
+        self.edit_state_flags().contains(flag)
+    }
+}
+
+fn insert_spellcheck_items(params: &ContextMenuParams, model: &impl ImplMenuModel) {
+    if params.misspelled_word().is_empty()
+        || model.get_index_of(cef_menu_id_t::MENU_ID_ADD_TO_DICTIONARY as c_int) >= 0
+    {
+        return;
+    }
+
+    let items = spellcheck_items(&params.dictionary_suggestions());
+    let separator = model.get_count() > 0;
+    for (index, (command_id, label, enabled)) in items.iter().enumerate() {
+        let label = CefString::from(&CefStringUtf8::from(label.as_str()));
+        model.insert_item_at(index, *command_id, Some(&label));
+        if !enabled {
+            model.set_enabled(*command_id, 0);
+        }
+    }
+    if separator {
+        model.insert_separator_at(items.len());
+    }
+}
+
+/// The `(command_id, label, enabled)` items offered for a misspelled word.
+fn spellcheck_items(suggestions: &[String]) -> Vec<(c_int, String, bool)> {
+    let first = cef_menu_id_t::MENU_ID_SPELLCHECK_SUGGESTION_0 as c_int;
+    let last = cef_menu_id_t::MENU_ID_SPELLCHECK_SUGGESTION_4 as c_int;
+
+    let mut items: Vec<_> = (first..=last)
+        .zip(suggestions)
+        .map(|(command_id, suggestion)| (command_id, suggestion.clone(), true))
+        .collect();
+    if items.is_empty() {
+        items.push((
+            cef_menu_id_t::MENU_ID_NO_SPELLING_SUGGESTIONS as c_int,
+            NO_SPELLING_SUGGESTIONS_LABEL.to_string(),
+            false,
+        ));
+    }
+    items.push((
+        cef_menu_id_t::MENU_ID_ADD_TO_DICTIONARY as c_int,
+        ADD_TO_DICTIONARY_LABEL.to_string(),
+        true,
+    ));
+    items
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    };
+
+    use cef_sys::{
+        _cef_context_menu_params_t, _cef_menu_model_t, _cef_run_context_menu_callback_t,
+        cef_base_ref_counted_t, cef_menu_item_type_t,
+    };
+
+    use super::*;
+    use crate::fixtures::FakeBrowser;
+
+    /// Stands in for the interfaces passed by CEF, without any reference counting.
+    #[derive(Clone)]
+    struct Mock {
+        base: Arc<cef_base_ref_counted_t>,
+        media_type: cef_context_menu_media_type_t,
+        cleared: Arc<AtomicUsize>,
+    }
+
+    impl Mock {
+        fn new(media_type: cef_context_menu_media_type_t) -> Self {
+            Self {
+                base: Arc::new(unsafe { std::mem::zeroed() }),
+                media_type,
+                cleared: Default::default(),
+            }
+        }
+    }
+
+    impl crate::rc::Rc for Mock {
+        fn as_base(&self) -> &cef_base_ref_counted_t {
+            &self.base
+        }
+    }
+
+    impl ImplContextMenuParams for Mock {
+        fn get_media_type(&self) -> ContextMenuMediaType {
+            self.media_type.into()
+        }
+
+        fn get_raw(&self) -> *mut _cef_context_menu_params_t {
+            std::ptr::null_mut()
+        }
+    }
+
+    impl ImplMenuModel for Mock {
+        fn clear(&self) -> c_int {
+            self.cleared.fetch_add(1, Ordering::Relaxed);
+            1
+        }
+
+        fn get_raw(&self) -> *mut _cef_menu_model_t {
+            std::ptr::null_mut()
+        }
+    }
+
+    fn show_menu(
+        builder: ContextMenuHandlerBuilder,
+        media_type: cef_context_menu_media_type_t,
+    ) -> bool {
+        let handler = BuiltContextMenuHandler {
+            object: std::ptr::null_mut(),
+            builder,
+            pending: Default::default(),
+        };
+        let params = Mock::new(media_type);
+        let model = Mock::new(media_type);
+        handler.on_before_context_menu(
+            Option::<&Browser>::None,
+            Option::<&Frame>::None,
+            Some(&params),
+            Some(&model),
+        );
+        model.cleared.load(Ordering::Relaxed) > 0
+    }
+
+    #[test]
+    fn test_suppress_all_menus() {
+        let builder = ContextMenuHandlerBuilder::new().suppress_all_menus();
+        assert!(show_menu(
+            builder.clone(),
+            cef_context_menu_media_type_t::CM_MEDIATYPE_NONE
+        ));
+        assert!(show_menu(
+            builder,
+            cef_context_menu_media_type_t::CM_MEDIATYPE_IMAGE
+        ));
+    }
+
+    #[test]
+    fn test_suppress_for_media_only() {
+        let builder = ContextMenuHandlerBuilder::new().suppress_for_media_only();
+        assert!(!show_menu(
+            builder.clone(),
+            cef_context_menu_media_type_t::CM_MEDIATYPE_NONE
+        ));
+        assert!(show_menu(
+            builder,
+            cef_context_menu_media_type_t::CM_MEDIATYPE_IMAGE
+        ));
+    }
+
+    /// The command IDs and colors of the items of a [ColoredMenu].
+    type ColoredItems = Arc<std::sync::Mutex<Vec<(c_int, Option<u32>)>>>;
+
+    /// A menu which only keeps its items and their colors.
+    struct ColoredMenu {
+        object: *mut RcImpl<_cef_menu_model_t, Self>,
+        items: ColoredItems,
+    }
+
+    impl ImplMenuModel for ColoredMenu {
+        fn get_count(&self) -> usize {
+            self.items.lock().unwrap().len()
+        }
+
+        fn add_item(&self, command_id: c_int, _label: Option<&CefStringUtf16>) -> c_int {
+            self.items.lock().unwrap().push((command_id, None));
+            1
+        }
+
+        fn set_color(
+            &self,
+            command_id: c_int,
+            color_type: crate::MenuColorType,
+            color: u32,
+        ) -> c_int {
+            assert_eq!(color_type, crate::MenuColorType::BackgroundHovered);
+            let mut items = self.items.lock().unwrap();
+            let Some(item) = items.iter_mut().find(|(id, _)| *id == command_id) else {
+                return 0;
+            };
+            item.1 = Some(color);
+            1
+        }
+
+        fn get_raw(&self) -> *mut _cef_menu_model_t {
+            self.object as *mut _
+        }
+    }
+
+    wrap_rc!(ColoredMenu, WrapMenuModel, _cef_menu_model_t, items);
+
+    #[test]
+    fn test_set_color() {
+        let items = Arc::default();
+        let model = MenuModel::new(ColoredMenu {
+            object: std::ptr::null_mut(),
+            items: Arc::clone(&items),
+        });
+        let label = CefString::from(&CefStringUtf8::from("Open"));
+        model.add_item(1, Some(&label));
+        assert!(model.set_color(1, MenuColorType::BackgroundHovered, 0xFF33_6699));
+        assert!(!model.set_color(2, MenuColorType::BackgroundHovered, 0xFF33_6699));
+        assert_eq!(model.get_count(), 1);
+        assert_eq!(*items.lock().unwrap(), [(1, Some(0xFF33_6699))]);
+    }
+
+    /// Params with the flags CEF reports for the element under the menu.
+    struct FlagParams {
+        object: *mut RcImpl<_cef_context_menu_params_t, Self>,
+        type_flags: u32,
+        edit_flags: u32,
+        spell_check: bool,
+    }
+
+    impl ImplContextMenuParams for FlagParams {
+        fn get_type_flags(&self) -> ContextMenuTypeFlags {
+            ContextMenuTypeFlags::from_bits(self.type_flags)
+        }
+
+        fn is_editable(&self) -> c_int {
+            (self.type_flags & ContextMenuTypeFlags::EDITABLE.bits() != 0) as c_int
+        }
+
+        fn is_spell_check_enabled(&self) -> c_int {
+            self.spell_check as c_int
+        }
+
+        fn get_edit_state_flags(&self) -> ContextMenuEditStateFlags {
+            ContextMenuEditStateFlags::from_bits(self.edit_flags)
+        }
+
+        fn get_raw(&self) -> *mut _cef_context_menu_params_t {
+            self.object as *mut _
+        }
+    }
+
+    wrap_rc!(
+        FlagParams,
+        WrapContextMenuParams,
+        _cef_context_menu_params_t,
+        type_flags,
+        edit_flags,
+        spell_check
+    );
+
+    fn params(type_flags: u32, edit_flags: u32, spell_check: bool) -> ContextMenuParams {
+        ContextMenuParams::new(FlagParams {
+            object: std::ptr::null_mut(),
+            type_flags,
+            edit_flags,
+            spell_check,
+        })
+    }
+
+    #[test]
+    fn test_computed_flags() {
+        let page = ContextMenuTypeFlags::PAGE.bits();
+        let selection = page | ContextMenuTypeFlags::SELECTION.bits();
+        let editable = page | ContextMenuTypeFlags::EDITABLE.bits();
+        let copy = ContextMenuEditStateFlags::CAN_COPY.bits();
+        let editing = copy
+            | ContextMenuEditStateFlags::CAN_CUT.bits()
+            | ContextMenuEditStateFlags::CAN_PASTE.bits()
+            | ContextMenuEditStateFlags::CAN_SELECT_ALL.bits();
+
+        let text = params(selection, copy, false);
+        assert!(text.can_copy());
+        assert!(!text.can_paste());
+        assert!(!text.can_spell_check());
+        assert!(!text.is_selection_in_editable_area());
+        assert!(!text.is_password_field());
+
+        let field = params(editable | selection, editing, true);
+        assert!(field.can_copy());
+        assert!(field.can_paste());
+        assert!(field.can_spell_check());
+        assert!(field.is_selection_in_editable_area());
+        assert!(!field.is_password_field());
+
+        let empty_field = params(
+            editable,
+            ContextMenuEditStateFlags::CAN_SELECT_ALL.bits(),
+            true,
+        );
+        assert!(!empty_field.can_copy());
+        assert!(!empty_field.can_paste());
+        assert!(empty_field.can_spell_check());
+
+        let password = params(
+            editable | selection,
+            ContextMenuEditStateFlags::CAN_PASTE.bits()
+                | ContextMenuEditStateFlags::CAN_DELETE.bits(),
+            false,
+        );
+        assert!(!password.can_copy());
+        assert!(password.can_paste());
+        assert!(!password.can_spell_check());
+        assert!(password.is_password_field());
+    }
+
+    #[test]
+    fn test_state_flag_mappings() {
+        use ContextMenuEditStateFlags as Edit;
+        assert!(Edit::CAN_UNDO.can_undo());
+        assert!(Edit::CAN_REDO.can_redo());
+        assert!(Edit::CAN_CUT.can_cut());
+        assert!(Edit::CAN_COPY.can_copy());
+        assert!(Edit::CAN_PASTE.can_paste());
+        assert!(Edit::CAN_DELETE.can_delete());
+        assert!(Edit::CAN_SELECT_ALL.can_select_all());
+        let copy_paste = Edit::CAN_COPY | Edit::CAN_PASTE;
+        assert!(copy_paste.can_copy() && copy_paste.can_paste());
+        assert!(!copy_paste.can_cut() && !copy_paste.can_undo());
+        assert_eq!(Edit::NONE, Edit::default());
+
+        use ContextMenuMediaStateFlags as Media;
+        assert!(Media::PAUSED.is_paused());
+        assert!(Media::MUTED.is_muted());
+        assert!(Media::LOOP.is_looping());
+        assert!(Media::HAS_AUDIO.has_audio());
+        assert!(Media::CAN_SAVE.can_save());
+        assert!(Media::CAN_PRINT.can_print());
+        assert!(!Media::CAN_LOOP.is_looping());
+        let muted_loop = Media::MUTED | Media::LOOP;
+        assert!(muted_loop.is_muted() && muted_loop.is_looping() && !muted_loop.is_paused());
+    }
+
+    #[test]
+    fn test_conditional_sections() {
+        let builder = ContextMenuHandlerBuilder::new()
+            .when_link(|_, menu| {
+                menu.add_item(1, None);
+            })
+            .when_editable(|params, menu| {
+                if params.edit_state_flags().can_paste() {
+                    menu.add_item(2, None);
+                }
+            })
+            .when_selection(|_, menu| {
+                menu.add_item(3, None);
+            });
+        assert!(builder.has_overrides());
+        let handler = BuiltContextMenuHandler {
+            object: std::ptr::null_mut(),
+            builder,
+            pending: Default::default(),
+        };
+        let show = |type_flags: u32, edit_flags: u32| {
+            let items = Arc::default();
+            let model = MenuModel::new(ColoredMenu {
+                object: std::ptr::null_mut(),
+                items: Arc::clone(&items),
+            });
+            handler.on_before_context_menu(
+                Option::<&Browser>::None,
+                Option::<&Frame>::None,
+                Some(&params(type_flags, edit_flags, false)),
+                Some(&model),
+            );
+            let ids: Vec<_> = items.lock().unwrap().iter().map(|(id, _)| *id).collect();
+            ids
+        };
+        let page = ContextMenuTypeFlags::PAGE.bits();
+        let paste = ContextMenuEditStateFlags::CAN_PASTE.bits();
+        assert_eq!(show(page, 0), Vec::<c_int>::new());
+        assert_eq!(show(page | ContextMenuTypeFlags::LINK.bits(), 0), [1]);
+        assert_eq!(
+            show(page | ContextMenuTypeFlags::EDITABLE.bits(), 0),
+            Vec::<c_int>::new()
+        );
+        assert_eq!(
+            show(
+                page | ContextMenuTypeFlags::EDITABLE.bits()
+                    | ContextMenuTypeFlags::SELECTION.bits(),
+                paste
+            ),
+            [2, 3]
+        );
+    }
+
+    /// An item of a [SnapshotMenu].
+    #[derive(Clone)]
+    struct Item {
+        command_id: c_int,
+        label: &'static str,
+        item_type: cef_menu_item_type_t,
+        visible: bool,
+        sub_menu: Vec<Item>,
+    }
+
+    fn item(command_id: c_int, label: &'static str, item_type: cef_menu_item_type_t) -> Item {
+        Item {
+            command_id,
+            label,
+            item_type,
+            visible: true,
+            sub_menu: vec![],
+        }
+    }
+
+    /// A menu which only reports its items, where odd command ids are disabled and checked.
+    struct SnapshotMenu {
+        object: *mut RcImpl<_cef_menu_model_t, Self>,
+        items: Arc<Vec<Item>>,
+    }
+
+    impl SnapshotMenu {
+        fn item(&self, index: usize) -> &Item {
+            &self.items[index]
+        }
+    }
+
+    impl ImplMenuModel for SnapshotMenu {
+        fn get_count(&self) -> usize {
+            self.items.len()
+        }
+
+        fn get_command_id_at(&self, index: usize) -> c_int {
+            self.item(index).command_id
+        }
+
+        fn get_label_at(&self, index: usize) -> Option<CefStringUtf16> {
+            Some(CefString::from(&CefStringUtf8::from(
+                self.item(index).label,
+            )))
+        }
+
+        fn get_type_at(&self, index: usize) -> crate::MenuItemType {
+            self.item(index).item_type.into()
+        }
+
+        fn get_group_id_at(&self, index: usize) -> c_int {
+            self.item(index).command_id / 10
+        }
+
+        fn get_sub_menu_at(&self, index: usize) -> Option<MenuModel> {
+            let item = self.item(index);
+            (item.item_type == cef_menu_item_type_t::MENUITEMTYPE_SUBMENU)
+                .then(|| menu(item.sub_menu.clone()))
+        }
+
+        fn is_visible_at(&self, index: usize) -> c_int {
+            self.item(index).visible as c_int
+        }
+
+        fn is_enabled_at(&self, index: usize) -> c_int {
+            (self.item(index).command_id % 2 == 0) as c_int
+        }
+
+        fn is_checked_at(&self, index: usize) -> c_int {
+            (self.item(index).command_id % 2 != 0) as c_int
+        }
+
+        fn get_raw(&self) -> *mut _cef_menu_model_t {
+            self.object as *mut _
+        }
+    }
+
+    wrap_rc!(SnapshotMenu, WrapMenuModel, _cef_menu_model_t, items);
+
+    fn menu(items: Vec<Item>) -> MenuModel {
+        MenuModel::new(SnapshotMenu {
+            object: std::ptr::null_mut(),
+            items: Arc::new(items),
+        })
+    }
+
+    #[test]
+    fn test_menu_entries() {
+        use cef_menu_item_type_t::*;
+
+        let model = menu(vec![
+            item(2, "&Back", MENUITEMTYPE_COMMAND),
+            item(0, "", MENUITEMTYPE_SEPARATOR),
+            Item {
+                visible: false,
+                ..item(4, "Hidden", MENUITEMTYPE_COMMAND)
+            },
+            item(11, "Spell check", MENUITEMTYPE_CHECK),
+            Item {
+                sub_menu: vec![item(21, "Left", MENUITEMTYPE_RADIO)],
+                ..item(6, "Direction", MENUITEMTYPE_SUBMENU)
+            },
+        ]);
+        let entry = |command_id, label: &str, kind, enabled| MenuEntry {
+            command_id,
+            label: label.to_string(),
+            kind,
+            enabled,
+            checked: !enabled,
+        };
+        assert_eq!(
+            menu_entries(&model),
+            vec![
+                entry(2, "&Back", MenuEntryKind::Command, true),
+                entry(0, "", MenuEntryKind::Separator, true),
+                entry(11, "Spell check", MenuEntryKind::Check, false),
+                entry(
+                    6,
+                    "Direction",
+                    MenuEntryKind::SubMenu(vec![entry(
+                        21,
+                        "Left",
+                        MenuEntryKind::Radio { group_id: 2 },
+                        false
+                    )]),
+                    true
+                ),
+            ]
+        );
+    }
+
+    /// Records how a menu was finished.
+    struct RecordingCallback {
+        object: *mut RcImpl<_cef_run_context_menu_callback_t, Self>,
+        commands: Arc<Mutex<Vec<c_int>>>,
+        cancelled: Arc<AtomicUsize>,
+    }
+
+    impl ImplRunContextMenuCallback for RecordingCallback {
+        fn cont(&self, command_id: c_int, _event_flags: EventFlags) {
+            self.commands.lock().unwrap().push(command_id);
+        }
+
+        fn cancel(&self) {
+            self.cancelled.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn get_raw(&self) -> *mut _cef_run_context_menu_callback_t {
+            self.object as *mut _
+        }
+    }
+
+    wrap_rc!(
+        RecordingCallback,
+        WrapRunContextMenuCallback,
+        _cef_run_context_menu_callback_t,
+        commands,
+        cancelled
+    );
+
+    /// Runs menus of one browser with a [RecordingCallback].
+    struct MenuRun {
+        handler: BuiltContextMenuHandler,
+        browser: Browser,
+        valid: Arc<AtomicBool>,
+        commands: Arc<Mutex<Vec<c_int>>>,
+        cancelled: Arc<AtomicUsize>,
+    }
+
+    impl MenuRun {
+        fn new(builder: ContextMenuHandlerBuilder) -> Self {
+            let valid = Arc::new(AtomicBool::new(true));
+            Self {
+                handler: BuiltContextMenuHandler {
+                    object: std::ptr::null_mut(),
+                    builder,
+                    pending: Default::default(),
+                },
+                browser: FakeBrowser::new(1)
+                    .valid({
+                        let valid = valid.clone();
+                        move || valid.load(Ordering::Relaxed)
+                    })
+                    .build(),
+                valid,
+                commands: Default::default(),
+                cancelled: Default::default(),
+            }
+        }
+
+        fn run(&self) -> c_int {
+            let callback = RunContextMenuCallback::new(RecordingCallback {
+                object: std::ptr::null_mut(),
+                commands: self.commands.clone(),
+                cancelled: self.cancelled.clone(),
+            });
+            self.handler.run_context_menu(
+                Some(&self.browser),
+                Option::<&Frame>::None,
+                Some(&params(0, 0, false)),
+                Some(&menu(vec![item(
+                    2,
+                    "Back",
+                    cef_menu_item_type_t::MENUITEMTYPE_COMMAND,
+                )])),
+                Some(&callback),
+            )
+        }
+
+        fn dismiss(&self) {
+            self.handler
+                .on_context_menu_dismissed(Some(&self.browser), Option::<&Frame>::None);
+        }
+
+        fn cancelled(&self) -> usize {
+            self.cancelled.load(Ordering::Relaxed)
+        }
+
+        fn pending(&self) -> usize {
+            self.handler.pending.lock().unwrap().len()
+        }
+    }
+
+    fn never(_: ContextMenuData) -> CustomMenuFuture {
+        Box::pin(std::future::pending())
+    }
+
+    #[test]
+    fn test_suppress_all_menus_cancels_run() {
+        let run = MenuRun::new(ContextMenuHandlerBuilder::new().suppress_all_menus());
+        assert_eq!(run.run(), 1);
+        assert_eq!(run.cancelled(), 1);
+
+        let run = MenuRun::new(ContextMenuHandlerBuilder::new().suppress_for_media_only());
+        assert_eq!(run.run(), 0);
+        assert_eq!(run.cancelled(), 0);
+    }
+
+    #[test]
+    fn test_custom_menu_command() {
+        let data = Arc::new(Mutex::new(None));
+        let run = MenuRun::new(ContextMenuHandlerBuilder::new().custom({
+            let data = data.clone();
+            move |menu_data| {
+                *data.lock().unwrap() = Some(menu_data);
+                Box::pin(std::future::ready(Some(2)))
+            }
+        }));
+        assert_eq!(run.run(), 1);
+        assert_eq!(*run.commands.lock().unwrap(), [2]);
+        assert_eq!(run.cancelled(), 0);
+        assert_eq!(run.pending(), 0);
+
+        let data = data.lock().unwrap().take().unwrap();
+        assert_eq!(data.entries.len(), 1);
+        assert_eq!(data.entries[0].label, "Back");
+    }
+
+    #[test]
+    fn test_custom_menu_dismissed() {
+        let run = MenuRun::new(ContextMenuHandlerBuilder::new().custom(never));
+        assert_eq!(run.run(), 1);
+        assert_eq!(run.pending(), 1);
+        assert_eq!(run.cancelled(), 0);
+
+        run.dismiss();
+        run.dismiss();
+        assert_eq!(run.cancelled(), 1);
+        assert_eq!(run.pending(), 0);
+        assert!(run.commands.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_custom_menu_browser_destroyed() {
+        let run = MenuRun::new(ContextMenuHandlerBuilder::new().custom(never));
+        assert_eq!(run.run(), 1);
+        run.valid.store(false, Ordering::Relaxed);
+        let menu = run.handler.pending.lock().unwrap()[&1].clone();
+        menu.poll();
+        assert_eq!(run.cancelled(), 1);
+        assert_eq!(run.pending(), 0);
+
+        run.dismiss();
+        drop(menu);
+        assert_eq!(run.cancelled(), 1);
+    }
+
+    #[test]
+    fn test_custom_menu_handler_released() {
+        let run = MenuRun::new(ContextMenuHandlerBuilder::new().custom(never));
+        assert_eq!(run.run(), 1);
+        let MenuRun {
+            handler, cancelled, ..
+        } = run;
+        drop(handler);
+        assert_eq!(cancelled.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_spellcheck_items() {
+        let suggestions: Vec<_> = ["one", "two", "three", "four", "five", "six"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let items = spellcheck_items(&suggestions);
+        assert_eq!(items.len(), 6);
+        assert_eq!(items[0], (200, "one".to_string(), true));
+        assert_eq!(items[4], (204, "five".to_string(), true));
+        assert_eq!(items[5], (206, ADD_TO_DICTIONARY_LABEL.to_string(), true));
+    }
+
+    #[test]
+    fn test_spellcheck_items_without_suggestions() {
+        let items = spellcheck_items(&[]);
+        assert_eq!(
+            items,
+            vec![
+                (205, NO_SPELLING_SUGGESTIONS_LABEL.to_string(), false),
+                (206, ADD_TO_DICTIONARY_LABEL.to_string(), true),
+            ]
+        );
+    }
+}
@@ -0,0 +1,226 @@
+//! CSS module
+//!
+//! [MediaQueryMonitor] notifies Rust code when the media queries of the page start or stop
+//! matching, e.g. after the browser was resized, using the DevTools `CSS` domain.
+
+use std::{
+    cell::{Cell, RefCell},
+    os::raw::c_int,
+    rc::Rc,
+};
+
+use cef_sys::_cef_dev_tools_message_observer_t;
+
+use crate::{
+    devtools::{json, next_message_id, DevToolsError},
+    rc::{wrap_rc, RcImpl},
+    BrowserHost, CefString, CefStringUtf16, CefStringUtf8, DevToolsMessageObserver,
+    DictionaryValue, ImplBrowser, ImplBrowserHost, ImplDevToolsMessageObserver, Registration,
+};
+
+const RESULT_CHANGED: &str = "CSS.mediaQueryResultChanged";
+
+/// A media query of the page, and whether it currently matches.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MediaQueryResult {
+    /// The media query text, e.g. `(max-width: 600px)`.
+    pub media: String,
+    pub active: bool,
+}
+
+type ChangeCallback = Box<dyn Fn(Vec<MediaQueryResult>)>;
+
+/// Calls back with the media queries of the page each time the result of one changes.
+///
+/// DevTools only reports changes of viewport-dependent media features. Create the monitor on
+/// the UI thread after the first navigation of the browser, and keep it as long as you want to be
+/// notified.
+///
+/// ```ignore
+/// let monitor = MediaQueryMonitor::new(&host);
+/// monitor.on_change(Box::new(|results| {
+///     let narrow = results.iter().any(|result| result.active);
+/// }));
+/// ```
+pub struct MediaQueryMonitor {
+    state: Rc<MonitorState>,
+    _registration: Option<Registration>,
+}
+
+impl MediaQueryMonitor {
+    pub fn new(host: &BrowserHost) -> Self {
+        let state = Rc::new(MonitorState::default());
+        let observer = DevToolsMessageObserver::new(MediaQueryObserver {
+            object: std::ptr::null_mut(),
+            host: host.clone(),
+            state: state.clone(),
+        });
+        let registration = host.add_dev_tools_message_observer(Some(&observer));
+        // The CSS domain needs the DOM domain, and only reports changes while it is enabled.
+        drop(host.execute_dev_tools("DOM.enable", None));
+        drop(host.execute_dev_tools("CSS.enable", None));
+        Self {
+            state,
+            _registration: registration,
+        }
+    }
+
+    /// Call `callback` with every media query of the page when the result of one changes.
+    pub fn on_change(&self, callback: ChangeCallback) {
+        self.state.callbacks.borrow_mut().push(callback);
+    }
+}
+
+/// The callbacks of a [MediaQueryMonitor], and its pending `CSS.getMediaQueries` method.
+#[derive(Default)]
+struct MonitorState {
+    callbacks: RefCell<Vec<ChangeCallback>>,
+    pending: Cell<Option<c_int>>,
+}
+
+impl MonitorState {
+    /// `true` if `method` is an event which needs the media queries again, and none are
+    /// pending yet.
+    fn needs_refresh(&self, method: &str) -> bool {
+        method == RESULT_CHANGED && self.pending.get().is_none()
+    }
+
+    /// Handle the result of a method, and return `true` if it was our pending one.
+    fn on_result(&self, message_id: c_int, result: &[u8]) -> bool {
+        if self.pending.get() != Some(message_id) {
+            return false;
+        }
+        self.pending.set(None);
+        if let Ok(results) = media_query_results(result) {
+            for callback in self.callbacks.borrow().iter() {
+                callback(results.clone());
+            }
+        }
+        true
+    }
+}
+
+struct MediaQueryObserver {
+    object: *mut RcImpl<_cef_dev_tools_message_observer_t, Self>,
+    host: BrowserHost,
+    state: Rc<MonitorState>,
+}
+
+wrap_rc!(
+    MediaQueryObserver,
+    WrapDevToolsMessageObserver,
+    _cef_dev_tools_message_observer_t,
+    host,
+    state
+);
+
+impl ImplDevToolsMessageObserver for MediaQueryObserver {
+    fn on_dev_tools_method_result(
+        &self,
+        _browser: Option<&impl ImplBrowser>,
+        message_id: c_int,
+        success: c_int,
+        result: Option<&[u8]>,
+    ) {
+        let result = if success != 0 {
+            result.unwrap_or_default()
+        } else {
+            Default::default()
+        };
+        self.state.on_result(message_id, result);
+    }
+
+    fn on_dev_tools_event(
+        &self,
+        _browser: Option<&impl ImplBrowser>,
+        method: Option<&CefStringUtf16>,
+        _params: Option<&[u8]>,
+    ) {
+        let method = method.map(|method| method.to_string()).unwrap_or_default();
+        if !self.state.needs_refresh(&method) {
+            return;
+        }
+        // The event has no parameters, so ask for the media queries. The result arrives in
+        // `on_dev_tools_method_result` of this observer.
+        let message_id = next_message_id();
+        let method = CefString::from(&CefStringUtf8::from("CSS.getMediaQueries"));
+        if ImplBrowserHost::execute_dev_tools_method(
+            &self.host,
+            message_id,
+            Some(&method),
+            Option::<&DictionaryValue>::None,
+        ) != 0
+        {
+            self.state.pending.set(Some(message_id));
+        }
+    }
+
+    fn get_raw(&self) -> *mut _cef_dev_tools_message_observer_t {
+        self.object as *mut _cef_dev_tools_message_observer_t
+    }
+}
+
+/// The media queries of a `CSS.getMediaQueries` result. A query is active if any of its media
+/// lists matches.
+fn media_query_results(result: &[u8]) -> Result<Vec<MediaQueryResult>, DevToolsError> {
+    let result = json::parse(result)?;
+    json::elements(&result, &["medias"])?
+        .iter()
+        .map(|media| {
+            let active = match json::value(media, &["mediaList"]) {
+                Ok(media_list) => json::elements(&media_list, &[])?
+                    .iter()
+                    .any(|query| json::boolean(query, &["active"]).unwrap_or_default()),
+                Err(_) => false,
+            };
+            Ok(MediaQueryResult {
+                media: json::string(media, &["text"])?,
+                active,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_media_query_result_changed() {
+        let state = MonitorState::default();
+        let received = Rc::new(RefCell::new(vec![]));
+        let results = received.clone();
+        state
+            .callbacks
+            .borrow_mut()
+            .push(Box::new(move |changed| results.borrow_mut().push(changed)));
+
+        assert!(!state.needs_refresh("CSS.styleSheetAdded"));
+        assert!(state.needs_refresh(RESULT_CHANGED));
+        state.pending.set(Some(42));
+        assert!(!state.needs_refresh(RESULT_CHANGED));
+
+        let result = br#"{"medias": [
+            {"text": "(max-width: 600px)", "source": "mediaRule",
+                "mediaList": [{"expressions": [], "active": true}]},
+            {"text": "print", "source": "linkedSheet",
+                "mediaList": [{"expressions": [], "active": false}]}]}"#;
+        assert!(!state.on_result(41, result));
+        assert!(received.borrow().is_empty());
+        assert!(state.on_result(42, result));
+        assert_eq!(
+            *received.borrow(),
+            [vec![
+                MediaQueryResult {
+                    media: "(max-width: 600px)".to_string(),
+                    active: true,
+                },
+                MediaQueryResult {
+                    media: "print".to_string(),
+                    active: false,
+                },
+            ]]
+        );
+        assert!(state.needs_refresh(RESULT_CHANGED));
+    }
+}
@@ -0,0 +1,182 @@
+//! Cursor module
+//!
+//! Typed arguments of [ImplDisplayHandler::on_cursor_change](crate::ImplDisplayHandler), which
+//! off-screen rendering hosts use to draw the cursor themselves.
+
+use std::slice;
+
+use cef_sys::cef_cursor_type_t;
+
+use crate::{diagnostics, Point, Size};
+
+macro_rules! cursor_types {
+    ($($(#[$attr:meta])* $name:ident = $raw:ident,)*) => {
+        /// The cursor to show, see [cef_cursor_type_t].
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        pub enum CursorType {
+            $($(#[$attr])* $name,)*
+        }
+
+        impl From<cef_cursor_type_t> for CursorType {
+            fn from(value: cef_cursor_type_t) -> Self {
+                match value {
+                    $(cef_cursor_type_t::$raw => Self::$name,)*
+                    // Cursors added by a newer CEF fall back to the default arrow.
+                    _ => diagnostics::unknown_enum_value(value, Self::Pointer),
+                }
+            }
+        }
+
+        impl From<CursorType> for cef_cursor_type_t {
+            fn from(value: CursorType) -> Self {
+                match value {
+                    $(CursorType::$name => Self::$raw,)*
+                }
+            }
+        }
+    };
+}
+
+cursor_types! {
+    Pointer = CT_POINTER,
+    Cross = CT_CROSS,
+    Hand = CT_HAND,
+    IBeam = CT_IBEAM,
+    Wait = CT_WAIT,
+    Help = CT_HELP,
+    EastResize = CT_EASTRESIZE,
+    NorthResize = CT_NORTHRESIZE,
+    NorthEastResize = CT_NORTHEASTRESIZE,
+    NorthWestResize = CT_NORTHWESTRESIZE,
+    SouthResize = CT_SOUTHRESIZE,
+    SouthEastResize = CT_SOUTHEASTRESIZE,
+    SouthWestResize = CT_SOUTHWESTRESIZE,
+    WestResize = CT_WESTRESIZE,
+    NorthSouthResize = CT_NORTHSOUTHRESIZE,
+    EastWestResize = CT_EASTWESTRESIZE,
+    NorthEastSouthWestResize = CT_NORTHEASTSOUTHWESTRESIZE,
+    NorthWestSouthEastResize = CT_NORTHWESTSOUTHEASTRESIZE,
+    ColumnResize = CT_COLUMNRESIZE,
+    RowResize = CT_ROWRESIZE,
+    MiddlePanning = CT_MIDDLEPANNING,
+    EastPanning = CT_EASTPANNING,
+    NorthPanning = CT_NORTHPANNING,
+    NorthEastPanning = CT_NORTHEASTPANNING,
+    NorthWestPanning = CT_NORTHWESTPANNING,
+    SouthPanning = CT_SOUTHPANNING,
+    SouthEastPanning = CT_SOUTHEASTPANNING,
+    SouthWestPanning = CT_SOUTHWESTPANNING,
+    WestPanning = CT_WESTPANNING,
+    Move = CT_MOVE,
+    VerticalText = CT_VERTICALTEXT,
+    Cell = CT_CELL,
+    ContextMenu = CT_CONTEXTMENU,
+    Alias = CT_ALIAS,
+    Progress = CT_PROGRESS,
+    NoDrop = CT_NODROP,
+    Copy = CT_COPY,
+    None = CT_NONE,
+    NotAllowed = CT_NOTALLOWED,
+    ZoomIn = CT_ZOOMIN,
+    ZoomOut = CT_ZOOMOUT,
+    Grab = CT_GRAB,
+    Grabbing = CT_GRABBING,
+    MiddlePanningVertical = CT_MIDDLE_PANNING_VERTICAL,
+    MiddlePanningHorizontal = CT_MIDDLE_PANNING_HORIZONTAL,
+    /// An image cursor, described by the [CursorInfo] passed along with it.
+    Custom = CT_CUSTOM,
+    DndNone = CT_DND_NONE,
+    DndMove = CT_DND_MOVE,
+    DndCopy = CT_DND_COPY,
+    DndLink = CT_DND_LINK,
+}
+
+impl From<crate::CursorType> for CursorType {
+    fn from(value: crate::CursorType) -> Self {
+        let value: cef_cursor_type_t = value.into();
+        value.into()
+    }
+}
+
+/// The image of a [CursorType::Custom] cursor, borrowed from CEF for the duration of the
+/// callback.
+#[derive(Clone)]
+pub struct CursorInfo<'a> {
+    /// The point of the image which is placed at the mouse position.
+    pub hotspot: Point,
+    /// Scale of the image relative to device independent pixels.
+    pub image_scale_factor: f32,
+    /// BGRA pixels, `size.width * size.height * 4` bytes with no row padding.
+    pub buffer: &'a [u8],
+    /// Size of the image in pixels.
+    pub size: Size,
+}
+
+impl<'a> From<&'a crate::CursorInfo> for CursorInfo<'a> {
+    fn from(value: &'a crate::CursorInfo) -> Self {
+        let len = buffer_len(&value.size);
+        let buffer = if value.buffer.is_null() || len == 0 {
+            &[][..]
+        } else {
+            unsafe { slice::from_raw_parts(value.buffer as *const u8, len) }
+        };
+        Self {
+            hotspot: value.hotspot.clone(),
+            image_scale_factor: value.image_scale_factor,
+            buffer,
+            size: value.size.clone(),
+        }
+    }
+}
+
+fn buffer_len(size: &Size) -> usize {
+    size.width.max(0) as usize * size.height.max(0) as usize * 4
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cursor_type_roundtrip() {
+        for raw in [
+            cef_cursor_type_t::CT_POINTER,
+            cef_cursor_type_t::CT_CUSTOM,
+            cef_cursor_type_t::CT_DND_LINK,
+        ] {
+            assert_eq!(cef_cursor_type_t::from(CursorType::from(raw)), raw);
+        }
+    }
+
+    #[test]
+    fn test_custom_cursor_buffer_len() {
+        let mut pixels = vec![0xffu8; 16 * 8 * 4];
+        let info = crate::CursorInfo {
+            hotspot: Point { x: 3, y: 4 },
+            image_scale_factor: 2.0,
+            buffer: pixels.as_mut_ptr().cast(),
+            size: Size {
+                width: 16,
+                height: 8,
+            },
+        };
+        let info = CursorInfo::from(&info);
+        assert_eq!(info.buffer.len(), pixels.len());
+        assert_eq!((info.hotspot.x, info.hotspot.y), (3, 4));
+        assert_eq!(info.image_scale_factor, 2.0);
+    }
+
+    #[test]
+    fn test_custom_cursor_null_buffer() {
+        let info = crate::CursorInfo {
+            hotspot: Point { x: 0, y: 0 },
+            image_scale_factor: 1.0,
+            buffer: std::ptr::null_mut(),
+            size: Size {
+                width: 16,
+                height: 16,
+            },
+        };
+        assert!(CursorInfo::from(&info).buffer.is_empty());
+    }
+}
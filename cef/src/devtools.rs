@@ -0,0 +1,461 @@
+//! DevTools module
+//!
+//! Runs [DevTools protocol](https://chromedevtools.github.io/devtools-protocol/) methods on a
+//! browser, for features which the CEF API only exposes through DevTools.
+
+pub mod cookies;
+pub mod session;
+pub mod storage;
+
+use std::{
+    fmt, fs,
+    future::Future,
+    io,
+    os::raw::c_int,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicI32, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use cef_sys::{_cef_dev_tools_message_observer_t, cef_thread_id_t};
+
+use crate::{
+    dictionary_value_create,
+    future::{channel, Sender},
+    rc::{wrap_rc, RcImpl},
+    thread, BrowserHost, CefString, CefStringUtf8, DevToolsMessageObserver, DictionaryValue,
+    ImplBrowser, ImplBrowserHost, ImplDevToolsMessageObserver, ImplDictionaryValue, Value,
+};
+
+/// Why a DevTools method failed.
+#[derive(Debug)]
+pub enum DevToolsError {
+    /// DevTools is not available for the browser, e.g. before its first navigation or after it
+    /// was closed.
+    Unavailable,
+    /// The method failed with this protocol error message.
+    Method(String),
+    /// The result of the method was not what the protocol describes.
+    InvalidResponse,
+    /// The [session::DevToolsSession] was detached from its target, e.g. because its frame was
+    /// destroyed.
+    Detached,
+    /// Writing the result failed.
+    Io(io::Error),
+}
+
+impl fmt::Display for DevToolsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unavailable => write!(f, "DevTools is not available"),
+            Self::Method(message) => write!(f, "DevTools method failed: {message}"),
+            Self::InvalidResponse => write!(f, "invalid DevTools response"),
+            Self::Detached => write!(f, "DevTools session is detached"),
+            Self::Io(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for DevToolsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for DevToolsError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+/// Image format of [BrowserHost::capture_screenshot].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ImageFormat {
+    #[default]
+    Png,
+    Jpeg,
+    Webp,
+}
+
+impl ImageFormat {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Jpeg => "jpeg",
+            Self::Webp => "webp",
+        }
+    }
+}
+
+/// Our message ids, which must be unique per browser to tell the results apart.
+static NEXT_MESSAGE_ID: AtomicI32 = AtomicI32::new(1);
+
+/// A message id for a DevTools method, which no other method result uses.
+pub(crate) fn next_message_id() -> c_int {
+    NEXT_MESSAGE_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+impl BrowserHost {
+    /// Run the DevTools protocol `method` with `params`, and resolve with the JSON result.
+    ///
+    /// Must be called on the UI thread, and panics otherwise. Fails with
+    /// [DevToolsError::Unavailable] if the browser can't run DevTools methods yet, e.g. before its
+    /// first navigation.
+    pub fn execute_dev_tools(
+        &self,
+        method: &str,
+        params: Option<DictionaryValue>,
+    ) -> impl Future<Output = Result<Vec<u8>, DevToolsError>> {
+        thread::assert_on_thread(cef_thread_id_t::TID_UI);
+        let message_id = next_message_id();
+        let (sender, completion) = channel();
+        let observer = DevToolsMessageObserver::new(MethodResultObserver {
+            object: std::ptr::null_mut(),
+            message_id,
+            sender: Arc::new(Mutex::new(Some(sender))),
+        });
+        let registration = self.add_dev_tools_message_observer(Some(&observer));
+        let method = CefString::from(&CefStringUtf8::from(method));
+        let sent = registration.is_some()
+            && ImplBrowserHost::execute_dev_tools_method(
+                self,
+                message_id,
+                Some(&method),
+                params.as_ref(),
+            ) != 0;
+
+        async move {
+            if !sent {
+                return Err(DevToolsError::Unavailable);
+            }
+            let (success, result) = completion.await.ok_or(DevToolsError::Unavailable)?;
+            drop(registration);
+            if success {
+                Ok(result)
+            } else {
+                let message = match json::parse(&result) {
+                    Ok(error) => error_message(&error),
+                    Err(_) => String::from_utf8_lossy(&result).into_owned(),
+                };
+                Err(DevToolsError::Method(message))
+            }
+        }
+    }
+
+    /// Save the page as a single MHTML file at `path`, and resolve with the path.
+    ///
+    /// Must be called on the UI thread, and the file is written where the future is polled.
+    pub fn save_page_mhtml(
+        &self,
+        path: &Path,
+    ) -> impl Future<Output = Result<PathBuf, DevToolsError>> {
+        let params = dictionary_value_create();
+        if let Some(params) = &params {
+            params.set_string(Some(&key("format")), Some(&key("mhtml")));
+        }
+        let result = self.execute_dev_tools("Page.captureSnapshot", params);
+        let path = path.to_path_buf();
+
+        async move {
+            let result = result.await?;
+            let page = json::string(&json::parse(&result)?, &["data"])?;
+            let written = fs::write(&path, page);
+            if written.is_err() {
+                let _ = fs::remove_file(&path);
+            }
+            written?;
+            Ok(path)
+        }
+    }
+
+    /// Capture the page as an image. `quality` from 0 to 100 applies to [ImageFormat::Jpeg] and
+    /// [ImageFormat::Webp], and `full_page` captures the whole page instead of the viewport.
+    ///
+    /// Must be called on the UI thread. The screenshot is requested when the future is first
+    /// polled, or after the page size arrives with `full_page`, so poll it on the UI thread too.
+    pub fn capture_screenshot(
+        &self,
+        format: ImageFormat,
+        quality: Option<u8>,
+        full_page: bool,
+    ) -> impl Future<Output = Result<Vec<u8>, DevToolsError>> {
+        let host = self.clone();
+        let layout = full_page.then(|| self.execute_dev_tools("Page.getLayoutMetrics", None));
+
+        async move {
+            let params = dictionary_value_create().ok_or(DevToolsError::Unavailable)?;
+            params.set_string(Some(&key("format")), Some(&key(format.as_str())));
+            if let (Some(quality), false) = (quality, format == ImageFormat::Png) {
+                params.set_int(Some(&key("quality")), quality.min(100) as c_int);
+            }
+            if let Some(layout) = layout {
+                let layout = json::parse(&layout.await?)?;
+                let clip = dictionary_value_create().ok_or(DevToolsError::Unavailable)?;
+                for (name, value) in [
+                    ("x", 0.0),
+                    ("y", 0.0),
+                    (
+                        "width",
+                        json::number(&layout, &["cssContentSize", "width"])?,
+                    ),
+                    (
+                        "height",
+                        json::number(&layout, &["cssContentSize", "height"])?,
+                    ),
+                    ("scale", 1.0),
+                ] {
+                    clip.set_double(Some(&key(name)), value);
+                }
+                params.set_dictionary(Some(&key("clip")), Some(&clip));
+                params.set_bool(Some(&key("captureBeyondViewport")), 1);
+            }
+
+            let result = host
+                .execute_dev_tools("Page.captureScreenshot", Some(params))
+                .await?;
+            let data = json::string(&json::parse(&result)?, &["data"])?;
+            decode_base64(data.as_bytes())
+        }
+    }
+}
+
+pub(crate) fn key(value: &str) -> CefString {
+    CefString::from(&CefStringUtf8::from(value))
+}
+
+/// The `message` of a protocol error, or the whole error if it has none.
+fn error_message(error: &Value) -> String {
+    json::string(error, &["message"])
+        .unwrap_or_else(|_| String::from_utf8_lossy(&json::write(error)).into_owned())
+}
+
+fn decode_base64(text: &[u8]) -> Result<Vec<u8>, DevToolsError> {
+    let mut data = Vec::with_capacity(text.len() / 4 * 3);
+    let (mut bits, mut count) = (0u32, 0);
+    for &byte in text {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => break,
+            _ => return Err(DevToolsError::InvalidResponse),
+        };
+        bits = (bits << 6) | value as u32;
+        count += 6;
+        if count >= 8 {
+            count -= 8;
+            data.push((bits >> count) as u8);
+        }
+    }
+    Ok(data)
+}
+
+type MethodResultSender = Arc<Mutex<Option<Sender<(bool, Vec<u8>)>>>>;
+
+struct MethodResultObserver {
+    object: *mut RcImpl<_cef_dev_tools_message_observer_t, Self>,
+    message_id: c_int,
+    sender: MethodResultSender,
+}
+
+wrap_rc!(
+    MethodResultObserver,
+    WrapDevToolsMessageObserver,
+    _cef_dev_tools_message_observer_t,
+    message_id,
+    sender
+);
+
+impl ImplDevToolsMessageObserver for MethodResultObserver {
+    fn on_dev_tools_method_result(
+        &self,
+        _browser: Option<&impl ImplBrowser>,
+        message_id: c_int,
+        success: c_int,
+        result: Option<&[u8]>,
+    ) {
+        if message_id != self.message_id {
+            return;
+        }
+        let sender = self.sender.lock().ok().and_then(|mut sender| sender.take());
+        if let Some(sender) = sender {
+            sender.send((success != 0, result.unwrap_or_default().to_vec()));
+        }
+    }
+
+    fn get_raw(&self) -> *mut _cef_dev_tools_message_observer_t {
+        self.object as *mut _cef_dev_tools_message_observer_t
+    }
+}
+
+/// Reads DevTools messages with CEF's JSON parser, so the values can be looked up by the path of
+/// nested object keys the protocol documents.
+pub(crate) mod json {
+    use super::{key, DevToolsError};
+    use crate::{
+        parse_json_buffer, write_json, ImplDictionaryValue, ImplListValue, ImplValue,
+        JsonParserOptions, JsonWriterOptions, Value, ValueType,
+    };
+
+    /// Parse the JSON text of a DevTools message or result.
+    pub fn parse(json: &[u8]) -> Result<Value, DevToolsError> {
+        parse_json_buffer(Some(json), JsonParserOptions::RFC).ok_or(DevToolsError::InvalidResponse)
+    }
+
+    /// The JSON text of `value`.
+    pub fn write(value: &Value) -> Vec<u8> {
+        write_json(Some(value), JsonWriterOptions::DEFAULT)
+            .map(|json| json.to_string().into_bytes())
+            .unwrap_or_default()
+    }
+
+    /// The value at `path` of nested object keys.
+    pub fn value(json: &Value, path: &[&str]) -> Result<Value, DevToolsError> {
+        path.iter().try_fold(json.clone(), |value, name| {
+            value
+                .get_dictionary()
+                .and_then(|object| object.get_value(Some(&key(name))))
+                .ok_or(DevToolsError::InvalidResponse)
+        })
+    }
+
+    /// The number at `path` of nested object keys.
+    pub fn number(json: &Value, path: &[&str]) -> Result<f64, DevToolsError> {
+        let value = value(json, path)?;
+        match value.get_type() {
+            ValueType::Int => Ok(value.get_int().into()),
+            ValueType::Double => Ok(value.get_double()),
+            _ => Err(DevToolsError::InvalidResponse),
+        }
+    }
+
+    /// The string at `path` of nested object keys.
+    pub fn string(json: &Value, path: &[&str]) -> Result<String, DevToolsError> {
+        let value = value(json, path)?;
+        if value.get_type() != ValueType::String {
+            return Err(DevToolsError::InvalidResponse);
+        }
+        Ok(value
+            .get_string()
+            .map(|value| value.to_string())
+            .unwrap_or_default())
+    }
+
+    /// The boolean at `path` of nested object keys.
+    pub fn boolean(json: &Value, path: &[&str]) -> Result<bool, DevToolsError> {
+        let value = value(json, path)?;
+        if value.get_type() != ValueType::Bool {
+            return Err(DevToolsError::InvalidResponse);
+        }
+        Ok(value.get_bool() != 0)
+    }
+
+    /// The elements of the array at `path` of nested object keys.
+    pub fn elements(json: &Value, path: &[&str]) -> Result<Vec<Value>, DevToolsError> {
+        let list = value(json, path)?
+            .get_list()
+            .ok_or(DevToolsError::InvalidResponse)?;
+        (0..list.get_size())
+            .map(|index| list.get_value(index).ok_or(DevToolsError::InvalidResponse))
+            .collect()
+    }
+
+    /// `value` as a JSON string literal, which is also a JavaScript one.
+    pub fn quote(value: &str) -> String {
+        let mut json = String::with_capacity(value.len() + 2);
+        json.push('"');
+        for c in value.chars() {
+            match c {
+                '"' => json.push_str("\\\""),
+                '\\' => json.push_str("\\\\"),
+                c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+                c => json.push(c),
+            }
+        }
+        json.push('"');
+        json
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn string(json: &str, path: &[&str]) -> Option<String> {
+        json::parse(json.as_bytes())
+            .and_then(|json| json::string(&json, path))
+            .ok()
+    }
+
+    #[test]
+    fn test_json_string() {
+        let json = r#"{"skip": {"data": [1, "}"]}, "data": "From: <Saved>\r\nAé😀\"/"}"#;
+        assert_eq!(
+            string(json, &["data"]).as_deref(),
+            Some("From: <Saved>\r\nAé😀\"/")
+        );
+        assert_eq!(string(json, &["missing"]), None);
+        assert_eq!(string(json, &["skip"]), None);
+    }
+
+    #[test]
+    fn test_json_quote() {
+        let value = "say \"hi\"\\\n\u{1}é";
+        let quoted = json::quote(value);
+        assert_eq!(quoted, r#""say \"hi\"\\\u000a\u0001é""#);
+        assert_eq!(
+            string(&format!(r#"{{"value": {quoted}}}"#), &["value"]).as_deref(),
+            Some(value)
+        );
+    }
+
+    #[test]
+    fn test_json_nested_number() {
+        let json = json::parse(br#"{"contentSize": {"width": 1}, "cssContentSize": {"x": 0, "y": 0, "width": 1280.5, "height": 4.2e3}}"#).unwrap();
+        assert_eq!(
+            json::number(&json, &["cssContentSize", "width"]).ok(),
+            Some(1280.5)
+        );
+        assert_eq!(
+            json::number(&json, &["cssContentSize", "height"]).ok(),
+            Some(4200.0)
+        );
+        assert_eq!(
+            json::number(&json, &["contentSize", "width"]).ok(),
+            Some(1.0)
+        );
+    }
+
+    #[test]
+    fn test_json_elements() {
+        let json = json::parse(br#"{"items": [{"a": [1, 2]}, "x,y", 3 ]}"#).unwrap();
+        let elements = json::elements(&json, &["items"]).unwrap();
+        assert_eq!(elements.len(), 3);
+        assert_eq!(json::elements(&elements[0], &["a"]).unwrap().len(), 2);
+        assert_eq!(json::string(&elements[1], &[]).ok().as_deref(), Some("x,y"));
+        assert_eq!(json::number(&elements[2], &[]).ok(), Some(3.0));
+        assert!(json::parse(b"{\"items\": [").is_err());
+    }
+
+    #[test]
+    fn test_error_message() {
+        let error = json::parse(br#"{"code": -32000, "message": "Not attached"}"#).unwrap();
+        assert_eq!(error_message(&error), "Not attached");
+        let error = json::parse(br#"{"code": -32000}"#).unwrap();
+        assert_eq!(error_message(&error), r#"{"code":-32000}"#);
+    }
+
+    #[test]
+    fn test_decode_base64() {
+        assert_eq!(decode_base64(b"aGVsbG8=").unwrap(), b"hello");
+        assert_eq!(decode_base64(b"iVBORw0K").unwrap(), b"\x89PNG\r\n");
+        assert!(decode_base64(b"not base64!").is_err());
+    }
+}
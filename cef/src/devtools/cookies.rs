@@ -0,0 +1,462 @@
+//! DevTools cookies module
+//!
+//! CEF can't observe changes of the cookie store, so [CookieWatcher] reports them by comparing
+//! snapshots of it. It takes a snapshot with `Network.getAllCookies` in a [DevToolsSession] of
+//! the browser, every [CookieFilter::reconcile_interval] and right after a response set a cookie,
+//! which the `Network.responseReceivedExtraInfo` events tell. Cookies set by scripts through
+//! `document.cookie` are only noticed by the periodic snapshots.
+//!
+//! Like the other DevTools modules, the watcher must be created and polled on the UI thread.
+//!
+//! ```ignore
+//! let mut watcher = CookieWatcher::new(&browser, CookieFilter::new().domain("example.com"));
+//! while let Some(change) = watcher.next().await {
+//!     match change? {
+//!         CookieChange::Added(cookie) | CookieChange::Updated(cookie) => sync(cookie),
+//!         CookieChange::Removed(cookie) => forget(cookie),
+//!     }
+//! }
+//! ```
+
+use std::{
+    collections::{HashMap, VecDeque},
+    future::Future,
+    mem,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+
+use cef_sys::cef_thread_id_t;
+
+use super::{json, session::DevToolsSession, storage, DevToolsError};
+use crate::{thread, Browser, CefStringList, Cookie, ImplDictionaryValue, ImplValue};
+
+const RESPONSE_RECEIVED_EXTRA_INFO: &str = "Network.responseReceivedExtraInfo";
+
+/// Which cookies a [CookieWatcher] reports, and how often it looks for changes.
+#[derive(Clone, Debug)]
+pub struct CookieFilter {
+    domains: Vec<String>,
+    reconcile_interval: Duration,
+}
+
+impl Default for CookieFilter {
+    fn default() -> Self {
+        Self {
+            domains: vec![],
+            reconcile_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+impl CookieFilter {
+    /// Report the cookies of every domain, and look for changes every second.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Only report the cookies of `domain` and its subdomains, e.g. `example.com` for the
+    /// cookies of `www.example.com` too. Without any domain, every cookie is reported.
+    pub fn domain(mut self, domain: impl Into<String>) -> Self {
+        let domain = domain.into();
+        self.domains
+            .push(domain.trim_start_matches('.').to_ascii_lowercase());
+        self
+    }
+
+    /// Look for changes every `interval`, besides after responses which set cookies.
+    pub fn reconcile_interval(mut self, interval: Duration) -> Self {
+        self.reconcile_interval = interval;
+        self
+    }
+
+    /// Whether `cookie` is reported.
+    pub fn matches(&self, cookie: &Cookie) -> bool {
+        if self.domains.is_empty() {
+            return true;
+        }
+        let domain = cookie.domain.to_string();
+        let domain = domain.trim_start_matches('.').to_ascii_lowercase();
+        self.domains.iter().any(|filter| {
+            domain == *filter
+                || domain
+                    .strip_suffix(filter.as_str())
+                    .is_some_and(|prefix| prefix.ends_with('.'))
+        })
+    }
+}
+
+/// A change of the cookie store reported by [CookieWatcher].
+#[derive(Clone)]
+pub enum CookieChange {
+    Added(Cookie),
+    /// The cookie was deleted or expired, with its last value.
+    Removed(Cookie),
+    /// The value or an attribute of the cookie changed, with its new value.
+    Updated(Cookie),
+}
+
+impl CookieChange {
+    /// The cookie which changed.
+    pub fn cookie(&self) -> &Cookie {
+        match self {
+            Self::Added(cookie) | Self::Removed(cookie) | Self::Updated(cookie) => cookie,
+        }
+    }
+}
+
+type PendingResult<T> = Pin<Box<dyn Future<Output = Result<T, DevToolsError>>>>;
+
+enum Phase {
+    Attaching(PendingResult<DevToolsSession>),
+    Enabling(DevToolsSession, PendingResult<Vec<u8>>),
+    Reconciling(DevToolsSession, PendingResult<Vec<u8>>),
+    Idle(DevToolsSession),
+    Ended,
+}
+
+/// The cookie changes of the request context of a browser, as a stream.
+///
+/// Take the changes with [CookieWatcher::next] or [CookieWatcher::poll_next] on the UI thread.
+/// Changes are relative to the first snapshot, so the cookies which exist when the watcher
+/// starts aren't reported. The stream ends with an error when the DevTools session detaches,
+/// e.g. because the browser closed, or fails to attach.
+///
+/// The crate has no dependency on a `Stream` trait. Adapt the watcher with e.g.
+/// `futures::stream::poll_fn(move |cx| watcher.poll_next(cx))`.
+pub struct CookieWatcher {
+    filter: CookieFilter,
+    phase: Phase,
+    snapshot: Option<HashMap<CookieKey, Cookie>>,
+    changes: VecDeque<CookieChange>,
+    signal: Arc<Mutex<Signal>>,
+}
+
+impl CookieWatcher {
+    /// Watch the cookies of the request context of `browser` which match `filter`. The
+    /// DevTools session attaches when the watcher is first polled.
+    pub fn new(browser: &Browser, filter: CookieFilter) -> Self {
+        Self {
+            filter,
+            phase: Phase::Attaching(Box::pin(DevToolsSession::attach_to_browser(browser))),
+            snapshot: None,
+            changes: Default::default(),
+            signal: Default::default(),
+        }
+    }
+
+    /// Resolve with the next change, or `None` when the stream ended.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(
+        &mut self,
+    ) -> impl Future<Output = Option<Result<CookieChange, DevToolsError>>> + '_ {
+        std::future::poll_fn(|cx| self.poll_next(cx))
+    }
+
+    /// Take the next change, or wake `cx` when there is one. Resolves to `None` when the stream
+    /// ended.
+    pub fn poll_next(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<CookieChange, DevToolsError>>> {
+        loop {
+            if let Some(change) = self.changes.pop_front() {
+                return Poll::Ready(Some(Ok(change)));
+            }
+            match mem::replace(&mut self.phase, Phase::Ended) {
+                Phase::Attaching(mut attach) => match attach.as_mut().poll(cx) {
+                    Poll::Pending => {
+                        self.phase = Phase::Attaching(attach);
+                        return Poll::Pending;
+                    }
+                    Poll::Ready(Ok(session)) => {
+                        let signal = self.signal.clone();
+                        session.on_event(Box::new(move |method, params| {
+                            if method == RESPONSE_RECEIVED_EXTRA_INFO && sets_cookie(params) {
+                                Signal::fire(&signal);
+                            }
+                        }));
+                        let enable = Box::pin(session.execute("Network.enable", None));
+                        self.phase = Phase::Enabling(session, enable);
+                    }
+                    Poll::Ready(Err(error)) => return Poll::Ready(Some(Err(error))),
+                },
+                Phase::Enabling(session, mut enable) => match enable.as_mut().poll(cx) {
+                    Poll::Pending => {
+                        self.phase = Phase::Enabling(session, enable);
+                        return Poll::Pending;
+                    }
+                    Poll::Ready(Ok(_)) => self.phase = reconcile(session),
+                    Poll::Ready(Err(error)) => return Poll::Ready(Some(Err(error))),
+                },
+                Phase::Reconciling(session, mut result) => match result.as_mut().poll(cx) {
+                    Poll::Pending => {
+                        self.phase = Phase::Reconciling(session, result);
+                        return Poll::Pending;
+                    }
+                    Poll::Ready(Ok(result)) => {
+                        let cookies = match storage::cookies(&result) {
+                            Ok(cookies) => cookies,
+                            Err(error) => return Poll::Ready(Some(Err(error))),
+                        };
+                        self.update(cookies);
+                        self.schedule();
+                        self.phase = Phase::Idle(session);
+                    }
+                    Poll::Ready(Err(error)) => return Poll::Ready(Some(Err(error))),
+                },
+                Phase::Idle(session) => {
+                    if !session.is_attached() {
+                        return Poll::Ready(Some(Err(DevToolsError::Detached)));
+                    }
+                    if Signal::take(&self.signal, cx.waker()) {
+                        self.phase = reconcile(session);
+                    } else {
+                        self.phase = Phase::Idle(session);
+                        return Poll::Pending;
+                    }
+                }
+                Phase::Ended => return Poll::Ready(None),
+            }
+        }
+    }
+
+    /// Queue the changes from the last snapshot to `cookies`, and keep them as the snapshot.
+    fn update(&mut self, cookies: Vec<Cookie>) {
+        let cookies: HashMap<_, _> = cookies
+            .into_iter()
+            .filter(|cookie| self.filter.matches(cookie))
+            .map(|cookie| (CookieKey::new(&cookie), cookie))
+            .collect();
+        if let Some(mut previous) = self.snapshot.take() {
+            for (key, cookie) in &cookies {
+                match previous.remove(key) {
+                    None => self.changes.push_back(CookieChange::Added(cookie.clone())),
+                    Some(old) if !same_cookie(&old, cookie) => self
+                        .changes
+                        .push_back(CookieChange::Updated(cookie.clone())),
+                    Some(_) => {}
+                }
+            }
+            self.changes
+                .extend(previous.into_values().map(CookieChange::Removed));
+        }
+        self.snapshot = Some(cookies);
+    }
+
+    /// Fire the signal after the reconcile interval, unless a timer is running already.
+    fn schedule(&self) {
+        let Ok(mut signal) = self.signal.lock() else {
+            return;
+        };
+        if mem::replace(&mut signal.timer, true) {
+            return;
+        }
+        drop(signal);
+        let shared = self.signal.clone();
+        let posted = thread::post_delayed(
+            cef_thread_id_t::TID_UI,
+            self.filter.reconcile_interval,
+            move || {
+                if let Ok(mut signal) = shared.lock() {
+                    signal.timer = false;
+                }
+                Signal::fire(&shared);
+            },
+        );
+        if !posted {
+            if let Ok(mut signal) = self.signal.lock() {
+                signal.timer = false;
+            }
+        }
+    }
+}
+
+/// Start a snapshot of the cookies.
+fn reconcile(session: DevToolsSession) -> Phase {
+    let result = Box::pin(session.execute("Network.getAllCookies", None));
+    Phase::Reconciling(session, result)
+}
+
+/// Tells the idle watcher to take a snapshot, from the timer and the event listener.
+#[derive(Default)]
+struct Signal {
+    due: bool,
+    timer: bool,
+    waker: Option<Waker>,
+}
+
+impl Signal {
+    fn fire(signal: &Mutex<Self>) {
+        let waker = signal.lock().ok().and_then(|mut signal| {
+            signal.due = true;
+            signal.waker.take()
+        });
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    /// Whether a snapshot is due, or else keep `waker` to wake when it is.
+    fn take(signal: &Mutex<Self>, waker: &Waker) -> bool {
+        let Ok(mut signal) = signal.lock() else {
+            return false;
+        };
+        if mem::take(&mut signal.due) {
+            return true;
+        }
+        signal.waker = Some(waker.clone());
+        false
+    }
+}
+
+/// A cookie is identified by its name, domain and path.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct CookieKey {
+    name: String,
+    domain: String,
+    path: String,
+}
+
+impl CookieKey {
+    fn new(cookie: &Cookie) -> Self {
+        Self {
+            name: cookie.name.to_string(),
+            domain: cookie.domain.to_string(),
+            path: cookie.path.to_string(),
+        }
+    }
+}
+
+/// Whether the value and attributes of two cookies with the same key are the same.
+fn same_cookie(a: &Cookie, b: &Cookie) -> bool {
+    a.value.to_string() == b.value.to_string()
+        && a.secure == b.secure
+        && a.httponly == b.httponly
+        && a.has_expires == b.has_expires
+        && a.expires.val == b.expires.val
+        && a.same_site == b.same_site
+        && a.priority == b.priority
+}
+
+/// Whether the `params` of `Network.responseReceivedExtraInfo` have a `Set-Cookie` header.
+fn sets_cookie(params: &[u8]) -> bool {
+    let Some(headers) = json::parse(params)
+        .and_then(|params| json::value(&params, &["headers"]))
+        .ok()
+        .and_then(|headers| headers.get_dictionary())
+    else {
+        return false;
+    };
+    let mut names = CefStringList::new();
+    headers.get_keys(Some(&mut names));
+    names
+        .into_iter()
+        .any(|name| name.eq_ignore_ascii_case("set-cookie"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{CefString, CefStringUtf8};
+
+    fn cookie(name: &str, value: &str, domain: &str) -> Cookie {
+        let string = |value: &str| CefString::from(&CefStringUtf8::from(value));
+        Cookie {
+            name: string(name),
+            value: string(value),
+            domain: string(domain),
+            path: string("/"),
+            secure: 0,
+            httponly: 0,
+            creation: Default::default(),
+            last_access: Default::default(),
+            has_expires: 0,
+            expires: Default::default(),
+            same_site: Default::default(),
+            priority: Default::default(),
+        }
+    }
+
+    fn watcher(filter: CookieFilter) -> CookieWatcher {
+        CookieWatcher {
+            filter,
+            phase: Phase::Ended,
+            snapshot: None,
+            changes: Default::default(),
+            signal: Default::default(),
+        }
+    }
+
+    fn changes(watcher: &mut CookieWatcher) -> Vec<(&'static str, String, String)> {
+        let mut changes: Vec<_> = watcher
+            .changes
+            .drain(..)
+            .map(|change| {
+                let kind = match &change {
+                    CookieChange::Added(_) => "added",
+                    CookieChange::Removed(_) => "removed",
+                    CookieChange::Updated(_) => "updated",
+                };
+                let cookie = change.cookie();
+                (kind, cookie.name.to_string(), cookie.value.to_string())
+            })
+            .collect();
+        changes.sort();
+        changes
+    }
+
+    #[test]
+    fn test_cookie_filter() {
+        let filter = CookieFilter::new().domain(".Example.com");
+        assert!(filter.matches(&cookie("a", "1", "example.com")));
+        assert!(filter.matches(&cookie("a", "1", ".www.example.com")));
+        assert!(!filter.matches(&cookie("a", "1", "badexample.com")));
+        assert!(!filter.matches(&cookie("a", "1", "example.org")));
+        assert!(CookieFilter::new().matches(&cookie("a", "1", "example.org")));
+    }
+
+    #[test]
+    fn test_reconcile_changes() {
+        let mut watcher = watcher(CookieFilter::new().domain("example.com"));
+        watcher.update(vec![
+            cookie("session", "1", "example.com"),
+            cookie("theme", "dark", "example.com"),
+        ]);
+        // The first snapshot is the baseline.
+        assert!(changes(&mut watcher).is_empty());
+
+        watcher.update(vec![
+            cookie("session", "2", "example.com"),
+            cookie("cart", "3", ".shop.example.com"),
+            cookie("tracker", "x", "ads.example.org"),
+        ]);
+        assert_eq!(
+            changes(&mut watcher),
+            [
+                ("added", "cart".to_string(), "3".to_string()),
+                ("removed", "theme".to_string(), "dark".to_string()),
+                ("updated", "session".to_string(), "2".to_string()),
+            ]
+        );
+
+        watcher.update(vec![
+            cookie("session", "2", "example.com"),
+            cookie("cart", "3", ".shop.example.com"),
+        ]);
+        assert!(changes(&mut watcher).is_empty());
+    }
+
+    #[test]
+    fn test_sets_cookie() {
+        assert!(sets_cookie(
+            br#"{"requestId":"1","headers":{"content-type":"text/html","Set-Cookie":"a=1"}}"#
+        ));
+        assert!(!sets_cookie(
+            br#"{"requestId":"1","headers":{"content-type":"text/html"}}"#
+        ));
+        assert!(!sets_cookie(br#"{"requestId":"1"}"#));
+    }
+}
@@ -0,0 +1,484 @@
+//! DevTools session module
+//!
+//! [BrowserHost::execute_dev_tools] only reaches the top level target of a browser. Out-of-process
+//! iframes have targets of their own, which a [DevToolsSession] attaches to with the `Target`
+//! domain in flat mode. Its messages carry the `sessionId` of the target, which the session adds
+//! to the methods it sends and uses to pick its results and events out of all messages of the
+//! browser.
+//!
+//! Like [BrowserHost::execute_dev_tools], every function must be called on the UI thread.
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    future::Future,
+    os::raw::c_int,
+    rc::Rc,
+};
+
+use cef_sys::_cef_dev_tools_message_observer_t;
+
+use super::{error_message, json, key, next_message_id, DevToolsError};
+use crate::{
+    dictionary_value_create,
+    future::{channel, Sender},
+    rc::{wrap_rc, RcImpl},
+    Browser, BrowserHost, DevToolsMessageObserver, Frame, ImplBrowser, ImplBrowserHost,
+    ImplDevToolsMessageObserver, ImplDictionaryValue, Registration, Value,
+};
+
+const DETACHED_FROM_TARGET: &str = "Target.detachedFromTarget";
+
+type EventCallback = Box<dyn Fn(&str, &[u8])>;
+
+type MethodResult = Result<Vec<u8>, DevToolsError>;
+
+/// A DevTools session attached to the target of a frame or browser.
+///
+/// Methods run with [DevToolsSession::execute] and events received with
+/// [DevToolsSession::on_event] belong to the target of the session. When the target goes away,
+/// e.g. because its frame was destroyed, the session detaches itself and its pending methods fail
+/// with [DevToolsError::Detached]. Dropping the session detaches it as well.
+///
+/// ```ignore
+/// let session = DevToolsSession::attach_to_frame(&iframe).await?;
+/// let result = session
+///     .execute("Runtime.evaluate", Some(r#"{"expression": "document.title"}"#))
+///     .await?;
+/// ```
+pub struct DevToolsSession {
+    host: BrowserHost,
+    state: Rc<SessionState>,
+    _registration: Option<Registration>,
+}
+
+impl DevToolsSession {
+    /// Attach to the target of `frame`.
+    ///
+    /// Only the main frame and out-of-process iframes have a target. The target of an iframe is
+    /// found by its URL, and the future fails with [DevToolsError::Unavailable] if there is none,
+    /// e.g. for a same-origin iframe which runs in the process of its parent. Attach to the
+    /// browser for those, and run methods in the execution context of the frame.
+    pub fn attach_to_frame(
+        frame: &Frame,
+    ) -> impl Future<Output = Result<DevToolsSession, DevToolsError>> {
+        let host = frame.browser().and_then(|browser| browser.get_host());
+        let is_main = frame.is_main();
+        let url = frame.url();
+        async move {
+            let host = host.ok_or(DevToolsError::Unavailable)?;
+            let target_id = if is_main {
+                own_target_id(&host).await?
+            } else {
+                let url = url.ok_or(DevToolsError::Unavailable)?;
+                let targets = host.execute_dev_tools("Target.getTargets", None).await?;
+                iframe_target_id(&targets, &url)?.ok_or(DevToolsError::Unavailable)?
+            };
+            Self::attach(host, &target_id).await
+        }
+    }
+
+    /// Attach to the top level target of `browser`, e.g. of a popup.
+    pub fn attach_to_browser(
+        browser: &Browser,
+    ) -> impl Future<Output = Result<DevToolsSession, DevToolsError>> {
+        let host = browser.get_host();
+        async move {
+            let host = host.ok_or(DevToolsError::Unavailable)?;
+            let target_id = own_target_id(&host).await?;
+            Self::attach(host, &target_id).await
+        }
+    }
+
+    async fn attach(host: BrowserHost, target_id: &str) -> Result<Self, DevToolsError> {
+        let params = dictionary_value_create();
+        if let Some(params) = &params {
+            params.set_string(Some(&key("targetId")), Some(&key(target_id)));
+            params.set_bool(Some(&key("flatten")), 1);
+        }
+        let result = host
+            .execute_dev_tools("Target.attachToTarget", params)
+            .await?;
+        let state = Rc::new(SessionState::new(json::string(
+            &json::parse(&result)?,
+            &["sessionId"],
+        )?));
+        let observer = DevToolsMessageObserver::new(SessionObserver {
+            object: std::ptr::null_mut(),
+            state: state.clone(),
+        });
+        let registration = host.add_dev_tools_message_observer(Some(&observer));
+        Ok(Self {
+            host,
+            state,
+            _registration: registration,
+        })
+    }
+
+    /// The `sessionId` of the session.
+    pub fn session_id(&self) -> &str {
+        &self.state.session_id
+    }
+
+    /// `false` once the session was detached from its target.
+    pub fn is_attached(&self) -> bool {
+        !self.state.detached.get()
+    }
+
+    /// Run the DevTools protocol `method` on the target of the session, with `params` as the
+    /// text of a JSON object, and resolve with the JSON result.
+    pub fn execute(
+        &self,
+        method: &str,
+        params: Option<&str>,
+    ) -> impl Future<Output = MethodResult> {
+        let message_id = next_message_id();
+        let (sender, completion) = channel();
+        let sent = self.is_attached() && {
+            self.state.pending.borrow_mut().insert(message_id, sender);
+            let message = self.state.message(message_id, method, params);
+            self.host.send_dev_tools_message(Some(message.as_bytes())) != 0
+        };
+        if !sent {
+            self.state.pending.borrow_mut().remove(&message_id);
+        }
+        let detached = self.state.detached.get();
+
+        async move {
+            match (sent, detached) {
+                (false, true) => Err(DevToolsError::Detached),
+                (false, false) => Err(DevToolsError::Unavailable),
+                _ => completion.await.unwrap_or(Err(DevToolsError::Detached)),
+            }
+        }
+    }
+
+    /// Call `callback` with the method and params of every event of the target.
+    pub fn on_event(&self, callback: EventCallback) {
+        self.state.listeners.borrow_mut().push(callback);
+    }
+}
+
+impl Drop for DevToolsSession {
+    fn drop(&mut self) {
+        if !self.is_attached() {
+            return;
+        }
+        let params = dictionary_value_create();
+        if let Some(params) = &params {
+            params.set_string(Some(&key("sessionId")), Some(&key(self.session_id())));
+        }
+        // The method runs without awaiting its result.
+        drop(
+            self.host
+                .execute_dev_tools("Target.detachFromTarget", params),
+        );
+        self.state.detach();
+    }
+}
+
+/// The `targetId` of the top level target of `host`.
+async fn own_target_id(host: &BrowserHost) -> Result<String, DevToolsError> {
+    let info = host.execute_dev_tools("Target.getTargetInfo", None).await?;
+    json::string(&json::parse(&info)?, &["targetInfo", "targetId"])
+}
+
+/// The `targetId` of the iframe target showing `url` in a `Target.getTargets` result.
+fn iframe_target_id(targets: &[u8], url: &str) -> Result<Option<String>, DevToolsError> {
+    let targets = json::parse(targets)?;
+    for target in json::elements(&targets, &["targetInfos"])? {
+        if json::string(&target, &["type"])? == "iframe" && json::string(&target, &["url"])? == url
+        {
+            return json::string(&target, &["targetId"]).map(Some);
+        }
+    }
+    Ok(None)
+}
+
+/// A message a browser sent to its DevTools observers, see
+/// [ImplDevToolsMessageObserver::on_dev_tools_message].
+enum Message {
+    /// The result of the method with the message id `id`, or its error message.
+    Result {
+        id: c_int,
+        session_id: Option<String>,
+        result: Result<Value, String>,
+    },
+    Event {
+        method: String,
+        session_id: Option<String>,
+        params: Option<Value>,
+    },
+}
+
+impl Message {
+    fn parse(message: &[u8]) -> Result<Self, DevToolsError> {
+        let message = json::parse(message)?;
+        let session_id = json::string(&message, &["sessionId"]).ok();
+        if let Ok(id) = json::number(&message, &["id"]) {
+            let result = match json::value(&message, &["error"]) {
+                Ok(error) => Err(error_message(&error)),
+                Err(_) => Ok(json::value(&message, &["result"])?),
+            };
+            return Ok(Self::Result {
+                id: id as c_int,
+                session_id,
+                result,
+            });
+        }
+        Ok(Self::Event {
+            method: json::string(&message, &["method"])?,
+            session_id,
+            params: json::value(&message, &["params"]).ok(),
+        })
+    }
+}
+
+/// The pending methods and event listeners of a [DevToolsSession].
+struct SessionState {
+    session_id: String,
+    pending: RefCell<HashMap<c_int, Sender<MethodResult>>>,
+    listeners: RefCell<Vec<EventCallback>>,
+    detached: Cell<bool>,
+}
+
+impl SessionState {
+    fn new(session_id: String) -> Self {
+        Self {
+            session_id,
+            pending: Default::default(),
+            listeners: Default::default(),
+            detached: Cell::new(false),
+        }
+    }
+
+    /// The JSON text of a method of the session.
+    fn message(&self, message_id: c_int, method: &str, params: Option<&str>) -> String {
+        format!(
+            r#"{{"id":{message_id},"sessionId":{},"method":{},"params":{}}}"#,
+            json::quote(&self.session_id),
+            json::quote(method),
+            params.unwrap_or("{}")
+        )
+    }
+
+    /// Handle a message of the browser, and return `true` if it belonged to the session.
+    fn dispatch(&self, message: &[u8]) -> bool {
+        let Ok(message) = Message::parse(message) else {
+            return false;
+        };
+        match message {
+            Message::Result {
+                id,
+                session_id,
+                result,
+            } if session_id.as_deref() == Some(&self.session_id) => {
+                let sender = self.pending.borrow_mut().remove(&id);
+                if let Some(sender) = sender {
+                    let result = result.map(|result| json::write(&result));
+                    sender.send(result.map_err(DevToolsError::Method));
+                }
+                true
+            }
+            Message::Event {
+                method,
+                session_id,
+                params,
+            } if session_id.as_deref() == Some(&self.session_id) => {
+                let params = params.as_ref().map_or_else(|| b"{}".to_vec(), json::write);
+                for listener in self.listeners.borrow().iter() {
+                    listener(&method, &params);
+                }
+                true
+            }
+            // The browser reports the detach of a session outside of the session.
+            Message::Event {
+                method,
+                session_id: None,
+                params,
+            } if method == DETACHED_FROM_TARGET
+                && params
+                    .as_ref()
+                    .and_then(|params| json::string(params, &["sessionId"]).ok())
+                    .as_deref()
+                    == Some(&self.session_id) =>
+            {
+                self.detach();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Mark the session detached, and fail its pending methods.
+    fn detach(&self) {
+        self.detached.set(true);
+        let pending: Vec<_> = self.pending.borrow_mut().drain().collect();
+        for (_, sender) in pending {
+            sender.send(Err(DevToolsError::Detached));
+        }
+    }
+}
+
+struct SessionObserver {
+    object: *mut RcImpl<_cef_dev_tools_message_observer_t, Self>,
+    state: Rc<SessionState>,
+}
+
+wrap_rc!(
+    SessionObserver,
+    WrapDevToolsMessageObserver,
+    _cef_dev_tools_message_observer_t,
+    state
+);
+
+impl ImplDevToolsMessageObserver for SessionObserver {
+    fn on_dev_tools_message(
+        &self,
+        _browser: Option<&impl ImplBrowser>,
+        message: Option<&[u8]>,
+    ) -> c_int {
+        // This observer only handles raw messages, so CEF needn't parse them for it.
+        if let Some(message) = message {
+            self.state.dispatch(message);
+        }
+        1
+    }
+
+    fn on_dev_tools_agent_detached(&self, _browser: Option<&impl ImplBrowser>) {
+        self.state.detach();
+    }
+
+    fn get_raw(&self) -> *mut _cef_dev_tools_message_observer_t {
+        self.object as *mut _cef_dev_tools_message_observer_t
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        pin::Pin,
+        task::{Context, Poll, Waker},
+    };
+
+    use super::*;
+    use crate::future::Completion;
+
+    fn poll(completion: &mut Completion<MethodResult>) -> Poll<Option<MethodResult>> {
+        let mut cx = Context::from_waker(Waker::noop());
+        Pin::new(completion).poll(&mut cx)
+    }
+
+    fn pending(state: &SessionState, message_id: c_int) -> Completion<MethodResult> {
+        let (sender, completion) = channel();
+        state.pending.borrow_mut().insert(message_id, sender);
+        completion
+    }
+
+    #[test]
+    fn test_session_message() {
+        let state = SessionState::new("S1".to_string());
+        assert_eq!(
+            state.message(7, "Runtime.evaluate", Some(r#"{"expression":"1 + 1"}"#)),
+            r#"{"id":7,"sessionId":"S1","method":"Runtime.evaluate","params":{"expression":"1 + 1"}}"#
+        );
+        assert_eq!(
+            state.message(8, "Runtime.enable", None),
+            r#"{"id":8,"sessionId":"S1","method":"Runtime.enable","params":{}}"#
+        );
+    }
+
+    #[test]
+    fn test_demultiplex_results() {
+        let state = SessionState::new("S1".to_string());
+        let mut evaluated = pending(&state, 7);
+        let mut failed = pending(&state, 8);
+
+        // The same message id in another session or at the top level is not ours.
+        assert!(!state.dispatch(br#"{"id": 7, "result": {"top": true}}"#));
+        assert!(!state.dispatch(br#"{"id": 7, "sessionId": "S2", "result": {}}"#));
+        assert!(poll(&mut evaluated).is_pending());
+
+        assert!(state.dispatch(
+            br#"{"id": 7, "result": {"result": {"type": "number", "value": 2}}, "sessionId": "S1"}"#
+        ));
+        let Poll::Ready(Some(Ok(result))) = poll(&mut evaluated) else {
+            panic!("the result was not routed to the session");
+        };
+        let result = json::parse(&result).unwrap();
+        assert_eq!(json::number(&result, &["result", "value"]).unwrap(), 2.0);
+
+        assert!(state.dispatch(
+            br#"{"id": 8, "sessionId": "S1", "error": {"code": -32601, "message": "'Nope' wasn't found"}}"#
+        ));
+        let Poll::Ready(Some(Err(DevToolsError::Method(message)))) = poll(&mut failed) else {
+            panic!("the error was not routed to the session");
+        };
+        assert_eq!(message, "'Nope' wasn't found");
+    }
+
+    #[test]
+    fn test_demultiplex_events() {
+        let state = SessionState::new("S1".to_string());
+        let received = Rc::new(RefCell::new(vec![]));
+        let events = received.clone();
+        state
+            .listeners
+            .borrow_mut()
+            .push(Box::new(move |method, params| {
+                events.borrow_mut().push((
+                    method.to_string(),
+                    json::string(&json::parse(params).unwrap(), &["name"]).ok(),
+                ))
+            }));
+
+        assert!(!state.dispatch(br#"{"method": "Page.loadEventFired", "params": {}}"#));
+        assert!(state.dispatch(
+            br#"{"method": "Runtime.bindingCalled", "params": {"name": "ping"}, "sessionId": "S1"}"#
+        ));
+        assert!(!state.dispatch(
+            br#"{"method": "Runtime.bindingCalled", "params": {"name": "pong"}, "sessionId": "S2"}"#
+        ));
+        assert_eq!(
+            *received.borrow(),
+            [(
+                "Runtime.bindingCalled".to_string(),
+                Some("ping".to_string())
+            )]
+        );
+    }
+
+    #[test]
+    fn test_detach_fails_pending() {
+        let state = SessionState::new("S1".to_string());
+        let mut evaluated = pending(&state, 7);
+
+        assert!(!state.dispatch(
+            br#"{"method": "Target.detachedFromTarget", "params": {"sessionId": "S2"}}"#
+        ));
+        assert!(!state.detached.get());
+        assert!(state.dispatch(
+            br#"{"method": "Target.detachedFromTarget", "params": {"sessionId": "S1", "targetId": "F1"}}"#
+        ));
+        assert!(state.detached.get());
+        assert!(matches!(
+            poll(&mut evaluated),
+            Poll::Ready(Some(Err(DevToolsError::Detached)))
+        ));
+    }
+
+    #[test]
+    fn test_iframe_target_id() {
+        let targets = br#"{"targetInfos": [
+            {"targetId": "P1", "type": "page", "title": "", "url": "https://example.com/", "attached": true},
+            {"targetId": "F1", "type": "iframe", "title": "", "url": "https://other.example/frame", "attached": false}]}"#;
+        assert_eq!(
+            iframe_target_id(targets, "https://other.example/frame").unwrap(),
+            Some("F1".to_string())
+        );
+        assert_eq!(
+            iframe_target_id(targets, "https://example.com/").unwrap(),
+            None
+        );
+    }
+}
@@ -0,0 +1,292 @@
+//! DevTools storage module
+//!
+//! Inspects the cookies and storage of a browser with the DevTools `Network`, `Storage` and
+//! `DOMStorage` domains, which reach storage types the cookie manager can't, e.g. localStorage.
+//!
+//! Like [BrowserHost::execute_dev_tools], every function must be called on the UI thread, and its
+//! future fails with [DevToolsError::Unavailable] before the first navigation of the browser.
+
+use std::{future::Future, ops::BitOr};
+
+use cef_sys::{cef_cookie_priority_t, cef_cookie_same_site_t};
+
+use super::{json, key, DevToolsError};
+use crate::{
+    dictionary_value_create, Basetime, BrowserHost, CefString, CefStringUtf8, Cookie,
+    CookiePriority, CookieSameSite, DictionaryValue, ImplDictionaryValue, Value,
+};
+
+/// A set of the storage types of `Storage.clearDataForOrigin`, combined with `|`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct StorageTypes(u32);
+
+impl StorageTypes {
+    pub const COOKIES: Self = Self(1 << 0);
+    pub const FILE_SYSTEMS: Self = Self(1 << 1);
+    pub const INDEXED_DB: Self = Self(1 << 2);
+    pub const LOCAL_STORAGE: Self = Self(1 << 3);
+    pub const SHADER_CACHE: Self = Self(1 << 4);
+    pub const WEBSQL: Self = Self(1 << 5);
+    pub const SERVICE_WORKERS: Self = Self(1 << 6);
+    pub const CACHE_STORAGE: Self = Self(1 << 7);
+
+    /// The protocol names of the storage types, in the order of their bits.
+    const NAMES: [&'static str; 8] = [
+        "cookies",
+        "file_systems",
+        "indexeddb",
+        "local_storage",
+        "shader_cache",
+        "websql",
+        "service_workers",
+        "cache_storage",
+    ];
+
+    /// No storage types.
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Every storage type.
+    pub const fn all() -> Self {
+        Self((1 << Self::NAMES.len()) - 1)
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// `true` if every storage type of `other` is in this set.
+    pub const fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// The `storageTypes` parameter of `Storage.clearDataForOrigin`.
+    fn as_param(&self) -> String {
+        Self::NAMES
+            .iter()
+            .enumerate()
+            .filter_map(|(bit, name)| self.contains(Self(1 << bit)).then_some(*name))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+impl BitOr for StorageTypes {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// Resolve with every cookie of the browser, as `Network.getAllCookies` reports them.
+///
+/// DevTools does not report when a cookie was created or last accessed, so
+/// [Cookie::creation] and [Cookie::last_access] are zero.
+pub fn get_all_cookies(
+    host: &BrowserHost,
+) -> impl Future<Output = Result<Vec<Cookie>, DevToolsError>> {
+    let result = host.execute_dev_tools("Network.getAllCookies", None);
+    async move { cookies(&result.await?) }
+}
+
+/// The cookies of a `Network.getAllCookies` result.
+pub(super) fn cookies(result: &[u8]) -> Result<Vec<Cookie>, DevToolsError> {
+    let result = json::parse(result)?;
+    json::elements(&result, &["cookies"])?
+        .iter()
+        .map(cookie)
+        .collect()
+}
+
+/// Clear `types` of storage for `origin`, e.g. `https://example.com`.
+pub fn clear_storage_for_origin(
+    host: &BrowserHost,
+    origin: &str,
+    types: StorageTypes,
+) -> impl Future<Output = Result<(), DevToolsError>> {
+    let params = dictionary_value_create();
+    if let Some(params) = &params {
+        params.set_string(Some(&key("origin")), Some(&key(origin)));
+        params.set_string(Some(&key("storageTypes")), Some(&key(&types.as_param())));
+    }
+    let cleared =
+        (!types.is_empty()).then(|| host.execute_dev_tools("Storage.clearDataForOrigin", params));
+    async move {
+        if let Some(cleared) = cleared {
+            cleared.await?;
+        }
+        Ok(())
+    }
+}
+
+/// Resolve with the localStorage items of `origin`, e.g. `https://example.com`, as key value
+/// pairs.
+///
+/// The `DOMStorage` domain is enabled for the request and disabled again afterwards.
+pub fn get_local_storage(
+    host: &BrowserHost,
+    origin: &str,
+) -> impl Future<Output = Result<Vec<(String, String)>, DevToolsError>> {
+    // The methods are sent right away, and DevTools runs them in order.
+    let enabled = host.execute_dev_tools("DOMStorage.enable", None);
+    let items = host.execute_dev_tools("DOMStorage.getDOMStorageItems", storage_id(origin));
+    let disabled = host.execute_dev_tools("DOMStorage.disable", None);
+    async move {
+        enabled.await?;
+        let items = items.await;
+        let _ = disabled.await;
+        local_storage_items(&items?)
+    }
+}
+
+/// The `{"storageId": {"securityOrigin": origin, "isLocalStorage": true}}` parameters.
+fn storage_id(origin: &str) -> Option<DictionaryValue> {
+    let storage_id = dictionary_value_create()?;
+    storage_id.set_string(Some(&key("securityOrigin")), Some(&key(origin)));
+    storage_id.set_bool(Some(&key("isLocalStorage")), 1);
+    let params = dictionary_value_create()?;
+    params.set_dictionary(Some(&key("storageId")), Some(&storage_id));
+    Some(params)
+}
+
+/// The `[key, value]` entries of a `DOMStorage.getDOMStorageItems` result.
+fn local_storage_items(result: &[u8]) -> Result<Vec<(String, String)>, DevToolsError> {
+    let result = json::parse(result)?;
+    json::elements(&result, &["entries"])?
+        .iter()
+        .map(|entry| {
+            let mut entry = json::elements(entry, &[])?.into_iter();
+            match (entry.next(), entry.next(), entry.next()) {
+                (Some(key), Some(value), None) => {
+                    Ok((json::string(&key, &[])?, json::string(&value, &[])?))
+                }
+                _ => Err(DevToolsError::InvalidResponse),
+            }
+        })
+        .collect()
+}
+
+/// A cookie of a `Network.getAllCookies` result.
+fn cookie(json: &Value) -> Result<Cookie, DevToolsError> {
+    let string = |name| {
+        json::string(json, &[name]).map(|value| CefString::from(&CefStringUtf8::from(&*value)))
+    };
+    let session = json::boolean(json, &["session"])?;
+    Ok(Cookie {
+        name: string("name")?,
+        value: string("value")?,
+        domain: string("domain")?,
+        path: string("path")?,
+        secure: json::boolean(json, &["secure"])?.into(),
+        httponly: json::boolean(json, &["httpOnly"])?.into(),
+        creation: Default::default(),
+        last_access: Default::default(),
+        has_expires: (!session).into(),
+        expires: if session {
+            Default::default()
+        } else {
+            basetime(json::number(json, &["expires"])?)
+        },
+        same_site: same_site(json::string(json, &["sameSite"]).ok().as_deref()),
+        priority: priority(json::string(json, &["priority"]).ok().as_deref()),
+    })
+}
+
+/// Microseconds between the Windows epoch of [Basetime] and the Unix epoch.
+const UNIX_EPOCH_MICROS: i64 = 11_644_473_600_000_000;
+
+/// A time in seconds since the Unix epoch, as DevTools reports it, as a [Basetime].
+fn basetime(seconds: f64) -> Basetime {
+    Basetime {
+        val: UNIX_EPOCH_MICROS + (seconds * 1_000_000.0) as i64,
+    }
+}
+
+fn same_site(same_site: Option<&str>) -> CookieSameSite {
+    match same_site {
+        Some("None") => cef_cookie_same_site_t::CEF_COOKIE_SAME_SITE_NO_RESTRICTION,
+        Some("Lax") => cef_cookie_same_site_t::CEF_COOKIE_SAME_SITE_LAX_MODE,
+        Some("Strict") => cef_cookie_same_site_t::CEF_COOKIE_SAME_SITE_STRICT_MODE,
+        _ => cef_cookie_same_site_t::CEF_COOKIE_SAME_SITE_UNSPECIFIED,
+    }
+    .into()
+}
+
+fn priority(priority: Option<&str>) -> CookiePriority {
+    match priority {
+        Some("Low") => cef_cookie_priority_t::CEF_COOKIE_PRIORITY_LOW,
+        Some("High") => cef_cookie_priority_t::CEF_COOKIE_PRIORITY_HIGH,
+        _ => cef_cookie_priority_t::CEF_COOKIE_PRIORITY_MEDIUM,
+    }
+    .into()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_storage_types() {
+        assert_eq!(
+            (StorageTypes::LOCAL_STORAGE | StorageTypes::COOKIES).as_param(),
+            "cookies,local_storage"
+        );
+        assert_eq!(
+            StorageTypes::all().as_param(),
+            "cookies,file_systems,indexeddb,local_storage,shader_cache,websql,service_workers,cache_storage"
+        );
+        assert!(StorageTypes::all().contains(StorageTypes::CACHE_STORAGE));
+        assert!(!StorageTypes::INDEXED_DB.contains(StorageTypes::all()));
+        assert!(StorageTypes::empty().is_empty());
+    }
+
+    #[test]
+    fn test_local_storage_items() {
+        let result = br#"{"entries": [["theme", "dark"], ["json", "{\"a\": [1]}"]]}"#;
+        assert_eq!(
+            local_storage_items(result).ok(),
+            Some(vec![
+                ("theme".to_string(), "dark".to_string()),
+                ("json".to_string(), r#"{"a": [1]}"#.to_string()),
+            ])
+        );
+        assert_eq!(
+            local_storage_items(br#"{"entries": []}"#).ok(),
+            Some(vec![])
+        );
+        assert!(local_storage_items(br#"{"entries": [["key"]]}"#).is_err());
+    }
+
+    #[test]
+    fn test_basetime() {
+        assert_eq!(basetime(0.0).val, UNIX_EPOCH_MICROS);
+        assert_eq!(basetime(1.5).val, UNIX_EPOCH_MICROS + 1_500_000);
+    }
+
+    #[test]
+    fn test_cookie_attributes() {
+        assert_eq!(
+            same_site(Some("Strict")),
+            cef_cookie_same_site_t::CEF_COOKIE_SAME_SITE_STRICT_MODE.into()
+        );
+        assert_eq!(same_site(None), CookieSameSite::default());
+        assert_eq!(
+            priority(Some("High")),
+            cef_cookie_priority_t::CEF_COOKIE_PRIORITY_HIGH.into()
+        );
+        assert_eq!(
+            priority(None),
+            cef_cookie_priority_t::CEF_COOKIE_PRIORITY_MEDIUM.into()
+        );
+    }
+
+    #[test]
+    fn test_json_boolean() {
+        let json = json::parse(br#"{"secure": true, "session": false, "expires": 1}"#).unwrap();
+        assert_eq!(json::boolean(&json, &["secure"]).ok(), Some(true));
+        assert_eq!(json::boolean(&json, &["session"]).ok(), Some(false));
+        assert!(json::boolean(&json, &["expires"]).is_err());
+    }
+}
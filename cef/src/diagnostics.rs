@@ -0,0 +1,286 @@
+//! Diagnostics module
+//!
+//! The bindings don't fail when CEF gives them something unexpected. A method CEF left null
+//! returns a default value, an unknown enum value maps to a fallback variant, and a string which
+//! isn't valid UTF-16 is converted lossily. [set_handler] installs a hook which is told about each
+//! of these fallbacks as a [Diagnostic], e.g. to log them or to fail a test.
+//!
+//! Without a handler, debug builds with the `tracing` feature log the diagnostics as warnings, and
+//! other builds ignore them.
+
+use std::{
+    fmt::{self, Debug, Display, Formatter},
+    panic::Location,
+    sync::{Arc, RwLock},
+};
+
+/// What kind of fallback a [Diagnostic] reports.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DiagnosticCategory {
+    /// CEF left a method of an object null, so the wrapper returned a default value.
+    NullFunction,
+    /// A string from CEF wasn't valid UTF-16, so invalid characters were replaced.
+    StringConversion,
+    /// CEF returned an enum value this version of the bindings doesn't know.
+    UnknownEnumValue,
+    /// An object was used after CEF was shut down.
+    AfterShutdown,
+    /// [crate::context::Context] had to close a browser or found an object still referenced
+    /// when it shut CEF down.
+    ForcedTeardown,
+    /// A [crate::thread::UiBound] was used off the UI thread inside a handler method checked by
+    /// [crate::thread::check_threads].
+    WrongThread,
+    /// An [crate::pump::ExternalPump] did the work CEF asked for later than its
+    /// [crate::pump::ExternalPump::warn_after] threshold.
+    SlowMessagePump,
+    /// A [crate::render::PreloadScript] threw an exception in the render process.
+    PreloadScript,
+}
+
+impl Display for DiagnosticCategory {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NullFunction => write!(f, "null function"),
+            Self::StringConversion => write!(f, "string conversion"),
+            Self::UnknownEnumValue => write!(f, "unknown enum value"),
+            Self::AfterShutdown => write!(f, "use after shutdown"),
+            Self::ForcedTeardown => write!(f, "forced teardown"),
+            Self::WrongThread => write!(f, "wrong thread"),
+            Self::SlowMessagePump => write!(f, "slow message pump"),
+            Self::PreloadScript => write!(f, "preload script"),
+        }
+    }
+}
+
+/// A fallback of the bindings, with the location in this crate where it happened.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub category: DiagnosticCategory,
+    pub message: String,
+    pub location: &'static Location<'static>,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} at {}",
+            self.category, self.message, self.location
+        )
+    }
+}
+
+type Handler = Arc<dyn Fn(Diagnostic) + Send + Sync>;
+
+static HANDLER: RwLock<Option<Handler>> = RwLock::new(None);
+
+/// Call `handler` with every [Diagnostic] of the bindings, on the thread where the fallback
+/// happened, instead of the default handler.
+pub fn set_handler(handler: impl Fn(Diagnostic) + Send + Sync + 'static) {
+    let mut current = HANDLER.write().unwrap_or_else(|err| err.into_inner());
+    *current = Some(Arc::new(handler));
+}
+
+/// Restore the default handler.
+pub fn clear_handler() {
+    let mut current = HANDLER.write().unwrap_or_else(|err| err.into_inner());
+    *current = None;
+}
+
+/// Report a fallback at the location of the caller.
+#[track_caller]
+pub(crate) fn report(category: DiagnosticCategory, message: impl Display) {
+    let diagnostic = Diagnostic {
+        category,
+        message: message.to_string(),
+        location: Location::caller(),
+    };
+    // Call the handler outside of the lock, so it may replace itself.
+    let handler = HANDLER
+        .read()
+        .unwrap_or_else(|err| err.into_inner())
+        .clone();
+    match handler {
+        Some(handler) => handler(diagnostic),
+        None => default_handler(diagnostic),
+    }
+}
+
+/// Report that the generated wrapper of `function` found it null, and return the default value the
+/// wrapper returns instead. The generated code calls this.
+#[track_caller]
+pub(crate) fn null_function<T: Default>(function: &str) -> T {
+    report(
+        DiagnosticCategory::NullFunction,
+        format_args!("{function} is null"),
+    );
+    T::default()
+}
+
+/// Report that `value` is unknown, and return `fallback`.
+#[track_caller]
+pub(crate) fn unknown_enum_value<T, U: Debug>(value: U, fallback: T) -> T {
+    report(
+        DiagnosticCategory::UnknownEnumValue,
+        format_args!(
+            "unknown {} value {value:?}, using {}",
+            std::any::type_name::<U>(),
+            std::any::type_name::<T>()
+        ),
+    );
+    fallback
+}
+
+#[cfg(all(feature = "tracing", debug_assertions))]
+fn default_handler(diagnostic: Diagnostic) {
+    tracing::warn!(
+        category = %diagnostic.category,
+        location = %diagnostic.location,
+        "{}",
+        diagnostic.message
+    );
+}
+
+#[cfg(not(all(feature = "tracing", debug_assertions)))]
+fn default_handler(_diagnostic: Diagnostic) {}
+
+/// Run `f`, and return the diagnostics it reported on this thread.
+#[cfg(test)]
+pub(crate) fn collect(f: impl FnOnce()) -> Vec<Diagnostic> {
+    use std::sync::Mutex;
+
+    /// The tests share the global handler.
+    static LOCK: Mutex<()> = Mutex::new(());
+
+    let _lock = LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    let collected = Arc::new(Mutex::new(vec![]));
+    let thread = std::thread::current().id();
+    set_handler({
+        let collected = collected.clone();
+        move |diagnostic| {
+            if std::thread::current().id() == thread {
+                collected.lock().unwrap().push(diagnostic);
+            }
+        }
+    });
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(f));
+    clear_handler();
+    if let Err(panic) = result {
+        std::panic::resume_unwind(panic);
+    }
+    let collected = collected.lock().unwrap().clone();
+    collected
+}
+
+#[cfg(test)]
+mod test {
+    use std::{os::raw::c_int, ptr};
+
+    use cef_sys::{_cef_dev_tools_message_observer_t, _cef_string_utf16_t};
+
+    use super::*;
+    use crate::{
+        rc::{wrap_rc, RcImpl},
+        Browser, CefStringUtf16, DevToolsMessageObserver, ImplDevToolsMessageObserver,
+    };
+
+    struct Observer {
+        object: *mut RcImpl<_cef_dev_tools_message_observer_t, Self>,
+    }
+
+    wrap_rc!(
+        Observer,
+        WrapDevToolsMessageObserver,
+        _cef_dev_tools_message_observer_t
+    );
+
+    impl ImplDevToolsMessageObserver for Observer {
+        fn on_dev_tools_message(
+            &self,
+            _browser: Option<&impl crate::ImplBrowser>,
+            _message: Option<&[u8]>,
+        ) -> c_int {
+            1
+        }
+
+        fn get_raw(&self) -> *mut _cef_dev_tools_message_observer_t {
+            self.object as *mut _
+        }
+    }
+
+    #[test]
+    fn test_null_function() {
+        let observer = DevToolsMessageObserver::new(Observer {
+            object: ptr::null_mut(),
+        });
+        let diagnostics = collect(|| {
+            assert_eq!(observer.on_dev_tools_message(None::<&Browser>, None), 1);
+        });
+        assert!(diagnostics.is_empty());
+
+        unsafe {
+            (*ImplDevToolsMessageObserver::get_raw(&observer)).on_dev_tools_message = None;
+        }
+        let diagnostics = collect(|| {
+            assert_eq!(observer.on_dev_tools_message(None::<&Browser>, None), 0);
+        });
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].category, DiagnosticCategory::NullFunction);
+        assert_eq!(
+            diagnostics[0].message,
+            "DevToolsMessageObserver::on_dev_tools_message is null"
+        );
+        assert!(diagnostics[0].location.file().ends_with("bindings.rs"));
+    }
+
+    #[test]
+    fn test_generated_enum() {
+        let severity = crate::LogSeverity::from(cef_sys::cef_log_severity_t::LOGSEVERITY_ERROR);
+        assert_eq!(severity, crate::LogSeverity::Error);
+        let raw: cef_sys::cef_log_severity_t = crate::LogSeverity::Disable.into();
+        assert_eq!(raw, cef_sys::cef_log_severity_t::LOGSEVERITY_DISABLE);
+        assert_eq!(crate::LogSeverity::default(), crate::LogSeverity::Default);
+
+        assert_eq!(
+            crate::Errorcode::try_from(-3),
+            Ok(crate::Errorcode::Aborted)
+        );
+        assert_eq!(crate::LogSeverity::try_from(42), Err(42));
+    }
+
+    #[test]
+    fn test_string_conversion() {
+        let unpaired_surrogate = [0x48, 0xd800, 0x69];
+        let value = _cef_string_utf16_t {
+            str_: unpaired_surrogate.as_ptr() as *mut _,
+            length: unpaired_surrogate.len(),
+            dtor: None,
+        };
+        let mut text = String::new();
+        let diagnostics = collect(|| {
+            text = CefStringUtf16::from(ptr::from_ref(&value)).to_string();
+        });
+        assert_eq!(text, "H\u{fffd}i");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].category,
+            DiagnosticCategory::StringConversion
+        );
+        assert!(diagnostics[0].location.file().ends_with("string.rs"));
+    }
+
+    #[test]
+    fn test_handler_replaced() {
+        let diagnostics = collect(|| {
+            report(DiagnosticCategory::AfterShutdown, "first");
+            set_handler(|_| {});
+            report(DiagnosticCategory::AfterShutdown, "ignored");
+        });
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].to_string(),
+            format!("use after shutdown: first at {}", diagnostics[0].location)
+        );
+    }
+}
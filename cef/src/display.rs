@@ -0,0 +1,150 @@
+//! Display handler module
+
+use std::{os::raw::c_int, sync::Arc};
+
+use cef_sys::_cef_display_handler_t;
+
+use crate::{
+    rc::{wrap_rc, RcImpl},
+    *,
+};
+
+type AddressChangeCallback = Arc<dyn Fn(&Browser, &Frame, &str) + Send + Sync>;
+
+type AutoResizeCallback = Arc<dyn Fn(&Browser, &Size) -> bool + Send + Sync>;
+
+type CursorChangeCallback =
+    Arc<dyn Fn(&Browser, cursor::CursorType, Option<cursor::CursorInfo>) -> bool + Send + Sync>;
+
+/// Builder of a [DisplayHandler] from closures.
+///
+/// Return the built handler from [ImplClient::get_display_handler].
+#[derive(Clone, Default)]
+pub struct DisplayHandlerBuilder {
+    on_address_change: Option<AddressChangeCallback>,
+    on_auto_resize: Option<AutoResizeCallback>,
+    on_cursor_change: Option<CursorChangeCallback>,
+}
+
+impl DisplayHandlerBuilder {
+    /// Create a builder with the default behavior for every callback.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Called with the new URL when the address of a frame changes.
+    pub fn on_address_change(
+        mut self,
+        callback: impl Fn(&Browser, &Frame, &str) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_address_change = Some(Arc::new(callback));
+        self
+    }
+
+    /// Called with the new content size when auto-resize is enabled with
+    /// [BrowserHost::set_auto_resize_enabled]. Return `true` if the size change was handled, or
+    /// `false` to let CEF resize the browser.
+    pub fn on_auto_resize(
+        mut self,
+        callback: impl Fn(&Browser, &Size) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.on_auto_resize = Some(Arc::new(callback));
+        self
+    }
+
+    /// Called when the cursor changes, with the image of [cursor::CursorType::Custom] cursors.
+    /// Return `true` if the cursor change was handled, e.g. by an off-screen rendering host
+    /// drawing the cursor itself, or `false` to let CEF set the cursor of the native window.
+    pub fn on_cursor_change(
+        mut self,
+        callback: impl Fn(&Browser, cursor::CursorType, Option<cursor::CursorInfo>) -> bool
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        self.on_cursor_change = Some(Arc::new(callback));
+        self
+    }
+
+    /// `false` if no callback was set, so the built handler only runs the defaults.
+    pub fn has_overrides(&self) -> bool {
+        self.on_address_change.is_some()
+            || self.on_auto_resize.is_some()
+            || self.on_cursor_change.is_some()
+    }
+
+    /// Build the [DisplayHandler].
+    pub fn build(self) -> DisplayHandler {
+        DisplayHandler::new(BuiltDisplayHandler {
+            object: std::ptr::null_mut(),
+            builder: self,
+        })
+    }
+}
+
+/// The built handler, or `None` without [overrides](DisplayHandlerBuilder::has_overrides), see
+/// [crate::client::ClientBuilder::display_handler].
+impl From<DisplayHandlerBuilder> for Option<DisplayHandler> {
+    fn from(builder: DisplayHandlerBuilder) -> Self {
+        builder.has_overrides().then(|| builder.build())
+    }
+}
+
+struct BuiltDisplayHandler {
+    object: *mut RcImpl<_cef_display_handler_t, Self>,
+    builder: DisplayHandlerBuilder,
+}
+
+wrap_rc!(
+    BuiltDisplayHandler,
+    WrapDisplayHandler,
+    _cef_display_handler_t,
+    builder
+);
+
+impl ImplDisplayHandler for BuiltDisplayHandler {
+    fn on_address_change(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        frame: Option<&impl ImplFrame>,
+        url: Option<&CefStringUtf16>,
+    ) {
+        let (Some(callback), Some(browser), Some(frame)) =
+            (&self.builder.on_address_change, browser, frame)
+        else {
+            return;
+        };
+        let url = url.map(|url| url.to_string()).unwrap_or_default();
+        callback(&Browser::from_impl(browser), &Frame::from_impl(frame), &url);
+    }
+
+    fn on_auto_resize(&self, browser: Option<&impl ImplBrowser>, new_size: Option<&Size>) -> c_int {
+        let (Some(callback), Some(browser), Some(new_size)) =
+            (&self.builder.on_auto_resize, browser, new_size)
+        else {
+            return 0;
+        };
+        callback(&Browser::from_impl(browser), new_size) as c_int
+    }
+
+    fn on_cursor_change(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        _cursor: ::std::os::raw::c_ulong,
+        type_: CursorType,
+        custom_cursor_info: Option<&CursorInfo>,
+    ) -> c_int {
+        let (Some(callback), Some(browser)) = (&self.builder.on_cursor_change, browser) else {
+            return 0;
+        };
+        let type_ = cursor::CursorType::from(type_);
+        let custom_cursor_info = custom_cursor_info
+            .filter(|_| type_ == cursor::CursorType::Custom)
+            .map(cursor::CursorInfo::from);
+        callback(&Browser::from_impl(browser), type_, custom_cursor_info) as c_int
+    }
+
+    fn get_raw(&self) -> *mut _cef_display_handler_t {
+        self.object as *mut _cef_display_handler_t
+    }
+}
@@ -0,0 +1,235 @@
+//! DOM module
+//!
+//! [DomSnapshot] reads the document of a browser with the DevTools `DOM` domain, e.g. to check
+//! the structure and accessibility attributes of a page in a test.
+//!
+//! Like [BrowserHost::execute_dev_tools], every function must be called and its future polled on
+//! the UI thread after the first navigation of the browser.
+
+use std::{collections::HashMap, future::Future};
+
+use crate::{
+    devtools::{json, key, DevToolsError},
+    dictionary_value_create, BrowserHost, DictionaryValue, ImplDictionaryValue, Value,
+};
+
+/// The document of a browser when it was captured.
+#[derive(Clone)]
+pub struct DomSnapshot {
+    /// The HTML of the whole document.
+    pub outer_html: String,
+    /// The number of nodes in the document, including text and comment nodes.
+    pub node_count: u32,
+    host: BrowserHost,
+    root_node_id: Option<u32>,
+}
+
+/// An element found by [DomSnapshot::find_by_selector].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DomNode {
+    /// The DevTools id of the node, valid until the document changes.
+    pub node_id: u32,
+    /// The lower case tag name, e.g. `h1`.
+    pub tag_name: String,
+    pub attributes: HashMap<String, String>,
+}
+
+impl DomSnapshot {
+    /// Capture the document loaded in `host`. The snapshot is empty if DevTools fails, e.g.
+    /// before the first navigation.
+    pub fn capture(host: &BrowserHost) -> impl Future<Output = DomSnapshot> {
+        let host = host.clone();
+        // The whole tree, so the nodes can be counted.
+        let document = host.execute_dev_tools("DOM.getDocument", depth_params(-1));
+
+        async move {
+            let document = document
+                .await
+                .and_then(|document| json::parse(&document))
+                .ok();
+            let root_node_id = document
+                .as_ref()
+                .and_then(|document| json::number(document, &["root", "nodeId"]).ok())
+                .map(|node_id| node_id as u32);
+            let node_count = document
+                .as_ref()
+                .and_then(|document| json::value(document, &["root"]).ok())
+                .map(|root| node_count(&root))
+                .unwrap_or_default();
+
+            let mut outer_html = String::new();
+            if let Some(root_node_id) = root_node_id {
+                let result = host
+                    .execute_dev_tools("DOM.getOuterHTML", node_params(root_node_id))
+                    .await;
+                if let Ok(result) = result.and_then(|result| json::parse(&result)) {
+                    outer_html = json::string(&result, &["outerHTML"]).unwrap_or_default();
+                }
+            }
+
+            Self {
+                outer_html,
+                node_count,
+                host,
+                root_node_id,
+            }
+        }
+    }
+
+    /// The elements of the document which match the CSS `selector`, in document order. Empty if
+    /// the document changed since the snapshot was captured.
+    pub fn find_by_selector(&self, selector: &str) -> impl Future<Output = Vec<DomNode>> {
+        let host = self.host.clone();
+        let node_ids = self.root_node_id.map(|root_node_id| {
+            host.execute_dev_tools(
+                "DOM.querySelectorAll",
+                selector_params(root_node_id, selector),
+            )
+        });
+
+        async move {
+            let Some(node_ids) = node_ids else {
+                return vec![];
+            };
+            let Ok(node_ids) = node_ids.await.and_then(|result| query_node_ids(&result)) else {
+                return vec![];
+            };
+            // DevTools runs the methods in order, so send them all before waiting.
+            let descriptions: Vec<_> = node_ids
+                .into_iter()
+                .map(|node_id| {
+                    let description =
+                        host.execute_dev_tools("DOM.describeNode", node_params(node_id));
+                    (node_id, description)
+                })
+                .collect();
+
+            let mut nodes = vec![];
+            for (node_id, description) in descriptions {
+                if let Ok(node) = description
+                    .await
+                    .and_then(|result| dom_node(node_id, &result))
+                {
+                    nodes.push(node);
+                }
+            }
+            nodes
+        }
+    }
+}
+
+fn depth_params(depth: i32) -> Option<DictionaryValue> {
+    let params = dictionary_value_create()?;
+    params.set_int(Some(&key("depth")), depth);
+    Some(params)
+}
+
+fn node_params(node_id: u32) -> Option<DictionaryValue> {
+    let params = dictionary_value_create()?;
+    params.set_int(Some(&key("nodeId")), node_id as i32);
+    Some(params)
+}
+
+fn selector_params(node_id: u32, selector: &str) -> Option<DictionaryValue> {
+    let params = node_params(node_id)?;
+    params.set_string(Some(&key("selector")), Some(&key(selector)));
+    Some(params)
+}
+
+/// The number of nodes in the tree of a protocol `Node`, including the documents of frames and
+/// the shadow roots which `DOM.getDocument` reports.
+fn node_count(node: &Value) -> u32 {
+    let children = |name| json::elements(node, &[name]).unwrap_or_default();
+    let content_document = json::value(node, &["contentDocument"])
+        .map(|document| node_count(&document))
+        .unwrap_or_default();
+    1 + content_document
+        + children("children")
+            .iter()
+            .chain(&children("shadowRoots"))
+            .map(node_count)
+            .sum::<u32>()
+}
+
+/// The `nodeIds` of a `DOM.querySelectorAll` result.
+fn query_node_ids(result: &[u8]) -> Result<Vec<u32>, DevToolsError> {
+    let result = json::parse(result)?;
+    json::elements(&result, &["nodeIds"])?
+        .iter()
+        .map(|node_id| json::number(node_id, &[]).map(|node_id| node_id as u32))
+        .collect()
+}
+
+/// The element of a `DOM.describeNode` result.
+fn dom_node(node_id: u32, result: &[u8]) -> Result<DomNode, DevToolsError> {
+    let node = json::value(&json::parse(result)?, &["node"])?;
+    // Attributes are a flat list of names and values.
+    let attributes = json::elements(&node, &["attributes"]).unwrap_or_default();
+    let attributes = attributes
+        .chunks_exact(2)
+        .map(|pair| Ok((json::string(&pair[0], &[])?, json::string(&pair[1], &[])?)))
+        .collect::<Result<_, DevToolsError>>()?;
+    Ok(DomNode {
+        node_id,
+        tag_name: json::string(&node, &["localName"])?,
+        attributes,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// `DOM.getDocument` of `<h1 id="title" class="big">Hello</h1><!-- note -->`.
+    const DOCUMENT: &[u8] = br##"{"root": {"nodeId": 1, "nodeType": 9, "nodeName": "#document",
+        "localName": "", "childNodeCount": 1, "children": [
+            {"nodeId": 2, "nodeType": 1, "nodeName": "HTML", "localName": "html",
+             "childNodeCount": 2, "attributes": [], "children": [
+                {"nodeId": 3, "nodeType": 1, "nodeName": "HEAD", "localName": "head",
+                 "childNodeCount": 0, "attributes": []},
+                {"nodeId": 4, "nodeType": 1, "nodeName": "BODY", "localName": "body",
+                 "childNodeCount": 2, "attributes": [], "children": [
+                    {"nodeId": 5, "nodeType": 1, "nodeName": "H1", "localName": "h1",
+                     "childNodeCount": 1, "attributes": ["id", "title", "class", "big"],
+                     "children": [
+                        {"nodeId": 6, "nodeType": 3, "nodeName": "#text", "localName": "",
+                         "nodeValue": "Hello"}
+                     ]},
+                    {"nodeId": 7, "nodeType": 8, "nodeName": "#comment", "localName": "",
+                     "nodeValue": " note "}
+                 ]}
+             ]}
+        ], "documentURL": "data:text/html,", "baseURL": "data:text/html,"}}"##;
+
+    #[test]
+    fn test_node_count() {
+        let document = json::parse(DOCUMENT).unwrap();
+        assert_eq!(node_count(&json::value(&document, &["root"]).unwrap()), 7);
+        assert_eq!(json::number(&document, &["root", "nodeId"]).ok(), Some(1.0));
+
+        let frame =
+            br#"{"nodeId": 1, "contentDocument": {"nodeId": 2, "children": [{"nodeId": 3}]},
+            "shadowRoots": [{"nodeId": 4}]}"#;
+        assert_eq!(node_count(&json::parse(frame).unwrap()), 4);
+    }
+
+    #[test]
+    fn test_find_h1() {
+        assert_eq!(query_node_ids(br#"{"nodeIds": [5]}"#).ok(), Some(vec![5]));
+        assert_eq!(query_node_ids(br#"{"nodeIds": []}"#).ok(), Some(vec![]));
+
+        let description = br#"{"node": {"nodeId": 0, "backendNodeId": 12, "nodeType": 1,
+            "nodeName": "H1", "localName": "h1", "nodeValue": "", "childNodeCount": 1,
+            "attributes": ["id", "title", "class", "big"]}}"#;
+        let node = dom_node(5, description).unwrap();
+        assert_eq!(node.node_id, 5);
+        assert_eq!(node.tag_name, "h1");
+        assert_eq!(
+            node.attributes,
+            HashMap::from([
+                ("id".to_string(), "title".to_string()),
+                ("class".to_string(), "big".to_string()),
+            ])
+        );
+    }
+}
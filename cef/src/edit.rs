@@ -0,0 +1,440 @@
+//! Edit module
+//!
+//! Editing the text of the focused frame from the browser process. The render process runs the
+//! edit through the editing commands of the page, so it lands in the undo history and fires
+//! `input` events like typing does. Both processes need a [MessageDispatcher] with
+//! [MessageDispatcher::handle_edit_messages].
+//!
+//! Edits only apply to the focused element of the focused frame. If the page has no focus, e.g.
+//! because the window is in the background, the edit is silently dropped, so focus the browser
+//! first with [crate::ImplBrowserHost::set_focus].
+//!
+//! [BrowserHost::execute_command] runs the rich text [EditCommand]s in the focused frame, without
+//! a round trip through the render process.
+
+use std::os::raw::c_int;
+
+use cef_sys::{cef_key_event_type_t, cef_process_id_t};
+
+use crate::{
+    devtools::json, message, message::MessageDispatcher, value_create, BrowserHost, CefString,
+    CefStringUtf8, Frame, ImplBrowser, ImplBrowserHost, ImplFrame, ImplV8value, ImplValue,
+    KeyEvent, ProcessId, Value,
+};
+
+/// Message to the render process which replaces the selection with its string argument.
+pub const REPLACE_SELECTED_TEXT: &str = "cef-rs.edit.replace_selected_text";
+
+/// Message to the browser process when the page has no `insertText` command, which types its
+/// string argument with key events instead.
+pub const REPLACE_SELECTED_TEXT_FALLBACK: &str = "cef-rs.edit.replace_selected_text_fallback";
+
+const VKEY_DELETE: c_int = 0x2E;
+
+impl BrowserHost {
+    /// Replace the selection of the focused frame with `replacement`, or insert it at the caret.
+    ///
+    /// Returns `false` if there is no frame or the message couldn't be sent. The edit runs
+    /// asynchronously in the render process, see the [module documentation](self).
+    pub fn replace_selected_text(&self, replacement: &str) -> bool {
+        let Some(browser) = self.get_browser() else {
+            return false;
+        };
+        let Some(frame) = browser
+            .get_focused_frame()
+            .or_else(|| browser.get_main_frame())
+        else {
+            return false;
+        };
+        send_replace_selected_text(&frame, replacement)
+    }
+
+    /// Run `command` in the focused frame. Returns `false` if no frame has the focus, or the
+    /// script of the command couldn't be run.
+    ///
+    /// Like a key press, the command applies to the selection or the caret of the page, and
+    /// does nothing outside of editable content for the formatting commands.
+    pub fn execute_command(&self, command: EditCommand) -> bool {
+        let Some(frame) = self
+            .get_browser()
+            .and_then(|browser| browser.get_focused_frame())
+            .filter(Frame::is_focused)
+        else {
+            return false;
+        };
+        match command {
+            // The native commands also work where the page may not run `execCommand` itself,
+            // e.g. pasting from the clipboard.
+            EditCommand::SelectAll => frame.select_all(),
+            EditCommand::Undo => frame.undo(),
+            EditCommand::Redo => frame.redo(),
+            EditCommand::Cut => frame.cut(),
+            EditCommand::Copy => frame.copy(),
+            EditCommand::Paste => frame.paste(),
+            EditCommand::Delete => frame.del(),
+            command => return frame.execute_script(&command.script(), "", 0).is_ok(),
+        }
+        true
+    }
+}
+
+/// A rich text editing command of [BrowserHost::execute_command].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum EditCommand {
+    Bold,
+    Italic,
+    Underline,
+    Strikethrough,
+    /// Turn the selection into a link to the URL.
+    InsertLink(String),
+    /// Replace the selection with the text.
+    InsertText(String),
+    SelectAll,
+    Undo,
+    Redo,
+    Cut,
+    Copy,
+    Paste,
+    Delete,
+    /// Collapse the selection.
+    Unselect,
+}
+
+impl EditCommand {
+    /// The name of the command for `document.execCommand`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Bold => "bold",
+            Self::Italic => "italic",
+            Self::Underline => "underline",
+            Self::Strikethrough => "strikeThrough",
+            Self::InsertLink(_) => "createLink",
+            Self::InsertText(_) => "insertText",
+            Self::SelectAll => "selectAll",
+            Self::Undo => "undo",
+            Self::Redo => "redo",
+            Self::Cut => "cut",
+            Self::Copy => "copy",
+            Self::Paste => "paste",
+            Self::Delete => "delete",
+            Self::Unselect => "unselect",
+        }
+    }
+
+    /// The value argument of `document.execCommand`.
+    pub fn value(&self) -> Option<&str> {
+        match self {
+            Self::InsertLink(value) | Self::InsertText(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The script which runs the command.
+    fn script(&self) -> String {
+        let value = self.value().map(json::quote);
+        format!(
+            "document.execCommand('{}', false, {})",
+            self.name(),
+            value.as_deref().unwrap_or("null")
+        )
+    }
+}
+
+impl MessageDispatcher {
+    /// Handle [REPLACE_SELECTED_TEXT] in the render process, and
+    /// [REPLACE_SELECTED_TEXT_FALLBACK] in the browser process.
+    pub fn handle_edit_messages(self) -> Self {
+        self.on_message(REPLACE_SELECTED_TEXT, |_browser, frame, args| {
+            let replacement = string_arg(&args);
+            if !insert_text(&frame, &replacement) {
+                message::send(
+                    &frame,
+                    ProcessId::from(cef_process_id_t::PID_BROWSER),
+                    REPLACE_SELECTED_TEXT_FALLBACK,
+                    string_value(&replacement),
+                );
+            }
+        })
+        .on_message(REPLACE_SELECTED_TEXT_FALLBACK, |browser, _frame, args| {
+            let Some(host) = browser.get_host() else {
+                return;
+            };
+            for event in typing_events(&string_arg(&args)) {
+                host.send_key_event(Some(&event));
+            }
+        })
+    }
+}
+
+fn send_replace_selected_text(frame: &Frame, replacement: &str) -> bool {
+    frame.is_valid()
+        && message::send(
+            frame,
+            ProcessId::from(cef_process_id_t::PID_RENDERER),
+            REPLACE_SELECTED_TEXT,
+            string_value(replacement),
+        )
+}
+
+/// The script which replaces the selection, and returns `false` if the command is unsupported.
+fn insert_text_script(replacement: &str) -> String {
+    format!(
+        "document.execCommand('insertText', false, {})",
+        json::quote(replacement)
+    )
+}
+
+/// Run the `insertText` command in `frame`, `false` if it failed.
+fn insert_text(frame: &Frame, replacement: &str) -> bool {
+    let Some(context) = frame.get_v8context() else {
+        return false;
+    };
+    match context.eval_script(&insert_text_script(replacement), "", 0) {
+        Ok(Some(result)) => result.is_bool() != 0 && result.get_bool_value() != 0,
+        _ => false,
+    }
+}
+
+/// The key events which type `text` over the selection, or delete it if `text` is empty.
+fn typing_events(text: &str) -> Vec<KeyEvent> {
+    if text.is_empty() {
+        return [
+            cef_key_event_type_t::KEYEVENT_RAWKEYDOWN,
+            cef_key_event_type_t::KEYEVENT_KEYUP,
+        ]
+        .map(|type_| KeyEvent {
+            type_: type_.into(),
+            windows_key_code: VKEY_DELETE,
+            native_key_code: VKEY_DELETE,
+            ..Default::default()
+        })
+        .to_vec();
+    }
+    text.encode_utf16()
+        .map(|unit| KeyEvent {
+            type_: cef_key_event_type_t::KEYEVENT_CHAR.into(),
+            windows_key_code: unit as c_int,
+            character: unit,
+            unmodified_character: unit,
+            ..Default::default()
+        })
+        .collect()
+}
+
+fn string_value(value: &str) -> Option<Value> {
+    let result = value_create()?;
+    result.set_string(Some(&CefString::from(&CefStringUtf8::from(value))));
+    Some(result)
+}
+
+fn string_arg(args: &crate::value::ListValue) -> String {
+    args.get(0)
+        .and_then(|value| value.get_string())
+        .map(|value| value.to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use cef_sys::{_cef_browser_host_t, _cef_browser_t, _cef_frame_t};
+
+    use super::*;
+    use crate::{
+        rc::{wrap_rc, RcImpl},
+        Browser, CefStringUtf16, ImplListValue, ImplProcessMessage,
+    };
+
+    type Sent = Arc<Mutex<Vec<(cef_process_id_t, String, String)>>>;
+
+    struct FocusedFrame {
+        object: *mut RcImpl<_cef_frame_t, Self>,
+        sent: Sent,
+    }
+
+    impl ImplFrame for FocusedFrame {
+        fn is_valid(&self) -> c_int {
+            1
+        }
+
+        fn send_process_message(
+            &self,
+            target_process: ProcessId,
+            message: Option<&impl ImplProcessMessage>,
+        ) {
+            let message = message.unwrap();
+            let name = message.get_name().unwrap().to_string();
+            let arg = message
+                .get_argument_list()
+                .and_then(|args| args.get_string(0))
+                .map(|arg| arg.to_string())
+                .unwrap_or_default();
+            self.sent
+                .lock()
+                .unwrap()
+                .push((target_process.into(), name, arg));
+        }
+
+        fn get_raw(&self) -> *mut _cef_frame_t {
+            self.object as *mut _
+        }
+    }
+
+    wrap_rc!(FocusedFrame, WrapFrame, _cef_frame_t, sent);
+
+    type Commands = Arc<Mutex<Vec<String>>>;
+
+    /// A focused frame which records the edit commands run in it.
+    struct CommandFrame {
+        object: *mut RcImpl<_cef_frame_t, Self>,
+        commands: Commands,
+    }
+
+    impl CommandFrame {
+        fn record(&self, command: &str) {
+            self.commands.lock().unwrap().push(command.to_string());
+        }
+    }
+
+    impl ImplFrame for CommandFrame {
+        fn is_valid(&self) -> c_int {
+            1
+        }
+
+        fn is_focused(&self) -> c_int {
+            1
+        }
+
+        fn paste(&self) {
+            self.record("paste");
+        }
+
+        fn execute_java_script(
+            &self,
+            code: Option<&CefStringUtf16>,
+            _script_url: Option<&CefStringUtf16>,
+            _start_line: c_int,
+        ) {
+            self.record(&code.unwrap().to_string());
+        }
+
+        fn get_raw(&self) -> *mut _cef_frame_t {
+            self.object as *mut _
+        }
+    }
+
+    wrap_rc!(CommandFrame, WrapFrame, _cef_frame_t, commands);
+
+    struct CommandBrowser {
+        object: *mut RcImpl<_cef_browser_t, Self>,
+        focused: Option<Commands>,
+    }
+
+    impl ImplBrowser for CommandBrowser {
+        fn get_focused_frame(&self) -> Option<Frame> {
+            self.focused.clone().map(|commands| {
+                Frame::new(CommandFrame {
+                    object: std::ptr::null_mut(),
+                    commands,
+                })
+            })
+        }
+
+        fn get_raw(&self) -> *mut _cef_browser_t {
+            self.object as *mut _
+        }
+    }
+
+    wrap_rc!(CommandBrowser, WrapBrowser, _cef_browser_t, focused);
+
+    struct CommandHost {
+        object: *mut RcImpl<_cef_browser_host_t, Self>,
+        focused: Option<Commands>,
+    }
+
+    impl ImplBrowserHost for CommandHost {
+        fn get_browser(&self) -> Option<Browser> {
+            Some(Browser::new(CommandBrowser {
+                object: std::ptr::null_mut(),
+                focused: self.focused.clone(),
+            }))
+        }
+
+        fn get_raw(&self) -> *mut _cef_browser_host_t {
+            self.object as *mut _
+        }
+    }
+
+    wrap_rc!(CommandHost, WrapBrowserHost, _cef_browser_host_t, focused);
+
+    fn command_host(focused: Option<Commands>) -> BrowserHost {
+        BrowserHost::new(CommandHost {
+            object: std::ptr::null_mut(),
+            focused,
+        })
+    }
+
+    #[test]
+    fn test_send_replace_selected_text() {
+        let sent = Sent::default();
+        let frame = Frame::new(FocusedFrame {
+            object: std::ptr::null_mut(),
+            sent: sent.clone(),
+        });
+        assert!(send_replace_selected_text(&frame, "naïve \"quote\""));
+        assert_eq!(
+            *sent.lock().unwrap(),
+            [(
+                cef_process_id_t::PID_RENDERER,
+                REPLACE_SELECTED_TEXT.to_string(),
+                "naïve \"quote\"".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_insert_text_script() {
+        assert_eq!(
+            insert_text_script("it's \"ok\"\n"),
+            r#"document.execCommand('insertText', false, "it's \"ok\"\u000a")"#
+        );
+    }
+
+    #[test]
+    fn test_typing_events() {
+        let events = typing_events("a😀");
+        let characters: Vec<_> = events.iter().map(|event| event.character).collect();
+        assert_eq!(characters, "a😀".encode_utf16().collect::<Vec<_>>());
+        assert!(events
+            .iter()
+            .all(|event| event.type_ == crate::KeyEventType::Char));
+
+        let delete = typing_events("");
+        assert_eq!(delete.len(), 2);
+        assert!(delete
+            .iter()
+            .all(|event| event.windows_key_code == VKEY_DELETE));
+    }
+
+    #[test]
+    fn test_execute_command() {
+        assert!(!command_host(None).execute_command(EditCommand::Bold));
+
+        let commands = Commands::default();
+        let host = command_host(Some(commands.clone()));
+        assert!(host.execute_command(EditCommand::Bold));
+        assert!(host.execute_command(EditCommand::InsertLink(
+            "https://example.com/?q='1'".to_string()
+        )));
+        assert!(host.execute_command(EditCommand::Paste));
+        assert_eq!(
+            *commands.lock().unwrap(),
+            [
+                "document.execCommand('bold', false, null)",
+                r#"document.execCommand('createLink', false, "https://example.com/?q='1'")"#,
+                "paste",
+            ]
+        );
+    }
+}
@@ -0,0 +1,529 @@
+//! Eval module
+//!
+//! [Frame::eval] runs JavaScript in a frame and resolves with its result as JSON, which
+//! [crate::ImplFrame::execute_java_script] can't do. Promises are awaited, so an `async` function
+//! or a `fetch` can be evaluated as well. Requires the `serde` feature.
+//!
+//! The main frame evaluates through the DevTools method `Runtime.evaluate`. Other frames, and
+//! the main frame before DevTools is available, evaluate through a bridge of process messages
+//! instead, which needs a [MessageDispatcher] with [MessageDispatcher::handle_eval_messages] in
+//! both processes. A render process without it never answers, so the future stays pending.
+//!
+//! ```ignore
+//! let title = frame.eval("document.title").await?;
+//! let status = frame.eval("fetch('/health').then((r) => r.status)").await?;
+//! ```
+
+use std::{
+    collections::HashMap,
+    fmt,
+    future::Future,
+    os::raw::c_int,
+    sync::{
+        atomic::{AtomicBool, AtomicI32, Ordering},
+        Mutex,
+    },
+};
+
+use cef_sys::{_cef_v8handler_t, cef_process_id_t};
+use serde_json::Value as JsonValue;
+
+use crate::{
+    devtools::{json, key, DevToolsError},
+    dictionary_value_create,
+    future::{channel, Sender},
+    message::{self, MessageDispatcher},
+    rc::{wrap_rc, RcImpl},
+    v8value_create_function, v8value_create_string, value_create, CefStringUtf16, Frame,
+    ImplBrowser, ImplDictionaryValue, ImplFrame, ImplV8handler, ImplV8value, ImplValue, ProcessId,
+    V8handler, V8value, Value,
+};
+
+/// Message to the render process which evaluates a script, with the request id and the code.
+pub const EVAL: &str = "cef-rs.eval.eval";
+
+/// Message to the browser process with the result of [EVAL], with the request id, the
+/// [ReplyKind] and the JSON, error message or stack.
+pub const EVAL_RESULT: &str = "cef-rs.eval.result";
+
+/// Why [Frame::eval] failed.
+#[derive(Debug)]
+pub enum EvalError {
+    /// The script threw, or the promise it returned was rejected. `stack` is the stack trace of
+    /// a thrown `Error`, with the message on its first line.
+    Exception {
+        message: String,
+        stack: Option<String>,
+    },
+    /// The result has no JSON representation, e.g. a function, a cyclic object or `NaN`.
+    Serialization(String),
+    /// The frame is gone, or neither DevTools nor the bridge can evaluate in it.
+    Unavailable,
+    /// The DevTools method failed.
+    DevTools(DevToolsError),
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Exception { message, .. } => write!(f, "uncaught exception: {message}"),
+            Self::Serialization(message) => write!(f, "result is not JSON: {message}"),
+            Self::Unavailable => write!(f, "the frame can't evaluate scripts"),
+            Self::DevTools(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for EvalError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::DevTools(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<DevToolsError> for EvalError {
+    fn from(error: DevToolsError) -> Self {
+        match error {
+            // The protocol errors of `returnByValue`.
+            DevToolsError::Method(message)
+                if message.contains("returned by value")
+                    || message.contains("reference chain is too long") =>
+            {
+                Self::Serialization(message)
+            }
+            error => Self::DevTools(error),
+        }
+    }
+}
+
+type EvalResult = Result<JsonValue, EvalError>;
+
+/// Whether this process handles [EVAL_RESULT].
+static BRIDGE_INSTALLED: AtomicBool = AtomicBool::new(false);
+
+static NEXT_REQUEST_ID: AtomicI32 = AtomicI32::new(1);
+
+/// The requests sent to render processes which wait for [EVAL_RESULT].
+static PENDING: Mutex<Option<HashMap<c_int, Sender<EvalResult>>>> = Mutex::new(None);
+
+impl Frame {
+    /// Evaluate `code` in this frame, await the result if it is a promise, and resolve with it
+    /// as JSON. `undefined` resolves with `null`.
+    ///
+    /// Must be called on the UI thread, and the future polled there too. See the
+    /// [module documentation](self) for how the script gets to the frame.
+    pub fn eval(&self, code: &str) -> impl Future<Output = EvalResult> {
+        let devtools = self
+            .is_main()
+            .then(|| self.browser().and_then(|browser| browser.get_host()))
+            .flatten()
+            .map(|host| host.execute_dev_tools("Runtime.evaluate", evaluate_params(code)));
+        let frame = self.clone();
+        let code = code.to_string();
+
+        async move {
+            let bridge = BRIDGE_INSTALLED.load(Ordering::Relaxed);
+            if let Some(result) = devtools {
+                match result.await {
+                    Ok(result) => return evaluate_result(&result),
+                    // Fall back to the bridge.
+                    Err(DevToolsError::Unavailable) if bridge => {}
+                    Err(error) => return Err(error.into()),
+                }
+            }
+            if !bridge || !frame.is_valid() {
+                return Err(EvalError::Unavailable);
+            }
+            send_eval(&frame, &code)
+                .ok_or(EvalError::Unavailable)?
+                .await
+                .unwrap_or(Err(EvalError::Unavailable))
+        }
+    }
+}
+
+impl MessageDispatcher {
+    /// Handle [EVAL] in the render process, and [EVAL_RESULT] in the browser process, so
+    /// [Frame::eval] can evaluate in frames without DevTools.
+    pub fn handle_eval_messages(self) -> Self {
+        BRIDGE_INSTALLED.store(true, Ordering::Relaxed);
+        self.on_message(EVAL, |_browser, frame, args| {
+            let id = args.get(0).map(|id| id.get_int()).unwrap_or_default();
+            let code = args
+                .get(1)
+                .and_then(|code| code.get_string())
+                .map(|code| code.to_string())
+                .unwrap_or_default();
+            if let Err(message) = eval_in_renderer(&frame, id, &code) {
+                send_reply(&frame, id, ReplyKind::Exception, &message, "");
+            }
+        })
+        .on_message(EVAL_RESULT, |_browser, _frame, args| {
+            let arg = |index| {
+                args.get(index)
+                    .and_then(|value| value.get_string())
+                    .map(|value| value.to_string())
+                    .unwrap_or_default()
+            };
+            let id = args.get(0).map(|id| id.get_int()).unwrap_or_default();
+            let kind = args.get(1).map(|kind| kind.get_int()).unwrap_or_default();
+            let sender = PENDING
+                .lock()
+                .ok()
+                .and_then(|mut pending| pending.as_mut()?.remove(&id));
+            if let Some(sender) = sender {
+                sender.send(bridge_result(kind, arg(2), arg(3)));
+            }
+        })
+    }
+}
+
+fn evaluate_params(code: &str) -> Option<crate::DictionaryValue> {
+    let params = dictionary_value_create()?;
+    params.set_string(Some(&key("expression")), Some(&key(code)));
+    params.set_bool(Some(&key("awaitPromise")), 1);
+    params.set_bool(Some(&key("returnByValue")), 1);
+    Some(params)
+}
+
+/// The value of a `Runtime.evaluate` result, or its exception.
+fn evaluate_result(result: &[u8]) -> EvalResult {
+    let result = json::parse(result)?;
+    if let Ok(details) = json::value(&result, &["exceptionDetails"]) {
+        return Err(exception(&details));
+    }
+    let remote = json::value(&result, &["result"])?;
+    let text = |name| json::string(&remote, &[name]).ok();
+    let description = || text("description").unwrap_or_default();
+    match text("type").as_deref() {
+        Some("undefined") => return Ok(JsonValue::Null),
+        Some(type_ @ ("function" | "symbol")) => {
+            return Err(EvalError::Serialization(format!(
+                "{type_} {}",
+                description()
+            )))
+        }
+        _ => {}
+    }
+    if let Some(value) = text("unserializableValue") {
+        return Err(EvalError::Serialization(value));
+    }
+    let value =
+        json::value(&remote, &["value"]).map_err(|_| EvalError::Serialization(description()))?;
+    serde_json::from_slice(&json::write(&value)).map_err(|_| DevToolsError::InvalidResponse.into())
+}
+
+/// The [EvalError::Exception] of the `exceptionDetails` of a DevTools result.
+fn exception(details: &Value) -> EvalError {
+    let text = |path: &[&str]| json::string(details, path).ok();
+    let is_error = text(&["exception", "subtype"]).as_deref() == Some("error");
+    match text(&["exception", "description"]) {
+        Some(description) if is_error => {
+            let first_line = description.lines().next().unwrap_or_default();
+            // The description starts like `error.stack`, with `TypeError: ` before the message.
+            let message = text(&["exception", "className"])
+                .and_then(|class| first_line.strip_prefix(&format!("{class}: ")))
+                .unwrap_or(first_line)
+                .to_string();
+            let stack = description.contains('\n').then_some(description);
+            EvalError::Exception { message, stack }
+        }
+        description => EvalError::Exception {
+            message: text(&["exception", "value"])
+                .or(description)
+                .or_else(|| text(&["text"]))
+                .unwrap_or_default(),
+            stack: None,
+        },
+    }
+}
+
+/// What the render process sends with [EVAL_RESULT].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(i32)]
+enum ReplyKind {
+    Json = 0,
+    Exception = 1,
+    Serialization = 2,
+}
+
+/// Evaluates the code of its first argument after a microtask, and calls its second argument
+/// with a [ReplyKind] and the JSON, error message or stack.
+const BRIDGE_SCRIPT: &str = r#"(function (code, reply) {
+  Promise.resolve()
+    .then(() => (0, eval)(code))
+    .then(
+      (value) => {
+        if (value === undefined) {
+          return reply(0, "null", "");
+        }
+        if (typeof value === "function" || typeof value === "symbol") {
+          return reply(2, typeof value + " " + String(value), "");
+        }
+        let json;
+        try {
+          json = JSON.stringify(value);
+        } catch (error) {
+          return reply(2, String(error), "");
+        }
+        if (json === undefined) {
+          return reply(2, String(value), "");
+        }
+        if (typeof value === "number" && !Number.isFinite(value)) {
+          return reply(2, String(value), "");
+        }
+        reply(0, json, "");
+      },
+      (error) => {
+        if (error instanceof Error) {
+          reply(1, error.message, String(error.stack || ""));
+        } else {
+          reply(1, String(error), "");
+        }
+      },
+    );
+})"#;
+
+/// The result of [EVAL_RESULT] with `kind` and its string arguments.
+fn bridge_result(kind: c_int, text: String, stack: String) -> EvalResult {
+    match kind {
+        kind if kind == ReplyKind::Json as c_int => {
+            serde_json::from_str(&text).map_err(|error| EvalError::Serialization(error.to_string()))
+        }
+        kind if kind == ReplyKind::Exception as c_int => Err(EvalError::Exception {
+            message: text,
+            stack: (!stack.is_empty()).then_some(stack),
+        }),
+        _ => Err(EvalError::Serialization(text)),
+    }
+}
+
+/// Send [EVAL] for `code` to the render process of `frame`, and the completion of its result.
+fn send_eval(frame: &Frame, code: &str) -> Option<crate::future::Completion<EvalResult>> {
+    let id = NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed);
+    let (sender, completion) = channel();
+    PENDING
+        .lock()
+        .ok()?
+        .get_or_insert_with(Default::default)
+        .insert(id, sender);
+    let sent = message::send(
+        frame,
+        ProcessId::from(cef_process_id_t::PID_RENDERER),
+        EVAL,
+        [int_value(id), string_value(code)].into_iter().flatten(),
+    );
+    if !sent {
+        // Dropping the sender resolves the completion with `None`.
+        PENDING
+            .lock()
+            .ok()
+            .and_then(|mut pending| pending.as_mut()?.remove(&id));
+    }
+    Some(completion)
+}
+
+/// Run [BRIDGE_SCRIPT] with `code` in the V8 context of `frame`.
+fn eval_in_renderer(frame: &Frame, id: c_int, code: &str) -> Result<(), String> {
+    let context = frame
+        .get_v8context()
+        .ok_or_else(|| "The frame has no V8 context".to_string())?;
+    let run = context
+        .eval_script(BRIDGE_SCRIPT, "", 0)?
+        .ok_or_else(|| "Failed to create the bridge".to_string())?;
+    let reply = V8handler::new(EvalReply {
+        object: std::ptr::null_mut(),
+        frame: frame.clone(),
+        id,
+    });
+    let args = [
+        v8value_create_string(Some(&key(code))),
+        v8value_create_function(Some(&key("reply")), Some(&reply)),
+    ];
+    run.execute_function_with_context(Some(&context), Option::<&V8value>::None, Some(&args))
+        .map(|_| ())
+        .ok_or_else(|| "Failed to run the bridge".to_string())
+}
+
+fn send_reply(frame: &Frame, id: c_int, kind: ReplyKind, text: &str, stack: &str) {
+    message::send(
+        frame,
+        ProcessId::from(cef_process_id_t::PID_BROWSER),
+        EVAL_RESULT,
+        [
+            int_value(id),
+            int_value(kind as c_int),
+            string_value(text),
+            string_value(stack),
+        ]
+        .into_iter()
+        .flatten(),
+    );
+}
+
+/// The `reply` function which [BRIDGE_SCRIPT] calls with the result.
+struct EvalReply {
+    object: *mut RcImpl<_cef_v8handler_t, Self>,
+    frame: Frame,
+    id: c_int,
+}
+
+wrap_rc!(EvalReply, WrapV8handler, _cef_v8handler_t, frame, id);
+
+impl ImplV8handler for EvalReply {
+    fn execute(
+        &self,
+        _name: Option<&CefStringUtf16>,
+        _object: Option<&impl ImplV8value>,
+        arguments: Option<&[Option<impl ImplV8value>]>,
+        _retval: Option<&mut impl ImplV8value>,
+        _exception: Option<&mut CefStringUtf16>,
+    ) -> c_int {
+        let arguments = arguments.unwrap_or_default();
+        let string = |index: usize| {
+            arguments
+                .get(index)
+                .and_then(Option::as_ref)
+                .and_then(|value| value.get_string_value())
+                .map(|value| value.to_string())
+                .unwrap_or_default()
+        };
+        let kind = match arguments
+            .first()
+            .and_then(Option::as_ref)
+            .map(|kind| kind.get_int_value())
+        {
+            Some(0) => ReplyKind::Json,
+            Some(1) => ReplyKind::Exception,
+            _ => ReplyKind::Serialization,
+        };
+        send_reply(&self.frame, self.id, kind, &string(1), &string(2));
+        1
+    }
+
+    fn get_raw(&self) -> *mut _cef_v8handler_t {
+        self.object as *mut _
+    }
+}
+
+fn int_value(value: c_int) -> Option<Value> {
+    let result = value_create()?;
+    result.set_int(value);
+    Some(result)
+}
+
+fn string_value(value: &str) -> Option<Value> {
+    let result = value_create()?;
+    result.set_string(Some(&key(value)));
+    Some(result)
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    fn exception_message(result: EvalResult) -> (String, Option<String>) {
+        match result {
+            Err(EvalError::Exception { message, stack }) => (message, stack),
+            result => panic!("Expected an exception: {result:?}"),
+        }
+    }
+
+    #[test]
+    fn test_evaluate_result() {
+        let value = br#"{"result":{"type":"object","value":{"title":"Example","links":[1,2]}}}"#;
+        assert_eq!(
+            evaluate_result(value).unwrap(),
+            json!({"title": "Example", "links": [1, 2]})
+        );
+        let undefined = br#"{"result":{"type":"undefined"}}"#;
+        assert_eq!(evaluate_result(undefined).unwrap(), JsonValue::Null);
+
+        let nan =
+            br#"{"result":{"type":"number","unserializableValue":"NaN","description":"NaN"}}"#;
+        assert!(
+            matches!(evaluate_result(nan), Err(EvalError::Serialization(value)) if value == "NaN")
+        );
+        let function = br#"{"result":{"type":"function","className":"Function","description":"() => 1","value":{}}}"#;
+        assert!(matches!(
+            evaluate_result(function),
+            Err(EvalError::Serialization(_))
+        ));
+        assert!(matches!(
+            EvalError::from(DevToolsError::Method(
+                "Object couldn't be returned by value".to_string()
+            )),
+            EvalError::Serialization(_)
+        ));
+        assert!(matches!(
+            evaluate_result(b"not json"),
+            Err(EvalError::DevTools(DevToolsError::InvalidResponse))
+        ));
+    }
+
+    #[test]
+    fn test_evaluate_exception() {
+        let error = br#"{
+            "result": {"type": "object", "subtype": "error"},
+            "exceptionDetails": {
+                "text": "Uncaught",
+                "exception": {
+                    "type": "object",
+                    "subtype": "error",
+                    "className": "TypeError",
+                    "description": "TypeError: x is not a function\n    at <anonymous>:1:1"
+                }
+            }
+        }"#;
+        assert_eq!(
+            exception_message(evaluate_result(error)),
+            (
+                "x is not a function".to_string(),
+                Some("TypeError: x is not a function\n    at <anonymous>:1:1".to_string())
+            )
+        );
+
+        let thrown = br#"{
+            "result": {"type": "string", "value": "boom"},
+            "exceptionDetails": {
+                "text": "Uncaught (in promise)",
+                "exception": {"type": "string", "value": "boom"}
+            }
+        }"#;
+        assert_eq!(
+            exception_message(evaluate_result(thrown)),
+            ("boom".to_string(), None)
+        );
+    }
+
+    #[test]
+    fn test_bridge_result() {
+        assert_eq!(
+            bridge_result(ReplyKind::Json as c_int, "[1,\"a\"]".into(), "".into()).unwrap(),
+            json!([1, "a"])
+        );
+        assert_eq!(
+            exception_message(bridge_result(
+                ReplyKind::Exception as c_int,
+                "boom".into(),
+                "Error: boom\n    at f".into()
+            )),
+            (
+                "boom".to_string(),
+                Some("Error: boom\n    at f".to_string())
+            )
+        );
+        assert!(matches!(
+            bridge_result(
+                ReplyKind::Serialization as c_int,
+                "TypeError: cyclic object value".into(),
+                "".into()
+            ),
+            Err(EvalError::Serialization(_))
+        ));
+    }
+}
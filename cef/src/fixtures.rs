@@ -0,0 +1,233 @@
+//! Fake browsers and hosts shared by the unit tests.
+//!
+//! Build a [FakeBrowser] or [FakeHost], override what the test needs, and call `build` to get
+//! the wrapper the code under test expects. Anything which isn't overridden falls back to the
+//! defaults of [ImplBrowser] and [ImplBrowserHost].
+
+use std::{
+    os::raw::{c_int, c_ulong},
+    sync::{Arc, Mutex},
+};
+
+use cef_sys::{_cef_browser_host_t, _cef_browser_t};
+
+use crate::{
+    browser::BrowserId,
+    rc::{wrap_rc, RcImpl},
+    Browser, BrowserHost, CefStringUtf16, Client, DictionaryValue, ImplBrowser, ImplBrowserHost,
+    ImplDictionaryValue,
+};
+
+type Valid = Arc<dyn Fn() -> bool + Send + Sync>;
+type OnClose = Arc<dyn Fn() + Send + Sync>;
+type OnDevToolsMethod = Arc<dyn Fn(String, Option<DictionaryValue>) + Send + Sync>;
+
+pub(crate) struct FakeBrowser {
+    object: *mut RcImpl<_cef_browser_t, Self>,
+    id: BrowserId,
+    popup: bool,
+    valid: Option<Valid>,
+    host: Option<BrowserHost>,
+}
+
+impl FakeBrowser {
+    /// A browser which isn't valid, isn't a popup and has no host.
+    pub(crate) fn new(id: BrowserId) -> Self {
+        Self {
+            object: std::ptr::null_mut(),
+            id,
+            popup: false,
+            valid: None,
+            host: None,
+        }
+    }
+
+    pub(crate) fn popup(mut self, popup: bool) -> Self {
+        self.popup = popup;
+        self
+    }
+
+    /// Ask `valid` every time the browser is checked.
+    pub(crate) fn valid(mut self, valid: impl Fn() -> bool + Send + Sync + 'static) -> Self {
+        self.valid = Some(Arc::new(valid));
+        self
+    }
+
+    pub(crate) fn host(mut self, host: BrowserHost) -> Self {
+        self.host = Some(host);
+        self
+    }
+
+    pub(crate) fn build(self) -> Browser {
+        Browser::new(self)
+    }
+}
+
+impl ImplBrowser for FakeBrowser {
+    fn is_valid(&self) -> c_int {
+        self.valid.as_ref().is_some_and(|valid| valid()).into()
+    }
+
+    fn get_identifier(&self) -> c_int {
+        self.id
+    }
+
+    fn is_popup(&self) -> c_int {
+        self.popup.into()
+    }
+
+    fn get_host(&self) -> Option<BrowserHost> {
+        self.host.clone()
+    }
+
+    fn get_raw(&self) -> *mut _cef_browser_t {
+        self.object as *mut _
+    }
+}
+
+wrap_rc!(
+    FakeBrowser,
+    WrapBrowser,
+    _cef_browser_t,
+    id,
+    popup,
+    valid,
+    host
+);
+
+pub(crate) struct FakeHost {
+    object: *mut RcImpl<_cef_browser_host_t, Self>,
+    browser: Option<BrowserId>,
+    opener: BrowserId,
+    client: Option<Client>,
+    zoom_level: Arc<Mutex<f64>>,
+    window_handle: c_ulong,
+    on_close: Option<OnClose>,
+    on_dev_tools_method: Option<OnDevToolsMethod>,
+}
+
+impl FakeHost {
+    /// A host without a browser, opener or client, which ignores being closed.
+    pub(crate) fn new() -> Self {
+        Self {
+            object: std::ptr::null_mut(),
+            browser: None,
+            opener: 0,
+            client: None,
+            zoom_level: Default::default(),
+            window_handle: 0,
+            on_close: None,
+            on_dev_tools_method: None,
+        }
+    }
+
+    /// Return a [FakeBrowser] with the identifier `id` from `get_browser`.
+    pub(crate) fn browser(mut self, id: BrowserId) -> Self {
+        self.browser = Some(id);
+        self
+    }
+
+    pub(crate) fn opener(mut self, opener: BrowserId) -> Self {
+        self.opener = opener;
+        self
+    }
+
+    pub(crate) fn client(mut self, client: Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Keep the zoom level in `zoom_level`.
+    pub(crate) fn zoom_level(mut self, zoom_level: Arc<Mutex<f64>>) -> Self {
+        self.zoom_level = zoom_level;
+        self
+    }
+
+    /// Return `window_handle` from `get_window_handle`, as if the browser had a native window.
+    pub(crate) fn window_handle(mut self, window_handle: c_ulong) -> Self {
+        self.window_handle = window_handle;
+        self
+    }
+
+    /// Call `on_close` when the browser is told to close.
+    pub(crate) fn on_close(mut self, on_close: impl Fn() + Send + Sync + 'static) -> Self {
+        self.on_close = Some(Arc::new(on_close));
+        self
+    }
+
+    /// Call `on_dev_tools_method` with each DevTools method and its parameters.
+    pub(crate) fn on_dev_tools_method(
+        mut self,
+        on_dev_tools_method: impl Fn(String, Option<DictionaryValue>) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_dev_tools_method = Some(Arc::new(on_dev_tools_method));
+        self
+    }
+
+    pub(crate) fn build(self) -> BrowserHost {
+        BrowserHost::new(self)
+    }
+}
+
+impl ImplBrowserHost for FakeHost {
+    fn get_browser(&self) -> Option<Browser> {
+        self.browser.map(|id| FakeBrowser::new(id).build())
+    }
+
+    fn get_window_handle(&self) -> c_ulong {
+        self.window_handle
+    }
+
+    fn close_browser(&self, _force_close: c_int) {
+        if let Some(on_close) = &self.on_close {
+            on_close();
+        }
+    }
+
+    fn get_opener_identifier(&self) -> c_int {
+        self.opener
+    }
+
+    fn get_client(&self) -> Option<Client> {
+        self.client.clone()
+    }
+
+    fn get_zoom_level(&self) -> f64 {
+        *self.zoom_level.lock().unwrap()
+    }
+
+    fn set_zoom_level(&self, zoom_level: f64) {
+        *self.zoom_level.lock().unwrap() = zoom_level;
+    }
+
+    fn execute_dev_tools_method(
+        &self,
+        _message_id: c_int,
+        method: Option<&CefStringUtf16>,
+        params: Option<&impl ImplDictionaryValue>,
+    ) -> c_int {
+        let Some(on_dev_tools_method) = &self.on_dev_tools_method else {
+            return 0;
+        };
+        let method = method.map(|method| method.to_string()).unwrap_or_default();
+        on_dev_tools_method(method, params.map(DictionaryValue::from_impl));
+        1
+    }
+
+    fn get_raw(&self) -> *mut _cef_browser_host_t {
+        self.object as *mut _
+    }
+}
+
+wrap_rc!(
+    FakeHost,
+    WrapBrowserHost,
+    _cef_browser_host_t,
+    browser,
+    opener,
+    client,
+    zoom_level,
+    window_handle,
+    on_close,
+    on_dev_tools_method
+);
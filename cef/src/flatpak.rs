@@ -0,0 +1,124 @@
+//! Flatpak module
+//!
+//! Applications outside of a sandbox can share the CEF of the `dev.crabnebula.Platform` runtime
+//! instead of installing their own copy. [detect_crabnebula_runtime] asks `flatpak` where the
+//! runtime is installed, and [CefRuntimeInfo::apply_to_settings] points CEF at it.
+
+use std::{
+    env,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::{CefString, CefStringUtf8, Settings};
+
+/// The Flatpak runtime which ships CEF.
+pub const CRABNEBULA_RUNTIME: &str = "dev.crabnebula.Platform";
+
+/// A CEF installed by a Flatpak runtime.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CefRuntimeInfo {
+    /// The directory which contains `libcef.so` and its resources.
+    pub lib_path: PathBuf,
+    /// The version of the runtime, or its branch if it has no version.
+    pub version: String,
+}
+
+impl CefRuntimeInfo {
+    /// Use the CEF of the runtime: set `framework_dir_path` to [CefRuntimeInfo::lib_path], and
+    /// `browser_subprocess_path` to the current executable unless it is already set.
+    pub fn apply_to_settings(&self, settings: &mut Settings) {
+        settings.framework_dir_path = cef_path(&self.lib_path);
+        if settings.browser_subprocess_path.to_string().is_empty() {
+            if let Ok(exe) = env::current_exe() {
+                settings.browser_subprocess_path = cef_path(&exe);
+            }
+        }
+    }
+}
+
+/// Find the CEF of the [CRABNEBULA_RUNTIME] runtime by running `flatpak info`.
+///
+/// Returns `None` inside a Flatpak sandbox, where the runtime is mounted at `/usr` already, and if
+/// `flatpak` is missing or the runtime isn't installed.
+pub fn detect_crabnebula_runtime() -> Option<CefRuntimeInfo> {
+    if env::var_os("FLATPAK_ID").is_some() {
+        return None;
+    }
+    // The keys of `flatpak info` are translated, so ask for the untranslated ones.
+    let output = Command::new("flatpak")
+        .args(["info", CRABNEBULA_RUNTIME])
+        .env("LC_ALL", "C")
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_flatpak_info(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Parse the `Key: value` lines of `flatpak info`. The files of a runtime are in the `files`
+/// directory of its location, which is mounted at `/usr` inside the sandbox.
+fn parse_flatpak_info(output: &str) -> Option<CefRuntimeInfo> {
+    let field = |name: &str| {
+        output.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            let value = value.trim();
+            (key.trim() == name && !value.is_empty()).then_some(value)
+        })
+    };
+    let location = Path::new(field("Location")?);
+    let version = field("Version").or_else(|| field("Branch"))?;
+    Some(CefRuntimeInfo {
+        lib_path: location.join("files/lib"),
+        version: version.to_string(),
+    })
+}
+
+fn cef_path(path: &Path) -> CefString {
+    CefString::from(&CefStringUtf8::from(path.to_string_lossy().as_ref()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const INFO: &str = "
+dev.crabnebula.Platform - Runtime with CEF
+
+          ID: dev.crabnebula.Platform
+         Ref: runtime/dev.crabnebula.Platform/x86_64/23.08
+        Arch: x86_64
+      Branch: 23.08
+     Version: 122.1.10
+     License: BSD-3-Clause
+      Origin: flathub
+Installation: system
+   Installed: 312.4 MB
+
+      Commit: 5d3c2b1a0f9e8d7c6b5a4f3e2d1c0b9a8f7e6d5c4b3a2f1e0d9c8b7a6f5e4d3c
+     Subject: Update CEF to 122.1.10 (a1b2c3d4)
+        Date: 2024-03-01 10:00:00 +0000
+    Location: /var/lib/flatpak/runtime/dev.crabnebula.Platform/x86_64/23.08/active
+";
+
+    #[test]
+    fn test_parse_flatpak_info() {
+        assert_eq!(
+            parse_flatpak_info(INFO),
+            Some(CefRuntimeInfo {
+                lib_path:
+                    "/var/lib/flatpak/runtime/dev.crabnebula.Platform/x86_64/23.08/active/files/lib"
+                        .into(),
+                version: "122.1.10".into(),
+            })
+        );
+
+        let info = INFO.replace("     Version: 122.1.10\n", "");
+        assert_eq!(parse_flatpak_info(&info).unwrap().version, "23.08");
+
+        let info = INFO.replace("    Location:", "    Deploy:");
+        assert_eq!(parse_flatpak_info(&info), None);
+        assert_eq!(parse_flatpak_info(""), None);
+    }
+}
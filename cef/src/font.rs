@@ -0,0 +1,294 @@
+//! Font module
+//!
+//! CEF has no API to list fonts, so [SystemFontList] asks the platform for the font families
+//! which the browser can use: fontconfig on Linux, Core Text on macOS and GDI on Windows.
+
+/// A font family installed on the system.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FontFamily {
+    pub name: String,
+    pub is_monospace: bool,
+    pub is_serif: bool,
+}
+
+impl FontFamily {
+    /// Guess the traits of a family the platform only reports the name of.
+    fn from_name(name: &str) -> Self {
+        let lower = name.to_ascii_lowercase();
+        let is_monospace = ["mono", "courier", "consol", "menlo", "code", "terminal"]
+            .iter()
+            .any(|hint| lower.contains(hint));
+        let is_serif = !lower.contains("sans")
+            && (lower.contains("serif")
+                || ["times", "georgia", "garamond", "palatino", "baskerville"]
+                    .iter()
+                    .any(|hint| lower.contains(hint)));
+        Self {
+            name: name.to_string(),
+            is_monospace,
+            is_serif,
+        }
+    }
+}
+
+/// The font families installed on the system.
+pub struct SystemFontList;
+
+impl SystemFontList {
+    /// List the installed font families, sorted by name. Returns an empty list if the platform
+    /// doesn't report any.
+    pub fn get() -> Vec<FontFamily> {
+        Self::sorted(native::families())
+    }
+
+    /// Sort `families` by name, dropping the duplicates the platform reports for each style.
+    fn sorted(mut families: Vec<FontFamily>) -> Vec<FontFamily> {
+        families.sort_by(|a, b| a.name.cmp(&b.name));
+        families.dedup_by(|a, b| a.name == b.name);
+        families
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod native {
+    use std::process::Command;
+
+    use super::FontFamily;
+
+    /// fontconfig's `FC_MONO` spacing.
+    const SPACING_MONO: &str = "100";
+
+    pub fn families() -> Vec<FontFamily> {
+        Command::new("fc-list")
+            .args([":", "family", "spacing"])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| parse_fc_list(&String::from_utf8_lossy(&output.stdout)))
+            .unwrap_or_default()
+    }
+
+    /// Parse `fc-list : family spacing` lines like `DejaVu Sans Mono,DejaVu Sans:spacing=100`,
+    /// keeping the first of the localized family names.
+    pub fn parse_fc_list(output: &str) -> Vec<FontFamily> {
+        output
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split(':');
+                let name = fields.next()?.split(',').next()?.trim();
+                if name.is_empty() {
+                    return None;
+                }
+                let mut family = FontFamily::from_name(name);
+                if fields.any(|field| field.trim() == format!("spacing={SPACING_MONO}")) {
+                    family.is_monospace = true;
+                }
+                Some(family)
+            })
+            .collect()
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod native {
+    use std::{
+        ffi::{c_char, c_void, CStr},
+        os::raw::c_long,
+    };
+
+    use super::FontFamily;
+
+    const UTF8: u32 = 0x0800_0100;
+
+    #[link(name = "CoreText", kind = "framework")]
+    extern "C" {
+        fn CTFontManagerCopyAvailableFontFamilyNames() -> *const c_void;
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        fn CFArrayGetCount(array: *const c_void) -> c_long;
+        fn CFArrayGetValueAtIndex(array: *const c_void, index: c_long) -> *const c_void;
+        fn CFStringGetCString(
+            string: *const c_void,
+            buffer: *mut c_char,
+            size: c_long,
+            encoding: u32,
+        ) -> u8;
+        fn CFRelease(object: *const c_void);
+    }
+
+    pub fn families() -> Vec<FontFamily> {
+        unsafe {
+            let names = CTFontManagerCopyAvailableFontFamilyNames();
+            if names.is_null() {
+                return vec![];
+            }
+            let mut buffer = [0 as c_char; 256];
+            let families = (0..CFArrayGetCount(names))
+                .filter_map(|index| {
+                    let name = CFArrayGetValueAtIndex(names, index);
+                    (CFStringGetCString(name, buffer.as_mut_ptr(), buffer.len() as c_long, UTF8)
+                        != 0)
+                        .then(|| CStr::from_ptr(buffer.as_ptr()).to_string_lossy())
+                        // Families starting with a dot are hidden system fonts.
+                        .filter(|name| !name.starts_with('.'))
+                        .map(|name| FontFamily::from_name(&name))
+                })
+                .collect();
+            CFRelease(names);
+            families
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod native {
+    use std::{ffi::c_void, ptr};
+
+    use super::FontFamily;
+
+    const DEFAULT_CHARSET: u8 = 1;
+    const FIXED_PITCH: u8 = 0x01;
+    const FF_ROMAN: u8 = 0x10;
+    const FF_MODERN: u8 = 0x30;
+    const FAMILY_MASK: u8 = 0xF0;
+
+    #[repr(C)]
+    struct LogFont {
+        height: i32,
+        width: i32,
+        escapement: i32,
+        orientation: i32,
+        weight: i32,
+        italic: u8,
+        underline: u8,
+        strike_out: u8,
+        char_set: u8,
+        out_precision: u8,
+        clip_precision: u8,
+        quality: u8,
+        pitch_and_family: u8,
+        face_name: [u16; 32],
+    }
+
+    type EnumFontProc = extern "system" fn(*const LogFont, *const c_void, u32, isize) -> i32;
+
+    #[link(name = "gdi32")]
+    extern "system" {
+        fn EnumFontFamiliesExW(
+            hdc: *mut c_void,
+            logfont: *const LogFont,
+            proc: EnumFontProc,
+            lparam: isize,
+            flags: u32,
+        ) -> i32;
+    }
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn GetDC(hwnd: *mut c_void) -> *mut c_void;
+        fn ReleaseDC(hwnd: *mut c_void, hdc: *mut c_void) -> i32;
+    }
+
+    extern "system" fn on_font(
+        logfont: *const LogFont,
+        _metrics: *const c_void,
+        _font_type: u32,
+        lparam: isize,
+    ) -> i32 {
+        let (logfont, families) = unsafe { (&*logfont, &mut *(lparam as *mut Vec<FontFamily>)) };
+        let len = logfont
+            .face_name
+            .iter()
+            .position(|&c| c == 0)
+            .unwrap_or(logfont.face_name.len());
+        let name = String::from_utf16_lossy(&logfont.face_name[..len]);
+        // Vertical variants of CJK fonts.
+        if !name.starts_with('@') {
+            let family = logfont.pitch_and_family & FAMILY_MASK;
+            families.push(FontFamily {
+                name,
+                is_monospace: logfont.pitch_and_family & FIXED_PITCH != 0 || family == FF_MODERN,
+                is_serif: family == FF_ROMAN,
+            });
+        }
+        1
+    }
+
+    pub fn families() -> Vec<FontFamily> {
+        let mut families = Vec::<FontFamily>::new();
+        unsafe {
+            let hdc = GetDC(ptr::null_mut());
+            if hdc.is_null() {
+                return families;
+            }
+            let mut logfont: LogFont = std::mem::zeroed();
+            logfont.char_set = DEFAULT_CHARSET;
+            EnumFontFamiliesExW(
+                hdc,
+                &logfont,
+                on_font,
+                &mut families as *mut Vec<FontFamily> as isize,
+                0,
+            );
+            ReleaseDC(ptr::null_mut(), hdc);
+        }
+        families
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+mod native {
+    use super::FontFamily;
+
+    pub fn families() -> Vec<FontFamily> {
+        vec![]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_system_font_list_sorted() {
+        let families = SystemFontList::sorted(native::parse_fc_list(
+            "Noto Serif:spacing=0\nDejaVu Sans\nNoto Serif\nDejaVu Sans Mono:spacing=100\n",
+        ));
+        let names: Vec<_> = families.iter().map(|family| family.name.as_str()).collect();
+        assert_eq!(names, ["DejaVu Sans", "DejaVu Sans Mono", "Noto Serif"]);
+    }
+
+    #[test]
+    fn test_family_from_name() {
+        let family = FontFamily::from_name("Noto Serif");
+        assert!(family.is_serif && !family.is_monospace);
+        let family = FontFamily::from_name("DejaVu Sans Mono");
+        assert!(!family.is_serif && family.is_monospace);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_parse_fc_list() {
+        let families = native::parse_fc_list(
+            "DejaVu Sans Mono,DejaVu Sans Mono Book:spacing=100\nDejaVu Serif\n:spacing=100\n",
+        );
+        assert_eq!(
+            families,
+            vec![
+                FontFamily {
+                    name: "DejaVu Sans Mono".to_string(),
+                    is_monospace: true,
+                    is_serif: false,
+                },
+                FontFamily {
+                    name: "DejaVu Serif".to_string(),
+                    is_monospace: false,
+                    is_serif: true,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,398 @@
+//! Frame module
+//!
+//! A [Frame] outlives its document. After a cross-origin navigation moves the frame to another
+//! renderer process, the old [Frame] is invalid and CEF ignores calls on it or returns empty
+//! values. The accessors here check [Frame::is_valid] first, and return `None` or
+//! [InvalidFrame] instead.
+//!
+//! A [FrameId] identifies a frame across these [Frame] objects, e.g. to route a reply to the
+//! subframe which sent a process message. [FrameTreeWalker] visits every frame of a browser.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+};
+
+use crate::{
+    compat, Browser, CefString, CefStringUtf16, CefStringUtf8, Frame, ImplBrowser, ImplFrame,
+};
+
+/// The identifier of a [Frame], unique within the browser process.
+///
+/// CEF uses string identifiers since version 122, and `i64` identifiers before. Both convert into
+/// a [FrameId], so code using it doesn't depend on the version of the bindings.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FrameId(String);
+
+impl FrameId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// `true` for the identifier of an invalid frame.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Display for FrameId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<String> for FrameId {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&str> for FrameId {
+    fn from(value: &str) -> Self {
+        Self(value.to_string())
+    }
+}
+
+impl From<CefStringUtf16> for FrameId {
+    fn from(value: CefStringUtf16) -> Self {
+        Self(value.to_string())
+    }
+}
+
+impl From<i64> for FrameId {
+    fn from(value: i64) -> Self {
+        Self(value.to_string())
+    }
+}
+
+/// The [Frame] was invalid, so the call did nothing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InvalidFrame;
+
+impl fmt::Display for InvalidFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the frame is no longer valid")
+    }
+}
+
+impl std::error::Error for InvalidFrame {}
+
+impl Frame {
+    /// `false` once the frame navigated away to another process or was removed from the page.
+    /// Get the current frame from the [crate::Browser] again in that case.
+    pub fn is_valid(&self) -> bool {
+        ImplFrame::is_valid(self) != 0
+    }
+
+    /// The URL of the document in the frame, or `None` if the frame is invalid.
+    pub fn url(&self) -> Option<String> {
+        self.valid()?.get_url().map(|url| url.to_string())
+    }
+
+    /// The `name` attribute of the frame element, or `None` if the frame is invalid.
+    pub fn name(&self) -> Option<String> {
+        self.valid()?.get_name().map(|name| name.to_string())
+    }
+
+    /// The browser of the frame, or `None` if the frame is invalid or detached.
+    pub fn browser(&self) -> Option<Browser> {
+        self.valid()?.get_browser()
+    }
+
+    /// The parent frame, or `None` for the main frame, and if the frame is invalid or detached.
+    pub fn parent(&self) -> Option<Frame> {
+        self.valid()?.get_parent()
+    }
+
+    /// The identifier of the frame, unique within the browser process. CEF uses string
+    /// identifiers since version 122, and an empty string if the frame is invalid.
+    pub fn identifier(&self) -> String {
+        self.id().0
+    }
+
+    /// The [FrameId] of the frame, which is empty if the frame is invalid.
+    pub fn id(&self) -> FrameId {
+        self.valid().map(compat::frame_id).unwrap_or_default()
+    }
+
+    /// `true` if the frame is valid and the main frame of its browser.
+    pub fn is_main(&self) -> bool {
+        self.is_valid() && ImplFrame::is_main(self) != 0
+    }
+
+    /// `true` if the frame is valid and has the focus.
+    pub fn is_focused(&self) -> bool {
+        self.is_valid() && ImplFrame::is_focused(self) != 0
+    }
+
+    /// Run `code` in the frame, reported as `script_url` starting at `start_line` in errors.
+    pub fn execute_script(
+        &self,
+        code: &str,
+        script_url: &str,
+        start_line: i32,
+    ) -> Result<(), InvalidFrame> {
+        let frame = self.valid().ok_or(InvalidFrame)?;
+        let code = CefString::from(&CefStringUtf8::from(code));
+        let script_url = CefString::from(&CefStringUtf8::from(script_url));
+        frame.execute_java_script(Some(&code), Some(&script_url), start_line);
+        Ok(())
+    }
+
+    fn valid(&self) -> Option<&Self> {
+        self.is_valid().then_some(self)
+    }
+}
+
+impl Browser {
+    /// The frame with the identifier `id`, or `None` if the browser has no such frame.
+    pub fn frame_by_id(&self, id: &FrameId) -> Option<Frame> {
+        if id.is_empty() {
+            return None;
+        }
+        compat::frame_by_id(self, id).filter(Frame::is_valid)
+    }
+}
+
+/// Iterates the frames of a browser breadth-first: the main frame, then its child frames, then
+/// their child frames, and so on.
+///
+/// The tree is read when the walker is created. Frames which are removed while walking are
+/// skipped, and frames which are added are not visited.
+pub struct FrameTreeWalker {
+    browser: Browser,
+    children: HashMap<FrameId, Vec<FrameId>>,
+    queue: VecDeque<FrameId>,
+}
+
+impl FrameTreeWalker {
+    pub fn new(browser: &Browser) -> Self {
+        // CEF only reports the parent of each frame.
+        let mut children: HashMap<FrameId, Vec<FrameId>> = HashMap::new();
+        for id in compat::frame_ids(browser) {
+            if let Some(parent) = browser.frame_by_id(&id).and_then(|frame| frame.parent()) {
+                children.entry(parent.id()).or_default().push(id);
+            }
+        }
+        let queue = browser
+            .get_main_frame()
+            .map(|frame| frame.id())
+            .filter(|id| !id.is_empty())
+            .into_iter()
+            .collect();
+        Self {
+            browser: browser.clone(),
+            children,
+            queue,
+        }
+    }
+}
+
+impl Iterator for FrameTreeWalker {
+    type Item = Frame;
+
+    fn next(&mut self) -> Option<Frame> {
+        while let Some(id) = self.queue.pop_front() {
+            // Visit the children even if their parent was removed since.
+            if let Some(children) = self.children.remove(&id) {
+                self.queue.extend(children);
+            }
+            if let Some(frame) = self.browser.frame_by_id(&id) {
+                return Some(frame);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use cef_sys::{_cef_browser_t, _cef_frame_t};
+
+    use super::*;
+    use crate::{
+        fixtures::FakeBrowser,
+        rc::{wrap_rc, RcImpl},
+        CefStringList, ImplBrowser,
+    };
+
+    /// A frame as seen after a cross-origin navigation replaced its document.
+    struct StaleFrame {
+        object: *mut RcImpl<_cef_frame_t, Self>,
+    }
+
+    impl ImplFrame for StaleFrame {
+        fn get_raw(&self) -> *mut _cef_frame_t {
+            self.object as *mut _
+        }
+    }
+
+    wrap_rc!(StaleFrame, WrapFrame, _cef_frame_t);
+
+    /// The main frame of a loaded page.
+    struct MainFrame {
+        object: *mut RcImpl<_cef_frame_t, Self>,
+    }
+
+    impl ImplFrame for MainFrame {
+        fn is_valid(&self) -> std::os::raw::c_int {
+            1
+        }
+
+        fn get_browser(&self) -> Option<Browser> {
+            Some(FakeBrowser::new(0).build())
+        }
+
+        fn get_raw(&self) -> *mut _cef_frame_t {
+            self.object as *mut _
+        }
+    }
+
+    wrap_rc!(MainFrame, WrapFrame, _cef_frame_t);
+
+    #[test]
+    fn test_main_frame() {
+        let frame = Frame::new(MainFrame {
+            object: std::ptr::null_mut(),
+        });
+        assert!(frame.is_valid());
+        assert!(frame.browser().is_some());
+        assert!(frame.parent().is_none());
+        assert!(!frame.is_focused());
+    }
+
+    #[test]
+    fn test_stale_frame() {
+        let frame = Frame::new(StaleFrame {
+            object: std::ptr::null_mut(),
+        });
+        assert!(!frame.is_valid());
+        assert_eq!(frame.url(), None);
+        assert_eq!(frame.name(), None);
+        assert!(frame.browser().is_none());
+        assert_eq!(frame.identifier(), "");
+        assert!(frame.id().is_empty());
+        assert!(!frame.is_main());
+        assert_eq!(frame.execute_script("1", "", 0), Err(InvalidFrame));
+        assert!(!frame.load_html("<p>stale</p>", ""));
+    }
+
+    /// A frame of [TreeBrowser], `None` once removed.
+    type TreeFrames = Arc<Mutex<Vec<(&'static str, Option<&'static str>)>>>;
+
+    struct TreeBrowser {
+        object: *mut RcImpl<_cef_browser_t, Self>,
+        /// The identifier and parent identifier of each frame.
+        frames: TreeFrames,
+    }
+
+    impl TreeBrowser {
+        fn frame(&self, id: &str) -> Option<Frame> {
+            let frames = self.frames.lock().unwrap();
+            let (id, parent) = frames.iter().find(|(frame, _)| *frame == id)?;
+            Some(Frame::new(TreeFrame {
+                object: std::ptr::null_mut(),
+                id,
+                parent: parent.and_then(|parent| self.frame(parent)),
+            }))
+        }
+    }
+
+    impl ImplBrowser for TreeBrowser {
+        fn get_main_frame(&self) -> Option<Frame> {
+            self.frame("main")
+        }
+
+        fn get_frame_by_identifier(&self, identifier: Option<&CefStringUtf16>) -> Option<Frame> {
+            self.frame(&identifier?.to_string())
+        }
+
+        fn get_frame_identifiers(&self, identifiers: Option<&mut CefStringList>) {
+            let Some(identifiers) = identifiers else {
+                return;
+            };
+            for (id, _) in self.frames.lock().unwrap().iter() {
+                identifiers.push(id);
+            }
+        }
+
+        fn get_raw(&self) -> *mut _cef_browser_t {
+            self.object as *mut _
+        }
+    }
+
+    wrap_rc!(TreeBrowser, WrapBrowser, _cef_browser_t, frames);
+
+    struct TreeFrame {
+        object: *mut RcImpl<_cef_frame_t, Self>,
+        id: &'static str,
+        parent: Option<Frame>,
+    }
+
+    impl ImplFrame for TreeFrame {
+        fn is_valid(&self) -> std::os::raw::c_int {
+            1
+        }
+
+        fn get_identifier(&self) -> Option<CefStringUtf16> {
+            Some(CefString::from(&CefStringUtf8::from(self.id)))
+        }
+
+        fn get_parent(&self) -> Option<Frame> {
+            self.parent.clone()
+        }
+
+        fn get_raw(&self) -> *mut _cef_frame_t {
+            self.object as *mut _
+        }
+    }
+
+    wrap_rc!(TreeFrame, WrapFrame, _cef_frame_t, id, parent);
+
+    #[test]
+    fn test_frame_tree_walker() {
+        // main
+        // ├── a
+        // │   ├── a1
+        // │   └── a2
+        // │       └── a2x
+        // └── b
+        //     └── b1
+        let frames = Arc::new(Mutex::new(vec![
+            ("a2x", Some("a2")),
+            ("main", None),
+            ("a", Some("main")),
+            ("a1", Some("a")),
+            ("b", Some("main")),
+            ("a2", Some("a")),
+            ("b1", Some("b")),
+        ]));
+        let browser = Browser::new(TreeBrowser {
+            object: std::ptr::null_mut(),
+            frames: frames.clone(),
+        });
+        let ids =
+            |walker: FrameTreeWalker| walker.map(|frame| frame.identifier()).collect::<Vec<_>>();
+        assert_eq!(
+            ids(FrameTreeWalker::new(&browser)),
+            ["main", "a", "b", "a1", "b1", "a2", "a2x"]
+        );
+
+        // A frame removed while walking is skipped, but not its children.
+        let mut walker = FrameTreeWalker::new(&browser);
+        assert_eq!(
+            walker.next().map(|frame| frame.identifier()).as_deref(),
+            Some("main")
+        );
+        frames.lock().unwrap().retain(|(id, _)| *id != "a2");
+        assert_eq!(ids(walker), ["a", "b", "a1", "b1", "a2x"]);
+    }
+
+    #[test]
+    fn test_frame_id_from_either_version() {
+        assert_eq!(FrameId::from(42i64), FrameId::from("42"));
+        assert_eq!(FrameId::from("3-5".to_string()).to_string(), "3-5");
+    }
+}
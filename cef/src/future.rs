@@ -0,0 +1,97 @@
+//! Future module
+//!
+//! A single value channel which turns CEF completion callbacks into [`Future`]s.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+};
+
+struct Shared<T> {
+    value: Option<T>,
+    closed: bool,
+    waker: Option<Waker>,
+}
+
+/// The sending half of [`channel`]. Dropping it without sending resolves the [`Completion`] with
+/// `None`, e.g. when CEF releases a callback without executing it.
+pub(crate) struct Sender<T>(Arc<Mutex<Shared<T>>>);
+
+impl<T> Sender<T> {
+    /// Resolve the [`Completion`] with `value`.
+    pub fn send(self, value: T) {
+        if let Ok(mut shared) = self.0.lock() {
+            shared.value = Some(value);
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let waker = self.0.lock().ok().and_then(|mut shared| {
+            shared.closed = true;
+            shared.waker.take()
+        });
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// The receiving half of [`channel`].
+pub(crate) struct Completion<T>(Arc<Mutex<Shared<T>>>);
+
+impl<T> Future for Completion<T> {
+    type Output = Option<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let Ok(mut shared) = self.0.lock() else {
+            return Poll::Ready(None);
+        };
+        if let Some(value) = shared.value.take() {
+            Poll::Ready(Some(value))
+        } else if shared.closed {
+            Poll::Ready(None)
+        } else {
+            shared.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+/// Create a [`Sender`] and [`Completion`] pair.
+pub(crate) fn channel<T>() -> (Sender<T>, Completion<T>) {
+    let shared = Arc::new(Mutex::new(Shared {
+        value: None,
+        closed: false,
+        waker: None,
+    }));
+    (Sender(shared.clone()), Completion(shared))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn poll<T>(completion: &mut Completion<T>) -> Poll<Option<T>> {
+        let mut cx = Context::from_waker(Waker::noop());
+        Pin::new(completion).poll(&mut cx)
+    }
+
+    #[test]
+    fn test_send() {
+        let (sender, mut completion) = channel();
+        assert!(poll(&mut completion).is_pending());
+        sender.send(42);
+        assert_eq!(poll(&mut completion), Poll::Ready(Some(42)));
+    }
+
+    #[test]
+    fn test_drop_without_send() {
+        let (sender, mut completion) = channel::<()>();
+        drop(sender);
+        assert_eq!(poll(&mut completion), Poll::Ready(None));
+    }
+}
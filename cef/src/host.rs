@@ -0,0 +1,101 @@
+//! Browser host module
+//!
+//! Typed wrappers of [`BrowserHost`] methods which work for both windowed and windowless
+//! browsers, unless noted otherwise. The windowless only methods are on [`crate::osr::OsrHost`].
+
+use std::os::raw::c_int;
+
+use crate::{
+    rc::from_impl,
+    touch::{zoom_factor, zoom_level},
+    BrowserHost, ImplBrowserHost, Size,
+};
+
+from_impl!(Browser, ImplBrowser);
+from_impl!(Frame, ImplFrame);
+
+/// The zoom range of Chromium in percent.
+const CSS_ZOOM_PERCENT: std::ops::RangeInclusive<f64> = 25.0..=500.0;
+
+impl BrowserHost {
+    /// Mute or unmute the audio of the browser.
+    pub fn set_audio_muted(&self, muted: bool) {
+        ImplBrowserHost::set_audio_muted(self, muted as c_int);
+    }
+
+    /// `true` if the audio of the browser is muted.
+    pub fn is_audio_muted(&self) -> bool {
+        ImplBrowserHost::is_audio_muted(self) != 0
+    }
+
+    /// Notify the browser that the window hosting it is about to be moved or resized, so popups
+    /// such as `<select>` menus are dismissed. Windowed browsers on Windows and Linux only.
+    pub fn notify_move_or_resize_started(&self) {
+        ImplBrowserHost::notify_move_or_resize_started(self);
+    }
+
+    /// Resize the browser to fit its content, between `min` and `max`. Content size changes are
+    /// reported to [`crate::display::DisplayHandlerBuilder::on_auto_resize`].
+    pub fn set_auto_resize_enabled(&self, enabled: bool, min: Size, max: Size) {
+        ImplBrowserHost::set_auto_resize_enabled(self, enabled as c_int, Some(&min), Some(&max));
+    }
+
+    /// The zoom of the page in percent, as browsers show it, e.g. `120.0` at zoom level 1.
+    /// Must be called on the UI thread, like [`ImplBrowserHost::get_zoom_level`].
+    pub fn css_zoom_percent(&self) -> f64 {
+        100.0 * zoom_factor(self.get_zoom_level())
+    }
+
+    /// Zoom the page to `percent`, e.g. `150.0`, clamped to Chromium's range of 25% to 500%.
+    /// `NaN` leaves the zoom as it is.
+    pub fn set_css_zoom_percent(&self, percent: f64) {
+        if percent.is_nan() {
+            return;
+        }
+        let percent = percent.clamp(*CSS_ZOOM_PERCENT.start(), *CSS_ZOOM_PERCENT.end());
+        self.set_zoom_level(zoom_level(percent / 100.0));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use crate::fixtures::FakeHost;
+
+    #[test]
+    fn test_css_zoom_percent() {
+        let zoom_level = Arc::<Mutex<f64>>::default();
+        let host = FakeHost::new().zoom_level(zoom_level.clone()).build();
+        assert!((host.css_zoom_percent() - 100.0).abs() < 1e-9);
+
+        host.set_css_zoom_percent(150.0);
+        let expected = 1.5f64.ln() / 1.2f64.ln();
+        assert!((*zoom_level.lock().unwrap() - expected).abs() < 1e-9);
+        assert!((host.css_zoom_percent() - 150.0).abs() < 1e-9);
+
+        *zoom_level.lock().unwrap() = 1.0;
+        assert!((host.css_zoom_percent() - 120.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_css_zoom_percent_out_of_range() {
+        let zoom_level = Arc::<Mutex<f64>>::default();
+        let host = FakeHost::new().zoom_level(zoom_level.clone()).build();
+        for (percent, expected) in [
+            (0.0, 25.0),
+            (-50.0, 25.0),
+            (f64::NEG_INFINITY, 25.0),
+            (10_000.0, 500.0),
+            (f64::INFINITY, 500.0),
+        ] {
+            host.set_css_zoom_percent(percent);
+            assert!(zoom_level.lock().unwrap().is_finite());
+            assert!((host.css_zoom_percent() - expected).abs() < 1e-9);
+        }
+
+        host.set_css_zoom_percent(150.0);
+        host.set_css_zoom_percent(f64::NAN);
+        assert!((host.css_zoom_percent() - 150.0).abs() < 1e-9);
+    }
+}
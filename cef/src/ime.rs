@@ -0,0 +1,141 @@
+//! Input method module
+//!
+//! The IME composition methods of [BrowserHost], which windowless browsers use to forward the
+//! composition of the native input method. Windowed browsers handle the IME themselves.
+
+use std::{ops::Range, ptr};
+
+use cef_sys::{_cef_composition_underline_t, _cef_range_t, cef_composition_underline_style_t};
+
+use crate::{BrowserHost, CefString, CefStringUtf8, Color, ImplBrowserHost};
+
+/// The line drawn under a segment of the composition text.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum UnderlineStyle {
+    #[default]
+    Solid,
+    Dot,
+    Dash,
+    None,
+}
+
+impl From<UnderlineStyle> for cef_composition_underline_style_t {
+    fn from(value: UnderlineStyle) -> Self {
+        match value {
+            UnderlineStyle::Solid => Self::CEF_CUS_SOLID,
+            UnderlineStyle::Dot => Self::CEF_CUS_DOT,
+            UnderlineStyle::Dash => Self::CEF_CUS_DASH,
+            UnderlineStyle::None => Self::CEF_CUS_NONE,
+        }
+    }
+}
+
+/// The underline of the characters in `range` of the composition text.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CompositionUnderline {
+    pub range: Range<i32>,
+    pub color: Color,
+    pub background_color: Color,
+    pub thick: bool,
+    pub style: UnderlineStyle,
+}
+
+impl From<&CompositionUnderline> for _cef_composition_underline_t {
+    fn from(value: &CompositionUnderline) -> Self {
+        Self {
+            range: cef_range(&value.range),
+            color: value.color,
+            background_color: value.background_color,
+            thick: value.thick.into(),
+            style: value.style.into(),
+        }
+    }
+}
+
+/// Negative offsets are clamped to the start of the text.
+fn cef_range(range: &Range<i32>) -> _cef_range_t {
+    _cef_range_t {
+        from: range.start.max(0) as u32,
+        to: range.end.max(0) as u32,
+    }
+}
+
+impl BrowserHost {
+    /// Replace the current composition with `text`, underlined with `underlines`, and select
+    /// `cursor_range` of it. Windowless browsers only.
+    pub fn set_ime_composition(
+        &self,
+        text: &str,
+        underlines: Vec<CompositionUnderline>,
+        cursor_range: Range<i32>,
+    ) {
+        let text = CefString::from(&CefStringUtf8::from(text));
+        let underlines: Vec<_cef_composition_underline_t> =
+            underlines.iter().map(Into::into).collect();
+        let selection_range = cef_range(&cursor_range);
+        // The generated wrapper only passes a single underline, so call CEF with the array.
+        unsafe {
+            let raw = ImplBrowserHost::get_raw(self);
+            if let Some(f) = (*raw).ime_set_composition {
+                f(
+                    raw,
+                    (&text).into(),
+                    underlines.len(),
+                    if underlines.is_empty() {
+                        ptr::null()
+                    } else {
+                        underlines.as_ptr()
+                    },
+                    ptr::null(),
+                    &selection_range,
+                );
+            }
+        }
+    }
+
+    /// Commit `text` in place of the current composition, and end composing. Windowless browsers
+    /// only.
+    pub fn commit_ime_text(&self, text: &str) {
+        let text = CefString::from(&CefStringUtf8::from(text));
+        self.ime_commit_text(Some(&text), None, 0);
+    }
+
+    /// Cancel the current composition, and remove its text. Windowless browsers only.
+    pub fn cancel_ime_composition(&self) {
+        ImplBrowserHost::ime_cancel_composition(self);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_composition_underline() {
+        let underline = CompositionUnderline {
+            range: 2..5,
+            color: 0xFF00_0000,
+            background_color: 0x00FF_FFFF,
+            thick: true,
+            style: UnderlineStyle::Dash,
+        };
+        assert_eq!(underline.range, 2..5);
+        assert_eq!(underline.color, 0xFF00_0000);
+        assert_eq!(underline.background_color, 0x00FF_FFFF);
+        assert!(underline.thick);
+        assert_eq!(underline.style, UnderlineStyle::Dash);
+
+        let raw = _cef_composition_underline_t::from(&underline);
+        assert_eq!((raw.range.from, raw.range.to), (2, 5));
+        assert_eq!(raw.color, 0xFF00_0000);
+        assert_eq!(raw.background_color, 0x00FF_FFFF);
+        assert_eq!(raw.thick, 1);
+        assert_eq!(raw.style, cef_composition_underline_style_t::CEF_CUS_DASH);
+    }
+
+    #[test]
+    fn test_negative_range() {
+        let range = cef_range(&(-1..3));
+        assert_eq!((range.from, range.to), (0, 3));
+    }
+}
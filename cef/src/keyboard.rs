@@ -0,0 +1,506 @@
+//! Keyboard module
+//!
+//! [WellKnownShortcut] types the editing shortcuts of the current platform into a browser.
+//! [ShortcutPolicy] decides which shortcuts the application handles and which the page gets.
+
+use std::{os::raw::c_int, sync::Arc};
+
+use cef_sys::{_cef_keyboard_handler_t, cef_key_event_type_t, XEvent};
+
+use crate::{
+    rc::{wrap_rc, RcImpl},
+    Browser, BrowserHost, EventFlags, ImplBrowser, ImplBrowserHost, ImplKeyboardHandler, KeyEvent,
+    KeyEventType, KeyboardHandler,
+};
+
+const VKEY_A: c_int = 0x41;
+const VKEY_C: c_int = 0x43;
+const VKEY_F: c_int = 0x46;
+const VKEY_G: c_int = 0x47;
+const VKEY_V: c_int = 0x56;
+const VKEY_X: c_int = 0x58;
+const VKEY_Y: c_int = 0x59;
+const VKEY_Z: c_int = 0x5A;
+const VKEY_F3: c_int = 0x72;
+
+const SHIFT: EventFlags = EventFlags::SHIFT_DOWN;
+
+/// The modifiers a [Shortcut] compares, ignoring e.g. the lock keys and mouse buttons.
+const SHORTCUT_MODIFIERS: EventFlags = EventFlags::from_bits(
+    SHIFT.bits()
+        | EventFlags::CONTROL_DOWN.bits()
+        | EventFlags::ALT_DOWN.bits()
+        | EventFlags::COMMAND_DOWN.bits(),
+);
+
+/// The modifier of the platform's editing shortcuts, `Cmd` on macOS and `Ctrl` elsewhere.
+#[cfg(target_os = "macos")]
+const PRIMARY: EventFlags = EventFlags::COMMAND_DOWN;
+#[cfg(not(target_os = "macos"))]
+const PRIMARY: EventFlags = EventFlags::CONTROL_DOWN;
+
+/// Editing and find shortcuts, sent with the key combination of the current platform.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WellKnownShortcut {
+    SelectAll,
+    Copy,
+    Cut,
+    Paste,
+    Undo,
+    Redo,
+    Find,
+    FindNext,
+    FindPrev,
+}
+
+impl WellKnownShortcut {
+    /// The `(windows_key_code, modifiers)` of the shortcut on the current platform.
+    pub fn key_combination(self) -> (c_int, EventFlags) {
+        match self {
+            Self::SelectAll => (VKEY_A, PRIMARY),
+            Self::Copy => (VKEY_C, PRIMARY),
+            Self::Cut => (VKEY_X, PRIMARY),
+            Self::Paste => (VKEY_V, PRIMARY),
+            Self::Undo => (VKEY_Z, PRIMARY),
+            Self::Redo if cfg!(target_os = "windows") => (VKEY_Y, PRIMARY),
+            Self::Redo => (VKEY_Z, PRIMARY | SHIFT),
+            Self::Find => (VKEY_F, PRIMARY),
+            Self::FindNext if cfg!(target_os = "macos") => (VKEY_G, PRIMARY),
+            Self::FindNext => (VKEY_F3, EventFlags::empty()),
+            Self::FindPrev if cfg!(target_os = "macos") => (VKEY_G, PRIMARY | SHIFT),
+            Self::FindPrev => (VKEY_F3, SHIFT),
+        }
+    }
+
+    /// The key down and key up events which type the shortcut.
+    pub fn key_events(self) -> [KeyEvent; 2] {
+        let (windows_key_code, modifiers) = self.key_combination();
+        [
+            cef_key_event_type_t::KEYEVENT_RAWKEYDOWN,
+            cef_key_event_type_t::KEYEVENT_KEYUP,
+        ]
+        .map(|type_| KeyEvent {
+            type_: type_.into(),
+            modifiers: modifiers.bits(),
+            windows_key_code,
+            ..Default::default()
+        })
+    }
+}
+
+impl BrowserHost {
+    /// Type `shortcut` into the focused frame of the browser.
+    pub fn send_keyboard_shortcut(&self, shortcut: WellKnownShortcut) {
+        for event in shortcut.key_events() {
+            self.send_key_event(Some(&event));
+        }
+    }
+}
+
+/// A key combination: the Windows virtual key code, and the modifiers which must be down.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Shortcut {
+    pub windows_key_code: c_int,
+    /// Some combination of [EventFlags::SHIFT_DOWN], [EventFlags::CONTROL_DOWN],
+    /// [EventFlags::ALT_DOWN] and [EventFlags::COMMAND_DOWN]. Other flags are ignored.
+    pub modifiers: EventFlags,
+}
+
+impl Shortcut {
+    pub fn new(windows_key_code: c_int, modifiers: EventFlags) -> Self {
+        Self {
+            windows_key_code,
+            modifiers: modifiers & SHORTCUT_MODIFIERS,
+        }
+    }
+
+    /// The key with the modifier of the platform's shortcuts, `Cmd` on macOS and `Ctrl`
+    /// elsewhere, e.g. `Shortcut::primary(0x57)` for closing a tab with `Ctrl+W`.
+    pub fn primary(windows_key_code: c_int) -> Self {
+        Self::new(windows_key_code, PRIMARY)
+    }
+
+    /// `true` if `event` presses exactly this key combination.
+    pub fn matches(&self, event: &KeyEvent) -> bool {
+        event.windows_key_code == self.windows_key_code
+            && EventFlags::from_bits(event.modifiers) & SHORTCUT_MODIFIERS == self.modifiers
+    }
+}
+
+impl From<WellKnownShortcut> for Shortcut {
+    fn from(shortcut: WellKnownShortcut) -> Self {
+        let (windows_key_code, modifiers) = shortcut.key_combination();
+        Self::new(windows_key_code, modifiers)
+    }
+}
+
+/// Who handles a key.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ShortcutRoute {
+    /// The application handles the key before the renderer sees it, so the page can't
+    /// intercept it.
+    Embedder,
+    /// The page gets the key first, and the application handles it if the page didn't, e.g.
+    /// because no `keydown` listener called `preventDefault`.
+    Page,
+    /// The page and CEF handle the key as if there was no policy.
+    #[default]
+    PassThrough,
+}
+
+/// A rule of a [ShortcutPolicy], which routes the key combinations it lists.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShortcutRule {
+    /// Route the keys to [ShortcutRoute::Embedder].
+    Embedder(Vec<Shortcut>),
+    /// Route the keys to [ShortcutRoute::Page].
+    Page(Vec<Shortcut>),
+}
+
+impl ShortcutRule {
+    fn route(&self) -> ShortcutRoute {
+        match self {
+            Self::Embedder(_) => ShortcutRoute::Embedder,
+            Self::Page(_) => ShortcutRoute::Page,
+        }
+    }
+
+    fn matches(&self, event: &KeyEvent) -> bool {
+        let (Self::Embedder(shortcuts) | Self::Page(shortcuts)) = self;
+        shortcuts.iter().any(|shortcut| shortcut.matches(event))
+    }
+}
+
+/// Why a [ShortcutPolicy] routed a key where it did.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ShortcutReason {
+    /// The rule at this index was the first one to match.
+    Rule(usize),
+    /// No rule matched, so the key took the default route.
+    Default,
+    /// Only key down events are routed, key up and character events always pass through.
+    NotKeyDown,
+}
+
+/// The route of a key and the reason for it, see [ShortcutPolicy::decide].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ShortcutDecision {
+    pub route: ShortcutRoute,
+    pub reason: ShortcutReason,
+}
+
+impl ShortcutDecision {
+    /// The result of [ImplKeyboardHandler::on_pre_key_event], which runs before the renderer
+    /// sees the key, and its `is_keyboard_shortcut` out-parameter: `(handled, is_shortcut)`.
+    fn pre_key_event(&self) -> (bool, bool) {
+        match self.route {
+            ShortcutRoute::Embedder => (true, false),
+            // Marking the key as a shortcut makes CEF call `on_key_event` if the page
+            // doesn't handle it.
+            ShortcutRoute::Page => (false, true),
+            ShortcutRoute::PassThrough => (false, false),
+        }
+    }
+
+    /// The result of [ImplKeyboardHandler::on_key_event], which runs after the page declined the
+    /// key.
+    fn key_event(&self) -> bool {
+        self.route == ShortcutRoute::Page
+    }
+}
+
+type ShortcutCallback = Arc<dyn Fn(&Browser, &KeyEvent) + Send + Sync>;
+
+/// Builder of a [KeyboardHandler] which routes keyboard shortcuts between the page and the
+/// application with ordered rules.
+///
+/// The first [ShortcutRule] which lists the key decides its route, and keys no rule lists take
+/// the default route. The callback of [ShortcutPolicy::on_shortcut] handles the keys routed to
+/// the application.
+///
+/// ```ignore
+/// let handler = ShortcutPolicy::new(ShortcutRoute::PassThrough)
+///     .rule(ShortcutRule::Embedder(vec![Shortcut::primary(VKEY_W), Shortcut::new(VKEY_F11, EventFlags::empty())]))
+///     .rule(ShortcutRule::Page(vec![Shortcut::primary(VKEY_T)]))
+///     .on_shortcut(|browser, event| handle_shortcut(browser, event))
+///     .build();
+/// ```
+#[derive(Clone, Default)]
+pub struct ShortcutPolicy {
+    rules: Vec<ShortcutRule>,
+    default_route: ShortcutRoute,
+    on_shortcut: Option<ShortcutCallback>,
+}
+
+impl ShortcutPolicy {
+    /// Create a policy without rules, which routes every key to `default_route`.
+    pub fn new(default_route: ShortcutRoute) -> Self {
+        Self {
+            default_route,
+            ..Default::default()
+        }
+    }
+
+    /// Add `rule` after the rules added before, which take precedence over it.
+    pub fn rule(mut self, rule: ShortcutRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Called with the key down events the application handles.
+    pub fn on_shortcut(
+        mut self,
+        callback: impl Fn(&Browser, &KeyEvent) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_shortcut = Some(Arc::new(callback));
+        self
+    }
+
+    /// Where the policy routes `event`, and why, e.g. to debug the rules.
+    pub fn decide(&self, event: &KeyEvent) -> ShortcutDecision {
+        let is_key_down = matches!(
+            event.type_,
+            KeyEventType::Rawkeydown | KeyEventType::Keydown
+        );
+        if !is_key_down {
+            return ShortcutDecision {
+                route: ShortcutRoute::PassThrough,
+                reason: ShortcutReason::NotKeyDown,
+            };
+        }
+        self.rules
+            .iter()
+            .position(|rule| rule.matches(event))
+            .map(|index| ShortcutDecision {
+                route: self.rules[index].route(),
+                reason: ShortcutReason::Rule(index),
+            })
+            .unwrap_or(ShortcutDecision {
+                route: self.default_route,
+                reason: ShortcutReason::Default,
+            })
+    }
+
+    /// Build the [KeyboardHandler].
+    pub fn build(self) -> KeyboardHandler {
+        KeyboardHandler::new(BuiltShortcutPolicy {
+            object: std::ptr::null_mut(),
+            policy: self,
+        })
+    }
+
+    fn handle(&self, browser: Option<&impl ImplBrowser>, event: &KeyEvent) {
+        if let (Some(callback), Some(browser)) = (&self.on_shortcut, browser) {
+            callback(&Browser::from_impl(browser), event);
+        }
+    }
+}
+
+struct BuiltShortcutPolicy {
+    object: *mut RcImpl<_cef_keyboard_handler_t, Self>,
+    policy: ShortcutPolicy,
+}
+
+wrap_rc!(
+    BuiltShortcutPolicy,
+    WrapKeyboardHandler,
+    _cef_keyboard_handler_t,
+    policy
+);
+
+impl ImplKeyboardHandler for BuiltShortcutPolicy {
+    fn on_pre_key_event(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        event: Option<&KeyEvent>,
+        _os_event: Option<&mut XEvent>,
+        is_keyboard_shortcut: Option<&mut c_int>,
+    ) -> c_int {
+        let Some(event) = event else {
+            return 0;
+        };
+        let (handled, is_shortcut) = self.policy.decide(event).pre_key_event();
+        if let (true, Some(is_keyboard_shortcut)) = (is_shortcut, is_keyboard_shortcut) {
+            *is_keyboard_shortcut = 1;
+        }
+        if handled {
+            self.policy.handle(browser, event);
+        }
+        handled.into()
+    }
+
+    fn on_key_event(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        event: Option<&KeyEvent>,
+        _os_event: Option<&mut XEvent>,
+    ) -> c_int {
+        let Some(event) = event else {
+            return 0;
+        };
+        let handled = self.policy.decide(event).key_event();
+        if handled {
+            self.policy.handle(browser, event);
+        }
+        handled.into()
+    }
+
+    fn get_raw(&self) -> *mut _cef_keyboard_handler_t {
+        self.object as *mut _
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures::FakeBrowser;
+
+    #[test]
+    fn test_copy_shortcut() {
+        #[cfg(target_os = "macos")]
+        let modifier = EventFlags::COMMAND_DOWN;
+        #[cfg(not(target_os = "macos"))]
+        let modifier = EventFlags::CONTROL_DOWN;
+
+        assert_eq!(
+            WellKnownShortcut::Copy.key_combination(),
+            (VKEY_C, modifier)
+        );
+
+        let [down, up] = WellKnownShortcut::Copy.key_events();
+        assert_eq!(down.type_, KeyEventType::Rawkeydown);
+        assert_eq!(up.type_, KeyEventType::Keyup);
+        assert_eq!(
+            (down.windows_key_code, down.modifiers),
+            (VKEY_C, modifier.bits())
+        );
+        assert_eq!(
+            (up.windows_key_code, up.modifiers),
+            (VKEY_C, modifier.bits())
+        );
+    }
+
+    #[test]
+    fn test_event_flags() {
+        let flags = EventFlags::SHIFT_DOWN | EventFlags::CONTROL_DOWN;
+        assert!(flags.contains(EventFlags::SHIFT_DOWN));
+        assert!(!flags.contains(EventFlags::SHIFT_DOWN | EventFlags::ALT_DOWN));
+        assert!(flags.intersects(EventFlags::SHIFT_DOWN | EventFlags::ALT_DOWN));
+        assert_eq!(flags & EventFlags::CONTROL_DOWN, EventFlags::CONTROL_DOWN);
+        assert!(EventFlags::default().is_empty());
+        assert_eq!(EventFlags::empty(), EventFlags::NONE);
+
+        // Bits without a constant survive the round trip through the C enum.
+        let unknown = EventFlags::from_bits(1 << 20) | EventFlags::ALT_DOWN;
+        let raw: cef_sys::cef_event_flags_t = unknown.into();
+        assert_eq!(
+            raw.0,
+            (1 << 20) | cef_sys::cef_event_flags_t::EVENTFLAG_ALT_DOWN.0
+        );
+        assert_eq!(EventFlags::from(raw), unknown);
+    }
+
+    const VKEY_T: c_int = 0x54;
+    const VKEY_W: c_int = 0x57;
+    const VKEY_F11: c_int = 0x7A;
+
+    fn key_down(windows_key_code: c_int, modifiers: EventFlags) -> KeyEvent {
+        KeyEvent {
+            type_: cef_key_event_type_t::KEYEVENT_RAWKEYDOWN.into(),
+            modifiers: modifiers.bits(),
+            windows_key_code,
+            ..Default::default()
+        }
+    }
+
+    fn policy() -> ShortcutPolicy {
+        ShortcutPolicy::new(ShortcutRoute::PassThrough)
+            .rule(ShortcutRule::Embedder(vec![
+                Shortcut::primary(VKEY_W),
+                Shortcut::new(VKEY_F11, EventFlags::empty()),
+            ]))
+            .rule(ShortcutRule::Page(vec![
+                Shortcut::primary(VKEY_T),
+                Shortcut::primary(VKEY_W),
+            ]))
+    }
+
+    #[test]
+    fn test_shortcut_decisions() {
+        let policy = policy();
+        let decide = |event: &KeyEvent| {
+            let decision = policy.decide(event);
+            (decision.route, decision.reason)
+        };
+        // The first matching rule wins, even if a later one lists the key as well.
+        assert_eq!(
+            decide(&key_down(VKEY_W, PRIMARY)),
+            (ShortcutRoute::Embedder, ShortcutReason::Rule(0))
+        );
+        // Lock keys don't change the combination.
+        let caps_lock = EventFlags::CAPS_LOCK_ON;
+        assert_eq!(
+            decide(&key_down(VKEY_F11, caps_lock)),
+            (ShortcutRoute::Embedder, ShortcutReason::Rule(0))
+        );
+        assert_eq!(
+            decide(&key_down(VKEY_T, PRIMARY)),
+            (ShortcutRoute::Page, ShortcutReason::Rule(1))
+        );
+        assert_eq!(
+            decide(&key_down(VKEY_T, PRIMARY | SHIFT)),
+            (ShortcutRoute::PassThrough, ShortcutReason::Default)
+        );
+        assert_eq!(
+            decide(&key_down(VKEY_W, EventFlags::empty())),
+            (ShortcutRoute::PassThrough, ShortcutReason::Default)
+        );
+        let mut key_up = key_down(VKEY_W, PRIMARY);
+        key_up.type_ = cef_key_event_type_t::KEYEVENT_KEYUP.into();
+        assert_eq!(
+            decide(&key_up),
+            (ShortcutRoute::PassThrough, ShortcutReason::NotKeyDown)
+        );
+        assert_eq!(
+            ShortcutPolicy::new(ShortcutRoute::Embedder)
+                .decide(&key_down(VKEY_T, EventFlags::empty()))
+                .route,
+            ShortcutRoute::Embedder
+        );
+    }
+
+    #[test]
+    fn test_shortcut_handler() {
+        let handled = Arc::new(std::sync::Mutex::new(vec![]));
+        let shortcuts = handled.clone();
+        let handler = policy()
+            .on_shortcut(move |_, event| shortcuts.lock().unwrap().push(event.windows_key_code))
+            .build();
+        let browser = FakeBrowser::new(0).build();
+        let pre_key_event = |event: &KeyEvent| {
+            let mut is_shortcut = 0;
+            let handled =
+                handler.on_pre_key_event(Some(&browser), Some(event), None, Some(&mut is_shortcut));
+            (handled, is_shortcut)
+        };
+
+        // Routed to the application before the page sees it.
+        assert_eq!(
+            pre_key_event(&key_down(VKEY_F11, EventFlags::empty())),
+            (1, 0)
+        );
+        // Passed to the page, and to the application once the page declined it.
+        let new_tab = key_down(VKEY_T, PRIMARY);
+        assert_eq!(pre_key_event(&new_tab), (0, 1));
+        assert_eq!(*handled.lock().unwrap(), [VKEY_F11]);
+        assert_eq!(
+            handler.on_key_event(Some(&browser), Some(&new_tab), None),
+            1
+        );
+        // Left alone in both phases.
+        let typed = key_down(VKEY_W, EventFlags::empty());
+        assert_eq!(pre_key_event(&typed), (0, 0));
+        assert_eq!(handler.on_key_event(Some(&browser), Some(&typed), None), 0);
+        assert_eq!(*handled.lock().unwrap(), [VKEY_F11, VKEY_T]);
+    }
+}
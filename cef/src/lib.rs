@@ -76,11 +76,75 @@ But please understand that some requests might result into not planned.
 - Provide tools to bundle and distribute application.
 */
 
+pub mod abi;
+pub mod app;
 pub mod args;
+pub mod browser;
+pub mod cache;
+pub mod callback;
+pub mod chain;
+pub mod client;
+pub mod compat;
+pub mod context;
+pub mod context_menu;
+pub mod css;
+pub mod cursor;
+pub mod devtools;
+pub mod diagnostics;
+pub mod display;
+pub mod dom;
+pub mod edit;
+#[cfg(feature = "serde")]
+pub mod eval;
+pub mod flatpak;
+pub mod font;
+pub mod frame;
+pub mod ime;
+pub mod keyboard;
+pub mod life_span;
+pub mod locale;
+pub mod media_devices;
+pub mod media_router;
+pub mod message;
+pub mod middleware;
+pub mod navigation;
+pub mod notification;
+pub mod osr;
+pub mod pool;
+pub mod presets;
+pub mod print;
+pub mod process;
+pub mod profile;
+pub mod pump;
 pub mod rc;
+pub mod render;
+pub mod request;
+pub mod request_context;
+pub mod scheme;
+pub mod shutdown;
+pub mod spellcheck;
+pub mod ssl;
+pub mod stats;
+pub mod storage;
 pub mod string;
+pub mod sw;
+pub mod switches;
+pub mod testing;
+pub mod thread;
+pub mod throttle;
+pub mod touch;
+pub mod url;
+pub mod v8;
+pub mod value;
+pub mod window_state;
 
 mod bindings;
+#[cfg(test)]
+mod fixtures;
+mod future;
+mod host;
+mod window;
 pub use bindings::*;
+pub use window::NativeWindowHandle;
 
 pub use cef_sys as sys;
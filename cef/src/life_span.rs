@@ -0,0 +1,710 @@
+//! Life span module
+//!
+//! [PopupBlocker] cancels the popups a page opens, e.g. with `window.open` or a link with
+//! `target="_blank"`, in [ImplLifeSpanHandler::on_before_popup].
+//!
+//! [OsrPopups] decides per popup of a windowless browser with a [PopupAction], and opens the
+//! popups it keeps as windowless browsers too, each painting into a [FrameStream] of its own.
+//! A popup keeps the client of the browser which opened it, so the render handler of
+//! [OsrPopups::render_handler] routes the paints of each popup to its stream, and those of the
+//! other browsers to the handler of the view.
+//!
+//! ```ignore
+//! let frames = FrameStreamBuilder::new(800, 600).build();
+//! let popups = OsrPopups::new(|_request| {
+//!     PopupAction::open_as_osr(|browser, frames| tabs.open(browser, frames))
+//! });
+//! let client = ClientBuilder::new()
+//!     .render_handler(popups.render_handler(frames.render_handler()))
+//!     .push_life_span_handler(popups.life_span_handler())
+//!     .build();
+//! ```
+
+use std::{
+    cell::RefCell,
+    collections::{HashMap, VecDeque},
+    os::raw::c_int,
+    rc::Rc,
+    sync::Arc,
+};
+
+use cef_sys::{_cef_life_span_handler_t, _cef_render_handler_t};
+
+use crate::{
+    browser::BrowserId,
+    osr::{FrameStream, FrameStreamBuilder},
+    rc::{wrap_rc, RcImpl},
+    throttle::host,
+    AcceleratedPaintInfo, AccessibilityHandler, Browser, BrowserSettings, CefStringUtf16,
+    DragOperationsMask, HorizontalAlignment, ImplBrowser, ImplBrowserHost, ImplClient,
+    ImplDictionaryValue, ImplDragData, ImplFrame, ImplLifeSpanHandler, ImplRenderHandler,
+    LifeSpanHandler, PaintElementType, PopupFeatures, Range, Rect, RenderHandler, ScreenInfo, Size,
+    TextInputMode, TouchHandleState, WindowInfo, WindowOpenDisposition,
+};
+
+/// A life span handler which blocks popups, except those of an allowlist of domains.
+///
+/// ```ignore
+/// let popup_blocker = LifeSpanHandler::new(PopupBlocker::with_allowlist(vec![
+///     "accounts.example.com".to_string(),
+/// ]));
+/// ```
+pub struct PopupBlocker {
+    object: *mut RcImpl<_cef_life_span_handler_t, Self>,
+    allowlist: Arc<[String]>,
+}
+
+wrap_rc!(
+    PopupBlocker,
+    WrapLifeSpanHandler,
+    _cef_life_span_handler_t,
+    allowlist
+);
+
+impl PopupBlocker {
+    /// Block all popups.
+    pub fn new() -> Self {
+        Self::with_allowlist(Vec::new())
+    }
+
+    /// Only allow the popups whose target URL is on one of `domains` or their subdomains.
+    pub fn with_allowlist(domains: Vec<String>) -> Self {
+        Self {
+            object: std::ptr::null_mut(),
+            allowlist: domains
+                .into_iter()
+                .map(|domain| domain.trim_matches('.').to_ascii_lowercase())
+                .collect(),
+        }
+    }
+
+    /// `true` if a popup of `target_url` is allowed.
+    pub fn allows(&self, target_url: &str) -> bool {
+        let Some(host) = host(target_url) else {
+            return false;
+        };
+        let host = host.to_ascii_lowercase();
+        self.allowlist.iter().any(|domain| {
+            host.strip_suffix(domain.as_str())
+                .is_some_and(|subdomain| subdomain.is_empty() || subdomain.ends_with('.'))
+        })
+    }
+}
+
+impl Default for PopupBlocker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ImplLifeSpanHandler for PopupBlocker {
+    fn on_before_popup(
+        &self,
+        _browser: Option<&impl ImplBrowser>,
+        _frame: Option<&impl ImplFrame>,
+        _popup_id: c_int,
+        target_url: Option<&CefStringUtf16>,
+        _target_frame_name: Option<&CefStringUtf16>,
+        _target_disposition: WindowOpenDisposition,
+        _user_gesture: c_int,
+        _popup_features: Option<&PopupFeatures>,
+        _window_info: Option<&mut WindowInfo>,
+        _client: Option<&mut impl ImplClient>,
+        _settings: Option<&mut BrowserSettings>,
+        _extra_info: Option<&mut impl ImplDictionaryValue>,
+        _no_javascript_access: Option<&mut c_int>,
+    ) -> c_int {
+        let allowed = target_url.is_some_and(|url| self.allows(&url.to_string()));
+        (!allowed).into()
+    }
+
+    fn get_raw(&self) -> *mut _cef_life_span_handler_t {
+        self.object as *mut _
+    }
+}
+
+/// The size of an [OsrPopups] popup whose page didn't ask for one, in DIP.
+const DEFAULT_POPUP_SIZE: (c_int, c_int) = (800, 600);
+
+/// A popup a page is about to open, see [OsrPopups::new].
+#[derive(Clone, Debug)]
+pub struct PopupRequest {
+    /// The [identifier](Browser::identifier) of the browser which opens the popup.
+    pub opener: BrowserId,
+    pub target_url: String,
+    pub target_frame_name: String,
+    pub disposition: WindowOpenDisposition,
+    pub user_gesture: bool,
+    /// The size the page asked for, e.g. with the features of `window.open`, in DIP.
+    pub size: Option<(c_int, c_int)>,
+}
+
+type OnCreated = dyn FnOnce(Browser, FrameStream);
+
+/// What [OsrPopups] does with a popup.
+pub enum PopupAction {
+    /// Open the popup as CEF would, painting into the handler of the view.
+    Allow,
+    Block,
+    /// Open the popup as a windowless browser which paints into a [FrameStream] of its own, and
+    /// call `on_created` with both once it was created, e.g. to show it in a new tab. The stream
+    /// ends when the popup closes.
+    OpenAsOsr {
+        on_created: Box<OnCreated>,
+    },
+}
+
+impl PopupAction {
+    /// A [PopupAction::OpenAsOsr] which calls `on_created`.
+    pub fn open_as_osr(on_created: impl FnOnce(Browser, FrameStream) + 'static) -> Self {
+        Self::OpenAsOsr {
+            on_created: Box::new(on_created),
+        }
+    }
+}
+
+/// A popup which [OsrPopups] opens as a windowless browser.
+struct OsrPopup {
+    opener: BrowserId,
+    popup_id: c_int,
+    render_handler: RenderHandler,
+    /// Taken when the popup was created.
+    created: Option<(FrameStream, Box<OnCreated>)>,
+}
+
+#[derive(Default)]
+struct OsrPopupState {
+    /// The popups which were not created yet, in the order they were opened.
+    pending: VecDeque<OsrPopup>,
+    popups: HashMap<BrowserId, OsrPopup>,
+}
+
+impl OsrPopupState {
+    /// The popup of `browser`, which is the first pending popup of its opener the first time.
+    fn bind(&mut self, browser: &impl ImplBrowser) -> Option<&mut OsrPopup> {
+        let id = browser.get_identifier();
+        if !self.popups.contains_key(&id) {
+            if browser.is_popup() == 0 {
+                return None;
+            }
+            let opener = browser.get_host()?.get_opener_identifier();
+            let index = self
+                .pending
+                .iter()
+                .position(|popup| popup.opener == opener)?;
+            let popup = self.pending.remove(index)?;
+            self.popups.insert(id, popup);
+        }
+        self.popups.get_mut(&id)
+    }
+}
+
+/// Opens the popups of windowless browsers as windowless browsers, see the
+/// [module documentation](self).
+///
+/// Every method must be called on the UI thread, like the handlers are.
+pub struct OsrPopups {
+    state: Rc<RefCell<OsrPopupState>>,
+    decide: Rc<dyn Fn(&PopupRequest) -> PopupAction>,
+}
+
+impl OsrPopups {
+    /// Decide with `decide` what to do with each popup.
+    pub fn new(decide: impl Fn(&PopupRequest) -> PopupAction + 'static) -> Self {
+        Self {
+            state: Default::default(),
+            decide: Rc::new(decide),
+        }
+    }
+
+    /// The handler which opens the popups, for the client of the windowless browsers.
+    pub fn life_span_handler(&self) -> LifeSpanHandler {
+        LifeSpanHandler::new(OsrPopupLifeSpan {
+            object: std::ptr::null_mut(),
+            state: self.state.clone(),
+            decide: self.decide.clone(),
+        })
+    }
+
+    /// The render handler of the client, which paints the popups opened as windowless browsers
+    /// into their streams, and every other browser into `view`.
+    pub fn render_handler(&self, view: RenderHandler) -> RenderHandler {
+        RenderHandler::new(OsrPopupRenderHandler {
+            object: std::ptr::null_mut(),
+            state: self.state.clone(),
+            view,
+        })
+    }
+
+    /// The number of popups which were opened as windowless browsers and are not closed yet.
+    pub fn popup_count(&self) -> usize {
+        let state = self.state.borrow();
+        state.pending.len() + state.popups.len()
+    }
+}
+
+struct OsrPopupLifeSpan {
+    object: *mut RcImpl<_cef_life_span_handler_t, Self>,
+    state: Rc<RefCell<OsrPopupState>>,
+    decide: Rc<dyn Fn(&PopupRequest) -> PopupAction>,
+}
+
+wrap_rc!(
+    OsrPopupLifeSpan,
+    WrapLifeSpanHandler,
+    _cef_life_span_handler_t,
+    state,
+    decide
+);
+
+impl ImplLifeSpanHandler for OsrPopupLifeSpan {
+    fn on_before_popup(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        _frame: Option<&impl ImplFrame>,
+        popup_id: c_int,
+        target_url: Option<&CefStringUtf16>,
+        target_frame_name: Option<&CefStringUtf16>,
+        target_disposition: WindowOpenDisposition,
+        user_gesture: c_int,
+        popup_features: Option<&PopupFeatures>,
+        window_info: Option<&mut WindowInfo>,
+        _client: Option<&mut impl ImplClient>,
+        _settings: Option<&mut BrowserSettings>,
+        _extra_info: Option<&mut impl ImplDictionaryValue>,
+        _no_javascript_access: Option<&mut c_int>,
+    ) -> c_int {
+        let request = PopupRequest {
+            opener: browser
+                .map(|browser| browser.get_identifier())
+                .unwrap_or_default(),
+            target_url: target_url.map(ToString::to_string).unwrap_or_default(),
+            target_frame_name: target_frame_name
+                .map(ToString::to_string)
+                .unwrap_or_default(),
+            disposition: target_disposition,
+            user_gesture: user_gesture != 0,
+            size: popup_features
+                .filter(|features| features.width_set != 0 && features.height_set != 0)
+                .map(|features| (features.width, features.height)),
+        };
+        match (self.decide)(&request) {
+            PopupAction::Allow => 0,
+            PopupAction::Block => 1,
+            PopupAction::OpenAsOsr { on_created } => {
+                let Some(window_info) = window_info else {
+                    return 1;
+                };
+                window_info.windowless_rendering_enabled = 1;
+                let (width, height) = request.size.unwrap_or(DEFAULT_POPUP_SIZE);
+                let frames = FrameStreamBuilder::new(width, height).build();
+                self.state.borrow_mut().pending.push_back(OsrPopup {
+                    opener: request.opener,
+                    popup_id,
+                    render_handler: frames.render_handler(),
+                    created: Some((frames, on_created)),
+                });
+                0
+            }
+        }
+    }
+
+    fn on_before_popup_aborted(&self, browser: Option<&impl ImplBrowser>, popup_id: c_int) {
+        let Some(opener) = browser.map(|browser| browser.get_identifier()) else {
+            return;
+        };
+        // Ends the stream.
+        let _aborted = {
+            let mut state = self.state.borrow_mut();
+            let index = state
+                .pending
+                .iter()
+                .position(|popup| popup.opener == opener && popup.popup_id == popup_id);
+            index.and_then(|index| state.pending.remove(index))
+        };
+    }
+
+    fn on_after_created(&self, browser: Option<&impl ImplBrowser>) {
+        let Some(browser) = browser else {
+            return;
+        };
+        let created = self
+            .state
+            .borrow_mut()
+            .bind(browser)
+            .and_then(|popup| popup.created.take());
+        // The callback may open or close browsers, so not while the state is borrowed.
+        if let Some((frames, on_created)) = created {
+            on_created(Browser::from_impl(browser), frames);
+        }
+    }
+
+    fn on_before_close(&self, browser: Option<&impl ImplBrowser>) {
+        if let Some(browser) = browser {
+            // Releases the render handler of the popup, which ends its stream.
+            let _closed = self
+                .state
+                .borrow_mut()
+                .popups
+                .remove(&browser.get_identifier());
+        }
+    }
+
+    fn get_raw(&self) -> *mut _cef_life_span_handler_t {
+        self.object as *mut _
+    }
+}
+
+struct OsrPopupRenderHandler {
+    object: *mut RcImpl<_cef_render_handler_t, Self>,
+    state: Rc<RefCell<OsrPopupState>>,
+    view: RenderHandler,
+}
+
+wrap_rc!(
+    OsrPopupRenderHandler,
+    WrapRenderHandler,
+    _cef_render_handler_t,
+    state,
+    view
+);
+
+impl OsrPopupRenderHandler {
+    /// The handler `browser` paints into.
+    fn route(&self, browser: Option<&impl ImplBrowser>) -> RenderHandler {
+        browser
+            .and_then(|browser| {
+                self.state
+                    .borrow_mut()
+                    .bind(browser)
+                    .map(|popup| popup.render_handler.clone())
+            })
+            .unwrap_or_else(|| self.view.clone())
+    }
+}
+
+impl ImplRenderHandler for OsrPopupRenderHandler {
+    fn get_accessibility_handler(&self) -> Option<AccessibilityHandler> {
+        self.view.get_accessibility_handler()
+    }
+
+    fn get_root_screen_rect(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        rect: Option<&mut Rect>,
+    ) -> c_int {
+        self.route(browser).get_root_screen_rect(browser, rect)
+    }
+
+    fn get_view_rect(&self, browser: Option<&impl ImplBrowser>, rect: Option<&mut Rect>) {
+        self.route(browser).get_view_rect(browser, rect)
+    }
+
+    fn get_screen_point(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        view_x: c_int,
+        view_y: c_int,
+        screen_x: Option<&mut c_int>,
+        screen_y: Option<&mut c_int>,
+    ) -> c_int {
+        self.route(browser)
+            .get_screen_point(browser, view_x, view_y, screen_x, screen_y)
+    }
+
+    fn get_screen_info(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        screen_info: Option<&mut ScreenInfo>,
+    ) -> c_int {
+        self.route(browser).get_screen_info(browser, screen_info)
+    }
+
+    fn on_popup_show(&self, browser: Option<&impl ImplBrowser>, show: c_int) {
+        self.route(browser).on_popup_show(browser, show)
+    }
+
+    fn on_popup_size(&self, browser: Option<&impl ImplBrowser>, rect: Option<&Rect>) {
+        self.route(browser).on_popup_size(browser, rect)
+    }
+
+    fn on_paint(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        type_: PaintElementType,
+        dirty_rects_count: usize,
+        dirty_rects: Option<&Rect>,
+        buffer: *const u8,
+        width: c_int,
+        height: c_int,
+    ) {
+        self.route(browser).on_paint(
+            browser,
+            type_,
+            dirty_rects_count,
+            dirty_rects,
+            buffer,
+            width,
+            height,
+        )
+    }
+
+    fn on_accelerated_paint(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        type_: PaintElementType,
+        dirty_rects_count: usize,
+        dirty_rects: Option<&Rect>,
+        info: Option<&AcceleratedPaintInfo>,
+    ) {
+        self.route(browser).on_accelerated_paint(
+            browser,
+            type_,
+            dirty_rects_count,
+            dirty_rects,
+            info,
+        )
+    }
+
+    fn get_touch_handle_size(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        orientation: HorizontalAlignment,
+        size: Option<&mut Size>,
+    ) {
+        self.route(browser)
+            .get_touch_handle_size(browser, orientation, size)
+    }
+
+    fn on_touch_handle_state_changed(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        state: Option<&TouchHandleState>,
+    ) {
+        self.route(browser)
+            .on_touch_handle_state_changed(browser, state)
+    }
+
+    fn start_dragging(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        drag_data: Option<&impl ImplDragData>,
+        allowed_ops: DragOperationsMask,
+        x: c_int,
+        y: c_int,
+    ) -> c_int {
+        self.route(browser)
+            .start_dragging(browser, drag_data, allowed_ops, x, y)
+    }
+
+    fn update_drag_cursor(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        operation: DragOperationsMask,
+    ) {
+        self.route(browser).update_drag_cursor(browser, operation)
+    }
+
+    fn on_scroll_offset_changed(&self, browser: Option<&impl ImplBrowser>, x: f64, y: f64) {
+        self.route(browser).on_scroll_offset_changed(browser, x, y)
+    }
+
+    fn on_ime_composition_range_changed(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        selected_range: Option<&Range>,
+        character_bounds_count: usize,
+        character_bounds: Option<&Rect>,
+    ) {
+        self.route(browser).on_ime_composition_range_changed(
+            browser,
+            selected_range,
+            character_bounds_count,
+            character_bounds,
+        )
+    }
+
+    fn on_text_selection_changed(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        selected_text: Option<&CefStringUtf16>,
+        selected_range: Option<&Range>,
+    ) {
+        self.route(browser)
+            .on_text_selection_changed(browser, selected_text, selected_range)
+    }
+
+    fn on_virtual_keyboard_requested(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        input_mode: TextInputMode,
+    ) {
+        self.route(browser)
+            .on_virtual_keyboard_requested(browser, input_mode)
+    }
+
+    fn get_raw(&self) -> *mut _cef_render_handler_t {
+        self.object as *mut _
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::task::{Context, Poll, Waker};
+
+    use super::*;
+    use crate::{
+        fixtures::{FakeBrowser, FakeHost},
+        CefString, CefStringUtf8, Client, DictionaryValue, Frame,
+    };
+
+    #[test]
+    fn test_allowlist() {
+        let blocker = PopupBlocker::with_allowlist(vec!["Allowed.com".to_string()]);
+        assert!(blocker.allows("https://allowed.com/popup"));
+        assert!(blocker.allows("https://login.allowed.com:8443/popup"));
+        assert!(!blocker.allows("https://evil.com/popup"));
+        assert!(!blocker.allows("https://notallowed.com/popup"));
+        assert!(!blocker.allows("https://allowed.com.evil.com/popup"));
+        assert!(!blocker.allows("about:blank"));
+    }
+
+    #[test]
+    fn test_block_all() {
+        assert!(!PopupBlocker::new().allows("https://allowed.com/popup"));
+    }
+
+    /// A browser opened by `opener`, or a browser which isn't a popup for `None`.
+    fn fake_browser(id: BrowserId, opener: Option<BrowserId>) -> Browser {
+        FakeBrowser::new(id)
+            .popup(opener.is_some())
+            .host(FakeHost::new().opener(opener.unwrap_or_default()).build())
+            .build()
+    }
+
+    fn open(
+        life_span: &LifeSpanHandler,
+        opener: &Browser,
+        popup_id: c_int,
+        url: &str,
+        window_info: &mut WindowInfo,
+    ) -> c_int {
+        let url = CefString::from(&CefStringUtf8::from(url));
+        let features = PopupFeatures {
+            width: 400,
+            width_set: 1,
+            height: 300,
+            height_set: 1,
+            ..Default::default()
+        };
+        life_span.on_before_popup(
+            Some(opener),
+            Option::<&Frame>::None,
+            popup_id,
+            Some(&url),
+            None,
+            WindowOpenDisposition::default(),
+            1,
+            Some(&features),
+            Some(window_info),
+            Option::<&mut Client>::None,
+            None,
+            Option::<&mut DictionaryValue>::None,
+            None,
+        )
+    }
+
+    fn view_size(render_handler: &RenderHandler, browser: &Browser) -> (c_int, c_int) {
+        let mut rect = Rect::default();
+        render_handler.get_view_rect(Some(browser), Some(&mut rect));
+        (rect.width, rect.height)
+    }
+
+    #[test]
+    fn test_osr_popups() {
+        let created = Rc::new(RefCell::new(Vec::new()));
+        let popups = OsrPopups::new({
+            let created = created.clone();
+            move |request: &PopupRequest| match request.target_url.as_str() {
+                "https://example.com/blocked" => PopupAction::Block,
+                "https://example.com/allowed" => PopupAction::Allow,
+                _ => {
+                    let created = created.clone();
+                    PopupAction::open_as_osr(move |browser: Browser, frames: FrameStream| {
+                        created.borrow_mut().push((browser.identifier(), frames))
+                    })
+                }
+            }
+        });
+        let life_span = popups.life_span_handler();
+        let view = FrameStreamBuilder::new(320, 240).build();
+        let render_handler = popups.render_handler(view.render_handler());
+        let opener = fake_browser(1, None);
+
+        let mut window_info = WindowInfo::default();
+        assert_eq!(
+            open(
+                &life_span,
+                &opener,
+                1,
+                "https://example.com/blocked",
+                &mut window_info
+            ),
+            1
+        );
+        assert_eq!(
+            open(
+                &life_span,
+                &opener,
+                2,
+                "https://example.com/allowed",
+                &mut window_info
+            ),
+            0
+        );
+        assert_eq!(window_info.windowless_rendering_enabled, 0);
+        assert_eq!(popups.popup_count(), 0);
+
+        assert_eq!(
+            open(
+                &life_span,
+                &opener,
+                3,
+                "https://example.com/tab",
+                &mut window_info
+            ),
+            0
+        );
+        assert_eq!(window_info.windowless_rendering_enabled, 1);
+        assert_eq!(popups.popup_count(), 1);
+
+        // The popup paints into its own stream, sized as the page asked, before it is created.
+        let popup = fake_browser(2, Some(1));
+        assert_eq!(view_size(&render_handler, &popup), (400, 300));
+        assert_eq!(view_size(&render_handler, &opener), (320, 240));
+        assert!(created.borrow().is_empty());
+
+        life_span.on_after_created(Some(&popup));
+        let (id, frames) = created.borrow_mut().pop().unwrap();
+        assert_eq!(id, 2);
+        assert_eq!(popups.popup_count(), 1);
+
+        let mut cx = Context::from_waker(Waker::noop());
+        assert!(frames.poll_next(&mut cx).is_pending());
+        life_span.on_before_close(Some(&popup));
+        assert_eq!(popups.popup_count(), 0);
+        assert!(matches!(frames.poll_next(&mut cx), Poll::Ready(None)));
+        assert_eq!(view_size(&render_handler, &popup), (320, 240));
+
+        // An aborted popup is dropped.
+        open(
+            &life_span,
+            &opener,
+            4,
+            "https://example.com/tab",
+            &mut window_info,
+        );
+        life_span.on_before_popup_aborted(Some(&opener), 4);
+        assert_eq!(popups.popup_count(), 0);
+    }
+}
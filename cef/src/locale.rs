@@ -0,0 +1,228 @@
+//! Locale module
+//!
+//! The UI locale and `Accept-Language` list of CEF. [Settings::set_locale] picks the UI locale at
+//! startup, and [RequestContext::set_accept_languages] changes the languages requested from
+//! websites at runtime. Language tags are validated and normalized as BCP 47 tags, because
+//! Chromium silently ignores POSIX style tags such as `de_DE`.
+
+use std::{env, fmt};
+
+use crate::{
+    command_line_get_global, request_context, value_create, CefString, CefStringUtf8,
+    ImplCommandLine, ImplValue, RequestContext, RequestContextSettings, Settings,
+};
+
+/// Preference which holds the comma separated `Accept-Language` list.
+pub const ACCEPT_LANGUAGES: &str = "intl.accept_languages";
+
+/// The locale CEF falls back to when the configured one has no `.pak` file.
+pub const DEFAULT_LOCALE: &str = "en-US";
+
+/// Why a locale couldn't be set.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LocaleError {
+    /// The tag is not a well-formed BCP 47 language tag.
+    InvalidTag(String),
+    /// CEF rejected the preference, with its error message.
+    Preference(String),
+}
+
+impl fmt::Display for LocaleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidTag(tag) => write!(f, "{tag:?} is not a BCP 47 language tag"),
+            Self::Preference(error) => write!(f, "failed to set {ACCEPT_LANGUAGES}: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for LocaleError {}
+
+/// Normalize the case of a BCP 47 language tag, e.g. `EN-latn-us` becomes `en-Latn-US`.
+///
+/// Only the syntax is checked, not whether the language exists. Underscores are rejected rather
+/// than converted, since they usually come from a POSIX locale which also carries an encoding.
+pub fn normalize_tag(tag: &str) -> Result<String, LocaleError> {
+    let invalid = || LocaleError::InvalidTag(tag.to_string());
+    let mut subtags = tag.split('-');
+    let language = subtags.next().unwrap_or_default();
+    if !matches!(language.len(), 2..=3 | 5..=8)
+        || !language.bytes().all(|byte| byte.is_ascii_alphabetic())
+    {
+        return Err(invalid());
+    }
+
+    let mut normalized = language.to_ascii_lowercase();
+    // Scripts and regions may only follow the language, later subtags are variants or extensions.
+    let (mut script, mut region, mut singleton) = (true, true, false);
+    for subtag in subtags {
+        if subtag.is_empty()
+            || subtag.len() > 8
+            || !subtag.bytes().all(|byte| byte.is_ascii_alphanumeric())
+        {
+            return Err(invalid());
+        }
+        normalized.push('-');
+        let alphabetic = subtag.bytes().all(|byte| byte.is_ascii_alphabetic());
+        match subtag.len() {
+            _ if singleton => normalized.push_str(&subtag.to_ascii_lowercase()),
+            4 if script && alphabetic => {
+                normalized.push_str(&subtag[..1].to_ascii_uppercase());
+                normalized.push_str(&subtag[1..].to_ascii_lowercase());
+            }
+            2 if region && alphabetic => normalized.push_str(&subtag.to_ascii_uppercase()),
+            1 => {
+                singleton = true;
+                normalized.push_str(&subtag.to_ascii_lowercase());
+            }
+            _ => normalized.push_str(&subtag.to_ascii_lowercase()),
+        }
+        script = false;
+        region &= subtag.len() == 4 && alphabetic;
+    }
+    Ok(normalized)
+}
+
+/// Normalize each tag and join them into an `Accept-Language` list, e.g. `de-DE,de,en`.
+pub fn accept_language_list(languages: &[&str]) -> Result<String, LocaleError> {
+    let tags = languages
+        .iter()
+        .map(|language| normalize_tag(language))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(tags.join(","))
+}
+
+impl Settings {
+    /// Set the UI locale, e.g. `de`. Returns [LocaleError::InvalidTag] without changing the
+    /// settings if `locale` is not a BCP 47 tag.
+    ///
+    /// CEF falls back to [DEFAULT_LOCALE] if the locales directory has no `.pak` file for the
+    /// locale, which [crate::context::preflight] reports.
+    pub fn set_locale(&mut self, locale: &str) -> Result<(), LocaleError> {
+        self.locale = cef_string(&normalize_tag(locale)?);
+        Ok(())
+    }
+
+    /// Set the default `Accept-Language` list of all request contexts, most preferred first.
+    /// Returns [LocaleError::InvalidTag] without changing the settings if any of `languages` is
+    /// not a BCP 47 tag.
+    pub fn set_accept_languages(&mut self, languages: &[&str]) -> Result<(), LocaleError> {
+        self.accept_language_list = cef_string(&accept_language_list(languages)?);
+        Ok(())
+    }
+}
+
+impl RequestContextSettings {
+    /// Set the `Accept-Language` list of the request context, most preferred first. Returns
+    /// [LocaleError::InvalidTag] without changing the settings if any of `languages` is not a
+    /// BCP 47 tag.
+    pub fn set_accept_languages(&mut self, languages: &[&str]) -> Result<(), LocaleError> {
+        self.accept_language_list = cef_string(&accept_language_list(languages)?);
+        Ok(())
+    }
+}
+
+impl RequestContext {
+    /// Change the `Accept-Language` list through the [ACCEPT_LANGUAGES] preference. Takes effect
+    /// for new requests, and for `navigator.languages` after the next navigation.
+    ///
+    /// Must be called on the UI thread.
+    pub fn set_accept_languages(&self, languages: &[&str]) -> Result<(), LocaleError> {
+        let list = accept_language_list(languages)?;
+        let mut value = value_create()
+            .ok_or_else(|| LocaleError::Preference("Failed to create value".to_string()))?;
+        value.set_string(Some(&cef_string(&list)));
+        request_context::set_preference(self, ACCEPT_LANGUAGES, &mut value)
+            .map_err(LocaleError::Preference)
+    }
+}
+
+/// The UI locale of the current process.
+///
+/// CEF passes the configured locale to every process with the `--lang` switch. Without it,
+/// Chromium picks the locale of the system, which is read from the environment on Linux, and
+/// otherwise reported as [DEFAULT_LOCALE].
+pub fn current() -> String {
+    let lang = command_line_get_global().and_then(|command_line| {
+        command_line
+            .get_switch_value(Some(&cef_string("lang")))
+            .map(|lang| lang.to_string())
+            .filter(|lang| !lang.is_empty())
+    });
+    lang.or_else(|| {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .into_iter()
+            .filter(|_| cfg!(target_os = "linux"))
+            .filter_map(|name| env::var(name).ok())
+            .find_map(|locale| from_posix(&locale))
+    })
+    .unwrap_or_else(|| DEFAULT_LOCALE.to_string())
+}
+
+/// Convert a POSIX locale such as `de_DE.UTF-8@euro` to a language tag, `None` for `C`, `POSIX`
+/// and invalid locales.
+fn from_posix(locale: &str) -> Option<String> {
+    let locale = locale.split(['.', '@']).next()?;
+    if locale.is_empty() || locale == "C" || locale == "POSIX" {
+        return None;
+    }
+    normalize_tag(&locale.replace('_', "-")).ok()
+}
+
+fn cef_string(value: &str) -> CefString {
+    CefString::from(&CefStringUtf8::from(value))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_normalize_tag() {
+        assert_eq!(normalize_tag("de").unwrap(), "de");
+        assert_eq!(normalize_tag("EN-us").unwrap(), "en-US");
+        assert_eq!(normalize_tag("zh-hant-tw").unwrap(), "zh-Hant-TW");
+        assert_eq!(normalize_tag("es-419").unwrap(), "es-419");
+        assert_eq!(normalize_tag("sl-ROZAJ").unwrap(), "sl-rozaj");
+        assert_eq!(normalize_tag("en-x-AB").unwrap(), "en-x-ab");
+        assert_eq!(normalize_tag("en-us-AB").unwrap(), "en-US-ab");
+    }
+
+    #[test]
+    fn test_invalid_tag() {
+        for tag in [
+            "",
+            "de_DE",
+            "d",
+            "de-",
+            "de--DE",
+            "de-DE.UTF-8",
+            "1de",
+            "de-toolongtag",
+        ] {
+            assert_eq!(
+                normalize_tag(tag),
+                Err(LocaleError::InvalidTag(tag.to_string())),
+                "{tag}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_accept_language_list() {
+        assert_eq!(
+            accept_language_list(&["de-de", "DE", "en"]).unwrap(),
+            "de-DE,de,en"
+        );
+        assert_eq!(accept_language_list(&[]).unwrap(), "");
+        assert!(accept_language_list(&["de", "en_US"]).is_err());
+    }
+
+    #[test]
+    fn test_from_posix() {
+        assert_eq!(from_posix("de_DE.UTF-8").as_deref(), Some("de-DE"));
+        assert_eq!(from_posix("sr_RS@latin").as_deref(), Some("sr-RS"));
+        assert_eq!(from_posix("C.UTF-8"), None);
+        assert_eq!(from_posix("POSIX"), None);
+    }
+}
@@ -0,0 +1,516 @@
+//! Media devices module
+//!
+//! Choosing the camera and microphone a page gets, without a permission prompt.
+//! [MediaAccessGrant] answers `getUserMedia` permission requests, and
+//! [RequestContext::set_default_media_device] picks the device Chromium opens when the page
+//! doesn't ask for one. [BrowserHost::enumerate_media_devices] lists the devices as the page sees
+//! them, so one can be picked by its label and opened with [MediaDeviceInfo::constraints].
+//! [BrowserHost::request_media_capture] grants the page of a browser access to its camera and
+//! microphone from the application, and reports the devices the page then gets.
+//!
+//! Granting media access without a prompt lets every page of the allowed origins record the user,
+//! so restrict [MediaAccessGrant] to the origins the application ships. Device ids are salted per
+//! origin and reset with the cookies, so don't persist them; keep the label instead. Labels are
+//! empty until the page was granted access to a device of the same kind once.
+
+use std::{fmt, future::Future, os::raw::c_int};
+
+use cef_sys::{_cef_permission_handler_t, cef_media_access_permission_types_t};
+
+use crate::{
+    devtools::{json, key, DevToolsError},
+    dictionary_value_create, list_value_create,
+    rc::{wrap_rc, RcImpl},
+    request_context, value_create, BrowserHost, CefString, CefStringUtf16, CefStringUtf8,
+    ImplBrowser, ImplBrowserHost, ImplDictionaryValue, ImplFrame, ImplListValue,
+    ImplMediaAccessCallback, ImplPermissionHandler, ImplValue, PermissionHandler, RequestContext,
+};
+
+/// Preference which holds the id of the microphone opened by default.
+pub const DEFAULT_AUDIO_CAPTURE_DEVICE: &str = "media.default_audio_capture_device";
+
+/// Preference which holds the id of the camera opened by default.
+pub const DEFAULT_VIDEO_CAPTURE_DEVICE: &str = "media.default_video_capture_device";
+
+/// The `kind` of a [MediaDeviceInfo].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MediaDeviceKind {
+    /// A microphone.
+    AudioInput,
+    /// A camera.
+    VideoInput,
+    /// A speaker or headset, selected per media element with `setSinkId`.
+    AudioOutput,
+}
+
+impl MediaDeviceKind {
+    /// The `kind` of the device in JavaScript.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::AudioInput => "audioinput",
+            Self::VideoInput => "videoinput",
+            Self::AudioOutput => "audiooutput",
+        }
+    }
+
+    fn from_str(kind: &str) -> Option<Self> {
+        match kind {
+            "audioinput" => Some(Self::AudioInput),
+            "videoinput" => Some(Self::VideoInput),
+            "audiooutput" => Some(Self::AudioOutput),
+            _ => None,
+        }
+    }
+
+    fn default_device_preference(&self) -> Option<&'static str> {
+        match self {
+            Self::AudioInput => Some(DEFAULT_AUDIO_CAPTURE_DEVICE),
+            Self::VideoInput => Some(DEFAULT_VIDEO_CAPTURE_DEVICE),
+            Self::AudioOutput => None,
+        }
+    }
+
+    fn permission(&self) -> u32 {
+        let permission = match self {
+            Self::AudioInput => {
+                cef_media_access_permission_types_t::CEF_MEDIA_PERMISSION_DEVICE_AUDIO_CAPTURE
+            }
+            Self::VideoInput => {
+                cef_media_access_permission_types_t::CEF_MEDIA_PERMISSION_DEVICE_VIDEO_CAPTURE
+            }
+            Self::AudioOutput => cef_media_access_permission_types_t::CEF_MEDIA_PERMISSION_NONE,
+        };
+        permission.0
+    }
+}
+
+/// A media device as returned by `navigator.mediaDevices.enumerateDevices()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MediaDeviceInfo {
+    /// The id of the device, salted for the origin of the page.
+    pub device_id: String,
+    pub kind: MediaDeviceKind,
+    /// The name of the device, empty until the page was granted access to a device of this kind.
+    pub label: String,
+    /// Devices of the same physical device, e.g. a headset, share this id.
+    pub group_id: String,
+}
+
+impl MediaDeviceInfo {
+    /// The device of `kind` named `label`, or else the first one whose label contains `label`
+    /// ignoring case, e.g. without the USB ids Chromium appends on Linux.
+    pub fn find_by_label<'a>(
+        devices: &'a [MediaDeviceInfo],
+        kind: MediaDeviceKind,
+        label: &str,
+    ) -> Option<&'a MediaDeviceInfo> {
+        let mut devices = devices.iter().filter(|device| device.kind == kind);
+        let label = label.to_lowercase();
+        devices
+            .clone()
+            .find(|device| device.label.to_lowercase() == label)
+            .or_else(|| devices.find(|device| device.label.to_lowercase().contains(&label)))
+    }
+
+    /// The `getUserMedia` constraints, as JSON, which open exactly this device. Only valid in
+    /// documents of the origin which enumerated the device.
+    pub fn constraints(&self) -> String {
+        let kind = match self.kind {
+            MediaDeviceKind::AudioInput => "audio",
+            MediaDeviceKind::VideoInput => "video",
+            MediaDeviceKind::AudioOutput => return "{}".to_string(),
+        };
+        format!(
+            r#"{{"{kind}":{{"deviceId":{{"exact":{}}}}}}}"#,
+            json::quote(&self.device_id)
+        )
+    }
+}
+
+/// Why a default media device couldn't be set.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MediaDeviceError {
+    /// Chromium has no default device of this kind.
+    Unsupported(MediaDeviceKind),
+    /// CEF rejected the preference, with its error message.
+    Preference(String),
+}
+
+impl fmt::Display for MediaDeviceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsupported(kind) => write!(f, "no default device for {}", kind.as_str()),
+            Self::Preference(error) => write!(f, "failed to set the default device: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for MediaDeviceError {}
+
+impl RequestContext {
+    /// Open the device with the system id `device_id` when a page asks for any device of `kind`,
+    /// or the first device again with an empty id.
+    ///
+    /// This is the raw id of the operating system, e.g. `/dev/video0` on Linux, not the salted
+    /// [MediaDeviceInfo::device_id] of a page. Pages which ask for a specific `deviceId` still
+    /// get that device. Must be called on the UI thread.
+    pub fn set_default_media_device(
+        &self,
+        kind: MediaDeviceKind,
+        device_id: &str,
+    ) -> Result<(), MediaDeviceError> {
+        let name = kind
+            .default_device_preference()
+            .ok_or(MediaDeviceError::Unsupported(kind))?;
+        let mut value = value_create()
+            .ok_or_else(|| MediaDeviceError::Preference("Failed to create value".to_string()))?;
+        value.set_string(Some(&CefString::from(&CefStringUtf8::from(device_id))));
+        request_context::set_preference(self, name, &mut value)
+            .map_err(MediaDeviceError::Preference)
+    }
+}
+
+const ENUMERATE_DEVICES: &str =
+    "navigator.mediaDevices.enumerateDevices().then(devices => JSON.stringify(devices))";
+
+impl BrowserHost {
+    /// The media devices as `navigator.mediaDevices.enumerateDevices()` in the main frame lists
+    /// them. The page must be a secure context, e.g. `https:` or `file:`.
+    ///
+    /// Must be called on the UI thread, see [BrowserHost::execute_dev_tools].
+    pub fn enumerate_media_devices(
+        &self,
+    ) -> impl Future<Output = Result<Vec<MediaDeviceInfo>, DevToolsError>> {
+        let result = self.evaluate(ENUMERATE_DEVICES);
+
+        async move { parse_evaluate_result(&result.await?) }
+    }
+
+    /// Grant the page of the main frame access to the microphone with `audio`, and the camera
+    /// with `video`, and open them once to check that it got them. Resolves with the labels of
+    /// the devices the page gets, or [MediaCaptureResult::Denied] if it can't open them, e.g.
+    /// because there is no such device or the page isn't a secure context.
+    ///
+    /// The grant lasts until the browser is closed, and only applies to the origin of the page
+    /// when this is called. It goes around the [PermissionHandler] of the client, so only call
+    /// it for pages the application trusts.
+    ///
+    /// Must be called on the UI thread, and the future polled there too.
+    pub fn request_media_capture(
+        &self,
+        audio: bool,
+        video: bool,
+    ) -> impl Future<Output = MediaCaptureResult> {
+        let host = self.clone();
+        let origin = self
+            .get_browser()
+            .and_then(|browser| browser.get_main_frame())
+            .and_then(|frame| frame.get_url())
+            .and_then(|url| origin(&url.to_string()));
+        let grant = (audio || video).then(|| {
+            self.execute_dev_tools(
+                "Browser.grantPermissions",
+                grant_params(audio, video, origin.as_deref()),
+            )
+        });
+
+        async move {
+            let Some(grant) = grant else {
+                return MediaCaptureResult::Granted {
+                    audio_device: None,
+                    video_device: None,
+                };
+            };
+            if origin.is_none() || grant.await.is_err() {
+                return MediaCaptureResult::Denied;
+            }
+            match host.evaluate(&capture_script(audio, video)).await {
+                Ok(result) => parse_capture_result(&result),
+                Err(_) => MediaCaptureResult::Denied,
+            }
+        }
+    }
+
+    /// Evaluate `expression` in the main frame with `Runtime.evaluate`, awaiting a promise and
+    /// returning the result by value.
+    fn evaluate(&self, expression: &str) -> impl Future<Output = Result<Vec<u8>, DevToolsError>> {
+        let params = dictionary_value_create();
+        if let Some(params) = &params {
+            params.set_string(Some(&key("expression")), Some(&key(expression)));
+            params.set_bool(Some(&key("awaitPromise")), 1);
+            params.set_bool(Some(&key("returnByValue")), 1);
+        }
+        self.execute_dev_tools("Runtime.evaluate", params)
+    }
+}
+
+/// The result of [BrowserHost::request_media_capture].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MediaCaptureResult {
+    /// The page may open the requested devices. The labels are those of the devices it gets by
+    /// default, `None` for the kinds which weren't requested.
+    Granted {
+        audio_device: Option<String>,
+        video_device: Option<String>,
+    },
+    Denied,
+}
+
+/// The origin of `url`, e.g. `https://example.com:8443`, `None` for URLs without one such as
+/// `about:blank` or `data:` URLs.
+fn origin(url: &str) -> Option<String> {
+    let (scheme, rest) = url.split_once("://")?;
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    // Drop the user info.
+    let host = authority.rsplit('@').next().unwrap_or_default();
+    (!host.is_empty() && matches!(scheme, "http" | "https")).then(|| format!("{scheme}://{host}"))
+}
+
+/// The parameters of `Browser.grantPermissions`.
+fn grant_params(audio: bool, video: bool, origin: Option<&str>) -> Option<crate::DictionaryValue> {
+    let params = dictionary_value_create()?;
+    let permissions = list_value_create()?;
+    let kinds = [(audio, "audioCapture"), (video, "videoCapture")];
+    for (index, (_, permission)) in kinds.iter().filter(|(wanted, _)| *wanted).enumerate() {
+        permissions.set_string(index, Some(&key(permission)));
+    }
+    params.set_list(Some(&key("permissions")), Some(&permissions));
+    if let Some(origin) = origin {
+        params.set_string(Some(&key("origin")), Some(&key(origin)));
+    }
+    Some(params)
+}
+
+/// Opens the requested devices and stops them again, and returns an object with the label of
+/// each device it opened.
+fn capture_script(audio: bool, video: bool) -> String {
+    format!(
+        "navigator.mediaDevices.getUserMedia({{audio: {audio}, video: {video}}}).then(stream => {{
+  const labels = {{}};
+  for (const track of stream.getTracks()) {{
+    labels[track.kind] = track.label;
+    track.stop();
+  }}
+  return labels;
+}})"
+    )
+}
+
+/// Parse the result of `Runtime.evaluate` with [capture_script].
+fn parse_capture_result(result: &[u8]) -> MediaCaptureResult {
+    let Ok(result) = json::parse(result) else {
+        return MediaCaptureResult::Denied;
+    };
+    if json::value(&result, &["exceptionDetails"]).is_ok() {
+        return MediaCaptureResult::Denied;
+    }
+    let label = |kind| json::string(&result, &["result", "value", kind]).ok();
+    MediaCaptureResult::Granted {
+        audio_device: label("audio"),
+        video_device: label("video"),
+    }
+}
+
+/// Parse the result of `Runtime.evaluate` with [ENUMERATE_DEVICES].
+fn parse_evaluate_result(result: &[u8]) -> Result<Vec<MediaDeviceInfo>, DevToolsError> {
+    let result = json::parse(result)?;
+    if let Ok(exception) = json::string(&result, &["exceptionDetails", "exception", "description"])
+    {
+        return Err(DevToolsError::Method(exception));
+    }
+    // The page sends the devices as a JSON string.
+    let devices = json::parse(json::string(&result, &["result", "value"])?.as_bytes())?;
+    json::elements(&devices, &[])?
+        .iter()
+        .map(|device| {
+            Ok(MediaDeviceInfo {
+                device_id: json::string(device, &["deviceId"])?,
+                kind: MediaDeviceKind::from_str(&json::string(device, &["kind"])?)
+                    .ok_or(DevToolsError::InvalidResponse)?,
+                label: json::string(device, &["label"])?,
+                group_id: json::string(device, &["groupId"])?,
+            })
+        })
+        .collect()
+}
+
+/// Grants `getUserMedia` requests of some origins without a prompt.
+///
+/// Requests for other devices, other origins, or screen capture get the default handling, which
+/// denies them with Alloy style browsers and prompts with Chrome style ones.
+#[derive(Clone, Debug, Default)]
+pub struct MediaAccessGrant {
+    permissions: u32,
+    origins: Vec<String>,
+}
+
+impl MediaAccessGrant {
+    /// Create a grant of no devices.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Grant access to devices of `kind`.
+    pub fn device(mut self, kind: MediaDeviceKind) -> Self {
+        self.permissions |= kind.permission();
+        self
+    }
+
+    /// Grant access to pages of `origin`, e.g. `https://example.com`.
+    pub fn origin(mut self, origin: impl Into<String>) -> Self {
+        let origin = origin.into();
+        self.origins.push(origin.trim_end_matches('/').to_string());
+        self
+    }
+
+    /// The permissions granted to `origin` out of `requested`, `None` unless all of them are.
+    fn grant(&self, origin: &str, requested: u32) -> Option<u32> {
+        let origin = origin.trim_end_matches('/');
+        let granted = requested != 0
+            && requested & !self.permissions == 0
+            && self.origins.iter().any(|allowed| allowed == origin);
+        granted.then_some(requested)
+    }
+
+    /// Build the [PermissionHandler] for [crate::client::ClientBuilder::permission_handler].
+    pub fn build(self) -> PermissionHandler {
+        PermissionHandler::new(MediaAccessHandler {
+            object: std::ptr::null_mut(),
+            grant: self,
+        })
+    }
+}
+
+struct MediaAccessHandler {
+    object: *mut RcImpl<_cef_permission_handler_t, Self>,
+    grant: MediaAccessGrant,
+}
+
+wrap_rc!(
+    MediaAccessHandler,
+    WrapPermissionHandler,
+    _cef_permission_handler_t,
+    grant
+);
+
+impl ImplPermissionHandler for MediaAccessHandler {
+    fn on_request_media_access_permission(
+        &self,
+        _browser: Option<&impl ImplBrowser>,
+        _frame: Option<&impl ImplFrame>,
+        requesting_origin: Option<&CefStringUtf16>,
+        requested_permissions: u32,
+        callback: Option<&impl ImplMediaAccessCallback>,
+    ) -> c_int {
+        let origin = requesting_origin
+            .map(|origin| origin.to_string())
+            .unwrap_or_default();
+        match (self.grant.grant(&origin, requested_permissions), callback) {
+            (Some(allowed), Some(callback)) => {
+                callback.cont(allowed);
+                1
+            }
+            _ => 0,
+        }
+    }
+
+    fn get_raw(&self) -> *mut _cef_permission_handler_t {
+        self.object as *mut _cef_permission_handler_t
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const RESULT: &str = r#"{"result":{"type":"string","value":"[{\"deviceId\":\"default\",\"kind\":\"audioinput\",\"label\":\"Default\",\"groupId\":\"a1\"},{\"deviceId\":\"4f0c\",\"kind\":\"videoinput\",\"label\":\"Integrated Camera (04f2:b6ea)\",\"groupId\":\"b2\"},{\"deviceId\":\"9e7d\",\"kind\":\"videoinput\",\"label\":\"USB \\\"Desk\\\" Camera (046d:085e)\",\"groupId\":\"c3\"}]"}}"#;
+
+    fn devices() -> Vec<MediaDeviceInfo> {
+        parse_evaluate_result(RESULT.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_evaluate_result() {
+        let devices = devices();
+        assert_eq!(devices.len(), 3);
+        assert_eq!(
+            devices[2],
+            MediaDeviceInfo {
+                device_id: "9e7d".into(),
+                kind: MediaDeviceKind::VideoInput,
+                label: r#"USB "Desk" Camera (046d:085e)"#.into(),
+                group_id: "c3".into(),
+            }
+        );
+
+        let exception = r#"{"result":{"type":"object"},"exceptionDetails":{"exception":{"description":"TypeError: Cannot read properties of undefined"}}}"#;
+        assert!(matches!(
+            parse_evaluate_result(exception.as_bytes()),
+            Err(DevToolsError::Method(message)) if message.starts_with("TypeError")
+        ));
+    }
+
+    #[test]
+    fn test_find_by_label() {
+        let devices = devices();
+        let find = |kind, label| {
+            MediaDeviceInfo::find_by_label(&devices, kind, label).map(|d| d.device_id.as_str())
+        };
+        assert_eq!(
+            find(MediaDeviceKind::VideoInput, "usb \"desk\" camera"),
+            Some("9e7d")
+        );
+        assert_eq!(
+            find(MediaDeviceKind::VideoInput, "Integrated Camera (04f2:b6ea)"),
+            Some("4f0c")
+        );
+        assert_eq!(find(MediaDeviceKind::AudioInput, "Camera"), None);
+        assert_eq!(
+            devices[1].constraints(),
+            r#"{"video":{"deviceId":{"exact":"4f0c"}}}"#
+        );
+    }
+
+    #[test]
+    fn test_request_media_capture() {
+        assert_eq!(
+            origin("https://user@example.com:8443/call?room=1#top").as_deref(),
+            Some("https://example.com:8443")
+        );
+        assert_eq!(origin("about:blank"), None);
+        assert_eq!(origin("file:///home/user/index.html"), None);
+        assert_eq!(
+            capture_script(false, true),
+            "navigator.mediaDevices.getUserMedia({audio: false, video: true}).then(stream => {\n  const labels = {};\n  for (const track of stream.getTracks()) {\n    labels[track.kind] = track.label;\n    track.stop();\n  }\n  return labels;\n})"
+        );
+
+        let granted =
+            r#"{"result":{"type":"object","value":{"video":"Integrated Camera (04f2:b6ea)"}}}"#;
+        assert_eq!(
+            parse_capture_result(granted.as_bytes()),
+            MediaCaptureResult::Granted {
+                audio_device: None,
+                video_device: Some("Integrated Camera (04f2:b6ea)".into()),
+            }
+        );
+        let denied = r#"{"result":{"type":"object","subtype":"error"},"exceptionDetails":{"text":"Uncaught (in promise)","exception":{"description":"NotAllowedError: Permission denied"}}}"#;
+        assert_eq!(
+            parse_capture_result(denied.as_bytes()),
+            MediaCaptureResult::Denied
+        );
+    }
+
+    #[test]
+    fn test_grant() {
+        let video = MediaDeviceKind::VideoInput.permission();
+        let audio = MediaDeviceKind::AudioInput.permission();
+        let grant = MediaAccessGrant::new()
+            .device(MediaDeviceKind::VideoInput)
+            .origin("https://kiosk.example/");
+        assert_eq!(grant.grant("https://kiosk.example", video), Some(video));
+        assert_eq!(grant.grant("https://kiosk.example/", video), Some(video));
+        assert_eq!(grant.grant("https://kiosk.example", video | audio), None);
+        assert_eq!(grant.grant("https://other.example", video), None);
+        assert_eq!(grant.grant("https://kiosk.example", 0), None);
+    }
+}
@@ -0,0 +1,399 @@
+//! Media router module
+//!
+//! Discover cast and DIAL sinks, e.g. Chromecast devices, and create routes which present a
+//! [`MediaSource`] on them.
+
+use std::{future::Future, os::raw::c_int, sync::Arc};
+
+use cef_sys::_cef_media_observer_t;
+
+use crate::{
+    callback::{media_route_create_callback, media_sink_device_info_callback},
+    future::channel,
+    media_router_get_global,
+    rc::{from_impl, wrap_rc, RcImpl},
+    CefString, CefStringUtf8, CompletionCallback, ImplMediaRoute, ImplMediaRouter, ImplMediaSink,
+    ImplRequestContext, MediaRoute, MediaRouteConnectionState, MediaRouteCreateResult, MediaRouter,
+    MediaSink, MediaSinkIconType, MediaSource, Registration, RequestContext,
+};
+
+from_impl!(MediaSink, ImplMediaSink);
+from_impl!(MediaRoute, ImplMediaRoute);
+
+/// Receives media router events, see [`MediaRouter::observe`].
+pub trait MediaObserver: Send + Sync + 'static {
+    /// The list of available sinks has changed.
+    fn on_sinks(&self, sinks: Vec<MediaSink>) {
+        let _ = sinks;
+    }
+
+    /// The list of available routes has changed.
+    fn on_routes(&self, routes: Vec<MediaRoute>) {
+        let _ = routes;
+    }
+
+    /// The connection state of `route` has changed.
+    fn on_route_state_changed(&self, route: MediaRoute, state: MediaRouteConnectionState) {
+        let _ = (route, state);
+    }
+
+    /// A message was received over `route`.
+    fn on_route_message_received(&self, route: MediaRoute, message: &[u8]) {
+        let _ = (route, message);
+    }
+}
+
+/// Receives the events of a single [`MediaRoute`], see [`RouteController::observe_route`].
+pub trait RouteObserver: Send + Sync + 'static {
+    /// A text message was received over the route. Binary messages are converted lossily.
+    fn on_route_message_received(&self, route: &MediaRoute, message: &str) {
+        let _ = (route, message);
+    }
+
+    /// The route was closed or terminated, by either side.
+    fn on_route_terminated(&self, route: &MediaRoute) {
+        let _ = route;
+    }
+}
+
+/// Manages the routes created with [`MediaRouter::create_route`].
+#[derive(Clone)]
+pub struct RouteController {
+    router: MediaRouter,
+}
+
+/// Keeps a [`RouteObserver`] registered. Dropping it removes the observer.
+pub struct RouteHandle {
+    _registration: Registration,
+}
+
+impl RouteController {
+    pub fn new(router: MediaRouter) -> Self {
+        Self { router }
+    }
+
+    /// Terminate `route`, which notifies the observers of the route.
+    pub fn terminate_route(&self, route: &MediaRoute) {
+        route.terminate();
+    }
+
+    /// Send a text message to the sink of `route`.
+    pub fn send_message_to_sink(&self, route: &MediaRoute, message: &str) {
+        route.send_route_message(Some(message.as_bytes()));
+    }
+
+    /// Observe the messages and termination of `route` until the returned [`RouteHandle`] is
+    /// dropped.
+    pub fn observe_route(
+        &self,
+        route: &MediaRoute,
+        observer: impl RouteObserver,
+    ) -> Option<RouteHandle> {
+        let registration = self.router.observe(RouteFilter {
+            route_id: route.id(),
+            observer,
+        })?;
+        Some(RouteHandle {
+            _registration: registration,
+        })
+    }
+}
+
+/// Forwards the events of one route to a [`RouteObserver`].
+struct RouteFilter<O> {
+    route_id: String,
+    observer: O,
+}
+
+impl<O: RouteObserver> MediaObserver for RouteFilter<O> {
+    fn on_route_state_changed(&self, route: MediaRoute, state: MediaRouteConnectionState) {
+        let terminated = matches!(
+            state,
+            MediaRouteConnectionState::Closed | MediaRouteConnectionState::Terminated
+        );
+        if terminated && route.id() == self.route_id {
+            self.observer.on_route_terminated(&route);
+        }
+    }
+
+    fn on_route_message_received(&self, route: MediaRoute, message: &[u8]) {
+        if route.id() == self.route_id {
+            self.observer
+                .on_route_message_received(&route, &String::from_utf8_lossy(message));
+        }
+    }
+}
+
+/// The network information of a [`MediaSink`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DeviceInfo {
+    pub ip_address: String,
+    pub port: c_int,
+    pub model_name: String,
+}
+
+impl MediaRouter {
+    /// Get the media router of `context`, or of the global request context if it is `None`.
+    pub fn get_global(context: Option<&RequestContext>) -> Option<MediaRouter> {
+        match context {
+            Some(context) => context.get_media_router(Option::<&CompletionCallback>::None),
+            None => media_router_get_global(Option::<&CompletionCallback>::None),
+        }
+    }
+
+    /// Add an observer of sinks, routes and route messages. The observer is removed when the
+    /// returned [`Registration`] is dropped.
+    pub fn observe(&self, observer: impl MediaObserver) -> Option<Registration> {
+        let observer = crate::MediaObserver::new(BuiltMediaObserver {
+            object: std::ptr::null_mut(),
+            observer: Arc::new(observer),
+        });
+        self.add_observer(Some(&observer))
+    }
+
+    /// Create a route which presents `source` on `sink`.
+    pub fn create_route(
+        &self,
+        sink: &MediaSink,
+        source: &MediaSource,
+    ) -> impl Future<Output = Result<MediaRoute, String>> {
+        let (sender, completion) = channel();
+        let callback = media_route_create_callback(move |result, error, route| {
+            sender.send((result, error, route))
+        });
+        ImplMediaRouter::create_route(
+            self,
+            Some(&source.clone()),
+            Some(&sink.clone()),
+            Some(&callback),
+        );
+        async move {
+            match completion.await {
+                Some((MediaRouteCreateResult::Ok, _, Some(route))) => Ok(route),
+                Some((result, error, _)) if error.is_empty() => Err(format!("{result:?}")),
+                Some((_, error, _)) => Err(error),
+                None => Err("Route creation was cancelled".to_string()),
+            }
+        }
+    }
+}
+
+impl MediaSink {
+    /// The ID of the sink.
+    pub fn id(&self) -> String {
+        self.get_id().map(|id| id.to_string()).unwrap_or_default()
+    }
+
+    /// The name of the sink.
+    pub fn name(&self) -> String {
+        self.get_name()
+            .map(|name| name.to_string())
+            .unwrap_or_default()
+    }
+
+    /// The icon type of the sink.
+    pub fn icon_type(&self) -> MediaSinkIconType {
+        self.get_icon_type()
+    }
+
+    /// Get the network information of the sink, or `None` if it isn't available.
+    pub fn device_info(&self) -> impl Future<Output = Option<DeviceInfo>> {
+        let (sender, completion) = channel();
+        let callback = media_sink_device_info_callback(move |device_info| {
+            sender.send(device_info.map(|device_info| DeviceInfo {
+                ip_address: device_info.ip_address.to_string(),
+                port: device_info.port,
+                model_name: device_info.model_name.to_string(),
+            }))
+        });
+        self.get_device_info(Some(&callback));
+        async move { completion.await.flatten() }
+    }
+}
+
+impl MediaRoute {
+    /// The ID of the route.
+    pub fn id(&self) -> String {
+        self.get_id().map(|id| id.to_string()).unwrap_or_default()
+    }
+}
+
+impl MediaSource {
+    /// Get a cast source, e.g. `cast:<appId>?clientId=<clientId>`. The `cast:` prefix is added
+    /// if it is missing.
+    pub fn from_cast_url(router: &MediaRouter, url: &str) -> Option<MediaSource> {
+        Self::from_urn(router, &source_urn("cast:", url)?)
+    }
+
+    /// Get a DIAL source, e.g. `dial:<appName>`. The `dial:` prefix is added if it is missing.
+    pub fn from_dial_url(router: &MediaRouter, url: &str) -> Option<MediaSource> {
+        Self::from_urn(router, &source_urn("dial:", url)?)
+    }
+
+    fn from_urn(router: &MediaRouter, urn: &str) -> Option<MediaSource> {
+        let urn = CefString::from(&CefStringUtf8::from(urn));
+        router.get_source(Some(&urn))
+    }
+}
+
+fn source_urn(scheme: &str, url: &str) -> Option<String> {
+    let url = url.trim();
+    let app = url.strip_prefix(scheme).unwrap_or(url);
+    if app.is_empty() || app.contains(':') {
+        return None;
+    }
+    Some(format!("{scheme}{app}"))
+}
+
+struct BuiltMediaObserver {
+    object: *mut RcImpl<_cef_media_observer_t, Self>,
+    observer: Arc<dyn MediaObserver>,
+}
+
+wrap_rc!(
+    BuiltMediaObserver,
+    WrapMediaObserver,
+    _cef_media_observer_t,
+    observer
+);
+
+impl crate::ImplMediaObserver for BuiltMediaObserver {
+    fn on_sinks(&self, sinks: Option<&[Option<impl ImplMediaSink>]>) {
+        let sinks = sinks
+            .unwrap_or_default()
+            .iter()
+            .flatten()
+            .map(MediaSink::from_impl)
+            .collect();
+        self.observer.on_sinks(sinks);
+    }
+
+    fn on_routes(&self, routes: Option<&[Option<impl ImplMediaRoute>]>) {
+        let routes = routes
+            .unwrap_or_default()
+            .iter()
+            .flatten()
+            .map(MediaRoute::from_impl)
+            .collect();
+        self.observer.on_routes(routes);
+    }
+
+    fn on_route_state_changed(
+        &self,
+        route: Option<&impl ImplMediaRoute>,
+        state: MediaRouteConnectionState,
+    ) {
+        if let Some(route) = route {
+            self.observer
+                .on_route_state_changed(MediaRoute::from_impl(route), state);
+        }
+    }
+
+    fn on_route_message_received(
+        &self,
+        route: Option<&impl ImplMediaRoute>,
+        message: Option<&[u8]>,
+    ) {
+        if let Some(route) = route {
+            self.observer.on_route_message_received(
+                MediaRoute::from_impl(route),
+                message.unwrap_or_default(),
+            );
+        }
+    }
+
+    fn get_raw(&self) -> *mut _cef_media_observer_t {
+        self.object as *mut _cef_media_observer_t
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Mutex;
+
+    use cef_sys::_cef_media_route_t;
+
+    use super::*;
+    use crate::CefStringUtf16;
+
+    struct FakeRoute {
+        object: *mut RcImpl<_cef_media_route_t, Self>,
+        id: &'static str,
+    }
+
+    impl ImplMediaRoute for FakeRoute {
+        fn get_id(&self) -> Option<CefStringUtf16> {
+            Some(CefString::from(&CefStringUtf8::from(self.id)))
+        }
+
+        fn get_raw(&self) -> *mut _cef_media_route_t {
+            self.object as *mut _
+        }
+    }
+
+    wrap_rc!(FakeRoute, WrapMediaRoute, _cef_media_route_t, id);
+
+    fn route(id: &'static str) -> MediaRoute {
+        MediaRoute::new(FakeRoute {
+            object: std::ptr::null_mut(),
+            id,
+        })
+    }
+
+    #[derive(Default)]
+    struct Events(Mutex<Vec<String>>);
+
+    impl RouteObserver for Arc<Events> {
+        fn on_route_message_received(&self, route: &MediaRoute, message: &str) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("{}: {message}", route.id()));
+        }
+
+        fn on_route_terminated(&self, route: &MediaRoute) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("{} terminated", route.id()));
+        }
+    }
+
+    #[test]
+    fn test_route_terminated() {
+        let events = Arc::new(Events::default());
+        let filter = RouteFilter {
+            route_id: String::from("route-1"),
+            observer: events.clone(),
+        };
+
+        filter.on_route_message_received(route("route-1"), b"hello");
+        filter.on_route_message_received(route("route-2"), b"other");
+        filter.on_route_state_changed(route("route-1"), MediaRouteConnectionState::Connected);
+        filter.on_route_state_changed(route("route-2"), MediaRouteConnectionState::Terminated);
+        filter.on_route_state_changed(route("route-1"), MediaRouteConnectionState::Terminated);
+
+        assert_eq!(
+            *events.0.lock().unwrap(),
+            vec!["route-1: hello", "route-1 terminated"]
+        );
+    }
+
+    #[test]
+    fn test_source_urn() {
+        assert_eq!(
+            source_urn("cast:", "CC1AD845?clientId=1").as_deref(),
+            Some("cast:CC1AD845?clientId=1")
+        );
+        assert_eq!(
+            source_urn("cast:", "cast:CC1AD845").as_deref(),
+            Some("cast:CC1AD845")
+        );
+        assert_eq!(
+            source_urn("dial:", "YouTube").as_deref(),
+            Some("dial:YouTube")
+        );
+        assert_eq!(source_urn("dial:", "cast:CC1AD845"), None);
+        assert_eq!(source_urn("cast:", ""), None);
+    }
+}
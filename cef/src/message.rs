@@ -0,0 +1,356 @@
+//! Process message module
+//!
+//! [MessageDispatcher] routes the process messages received by a [crate::Client] or a
+//! [crate::RenderProcessHandler] to handlers registered by message name.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    browser::BrowserId, frame::FrameId, process_message_create, value, Browser, CefString,
+    CefStringUtf8, Frame, ImplBrowser, ImplFrame, ImplListValue, ImplProcessMessage, ProcessId,
+    Value,
+};
+
+/// A process message with the browser and frame it was received for.
+#[derive(Clone)]
+pub struct ReceivedMessage {
+    pub browser: Browser,
+    pub frame: Frame,
+    /// The identifier of [ReceivedMessage::browser] when the message was received.
+    pub browser_id: BrowserId,
+    /// The identifier of [ReceivedMessage::frame] when the message was received, which stays
+    /// the same if the frame becomes invalid.
+    pub frame_id: FrameId,
+    pub source_process: ProcessId,
+    pub name: String,
+    pub args: value::ListValue,
+}
+
+impl ReceivedMessage {
+    /// The key of state kept per sending frame, e.g. the pending requests of subframes which
+    /// send messages of the same name.
+    pub fn route(&self) -> (BrowserId, FrameId) {
+        (self.browser_id, self.frame_id.clone())
+    }
+
+    /// Send a message called `name` with `args` back to the process and frame which sent this
+    /// one. Returns `false` if the frame is gone or the message could not be created.
+    pub fn reply(&self, name: &str, args: impl IntoIterator<Item = Value>) -> bool {
+        let frame = if self.frame.is_valid() {
+            Some(self.frame.clone())
+        } else {
+            self.browser.frame_by_id(&self.frame_id)
+        };
+        frame.is_some_and(|frame| send(&frame, self.source_process, name, args))
+    }
+}
+
+/// Routes received process messages to handlers by name.
+///
+/// Call [MessageDispatcher::dispatch] from [crate::ImplClient::on_process_message_received] or
+/// [crate::ImplRenderProcessHandler::on_process_message_received], or pass the dispatcher to
+/// [crate::client::ClientBuilder::message_dispatcher] or
+/// [crate::render::PreloadScripts::message_dispatcher].
+#[derive(Clone, Default)]
+pub struct MessageDispatcher(Routes<ReceivedMessage>);
+
+impl MessageDispatcher {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Handle the messages called `name`. A later handler for the same name replaces the
+    /// earlier one.
+    pub fn on_message(
+        self,
+        name: &str,
+        mut handler: impl FnMut(Browser, Frame, value::ListValue) + Send + 'static,
+    ) -> Self {
+        self.0.on(name, move |message: ReceivedMessage| {
+            handler(message.browser, message.frame, message.args)
+        });
+        self
+    }
+
+    /// Offer the messages without a handler for their name to `handler`, which returns `true`
+    /// if it handled the message.
+    pub fn catch_all(self, handler: impl FnMut(ReceivedMessage) -> bool + Send + 'static) -> Self {
+        self.0.fallback(handler);
+        self
+    }
+
+    /// Run the handler of `message`, and return `true` if there was one. Unmatched messages
+    /// return `false` so CEF can pass them on.
+    pub fn dispatch(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        frame: Option<&impl ImplFrame>,
+        source_process: ProcessId,
+        message: Option<&impl ImplProcessMessage>,
+    ) -> bool {
+        let (Some(browser), Some(frame), Some(message)) = (browser, frame, message) else {
+            return false;
+        };
+        let name = message
+            .get_name()
+            .map(|name| name.to_string())
+            .unwrap_or_default();
+        let Some(args) = message.get_argument_list() else {
+            return false;
+        };
+        let browser = Browser::from_impl(browser);
+        let frame = Frame::from_impl(frame);
+        let message = ReceivedMessage {
+            browser_id: browser.identifier(),
+            frame_id: frame.id(),
+            browser,
+            frame,
+            source_process,
+            name: name.clone(),
+            args: args.into(),
+        };
+        self.0.dispatch(&name, message)
+    }
+}
+
+/// Send a process message called `name` with `args` from `frame` to `target_process`.
+///
+/// Returns `false` if the message could not be created.
+pub fn send(
+    frame: &Frame,
+    target_process: ProcessId,
+    name: &str,
+    args: impl IntoIterator<Item = Value>,
+) -> bool {
+    let name = CefString::from(&CefStringUtf8::from(name));
+    let Some(message) = process_message_create(Some(&name)) else {
+        return false;
+    };
+    let Some(list) = message.get_argument_list() else {
+        return false;
+    };
+    for (index, arg) in args.into_iter().enumerate() {
+        list.set_value(index, Some(&arg));
+    }
+    frame.send_process_message(target_process, Some(&message));
+    true
+}
+
+type Handler<T> = Arc<Mutex<dyn FnMut(T) + Send>>;
+
+type Fallback<T> = Arc<Mutex<dyn FnMut(T) -> bool + Send>>;
+
+/// Handlers by name, shared by the clones of a [MessageDispatcher].
+struct Routes<T> {
+    handlers: Arc<Mutex<HashMap<String, Handler<T>>>>,
+    fallback: Arc<Mutex<Option<Fallback<T>>>>,
+}
+
+impl<T> Default for Routes<T> {
+    fn default() -> Self {
+        Self {
+            handlers: Default::default(),
+            fallback: Default::default(),
+        }
+    }
+}
+
+impl<T> Clone for Routes<T> {
+    fn clone(&self) -> Self {
+        Self {
+            handlers: self.handlers.clone(),
+            fallback: self.fallback.clone(),
+        }
+    }
+}
+
+impl<T> Routes<T> {
+    fn on(&self, name: &str, handler: impl FnMut(T) + Send + 'static) {
+        if let Ok(mut handlers) = self.handlers.lock() {
+            handlers.insert(name.to_string(), Arc::new(Mutex::new(handler)));
+        }
+    }
+
+    fn fallback(&self, handler: impl FnMut(T) -> bool + Send + 'static) {
+        if let Ok(mut fallback) = self.fallback.lock() {
+            *fallback = Some(Arc::new(Mutex::new(handler)));
+        }
+    }
+
+    fn dispatch(&self, name: &str, message: T) -> bool {
+        // Release the map before running the handler, which may register other handlers.
+        let handler = self
+            .handlers
+            .lock()
+            .ok()
+            .and_then(|handlers| handlers.get(name).cloned());
+        if let Some(handler) = handler {
+            if let Ok(mut handler) = handler.lock() {
+                handler(message);
+                return true;
+            }
+            return false;
+        }
+
+        let fallback = self
+            .fallback
+            .lock()
+            .ok()
+            .and_then(|fallback| fallback.clone());
+        fallback
+            .and_then(|fallback| fallback.lock().ok().map(|mut fallback| fallback(message)))
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        os::raw::c_int,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    use cef_sys::{_cef_frame_t, _cef_process_message_t, cef_process_id_t};
+
+    use super::*;
+    use crate::{
+        fixtures::FakeBrowser,
+        rc::{wrap_rc, RcImpl},
+        CefStringUtf16, ListValue, ProcessMessage,
+    };
+
+    #[test]
+    fn test_dispatch_by_name() {
+        let routes = Routes::default();
+        let received = Arc::new(Mutex::new(vec![]));
+        let frame_ready = received.clone();
+        routes.on("frame-ready", move |value: u32| {
+            frame_ready.lock().unwrap().push(value)
+        });
+
+        assert!(routes.dispatch("frame-ready", 1));
+        assert!(routes.dispatch("frame-ready", 2));
+        assert!(!routes.dispatch("frame-gone", 3));
+        assert_eq!(*received.lock().unwrap(), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_dispatch_catch_all() {
+        let routes = Routes::default();
+        let unmatched = Arc::new(AtomicUsize::new(0));
+        let counter = unmatched.clone();
+        routes.on("frame-ready", |_: u32| {});
+        routes.fallback(move |value: u32| {
+            counter.fetch_add(1, Ordering::Relaxed);
+            value.is_multiple_of(2)
+        });
+
+        assert!(routes.dispatch("frame-ready", 1));
+        assert!(!routes.dispatch("other", 1));
+        assert!(routes.dispatch("other", 2));
+        assert_eq!(unmatched.load(Ordering::Relaxed), 2);
+    }
+
+    struct FakeFrame {
+        object: *mut RcImpl<_cef_frame_t, Self>,
+        id: &'static str,
+        parent: Option<Frame>,
+    }
+
+    impl ImplFrame for FakeFrame {
+        fn is_valid(&self) -> c_int {
+            1
+        }
+
+        fn is_main(&self) -> c_int {
+            self.parent.is_none().into()
+        }
+
+        fn get_identifier(&self) -> Option<CefStringUtf16> {
+            Some(CefString::from(&CefStringUtf8::from(self.id)))
+        }
+
+        fn get_parent(&self) -> Option<Frame> {
+            self.parent.clone()
+        }
+
+        fn get_raw(&self) -> *mut _cef_frame_t {
+            self.object as *mut _
+        }
+    }
+
+    wrap_rc!(FakeFrame, WrapFrame, _cef_frame_t, id, parent);
+
+    struct FakeMessage {
+        object: *mut RcImpl<_cef_process_message_t, Self>,
+        name: &'static str,
+    }
+
+    impl ImplProcessMessage for FakeMessage {
+        fn get_name(&self) -> Option<CefStringUtf16> {
+            Some(CefString::from(&CefStringUtf8::from(self.name)))
+        }
+
+        fn get_argument_list(&self) -> Option<ListValue> {
+            Some(value::ListValue::new().into())
+        }
+
+        fn get_raw(&self) -> *mut _cef_process_message_t {
+            self.object as *mut _
+        }
+    }
+
+    wrap_rc!(
+        FakeMessage,
+        WrapProcessMessage,
+        _cef_process_message_t,
+        name
+    );
+
+    fn frame(id: &'static str, parent: Option<&Frame>) -> Frame {
+        Frame::new(FakeFrame {
+            object: std::ptr::null_mut(),
+            id,
+            parent: parent.cloned(),
+        })
+    }
+
+    #[test]
+    fn test_dispatch_keys_nested_frames() {
+        let browser = FakeBrowser::new(7).build();
+        let main = frame("1-1", None);
+        let child = frame("1-2", Some(&main));
+        let grandchild = frame("1-3", Some(&child));
+        assert!(main.is_main());
+        assert!(!grandchild.is_main());
+        assert_eq!(
+            grandchild.parent().map(|parent| parent.id()),
+            Some(child.id())
+        );
+
+        let pending = Arc::new(Mutex::new(HashMap::<(BrowserId, FrameId), usize>::new()));
+        let requests = pending.clone();
+        let dispatcher = MessageDispatcher::new().catch_all(move |message| {
+            *requests.lock().unwrap().entry(message.route()).or_default() += 1;
+            true
+        });
+        let ready = ProcessMessage::new(FakeMessage {
+            object: std::ptr::null_mut(),
+            name: "ready",
+        });
+        let renderer = cef_process_id_t::PID_RENDERER.into();
+        // Both iframes send a message of the same name before either is handled.
+        for frame in [&child, &grandchild, &child] {
+            assert!(dispatcher.dispatch(Some(&browser), Some(frame), renderer, Some(&ready)));
+        }
+
+        let pending = pending.lock().unwrap();
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending.get(&(7, FrameId::from("1-2"))), Some(&2));
+        assert_eq!(pending.get(&(7, FrameId::from("1-3"))), Some(&1));
+        assert_eq!(pending.get(&(7, main.id())), None);
+    }
+}
@@ -0,0 +1,188 @@
+//! Middleware module
+//!
+//! A [RequestInterceptor] sees each request before it is loaded, and may change it, answer it
+//! with a response of its own, or redirect it. [RequestInterceptorChain] runs several of them in
+//! order, and [crate::request::ResourceRequestHandlerBuilder::interceptors] runs a chain in
+//! [crate::ImplResourceRequestHandler::on_before_resource_load].
+//!
+//! ```ignore
+//! let interceptors = RequestInterceptorChain::new(vec![
+//!     Box::new(|request: &mut Request| {
+//!         println!("{:?}", request.get_url().map(|url| url.to_string()));
+//!         InterceptResult::Pass
+//!     }),
+//!     Box::new(MockApi::default()),
+//! ]);
+//! let client = ClientBuilder::new()
+//!     .request_handler(
+//!         ResourceRequestHandlerBuilder::new()
+//!             .interceptors(interceptors)
+//!             .request_handler(),
+//!     )
+//!     .build();
+//! ```
+
+use crate::{Request, Response};
+
+/// What a [RequestInterceptor] does with a request.
+#[derive(Clone)]
+pub enum InterceptResult {
+    /// Load the request, after the next interceptors.
+    Pass,
+    /// Answer the request with the status, MIME type and headers of the response, and the body,
+    /// instead of loading it.
+    Respond(Response, Vec<u8>),
+    /// Load this URL instead.
+    Redirect(String),
+}
+
+/// Sees each request before it is loaded, on the IO thread.
+///
+/// Closures taking the request implement it too.
+pub trait RequestInterceptor: Send + Sync {
+    /// Inspect or change `request`, and decide what to do with it.
+    fn intercept(&self, request: &mut Request) -> InterceptResult;
+}
+
+impl<F> RequestInterceptor for F
+where
+    F: Fn(&mut Request) -> InterceptResult + Send + Sync,
+{
+    fn intercept(&self, request: &mut Request) -> InterceptResult {
+        self(request)
+    }
+}
+
+/// Runs interceptors in order until one of them answers or redirects the request.
+pub struct RequestInterceptorChain {
+    interceptors: Vec<Box<dyn RequestInterceptor>>,
+}
+
+impl RequestInterceptorChain {
+    pub fn new(interceptors: Vec<Box<dyn RequestInterceptor>>) -> Self {
+        Self { interceptors }
+    }
+}
+
+impl RequestInterceptor for RequestInterceptorChain {
+    /// The result of the first interceptor which doesn't [pass](InterceptResult::Pass) the
+    /// request on, skipping the interceptors after it.
+    fn intercept(&self, request: &mut Request) -> InterceptResult {
+        for interceptor in &self.interceptors {
+            match interceptor.intercept(request) {
+                InterceptResult::Pass => {}
+                result => return result,
+            }
+        }
+        InterceptResult::Pass
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use cef_sys::_cef_request_t;
+
+    use super::*;
+    use crate::{
+        rc::{wrap_rc, RcImpl},
+        request::ResourceRequestHandlerBuilder,
+        Browser, Callback, CefString, CefStringUtf16, CefStringUtf8, Frame, ImplRequest,
+        ImplResourceRequestHandler,
+    };
+
+    struct FakeRequest {
+        object: *mut RcImpl<_cef_request_t, Self>,
+        url: Arc<Mutex<String>>,
+    }
+
+    impl ImplRequest for FakeRequest {
+        fn get_url(&self) -> Option<CefStringUtf16> {
+            let url = self.url.lock().unwrap();
+            Some(CefString::from(&CefStringUtf8::from(url.as_str())))
+        }
+
+        fn set_url(&self, url: Option<&CefStringUtf16>) {
+            *self.url.lock().unwrap() = url.map(ToString::to_string).unwrap_or_default();
+        }
+
+        fn get_raw(&self) -> *mut _cef_request_t {
+            self.object as *mut _
+        }
+    }
+
+    wrap_rc!(FakeRequest, WrapRequest, _cef_request_t, url);
+
+    fn fake_request(url: &str) -> (Request, Arc<Mutex<String>>) {
+        let url = Arc::new(Mutex::new(url.to_string()));
+        let request = Request::new(FakeRequest {
+            object: std::ptr::null_mut(),
+            url: url.clone(),
+        });
+        (request, url)
+    }
+
+    fn url(request: &Request) -> String {
+        request
+            .get_url()
+            .map(|url| url.to_string())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_second_interceptor_redirects() {
+        let seen = Arc::new(Mutex::new(vec![]));
+        let record = |name: &'static str| {
+            let seen = seen.clone();
+            Box::new(move |request: &mut Request| {
+                seen.lock()
+                    .unwrap()
+                    .push(format!("{name} {}", url(request)));
+                InterceptResult::Pass
+            })
+        };
+        let chain = RequestInterceptorChain::new(vec![
+            record("first"),
+            Box::new(|request: &mut Request| match url(request).as_str() {
+                "https://example.com/old" => {
+                    InterceptResult::Redirect("https://example.com/new".to_string())
+                }
+                _ => InterceptResult::Pass,
+            }),
+            record("third"),
+        ]);
+
+        let (mut request, _) = fake_request("https://example.com/page");
+        assert!(matches!(
+            chain.intercept(&mut request),
+            InterceptResult::Pass
+        ));
+        let (mut request, _) = fake_request("https://example.com/old");
+        assert!(matches!(
+            chain.intercept(&mut request),
+            InterceptResult::Redirect(url) if url == "https://example.com/new"
+        ));
+        assert_eq!(
+            *seen.lock().unwrap(),
+            [
+                "first https://example.com/page",
+                "third https://example.com/page",
+                "first https://example.com/old",
+            ]
+        );
+
+        // The built handler loads the new URL instead.
+        let handler = ResourceRequestHandlerBuilder::new()
+            .interceptors(chain)
+            .build();
+        let (request, loaded) = fake_request("https://example.com/old");
+        handler.on_before_resource_load(
+            Option::<&Browser>::None,
+            Option::<&Frame>::None,
+            Some(&request),
+            Option::<&Callback>::None,
+        );
+        assert_eq!(*loaded.lock().unwrap(), "https://example.com/new");
+    }
+}
@@ -0,0 +1,706 @@
+//! Navigation module
+//!
+//! [cef_transition_type_t] is a bitfield which combines one source value with qualifier flags,
+//! so most raw values are not variants of the generated enum. [TransitionType] splits it into a
+//! [TransitionCore] and the qualifiers, and [NavigationClassifier] answers the questions
+//! navigation policies usually ask.
+//!
+//! [NavigationPolicyBuilder] builds a [RequestHandler] which enforces a [UrlFilterPolicy], e.g. to
+//! keep users away from `chrome://` pages and `view-source:`.
+
+use std::{fmt, os::raw::c_int, sync::Arc};
+
+use cef_sys::{_cef_request_handler_t, cef_transition_type_t};
+
+use crate::{
+    diagnostics,
+    rc::{wrap_rc, RcImpl},
+    storage::origin_of,
+    Browser, CefStringUtf16, Frame, ImplBrowser, ImplFrame, ImplNavigationEntry, ImplRequest,
+    ImplRequestHandler, NavigationEntry, RequestHandler, WindowOpenDisposition,
+};
+
+const SOURCE_MASK: u32 = 0xFF;
+const BLOCKED_FLAG: u32 = 0x0080_0000;
+const FORWARD_BACK_FLAG: u32 = 0x0100_0000;
+const DIRECT_LOAD_FLAG: u32 = 0x0200_0000;
+const HOME_PAGE_FLAG: u32 = 0x0400_0000;
+const FROM_API_FLAG: u32 = 0x0800_0000;
+const CHAIN_START_FLAG: u32 = 0x1000_0000;
+const CHAIN_END_FLAG: u32 = 0x2000_0000;
+const CLIENT_REDIRECT_FLAG: u32 = 0x4000_0000;
+const SERVER_REDIRECT_FLAG: u32 = 0x8000_0000;
+
+const QUALIFIERS: [(u32, &str); 9] = [
+    (BLOCKED_FLAG, "Blocked"),
+    (FORWARD_BACK_FLAG, "ForwardBack"),
+    (DIRECT_LOAD_FLAG, "DirectLoad"),
+    (HOME_PAGE_FLAG, "HomePage"),
+    (FROM_API_FLAG, "FromApi"),
+    (CHAIN_START_FLAG, "ChainStart"),
+    (CHAIN_END_FLAG, "ChainEnd"),
+    (CLIENT_REDIRECT_FLAG, "ClientRedirect"),
+    (SERVER_REDIRECT_FLAG, "ServerRedirect"),
+];
+
+/// The source of a navigation, without the qualifier flags.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TransitionCore {
+    /// A link click or `window.open`, also used by sub-resource loads.
+    Link,
+    /// Some other explicit navigation, e.g. a typed URL or [crate::ImplFrame::load_url].
+    Explicit,
+    /// A suggestion such as a bookmark. Chrome style only.
+    AutoBookmark,
+    /// A subframe loaded automatically with its parent.
+    AutoSubframe,
+    /// A subframe navigated by the user.
+    ManualSubframe,
+    /// A search suggestion of the URL bar. Chrome style only.
+    Generated,
+    /// A top-level navigation which was not requested by the user, e.g. the startup page.
+    AutoToplevel,
+    FormSubmit,
+    /// A reload, or a restored session.
+    Reload,
+    /// A keyword search of the URL bar. Chrome style only.
+    Keyword,
+    /// A visit generated for a [TransitionCore::Keyword]. Chrome style only.
+    KeywordGenerated,
+}
+
+impl TransitionCore {
+    fn from_source(source: u32) -> Self {
+        match source {
+            0 => Self::Link,
+            1 => Self::Explicit,
+            2 => Self::AutoBookmark,
+            3 => Self::AutoSubframe,
+            4 => Self::ManualSubframe,
+            5 => Self::Generated,
+            6 => Self::AutoToplevel,
+            7 => Self::FormSubmit,
+            8 => Self::Reload,
+            9 => Self::Keyword,
+            10 => Self::KeywordGenerated,
+            // CEF uses explicit for navigations of unknown type.
+            _ => diagnostics::unknown_enum_value(source, Self::Explicit),
+        }
+    }
+}
+
+/// A [cef_transition_type_t] split into its source and qualifiers.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct TransitionType(u32);
+
+impl TransitionType {
+    pub fn from_raw(raw: u32) -> Self {
+        Self(raw)
+    }
+
+    pub fn raw(&self) -> u32 {
+        self.0
+    }
+
+    pub fn core(&self) -> TransitionCore {
+        TransitionCore::from_source(self.0 & SOURCE_MASK)
+    }
+
+    /// The navigation was blocked by a navigation throttle.
+    pub fn is_blocked(&self) -> bool {
+        self.has(BLOCKED_FLAG)
+    }
+
+    /// The navigation went back or forward in the history.
+    pub fn is_forward_back(&self) -> bool {
+        self.has(FORWARD_BACK_FLAG)
+    }
+
+    /// The URL was loaded directly from the URL bar or the command line.
+    pub fn is_direct_load(&self) -> bool {
+        self.has(DIRECT_LOAD_FLAG)
+    }
+
+    pub fn is_home_page(&self) -> bool {
+        self.has(HOME_PAGE_FLAG)
+    }
+
+    /// The navigation was started by the application, e.g. with
+    /// [crate::ImplFrame::load_url].
+    pub fn is_from_api(&self) -> bool {
+        self.has(FROM_API_FLAG)
+    }
+
+    /// The first navigation of a redirect chain.
+    pub fn is_chain_start(&self) -> bool {
+        self.has(CHAIN_START_FLAG)
+    }
+
+    /// The last navigation of a redirect chain.
+    pub fn is_chain_end(&self) -> bool {
+        self.has(CHAIN_END_FLAG)
+    }
+
+    /// A redirect by JavaScript or a meta refresh.
+    pub fn is_client_redirect(&self) -> bool {
+        self.has(CLIENT_REDIRECT_FLAG)
+    }
+
+    /// A redirect by an HTTP response.
+    pub fn is_server_redirect(&self) -> bool {
+        self.has(SERVER_REDIRECT_FLAG)
+    }
+
+    pub fn is_redirect(&self) -> bool {
+        self.is_client_redirect() || self.is_server_redirect()
+    }
+
+    fn has(&self, flag: u32) -> bool {
+        self.0 & flag != 0
+    }
+}
+
+impl fmt::Debug for TransitionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let qualifiers: Vec<_> = QUALIFIERS
+            .iter()
+            .filter_map(|&(flag, name)| self.has(flag).then_some(name))
+            .collect();
+        f.debug_struct("TransitionType")
+            .field("core", &self.core())
+            .field("qualifiers", &qualifiers)
+            .finish()
+    }
+}
+
+impl From<cef_transition_type_t> for TransitionType {
+    fn from(value: cef_transition_type_t) -> Self {
+        Self(value.0)
+    }
+}
+
+impl From<crate::TransitionType> for TransitionType {
+    fn from(value: crate::TransitionType) -> Self {
+        Self::from(*value.as_ref())
+    }
+}
+
+impl NavigationEntry {
+    /// `false` if the entry is empty, e.g. the entry of a browser which hasn't navigated yet.
+    pub fn is_valid(&self) -> bool {
+        ImplNavigationEntry::is_valid(self) != 0
+    }
+}
+
+/// Classifies a navigation for analytics and navigation policies.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NavigationClassifier {
+    pub transition: TransitionType,
+    pub is_main_frame: bool,
+}
+
+impl NavigationClassifier {
+    pub fn new(transition: TransitionType, is_main_frame: bool) -> Self {
+        Self {
+            transition,
+            is_main_frame,
+        }
+    }
+
+    /// Classify the navigation of `request` in `frame`, e.g. in
+    /// [crate::ImplRequestHandler::on_before_browse].
+    pub fn from_request(frame: &impl ImplFrame, request: &impl ImplRequest) -> Self {
+        Self::new(request.get_transition_type().into(), frame.is_main() != 0)
+    }
+
+    /// Classify a history entry, which is always a main frame navigation.
+    pub fn from_navigation_entry(entry: &impl ImplNavigationEntry) -> Self {
+        Self::new(entry.get_transition_type().into(), true)
+    }
+
+    /// The navigation replaces the page of the main frame.
+    pub fn is_top_level(&self) -> bool {
+        self.is_main_frame && self.transition.core() != TransitionCore::AutoSubframe
+    }
+
+    /// The user asked for this navigation, rather than the page, a redirect or the
+    /// application.
+    pub fn is_user_initiated(&self) -> bool {
+        if self.transition.is_redirect() || self.transition.is_from_api() {
+            return false;
+        }
+        !matches!(
+            self.transition.core(),
+            TransitionCore::AutoSubframe
+                | TransitionCore::AutoToplevel
+                | TransitionCore::KeywordGenerated
+        )
+    }
+
+    pub fn is_user_initiated_top_level(&self) -> bool {
+        self.is_top_level() && self.is_user_initiated()
+    }
+
+    /// Back, forward or reload, which show a page the user has already seen.
+    pub fn is_history_navigation(&self) -> bool {
+        self.transition.is_forward_back() || self.transition.core() == TransitionCore::Reload
+    }
+}
+
+/// The schemes blocked by [UrlFilterPolicy::default].
+const BLOCKED_SCHEMES: [&str; 5] = [
+    "chrome",
+    "chrome-untrusted",
+    "devtools",
+    "file",
+    "view-source",
+];
+
+/// The schemes restricted by [UrlFilterPolicy::allow_origin].
+const WEB_SCHEMES: [&str; 2] = ["http", "https"];
+
+/// The pages of blocked schemes which [UrlFilterPolicy::default] allows.
+const ALLOWED_URLS: [&str; 1] = ["chrome://version"];
+
+/// Which URLs may be navigated to, by scheme with exceptions for single pages.
+///
+/// The default policy blocks `chrome://` (except `chrome://version`), `chrome-untrusted://`,
+/// `devtools://`, `file://` and `view-source:` URLs. It allows every web origin unless some are
+/// allowed with [UrlFilterPolicy::allow_origin].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UrlFilterPolicy {
+    blocked_schemes: Vec<String>,
+    allowed_urls: Vec<String>,
+    allowed_origins: Vec<String>,
+}
+
+impl Default for UrlFilterPolicy {
+    fn default() -> Self {
+        Self {
+            blocked_schemes: BLOCKED_SCHEMES.iter().map(|s| s.to_string()).collect(),
+            allowed_urls: ALLOWED_URLS.iter().map(|s| s.to_string()).collect(),
+            allowed_origins: vec![],
+        }
+    }
+}
+
+impl UrlFilterPolicy {
+    /// Create the default policy.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Create a policy which allows every URL.
+    pub fn allow_all() -> Self {
+        Self {
+            blocked_schemes: vec![],
+            allowed_urls: vec![],
+            allowed_origins: vec![],
+        }
+    }
+
+    /// Block the URLs with `scheme`, e.g. `"data"`.
+    pub fn block_scheme(mut self, scheme: &str) -> Self {
+        let scheme = scheme.to_ascii_lowercase();
+        if !self.blocked_schemes.contains(&scheme) {
+            self.blocked_schemes.push(scheme);
+        }
+        self
+    }
+
+    /// Allow the URLs with `scheme` again.
+    pub fn allow_scheme(mut self, scheme: &str) -> Self {
+        self.blocked_schemes
+            .retain(|blocked| !blocked.eq_ignore_ascii_case(scheme));
+        self
+    }
+
+    /// Allow `url` and its subpaths, e.g. `"chrome://gpu"`, even if its scheme is blocked.
+    pub fn allow_url(mut self, url: &str) -> Self {
+        self.allowed_urls
+            .push(url.trim_end_matches('/').to_ascii_lowercase());
+        self
+    }
+
+    /// Only allow the `http` and `https` URLs of `origin`, e.g. `https://example.com`, and the
+    /// other allowed origins. The other schemes are not affected.
+    pub fn allow_origin(mut self, origin: &str) -> Self {
+        if let Some(origin) = origin_of(origin) {
+            if !self.allowed_origins.contains(&origin) {
+                self.allowed_origins.push(origin);
+            }
+        }
+        self
+    }
+
+    /// `true` if navigating to `url` is allowed.
+    pub fn is_allowed(&self, url: &str) -> bool {
+        let url = url.trim().to_ascii_lowercase();
+        let Some((scheme, _)) = url.split_once(':') else {
+            return true;
+        };
+        if !self.allowed_origins.is_empty() && WEB_SCHEMES.contains(&scheme) {
+            return origin_of(&url).is_some_and(|origin| self.allowed_origins.contains(&origin));
+        }
+        if !self.blocked_schemes.iter().any(|blocked| blocked == scheme) {
+            return true;
+        }
+        self.allowed_urls.iter().any(|allowed| {
+            url.strip_prefix(allowed.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(['/', '?', '#']))
+        })
+    }
+}
+
+/// Where a blocked navigation came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NavigationSource {
+    /// A navigation of the frame, see [ImplRequestHandler::on_before_browse].
+    Browse,
+    /// A redirect of a frame navigation to the blocked URL.
+    Redirect,
+    /// A link opened in a new tab or window, see [ImplRequestHandler::on_open_urlfrom_tab].
+    OpenUrlFromTab,
+}
+
+/// A navigation which was cancelled by the [UrlFilterPolicy] of a [NavigationPolicyBuilder].
+#[derive(Clone)]
+pub struct BlockedNavigation {
+    pub browser: Browser,
+    pub frame: Option<Frame>,
+    pub url: String,
+    pub source: NavigationSource,
+    pub is_main_frame: bool,
+    pub user_gesture: bool,
+}
+
+type BlockedNavigationCallback = Arc<dyn Fn(&BlockedNavigation) + Send + Sync>;
+
+/// Builder of a [RequestHandler] which enforces navigation policies.
+///
+/// Return the built handler from [crate::ImplClient::get_request_handler], e.g. with
+/// [crate::client::ClientBuilder::request_handler].
+#[derive(Clone, Default)]
+pub struct NavigationPolicyBuilder {
+    url_filter: Option<UrlFilterPolicy>,
+    on_blocked: Option<BlockedNavigationCallback>,
+}
+
+impl NavigationPolicyBuilder {
+    /// Create a builder which allows every navigation.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Cancel the navigations of every frame, including redirects and links opened in new tabs,
+    /// to URLs which `policy` does not allow.
+    pub fn url_filter(mut self, policy: UrlFilterPolicy) -> Self {
+        self.url_filter = Some(policy);
+        self
+    }
+
+    /// Called for every navigation which was cancelled, e.g. to log it or show a message.
+    pub fn on_blocked(
+        mut self,
+        callback: impl Fn(&BlockedNavigation) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_blocked = Some(Arc::new(callback));
+        self
+    }
+
+    /// `false` without a [NavigationPolicyBuilder::url_filter], since every navigation is
+    /// allowed and [NavigationPolicyBuilder::on_blocked] is never called.
+    pub fn has_overrides(&self) -> bool {
+        self.url_filter.is_some()
+    }
+
+    /// Build the [RequestHandler].
+    pub fn build(self) -> RequestHandler {
+        RequestHandler::new(BuiltNavigationPolicy {
+            object: std::ptr::null_mut(),
+            builder: self,
+        })
+    }
+
+    fn is_allowed(&self, url: &str) -> bool {
+        self.url_filter
+            .as_ref()
+            .is_none_or(|policy| policy.is_allowed(url))
+    }
+
+    /// Return `true` if the navigation to `url` must be cancelled, after reporting it.
+    fn block(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        frame: Option<&impl ImplFrame>,
+        url: String,
+        source: NavigationSource,
+        user_gesture: c_int,
+    ) -> bool {
+        if self.is_allowed(&url) {
+            return false;
+        }
+        if let (Some(callback), Some(browser)) = (&self.on_blocked, browser) {
+            let frame = frame.map(|frame| Frame::from_impl(frame));
+            callback(&BlockedNavigation {
+                browser: Browser::from_impl(browser),
+                is_main_frame: frame.as_ref().is_none_or(|frame| frame.is_main()),
+                frame,
+                url,
+                source,
+                user_gesture: user_gesture != 0,
+            });
+        }
+        true
+    }
+}
+
+/// The built handler, or `None` without [overrides](NavigationPolicyBuilder::has_overrides), see
+/// [crate::client::ClientBuilder::request_handler].
+impl From<NavigationPolicyBuilder> for Option<RequestHandler> {
+    fn from(builder: NavigationPolicyBuilder) -> Self {
+        builder.has_overrides().then(|| builder.build())
+    }
+}
+
+struct BuiltNavigationPolicy {
+    object: *mut RcImpl<_cef_request_handler_t, Self>,
+    builder: NavigationPolicyBuilder,
+}
+
+wrap_rc!(
+    BuiltNavigationPolicy,
+    WrapRequestHandler,
+    _cef_request_handler_t,
+    builder
+);
+
+impl ImplRequestHandler for BuiltNavigationPolicy {
+    fn on_before_browse(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        frame: Option<&impl ImplFrame>,
+        request: Option<&impl ImplRequest>,
+        user_gesture: c_int,
+        is_redirect: c_int,
+    ) -> c_int {
+        let url = request
+            .and_then(|request| request.get_url())
+            .map(|url| url.to_string())
+            .unwrap_or_default();
+        let source = if is_redirect != 0 {
+            NavigationSource::Redirect
+        } else {
+            NavigationSource::Browse
+        };
+        self.builder
+            .block(browser, frame, url, source, user_gesture) as c_int
+    }
+
+    fn on_open_urlfrom_tab(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        frame: Option<&impl ImplFrame>,
+        target_url: Option<&CefStringUtf16>,
+        _target_disposition: WindowOpenDisposition,
+        user_gesture: c_int,
+    ) -> c_int {
+        let url = target_url.map(|url| url.to_string()).unwrap_or_default();
+        self.builder.block(
+            browser,
+            frame,
+            url,
+            NavigationSource::OpenUrlFromTab,
+            user_gesture,
+        ) as c_int
+    }
+
+    fn get_raw(&self) -> *mut _cef_request_handler_t {
+        self.object as *mut _cef_request_handler_t
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_transition_type() {
+        // (raw, core, blocked, forward_back, redirect)
+        let cases = [
+            (0x0000_0000, TransitionCore::Link, false, false, false),
+            (0x0000_0001, TransitionCore::Explicit, false, false, false),
+            (0x0000_0007, TransitionCore::FormSubmit, false, false, false),
+            (0x0000_0008, TransitionCore::Reload, false, false, false),
+            (0x0100_0000, TransitionCore::Link, false, true, false),
+            (
+                0x0080_0003,
+                TransitionCore::AutoSubframe,
+                true,
+                false,
+                false,
+            ),
+            (0x3000_0000, TransitionCore::Link, false, false, false),
+            (0x4000_0000, TransitionCore::Link, false, false, true),
+            (0xA000_0007, TransitionCore::FormSubmit, false, false, true),
+            (0x0000_00FE, TransitionCore::Explicit, false, false, false),
+        ];
+        for (raw, core, blocked, forward_back, redirect) in cases {
+            let transition = TransitionType::from_raw(raw);
+            assert_eq!(transition.core(), core, "{raw:#x}");
+            assert_eq!(transition.is_blocked(), blocked, "{raw:#x}");
+            assert_eq!(transition.is_forward_back(), forward_back, "{raw:#x}");
+            assert_eq!(transition.is_redirect(), redirect, "{raw:#x}");
+        }
+    }
+
+    #[test]
+    fn test_unknown_core_diagnostic() {
+        let diagnostics = diagnostics::collect(|| {
+            assert_eq!(
+                TransitionType::from_raw(0x0000_0001).core(),
+                TransitionCore::Explicit
+            );
+        });
+        assert!(diagnostics.is_empty());
+        let diagnostics = diagnostics::collect(|| {
+            assert_eq!(
+                TransitionType::from_raw(0x0000_00FE).core(),
+                TransitionCore::Explicit
+            );
+        });
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].category,
+            diagnostics::DiagnosticCategory::UnknownEnumValue
+        );
+        assert!(diagnostics[0].message.contains("254"));
+    }
+
+    #[test]
+    fn test_from_raw_enum() {
+        let transition = TransitionType::from(cef_transition_type_t::TT_FORM_SUBMIT);
+        assert_eq!(transition.raw(), 7);
+        let transition = TransitionType::from(crate::TransitionType::from(
+            cef_transition_type_t::TT_SERVER_REDIRECT_FLAG,
+        ));
+        assert!(transition.is_server_redirect());
+    }
+
+    #[test]
+    fn test_debug() {
+        assert_eq!(
+            format!("{:?}", TransitionType::from_raw(0x1100_0000)),
+            r#"TransitionType { core: Link, qualifiers: ["ForwardBack", "ChainStart"] }"#
+        );
+    }
+
+    #[test]
+    fn test_navigation_classifier() {
+        // (raw, is_main_frame, user_initiated_top_level, history)
+        let cases = [
+            (0x0000_0000, true, true, false),
+            (0x0000_0000, false, false, false),
+            (0x0000_0001, true, true, false),
+            (0x0800_0001, true, false, false),
+            (0x0000_0003, true, false, false),
+            (0x0000_0004, false, false, false),
+            (0x0000_0006, true, false, false),
+            (0x3000_0007, true, true, false),
+            (0x4000_0000, true, false, false),
+            (0x8000_0000, true, false, false),
+            (0x0000_0008, true, true, true),
+            (0x0100_0000, true, true, true),
+        ];
+        for (raw, is_main_frame, user_initiated_top_level, history) in cases {
+            let classifier =
+                NavigationClassifier::new(TransitionType::from_raw(raw), is_main_frame);
+            assert_eq!(
+                classifier.is_user_initiated_top_level(),
+                user_initiated_top_level,
+                "{raw:#x} main={is_main_frame}"
+            );
+            assert_eq!(classifier.is_history_navigation(), history, "{raw:#x}");
+        }
+    }
+
+    #[test]
+    fn test_default_url_filter() {
+        let policy = UrlFilterPolicy::default();
+        let cases = [
+            ("https://example.com/", true),
+            ("about:blank", true),
+            ("data:text/html,hi", true),
+            ("chrome://version", true),
+            ("chrome://version/", true),
+            ("CHROME://VERSION?x", true),
+            ("chrome://versions", false),
+            ("chrome://settings", false),
+            ("chrome://gpu", false),
+            ("chrome-untrusted://print/", false),
+            ("view-source:https://example.com/", false),
+            ("file:///etc/passwd", false),
+            ("devtools://devtools/bundled/inspector.html", false),
+            ("", true),
+        ];
+        for (url, allowed) in cases {
+            assert_eq!(policy.is_allowed(url), allowed, "{url}");
+        }
+    }
+
+    #[test]
+    fn test_custom_url_filter() {
+        let policy = UrlFilterPolicy::new()
+            .allow_scheme("FILE")
+            .block_scheme("data")
+            .allow_url("chrome://gpu/");
+        assert!(policy.is_allowed("file:///tmp/index.html"));
+        assert!(!policy.is_allowed("data:text/html,hi"));
+        assert!(policy.is_allowed("chrome://gpu"));
+        assert!(!policy.is_allowed("chrome://settings"));
+        assert!(UrlFilterPolicy::allow_all().is_allowed("chrome://settings"));
+    }
+
+    #[test]
+    fn test_allowed_origins() {
+        let policy = UrlFilterPolicy::new()
+            .allow_origin("https://kiosk.example.com/")
+            .allow_origin("http://localhost:8080");
+        assert!(policy.is_allowed("https://kiosk.example.com/home?x=1"));
+        assert!(policy.is_allowed("HTTP://LOCALHOST:8080/"));
+        assert!(!policy.is_allowed("https://example.com/"));
+        assert!(!policy.is_allowed("http://localhost:8081/"));
+        assert!(policy.is_allowed("about:blank"));
+        assert!(!policy.is_allowed("chrome://settings"));
+    }
+
+    #[test]
+    fn test_blocked_redirect_is_cancelled() {
+        let blocked = Arc::new(std::sync::Mutex::new(0));
+        let counter = blocked.clone();
+        let builder = NavigationPolicyBuilder::new()
+            .url_filter(UrlFilterPolicy::default())
+            .on_blocked(move |_| *counter.lock().unwrap() += 1);
+        let no_browser = Option::<&Browser>::None;
+        let no_frame = Option::<&Frame>::None;
+        assert!(builder.block(
+            no_browser,
+            no_frame,
+            "view-source:https://example.com/".to_string(),
+            NavigationSource::Redirect,
+            0,
+        ));
+        let no_browser = Option::<&Browser>::None;
+        let no_frame = Option::<&Frame>::None;
+        assert!(!builder.block(
+            no_browser,
+            no_frame,
+            "https://example.com/".to_string(),
+            NavigationSource::Browse,
+            1,
+        ));
+        // Without a browser there is nothing to report.
+        assert_eq!(*blocked.lock().unwrap(), 0);
+        assert!(NavigationPolicyBuilder::new().is_allowed("chrome://settings"));
+    }
+}
@@ -0,0 +1,337 @@
+//! Notification module
+//!
+//! Web Notifications need the `notifications` permission, which Chromium asks the
+//! [PermissionHandler] of the client for with a permission prompt.
+//! [NotificationPermissionHandler] answers those prompts per origin, and
+//! [BrowserHost::request_notification_permission] asks the same handler from the application,
+//! e.g. to show the permission of an origin in its settings.
+//!
+//! ```ignore
+//! let client = ClientBuilder::new()
+//!     .permission_handler(
+//!         NotificationPermissionHandler::new()
+//!             .grant("https://mail.example.com")
+//!             .build(),
+//!     )
+//!     .build();
+//! ```
+
+use std::{
+    future::Future,
+    os::raw::c_int,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use cef_sys::{
+    _cef_permission_handler_t, _cef_permission_prompt_callback_t, cef_permission_request_result_t,
+    cef_permission_request_types_t,
+};
+
+use crate::{
+    future::{channel, Sender},
+    rc::{wrap_rc, RcImpl},
+    BrowserHost, CefString, CefStringUtf16, CefStringUtf8, ImplBrowser, ImplBrowserHost,
+    ImplClient, ImplPermissionHandler, ImplPermissionPromptCallback, PermissionHandler,
+    PermissionPromptCallback, PermissionRequestResult,
+};
+
+/// The permission of an origin to show notifications, as `Notification.permission` has it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum NotificationPermissionStatus {
+    Granted,
+    Denied,
+    /// Neither granted nor denied, so the page may ask again.
+    #[default]
+    Default,
+}
+
+impl NotificationPermissionStatus {
+    /// The value of `Notification.permission`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Granted => "granted",
+            Self::Denied => "denied",
+            Self::Default => "default",
+        }
+    }
+
+    /// The answer to a permission prompt, or `None` to let CEF handle it.
+    fn prompt_result(&self) -> Option<PermissionRequestResult> {
+        match self {
+            Self::Granted => Some(cef_permission_request_result_t::CEF_PERMISSION_RESULT_ACCEPT),
+            Self::Denied => Some(cef_permission_request_result_t::CEF_PERMISSION_RESULT_DENY),
+            Self::Default => None,
+        }
+        .map(PermissionRequestResult::from)
+    }
+}
+
+impl From<PermissionRequestResult> for NotificationPermissionStatus {
+    fn from(result: PermissionRequestResult) -> Self {
+        match result.into() {
+            cef_permission_request_result_t::CEF_PERMISSION_RESULT_ACCEPT => Self::Granted,
+            cef_permission_request_result_t::CEF_PERMISSION_RESULT_DENY => Self::Denied,
+            _ => Self::Default,
+        }
+    }
+}
+
+/// The `requested_permissions` of a notifications prompt.
+fn notifications_permission() -> u32 {
+    cef_permission_request_types_t::CEF_PERMISSION_TYPE_NOTIFICATIONS.0
+}
+
+type PromptFn = dyn Fn(&str) -> NotificationPermissionStatus + Send + Sync;
+
+/// Builds a [PermissionHandler] which answers the notifications prompts of some origins.
+///
+/// Prompts for other permissions, and for origins which are neither granted, denied nor answered
+/// by [NotificationPermissionHandler::on_show_permission_prompt], get the default handling.
+#[derive(Clone, Default)]
+pub struct NotificationPermissionHandler {
+    granted: Vec<String>,
+    denied: Vec<String>,
+    on_prompt: Option<Arc<PromptFn>>,
+}
+
+impl NotificationPermissionHandler {
+    /// Create a builder which answers no prompts.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Grant notifications to pages of `origin`, e.g. `https://example.com`.
+    pub fn grant(mut self, origin: impl Into<String>) -> Self {
+        self.granted.push(trim_origin(origin.into()));
+        self
+    }
+
+    /// Deny notifications to pages of `origin`.
+    pub fn deny(mut self, origin: impl Into<String>) -> Self {
+        self.denied.push(trim_origin(origin.into()));
+        self
+    }
+
+    /// Decide with `on_prompt` for the origins which are neither granted nor denied. Return
+    /// [NotificationPermissionStatus::Default] to let CEF handle the prompt.
+    pub fn on_show_permission_prompt(
+        mut self,
+        on_prompt: impl Fn(&str) -> NotificationPermissionStatus + Send + Sync + 'static,
+    ) -> Self {
+        self.on_prompt = Some(Arc::new(on_prompt));
+        self
+    }
+
+    /// The answer to a notifications prompt of `origin`.
+    pub fn status(&self, origin: &str) -> NotificationPermissionStatus {
+        let origin = origin.trim_end_matches('/');
+        if self.granted.iter().any(|granted| granted == origin) {
+            NotificationPermissionStatus::Granted
+        } else if self.denied.iter().any(|denied| denied == origin) {
+            NotificationPermissionStatus::Denied
+        } else if let Some(on_prompt) = &self.on_prompt {
+            on_prompt(origin)
+        } else {
+            NotificationPermissionStatus::Default
+        }
+    }
+
+    /// Build the [PermissionHandler] for [crate::client::ClientBuilder::permission_handler].
+    pub fn build(self) -> PermissionHandler {
+        PermissionHandler::new(NotificationHandler {
+            object: std::ptr::null_mut(),
+            builder: self,
+        })
+    }
+}
+
+fn trim_origin(origin: String) -> String {
+    origin.trim_end_matches('/').to_string()
+}
+
+struct NotificationHandler {
+    object: *mut RcImpl<_cef_permission_handler_t, Self>,
+    builder: NotificationPermissionHandler,
+}
+
+wrap_rc!(
+    NotificationHandler,
+    WrapPermissionHandler,
+    _cef_permission_handler_t,
+    builder
+);
+
+impl ImplPermissionHandler for NotificationHandler {
+    fn on_show_permission_prompt(
+        &self,
+        _browser: Option<&impl ImplBrowser>,
+        _prompt_id: u64,
+        requesting_origin: Option<&CefStringUtf16>,
+        requested_permissions: u32,
+        callback: Option<&impl ImplPermissionPromptCallback>,
+    ) -> c_int {
+        if requested_permissions != notifications_permission() {
+            return 0;
+        }
+        let origin = requesting_origin
+            .map(|origin| origin.to_string())
+            .unwrap_or_default();
+        match (self.builder.status(&origin).prompt_result(), callback) {
+            (Some(result), Some(callback)) => {
+                callback.cont(result);
+                1
+            }
+            _ => 0,
+        }
+    }
+
+    fn get_raw(&self) -> *mut _cef_permission_handler_t {
+        self.object as *mut _cef_permission_handler_t
+    }
+}
+
+/// The prompt ids of [BrowserHost::request_notification_permission], counting down from the top
+/// so they don't collide with the ids of Chromium's prompts.
+static NEXT_PROMPT_ID: AtomicU64 = AtomicU64::new(u64::MAX);
+
+impl BrowserHost {
+    /// Ask the [PermissionHandler] of the client whether `origin` may show notifications, as
+    /// Chromium does with a permission prompt. Resolves with
+    /// [NotificationPermissionStatus::Default] if the client has no permission handler, or the
+    /// handler leaves the prompt to CEF or dismisses it.
+    ///
+    /// Nothing is shown to the user, and Chromium doesn't remember the answer, so pages still
+    /// prompt through the handler themselves.
+    pub fn request_notification_permission(
+        &self,
+        origin: &str,
+    ) -> impl Future<Output = NotificationPermissionStatus> {
+        let (sender, completion) = channel();
+        let callback = PermissionPromptCallback::new(PromptResultCallback {
+            object: std::ptr::null_mut(),
+            sender: Arc::new(Mutex::new(Some(sender))),
+        });
+        let origin = CefString::from(&CefStringUtf8::from(origin));
+        let handled = self
+            .get_client()
+            .and_then(|client| client.get_permission_handler())
+            .is_some_and(|handler| {
+                handler.on_show_permission_prompt(
+                    self.get_browser().as_ref(),
+                    NEXT_PROMPT_ID.fetch_sub(1, Ordering::Relaxed),
+                    Some(&origin),
+                    notifications_permission(),
+                    Some(&callback),
+                ) != 0
+            });
+        // Resolves the future with `None` unless the handler keeps the callback.
+        drop(callback);
+
+        async move {
+            if !handled {
+                return NotificationPermissionStatus::Default;
+            }
+            completion
+                .await
+                .map(NotificationPermissionStatus::from)
+                .unwrap_or_default()
+        }
+    }
+}
+
+/// Resolves [BrowserHost::request_notification_permission] with the answer of the handler.
+struct PromptResultCallback {
+    object: *mut RcImpl<_cef_permission_prompt_callback_t, Self>,
+    sender: Arc<Mutex<Option<Sender<PermissionRequestResult>>>>,
+}
+
+wrap_rc!(
+    PromptResultCallback,
+    WrapPermissionPromptCallback,
+    _cef_permission_prompt_callback_t,
+    sender
+);
+
+impl ImplPermissionPromptCallback for PromptResultCallback {
+    fn cont(&self, result: PermissionRequestResult) {
+        let sender = self.sender.lock().ok().and_then(|mut sender| sender.take());
+        if let Some(sender) = sender {
+            sender.send(result);
+        }
+    }
+
+    fn get_raw(&self) -> *mut _cef_permission_prompt_callback_t {
+        self.object as *mut _
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        pin::pin,
+        task::{Context, Poll, Waker},
+    };
+
+    use super::*;
+    use crate::{client::ClientBuilder, fixtures::FakeHost};
+
+    fn request(
+        handler: NotificationPermissionHandler,
+        origin: &str,
+    ) -> NotificationPermissionStatus {
+        let host = FakeHost::new()
+            .client(
+                ClientBuilder::new()
+                    .permission_handler(handler.build())
+                    .build(),
+            )
+            .build();
+        let mut cx = Context::from_waker(Waker::noop());
+        match pin!(host.request_notification_permission(origin)).poll(&mut cx) {
+            Poll::Ready(status) => status,
+            Poll::Pending => panic!("The handler didn't answer"),
+        }
+    }
+
+    #[test]
+    fn test_request_notification_permission() {
+        let handler = NotificationPermissionHandler::new()
+            .grant("https://mail.example.com/")
+            .deny("https://ads.example.com");
+        assert_eq!(
+            request(handler.clone(), "https://mail.example.com"),
+            NotificationPermissionStatus::Granted
+        );
+        assert_eq!(
+            request(handler.clone(), "https://ads.example.com"),
+            NotificationPermissionStatus::Denied
+        );
+        assert_eq!(
+            request(handler.clone(), "https://other.example.com"),
+            NotificationPermissionStatus::Default
+        );
+
+        let handler = handler.on_show_permission_prompt(|origin| {
+            if origin.ends_with(".example.com") {
+                NotificationPermissionStatus::Denied
+            } else {
+                NotificationPermissionStatus::Default
+            }
+        });
+        assert_eq!(
+            request(handler.clone(), "https://mail.example.com"),
+            NotificationPermissionStatus::Granted
+        );
+        assert_eq!(
+            request(handler.clone(), "https://other.example.com"),
+            NotificationPermissionStatus::Denied
+        );
+        assert_eq!(
+            request(handler, "https://example.org"),
+            NotificationPermissionStatus::Default
+        );
+    }
+}
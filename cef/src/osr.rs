@@ -0,0 +1,1098 @@
+//! Off-screen rendering module
+//!
+//! [OsrHost] has the [BrowserHost] methods of windowless browsers, and [FrameStream] turns their
+//! paints into a stream of frames which only keeps the latest one.
+//!
+//! ## Popup widgets
+//!
+//! The list of a `<select>` and similar widgets are painted separately from the view, as
+//! [PaintElementType] `PET_POPUP`. [FrameStream] draws them over the view, and
+//! [FrameStreamBuilder::on_popup] reports when they are shown and hidden, e.g. to draw them in a
+//! separate surface instead.
+//!
+//! CEF reports the popup rectangle in device independent pixels (DIP) of the view, while its
+//! buffer has the size of the rectangle times the device scale factor. Mouse events over the popup
+//! still go to the browser, in DIP view coordinates like every other mouse event. Convert the
+//! coordinates of a popup surface with [popup_to_view] or [popup_pixel_to_view] first.
+//!
+//! ## Fixed layout size
+//!
+//! [BrowserHost::set_fixed_layout_size] lays the page out at a size of its own, independent of
+//! the view rectangle of the render handler, e.g. to render a page for a 1920x1080 screen into a
+//! thumbnail. CEF has no method for it, so it overrides the device metrics through DevTools.
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt,
+    future::Future,
+    mem,
+    os::raw::c_int,
+    slice,
+    sync::{Arc, Mutex, MutexGuard},
+    task::{Context, Poll, Waker},
+    time::{Duration, Instant},
+};
+
+use cef_sys::{_cef_render_handler_t, cef_paint_element_type_t, cef_thread_id_t};
+
+use crate::{
+    browser::BrowserId,
+    devtools::{key, next_message_id},
+    dictionary_value_create,
+    rc::{wrap_rc, RcImpl},
+    thread, Browser, BrowserHost, ImplBrowser, ImplBrowserHost, ImplDictionaryValue,
+    ImplRenderHandler, PaintElementType, Rect, RenderHandler, Size,
+};
+
+/// The lifecycle of a popup widget of a windowless browser, see [FrameStreamBuilder::on_popup].
+#[derive(Clone)]
+pub enum PopupEvent {
+    /// The popup was shown at this rectangle in DIP view coordinates, or moved or resized to it.
+    PopupShown(Rect),
+    PopupHidden,
+}
+
+type PopupCallback = dyn Fn(PopupEvent) + Send + Sync;
+
+/// Convert `(x, y)` in DIP relative to the `popup` rectangle to DIP view coordinates.
+pub fn popup_to_view(popup: &Rect, x: c_int, y: c_int) -> (c_int, c_int) {
+    (popup.x + x, popup.y + y)
+}
+
+/// Convert DIP view coordinates to DIP relative to the `popup` rectangle, or `None` if they are
+/// outside of the popup.
+pub fn view_to_popup(popup: &Rect, x: c_int, y: c_int) -> Option<(c_int, c_int)> {
+    let (x, y) = (x - popup.x, y - popup.y);
+    ((0..popup.width).contains(&x) && (0..popup.height).contains(&y)).then_some((x, y))
+}
+
+/// Convert `(x, y)` in pixels of a popup buffer `buffer_width` pixels wide, as painted by CEF, to
+/// DIP view coordinates. The device scale factor is the buffer width over the `popup` width.
+pub fn popup_pixel_to_view(
+    popup: &Rect,
+    buffer_width: usize,
+    x: usize,
+    y: usize,
+) -> (c_int, c_int) {
+    if popup.width <= 0 || buffer_width == 0 {
+        return (popup.x, popup.y);
+    }
+    let scale = buffer_width as f64 / popup.width as f64;
+    popup_to_view(
+        popup,
+        (x as f64 / scale).floor() as c_int,
+        (y as f64 / scale).floor() as c_int,
+    )
+}
+
+/// The methods of a [`BrowserHost`] which are only used with windowless (off-screen) rendering.
+///
+/// Get it with [`BrowserHost::osr`]. Windowed browsers silently ignore these methods, so `osr`
+/// asserts the browser is windowless in debug builds.
+#[derive(Clone)]
+pub struct OsrHost(BrowserHost);
+
+impl BrowserHost {
+    /// Get the off-screen rendering methods of this host.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, if window rendering is enabled for the browser.
+    pub fn osr(&self) -> OsrHost {
+        debug_assert!(
+            self.is_window_rendering_disabled() != 0,
+            "BrowserHost::osr requires a windowless browser, windowed browsers ignore these methods"
+        );
+        OsrHost(self.clone())
+    }
+
+    /// Lay the page out at `size` in DIP instead of the size of the view, or at the size of the
+    /// view again for `None`. The view keeps painting at its own size, so the page is cropped or
+    /// padded.
+    ///
+    /// Only meaningful for windowless browsers, whose view size comes from the render handler.
+    /// Sent through the DevTools method `Emulation.setDeviceMetricsOverride`, so it does nothing
+    /// before the first navigation. Must be called on the UI thread.
+    pub fn set_fixed_layout_size(&self, size: Option<Size>) {
+        let params = dictionary_value_create();
+        let method = match (&size, &params) {
+            (Some(size), Some(params)) => {
+                params.set_int(Some(&key("width")), size.width);
+                params.set_int(Some(&key("height")), size.height);
+                // Keep the device scale factor of the screen.
+                params.set_double(Some(&key("deviceScaleFactor")), 0.0);
+                params.set_bool(Some(&key("mobile")), 0);
+                "Emulation.setDeviceMetricsOverride"
+            }
+            _ => "Emulation.clearDeviceMetricsOverride",
+        };
+        self.execute_dev_tools_method(next_message_id(), Some(&key(method)), params.as_ref());
+
+        let Some(id) = self.get_browser().map(|browser| browser.identifier()) else {
+            return;
+        };
+        FIXED_LAYOUT_SIZES.with_borrow_mut(|sizes| match size {
+            Some(size) => sizes.insert(id, size),
+            None => sizes.remove(&id),
+        });
+    }
+
+    /// The size set with [BrowserHost::set_fixed_layout_size].
+    pub fn get_fixed_layout_size(&self) -> Option<Size> {
+        let id = self.get_browser()?.identifier();
+        FIXED_LAYOUT_SIZES.with_borrow(|sizes| sizes.get(&id).cloned())
+    }
+}
+
+thread_local! {
+    /// The sizes of [BrowserHost::set_fixed_layout_size], on the UI thread.
+    static FIXED_LAYOUT_SIZES: RefCell<HashMap<BrowserId, Size>> = Default::default();
+}
+
+impl OsrHost {
+    /// Get the [`BrowserHost`].
+    pub fn host(&self) -> &BrowserHost {
+        &self.0
+    }
+
+    /// Notify the browser that it has been hidden or shown, which suspends or resumes painting.
+    pub fn was_hidden(&self, hidden: bool) {
+        self.0.was_hidden(hidden as c_int);
+    }
+
+    /// Notify the browser that the view size from `get_view_rect` has changed.
+    pub fn was_resized(&self) {
+        self.0.was_resized();
+    }
+
+    /// Notify the browser that the screen info from `get_screen_info` has changed.
+    pub fn notify_screen_info_changed(&self) {
+        self.0.notify_screen_info_changed();
+    }
+
+    /// Request a repaint of `element`.
+    pub fn invalidate(&self, element: impl Into<PaintElementType>) {
+        self.0.invalidate(element.into());
+    }
+}
+
+/// The number of buffers a [FrameBufferPool] keeps: the frame being painted, the latest frame,
+/// and the frame the consumer holds.
+const POOL_SIZE: usize = 3;
+
+/// Released pixel buffers which are reused for the next frames, so a stream of frames doesn't
+/// allocate one buffer per paint. Clones share the buffers.
+#[derive(Clone, Default)]
+pub struct FrameBufferPool(Arc<Mutex<Vec<Vec<u8>>>>);
+
+impl FrameBufferPool {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// A buffer of `len` bytes, reusing a released one if there is any. The contents of a reused
+    /// buffer are unspecified.
+    pub fn acquire(&self, len: usize) -> Vec<u8> {
+        let mut buffer = self.lock().pop().unwrap_or_default();
+        buffer.resize(len, 0);
+        buffer
+    }
+
+    /// Keep `buffer` for [FrameBufferPool::acquire], unless the pool is full.
+    pub fn release(&self, buffer: Vec<u8>) {
+        let mut buffers = self.lock();
+        if buffers.len() < POOL_SIZE && buffer.capacity() > 0 {
+            buffers.push(buffer);
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<Vec<u8>>> {
+        self.0.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+/// A painted view of a windowless browser, with the popup widget drawn over it. Dropping the
+/// frame returns its buffer to the [FrameBufferPool] it came from.
+pub struct PaintFrame {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+    pool: FrameBufferPool,
+}
+
+impl PaintFrame {
+    /// The width in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The height in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The BGRA pixels, row by row without padding, like the buffer of
+    /// [ImplRenderHandler::on_paint].
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+}
+
+impl fmt::Debug for PaintFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PaintFrame")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for PaintFrame {
+    fn drop(&mut self) {
+        self.pool.release(mem::take(&mut self.pixels));
+    }
+}
+
+/// How a [FrameStream] lets a windowless browser stop painting while nobody takes its frames.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PausePainting {
+    /// Never pause.
+    Never,
+    /// Lower the frame rate to the given one, and restore it when the frames are taken again.
+    FrameRate(c_int),
+    /// Notify the browser that it was hidden, which also makes the page hidden for the
+    /// Page Visibility API.
+    Hide,
+}
+
+/// Builds a [FrameStream].
+pub struct FrameStreamBuilder {
+    width: c_int,
+    height: c_int,
+    idle_timeout: Duration,
+    pause: PausePainting,
+    on_popup: Option<Arc<PopupCallback>>,
+}
+
+impl FrameStreamBuilder {
+    /// A stream of a view of `width` by `height` device independent pixels, which lowers the
+    /// frame rate to 1 after nobody took a frame for a second.
+    pub fn new(width: c_int, height: c_int) -> Self {
+        Self {
+            width,
+            height,
+            idle_timeout: Duration::from_secs(1),
+            pause: PausePainting::FrameRate(1),
+            on_popup: None,
+        }
+    }
+
+    /// Call `on_popup` on the UI thread when a popup widget is shown, moved, resized or hidden.
+    pub fn on_popup(mut self, on_popup: impl Fn(PopupEvent) + Send + Sync + 'static) -> Self {
+        self.on_popup = Some(Arc::new(on_popup));
+        self
+    }
+
+    /// Pause painting after nobody took a frame for `idle_timeout`.
+    pub fn idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    /// How to pause painting.
+    pub fn pause(mut self, pause: PausePainting) -> Self {
+        self.pause = pause;
+        self
+    }
+
+    pub fn build(self) -> FrameStream {
+        FrameStream {
+            state: Arc::new(StreamState {
+                inner: Mutex::new(StreamInner {
+                    size: (self.width, self.height),
+                    latest: None,
+                    compositor: Default::default(),
+                    host: None,
+                    waker: None,
+                    last_taken: Instant::now(),
+                    paused: None,
+                    closed: false,
+                }),
+                pool: FrameBufferPool::new(),
+                idle_timeout: self.idle_timeout,
+                pause: self.pause,
+                on_popup: self.on_popup,
+            }),
+        }
+    }
+}
+
+/// The frames of a windowless browser as a stream which only keeps the latest frame.
+///
+/// Pass [FrameStream::render_handler] to the client of the browser, and take the frames with
+/// [FrameStream::next], [FrameStream::poll_next] or [FrameStream::latest] on any thread. A
+/// consumer which is slower than the browser skips frames instead of buffering them, and the
+/// buffers of skipped and dropped frames are reused. The popup widget, e.g. the list of a
+/// `<select>`, is drawn over the view at the rectangle CEF reports.
+///
+/// The crate has no dependency on a `Stream` trait. Adapt the stream with e.g.
+/// `futures::stream::poll_fn(move |cx| frames.poll_next(cx))`.
+pub struct FrameStream {
+    state: Arc<StreamState>,
+}
+
+impl FrameStream {
+    /// The handler which paints into this stream. The stream ends when CEF releases it.
+    pub fn render_handler(&self) -> RenderHandler {
+        RenderHandler::new(StreamRenderHandler {
+            object: std::ptr::null_mut(),
+            state: self.state.clone(),
+            _close: Arc::new(CloseOnDrop(self.state.clone())),
+        })
+    }
+
+    /// Change the size of the view in device independent pixels, and notify the browser.
+    pub fn resize(&self, width: c_int, height: c_int) {
+        let host = {
+            let mut inner = self.state.lock();
+            inner.size = (width, height);
+            inner.host.clone()
+        };
+        if let Some(host) = host {
+            thread::post(cef_thread_id_t::TID_UI, move || host.osr().was_resized());
+        }
+    }
+
+    /// The rectangle of the shown popup widget in DIP view coordinates, e.g. to tell whether a
+    /// mouse event is over it.
+    pub fn popup_rect(&self) -> Option<Rect> {
+        self.state.lock().compositor.shown_popup_rect()
+    }
+
+    /// Take the latest frame without waiting, e.g. once per frame of a render loop.
+    pub fn latest(&self) -> Option<PaintFrame> {
+        let (frame, resume) = self.state.take();
+        resume_painting(resume);
+        frame
+    }
+
+    /// Take the latest frame, or wake `cx` when there is one. Resolves to `None` when the stream
+    /// ended.
+    pub fn poll_next(&self, cx: &mut Context<'_>) -> Poll<Option<PaintFrame>> {
+        let (frame, resume) = self.state.poll_next(cx);
+        resume_painting(resume);
+        frame
+    }
+
+    /// Resolve with the next frame, or `None` when the stream ended.
+    pub fn next(&self) -> impl Future<Output = Option<PaintFrame>> + '_ {
+        std::future::poll_fn(|cx| self.poll_next(cx))
+    }
+}
+
+/// Undo the pause of a [FrameStream], when its frames are taken again.
+enum Resume {
+    Show(BrowserHost),
+    FrameRate(BrowserHost, c_int),
+}
+
+fn resume_painting(resume: Option<Resume>) {
+    if let Some(resume) = resume {
+        thread::post(cef_thread_id_t::TID_UI, move || match resume {
+            Resume::Show(host) => host.osr().was_hidden(false),
+            Resume::FrameRate(host, frame_rate) => host.set_windowless_frame_rate(frame_rate),
+        });
+    }
+}
+
+struct StreamState {
+    inner: Mutex<StreamInner>,
+    pool: FrameBufferPool,
+    idle_timeout: Duration,
+    pause: PausePainting,
+    on_popup: Option<Arc<PopupCallback>>,
+}
+
+struct StreamInner {
+    size: (c_int, c_int),
+    latest: Option<PaintFrame>,
+    compositor: Compositor,
+    host: Option<BrowserHost>,
+    waker: Option<Waker>,
+    /// When a frame was last taken, or asked for.
+    last_taken: Instant,
+    /// How to resume painting, if it is paused.
+    paused: Option<PausePainting>,
+    closed: bool,
+}
+
+impl StreamState {
+    fn lock(&self) -> MutexGuard<'_, StreamInner> {
+        self.inner.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn take(&self) -> (Option<PaintFrame>, Option<Resume>) {
+        let mut inner = self.lock();
+        let resume = self.touch(&mut inner);
+        (inner.latest.take(), resume)
+    }
+
+    fn poll_next(&self, cx: &mut Context<'_>) -> (Poll<Option<PaintFrame>>, Option<Resume>) {
+        let mut inner = self.lock();
+        let resume = self.touch(&mut inner);
+        let frame = match inner.latest.take() {
+            Some(frame) => Poll::Ready(Some(frame)),
+            None if inner.closed => Poll::Ready(None),
+            None => {
+                inner.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        };
+        (frame, resume)
+    }
+
+    /// Record that the consumer asked for a frame, and end a pause.
+    fn touch(&self, inner: &mut StreamInner) -> Option<Resume> {
+        inner.last_taken = Instant::now();
+        let paused = inner.paused.take()?;
+        let host = inner.host.clone()?;
+        match paused {
+            PausePainting::Never => None,
+            PausePainting::FrameRate(frame_rate) => Some(Resume::FrameRate(host, frame_rate)),
+            PausePainting::Hide => Some(Resume::Show(host)),
+        }
+    }
+
+    /// Update the latest frame with a change of the compositor, and return `true` if painting
+    /// should pause because nobody took a frame for the idle timeout.
+    fn update(&self, now: Instant, change: impl FnOnce(&mut Compositor)) -> bool {
+        let mut inner = self.lock();
+        change(&mut inner.compositor);
+        let Some(frame) = inner.compositor.compose(&self.pool) else {
+            return false;
+        };
+        // The replaced frame returns its buffer to the pool.
+        inner.latest = Some(frame);
+        if let Some(waker) = inner.waker.take() {
+            // A consumer waiting for a frame isn't idle.
+            waker.wake();
+            return false;
+        }
+        let idle = now.saturating_duration_since(inner.last_taken) >= self.idle_timeout;
+        idle && self.pause != PausePainting::Never && inner.paused.is_none()
+    }
+
+    /// Pause painting of `host`, and remember how to resume it.
+    fn pause(&self, host: &BrowserHost) {
+        let paused = match self.pause {
+            PausePainting::Never => return,
+            PausePainting::FrameRate(frame_rate) => {
+                let active = host.get_windowless_frame_rate();
+                host.set_windowless_frame_rate(frame_rate);
+                PausePainting::FrameRate(active)
+            }
+            PausePainting::Hide => {
+                host.osr().was_hidden(true);
+                PausePainting::Hide
+            }
+        };
+        self.lock().paused = Some(paused);
+    }
+
+    /// Report a popup event, without holding the lock.
+    fn notify(&self, event: Option<PopupEvent>) {
+        if let (Some(on_popup), Some(event)) = (&self.on_popup, event) {
+            on_popup(event);
+        }
+    }
+
+    fn close(&self) {
+        let waker = {
+            let mut inner = self.lock();
+            inner.closed = true;
+            inner.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// Ends the stream when the last clone of the render handler is dropped.
+struct CloseOnDrop(Arc<StreamState>);
+
+impl Drop for CloseOnDrop {
+    fn drop(&mut self) {
+        self.0.close();
+    }
+}
+
+/// A rectangle in pixels.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct PixelRect {
+    x: isize,
+    y: isize,
+    width: usize,
+    height: usize,
+}
+
+/// Draws the popup widget over the view. CEF paints them separately, and reports the popup
+/// rectangle in device independent pixels of the view.
+#[derive(Default)]
+struct Compositor {
+    view: Vec<u8>,
+    width: usize,
+    height: usize,
+    popup: Vec<u8>,
+    popup_size: (usize, usize),
+    /// The popup rectangle in device independent pixels.
+    popup_rect: Option<(c_int, c_int, c_int, c_int)>,
+    popup_shown: bool,
+}
+
+impl Compositor {
+    fn paint_view(&mut self, pixels: &[u8], width: usize, height: usize) {
+        self.view.clear();
+        self.view.extend_from_slice(pixels);
+        (self.width, self.height) = (width, height);
+    }
+
+    fn paint_popup(&mut self, pixels: &[u8], width: usize, height: usize) {
+        self.popup.clear();
+        self.popup.extend_from_slice(pixels);
+        self.popup_size = (width, height);
+    }
+
+    /// Show or hide the popup, and return the event if that changed its visibility.
+    fn show_popup(&mut self, show: bool) -> Option<PopupEvent> {
+        let shown = mem::replace(&mut self.popup_shown, show);
+        if !show {
+            // A popup shown again paints again, so don't draw the old one meanwhile.
+            self.popup.clear();
+            self.popup_rect = None;
+            return shown.then_some(PopupEvent::PopupHidden);
+        }
+        // CEF sizes the popup after showing it, which reports it instead.
+        (!shown)
+            .then(|| self.shown_popup_rect())
+            .flatten()
+            .map(PopupEvent::PopupShown)
+    }
+
+    /// Move or resize the popup, and return the event if it is shown.
+    fn size_popup(&mut self, rect: &Rect) -> Option<PopupEvent> {
+        self.popup_rect = Some((rect.x, rect.y, rect.width, rect.height));
+        self.shown_popup_rect().map(PopupEvent::PopupShown)
+    }
+
+    fn shown_popup_rect(&self) -> Option<Rect> {
+        let (x, y, width, height) = self.popup_rect.filter(|_| self.popup_shown)?;
+        Some(Rect {
+            x,
+            y,
+            width,
+            height,
+        })
+    }
+
+    /// The popup rectangle in pixels of the painted buffers. The popup buffer has the size of
+    /// its rectangle times the device scale factor.
+    fn popup_pixel_rect(&self) -> Option<PixelRect> {
+        let (x, y, width, _) = self.popup_rect?;
+        let (popup_width, popup_height) = self.popup_size;
+        if !self.popup_shown || self.popup.is_empty() || width <= 0 {
+            return None;
+        }
+        let scale = popup_width as f64 / width as f64;
+        Some(PixelRect {
+            x: (x as f64 * scale).round() as isize,
+            y: (y as f64 * scale).round() as isize,
+            width: popup_width,
+            height: popup_height,
+        })
+    }
+
+    /// The view with the popup drawn over it, or `None` before the view was painted.
+    fn compose(&self, pool: &FrameBufferPool) -> Option<PaintFrame> {
+        if self.view.is_empty() {
+            return None;
+        }
+        let mut pixels = pool.acquire(self.view.len());
+        pixels.copy_from_slice(&self.view);
+        if let Some(rect) = self.popup_pixel_rect() {
+            blit(&mut pixels, self.width, self.height, &self.popup, rect);
+        }
+        Some(PaintFrame {
+            width: self.width,
+            height: self.height,
+            pixels,
+            pool: pool.clone(),
+        })
+    }
+}
+
+/// Copy the BGRA `source` of the size of `rect` into `target` at `rect`, clipped to the target.
+fn blit(target: &mut [u8], width: usize, height: usize, source: &[u8], rect: PixelRect) {
+    let left = rect.x.max(0) as usize;
+    let right = (rect.x + rect.width as isize).clamp(0, width as isize) as usize;
+    if left >= right {
+        return;
+    }
+    for row in 0..rect.height {
+        let y = rect.y + row as isize;
+        if y < 0 || y >= height as isize {
+            continue;
+        }
+        let source_start = (row * rect.width + (left as isize - rect.x) as usize) * 4;
+        let target_start = (y as usize * width + left) * 4;
+        let len = (right - left) * 4;
+        if let (Some(target), Some(source)) = (
+            target.get_mut(target_start..target_start + len),
+            source.get(source_start..source_start + len),
+        ) {
+            target.copy_from_slice(source);
+        }
+    }
+}
+
+struct StreamRenderHandler {
+    object: *mut RcImpl<_cef_render_handler_t, Self>,
+    state: Arc<StreamState>,
+    _close: Arc<CloseOnDrop>,
+}
+
+wrap_rc!(
+    StreamRenderHandler,
+    WrapRenderHandler,
+    _cef_render_handler_t,
+    state,
+    _close
+);
+
+impl StreamRenderHandler {
+    /// The host of `browser`, remembered to resume painting from the consumer.
+    fn host(&self, browser: Option<&impl ImplBrowser>) -> Option<BrowserHost> {
+        let mut inner = self.state.lock();
+        if inner.host.is_none() {
+            inner.host = browser
+                .map(Browser::from_impl)
+                .and_then(|browser| browser.get_host());
+        }
+        inner.host.clone()
+    }
+
+    fn update(&self, browser: Option<&impl ImplBrowser>, change: impl FnOnce(&mut Compositor)) {
+        let host = self.host(browser);
+        if self.state.update(Instant::now(), change) {
+            if let Some(host) = host {
+                self.state.pause(&host);
+            }
+        }
+    }
+}
+
+impl ImplRenderHandler for StreamRenderHandler {
+    fn get_view_rect(&self, _browser: Option<&impl ImplBrowser>, rect: Option<&mut Rect>) {
+        if let Some(rect) = rect {
+            (rect.width, rect.height) = self.state.lock().size;
+        }
+    }
+
+    fn on_popup_show(&self, browser: Option<&impl ImplBrowser>, show: c_int) {
+        let mut event = None;
+        self.update(browser, |compositor| {
+            event = compositor.show_popup(show != 0)
+        });
+        self.state.notify(event);
+    }
+
+    fn on_popup_size(&self, _browser: Option<&impl ImplBrowser>, rect: Option<&Rect>) {
+        if let Some(rect) = rect {
+            let event = self.state.lock().compositor.size_popup(rect);
+            self.state.notify(event);
+        }
+    }
+
+    fn on_paint(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        type_: PaintElementType,
+        _dirty_rects_count: usize,
+        _dirty_rects: Option<&Rect>,
+        buffer: *const u8,
+        width: c_int,
+        height: c_int,
+    ) {
+        let (Ok(width), Ok(height)) = (usize::try_from(width), usize::try_from(height)) else {
+            return;
+        };
+        if buffer.is_null() {
+            return;
+        }
+        let pixels = unsafe { slice::from_raw_parts(buffer, width * height * 4) };
+        let is_popup = type_ == PaintElementType::from(cef_paint_element_type_t::PET_POPUP);
+        self.update(browser, |compositor| {
+            if is_popup {
+                compositor.paint_popup(pixels, width, height);
+            } else {
+                compositor.paint_view(pixels, width, height);
+            }
+        });
+    }
+
+    fn get_raw(&self) -> *mut _cef_render_handler_t {
+        self.object as *mut _
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        pin::pin,
+        sync::atomic::{AtomicBool, Ordering},
+        task::Wake,
+    };
+
+    use super::*;
+    use crate::fixtures::FakeHost;
+
+    /// An event of the render handler, as recorded from a browser opening a `<select>`.
+    enum Event {
+        View(usize, usize, u8),
+        Popup(usize, usize, u8),
+        PopupShow(bool),
+        PopupSize(c_int, c_int, c_int, c_int),
+    }
+
+    fn solid(width: usize, height: usize, value: u8) -> Vec<u8> {
+        vec![value; width * height * 4]
+    }
+
+    fn replay(compositor: &mut Compositor, events: &[Event]) {
+        for event in events {
+            match *event {
+                Event::View(width, height, value) => {
+                    compositor.paint_view(&solid(width, height, value), width, height)
+                }
+                Event::Popup(width, height, value) => {
+                    compositor.paint_popup(&solid(width, height, value), width, height)
+                }
+                Event::PopupShow(show) => {
+                    compositor.show_popup(show);
+                }
+                Event::PopupSize(x, y, width, height) => {
+                    compositor.size_popup(&Rect {
+                        x,
+                        y,
+                        width,
+                        height,
+                    });
+                }
+            }
+        }
+    }
+
+    /// The rows of the composed frame, with the first byte of each pixel.
+    fn rows(compositor: &Compositor) -> Vec<Vec<u8>> {
+        let frame = compositor.compose(&FrameBufferPool::new()).unwrap();
+        frame
+            .pixels()
+            .chunks(frame.width() * 4)
+            .map(|row| row.iter().step_by(4).copied().collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_compose_popup_sequences() {
+        let mut compositor = Compositor::default();
+        assert!(compositor.compose(&FrameBufferPool::new()).is_none());
+
+        // The popup is shown and sized before its first paint.
+        replay(
+            &mut compositor,
+            &[
+                Event::View(4, 4, 1),
+                Event::PopupShow(true),
+                Event::PopupSize(1, 1, 2, 2),
+            ],
+        );
+        assert_eq!(rows(&compositor), [[1; 4]; 4]);
+        replay(&mut compositor, &[Event::Popup(2, 2, 9)]);
+        assert_eq!(
+            rows(&compositor),
+            [[1, 1, 1, 1], [1, 9, 9, 1], [1, 9, 9, 1], [1, 1, 1, 1]]
+        );
+
+        // The view repaints under the open popup.
+        replay(&mut compositor, &[Event::View(4, 4, 2)]);
+        assert_eq!(
+            rows(&compositor),
+            [[2, 2, 2, 2], [2, 9, 9, 2], [2, 9, 9, 2], [2, 2, 2, 2]]
+        );
+
+        // Closing the popup removes it, and opening it again waits for its paint.
+        replay(
+            &mut compositor,
+            &[
+                Event::PopupShow(false),
+                Event::PopupShow(true),
+                Event::PopupSize(0, 2, 3, 1),
+            ],
+        );
+        assert_eq!(rows(&compositor), [[2; 4]; 4]);
+        replay(&mut compositor, &[Event::Popup(3, 1, 7)]);
+        assert_eq!(
+            rows(&compositor),
+            [[2, 2, 2, 2], [2, 2, 2, 2], [7, 7, 7, 2], [2, 2, 2, 2]]
+        );
+    }
+
+    #[test]
+    fn test_compose_clipped_popup() {
+        let mut compositor = Compositor::default();
+        replay(
+            &mut compositor,
+            &[
+                Event::View(3, 3, 1),
+                Event::PopupShow(true),
+                Event::PopupSize(2, -1, 2, 3),
+                Event::Popup(2, 3, 9),
+            ],
+        );
+        assert_eq!(rows(&compositor), [[1, 1, 9], [1, 1, 9], [1, 1, 1]]);
+        replay(&mut compositor, &[Event::PopupSize(5, 0, 2, 3)]);
+        assert_eq!(rows(&compositor), [[1; 3]; 3]);
+    }
+
+    #[test]
+    fn test_compose_scaled_popup() {
+        // At a device scale factor of 2, the popup rectangle is half the size of its buffer.
+        let mut compositor = Compositor::default();
+        replay(
+            &mut compositor,
+            &[
+                Event::View(4, 4, 1),
+                Event::PopupShow(true),
+                Event::PopupSize(1, 0, 1, 1),
+                Event::Popup(2, 2, 9),
+            ],
+        );
+        assert_eq!(
+            rows(&compositor),
+            [[1, 1, 9, 9], [1, 1, 9, 9], [1, 1, 1, 1], [1, 1, 1, 1]]
+        );
+    }
+
+    #[test]
+    fn test_frame_buffer_pool() {
+        let pool = FrameBufferPool::new();
+        let buffer = pool.acquire(16);
+        let address = buffer.as_ptr();
+        pool.release(buffer);
+        let buffer = pool.acquire(8);
+        assert_eq!(buffer.as_ptr(), address);
+        assert_eq!(buffer.len(), 8);
+
+        for _ in 0..POOL_SIZE + 2 {
+            pool.release(vec![0; 4]);
+        }
+        assert_eq!(pool.lock().len(), POOL_SIZE);
+    }
+
+    fn paint(stream: &FrameStream, now: Instant, value: u8) -> bool {
+        stream.state.update(now, |compositor| {
+            compositor.paint_view(&solid(2, 2, value), 2, 2)
+        })
+    }
+
+    #[test]
+    fn test_latest_frame() {
+        let stream = FrameStreamBuilder::new(2, 2).build();
+        assert!(stream.latest().is_none());
+        let now = Instant::now();
+        for value in 1..=5 {
+            paint(&stream, now, value);
+        }
+        let frame = stream.latest().unwrap();
+        assert_eq!((frame.width(), frame.height()), (2, 2));
+        assert_eq!(frame.pixels(), solid(2, 2, 5));
+        assert!(stream.latest().is_none());
+
+        // The buffers of the skipped frames were reused.
+        drop(frame);
+        assert!(stream.state.pool.lock().len() <= POOL_SIZE);
+    }
+
+    struct Flag(AtomicBool);
+
+    impl Wake for Flag {
+        fn wake(self: Arc<Self>) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_poll_next() {
+        let stream = FrameStreamBuilder::new(2, 2).build();
+        let flag = Arc::new(Flag(AtomicBool::new(false)));
+        let waker = Waker::from(flag.clone());
+        let mut cx = Context::from_waker(&waker);
+        let mut next = pin!(stream.next());
+        assert!(next.as_mut().poll(&mut cx).is_pending());
+
+        // A waiting consumer keeps painting going.
+        assert!(!paint(&stream, Instant::now() + Duration::from_secs(10), 3));
+        assert!(flag.0.load(Ordering::SeqCst));
+        let Poll::Ready(Some(frame)) = next.as_mut().poll(&mut cx) else {
+            panic!("no frame");
+        };
+        assert_eq!(frame.pixels(), solid(2, 2, 3));
+
+        drop(CloseOnDrop(stream.state.clone()));
+        assert!(matches!(stream.poll_next(&mut cx), Poll::Ready(None)));
+    }
+
+    #[test]
+    fn test_pause_when_idle() {
+        let stream = FrameStreamBuilder::new(2, 2)
+            .idle_timeout(Duration::from_millis(100))
+            .build();
+        let now = Instant::now();
+        assert!(!paint(&stream, now, 1));
+        assert!(paint(&stream, now + Duration::from_millis(150), 2));
+        stream.state.lock().paused = Some(PausePainting::FrameRate(60));
+        assert!(!paint(&stream, now + Duration::from_millis(200), 3));
+
+        // Taking a frame ends the pause.
+        assert!(stream.latest().is_some());
+        assert!(stream.state.lock().paused.is_none());
+        assert!(!paint(&stream, Instant::now(), 4));
+
+        let stream = FrameStreamBuilder::new(2, 2)
+            .idle_timeout(Duration::ZERO)
+            .pause(PausePainting::Never)
+            .build();
+        assert!(!paint(&stream, now + Duration::from_secs(1), 1));
+    }
+
+    fn rect(x: c_int, y: c_int, width: c_int, height: c_int) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    fn fields(rect: &Rect) -> (c_int, c_int, c_int, c_int) {
+        (rect.x, rect.y, rect.width, rect.height)
+    }
+
+    #[test]
+    fn test_popup_events() {
+        let events = Arc::new(Mutex::new(vec![]));
+        let stream = FrameStreamBuilder::new(400, 300)
+            .on_popup({
+                let events = events.clone();
+                move |event| {
+                    events.lock().unwrap().push(match event {
+                        PopupEvent::PopupShown(rect) => Some(fields(&rect)),
+                        PopupEvent::PopupHidden => None,
+                    })
+                }
+            })
+            .build();
+        let handler = StreamRenderHandler {
+            object: std::ptr::null_mut(),
+            state: stream.state.clone(),
+            _close: Arc::new(CloseOnDrop(stream.state.clone())),
+        };
+        let browser = None::<&Browser>;
+
+        // As recorded from clicking a `<select>` of 3 options at (20, 40), and then an option.
+        handler.on_paint(
+            browser,
+            cef_paint_element_type_t::PET_VIEW.into(),
+            0,
+            None,
+            solid(400, 300, 1).as_ptr(),
+            400,
+            300,
+        );
+        handler.on_popup_show(browser, 1);
+        assert!(stream.popup_rect().is_none());
+        handler.on_popup_size(browser, Some(&rect(20, 62, 120, 60)));
+        handler.on_paint(
+            browser,
+            cef_paint_element_type_t::PET_POPUP.into(),
+            0,
+            None,
+            solid(120, 60, 9).as_ptr(),
+            120,
+            60,
+        );
+        let popup = stream.popup_rect().unwrap();
+        assert_eq!(fields(&popup), (20, 62, 120, 60));
+        // The popup lies within the view.
+        assert!(popup.x >= 0 && popup.x + popup.width <= 400);
+        assert!(popup.y >= 0 && popup.y + popup.height <= 300);
+        handler.on_popup_show(browser, 0);
+        handler.on_popup_show(browser, 0);
+        assert!(stream.popup_rect().is_none());
+
+        assert_eq!(*events.lock().unwrap(), [Some((20, 62, 120, 60)), None]);
+    }
+
+    #[test]
+    fn test_popup_coordinates() {
+        let popup = rect(20, 62, 120, 60);
+        assert_eq!(popup_to_view(&popup, 10, 25), (30, 87));
+        assert_eq!(view_to_popup(&popup, 30, 87), Some((10, 25)));
+        assert_eq!(view_to_popup(&popup, 140, 87), None);
+        assert_eq!(view_to_popup(&popup, 19, 62), None);
+
+        // At a device scale factor of 2, the buffer is 240 pixels wide.
+        assert_eq!(popup_pixel_to_view(&popup, 240, 21, 51), (30, 87));
+        assert_eq!(popup_pixel_to_view(&popup, 120, 10, 25), (30, 87));
+        assert_eq!(popup_pixel_to_view(&rect(5, 5, 0, 0), 240, 21, 51), (5, 5));
+    }
+
+    #[test]
+    fn test_fixed_layout_size() {
+        let methods = Arc::new(Mutex::new(Vec::new()));
+        let recorded = methods.clone();
+        let host = FakeHost::new()
+            .browser(7)
+            .on_dev_tools_method(move |method, params| {
+                let size = params.map(|params| {
+                    let width = params.get_int(Some(&key("width")));
+                    let height = params.get_int(Some(&key("height")));
+                    format!(" {width}x{height}")
+                });
+                recorded
+                    .lock()
+                    .unwrap()
+                    .push(method + &size.unwrap_or_default());
+            })
+            .build();
+        assert!(host.get_fixed_layout_size().is_none());
+
+        host.set_fixed_layout_size(Some(Size {
+            width: 1920,
+            height: 1080,
+        }));
+        let size = host.get_fixed_layout_size().unwrap();
+        assert_eq!((size.width, size.height), (1920, 1080));
+
+        host.set_fixed_layout_size(None);
+        assert!(host.get_fixed_layout_size().is_none());
+        assert_eq!(
+            *methods.lock().unwrap(),
+            [
+                "Emulation.setDeviceMetricsOverride 1920x1080",
+                "Emulation.clearDeviceMetricsOverride",
+            ]
+        );
+    }
+}
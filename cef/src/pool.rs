@@ -0,0 +1,277 @@
+//! Pool module
+//!
+//! [BrowserPool] creates browsers which share a client and keeps track of them until they close,
+//! with its own [LifeSpanHandler] pushed to the client. Popups opened by the browsers of the pool
+//! join it too.
+//!
+//! Like [crate::browser::create_browser_sync], every function must be called on the UI thread.
+
+use std::{cell::RefCell, collections::VecDeque, future::Future, rc::Rc};
+
+use cef_sys::_cef_life_span_handler_t;
+
+use crate::{
+    browser::{BrowserId, CreateBrowserError},
+    browser_host_create_browser,
+    client::ClientBuilder,
+    context::{self, is_running},
+    future::{channel, Sender},
+    rc::{wrap_rc, RcImpl},
+    thread::is_ui_thread,
+    Browser, BrowserSettings, CefString, CefStringUtf8, Client, DictionaryValue, ImplBrowser,
+    ImplBrowserHost, ImplLifeSpanHandler, LifeSpanHandler, RequestContext, WindowInfo,
+};
+
+/// The browsers of a pool, and the futures waiting for them.
+#[derive(Default)]
+struct PoolState {
+    browsers: Vec<Browser>,
+    /// The browsers requested by [BrowserPool::create_browser], in order.
+    created: VecDeque<Sender<Browser>>,
+    /// The futures of [BrowserPool::close_all].
+    closed: Vec<Sender<()>>,
+}
+
+impl PoolState {
+    fn add(&mut self, browser: Browser) {
+        // Popups aren't requested by the pool.
+        let requested = browser.is_popup() == 0;
+        self.browsers.push(browser.clone());
+        if requested {
+            if let Some(created) = self.created.pop_front() {
+                created.send(browser);
+            }
+        }
+    }
+
+    fn remove(&mut self, id: BrowserId) {
+        self.browsers.retain(|browser| browser.identifier() != id);
+        if self.browsers.is_empty() {
+            self.closed.drain(..).for_each(|closed| closed.send(()));
+        }
+    }
+}
+
+/// A set of browsers created with the same client and [WindowInfo], which tracks them from
+/// [ImplLifeSpanHandler::on_after_created] to [ImplLifeSpanHandler::on_before_close].
+///
+/// ```ignore
+/// let pool = BrowserPool::new(ClientBuilder::new(), WindowInfo::default());
+/// let browser = pool
+///     .create_browser("https://example.com", BrowserSettings::default())
+///     .await?;
+/// assert_eq!(pool.browser_count(), 1);
+/// pool.close_all().await;
+/// ```
+pub struct BrowserPool {
+    state: Rc<RefCell<PoolState>>,
+    client: Client,
+    window_info: WindowInfo,
+    request_context: Option<RequestContext>,
+}
+
+impl BrowserPool {
+    /// Create the browsers with the client built from `client`, and `window_info`.
+    pub fn new(client: ClientBuilder, window_info: WindowInfo) -> Self {
+        let state = Rc::<RefCell<PoolState>>::default();
+        let client = client
+            .push_life_span_handler(PoolLifeSpan::handler(state.clone()))
+            .build();
+        Self {
+            state,
+            client,
+            window_info,
+            request_context: None,
+        }
+    }
+
+    /// Create the browsers in `request_context` instead of the global one.
+    pub fn with_request_context(mut self, request_context: RequestContext) -> Self {
+        self.request_context = Some(request_context);
+        self
+    }
+
+    /// Create a browser which loads `url`. The future resolves once CEF created it, or with
+    /// [CreateBrowserError::Failed] if CEF refused to.
+    pub fn create_browser(
+        &self,
+        url: &str,
+        settings: BrowserSettings,
+    ) -> impl Future<Output = Result<Browser, CreateBrowserError>> {
+        let created = self.request().inspect(|_| {
+            let url = CefString::from(&CefStringUtf8::from(url));
+            let started = browser_host_create_browser(
+                Some(&self.window_info),
+                Some(&self.client),
+                Some(&url),
+                Some(&settings),
+                Option::<&DictionaryValue>::None,
+                self.request_context.as_ref(),
+            ) != 0;
+            if !started {
+                // Resolves the future with `None`.
+                self.state.borrow_mut().created.pop_back();
+            }
+        });
+
+        async move { created?.await.ok_or(CreateBrowserError::Failed) }
+    }
+
+    /// Wait for the next browser created by the pool.
+    fn request(&self) -> Result<impl Future<Output = Option<Browser>>, CreateBrowserError> {
+        if !is_ui_thread() {
+            return Err(if is_running() {
+                CreateBrowserError::WrongThread
+            } else {
+                CreateBrowserError::NotInitialized
+            });
+        }
+        let (sender, created) = channel();
+        self.state.borrow_mut().created.push_back(sender);
+        Ok(created)
+    }
+
+    /// Close every browser of the pool. The future resolves once all of them closed, including
+    /// browsers created meanwhile.
+    pub fn close_all(&self) -> impl Future<Output = ()> {
+        let (sender, closed) = channel();
+        let browsers = {
+            let mut state = self.state.borrow_mut();
+            if state.browsers.is_empty() {
+                sender.send(());
+            } else {
+                state.closed.push(sender);
+            }
+            state.browsers.clone()
+        };
+        // Closing may call back into the pool, so not while it is borrowed.
+        for browser in browsers {
+            if let Some(host) = browser.get_host() {
+                host.close_browser(1);
+            }
+        }
+
+        async move {
+            closed.await;
+        }
+    }
+
+    /// The number of browsers which were created and are not closed yet.
+    pub fn browser_count(&self) -> usize {
+        self.state.borrow().browsers.len()
+    }
+
+    /// The browsers of the pool, in the order they were created.
+    pub fn iter(&self) -> impl Iterator<Item = Browser> {
+        self.state.borrow().browsers.clone().into_iter()
+    }
+
+    /// The browser of the pool with the [identifier](Browser::identifier) `id`.
+    pub fn find_by_id(&self, id: BrowserId) -> Option<Browser> {
+        self.state
+            .borrow()
+            .browsers
+            .iter()
+            .find(|browser| browser.identifier() == id)
+            .cloned()
+    }
+}
+
+/// The [LifeSpanHandler] which a [BrowserPool] pushes to its client.
+struct PoolLifeSpan {
+    object: *mut RcImpl<_cef_life_span_handler_t, Self>,
+    state: Rc<RefCell<PoolState>>,
+}
+
+wrap_rc!(
+    PoolLifeSpan,
+    WrapLifeSpanHandler,
+    _cef_life_span_handler_t,
+    state
+);
+
+impl PoolLifeSpan {
+    fn handler(state: Rc<RefCell<PoolState>>) -> LifeSpanHandler {
+        LifeSpanHandler::new(Self {
+            object: std::ptr::null_mut(),
+            state,
+        })
+    }
+}
+
+impl ImplLifeSpanHandler for PoolLifeSpan {
+    fn on_after_created(&self, browser: Option<&impl ImplBrowser>) {
+        let Some(browser) = browser else {
+            return;
+        };
+        let browser = Browser::from_impl(browser);
+        // Closed before shutdown like those of create_browser_sync.
+        context::register_browser(&browser);
+        self.state.borrow_mut().add(browser);
+    }
+
+    fn on_before_close(&self, browser: Option<&impl ImplBrowser>) {
+        if let Some(browser) = browser {
+            self.state.borrow_mut().remove(browser.get_identifier());
+        }
+    }
+
+    fn get_raw(&self) -> *mut _cef_life_span_handler_t {
+        self.object as *mut _
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        pin::pin,
+        sync::{Arc, Mutex},
+        task::{Context, Poll, Waker},
+    };
+
+    use super::*;
+    use crate::fixtures::{FakeBrowser, FakeHost};
+
+    type Closed = Arc<Mutex<Vec<BrowserId>>>;
+
+    fn fake_browser(id: BrowserId, closed: &Closed) -> Browser {
+        let closed = closed.clone();
+        let host = FakeHost::new().on_close(move || closed.lock().unwrap().push(id));
+        FakeBrowser::new(id).host(host.build()).build()
+    }
+
+    #[test]
+    fn test_create_and_close_all() {
+        let pool = BrowserPool::new(ClientBuilder::new(), WindowInfo::default());
+        // The handler CEF would get from the client of the pool.
+        let life_span = PoolLifeSpan::handler(pool.state.clone());
+        let closed = Closed::default();
+        let mut cx = Context::from_waker(Waker::noop());
+
+        for id in 1..=3 {
+            let (sender, created) = channel();
+            pool.state.borrow_mut().created.push_back(sender);
+            life_span.on_after_created(Some(&fake_browser(id, &closed)));
+            let created = pin!(created).poll(&mut cx);
+            assert!(matches!(created, Poll::Ready(Some(browser)) if browser.identifier() == id));
+        }
+        assert_eq!(pool.browser_count(), 3);
+        let ids: Vec<_> = pool.iter().map(|browser| browser.identifier()).collect();
+        assert_eq!(ids, [1, 2, 3]);
+        assert_eq!(
+            pool.find_by_id(2).map(|browser| browser.identifier()),
+            Some(2)
+        );
+        assert!(pool.find_by_id(4).is_none());
+
+        let mut close_all = pin!(pool.close_all());
+        assert_eq!(*closed.lock().unwrap(), [1, 2, 3]);
+        for id in 1..=3 {
+            assert!(close_all.as_mut().poll(&mut cx).is_pending());
+            life_span.on_before_close(Some(&fake_browser(id, &closed)));
+        }
+        assert_eq!(close_all.as_mut().poll(&mut cx), Poll::Ready(()));
+        assert_eq!(pool.browser_count(), 0);
+        assert_eq!(pin!(pool.close_all()).poll(&mut cx), Poll::Ready(()));
+    }
+}
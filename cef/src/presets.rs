@@ -0,0 +1,273 @@
+//! Presets module
+//!
+//! [kiosk] assembles the settings and handlers a kiosk deployment needs from a [KioskConfig].
+//! It returns the [Settings], [BrowserSettings] and [ClientBuilder] instead of a finished
+//! [crate::Client], so each piece can still be changed, e.g. by replacing a handler slot of the
+//! builder.
+
+use std::os::raw::c_int;
+
+use cef_sys::{_cef_jsdialog_handler_t, _cef_load_handler_t, _cef_request_handler_t, cef_state_t};
+
+use crate::{
+    client::ClientBuilder,
+    context_menu::ContextMenuHandlerBuilder,
+    life_span::PopupBlocker,
+    media_devices::{MediaAccessGrant, MediaDeviceKind},
+    navigation::{NavigationPolicyBuilder, UrlFilterPolicy},
+    rc::{wrap_rc, RcImpl},
+    switches::SwitchSet,
+    BrowserSettings, CefStringUtf16, Frame, ImplBrowser, ImplFrame, ImplJsdialogCallback,
+    ImplJsdialogHandler, ImplLoadHandler, ImplRequestHandler, JsdialogHandler, JsdialogType,
+    LifeSpanHandler, LoadHandler, RequestHandler, Settings, State, TerminationStatus,
+};
+
+/// Injected into each main frame by [KioskConfig::hide_cursor].
+const HIDE_CURSOR_SCRIPT: &str = "(() => { \
+    const style = document.createElement('style'); \
+    style.textContent = '* { cursor: none !important; }'; \
+    document.documentElement.appendChild(style); \
+})()";
+
+/// The configuration of [kiosk].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KioskConfig {
+    /// The page the kiosk shows, which is also allowed to load.
+    pub home_url: String,
+    /// The origins which may be navigated to besides the origin of [KioskConfig::home_url].
+    pub allowed_origins: Vec<String>,
+    /// The origin which gets camera and microphone access without a prompt.
+    pub media_origin: Option<String>,
+    /// Reload the page when its renderer process crashes or is killed.
+    pub reload_on_crash: bool,
+    /// Hide the mouse cursor over the page, e.g. for touch screens.
+    pub hide_cursor: bool,
+}
+
+impl KioskConfig {
+    /// A configuration which only allows the origin of `home_url`.
+    pub fn new(home_url: impl Into<String>) -> Self {
+        Self {
+            home_url: home_url.into(),
+            allowed_origins: vec![],
+            media_origin: None,
+            reload_on_crash: true,
+            hide_cursor: false,
+        }
+    }
+
+    /// The switches which open Chrome style browsers in a fullscreen frameless window, for
+    /// [crate::app::AppBuilder::switches].
+    pub fn switches(&self) -> SwitchSet {
+        SwitchSet::new().raw("kiosk", None).kiosk_printing()
+    }
+
+    /// The navigations the kiosk allows: the origins of the home URL and the allowed origins,
+    /// and the pages [UrlFilterPolicy::default] allows.
+    fn url_filter(&self) -> UrlFilterPolicy {
+        [self.home_url.as_str()]
+            .into_iter()
+            .chain(self.allowed_origins.iter().map(String::as_str))
+            .fold(UrlFilterPolicy::new(), |policy, origin| {
+                policy.allow_origin(origin)
+            })
+    }
+}
+
+/// Assemble the pieces of a kiosk:
+///
+/// - [Settings] which ignore the command line of the process, so users can't add switches.
+/// - [BrowserSettings] which don't let pages close the window or read the clipboard.
+/// - A [ClientBuilder] with handlers which block popups and navigations outside the allowed
+///   origins, grant media access to [KioskConfig::media_origin], suppress context menus and
+///   JavaScript dialogs, and reload crashed pages.
+///
+/// Apply [KioskConfig::switches] for a fullscreen frameless window.
+pub fn kiosk(config: KioskConfig) -> (Settings, BrowserSettings, ClientBuilder) {
+    let settings = Settings {
+        command_line_args_disabled: 1,
+        ..Default::default()
+    };
+    let browser_settings = BrowserSettings {
+        javascript_close_windows: State::from(cef_state_t::STATE_DISABLED),
+        javascript_access_clipboard: State::from(cef_state_t::STATE_DISABLED),
+        ..Default::default()
+    };
+
+    let mut client = ClientBuilder::new()
+        .request_handler(
+            NavigationPolicyBuilder::new()
+                .url_filter(config.url_filter())
+                .build(),
+        )
+        .life_span_handler(LifeSpanHandler::new(PopupBlocker::new()))
+        .context_menu_handler(
+            ContextMenuHandlerBuilder::new()
+                .suppress_all_menus()
+                .build(),
+        )
+        .jsdialog_handler(JsdialogHandler::new(SuppressDialogs {
+            object: std::ptr::null_mut(),
+        }));
+    if let Some(origin) = &config.media_origin {
+        client = client.permission_handler(
+            MediaAccessGrant::new()
+                .device(MediaDeviceKind::AudioInput)
+                .device(MediaDeviceKind::VideoInput)
+                .origin(origin.as_str())
+                .build(),
+        );
+    }
+    if config.reload_on_crash {
+        client = client.push_request_handler(RequestHandler::new(ReloadOnCrash {
+            object: std::ptr::null_mut(),
+        }));
+    }
+    if config.hide_cursor {
+        client = client.load_handler(LoadHandler::new(HideCursor {
+            object: std::ptr::null_mut(),
+        }));
+    }
+
+    (settings, browser_settings, client)
+}
+
+/// Suppresses every JavaScript dialog, and lets pages unload without asking.
+struct SuppressDialogs {
+    object: *mut RcImpl<_cef_jsdialog_handler_t, Self>,
+}
+
+wrap_rc!(
+    SuppressDialogs,
+    WrapJsdialogHandler,
+    _cef_jsdialog_handler_t
+);
+
+impl ImplJsdialogHandler for SuppressDialogs {
+    fn on_jsdialog(
+        &self,
+        _browser: Option<&impl ImplBrowser>,
+        _origin_url: Option<&CefStringUtf16>,
+        _dialog_type: JsdialogType,
+        _message_text: Option<&CefStringUtf16>,
+        _default_prompt_text: Option<&CefStringUtf16>,
+        _callback: Option<&impl ImplJsdialogCallback>,
+        suppress_message: Option<&mut c_int>,
+    ) -> c_int {
+        if let Some(suppress_message) = suppress_message {
+            *suppress_message = 1;
+        }
+        0
+    }
+
+    fn on_before_unload_dialog(
+        &self,
+        _browser: Option<&impl ImplBrowser>,
+        _message_text: Option<&CefStringUtf16>,
+        _is_reload: c_int,
+        callback: Option<&impl ImplJsdialogCallback>,
+    ) -> c_int {
+        let Some(callback) = callback else {
+            return 0;
+        };
+        callback.cont(1, None);
+        1
+    }
+
+    fn get_raw(&self) -> *mut _cef_jsdialog_handler_t {
+        self.object as *mut _
+    }
+}
+
+/// Reloads the page of a browser whose renderer process terminated.
+struct ReloadOnCrash {
+    object: *mut RcImpl<_cef_request_handler_t, Self>,
+}
+
+wrap_rc!(ReloadOnCrash, WrapRequestHandler, _cef_request_handler_t);
+
+impl ImplRequestHandler for ReloadOnCrash {
+    fn on_render_process_terminated(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        _status: TerminationStatus,
+        _error_code: c_int,
+        _error_string: Option<&CefStringUtf16>,
+    ) {
+        if let Some(browser) = browser {
+            browser.reload();
+        }
+    }
+
+    fn get_raw(&self) -> *mut _cef_request_handler_t {
+        self.object as *mut _
+    }
+}
+
+/// Hides the cursor in every page loaded in a main frame.
+struct HideCursor {
+    object: *mut RcImpl<_cef_load_handler_t, Self>,
+}
+
+wrap_rc!(HideCursor, WrapLoadHandler, _cef_load_handler_t);
+
+impl ImplLoadHandler for HideCursor {
+    fn on_load_end(
+        &self,
+        _browser: Option<&impl ImplBrowser>,
+        frame: Option<&impl ImplFrame>,
+        _http_status_code: c_int,
+    ) {
+        let Some(frame) = frame.map(Frame::from_impl) else {
+            return;
+        };
+        if frame.is_main() {
+            let _ = frame.execute_script(HIDE_CURSOR_SCRIPT, "", 0);
+        }
+    }
+
+    fn get_raw(&self) -> *mut _cef_load_handler_t {
+        self.object as *mut _
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_kiosk_url_filter() {
+        let mut config = KioskConfig::new("https://kiosk.example.com/home");
+        config
+            .allowed_origins
+            .push("https://cdn.example.com".into());
+        let policy = config.url_filter();
+        assert!(policy.is_allowed("https://kiosk.example.com/home"));
+        assert!(policy.is_allowed("https://cdn.example.com/app.js"));
+        assert!(!policy.is_allowed("https://evil.com/popup"));
+        assert!(!policy.is_allowed("file:///etc/passwd"));
+        assert!(!policy.is_allowed("chrome://settings"));
+        assert!(policy.is_allowed("about:blank"));
+    }
+
+    #[test]
+    fn test_kiosk_settings() {
+        let (settings, browser_settings, client) =
+            kiosk(KioskConfig::new("https://kiosk.example.com/"));
+        assert_eq!(settings.command_line_args_disabled, 1);
+        assert_eq!(
+            browser_settings.javascript_close_windows,
+            State::from(cef_state_t::STATE_DISABLED)
+        );
+        assert_eq!(
+            KioskConfig::new("https://kiosk.example.com/")
+                .switches()
+                .argv(),
+            ["--kiosk", "--kiosk-printing"]
+        );
+        // The handlers of the preset can still be replaced.
+        let _client = client
+            .context_menu_handler(ContextMenuHandlerBuilder::new().build())
+            .build();
+    }
+}
@@ -0,0 +1,719 @@
+//! Print module
+//!
+//! Typed accessors for [PrintSettings], a [PrintSettingsBuilder] for the settings handed to the
+//! print dialog callback, and [SimplePrintToPdfHandler], which prints to a PDF file without a
+//! dialog.
+//!
+//! ## Units
+//!
+//! [PrintSettings] describes the paper in device units, the dots of the printer at
+//! [PrintSettings::get_dpi], e.g. a US Letter page at 300 DPI is 2550 by 3300 device units.
+//! [PdfPrintSettings] measures the paper in inches and its margins in points, 72 of which make an
+//! inch, like CSS `pt`. [device_units_to_points] and [points_to_device_units] convert between
+//! them.
+//!
+//! ## Page ranges
+//!
+//! [PageRange] is 0-based and inclusive, like the ranges of [PrintSettings]. The page ranges of
+//! [PdfPrintSettings] are a 1-based string instead, see [PrintSettingsBuilder::pdf_print_settings].
+
+use std::{fmt::Write, fs, future::Future, os::raw::c_int, path::PathBuf};
+
+use cef_sys::{_cef_print_handler_t, _cef_range_t};
+
+use crate::{
+    callback::pdf_print_callback,
+    future::channel,
+    print_settings_create,
+    rc::{from_impl, wrap_rc, RcImpl},
+    BrowserHost, CefString, CefStringUtf16, CefStringUtf8, ColorModel, DuplexMode, ImplBrowser,
+    ImplBrowserHost, ImplPrintDialogCallback, ImplPrintHandler, ImplPrintJobCallback,
+    ImplPrintSettings, PdfPrintSettings, PrintHandler, PrintSettings, Rect, Size,
+};
+
+from_impl!(PrintSettings, ImplPrintSettings);
+
+/// Points per inch, the unit of PDF and of CSS `pt`.
+pub const POINTS_PER_INCH: i32 = 72;
+
+/// Convert `value` device units at `dpi` to points.
+pub fn device_units_to_points(value: i32, dpi: i32) -> f64 {
+    if dpi <= 0 {
+        return 0.0;
+    }
+    f64::from(value) * f64::from(POINTS_PER_INCH) / f64::from(dpi)
+}
+
+/// Convert `points` to device units at `dpi`, rounded to the nearest one.
+pub fn points_to_device_units(points: f64, dpi: i32) -> i32 {
+    (points * f64::from(dpi) / f64::from(POINTS_PER_INCH)).round() as i32
+}
+
+/// Pages `from` through `to`, 0-based and inclusive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PageRange {
+    pub from: u32,
+    pub to: u32,
+}
+
+impl PageRange {
+    pub fn new(from: u32, to: u32) -> Self {
+        Self { from, to }
+    }
+
+    /// The range of the single page `page`.
+    pub fn page(page: u32) -> Self {
+        Self::new(page, page)
+    }
+}
+
+impl From<_cef_range_t> for PageRange {
+    fn from(value: _cef_range_t) -> Self {
+        Self::new(value.from, value.to)
+    }
+}
+
+impl From<PageRange> for _cef_range_t {
+    fn from(value: PageRange) -> Self {
+        Self {
+            from: value.from,
+            to: value.to,
+        }
+    }
+}
+
+/// The page ranges of [PdfPrintSettings], 1-based, e.g. `1-5,8` for the 0-based ranges 0 through
+/// 4 and 7.
+pub fn pdf_page_ranges(ranges: &[PageRange]) -> String {
+    let mut pages = String::new();
+    for range in ranges {
+        if !pages.is_empty() {
+            pages.push(',');
+        }
+        let (from, to) = (range.from + 1, range.to.max(range.from) + 1);
+        if from == to {
+            let _ = write!(pages, "{from}");
+        } else {
+            let _ = write!(pages, "{from}-{to}");
+        }
+    }
+    pages
+}
+
+/// The orientation of the paper.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Orientation {
+    #[default]
+    Portrait,
+    Landscape,
+}
+
+/// The paper and the area of it the printer can print on, in device units, see
+/// [PrintSettings::set_printable_area].
+#[derive(Clone)]
+pub struct PrintableArea {
+    /// The size of the paper.
+    pub paper_size: Size,
+    /// The printable area of the paper.
+    pub printable_area: Rect,
+    /// Whether the printable area has to be flipped for landscape, which some printers expect.
+    pub landscape_needs_flip: bool,
+}
+
+impl PrintSettings {
+    /// Build [PrintSettings], e.g. to continue the print dialog with.
+    pub fn builder() -> PrintSettingsBuilder {
+        PrintSettingsBuilder::default()
+    }
+
+    /// `false` if the settings are read-only, which the setters then ignore.
+    pub fn is_writable(&self) -> bool {
+        self.is_valid() != 0 && self.is_read_only() == 0
+    }
+
+    pub fn orientation(&self) -> Orientation {
+        if self.is_landscape() != 0 {
+            Orientation::Landscape
+        } else {
+            Orientation::Portrait
+        }
+    }
+
+    pub fn set_orientation(&self, orientation: Orientation) {
+        ImplPrintSettings::set_orientation(self, (orientation == Orientation::Landscape) as c_int);
+    }
+
+    /// The pages to print, empty for every page.
+    pub fn page_ranges(&self) -> Vec<PageRange> {
+        let mut count = self.get_page_ranges_count();
+        let mut ranges = vec![_cef_range_t { from: 0, to: 0 }; count];
+        // The generated wrapper only passes a single range, so call CEF with the array.
+        unsafe {
+            let raw = ImplPrintSettings::get_raw(self);
+            match (*raw).get_page_ranges {
+                Some(f) if count > 0 => f(raw, &mut count, ranges.as_mut_ptr()),
+                _ => count = 0,
+            }
+        }
+        ranges.truncate(count);
+        ranges.into_iter().map(PageRange::from).collect()
+    }
+
+    /// Print only the pages in `ranges`, or every page if it is empty.
+    pub fn set_page_ranges(&self, ranges: &[PageRange]) {
+        let ranges: Vec<_cef_range_t> = ranges.iter().copied().map(Into::into).collect();
+        // The generated wrapper only passes a single range, so call CEF with the array.
+        unsafe {
+            let raw = ImplPrintSettings::get_raw(self);
+            if let Some(f) = (*raw).set_page_ranges {
+                f(raw, ranges.len(), ranges.as_ptr());
+            }
+        }
+    }
+
+    /// Describe the paper of the printer, in device units at [PrintSettings::get_dpi].
+    pub fn set_printable_area(&self, area: &PrintableArea) {
+        self.set_printer_printable_area(
+            Some(&area.paper_size),
+            Some(&area.printable_area),
+            area.landscape_needs_flip as c_int,
+        );
+    }
+
+    pub fn device_name(&self) -> String {
+        self.get_device_name()
+            .map(|name| name.to_string())
+            .unwrap_or_default()
+    }
+
+    pub fn collate(&self) -> bool {
+        self.will_collate() != 0
+    }
+
+    pub fn selection_only(&self) -> bool {
+        self.is_selection_only() != 0
+    }
+}
+
+/// Builds [PrintSettings], see [PrintSettings::builder]. Settings which aren't set keep the
+/// values of the settings they are applied to.
+#[derive(Clone, Default)]
+pub struct PrintSettingsBuilder {
+    orientation: Option<Orientation>,
+    page_ranges: Option<Vec<PageRange>>,
+    printable_area: Option<PrintableArea>,
+    dpi: Option<i32>,
+    device_name: Option<String>,
+    duplex_mode: Option<DuplexMode>,
+    color_model: Option<ColorModel>,
+    copies: Option<i32>,
+    collate: Option<bool>,
+    selection_only: Option<bool>,
+}
+
+impl PrintSettingsBuilder {
+    pub fn orientation(mut self, orientation: Orientation) -> Self {
+        self.orientation = Some(orientation);
+        self
+    }
+
+    /// Print only the pages in `ranges`, or every page if it is empty.
+    pub fn page_ranges(mut self, ranges: impl IntoIterator<Item = PageRange>) -> Self {
+        self.page_ranges = Some(ranges.into_iter().collect());
+        self
+    }
+
+    /// The paper of the printer, in device units at [PrintSettingsBuilder::dpi].
+    pub fn printable_area(mut self, area: PrintableArea) -> Self {
+        self.printable_area = Some(area);
+        self
+    }
+
+    /// The dots per inch of the printer, which the device units of the printable area are in.
+    pub fn dpi(mut self, dpi: i32) -> Self {
+        self.dpi = Some(dpi);
+        self
+    }
+
+    pub fn device_name(mut self, name: &str) -> Self {
+        self.device_name = Some(name.to_string());
+        self
+    }
+
+    pub fn duplex_mode(mut self, mode: DuplexMode) -> Self {
+        self.duplex_mode = Some(mode);
+        self
+    }
+
+    pub fn color_model(mut self, model: ColorModel) -> Self {
+        self.color_model = Some(model);
+        self
+    }
+
+    pub fn copies(mut self, copies: i32) -> Self {
+        self.copies = Some(copies);
+        self
+    }
+
+    pub fn collate(mut self, collate: bool) -> Self {
+        self.collate = Some(collate);
+        self
+    }
+
+    /// Print only the selection, if there is one.
+    pub fn selection_only(mut self, selection_only: bool) -> Self {
+        self.selection_only = Some(selection_only);
+        self
+    }
+
+    /// Write the settings which were set to `settings`, unless they are read-only.
+    pub fn apply(&self, settings: &PrintSettings) {
+        if !settings.is_writable() {
+            return;
+        }
+        if let Some(orientation) = self.orientation {
+            settings.set_orientation(orientation);
+        }
+        if let Some(ranges) = &self.page_ranges {
+            settings.set_page_ranges(ranges);
+        }
+        // The printable area is in device units at this DPI, so set it first.
+        if let Some(dpi) = self.dpi {
+            settings.set_dpi(dpi);
+        }
+        if let Some(area) = &self.printable_area {
+            settings.set_printable_area(area);
+        }
+        if let Some(name) = &self.device_name {
+            let name = CefString::from(&CefStringUtf8::from(name.as_str()));
+            settings.set_device_name(Some(&name));
+        }
+        if let Some(mode) = self.duplex_mode {
+            settings.set_duplex_mode(mode);
+        }
+        if let Some(model) = self.color_model {
+            settings.set_color_model(model);
+        }
+        if let Some(copies) = self.copies {
+            settings.set_copies(copies);
+        }
+        if let Some(collate) = self.collate {
+            settings.set_collate(collate as c_int);
+        }
+        if let Some(selection_only) = self.selection_only {
+            settings.set_selection_only(selection_only as c_int);
+        }
+    }
+
+    /// Create [PrintSettings] with the settings which were set, `None` if CEF isn't initialized.
+    pub fn build(&self) -> Option<PrintSettings> {
+        let settings = print_settings_create()?;
+        self.apply(&settings);
+        Some(settings)
+    }
+
+    /// The paper size in device units at `device_units_per_inch`, if both the printable area and
+    /// the DPI were set.
+    pub fn paper_size(&self, device_units_per_inch: i32) -> Option<Size> {
+        let (area, dpi) = (
+            self.printable_area.as_ref()?,
+            self.dpi.filter(|dpi| *dpi > 0)?,
+        );
+        let scale = |value: i32| {
+            points_to_device_units(device_units_to_points(value, dpi), device_units_per_inch)
+        };
+        Some(Size {
+            width: scale(area.paper_size.width),
+            height: scale(area.paper_size.height),
+        })
+    }
+
+    /// The [PdfPrintSettings] for [BrowserHost::print_to_pdf] with the orientation, the page ranges
+    /// and the paper size which were set. The other settings only apply to printers.
+    pub fn pdf_print_settings(&self) -> PdfPrintSettings {
+        let mut settings = PdfPrintSettings {
+            landscape: (self.orientation == Some(Orientation::Landscape)) as c_int,
+            ..Default::default()
+        };
+        if let Some(ranges) = &self.page_ranges {
+            let ranges = pdf_page_ranges(ranges);
+            settings.page_ranges = CefString::from(&CefStringUtf8::from(ranges.as_str()));
+        }
+        if let Some(paper_size) = self.paper_size(POINTS_PER_INCH) {
+            let inches = |points: i32| f64::from(points) / f64::from(POINTS_PER_INCH);
+            settings.paper_width = inches(paper_size.width);
+            settings.paper_height = inches(paper_size.height);
+        }
+        settings
+    }
+}
+
+/// Prints to a PDF file with [PrintSettingsBuilder] settings instead of showing a print dialog.
+///
+/// [SimplePrintToPdfHandler::print_handler] answers `window.print()`, whose print dialog and
+/// print job CEF only hands to the print handler on Linux. [SimplePrintToPdfHandler::print_to_pdf]
+/// prints through [BrowserHost::print_to_pdf] on every platform. Both print the configured page
+/// ranges only.
+#[derive(Clone)]
+pub struct SimplePrintToPdfHandler {
+    path: PathBuf,
+    settings: PrintSettingsBuilder,
+}
+
+impl SimplePrintToPdfHandler {
+    /// Print to the PDF file `path` with `settings`.
+    pub fn new(path: impl Into<PathBuf>, settings: PrintSettingsBuilder) -> Self {
+        Self {
+            path: path.into(),
+            settings,
+        }
+    }
+
+    /// The handler for [crate::client::ClientBuilder::print_handler].
+    pub fn print_handler(&self) -> PrintHandler {
+        PrintHandler::new(PdfPrintHandler {
+            object: std::ptr::null_mut(),
+            handler: self.clone(),
+        })
+    }
+
+    /// Print the page of `host` to the PDF file, and resolve with whether it succeeded. Must be
+    /// called on the UI thread.
+    pub fn print_to_pdf(&self, host: &BrowserHost) -> impl Future<Output = bool> {
+        let (sender, completion) = channel();
+        let callback = pdf_print_callback(move |_, ok| sender.send(ok));
+        let path = self.path.to_string_lossy();
+        let path = CefString::from(&CefStringUtf8::from(path.as_ref()));
+        let settings = self.settings.pdf_print_settings();
+        ImplBrowserHost::print_to_pdf(host, Some(&path), Some(&settings), Some(&callback));
+        async move { completion.await.unwrap_or(false) }
+    }
+}
+
+struct PdfPrintHandler {
+    object: *mut RcImpl<_cef_print_handler_t, Self>,
+    handler: SimplePrintToPdfHandler,
+}
+
+wrap_rc!(
+    PdfPrintHandler,
+    WrapPrintHandler,
+    _cef_print_handler_t,
+    handler
+);
+
+impl ImplPrintHandler for PdfPrintHandler {
+    fn on_print_settings(
+        &self,
+        _browser: Option<&impl ImplBrowser>,
+        settings: Option<&impl ImplPrintSettings>,
+        _get_defaults: c_int,
+    ) {
+        if let Some(settings) = settings {
+            self.handler
+                .settings
+                .apply(&PrintSettings::from_impl(settings));
+        }
+    }
+
+    fn on_print_dialog(
+        &self,
+        _browser: Option<&impl ImplBrowser>,
+        _has_selection: c_int,
+        callback: Option<&impl ImplPrintDialogCallback>,
+    ) -> c_int {
+        let Some(callback) = callback else {
+            return 0;
+        };
+        match self.handler.settings.build() {
+            Some(settings) => callback.cont(Some(&settings)),
+            None => callback.cancel(),
+        }
+        1
+    }
+
+    fn on_print_job(
+        &self,
+        _browser: Option<&impl ImplBrowser>,
+        _document_name: Option<&CefStringUtf16>,
+        pdf_file_path: Option<&CefStringUtf16>,
+        callback: Option<&impl ImplPrintJobCallback>,
+    ) -> c_int {
+        let Some(pdf_file_path) = pdf_file_path else {
+            return 0;
+        };
+        if fs::copy(pdf_file_path.to_string(), &self.handler.path).is_err() {
+            return 0;
+        }
+        if let Some(callback) = callback {
+            callback.cont();
+        }
+        1
+    }
+
+    fn get_pdf_paper_size(
+        &self,
+        _browser: Option<&impl ImplBrowser>,
+        device_units_per_inch: c_int,
+    ) -> Size {
+        // An empty size leaves the paper size to CEF.
+        self.handler
+            .settings
+            .paper_size(device_units_per_inch)
+            .unwrap_or_default()
+    }
+
+    fn get_raw(&self) -> *mut _cef_print_handler_t {
+        self.object as *mut _cef_print_handler_t
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, ptr};
+
+    use cef_sys::{_cef_print_settings_t, cef_color_model_t, cef_duplex_mode_t};
+
+    use super::*;
+
+    thread_local! {
+        /// The page ranges of the [FakeSettings] on this thread, as CEF stores them.
+        static RANGES: RefCell<Vec<_cef_range_t>> = const { RefCell::new(Vec::new()) };
+    }
+
+    #[derive(Default)]
+    struct Values {
+        landscape: c_int,
+        dpi: c_int,
+        copies: c_int,
+        collate: c_int,
+        duplex_mode: Option<DuplexMode>,
+        color_model: Option<ColorModel>,
+        paper_size: Option<(c_int, c_int)>,
+    }
+
+    struct FakeSettings {
+        object: *mut RcImpl<_cef_print_settings_t, Self>,
+        values: std::sync::Arc<std::sync::Mutex<Values>>,
+    }
+
+    wrap_rc!(
+        FakeSettings,
+        WrapPrintSettings,
+        _cef_print_settings_t,
+        values
+    );
+
+    impl ImplPrintSettings for FakeSettings {
+        fn is_valid(&self) -> c_int {
+            1
+        }
+
+        fn set_orientation(&self, landscape: c_int) {
+            self.values.lock().unwrap().landscape = landscape;
+        }
+
+        fn is_landscape(&self) -> c_int {
+            self.values.lock().unwrap().landscape
+        }
+
+        fn set_printer_printable_area(
+            &self,
+            physical_size_device_units: Option<&Size>,
+            _printable_area_device_units: Option<&Rect>,
+            _landscape_needs_flip: c_int,
+        ) {
+            self.values.lock().unwrap().paper_size =
+                physical_size_device_units.map(|size| (size.width, size.height));
+        }
+
+        fn set_dpi(&self, dpi: c_int) {
+            self.values.lock().unwrap().dpi = dpi;
+        }
+
+        fn get_page_ranges_count(&self) -> usize {
+            RANGES.with_borrow(Vec::len)
+        }
+
+        fn set_collate(&self, collate: c_int) {
+            self.values.lock().unwrap().collate = collate;
+        }
+
+        fn set_color_model(&self, model: ColorModel) {
+            self.values.lock().unwrap().color_model = Some(model);
+        }
+
+        fn set_copies(&self, copies: c_int) {
+            self.values.lock().unwrap().copies = copies;
+        }
+
+        fn set_duplex_mode(&self, mode: DuplexMode) {
+            self.values.lock().unwrap().duplex_mode = Some(mode);
+        }
+
+        fn get_raw(&self) -> *mut _cef_print_settings_t {
+            self.object as *mut _
+        }
+    }
+
+    extern "C" fn set_page_ranges(
+        _self_: *mut _cef_print_settings_t,
+        count: usize,
+        ranges: *const _cef_range_t,
+    ) {
+        let ranges = if count == 0 {
+            vec![]
+        } else {
+            unsafe { std::slice::from_raw_parts(ranges, count) }.to_vec()
+        };
+        RANGES.set(ranges);
+    }
+
+    extern "C" fn get_page_ranges(
+        _self_: *mut _cef_print_settings_t,
+        count: *mut usize,
+        ranges: *mut _cef_range_t,
+    ) {
+        RANGES.with_borrow(|stored| unsafe {
+            let copied = stored.len().min(*count);
+            ptr::copy_nonoverlapping(stored.as_ptr(), ranges, copied);
+            *count = copied;
+        });
+    }
+
+    fn fake_settings() -> (PrintSettings, std::sync::Arc<std::sync::Mutex<Values>>) {
+        let values = std::sync::Arc::new(std::sync::Mutex::new(Values::default()));
+        let settings = PrintSettings::new(FakeSettings {
+            object: ptr::null_mut(),
+            values: values.clone(),
+        });
+        // The generated wrappers pass a single range, so fake the array functions of CEF.
+        unsafe {
+            let raw = ImplPrintSettings::get_raw(&settings);
+            (*raw).set_page_ranges = Some(set_page_ranges);
+            (*raw).get_page_ranges = Some(get_page_ranges);
+        }
+        (settings, values)
+    }
+
+    #[test]
+    fn test_page_ranges_roundtrip() {
+        let (settings, _) = fake_settings();
+        let ranges = [
+            PageRange::new(0, 4),
+            PageRange::page(7),
+            PageRange::new(10, 12),
+        ];
+        settings.set_page_ranges(&ranges);
+        RANGES.with_borrow(|stored| {
+            let stored: Vec<_> = stored.iter().map(|range| (range.from, range.to)).collect();
+            assert_eq!(stored, [(0, 4), (7, 7), (10, 12)]);
+        });
+        assert_eq!(settings.page_ranges(), ranges);
+
+        settings.set_page_ranges(&[]);
+        assert!(settings.page_ranges().is_empty());
+    }
+
+    #[test]
+    fn test_pdf_page_ranges() {
+        let ranges = [
+            PageRange::new(0, 4),
+            PageRange::page(7),
+            PageRange::new(10, 12),
+        ];
+        assert_eq!(pdf_page_ranges(&ranges), "1-5,8,11-13");
+        assert_eq!(pdf_page_ranges(&[]), "");
+    }
+
+    #[test]
+    fn test_print_enums() {
+        let cases = [
+            (cef_duplex_mode_t::DUPLEX_MODE_UNKNOWN, DuplexMode::Unknown),
+            (cef_duplex_mode_t::DUPLEX_MODE_SIMPLEX, DuplexMode::Simplex),
+            (
+                cef_duplex_mode_t::DUPLEX_MODE_LONG_EDGE,
+                DuplexMode::LongEdge,
+            ),
+            (
+                cef_duplex_mode_t::DUPLEX_MODE_SHORT_EDGE,
+                DuplexMode::ShortEdge,
+            ),
+        ];
+        for (raw, mode) in cases {
+            assert_eq!(DuplexMode::from(raw), mode);
+            let back: cef_duplex_mode_t = mode.into();
+            assert_eq!(back, raw);
+        }
+
+        let cases = [
+            (cef_color_model_t::COLOR_MODEL_GRAY, ColorModel::Gray),
+            (cef_color_model_t::COLOR_MODEL_COLOR, ColorModel::Color),
+            (cef_color_model_t::COLOR_MODEL_CMYK, ColorModel::Cmyk),
+            (
+                cef_color_model_t::COLOR_MODEL_GRAYSCALE,
+                ColorModel::Grayscale,
+            ),
+        ];
+        for (raw, model) in cases {
+            assert_eq!(ColorModel::from(raw), model);
+            let back: cef_color_model_t = model.into();
+            assert_eq!(back, raw);
+        }
+    }
+
+    #[test]
+    fn test_builder_apply() {
+        let (settings, values) = fake_settings();
+        let letter = PrintableArea {
+            paper_size: Size {
+                width: 2550,
+                height: 3300,
+            },
+            printable_area: Rect {
+                x: 75,
+                y: 75,
+                width: 2400,
+                height: 3150,
+            },
+            landscape_needs_flip: false,
+        };
+        let builder = PrintSettings::builder()
+            .orientation(Orientation::Landscape)
+            .page_ranges([PageRange::new(1, 2)])
+            .dpi(300)
+            .printable_area(letter)
+            .duplex_mode(DuplexMode::LongEdge)
+            .color_model(ColorModel::Gray)
+            .copies(2)
+            .collate(true);
+        builder.apply(&settings);
+
+        assert_eq!(settings.orientation(), Orientation::Landscape);
+        assert_eq!(settings.page_ranges(), [PageRange::new(1, 2)]);
+        let values = values.lock().unwrap();
+        assert_eq!((values.dpi, values.copies, values.collate), (300, 2, 1));
+        assert_eq!(values.duplex_mode, Some(DuplexMode::LongEdge));
+        assert_eq!(values.color_model, Some(ColorModel::Gray));
+        assert_eq!(values.paper_size, Some((2550, 3300)));
+
+        // 8.5 by 11 inches.
+        let paper_size = builder.paper_size(POINTS_PER_INCH).unwrap();
+        assert_eq!((paper_size.width, paper_size.height), (612, 792));
+        let pdf = builder.pdf_print_settings();
+        assert_eq!(pdf.landscape, 1);
+        assert_eq!(pdf.page_ranges.to_string(), "2-3");
+        assert_eq!((pdf.paper_width, pdf.paper_height), (8.5, 11.0));
+    }
+
+    #[test]
+    fn test_unit_conversion() {
+        assert_eq!(device_units_to_points(300, 300), 72.0);
+        assert_eq!(points_to_device_units(72.0, 600), 600);
+        assert_eq!(
+            points_to_device_units(device_units_to_points(2550, 300), 300),
+            2550
+        );
+        assert_eq!(device_units_to_points(100, 0), 0.0);
+    }
+}
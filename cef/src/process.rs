@@ -0,0 +1,193 @@
+//! Process module
+//!
+//! Typed metrics of the browser processes, e.g. for a task manager panel. These bindings include
+//! [TaskInfo], but not the CEF task manager which reports it, so the metrics come from the
+//! DevTools `Performance` and `SystemInfo` domains for now. [ChildProcessInfo] converts from
+//! [TaskInfo], so the types stay the same when the native API is available.
+
+use std::{future::Future, time::Duration};
+
+use cef_sys::cef_task_type_t;
+
+use crate::{
+    devtools::{json, DevToolsError},
+    Browser, BrowserHost, ImplBrowser, TaskInfo, TaskType,
+};
+
+/// Memory metrics of the renderer of a browser, from `Performance.getMetrics`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MemoryMetrics {
+    /// Bytes used by the JavaScript heap.
+    pub js_heap_used_bytes: u64,
+    /// Bytes allocated for the JavaScript heap.
+    pub js_heap_total_bytes: u64,
+    /// Number of documents, including those of iframes.
+    pub documents: u64,
+    /// Number of DOM nodes.
+    pub nodes: u64,
+}
+
+impl MemoryMetrics {
+    /// The metrics of a `Performance.getMetrics` result.
+    fn from_metrics(result: &[u8]) -> Result<Self, DevToolsError> {
+        let mut metrics = Self::default();
+        let result = json::parse(result)?;
+        for metric in json::elements(&result, &["metrics"])? {
+            let value = json::number(&metric, &["value"])? as u64;
+            match json::string(&metric, &["name"])?.as_str() {
+                "JSHeapUsedSize" => metrics.js_heap_used_bytes = value,
+                "JSHeapTotalSize" => metrics.js_heap_total_bytes = value,
+                "Documents" => metrics.documents = value,
+                "Nodes" => metrics.nodes = value,
+                _ => {}
+            }
+        }
+        Ok(metrics)
+    }
+}
+
+impl Browser {
+    /// Resolve with the [MemoryMetrics] of the renderer of the browser.
+    ///
+    /// The `Performance` domain is enabled for the request and disabled again afterwards. Must be
+    /// called on the UI thread, and fails with [DevToolsError::Unavailable] before the first
+    /// navigation of the browser.
+    pub fn renderer_memory_usage(
+        &self,
+    ) -> impl Future<Output = Result<MemoryMetrics, DevToolsError>> {
+        // The methods are sent right away, and DevTools runs them in order.
+        let methods = self.get_host().map(|host| {
+            (
+                host.execute_dev_tools("Performance.enable", None),
+                host.execute_dev_tools("Performance.getMetrics", None),
+                host.execute_dev_tools("Performance.disable", None),
+            )
+        });
+        async move {
+            let (enabled, metrics, disabled) = methods.ok_or(DevToolsError::Unavailable)?;
+            enabled.await?;
+            let metrics = metrics.await;
+            let _ = disabled.await;
+            MemoryMetrics::from_metrics(&metrics?)
+        }
+    }
+}
+
+/// A process of the browser, as a task manager shows it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChildProcessInfo {
+    /// The task id of the CEF task manager, or the process id reported by DevTools.
+    pub id: i64,
+    pub task_type: TaskType,
+    pub title: String,
+    /// CPU usage in percent of one processor, if known.
+    pub cpu_usage: Option<f64>,
+    /// CPU time used since the process started, if known.
+    pub cpu_time: Option<Duration>,
+    pub memory_bytes: Option<u64>,
+    pub gpu_memory_bytes: Option<u64>,
+}
+
+impl From<&TaskInfo> for ChildProcessInfo {
+    fn from(info: &TaskInfo) -> Self {
+        Self {
+            id: info.id,
+            task_type: info.type_,
+            title: info.title.to_string(),
+            cpu_usage: Some(info.cpu_usage),
+            cpu_time: None,
+            memory_bytes: u64::try_from(info.memory).ok(),
+            gpu_memory_bytes: u64::try_from(info.gpu_memory).ok(),
+        }
+    }
+}
+
+/// Resolve with the processes of the browser, from `SystemInfo.getProcessInfo`.
+///
+/// DevTools only reports the process type, id and CPU time. Must be called on the UI thread, and
+/// fails with [DevToolsError::Unavailable] before the first navigation of the browser, or with
+/// [DevToolsError::Method] if the DevTools target of the browser doesn't expose `SystemInfo`.
+pub fn child_processes(
+    host: &BrowserHost,
+) -> impl Future<Output = Result<Vec<ChildProcessInfo>, DevToolsError>> {
+    let result = host.execute_dev_tools("SystemInfo.getProcessInfo", None);
+    async move { process_info(&result.await?) }
+}
+
+/// The processes of a `SystemInfo.getProcessInfo` result.
+fn process_info(result: &[u8]) -> Result<Vec<ChildProcessInfo>, DevToolsError> {
+    let result = json::parse(result)?;
+    json::elements(&result, &["processInfo"])?
+        .iter()
+        .map(|process| {
+            let process_type = json::string(process, &["type"])?;
+            let cpu_time = json::number(process, &["cpuTime"])?;
+            Ok(ChildProcessInfo {
+                id: json::number(process, &["id"])? as i64,
+                task_type: task_type(&process_type),
+                title: process_type,
+                cpu_usage: None,
+                cpu_time: Duration::try_from_secs_f64(cpu_time).ok(),
+                memory_bytes: None,
+                gpu_memory_bytes: None,
+            })
+        })
+        .collect()
+}
+
+/// The [TaskType] of a DevTools process type.
+fn task_type(process_type: &str) -> TaskType {
+    match process_type.to_ascii_lowercase().as_str() {
+        "browser" => cef_task_type_t::CEF_TASK_TYPE_BROWSER,
+        "gpu" => cef_task_type_t::CEF_TASK_TYPE_GPU,
+        "zygote" => cef_task_type_t::CEF_TASK_TYPE_ZYGOTE,
+        "utility" => cef_task_type_t::CEF_TASK_TYPE_UTILITY,
+        "renderer" => cef_task_type_t::CEF_TASK_TYPE_RENDERER,
+        "extension" => cef_task_type_t::CEF_TASK_TYPE_EXTENSION,
+        "plugin" | "ppapi" => cef_task_type_t::CEF_TASK_TYPE_PLUGIN,
+        "sandbox helper" => cef_task_type_t::CEF_TASK_TYPE_SANDBOX_HELPER,
+        _ => cef_task_type_t::CEF_TASK_TYPE_UNKNOWN,
+    }
+    .into()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_memory_metrics() {
+        let result = br#"{"metrics": [{"name": "Timestamp", "value": 1234.5},
+            {"name": "Documents", "value": 3}, {"name": "Nodes", "value": 142},
+            {"name": "JSHeapUsedSize", "value": 1843200}, {"name": "JSHeapTotalSize", "value": 3145728}]}"#;
+        assert_eq!(
+            MemoryMetrics::from_metrics(result).ok(),
+            Some(MemoryMetrics {
+                js_heap_used_bytes: 1843200,
+                js_heap_total_bytes: 3145728,
+                documents: 3,
+                nodes: 142,
+            })
+        );
+        assert!(MemoryMetrics::from_metrics(b"{}").is_err());
+    }
+
+    #[test]
+    fn test_process_info() {
+        let result = br#"{"processInfo": [{"type": "browser", "id": 4242, "cpuTime": 1.5},
+            {"type": "GPU", "id": 4243, "cpuTime": 0}]}"#;
+        let processes = process_info(result).unwrap();
+        assert_eq!(processes.len(), 2);
+        assert_eq!(processes[0].id, 4242);
+        assert_eq!(
+            processes[0].task_type,
+            cef_task_type_t::CEF_TASK_TYPE_BROWSER.into()
+        );
+        assert_eq!(processes[0].cpu_time, Some(Duration::from_millis(1500)));
+        assert_eq!(
+            processes[1].task_type,
+            cef_task_type_t::CEF_TASK_TYPE_GPU.into()
+        );
+        assert_eq!(processes[1].title, "GPU");
+    }
+}
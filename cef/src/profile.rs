@@ -0,0 +1,254 @@
+//! Profile module
+//!
+//! [ProfileManager] keeps named profiles, each a [RequestContext] with its own cache directory
+//! under the root cache path of [Settings]. CEF aborts if the cache path of a request context
+//! is outside the root cache path, so the manager picks the paths and sets the root itself.
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use cef_sys::cef_thread_id_t;
+
+use crate::{
+    context, rc::Rc, request_context_create_context, thread, CefString, CefStringUtf8,
+    RequestContext, RequestContextHandler, RequestContextSettings, Settings,
+};
+
+/// The directory under the root cache path which holds the profiles, keeping them apart from the
+/// files CEF stores in the root.
+pub const PROFILES_DIR: &str = "Profiles";
+
+/// Why a [ProfileManager] operation failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProfileError {
+    /// The root cache path must be absolute.
+    RelativeRoot(PathBuf),
+    /// Profile names may only contain ASCII letters, digits, `-`, `_` and `.`, and can't start
+    /// with `.`.
+    InvalidName(String),
+    /// The cache path of the settings is not the directory of the profile.
+    CachePathMismatch { cache: PathBuf, expected: PathBuf },
+    /// A request context of the profile is still alive.
+    InUse(String),
+    /// There is no profile with this name.
+    NotFound(String),
+    /// CEF failed to create the request context, see its log for details.
+    CreateFailed(String),
+    /// Reading or writing the profile directory failed.
+    Io { path: PathBuf, kind: io::ErrorKind },
+}
+
+impl fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RelativeRoot(root) => {
+                write!(f, "root cache path {} is relative", root.display())
+            }
+            Self::InvalidName(name) => write!(f, "{name:?} is not a valid profile name"),
+            Self::CachePathMismatch { cache, expected } => write!(
+                f,
+                "cache path {} is not the profile directory {}",
+                cache.display(),
+                expected.display()
+            ),
+            Self::InUse(name) => write!(f, "profile {name:?} is in use"),
+            Self::NotFound(name) => write!(f, "profile {name:?} doesn't exist"),
+            Self::CreateFailed(name) => {
+                write!(f, "CEF failed to create the request context of {name:?}")
+            }
+            Self::Io { path, kind } => write!(f, "{}: {kind}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for ProfileError {}
+
+/// Named profiles with separate cookies, storage and caches.
+///
+/// Create the manager before initializing CEF, and pass the [Settings] to
+/// [ProfileManager::configure]. Profiles are then opened on the UI thread with
+/// [ProfileManager::create_or_open], and their [RequestContext] passed to the browser creation
+/// functions.
+pub struct ProfileManager {
+    root: PathBuf,
+    contexts: RefCell<HashMap<String, RequestContext>>,
+}
+
+impl ProfileManager {
+    /// Manage the profiles under the absolute `root_cache_path`, creating it if it is missing.
+    pub fn new(root_cache_path: impl Into<PathBuf>) -> Result<Self, ProfileError> {
+        let root = root_cache_path.into();
+        if root.is_relative() {
+            return Err(ProfileError::RelativeRoot(root));
+        }
+        let profiles = root.join(PROFILES_DIR);
+        fs::create_dir_all(&profiles).map_err(|err| io_error(&profiles, err))?;
+        Ok(Self {
+            root,
+            contexts: Default::default(),
+        })
+    }
+
+    /// The root cache path.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Set the root cache path of `settings`, which CEF needs to accept the profile cache paths.
+    pub fn configure(&self, settings: &mut Settings) {
+        settings.root_cache_path = cef_path(&self.root);
+    }
+
+    /// The cache directory of the profile `name`.
+    pub fn path(&self, name: &str) -> Result<PathBuf, ProfileError> {
+        validate_name(name)?;
+        Ok(self.root.join(PROFILES_DIR).join(name))
+    }
+
+    /// The request context of the profile `name`, created with `settings` if it isn't open yet.
+    ///
+    /// The cache path of `settings` is set to [ProfileManager::path], and must be empty or
+    /// already equal to it. Must be called on the UI thread, and panics otherwise.
+    pub fn create_or_open(
+        &self,
+        name: &str,
+        mut settings: RequestContextSettings,
+    ) -> Result<RequestContext, ProfileError> {
+        thread::assert_on_thread(cef_thread_id_t::TID_UI);
+        let path = self.path(name)?;
+        if let Some(context) = self.contexts.borrow().get(name) {
+            return Ok(context.clone());
+        }
+
+        let cache = PathBuf::from(settings.cache_path.to_string());
+        if !cache.as_os_str().is_empty() && cache != path {
+            return Err(ProfileError::CachePathMismatch {
+                cache,
+                expected: path,
+            });
+        }
+        settings.cache_path = cef_path(&path);
+
+        let context =
+            request_context_create_context(Some(&settings), Option::<&RequestContextHandler>::None)
+                .ok_or_else(|| ProfileError::CreateFailed(name.to_string()))?;
+        context::register_request_context(&context);
+        self.contexts
+            .borrow_mut()
+            .insert(name.to_string(), context.clone());
+        Ok(context)
+    }
+
+    /// The names of the profiles on disk, sorted.
+    pub fn list(&self) -> Vec<String> {
+        let Ok(entries) = fs::read_dir(self.root.join(PROFILES_DIR)) else {
+            return vec![];
+        };
+        let mut names: Vec<_> = entries
+            .flatten()
+            .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_dir()))
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|name| validate_name(name).is_ok())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Delete the profile `name` and its cache directory. Fails with [ProfileError::InUse] while
+    /// its request context is referenced outside of the manager, e.g. by a browser.
+    pub fn delete(&self, name: &str) -> Result<(), ProfileError> {
+        let path = self.path(name)?;
+        let mut contexts = self.contexts.borrow_mut();
+        if contexts
+            .get(name)
+            .is_some_and(|context| !context.has_one_ref())
+        {
+            return Err(ProfileError::InUse(name.to_string()));
+        }
+        if !path.is_dir() {
+            return Err(ProfileError::NotFound(name.to_string()));
+        }
+        contexts.remove(name);
+        fs::remove_dir_all(&path).map_err(|err| io_error(&path, err))
+    }
+}
+
+fn validate_name(name: &str) -> Result<(), ProfileError> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(ProfileError::InvalidName(name.to_string()))
+    }
+}
+
+fn io_error(path: &Path, err: io::Error) -> ProfileError {
+    ProfileError::Io {
+        path: path.to_path_buf(),
+        kind: err.kind(),
+    }
+}
+
+fn cef_path(path: &Path) -> CefString {
+    CefString::from(&CefStringUtf8::from(path.to_string_lossy().as_ref()))
+}
+
+#[cfg(test)]
+mod test {
+    use std::env;
+
+    use super::*;
+
+    fn manager(test: &str) -> ProfileManager {
+        let root = env::temp_dir().join(format!("cef-rs-profile-{test}"));
+        let _ = fs::remove_dir_all(&root);
+        ProfileManager::new(root).unwrap()
+    }
+
+    #[test]
+    fn test_relative_root() {
+        assert_eq!(
+            ProfileManager::new("cache").err(),
+            Some(ProfileError::RelativeRoot("cache".into()))
+        );
+    }
+
+    #[test]
+    fn test_path() {
+        let manager = manager("path");
+        let path = manager.path("work-1").unwrap();
+        assert!(path.starts_with(manager.root()));
+        assert!(path.ends_with("Profiles/work-1"));
+        for name in ["", ".hidden", "..", "a/b", "a\\b", "ü"] {
+            assert_eq!(
+                manager.path(name),
+                Err(ProfileError::InvalidName(name.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_list_and_delete() {
+        let manager = manager("list");
+        for name in ["work", "personal", ".tmp"] {
+            fs::create_dir_all(manager.root().join(PROFILES_DIR).join(name)).unwrap();
+        }
+        fs::write(manager.root().join(PROFILES_DIR).join("file"), b"").unwrap();
+        assert_eq!(manager.list(), ["personal", "work"]);
+
+        assert_eq!(manager.delete("work"), Ok(()));
+        assert_eq!(
+            manager.delete("work"),
+            Err(ProfileError::NotFound("work".into()))
+        );
+        assert_eq!(manager.list(), ["personal"]);
+    }
+}
@@ -0,0 +1,382 @@
+//! Pump module
+//!
+//! With [crate::Settings::external_message_pump], CEF doesn't run its work on a timer of its own
+//! but asks the application for it with
+//! [crate::ImplBrowserProcessHandler::on_schedule_message_pump_work], and the application calls
+//! [crate::do_message_loop_work] from its event loop. [ExternalPump] keeps track of these
+//! requests: pass [ExternalPump::browser_process_handler] to the app, wake the event loop with
+//! [ExternalPump::on_schedule], and call [ExternalPump::pump_if_due] from it.
+//!
+//! An event loop which doesn't come around often enough starves CEF, which shows as sluggish
+//! pages and late timers. [ExternalPump::stats] tells how late the work ran, and
+//! [ExternalPump::warn_after] reports late work as a [DiagnosticCategory::SlowMessagePump].
+//!
+//! ```ignore
+//! let pump = ExternalPump::new()
+//!     .warn_after(Duration::from_millis(50))
+//!     .on_schedule(move |_delay| proxy.send_event(UserEvent::Pump).unwrap_or_default());
+//! let app = AppBuilder::new()
+//!     .browser_process_handler(pump.browser_process_handler())
+//!     .build();
+//!
+//! // In the event loop:
+//! pump.pump_if_due();
+//! if let Some(deadline) = pump.next_deadline() {
+//!     event_loop.set_control_flow(ControlFlow::WaitUntil(deadline));
+//! }
+//! ```
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::{Arc, Mutex, MutexGuard},
+    thread,
+    time::{Duration, Instant},
+};
+
+use cef_sys::_cef_browser_process_handler_t;
+
+use crate::{
+    diagnostics::{self, DiagnosticCategory},
+    do_message_loop_work,
+    rc::{wrap_rc, RcImpl},
+    BrowserProcessHandler, ImplBrowserProcessHandler,
+};
+
+/// How many of the latest delays [PumpStats::histogram] covers.
+pub const HISTOGRAM_WINDOW: usize = 1024;
+
+/// The upper bounds of the buckets of a [DelayHistogram], the last bucket has none.
+pub const HISTOGRAM_BOUNDS: [Duration; 5] = [
+    Duration::from_millis(1),
+    Duration::from_millis(4),
+    Duration::from_millis(16),
+    Duration::from_millis(64),
+    Duration::from_millis(256),
+];
+
+/// How long [ExternalPump::force_pump_for] waits between two calls of
+/// [crate::do_message_loop_work].
+const FORCED_PUMP_INTERVAL: Duration = Duration::from_millis(5);
+
+/// How often work ran how late, in the buckets of [HISTOGRAM_BOUNDS].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DelayHistogram {
+    /// `buckets[i]` counts the delays below `HISTOGRAM_BOUNDS[i]` and at least the bound before,
+    /// the last bucket those of at least 256ms.
+    pub buckets: [u64; HISTOGRAM_BOUNDS.len() + 1],
+}
+
+impl DelayHistogram {
+    fn bucket(delay: Duration) -> usize {
+        HISTOGRAM_BOUNDS
+            .iter()
+            .position(|bound| delay < *bound)
+            .unwrap_or(HISTOGRAM_BOUNDS.len())
+    }
+
+    /// The number of delays in the histogram.
+    pub fn count(&self) -> u64 {
+        self.buckets.iter().sum()
+    }
+}
+
+/// The statistics of an [ExternalPump] since it was created or [ExternalPump::reset_stats].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PumpStats {
+    /// How often CEF asked for work.
+    pub scheduled: u64,
+    /// How often the pump called [crate::do_message_loop_work], including forced calls.
+    pub executed: u64,
+    /// The longest time work ran after CEF wanted it to, i.e. after the delay it asked for.
+    pub max_delay: Duration,
+    /// The delays of the latest [HISTOGRAM_WINDOW] requests which ran.
+    pub histogram: DelayHistogram,
+}
+
+type ScheduleCallback = Arc<dyn Fn(Duration) + Send + Sync>;
+
+/// Schedules [crate::do_message_loop_work] for an external message pump, see the
+/// [module documentation](self). Clones share the schedule and the statistics.
+#[derive(Clone, Default)]
+pub struct ExternalPump {
+    state: Arc<Mutex<PumpState>>,
+    on_schedule: Option<ScheduleCallback>,
+}
+
+impl ExternalPump {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Report work which runs more than `threshold` late as a
+    /// [DiagnosticCategory::SlowMessagePump].
+    pub fn warn_after(self, threshold: Duration) -> Self {
+        self.lock().warn_after = Some(threshold);
+        self
+    }
+
+    /// Call `callback` with the delay CEF asked for whenever it schedules work, on the thread
+    /// which scheduled it, e.g. to wake the event loop.
+    pub fn on_schedule(mut self, callback: impl Fn(Duration) + Send + Sync + 'static) -> Self {
+        self.on_schedule = Some(Arc::new(callback));
+        self
+    }
+
+    /// The handler which takes the requests of CEF, for
+    /// [crate::app::AppBuilder::browser_process_handler].
+    pub fn browser_process_handler(&self) -> BrowserProcessHandler {
+        BrowserProcessHandler::new(PumpHandler {
+            object: std::ptr::null_mut(),
+            pump: self.clone(),
+        })
+    }
+
+    /// Take a request of CEF to do work after `delay_ms`, from a browser process handler of the
+    /// application's own.
+    pub fn schedule(&self, delay_ms: i64) {
+        let delay = Duration::from_millis(delay_ms.max(0) as u64);
+        self.lock().schedule(Instant::now(), delay);
+        if let Some(on_schedule) = &self.on_schedule {
+            on_schedule(delay);
+        }
+    }
+
+    /// When [ExternalPump::pump_if_due] should be called next, `None` if CEF asked for no work.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.lock().deadline
+    }
+
+    /// Do the work CEF asked for if it is due, and return whether it was. Call it on the UI
+    /// thread.
+    pub fn pump_if_due(&self) -> bool {
+        let due = self
+            .lock()
+            .deadline
+            .is_some_and(|deadline| deadline <= Instant::now());
+        if due {
+            self.pump();
+        }
+        due
+    }
+
+    /// Do work now, whether or not CEF asked for it. Call it on the UI thread.
+    pub fn pump(&self) {
+        let late = self.lock().execute(Instant::now());
+        if let Some((delay, threshold)) = late {
+            diagnostics::report(
+                DiagnosticCategory::SlowMessagePump,
+                format_args!("message pump work ran {delay:?} late, more than {threshold:?}"),
+            );
+        }
+        // CEF may schedule more work from inside, so the state isn't locked.
+        do_message_loop_work();
+    }
+
+    /// Do work every few milliseconds for `duration`, whether or not CEF asked for it, e.g. to
+    /// catch up after a modal dialog blocked the event loop. Call it on the UI thread.
+    pub fn force_pump_for(&self, duration: Duration) {
+        let end = Instant::now() + duration;
+        loop {
+            self.pump();
+            let now = Instant::now();
+            if now >= end {
+                break;
+            }
+            thread::sleep(FORCED_PUMP_INTERVAL.min(end - now));
+        }
+    }
+
+    pub fn stats(&self) -> PumpStats {
+        self.lock().stats()
+    }
+
+    /// Start the statistics over, e.g. after the application was suspended.
+    pub fn reset_stats(&self) {
+        self.lock().reset_stats();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, PumpState> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl fmt::Debug for ExternalPump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExternalPump")
+            .field("deadline", &self.next_deadline())
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+/// The schedule and statistics of an [ExternalPump], on a clock of the caller.
+#[derive(Default)]
+struct PumpState {
+    /// When CEF wants the pending work to run.
+    deadline: Option<Instant>,
+    warn_after: Option<Duration>,
+    scheduled: u64,
+    executed: u64,
+    max_delay: Duration,
+    /// The buckets of the latest delays, oldest first.
+    window: VecDeque<usize>,
+    histogram: DelayHistogram,
+}
+
+impl PumpState {
+    /// CEF asked for work at `now + delay`. Earlier pending work isn't postponed.
+    fn schedule(&mut self, now: Instant, delay: Duration) {
+        self.scheduled += 1;
+        let deadline = now + delay;
+        self.deadline = Some(match self.deadline {
+            Some(pending) => pending.min(deadline),
+            None => deadline,
+        });
+    }
+
+    /// Record work at `now`, and return how late the pending work ran and the threshold if it
+    /// was too late.
+    fn execute(&mut self, now: Instant) -> Option<(Duration, Duration)> {
+        self.executed += 1;
+        let delay = now.saturating_duration_since(self.deadline.take()?);
+        self.max_delay = self.max_delay.max(delay);
+        let bucket = DelayHistogram::bucket(delay);
+        self.histogram.buckets[bucket] += 1;
+        self.window.push_back(bucket);
+        if self.window.len() > HISTOGRAM_WINDOW {
+            if let Some(oldest) = self.window.pop_front() {
+                self.histogram.buckets[oldest] -= 1;
+            }
+        }
+        self.warn_after
+            .filter(|threshold| delay > *threshold)
+            .map(|threshold| (delay, threshold))
+    }
+
+    fn stats(&self) -> PumpStats {
+        PumpStats {
+            scheduled: self.scheduled,
+            executed: self.executed,
+            max_delay: self.max_delay,
+            histogram: self.histogram,
+        }
+    }
+
+    fn reset_stats(&mut self) {
+        *self = Self {
+            deadline: self.deadline,
+            warn_after: self.warn_after,
+            ..Default::default()
+        };
+    }
+}
+
+struct PumpHandler {
+    object: *mut RcImpl<_cef_browser_process_handler_t, Self>,
+    pump: ExternalPump,
+}
+
+wrap_rc!(
+    PumpHandler,
+    WrapBrowserProcessHandler,
+    _cef_browser_process_handler_t,
+    pump
+);
+
+impl ImplBrowserProcessHandler for PumpHandler {
+    fn on_schedule_message_pump_work(&self, delay_ms: i64) {
+        self.pump.schedule(delay_ms);
+    }
+
+    fn get_raw(&self) -> *mut _cef_browser_process_handler_t {
+        self.object as *mut _cef_browser_process_handler_t
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    /// Report the late work of `state` at `now` like [ExternalPump::pump].
+    fn execute(state: &mut PumpState, now: Instant) {
+        if let Some((delay, threshold)) = state.execute(now) {
+            diagnostics::report(
+                DiagnosticCategory::SlowMessagePump,
+                format_args!("{delay:?} late, more than {threshold:?}"),
+            );
+        }
+    }
+
+    #[test]
+    fn test_pump_stats() {
+        let start = Instant::now();
+        let mut state = PumpState::default();
+
+        // Work for now, which a later request doesn't postpone.
+        state.schedule(start, Duration::ZERO);
+        state.schedule(start, ms(30));
+        assert_eq!(state.deadline, Some(start));
+        state.execute(start + ms(2));
+
+        // Delayed work which ran on time, and work which ran late.
+        state.schedule(start, ms(30));
+        state.execute(start + ms(30));
+        state.schedule(start + ms(40), Duration::ZERO);
+        state.execute(start + ms(140));
+
+        // Work without a request doesn't count as a delay.
+        state.execute(start + ms(150));
+
+        let stats = state.stats();
+        assert_eq!(stats.scheduled, 4);
+        assert_eq!(stats.executed, 4);
+        assert_eq!(stats.max_delay, ms(100));
+        assert_eq!(stats.histogram.buckets, [1, 1, 0, 0, 1, 0]);
+        assert_eq!(stats.histogram.count(), 3);
+        assert_eq!(state.deadline, None);
+
+        state.reset_stats();
+        assert_eq!(state.stats(), PumpStats::default());
+    }
+
+    #[test]
+    fn test_pump_histogram_window() {
+        let start = Instant::now();
+        let mut state = PumpState::default();
+        state.schedule(start, Duration::ZERO);
+        state.execute(start + ms(300));
+        for _ in 0..HISTOGRAM_WINDOW {
+            state.schedule(start, Duration::ZERO);
+            state.execute(start);
+        }
+
+        let stats = state.stats();
+        // The late work fell out of the window, but not out of the maximum.
+        assert_eq!(stats.histogram.buckets[0], HISTOGRAM_WINDOW as u64);
+        assert_eq!(stats.histogram.count(), HISTOGRAM_WINDOW as u64);
+        assert_eq!(stats.max_delay, ms(300));
+    }
+
+    #[test]
+    fn test_pump_warning() {
+        let start = Instant::now();
+        let mut state = PumpState {
+            warn_after: Some(ms(50)),
+            ..Default::default()
+        };
+        let diagnostics = diagnostics::collect(|| {
+            state.schedule(start, ms(10));
+            execute(&mut state, start + ms(60));
+            state.schedule(start, ms(10));
+            execute(&mut state, start + ms(61));
+        });
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].category, DiagnosticCategory::SlowMessagePump);
+        assert_eq!(diagnostics[0].message, "51ms late, more than 50ms");
+    }
+}
@@ -31,6 +31,15 @@
 //! Finally, define a method called `from_raw`. For more implementation details, please see the
 //! documentation of [`RefGuard`].
 //!
+//! ## Reference counted arguments of callbacks
+//!
+//! The generated `Impl*` traits receive reference counted arguments as `Option<&impl ImplFoo>`.
+//! CEF hands the callee a reference of its own, which the shim wraps in a [`RefGuard`] and
+//! releases when the callback returns, so the argument is only borrowed for the duration of the
+//! call. Call `to_owned()` (or `clone()`) to add a reference and keep it, e.g.
+//! `browser.map(ToOwned::to_owned)`. Out-parameters which return an object to CEF are still
+//! `Option<&mut impl ImplFoo>`.
+//!
 //! [`cef_settings_t`]: cef_sys::cef_settings_t
 //! [`cef_window_delegate_t`]: cef_sys::cef_window_delegate_t
 //! [`Settings`]: crate::Settings
@@ -41,12 +50,17 @@
 //! [`Window`]: crate::Window
 
 use std::{
+    any::TypeId,
+    ffi::c_void,
     fmt::Debug,
     mem,
     ops::Deref,
     sync::atomic::{fence, AtomicUsize, Ordering},
 };
 
+#[cfg(feature = "leak-tracking")]
+use std::{any, collections::BTreeMap, sync::Mutex};
+
 use cef_sys::cef_base_ref_counted_t;
 
 /// Reference counted trait for types has [`cef_base_ref_counted_t`].
@@ -150,19 +164,38 @@ where
     }
 }
 
-pub struct WrapParamRef<T>(mem::ManuallyDrop<T>);
+type WriteBack<T> = unsafe fn(*mut c_void, &T);
+
+pub struct WrapParamRef<T> {
+    value: mem::ManuallyDrop<T>,
+    write_back: Option<(*mut c_void, WriteBack<T>)>,
+}
+
+/// Copy the (possibly modified) value back into the out-parameter it was read from.
+unsafe fn write_back<T, U>(ptr: *mut c_void, value: &U)
+where
+    T: Sized,
+    U: Sized + Clone + Into<T>,
+{
+    *(ptr as *mut T) = value.clone().into();
+}
 
 impl<T, U> From<*mut T> for WrapParamRef<U>
 where
     T: Sized + Copy + Into<U>,
-    U: Sized,
+    U: Sized + Clone + Into<T>,
 {
     fn from(value: *mut T) -> Self {
+        let write_back = (!value.is_null())
+            .then_some((value as *mut c_void, write_back::<T, U> as WriteBack<U>));
         let value = unsafe { value.as_ref() }
             .map(|value| (*value).into())
             .unwrap_or_else(|| unsafe { mem::zeroed() });
 
-        WrapParamRef(mem::ManuallyDrop::new(value))
+        WrapParamRef {
+            value: mem::ManuallyDrop::new(value),
+            write_back,
+        }
     }
 }
 
@@ -176,19 +209,32 @@ where
             .map(|value| (*value).into())
             .unwrap_or_else(|| unsafe { mem::zeroed() });
 
-        WrapParamRef(mem::ManuallyDrop::new(value))
+        WrapParamRef {
+            value: mem::ManuallyDrop::new(value),
+            write_back: None,
+        }
+    }
+}
+
+impl<T> Drop for WrapParamRef<T> {
+    /// Out-parameters are converted to a Rust copy before calling the trait method, so any changes
+    /// need to be written back to the original pointer before returning to CEF.
+    fn drop(&mut self) {
+        if let Some((ptr, write_back)) = self.write_back.take() {
+            unsafe { write_back(ptr, &self.value) };
+        }
     }
 }
 
 impl<T> AsMut<T> for WrapParamRef<T> {
     fn as_mut(&mut self) -> &mut T {
-        &mut *self.0
+        &mut self.value
     }
 }
 
 impl<T> AsRef<T> for WrapParamRef<T> {
     fn as_ref(&self) -> &T {
-        &*self.0
+        &self.value
     }
 }
 
@@ -206,9 +252,140 @@ where
     }
 }
 
+/// Pass an optional wrapper to CEF as a ref-counted **parameter**. The generated code calls this
+/// instead of repeating the conversion in every method.
+///
+/// # Safety
+///
+/// This adds a reference which CEF takes ownership of, so the raw pointer must be passed to CEF.
+pub(crate) unsafe fn ref_arg<A, T>(arg: Option<&A>, get_raw: impl FnOnce(&A) -> *mut T) -> *mut T
+where
+    A: Rc,
+{
+    match arg {
+        Some(arg) => {
+            arg.add_ref();
+            get_raw(arg)
+        }
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// Wrap a ref-counted parameter CEF passed to a callback, which may be null. The generated code
+/// calls this instead of repeating the conversion in every callback.
+///
+/// # Safety
+///
+/// The wrapper takes ownership of the reference CEF passed in, and releases it when dropped.
+pub(crate) unsafe fn wrap_arg<T, U>(arg: *mut T) -> Option<U>
+where
+    *mut T: ConvertReturnValue<U>,
+{
+    (!arg.is_null()).then(|| arg.as_wrapper())
+}
+
+/// Call `method` with the Rust object behind `self_`, the object CEF called a callback on. The
+/// generated callbacks share this instead of each looking up the object.
+pub(crate) fn forward_to_impl<T, I, R>(self_: *mut T, method: impl FnOnce(&I) -> R) -> R {
+    let object: &RcImpl<T, I> = RcImpl::get(self_);
+    method(&object.interface)
+}
+
+/// The callbacks of a CEF struct, as pairs of the offset of a function pointer field and the
+/// function to store in it.
+pub(crate) type VtableMethods = [(usize, *const ())];
+
+/// The [VtableMethods] of a CEF struct `$ty` whose callbacks `$method::<$impl>` are named after
+/// their fields.
+macro_rules! vtable_methods {
+    ($ty:ty, $impl:ident, $($method:ident),* $(,)?) => {
+        &[$((::std::mem::offset_of!($ty, $method), $method::<$impl> as *const ())),*]
+    };
+}
+
+pub(crate) use vtable_methods;
+
+/// Store the callbacks of `methods` in the function pointer fields of `object`. The generated
+/// `init_methods` share this loop instead of each assigning every field.
+///
+/// # Safety
+///
+/// Each offset must be that of an `Option` of an `extern "C"` function pointer field of `T`, and
+/// the function stored there must have the type of the field.
+pub(crate) unsafe fn init_vtable<T>(object: &mut T, methods: &VtableMethods) {
+    let object = (object as *mut T).cast::<u8>();
+    for &(offset, method) in methods {
+        unsafe { object.add(offset).cast::<*const ()>().write(method) };
+    }
+}
+
+/// Incremented when [crate::context::Context] shuts CEF down. Debug builds record the epoch in
+/// every [RefGuard], so using one from before the shutdown panics instead of calling through a
+/// dangling vtable.
+static SHUTDOWN_EPOCH: AtomicUsize = AtomicUsize::new(0);
+
+/// Mark every existing [RefGuard] as used after shutdown.
+pub(crate) fn end_shutdown_epoch() {
+    SHUTDOWN_EPOCH.fetch_add(1, Ordering::SeqCst);
+}
+
+#[cfg(debug_assertions)]
+fn shutdown_epoch() -> usize {
+    SHUTDOWN_EPOCH.load(Ordering::Relaxed)
+}
+
+/// The number of live [RefGuard]s by raw type name.
+#[cfg(feature = "leak-tracking")]
+static LIVE_OBJECTS: Mutex<BTreeMap<&'static str, usize>> = Mutex::new(BTreeMap::new());
+
+#[cfg(feature = "leak-tracking")]
+fn track_object<T>(created: bool) {
+    let Ok(mut live) = LIVE_OBJECTS.lock() else {
+        return;
+    };
+    let count = live.entry(any::type_name::<T>()).or_default();
+    if created {
+        *count += 1;
+    } else {
+        *count = count.saturating_sub(1);
+        if *count == 0 {
+            live.remove(any::type_name::<T>());
+        }
+    }
+}
+
+/// The raw type names and counts of the wrapper objects which are still alive. Requires the
+/// `leak-tracking` feature.
+#[cfg(feature = "leak-tracking")]
+pub fn live_objects() -> Vec<(&'static str, usize)> {
+    LIVE_OBJECTS
+        .lock()
+        .map(|live| live.iter().map(|(name, count)| (*name, *count)).collect())
+        .unwrap_or_default()
+}
+
+/// Describe the [live_objects], or `None` if there are none.
+#[cfg(feature = "leak-tracking")]
+pub(crate) fn leak_report() -> Option<String> {
+    let live = live_objects();
+    if live.is_empty() {
+        return None;
+    }
+    let objects: Vec<_> = live
+        .iter()
+        .map(|(name, count)| format!("{count} x {name}"))
+        .collect();
+    Some(format!(
+        "CEF objects still alive at shutdown: {}",
+        objects.join(", ")
+    ))
+}
+
 /// A smart pointer for types from cef library.
 pub struct RefGuard<T: Rc> {
     object: *mut T,
+    #[cfg(debug_assertions)]
+    epoch: usize,
 }
 
 impl<T: Debug + Rc> Debug for RefGuard<T> {
@@ -228,7 +405,7 @@ impl<T: Rc> RefGuard<T> {
     /// you get it. So we don't need to increase it again manually. Using this method elsewhere may
     /// cause incorrect reference count and memory safety issues.
     pub unsafe fn from_raw(ptr: *mut T) -> RefGuard<T> {
-        RefGuard { object: ptr }
+        Self::guard(ptr)
     }
 
     /// Create [RefGuard] from a raw C pointer and increase a reference count. This should be used
@@ -240,7 +417,7 @@ impl<T: Rc> RefGuard<T> {
     /// raw pointer. Using this method elsewhere may cause incorrect reference count and memory
     /// safety issues.
     pub unsafe fn from_raw_add_ref(ptr: *mut T) -> RefGuard<T> {
-        let guard = RefGuard { object: ptr };
+        let guard = Self::guard(ptr);
 
         guard.add_ref();
 
@@ -268,6 +445,8 @@ impl<T: Rc> RefGuard<T> {
     /// value to the function call. Using this method elsewhere may cause incorrect reference count
     /// and memory safety issues.
     pub unsafe fn into_raw(self) -> *mut T {
+        #[cfg(feature = "leak-tracking")]
+        track_object::<T>(false);
         mem::ManuallyDrop::new(self).object
     }
 
@@ -280,6 +459,36 @@ impl<T: Rc> RefGuard<T> {
     pub unsafe fn convert<U: Rc>(&self) -> RefGuard<U> {
         RefGuard::from_raw_add_ref(self.as_raw() as *mut _)
     }
+
+    fn guard(object: *mut T) -> RefGuard<T> {
+        #[cfg(feature = "leak-tracking")]
+        track_object::<T>(true);
+        RefGuard {
+            object,
+            #[cfg(debug_assertions)]
+            epoch: shutdown_epoch(),
+        }
+    }
+
+    /// `true` if CEF was shut down after the guard was created.
+    #[cfg(debug_assertions)]
+    fn is_after_shutdown(&self) -> bool {
+        self.epoch != shutdown_epoch()
+    }
+
+    #[cfg(debug_assertions)]
+    fn check_epoch(&self) {
+        if self.is_after_shutdown() {
+            crate::diagnostics::report(
+                crate::diagnostics::DiagnosticCategory::AfterShutdown,
+                std::any::type_name::<T>(),
+            );
+            panic!(
+                "CEF object used after shutdown: {}",
+                std::any::type_name::<T>()
+            );
+        }
+    }
 }
 
 unsafe impl<T: Rc> Send for RefGuard<T> {}
@@ -289,9 +498,7 @@ impl<T: Rc> Clone for RefGuard<T> {
     fn clone(&self) -> RefGuard<T> {
         unsafe { self.add_ref() };
 
-        RefGuard {
-            object: self.object,
-        }
+        Self::guard(self.object)
     }
 }
 
@@ -299,12 +506,22 @@ impl<T: Rc> Deref for RefGuard<T> {
     type Target = T;
 
     fn deref(&self) -> &T {
+        // Every generated method goes through here to reach the vtable.
+        #[cfg(debug_assertions)]
+        self.check_epoch();
         unsafe { &*self.object }
     }
 }
 
 impl<T: Rc> Drop for RefGuard<T> {
     fn drop(&mut self) {
+        // Don't turn the panic of a use after shutdown into an abort.
+        #[cfg(debug_assertions)]
+        if std::thread::panicking() && self.is_after_shutdown() {
+            return;
+        }
+        #[cfg(feature = "leak-tracking")]
+        track_object::<T>(false);
         unsafe { self.release() };
     }
 }
@@ -315,13 +532,18 @@ impl<T: Rc> Drop for RefGuard<T> {
 pub struct RcImpl<T, I> {
     /// Raw cef types
     pub cef_object: T,
+    /// The [TypeId] of `I`, which comes before `interface` so its offset doesn't depend on `I`.
+    type_id: TypeId,
+    ref_count: AtomicUsize,
     /// Rust interface of such type
     pub interface: I,
-    ref_count: AtomicUsize,
 }
 
 impl<T, I> RcImpl<T, I> {
-    pub fn new(mut cef_object: T, interface: I) -> *mut RcImpl<T, I> {
+    pub fn new(mut cef_object: T, interface: I) -> *mut RcImpl<T, I>
+    where
+        I: 'static,
+    {
         let base = unsafe { &mut *(&mut cef_object as *mut T as *mut cef_base_ref_counted_t) };
 
         base.size = std::mem::size_of::<T>();
@@ -332,8 +554,9 @@ impl<T, I> RcImpl<T, I> {
 
         Box::into_raw(Box::new(RcImpl {
             cef_object,
-            interface,
+            type_id: TypeId::of::<I>(),
             ref_count: AtomicUsize::new(1),
+            interface,
         }))
     }
 
@@ -342,6 +565,32 @@ impl<T, I> RcImpl<T, I> {
     }
 }
 
+impl<T, I: 'static> RcImpl<T, I> {
+    /// Get the Rust interface of the raw cef object `ptr`, if it was created by [RcImpl::new]
+    /// with an interface of type `I`. Objects implemented in C++, or in Rust with another type,
+    /// return `None`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be null or point to a live cef object of type `T`, and the caller must hold a
+    /// reference to the object for as long as it uses the returned reference, e.g. in a
+    /// [RefGuard]. The lifetime `'a` is unbounded, so nothing else ties it to the object.
+    pub unsafe fn downcast_ref<'a>(ptr: *mut T) -> Option<&'a I> {
+        let base = unsafe { (ptr as *const cef_base_ref_counted_t).as_ref()? };
+        // Only objects created by `RcImpl::new` have a `type_id` to compare.
+        let add_ref: unsafe extern "C" fn(*mut cef_base_ref_counted_t) = add_ref::<T, I>;
+        if !base
+            .add_ref
+            .is_some_and(|base_add_ref| std::ptr::fn_addr_eq(base_add_ref, add_ref))
+        {
+            return None;
+        }
+        let object = ptr as *const RcImpl<T, I>;
+        let type_id = unsafe { std::ptr::addr_of!((*object).type_id).read() };
+        (type_id == TypeId::of::<I>()).then(|| unsafe { &(*object).interface })
+    }
+}
+
 extern "C" fn add_ref<T, I>(this: *mut cef_base_ref_counted_t) {
     let obj = RcImpl::<T, I>::get(this as *mut T);
 
@@ -379,3 +628,152 @@ pub extern "C" fn release<T, I>(this: *mut cef_base_ref_counted_t) -> i32 {
         1
     }
 }
+
+/// Implement the `Wrap*`, [`Clone`] and [`Rc`] boilerplate for a Rust type which is passed to the
+/// C API through [`RcImpl`]. The type must have an `object: *mut RcImpl<T, Self>` field which is
+/// filled in by `wrap_rc`, and every other field listed after the raw type will be cloned.
+///
+/// ```ignore
+/// struct MyTask {
+///     object: *mut RcImpl<_cef_task_t, Self>,
+///     name: String,
+/// }
+///
+/// wrap_rc!(MyTask, WrapTask, _cef_task_t, name);
+/// ```
+macro_rules! wrap_rc {
+    ($name:ident, $wrap:ident, $raw:ty $(, $field:ident)* $(,)?) => {
+        impl $crate::$wrap for $name {
+            fn wrap_rc(&mut self, object: *mut $crate::rc::RcImpl<$raw, Self>) {
+                self.object = object;
+            }
+        }
+
+        impl Clone for $name {
+            fn clone(&self) -> Self {
+                unsafe {
+                    let rc_impl = &mut *self.object;
+                    $crate::rc::Rc::add_ref(&rc_impl.interface);
+                }
+
+                Self {
+                    object: self.object,
+                    $($field: self.$field.clone(),)*
+                }
+            }
+        }
+
+        impl $crate::rc::Rc for $name {
+            fn as_base(&self) -> &cef_sys::cef_base_ref_counted_t {
+                unsafe {
+                    let base = &*self.object;
+                    &*(&base.cef_object as *const $raw as *const cef_sys::cef_base_ref_counted_t)
+                }
+            }
+        }
+    };
+}
+
+pub(crate) use wrap_rc;
+
+/// Implement `from_impl` for a generated wrapper type, which gets an owned wrapper from the
+/// borrowed `impl Impl*` parameter passed to a handler callback, adding a reference.
+///
+/// ```ignore
+/// from_impl!(Browser, ImplBrowser);
+/// ```
+macro_rules! from_impl {
+    ($name:ident, $impl:ident) => {
+        impl $crate::$name {
+            #[doc = concat!("Get an owned [`", stringify!($name), "`] from the parameter passed to a handler callback.")]
+            pub fn from_impl(value: &impl $crate::$impl) -> Self {
+                unsafe {
+                    $crate::rc::Rc::add_ref(value);
+                    $crate::rc::ConvertReturnValue::as_wrapper($crate::$impl::get_raw(value))
+                }
+            }
+        }
+    };
+}
+
+pub(crate) use from_impl;
+
+#[cfg(all(test, any(debug_assertions, feature = "leak-tracking")))]
+mod test {
+    use std::sync::Mutex;
+
+    use cef_sys::_cef_browser_t;
+
+    use super::*;
+    use crate::{Browser, ImplBrowser};
+
+    /// Serialises the tests which end the process-wide [SHUTDOWN_EPOCH] or count live objects.
+    static LOCK: Mutex<()> = Mutex::new(());
+
+    struct LeakedBrowser {
+        object: *mut RcImpl<_cef_browser_t, Self>,
+    }
+
+    impl ImplBrowser for LeakedBrowser {
+        fn get_raw(&self) -> *mut _cef_browser_t {
+            self.object as *mut _
+        }
+    }
+
+    wrap_rc!(LeakedBrowser, WrapBrowser, _cef_browser_t);
+
+    fn leak_browser() -> Browser {
+        Browser::new(LeakedBrowser {
+            object: std::ptr::null_mut(),
+        })
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "CEF object used after shutdown")]
+    fn test_use_after_shutdown() {
+        let _lock = LOCK.lock().unwrap_or_else(|err| err.into_inner());
+        let browser = leak_browser();
+        assert!(!browser.clone().is_valid());
+        end_shutdown_epoch();
+        browser.is_valid();
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn test_use_after_shutdown_diagnostic() {
+        let _lock = LOCK.lock().unwrap_or_else(|err| err.into_inner());
+        let browser = leak_browser();
+        end_shutdown_epoch();
+        let diagnostics = crate::diagnostics::collect(|| {
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                browser.is_valid();
+            }));
+            assert!(result.is_err());
+        });
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].category,
+            crate::diagnostics::DiagnosticCategory::AfterShutdown
+        );
+        assert!(diagnostics[0].message.ends_with("_cef_browser_t"));
+    }
+
+    #[cfg(feature = "leak-tracking")]
+    #[test]
+    fn test_leak_report() {
+        let _lock = LOCK.lock().unwrap_or_else(|err| err.into_inner());
+        let count = |live: Vec<(&str, usize)>| {
+            live.into_iter()
+                .find(|(name, _)| name.ends_with("_cef_browser_t"))
+                .map(|(_, count)| count)
+                .unwrap_or_default()
+        };
+        let before = count(live_objects());
+        let browser = leak_browser();
+        assert_eq!(count(live_objects()), before + 1);
+        assert!(leak_report().unwrap().contains("_cef_browser_t"));
+        drop(browser);
+        assert_eq!(count(live_objects()), before);
+    }
+}
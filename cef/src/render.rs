@@ -0,0 +1,513 @@
+//! Render process module
+
+use std::{collections::BTreeSet, fmt, os::raw::c_int, ptr, sync::Arc};
+
+use cef_sys::{_cef_render_process_handler_t, _cef_string_utf16_t};
+
+use crate::{
+    browser::BrowserMap,
+    diagnostics::{self, DiagnosticCategory},
+    message::MessageDispatcher,
+    rc::{from_impl, wrap_rc, ConvertReturnValue, RcImpl},
+    *,
+};
+
+from_impl!(V8context, ImplV8context);
+from_impl!(DictionaryValue, ImplDictionaryValue);
+
+impl V8context {
+    /// Evaluate `code` in this context and return its result, or the exception message if it
+    /// threw. `script_url` and `start_line` are used in stack traces and the devtools.
+    pub fn eval_script(
+        &self,
+        code: &str,
+        script_url: &str,
+        start_line: c_int,
+    ) -> Result<Option<V8value>, String> {
+        let code = CefString::from(&CefStringUtf8::from(code));
+        let script_url = CefString::from(&CefStringUtf8::from(script_url));
+        let code: *const _cef_string_utf16_t = (&code).into();
+        let script_url: *const _cef_string_utf16_t = (&script_url).into();
+
+        // The generated `eval` can't return the out-parameters, so call it directly.
+        let raw = ImplV8context::get_raw(self);
+        let mut retval = ptr::null_mut();
+        let mut exception = ptr::null_mut();
+        let result = unsafe {
+            (*raw).eval.map_or(0, |f| {
+                f(
+                    raw,
+                    code,
+                    script_url,
+                    start_line,
+                    &mut retval,
+                    &mut exception,
+                )
+            })
+        };
+        let retval: Option<V8value> = (!retval.is_null()).then(|| retval.as_wrapper());
+        let exception: Option<V8exception> = (!exception.is_null()).then(|| exception.as_wrapper());
+
+        if result != 0 {
+            return Ok(retval);
+        }
+        Err(exception
+            .and_then(|exception| exception.get_message())
+            .map(|message| message.to_string())
+            .unwrap_or_else(|| "Failed to evaluate script".to_string()))
+    }
+}
+
+/// A script injected by [PreloadScripts].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PreloadScript {
+    /// The JavaScript source.
+    pub code: String,
+    /// The URL reported in stack traces and the devtools.
+    pub url: String,
+    /// The line of `code` in the script at `url`.
+    pub start_line: i32,
+}
+
+type FrameFilter = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// A render process handler which runs [PreloadScript]s in every new V8 context, before any
+/// script of the page. Scripts which throw are reported as a [DiagnosticCategory::PreloadScript].
+///
+/// Return `RenderProcessHandler::new(scripts)` from [ImplApp::get_render_process_handler].
+pub struct PreloadScripts {
+    object: *mut RcImpl<_cef_render_process_handler_t, Self>,
+    scripts: Arc<Vec<PreloadScript>>,
+    per_frame_filter: Option<FrameFilter>,
+    message_dispatcher: Option<MessageDispatcher>,
+}
+
+wrap_rc!(
+    PreloadScripts,
+    WrapRenderProcessHandler,
+    _cef_render_process_handler_t,
+    scripts,
+    per_frame_filter,
+    message_dispatcher
+);
+
+impl PreloadScripts {
+    /// Run `scripts`, in order, in every frame.
+    pub fn new(scripts: Vec<PreloadScript>) -> Self {
+        Self {
+            object: ptr::null_mut(),
+            scripts: Arc::new(scripts),
+            per_frame_filter: None,
+            message_dispatcher: None,
+        }
+    }
+
+    /// Only run the scripts in frames whose URL is accepted by `filter`.
+    pub fn per_frame_filter(
+        mut self,
+        filter: impl Fn(&str) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.per_frame_filter = Some(Arc::new(filter));
+        self
+    }
+
+    /// Route the process messages sent to the render process through `dispatcher`.
+    pub fn message_dispatcher(mut self, dispatcher: MessageDispatcher) -> Self {
+        self.message_dispatcher = Some(dispatcher);
+        self
+    }
+
+    /// The scripts to run in a frame loaded from `frame_url`.
+    fn scripts_for(&self, frame_url: &str) -> &[PreloadScript] {
+        match &self.per_frame_filter {
+            Some(filter) if !filter(frame_url) => &[],
+            _ => &self.scripts,
+        }
+    }
+}
+
+impl ImplRenderProcessHandler for PreloadScripts {
+    fn on_context_created(
+        &self,
+        _browser: Option<&impl ImplBrowser>,
+        frame: Option<&impl ImplFrame>,
+        context: Option<&impl ImplV8context>,
+    ) {
+        let (Some(frame), Some(context)) = (frame, context) else {
+            return;
+        };
+        let frame_url = frame
+            .get_url()
+            .map(|url| url.to_string())
+            .unwrap_or_default();
+        let context = V8context::from_impl(context);
+        for script in self.scripts_for(&frame_url) {
+            if let Err(message) = context.eval_script(&script.code, &script.url, script.start_line)
+            {
+                diagnostics::report(
+                    DiagnosticCategory::PreloadScript,
+                    format!("{} failed: {message}", script.url),
+                );
+            }
+        }
+    }
+
+    fn on_process_message_received(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        frame: Option<&impl ImplFrame>,
+        source_process: ProcessId,
+        message: Option<&impl ImplProcessMessage>,
+    ) -> c_int {
+        let Some(dispatcher) = &self.message_dispatcher else {
+            return 0;
+        };
+        dispatcher.dispatch(browser, frame, source_process, message) as c_int
+    }
+
+    fn get_raw(&self) -> *mut _cef_render_process_handler_t {
+        self.object as *mut _cef_render_process_handler_t
+    }
+}
+
+/// The key of the [RendererConfig] in the `extra_info` of a browser.
+pub const RENDERER_CONFIG_KEY: &str = "cef.renderer_config";
+
+/// Configuration of the render process for one browser, decided when the browser is created.
+///
+/// Set [crate::browser::BrowserOptions::renderer_config] to send it with the browser, and read it
+/// in the render process with [RenderApp::config_for]. Both processes must be built from the same
+/// version of this crate.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RendererConfig {
+    /// The enabled feature flags.
+    pub features: BTreeSet<String>,
+    /// The native functions which the JavaScript bridge may inject into the pages of the browser.
+    pub bridge_functions: BTreeSet<String>,
+}
+
+/// Why [RendererConfig::read_from] failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RendererConfigError {
+    /// The browser process was built with another version of this crate.
+    VersionMismatch { browser: String, renderer: String },
+    /// A field is missing or has the wrong type.
+    Malformed(&'static str),
+}
+
+impl fmt::Display for RendererConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::VersionMismatch { browser, renderer } => write!(
+                f,
+                "the browser process uses cef {browser}, but the render process uses cef {renderer}"
+            ),
+            Self::Malformed(field) => write!(f, "the renderer config has no valid `{field}`"),
+        }
+    }
+}
+
+impl std::error::Error for RendererConfigError {}
+
+fn key(name: &str) -> CefStringUtf16 {
+    CefString::from(&CefStringUtf8::from(name))
+}
+
+fn write_strings(dictionary: &DictionaryValue, name: &str, strings: &BTreeSet<String>) -> bool {
+    let Some(list) = list_value_create() else {
+        return false;
+    };
+    list.set_size(strings.len());
+    for (index, string) in strings.iter().enumerate() {
+        list.set_string(index, Some(&key(string)));
+    }
+    dictionary.set_list(Some(&key(name)), Some(&list)) != 0
+}
+
+fn read_strings(
+    dictionary: &DictionaryValue,
+    name: &'static str,
+) -> Result<BTreeSet<String>, RendererConfigError> {
+    let list = dictionary
+        .get_list(Some(&key(name)))
+        .ok_or(RendererConfigError::Malformed(name))?;
+    (0..list.get_size())
+        .map(|index| {
+            list.get_string(index)
+                .map(|string| string.to_string())
+                .ok_or(RendererConfigError::Malformed(name))
+        })
+        .collect()
+}
+
+impl RendererConfig {
+    /// Whether the feature flag `name` is enabled.
+    pub fn has_feature(&self, name: &str) -> bool {
+        self.features.contains(name)
+    }
+
+    /// Whether the JavaScript bridge may inject the function `name`.
+    pub fn allows_bridge_function(&self, name: &str) -> bool {
+        self.bridge_functions.contains(name)
+    }
+
+    /// A new `extra_info` dictionary which holds only this config.
+    pub fn to_extra_info(&self) -> Option<DictionaryValue> {
+        let extra_info = dictionary_value_create()?;
+        self.write_to(&extra_info).then_some(extra_info)
+    }
+
+    /// Add this config to the `extra_info` of a browser, replacing the one it had.
+    pub fn write_to(&self, extra_info: &DictionaryValue) -> bool {
+        let Some(config) = dictionary_value_create() else {
+            return false;
+        };
+        config.set_string(Some(&key("version")), Some(&key(VERSION))) != 0
+            && write_strings(&config, "features", &self.features)
+            && write_strings(&config, "bridge_functions", &self.bridge_functions)
+            && extra_info.set_dictionary(Some(&key(RENDERER_CONFIG_KEY)), Some(&config)) != 0
+    }
+
+    /// The config in the `extra_info` of a browser, `None` if it has none.
+    pub fn read_from(extra_info: &DictionaryValue) -> Result<Option<Self>, RendererConfigError> {
+        let Some(config) = extra_info.get_dictionary(Some(&key(RENDERER_CONFIG_KEY))) else {
+            return Ok(None);
+        };
+        let version = config
+            .get_string(Some(&key("version")))
+            .map(|version| version.to_string())
+            .ok_or(RendererConfigError::Malformed("version"))?;
+        // The layout of the config may change between versions.
+        if version != VERSION {
+            return Err(RendererConfigError::VersionMismatch {
+                browser: version,
+                renderer: VERSION.to_string(),
+            });
+        }
+        Ok(Some(Self {
+            features: read_strings(&config, "features")?,
+            bridge_functions: read_strings(&config, "bridge_functions")?,
+        }))
+    }
+}
+
+/// The version of this crate, which must match in the browser and render processes.
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Keeps the [RendererConfig] of each browser in the render process.
+///
+/// Return [RenderApp::render_process_handler] from [ImplApp::get_render_process_handler], and
+/// look up the config of a browser with [RenderApp::config_for], e.g. before the JavaScript
+/// bridge injects its functions in [ImplRenderProcessHandler::on_context_created]. Clones share
+/// the configs.
+///
+/// A render process built from another version of this crate than the browser process panics
+/// when the browser is created, instead of misreading the config.
+#[derive(Clone, Default)]
+pub struct RenderApp {
+    configs: BrowserMap<RendererConfig>,
+    handler: Option<RenderProcessHandler>,
+}
+
+impl RenderApp {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Pass the callbacks on to `handler`, e.g. [PreloadScripts], after the config of the
+    /// browser is stored.
+    pub fn handler(mut self, handler: RenderProcessHandler) -> Self {
+        self.handler = Some(handler);
+        self
+    }
+
+    /// The config which the browser process sent with `browser`, `None` if it sent none.
+    pub fn config_for(&self, browser: &Browser) -> Option<RendererConfig> {
+        self.configs.get(browser).map(|config| config.clone())
+    }
+
+    /// The render process handler which stores the configs.
+    pub fn render_process_handler(&self) -> RenderProcessHandler {
+        RenderProcessHandler::new(RenderAppHandler {
+            object: ptr::null_mut(),
+            app: self.clone(),
+        })
+    }
+}
+
+/// The handler of [RenderApp::render_process_handler].
+struct RenderAppHandler {
+    object: *mut RcImpl<_cef_render_process_handler_t, Self>,
+    app: RenderApp,
+}
+
+wrap_rc!(
+    RenderAppHandler,
+    WrapRenderProcessHandler,
+    _cef_render_process_handler_t,
+    app
+);
+
+impl ImplRenderProcessHandler for RenderAppHandler {
+    fn on_web_kit_initialized(&self) {
+        if let Some(handler) = &self.app.handler {
+            handler.on_web_kit_initialized();
+        }
+    }
+
+    fn on_browser_created(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        extra_info: Option<&impl ImplDictionaryValue>,
+    ) {
+        if let (Some(browser), Some(extra_info)) = (browser, extra_info) {
+            let browser = Browser::from_impl(browser);
+            let extra_info = DictionaryValue::from_impl(extra_info);
+            match RendererConfig::read_from(&extra_info) {
+                Ok(Some(config)) => {
+                    self.app.configs.insert(&browser, config);
+                }
+                Ok(None) => {}
+                Err(error) => panic!("Incompatible renderer config: {error}"),
+            }
+        }
+        if let Some(handler) = &self.app.handler {
+            handler.on_browser_created(browser, extra_info);
+        }
+    }
+
+    fn on_browser_destroyed(&self, browser: Option<&impl ImplBrowser>) {
+        if let Some(handler) = &self.app.handler {
+            handler.on_browser_destroyed(browser);
+        }
+        if let Some(browser) = browser {
+            self.app.configs.remove(&Browser::from_impl(browser));
+        }
+    }
+
+    fn on_context_created(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        frame: Option<&impl ImplFrame>,
+        context: Option<&impl ImplV8context>,
+    ) {
+        if let Some(handler) = &self.app.handler {
+            handler.on_context_created(browser, frame, context);
+        }
+    }
+
+    fn on_context_released(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        frame: Option<&impl ImplFrame>,
+        context: Option<&impl ImplV8context>,
+    ) {
+        if let Some(handler) = &self.app.handler {
+            handler.on_context_released(browser, frame, context);
+        }
+    }
+
+    fn on_process_message_received(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        frame: Option<&impl ImplFrame>,
+        source_process: ProcessId,
+        message: Option<&impl ImplProcessMessage>,
+    ) -> c_int {
+        self.app.handler.as_ref().map_or(0, |handler| {
+            handler.on_process_message_received(browser, frame, source_process, message)
+        })
+    }
+
+    fn get_raw(&self) -> *mut _cef_render_process_handler_t {
+        self.object as *mut _cef_render_process_handler_t
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures::FakeBrowser;
+
+    fn injected() -> Vec<PreloadScript> {
+        vec![PreloadScript {
+            code: "window.injected = true;".to_string(),
+            url: "preload://injected.js".to_string(),
+            start_line: 1,
+        }]
+    }
+
+    #[test]
+    fn test_preload_every_frame() {
+        let scripts = PreloadScripts::new(injected());
+        assert_eq!(
+            scripts.scripts_for("https://example.com/"),
+            injected().as_slice()
+        );
+    }
+
+    #[test]
+    fn test_per_frame_filter() {
+        let scripts = PreloadScripts::new(injected())
+            .per_frame_filter(|url| url.starts_with("https://example.com/"));
+        assert_eq!(
+            scripts.scripts_for("https://example.com/"),
+            injected().as_slice()
+        );
+        assert!(scripts.scripts_for("https://example.org/").is_empty());
+    }
+
+    fn renderer_config() -> RendererConfig {
+        RendererConfig {
+            features: ["smooth-scrolling".to_string()].into(),
+            bridge_functions: ["open_file".to_string(), "save_file".to_string()].into(),
+        }
+    }
+
+    #[test]
+    fn test_renderer_config_roundtrip() {
+        let config = renderer_config();
+        let extra_info = config.to_extra_info().unwrap();
+        assert_eq!(RendererConfig::read_from(&extra_info), Ok(Some(config)));
+        assert_eq!(
+            RendererConfig::read_from(&dictionary_value_create().unwrap()),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn test_renderer_config_version_mismatch() {
+        let extra_info = renderer_config().to_extra_info().unwrap();
+        let config = extra_info
+            .get_dictionary(Some(&key(RENDERER_CONFIG_KEY)))
+            .unwrap();
+        config.set_string(Some(&key("version")), Some(&key("0.0.1")));
+        assert_eq!(
+            RendererConfig::read_from(&extra_info),
+            Err(RendererConfigError::VersionMismatch {
+                browser: "0.0.1".to_string(),
+                renderer: VERSION.to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_render_app_config_per_browser() {
+        let app = RenderApp::new();
+        let handler = app.render_process_handler();
+        let (configured, plain) = (FakeBrowser::new(1).build(), FakeBrowser::new(2).build());
+        let extra_info = renderer_config().to_extra_info().unwrap();
+        handler.on_browser_created(Some(&configured), Some(&extra_info));
+        handler.on_browser_created(Some(&plain), dictionary_value_create().as_ref());
+
+        let config = app.config_for(&configured).unwrap();
+        assert!(config.has_feature("smooth-scrolling"));
+        assert!(config.allows_bridge_function("open_file"));
+        assert!(!config.allows_bridge_function("delete_file"));
+        assert_eq!(app.config_for(&plain), None);
+
+        handler.on_browser_destroyed(Some(&configured));
+        assert_eq!(app.config_for(&configured), None);
+    }
+}
@@ -0,0 +1,399 @@
+//! Request module
+//!
+//! [HeaderInjector] adds headers to outgoing requests, e.g. an API key for the requests to your
+//! own backend, in [ImplResourceRequestHandler::on_before_resource_load].
+//! [ResourceRequestHandlerBuilder] runs [RequestInterceptor]s there.
+
+use std::{
+    collections::HashMap,
+    os::raw::c_int,
+    sync::{Arc, Mutex},
+};
+
+use cef_sys::{_cef_request_handler_t, _cef_resource_request_handler_t, cef_return_value_t};
+
+use crate::{
+    middleware::{InterceptResult, RequestInterceptor, RequestInterceptorChain},
+    rc::{from_impl, wrap_rc, RcImpl},
+    scheme::ResourceHandlerBuilder,
+    CefString, CefStringMultimap, CefStringUtf16, CefStringUtf8, ImplBrowser, ImplCallback,
+    ImplFrame, ImplRequest, ImplRequestHandler, ImplResourceRequestHandler, ImplResponse, Request,
+    RequestHandler, ResourceHandler, ResourceRequestHandler, Response, ReturnValue,
+    UrlrequestStatus,
+};
+
+from_impl!(Request, ImplRequest);
+
+/// A [RequestHandler] which adds headers to outgoing requests.
+///
+/// The headers replace any headers of the same name the request already has. The handler returns
+/// a [ResourceRequestHandler] for every request, so in a [crate::chain::HandlerChain] it only
+/// injects headers if no earlier handler returns one.
+///
+/// ```ignore
+/// let headers = HashMap::from([("X-Api-Key".to_string(), api_key)]);
+/// let client = ClientBuilder::new()
+///     .request_handler(HeaderInjector::for_url_pattern("https://api.example.com/*", headers))
+///     .build();
+/// ```
+pub struct HeaderInjector {
+    object: *mut RcImpl<_cef_request_handler_t, Self>,
+    rule: Arc<HeaderRule>,
+}
+
+impl HeaderInjector {
+    /// Add `headers` to every request.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(headers: HashMap<String, String>) -> RequestHandler {
+        Self::for_url_pattern("*", headers)
+    }
+
+    /// Add `headers` to the requests whose URL matches the glob `pattern`, where `*` matches any
+    /// characters and `?` matches one character, e.g. `https://api.example.com/*`.
+    pub fn for_url_pattern(pattern: &str, headers: HashMap<String, String>) -> RequestHandler {
+        let mut headers: Vec<_> = headers.into_iter().collect();
+        headers.sort();
+        RequestHandler::new(Self {
+            object: std::ptr::null_mut(),
+            rule: Arc::new(HeaderRule {
+                pattern: pattern.to_string(),
+                headers,
+            }),
+        })
+    }
+}
+
+wrap_rc!(
+    HeaderInjector,
+    WrapRequestHandler,
+    _cef_request_handler_t,
+    rule
+);
+
+impl ImplRequestHandler for HeaderInjector {
+    fn get_resource_request_handler(
+        &self,
+        _browser: Option<&impl ImplBrowser>,
+        _frame: Option<&impl ImplFrame>,
+        _request: Option<&impl ImplRequest>,
+        _is_navigation: c_int,
+        _is_download: c_int,
+        _request_initiator: Option<&CefStringUtf16>,
+        _disable_default_handling: Option<&mut c_int>,
+    ) -> Option<ResourceRequestHandler> {
+        // The URL is matched for each request, since redirects load it again.
+        Some(ResourceRequestHandler::new(InjectHeaders {
+            object: std::ptr::null_mut(),
+            rule: self.rule.clone(),
+        }))
+    }
+
+    fn get_raw(&self) -> *mut _cef_request_handler_t {
+        self.object as *mut _cef_request_handler_t
+    }
+}
+
+/// The headers added to the requests matching a URL pattern.
+struct HeaderRule {
+    pattern: String,
+    /// Sorted by name, so they are added in a stable order.
+    headers: Vec<(String, String)>,
+}
+
+impl HeaderRule {
+    /// The `existing` headers of a request for `url` with the headers of the rule, or `None` if
+    /// the rule doesn't apply to `url`.
+    fn apply(
+        &self,
+        url: &str,
+        mut existing: Vec<(String, String)>,
+    ) -> Option<Vec<(String, String)>> {
+        if self.headers.is_empty() || !glob_match(&self.pattern, url) {
+            return None;
+        }
+        existing.retain(|(name, _)| {
+            !self
+                .headers
+                .iter()
+                .any(|(header, _)| header.eq_ignore_ascii_case(name))
+        });
+        existing.extend(self.headers.iter().cloned());
+        Some(existing)
+    }
+}
+
+/// Match `text` against `pattern`, where `*` matches any characters and `?` matches one.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<_> = pattern.chars().collect();
+    let text: Vec<_> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // The position after the last `*`, and the text it has matched up to.
+    let mut backtrack = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                p += 1;
+                backtrack = Some((p, t));
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_t)) => {
+                    p = star_p;
+                    t = star_t + 1;
+                    backtrack = Some((star_p, t));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+struct InjectHeaders {
+    object: *mut RcImpl<_cef_resource_request_handler_t, Self>,
+    rule: Arc<HeaderRule>,
+}
+
+wrap_rc!(
+    InjectHeaders,
+    WrapResourceRequestHandler,
+    _cef_resource_request_handler_t,
+    rule
+);
+
+impl ImplResourceRequestHandler for InjectHeaders {
+    fn on_before_resource_load(
+        &self,
+        _browser: Option<&impl ImplBrowser>,
+        _frame: Option<&impl ImplFrame>,
+        request: Option<&impl ImplRequest>,
+        _callback: Option<&impl ImplCallback>,
+    ) -> ReturnValue {
+        if let Some(request) = request {
+            let url = request.get_url().map(|url| url.to_string());
+            let mut header_map = CefStringMultimap::new();
+            request.get_header_map(Some(&mut header_map));
+            if let Some(headers) = self
+                .rule
+                .apply(url.as_deref().unwrap_or_default(), header_map.to_vec())
+            {
+                let mut header_map: CefStringMultimap = headers.into_iter().collect();
+                request.set_header_map(Some(&mut header_map));
+            }
+        }
+        cef_return_value_t::RV_CONTINUE.into()
+    }
+
+    fn get_raw(&self) -> *mut _cef_resource_request_handler_t {
+        self.object as *mut _cef_resource_request_handler_t
+    }
+}
+
+/// Builder of a [ResourceRequestHandler] which runs [RequestInterceptor]s on each request.
+///
+/// Return [ResourceRequestHandlerBuilder::request_handler] from
+/// [crate::ImplClient::get_request_handler], or the built handler from your own
+/// [ImplRequestHandler::get_resource_request_handler].
+#[derive(Clone, Default)]
+pub struct ResourceRequestHandlerBuilder {
+    interceptors: Option<Arc<RequestInterceptorChain>>,
+}
+
+impl ResourceRequestHandlerBuilder {
+    /// Create a builder with the default behavior for every callback.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Run `interceptors` on each request before it is loaded. A request they
+    /// [answer](InterceptResult::Respond) isn't loaded, and one they
+    /// [redirect](InterceptResult::Redirect) loads the new URL instead.
+    pub fn interceptors(mut self, interceptors: RequestInterceptorChain) -> Self {
+        self.interceptors = Some(Arc::new(interceptors));
+        self
+    }
+
+    /// `false` if nothing was set, so the built handler only runs the defaults.
+    pub fn has_overrides(&self) -> bool {
+        self.interceptors.is_some()
+    }
+
+    /// Build the [ResourceRequestHandler].
+    pub fn build(self) -> ResourceRequestHandler {
+        ResourceRequestHandler::new(BuiltResourceRequestHandler {
+            object: std::ptr::null_mut(),
+            interceptors: self.interceptors,
+            responses: Default::default(),
+        })
+    }
+
+    /// A [RequestHandler] which returns the built handler for every request.
+    pub fn request_handler(self) -> RequestHandler {
+        RequestHandler::new(BuiltRequestHandler {
+            object: std::ptr::null_mut(),
+            handler: self.build(),
+        })
+    }
+}
+
+/// The responses of [InterceptResult::Respond] until CEF asks for them, by request identifier.
+type InterceptedResponses = Arc<Mutex<HashMap<u64, (Response, Vec<u8>)>>>;
+
+struct BuiltResourceRequestHandler {
+    object: *mut RcImpl<_cef_resource_request_handler_t, Self>,
+    interceptors: Option<Arc<RequestInterceptorChain>>,
+    responses: InterceptedResponses,
+}
+
+wrap_rc!(
+    BuiltResourceRequestHandler,
+    WrapResourceRequestHandler,
+    _cef_resource_request_handler_t,
+    interceptors,
+    responses
+);
+
+impl BuiltResourceRequestHandler {
+    fn responses(&self) -> std::sync::MutexGuard<'_, HashMap<u64, (Response, Vec<u8>)>> {
+        self.responses.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl ImplResourceRequestHandler for BuiltResourceRequestHandler {
+    fn on_before_resource_load(
+        &self,
+        _browser: Option<&impl ImplBrowser>,
+        _frame: Option<&impl ImplFrame>,
+        request: Option<&impl ImplRequest>,
+        _callback: Option<&impl ImplCallback>,
+    ) -> ReturnValue {
+        if let (Some(interceptors), Some(request)) = (&self.interceptors, request) {
+            let mut request = Request::from_impl(request);
+            match interceptors.intercept(&mut request) {
+                InterceptResult::Pass => {}
+                InterceptResult::Respond(response, body) => {
+                    let identifier = request.get_identifier();
+                    self.responses().insert(identifier, (response, body));
+                }
+                InterceptResult::Redirect(url) => {
+                    request.set_url(Some(&CefString::from(&CefStringUtf8::from(url.as_str()))));
+                }
+            }
+        }
+        cef_return_value_t::RV_CONTINUE.into()
+    }
+
+    fn get_resource_handler(
+        &self,
+        _browser: Option<&impl ImplBrowser>,
+        _frame: Option<&impl ImplFrame>,
+        request: Option<&impl ImplRequest>,
+    ) -> Option<ResourceHandler> {
+        let (response, body) = self.responses().remove(&request?.get_identifier())?;
+        Some(ResourceHandlerBuilder::from_response(&response, body))
+    }
+
+    fn on_resource_load_complete(
+        &self,
+        _browser: Option<&impl ImplBrowser>,
+        _frame: Option<&impl ImplFrame>,
+        request: Option<&impl ImplRequest>,
+        _response: Option<&impl ImplResponse>,
+        _status: UrlrequestStatus,
+        _received_content_length: i64,
+    ) {
+        // Forget the responses of requests cancelled before they were answered.
+        if let Some(request) = request {
+            self.responses().remove(&request.get_identifier());
+        }
+    }
+
+    fn get_raw(&self) -> *mut _cef_resource_request_handler_t {
+        self.object as *mut _cef_resource_request_handler_t
+    }
+}
+
+struct BuiltRequestHandler {
+    object: *mut RcImpl<_cef_request_handler_t, Self>,
+    handler: ResourceRequestHandler,
+}
+
+wrap_rc!(
+    BuiltRequestHandler,
+    WrapRequestHandler,
+    _cef_request_handler_t,
+    handler
+);
+
+impl ImplRequestHandler for BuiltRequestHandler {
+    fn get_resource_request_handler(
+        &self,
+        _browser: Option<&impl ImplBrowser>,
+        _frame: Option<&impl ImplFrame>,
+        _request: Option<&impl ImplRequest>,
+        _is_navigation: c_int,
+        _is_download: c_int,
+        _request_initiator: Option<&CefStringUtf16>,
+        _disable_default_handling: Option<&mut c_int>,
+    ) -> Option<ResourceRequestHandler> {
+        Some(self.handler.clone())
+    }
+
+    fn get_raw(&self) -> *mut _cef_request_handler_t {
+        self.object as *mut _cef_request_handler_t
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn rule(pattern: &str, headers: &[(&str, &str)]) -> HeaderRule {
+        HeaderRule {
+            pattern: pattern.to_string(),
+            headers: headers
+                .iter()
+                .map(|&(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_inject_for_url_pattern() {
+        let rule = rule("https://api.example.com/*", &[("X-Custom", "value")]);
+        let existing = vec![
+            ("Accept".to_string(), "*/*".to_string()),
+            ("x-custom".to_string(), "old".to_string()),
+        ];
+        assert_eq!(
+            rule.apply("https://api.example.com/v1/items?page=2", existing.clone()),
+            Some(vec![
+                ("Accept".to_string(), "*/*".to_string()),
+                ("X-Custom".to_string(), "value".to_string()),
+            ])
+        );
+        assert_eq!(rule.apply("https://example.com/v1/items", existing), None);
+        assert_eq!(rule.apply("https://api.example.com", vec![]), None);
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*", ""));
+        assert!(glob_match("*", "https://example.com/"));
+        assert!(glob_match(
+            "https://*.example.com/*",
+            "https://a.b.example.com/x"
+        ));
+        assert!(!glob_match(
+            "https://*.example.com/*",
+            "https://example.com/x"
+        ));
+        assert!(glob_match("http?://a/*.js", "https://a/lib/app.js"));
+        assert!(!glob_match("http?://a/*.js", "http://a/app.json"));
+        assert!(glob_match("*a*b", "xaayb"));
+        assert!(!glob_match("a", "ab"));
+    }
+}
@@ -0,0 +1,424 @@
+//! Request context module
+//!
+//! Futures for the maintenance operations of a [`RequestContext`]. Each operation starts when the
+//! method is called, and the returned future resolves once CEF reports its completion.
+//!
+//! [`SessionRequestContext`] creates in-memory request contexts for private browsing sessions.
+
+use std::{future::Future, net::IpAddr, os::raw::c_int};
+
+use cef_sys::{_cef_request_context_handler_t, cef_errorcode_t, cef_thread_id_t};
+
+use crate::{
+    callback::{completion_callback, delete_cookies_callback, resolve_callback},
+    context,
+    future::channel,
+    rc::{wrap_rc, RcImpl},
+    request_context_create_context, thread, CefString, CefStringList, CefStringUtf16,
+    CefStringUtf8, CompletionCallback, DictionaryValue, Errorcode, ImplBrowser, ImplCookieManager,
+    ImplDictionaryValue, ImplFrame, ImplPreferenceManager, ImplRequest, ImplRequestContext,
+    ImplRequestContextHandler, RequestContext, RequestContextHandler, RequestContextSettings,
+    ResourceRequestHandler, Value,
+};
+
+/// The browsing data removed by [`RequestContext::clear_browsing_data`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ClearDataOptions {
+    /// Clear the SSL certificate exceptions allowed by the user.
+    pub certificate_exceptions: bool,
+    /// Clear the HTTP authentication credentials cached for the session.
+    pub http_auth_credentials: bool,
+    /// Close all idle and active connections.
+    pub connections: bool,
+    /// Delete all cookies.
+    pub cookies: bool,
+}
+
+impl ClearDataOptions {
+    /// Clear everything.
+    pub fn all() -> Self {
+        Self {
+            certificate_exceptions: true,
+            http_auth_credentials: true,
+            connections: true,
+            cookies: true,
+        }
+    }
+}
+
+impl RequestContext {
+    /// Clear all SSL certificate exceptions which were allowed by the user.
+    pub fn clear_certificate_exceptions(&self) -> impl Future<Output = ()> {
+        let (callback, completion) = completion();
+        ImplRequestContext::clear_certificate_exceptions(self, Some(&callback));
+        completion
+    }
+
+    /// Clear all HTTP authentication credentials which were cached for the session.
+    pub fn clear_http_auth_credentials(&self) -> impl Future<Output = ()> {
+        let (callback, completion) = completion();
+        ImplRequestContext::clear_http_auth_credentials(self, Some(&callback));
+        completion
+    }
+
+    /// Close all idle and active connections.
+    pub fn close_all_connections(&self) -> impl Future<Output = ()> {
+        let (callback, completion) = completion();
+        ImplRequestContext::close_all_connections(self, Some(&callback));
+        completion
+    }
+
+    /// Delete all cookies of the cookie manager of this context.
+    pub fn clear_cookies(&self) -> impl Future<Output = ()> {
+        let (sender, completion) = channel();
+        let callback = delete_cookies_callback(move |_| sender.send(()));
+        if let Some(cookie_manager) = self.get_cookie_manager(Option::<&CompletionCallback>::None) {
+            cookie_manager.delete_cookies(None, None, Some(&callback));
+        }
+        async move {
+            completion.await;
+        }
+    }
+
+    /// Write the cookies of the cookie manager of this context to disk. Resolves right away if
+    /// the context doesn't persist cookies.
+    pub fn flush_cookies(&self) -> impl Future<Output = ()> {
+        let (callback, completion) = completion();
+        if let Some(cookie_manager) = self.get_cookie_manager(Option::<&CompletionCallback>::None) {
+            cookie_manager.flush_store(Some(&callback));
+        }
+        completion
+    }
+
+    /// Run the operations selected by `options` concurrently, and resolve once all of them
+    /// complete.
+    pub fn clear_browsing_data(&self, options: ClearDataOptions) -> impl Future<Output = ()> {
+        let certificate_exceptions = options
+            .certificate_exceptions
+            .then(|| self.clear_certificate_exceptions());
+        let http_auth_credentials = options
+            .http_auth_credentials
+            .then(|| self.clear_http_auth_credentials());
+        let connections = options.connections.then(|| self.close_all_connections());
+        let cookies = options.cookies.then(|| self.clear_cookies());
+        async move {
+            if let Some(future) = certificate_exceptions {
+                future.await;
+            }
+            if let Some(future) = http_auth_credentials {
+                future.await;
+            }
+            if let Some(future) = connections {
+                future.await;
+            }
+            if let Some(future) = cookies {
+                future.await;
+            }
+        }
+    }
+
+    /// Resolve `hostname`, which may also be an origin like `https://example.com`, to its IP
+    /// addresses.
+    pub fn resolve_host(
+        &self,
+        hostname: &str,
+    ) -> impl Future<Output = Result<Vec<IpAddr>, Errorcode>> {
+        let (sender, completion) = channel();
+        let callback =
+            resolve_callback(move |result, resolved_ips| sender.send((result, resolved_ips)));
+        let origin = if hostname.contains("://") {
+            hostname.to_string()
+        } else {
+            format!("http://{hostname}")
+        };
+        let origin = CefString::from(&CefStringUtf8::from(origin.as_str()));
+        ImplRequestContext::resolve_host(self, Some(&origin), Some(&callback));
+        async move {
+            let (result, resolved_ips) = completion
+                .await
+                .unwrap_or((cef_errorcode_t::ERR_ABORTED.into(), vec![]));
+            parse_resolved_ips(result, &resolved_ips)
+        }
+    }
+}
+
+/// Request contexts for private browsing sessions, which keep their cookies, storage and caches
+/// in memory and apart from every other request context. Everything they stored is gone once
+/// they are released.
+///
+/// Like [`crate::profile::ProfileManager::create_or_open`], the contexts are released in order
+/// when [`crate::context::Context`] shuts down.
+pub struct SessionRequestContext;
+
+impl SessionRequestContext {
+    /// A new request context without a cache path, so nothing it stores reaches the disk.
+    pub fn new_ephemeral() -> Option<RequestContext> {
+        create_in_memory(Option::<&RequestContextHandler>::None)
+    }
+
+    /// A new in-memory request context which starts with the preferences changed in `parent`,
+    /// e.g. its SSL settings, and gets its resource request handlers from the
+    /// [`RequestContextHandler`] of `parent`. Its cookies, storage and certificate exceptions
+    /// are separate, so cookies set in the fork never reach `parent`.
+    pub fn fork_from(parent: &RequestContext) -> Option<RequestContext> {
+        create_in_memory(Some(&ForkHandler::handler(parent.clone())))
+    }
+}
+
+fn create_in_memory(handler: Option<&RequestContextHandler>) -> Option<RequestContext> {
+    // CEF shares the storage, and with it the cookies, of contexts with the same cache path. An
+    // empty one gives each context its own storage in memory.
+    let settings = RequestContextSettings::default();
+    let context = request_context_create_context(Some(&settings), handler)?;
+    context::register_request_context(&context);
+    Some(context)
+}
+
+/// The [`RequestContextHandler`] of [`SessionRequestContext::fork_from`].
+struct ForkHandler {
+    object: *mut RcImpl<_cef_request_context_handler_t, Self>,
+    parent: RequestContext,
+}
+
+wrap_rc!(
+    ForkHandler,
+    WrapRequestContextHandler,
+    _cef_request_context_handler_t,
+    parent
+);
+
+impl ForkHandler {
+    fn handler(parent: RequestContext) -> RequestContextHandler {
+        RequestContextHandler::new(Self {
+            object: std::ptr::null_mut(),
+            parent,
+        })
+    }
+}
+
+impl ImplRequestContextHandler for ForkHandler {
+    fn on_request_context_initialized(&self, request_context: Option<&impl ImplRequestContext>) {
+        let Some(fork) = request_context else {
+            return;
+        };
+        // Only the preferences which differ from their defaults.
+        if let Some(preferences) = self.parent.get_all_preferences(0) {
+            copy_preferences(&preferences, "", fork);
+        }
+    }
+
+    fn get_resource_request_handler(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        frame: Option<&impl ImplFrame>,
+        request: Option<&impl ImplRequest>,
+        is_navigation: c_int,
+        is_download: c_int,
+        request_initiator: Option<&CefStringUtf16>,
+        disable_default_handling: Option<&mut c_int>,
+    ) -> Option<ResourceRequestHandler> {
+        self.parent.get_handler()?.get_resource_request_handler(
+            browser,
+            frame,
+            request,
+            is_navigation,
+            is_download,
+            request_initiator,
+            disable_default_handling,
+        )
+    }
+
+    fn get_raw(&self) -> *mut _cef_request_context_handler_t {
+        self.object as *mut _
+    }
+}
+
+/// Set the preferences of `preferences`, which nests them by the dotted parts of their names, on
+/// `target`. Preferences which `target` doesn't let us set are skipped.
+fn copy_preferences(
+    preferences: &DictionaryValue,
+    prefix: &str,
+    target: &impl ImplPreferenceManager,
+) {
+    let mut keys = CefStringList::new();
+    preferences.get_keys(Some(&mut keys));
+    for key in keys {
+        let name = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        let key = CefString::from(&CefStringUtf8::from(key.as_str()));
+        let cef_name = CefString::from(&CefStringUtf8::from(name.as_str()));
+        if target.can_set_preference(Some(&cef_name)) != 0 {
+            if let Some(value) = preferences.get_value(Some(&key)) {
+                target.set_preference(Some(&cef_name), Some(&value), None);
+            }
+        } else if let Some(nested) = preferences.get_dictionary(Some(&key)) {
+            copy_preferences(&nested, &name, target);
+        }
+    }
+}
+
+/// Set the preference `name` of `context` to `value`. Must be called on the UI thread.
+///
+/// Returns the error message from CEF on failure.
+pub(crate) fn set_preference(
+    context: &RequestContext,
+    name: &str,
+    value: &mut Value,
+) -> Result<(), String> {
+    thread::assert_on_thread(cef_thread_id_t::TID_UI);
+    let name = CefString::from(&CefStringUtf8::from(name));
+    // CEF writes the message into this string, which frees it when it's dropped.
+    let mut error = CefString::from(unsafe { cef_sys::cef_string_userfree_utf16_alloc() });
+    if context.set_preference(Some(&name), Some(value), Some(&mut error)) == 0 {
+        return Err(error.to_string());
+    }
+    Ok(())
+}
+
+fn completion() -> (CompletionCallback, impl Future<Output = ()>) {
+    let (sender, completion) = channel();
+    let callback = completion_callback(move || sender.send(()));
+    (callback, async move {
+        completion.await;
+    })
+}
+
+fn parse_resolved_ips(
+    result: Errorcode,
+    resolved_ips: &[String],
+) -> Result<Vec<IpAddr>, Errorcode> {
+    if result != Errorcode::None {
+        return Err(result);
+    }
+    Ok(resolved_ips
+        .iter()
+        .filter_map(|ip| ip.parse().ok())
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_resolved_ips() {
+        let resolved_ips = ["127.0.0.1", "::1", "not an ip"].map(String::from);
+        let ips = parse_resolved_ips(cef_errorcode_t::ERR_NONE.into(), &resolved_ips).unwrap();
+        assert_eq!(
+            ips,
+            vec![
+                "127.0.0.1".parse::<IpAddr>().unwrap(),
+                "::1".parse::<IpAddr>().unwrap()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_resolve_error() {
+        let result = parse_resolved_ips(cef_errorcode_t::ERR_NAME_NOT_RESOLVED.into(), &[]);
+        assert_eq!(result, Err(cef_errorcode_t::ERR_NAME_NOT_RESOLVED.into()));
+    }
+
+    use std::sync::{Arc, Mutex};
+
+    use cef_sys::{_cef_preference_manager_t, _cef_request_context_t};
+
+    use crate::{dictionary_value_create, ImplValue};
+
+    fn key(name: &str) -> CefStringUtf16 {
+        CefString::from(&CefStringUtf8::from(name))
+    }
+
+    type SetPreferences = Arc<Mutex<Vec<(String, String)>>>;
+
+    /// A request context with the preferences `preferences`, which records the preferences set on
+    /// it in `set`.
+    struct FakeRequestContext {
+        object: *mut RcImpl<_cef_request_context_t, Self>,
+        preferences: Option<DictionaryValue>,
+        settable: Vec<&'static str>,
+        set: SetPreferences,
+    }
+
+    impl ImplPreferenceManager for FakeRequestContext {
+        fn get_all_preferences(&self, _include_defaults: c_int) -> Option<DictionaryValue> {
+            self.preferences.clone()
+        }
+
+        fn can_set_preference(&self, name: Option<&CefStringUtf16>) -> c_int {
+            let name = name.map(ToString::to_string).unwrap_or_default();
+            self.settable.contains(&name.as_str()) as c_int
+        }
+
+        fn set_preference(
+            &self,
+            name: Option<&CefStringUtf16>,
+            value: Option<&impl ImplValue>,
+            _error: Option<&mut CefStringUtf16>,
+        ) -> c_int {
+            let name = name.map(ToString::to_string).unwrap_or_default();
+            let value = value
+                .and_then(|value| value.get_string())
+                .map(|value| value.to_string())
+                .unwrap_or_default();
+            self.set.lock().unwrap().push((name, value));
+            1
+        }
+
+        fn get_raw(&self) -> *mut _cef_preference_manager_t {
+            self.object as *mut _
+        }
+    }
+
+    impl ImplRequestContext for FakeRequestContext {
+        fn get_raw(&self) -> *mut _cef_request_context_t {
+            self.object as *mut _
+        }
+    }
+
+    wrap_rc!(
+        FakeRequestContext,
+        WrapRequestContext,
+        _cef_request_context_t,
+        preferences,
+        settable,
+        set
+    );
+
+    fn fake_context(
+        preferences: Option<DictionaryValue>,
+        settable: Vec<&'static str>,
+    ) -> (RequestContext, SetPreferences) {
+        let set = SetPreferences::default();
+        let context = RequestContext::new(FakeRequestContext {
+            object: std::ptr::null_mut(),
+            preferences,
+            settable,
+            set: set.clone(),
+        });
+        (context, set)
+    }
+
+    #[test]
+    fn test_fork_copies_preferences() {
+        let ssl = dictionary_value_create().unwrap();
+        ssl.set_string(Some(&key("version_min")), Some(&key("tls1.2")));
+        let preferences = dictionary_value_create().unwrap();
+        preferences.set_dictionary(Some(&key("ssl")), Some(&ssl));
+        preferences.set_string(Some(&key("homepage")), Some(&key("about:blank")));
+        let (parent, parent_set) = fake_context(Some(preferences), vec![]);
+        // The fork lets us set the SSL preference, but not the home page.
+        let (fork, fork_set) = fake_context(None, vec!["ssl.version_min"]);
+
+        let handler = ForkHandler::handler(parent);
+        handler.on_request_context_initialized(Some(&fork));
+        assert_eq!(
+            *fork_set.lock().unwrap(),
+            [("ssl.version_min".to_string(), "tls1.2".to_string())]
+        );
+        assert!(parent_set.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,1249 @@
+//! Scheme handler module
+//!
+//! Helpers for answering requests to custom schemes (e.g. `myapp://localhost/index.html`) from
+//! Rust. Register the factories returned here with [`register_scheme_handler_factory`] or
+//! [`ImplRequestContext::register_scheme_handler_factory`].
+//!
+//! [`LocalContentBuilder`] registers a [`StaticFileFactory`] for generated HTML and its assets on
+//! a one-off origin, and removes it again when the returned [`LocalContent`] is dropped.
+//! [`SchemeHandlerFactoryRouter`] combines factories for different paths of the same origin, and
+//! [`CachePolicy`] sets the caching headers of a response, and [`SchemeHandlerCache`] answers
+//! conditional requests for it. [`ResourceHandlerBuilder`] serves a single response from memory
+//! in a factory of your own.
+
+use std::{
+    collections::{hash_map::RandomState, HashMap},
+    hash::{BuildHasher, Hasher},
+    ops::Range,
+    os::raw::c_int,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use cef_sys::{_cef_resource_handler_t, _cef_scheme_handler_factory_t};
+
+use crate::{
+    rc::{from_impl, wrap_rc, RcImpl},
+    *,
+};
+
+from_impl!(Response, ImplResponse);
+
+/// A file served by [`StaticFileFactory`].
+struct StaticFile {
+    mime_type: String,
+    body: Arc<[u8]>,
+    cache: SchemeHandlerCache,
+}
+
+/// A [`SchemeHandlerFactory`] which serves static content from memory, e.g. an embedded web app.
+///
+/// Requests are matched on the URL path, so `myapp://localhost/index.html` is answered by the
+/// route registered for `/index.html`. Paths ending in `/` fall back to `index.html` in that
+/// directory. Single `Range` requests are answered with `206 Partial Content`.
+///
+/// Every response has the `ETag` and `Last-Modified` headers of a [`SchemeHandlerCache`], with the
+/// time the factory was created as the modification time. Requests whose `If-None-Match` or
+/// `If-Modified-Since` header still matches are answered with `304 Not Modified`.
+pub struct StaticFileFactory {
+    object: *mut RcImpl<_cef_scheme_handler_factory_t, Self>,
+    routes: Arc<HashMap<String, StaticFile>>,
+}
+
+wrap_rc!(
+    StaticFileFactory,
+    WrapSchemeHandlerFactory,
+    _cef_scheme_handler_factory_t,
+    routes
+);
+
+impl StaticFileFactory {
+    /// Create a factory from a map of paths to `(mime_type, body_bytes)`. Register it with
+    /// [`SchemeHandlerFactory::new`].
+    pub fn new(routes: HashMap<String, (String, Vec<u8>)>) -> Self {
+        let last_modified = SystemTime::now();
+        let routes = routes
+            .into_iter()
+            .map(|(path, (mime_type, body))| {
+                let path = if path.starts_with('/') {
+                    path
+                } else {
+                    format!("/{path}")
+                };
+                (
+                    path,
+                    StaticFile {
+                        mime_type,
+                        cache: SchemeHandlerCache::new(&body, last_modified),
+                        body: body.into(),
+                    },
+                )
+            })
+            .collect();
+
+        Self {
+            object: std::ptr::null_mut(),
+            routes: Arc::new(routes),
+        }
+    }
+
+    /// Build the response for a request URL and its headers.
+    fn respond(&self, url: &str, request: &RequestHeaders) -> StaticResponse {
+        let path = url_path(url);
+        let file = self.routes.get(&path).or_else(|| {
+            path.ends_with('/')
+                .then(|| self.routes.get(&format!("{path}index.html")))
+                .flatten()
+        });
+        let Some(file) = file else {
+            return StaticResponse::not_found();
+        };
+
+        let len = file.body.len();
+        let mut headers = vec![(String::from("Accept-Ranges"), String::from("bytes"))];
+        headers.extend(
+            file.cache
+                .headers()
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value)),
+        );
+        if file.cache.is_not_modified(
+            request.if_none_match.as_deref(),
+            request.if_modified_since.as_deref(),
+        ) {
+            return StaticResponse {
+                status: 304,
+                status_text: "Not Modified".into(),
+                mime_type: file.mime_type.clone(),
+                headers,
+                body: Arc::from([]),
+                range: 0..0,
+            };
+        }
+
+        match request
+            .range
+            .as_deref()
+            .map(|range| parse_range(range, len))
+        {
+            Some(Ok(Some(range))) => {
+                headers.push((
+                    String::from("Content-Range"),
+                    format!("bytes {}-{}/{len}", range.start, range.end - 1),
+                ));
+                StaticResponse {
+                    status: 206,
+                    status_text: "Partial Content".into(),
+                    mime_type: file.mime_type.clone(),
+                    headers,
+                    body: file.body.clone(),
+                    range,
+                }
+            }
+            Some(Err(())) => {
+                headers.push((String::from("Content-Range"), format!("bytes */{len}")));
+                StaticResponse {
+                    status: 416,
+                    status_text: "Range Not Satisfiable".into(),
+                    mime_type: file.mime_type.clone(),
+                    headers,
+                    body: Arc::from([]),
+                    range: 0..0,
+                }
+            }
+            _ => StaticResponse {
+                headers,
+                ..StaticResponse::ok(file.mime_type.clone(), file.body.clone())
+            },
+        }
+    }
+}
+
+/// Builds the [`ResourceHandler`] of a single static response, e.g. to return from
+/// [`ImplSchemeHandlerFactory::create`] of your own factory.
+///
+/// ```ignore
+/// fn create(&self, ...) -> Option<ResourceHandler> {
+///     Some(ResourceHandlerBuilder::from_string("text/html", render_page()))
+/// }
+/// ```
+pub struct ResourceHandlerBuilder;
+
+impl ResourceHandlerBuilder {
+    /// Serve `data` with `200 OK` and the `mime_type`, e.g. `image/png`.
+    pub fn from_bytes(mime_type: &str, data: Vec<u8>) -> ResourceHandler {
+        StaticResourceHandler::new(StaticResponse::ok(mime_type.to_string(), data.into()))
+    }
+
+    /// Serve `content` with `200 OK` and the `mime_type`, e.g. `text/html`.
+    pub fn from_string(mime_type: &str, content: String) -> ResourceHandler {
+        Self::from_bytes(mime_type, content.into_bytes())
+    }
+
+    /// Serve `data` with the status, status text, MIME type and headers of `response`, e.g. one
+    /// created with [`response_create`]. A response without a status is served as `200`.
+    pub fn from_response(response: &Response, data: Vec<u8>) -> ResourceHandler {
+        let text = |text: Option<CefStringUtf16>| text.map(|text| text.to_string());
+        let mut header_map = CefStringMultimap::new();
+        response.get_header_map(Some(&mut header_map));
+        let body: Arc<[u8]> = data.into();
+        StaticResourceHandler::new(StaticResponse {
+            status: Some(response.get_status())
+                .filter(|&status| status != 0)
+                .unwrap_or(200),
+            status_text: text(response.get_status_text()).unwrap_or_default(),
+            mime_type: text(response.get_mime_type()).unwrap_or_default(),
+            headers: header_map.to_vec(),
+            range: 0..body.len(),
+            body,
+        })
+    }
+}
+
+impl ImplSchemeHandlerFactory for StaticFileFactory {
+    fn create(
+        &self,
+        _browser: Option<&impl ImplBrowser>,
+        _frame: Option<&impl ImplFrame>,
+        _scheme_name: Option<&CefStringUtf16>,
+        request: Option<&impl ImplRequest>,
+    ) -> Option<ResourceHandler> {
+        let request = request?;
+        let url = request.get_url()?.to_string();
+        let headers = RequestHeaders::from_request(request);
+
+        Some(StaticResourceHandler::new(self.respond(&url, &headers)))
+    }
+
+    fn get_raw(&self) -> *mut _cef_scheme_handler_factory_t {
+        self.object as *mut _
+    }
+}
+
+/// The request headers [`StaticFileFactory`] looks at.
+#[derive(Default)]
+struct RequestHeaders {
+    range: Option<String>,
+    if_none_match: Option<String>,
+    if_modified_since: Option<String>,
+}
+
+impl RequestHeaders {
+    fn from_request(request: &impl ImplRequest) -> Self {
+        let header = |name: &str| {
+            request
+                .get_header_by_name(Some(&CefString::from(&CefStringUtf8::from(name))))
+                .map(|value| value.to_string())
+                .filter(|value| !value.is_empty())
+        };
+        Self {
+            range: header("Range"),
+            if_none_match: header("If-None-Match"),
+            if_modified_since: header("If-Modified-Since"),
+        }
+    }
+}
+
+/// Everything [`StaticResourceHandler`] needs to answer a single request.
+struct StaticResponse {
+    status: i32,
+    status_text: String,
+    mime_type: String,
+    headers: Vec<(String, String)>,
+    body: Arc<[u8]>,
+    range: Range<usize>,
+}
+
+impl StaticResponse {
+    fn ok(mime_type: String, body: Arc<[u8]>) -> Self {
+        Self {
+            status: 200,
+            status_text: "OK".into(),
+            mime_type,
+            headers: Vec::new(),
+            range: 0..body.len(),
+            body,
+        }
+    }
+
+    fn not_found() -> Self {
+        Self {
+            status: 404,
+            status_text: "Not Found".into(),
+            mime_type: String::from("text/plain"),
+            headers: Vec::new(),
+            body: Arc::from([]),
+            range: 0..0,
+        }
+    }
+
+    fn content(&self) -> &[u8] {
+        &self.body[self.range.clone()]
+    }
+}
+
+/// The [`ResourceHandler`] created by [`StaticFileFactory`] for each request.
+struct StaticResourceHandler {
+    object: *mut RcImpl<_cef_resource_handler_t, Self>,
+    response: Arc<StaticResponse>,
+    offset: Arc<AtomicUsize>,
+}
+
+wrap_rc!(
+    StaticResourceHandler,
+    WrapResourceHandler,
+    _cef_resource_handler_t,
+    response,
+    offset
+);
+
+impl StaticResourceHandler {
+    #[allow(clippy::new_ret_no_self)]
+    fn new(response: StaticResponse) -> ResourceHandler {
+        ResourceHandler::new(Self {
+            object: std::ptr::null_mut(),
+            response: Arc::new(response),
+            offset: Default::default(),
+        })
+    }
+}
+
+impl ImplResourceHandler for StaticResourceHandler {
+    fn open(
+        &self,
+        _request: Option<&impl ImplRequest>,
+        handle_request: Option<&mut ::std::os::raw::c_int>,
+        _callback: Option<&impl ImplCallback>,
+    ) -> ::std::os::raw::c_int {
+        if let Some(handle_request) = handle_request {
+            *handle_request = 1;
+        }
+        1
+    }
+
+    fn get_response_headers(
+        &self,
+        response: Option<&impl ImplResponse>,
+        response_length: Option<&mut i64>,
+        _redirect_url: Option<&mut CefStringUtf16>,
+    ) {
+        if let Some(response) = response {
+            response.set_status(self.response.status);
+            response.set_status_text(Some(&CefString::from(&CefStringUtf8::from(
+                self.response.status_text.as_str(),
+            ))));
+            response.set_mime_type(Some(&CefString::from(&CefStringUtf8::from(
+                self.response.mime_type.as_str(),
+            ))));
+            let content_length = self.response.range.len().to_string();
+            let headers = self
+                .response
+                .headers
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_str()))
+                .chain([("Content-Length", content_length.as_str())]);
+            for (name, value) in headers {
+                response.set_header_by_name(
+                    Some(&CefString::from(&CefStringUtf8::from(name))),
+                    Some(&CefString::from(&CefStringUtf8::from(value))),
+                    1,
+                );
+            }
+        }
+
+        if let Some(response_length) = response_length {
+            *response_length = self.response.range.len() as i64;
+        }
+    }
+
+    fn skip(
+        &self,
+        bytes_to_skip: i64,
+        bytes_skipped: Option<&mut i64>,
+        _callback: Option<&impl ImplResourceSkipCallback>,
+    ) -> ::std::os::raw::c_int {
+        let remaining = self.response.range.len() - self.offset.load(Ordering::Acquire);
+        let skipped = remaining.min(bytes_to_skip.max(0) as usize);
+        self.offset.fetch_add(skipped, Ordering::AcqRel);
+
+        if let Some(bytes_skipped) = bytes_skipped {
+            *bytes_skipped = skipped as i64;
+        }
+        (skipped > 0) as ::std::os::raw::c_int
+    }
+
+    fn read(
+        &self,
+        data_out: *mut u8,
+        bytes_to_read: ::std::os::raw::c_int,
+        bytes_read: Option<&mut ::std::os::raw::c_int>,
+        _callback: Option<&impl ImplResourceReadCallback>,
+    ) -> ::std::os::raw::c_int {
+        let content = self.response.content();
+        let offset = self.offset.load(Ordering::Acquire);
+        let count = (content.len() - offset).min(bytes_to_read.max(0) as usize);
+        if count > 0 && !data_out.is_null() {
+            unsafe {
+                std::ptr::copy_nonoverlapping(content[offset..].as_ptr(), data_out, count);
+            }
+            self.offset.fetch_add(count, Ordering::AcqRel);
+        }
+
+        if let Some(bytes_read) = bytes_read {
+            *bytes_read = count as ::std::os::raw::c_int;
+        }
+        (count > 0) as ::std::os::raw::c_int
+    }
+
+    fn get_raw(&self) -> *mut _cef_resource_handler_t {
+        self.object as *mut _
+    }
+}
+
+/// How browsers may cache a response, set with [`Response::set_cache_policy`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CachePolicy {
+    /// Never store the response, e.g. for responses with secrets.
+    NoStore,
+    /// Store the response, but revalidate it before each use.
+    NoCache,
+    /// The response never changes while it is fresh, e.g. for assets with a content hash in
+    /// their name.
+    Immutable(Duration),
+    /// The response may change, and is fresh for `max_age`. With `must_revalidate`, a stale
+    /// response is never used without revalidating it.
+    Mutable {
+        max_age: Duration,
+        must_revalidate: bool,
+    },
+}
+
+impl CachePolicy {
+    /// The `Cache-Control` and `Pragma` headers of the policy. `Pragma` is only set for HTTP/1.0
+    /// caches which shouldn't use the response.
+    pub fn headers(&self) -> Vec<(&'static str, String)> {
+        let cache_control = match self {
+            Self::NoStore => String::from("no-store"),
+            Self::NoCache => String::from("no-cache"),
+            Self::Immutable(max_age) => {
+                format!("public, max-age={}, immutable", max_age.as_secs())
+            }
+            Self::Mutable {
+                max_age,
+                must_revalidate,
+            } => {
+                let must_revalidate = if *must_revalidate {
+                    ", must-revalidate"
+                } else {
+                    ""
+                };
+                format!("public, max-age={}{must_revalidate}", max_age.as_secs())
+            }
+        };
+        let mut headers = vec![("Cache-Control", cache_control)];
+        if matches!(self, Self::NoStore | Self::NoCache) {
+            headers.push(("Pragma", String::from("no-cache")));
+        }
+        headers
+    }
+}
+
+/// The validators of a response for conditional requests: an `ETag` hashed from the content, and
+/// the `Last-Modified` time. A client which already has the response sends them back in the
+/// `If-None-Match` and `If-Modified-Since` headers, and [`SchemeHandlerCache::is_not_modified`]
+/// tells if it can be answered with `304 Not Modified` instead of the content.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SchemeHandlerCache {
+    etag: String,
+    last_modified: SystemTime,
+}
+
+impl SchemeHandlerCache {
+    /// The validators of `content`, changed last at `last_modified`. The `ETag` is the hex of the
+    /// xxHash64 of the content with the `xxhash` feature, and of [`DefaultHasher`] without it,
+    /// which may change between Rust versions.
+    ///
+    /// [`DefaultHasher`]: std::hash::DefaultHasher
+    pub fn new(content: &[u8], last_modified: SystemTime) -> Self {
+        // HTTP dates have no fractions of a second.
+        let seconds = last_modified
+            .duration_since(UNIX_EPOCH)
+            .map(|since| since.as_secs())
+            .unwrap_or_default();
+        Self {
+            etag: format!("\"{:016x}\"", content_hash(content)),
+            last_modified: UNIX_EPOCH + Duration::from_secs(seconds),
+        }
+    }
+
+    /// The `ETag` header value, including the quotes.
+    pub fn etag(&self) -> &str {
+        &self.etag
+    }
+
+    pub fn last_modified(&self) -> SystemTime {
+        self.last_modified
+    }
+
+    /// The `ETag` and `Last-Modified` headers.
+    pub fn headers(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("ETag", self.etag.clone()),
+            ("Last-Modified", http_date(self.last_modified)),
+        ]
+    }
+
+    /// `true` if a request with these `If-None-Match` and `If-Modified-Since` header values
+    /// should be answered with `304 Not Modified`. Like RFC 9110 requires, `If-Modified-Since` is
+    /// ignored if the request has an `If-None-Match` header.
+    pub fn is_not_modified(
+        &self,
+        if_none_match: Option<&str>,
+        if_modified_since: Option<&str>,
+    ) -> bool {
+        if let Some(if_none_match) = if_none_match {
+            // `If-None-Match` compares weakly, so `W/"tag"` matches `"tag"`.
+            return if_none_match
+                .split(',')
+                .map(|etag| etag.trim())
+                .any(|etag| etag == "*" || etag.trim_start_matches("W/") == self.etag);
+        }
+        if_modified_since
+            .and_then(parse_http_date)
+            .is_some_and(|since| self.last_modified <= since)
+    }
+}
+
+#[cfg(feature = "xxhash")]
+fn content_hash(content: &[u8]) -> u64 {
+    xxhash_rust::xxh64::xxh64(content, 0)
+}
+
+#[cfg(not(feature = "xxhash"))]
+fn content_hash(content: &[u8]) -> u64 {
+    let mut hasher = std::hash::DefaultHasher::new();
+    hasher.write(content);
+    hasher.finish()
+}
+
+impl Response {
+    /// Set the `Cache-Control` and `Pragma` headers of `policy`, replacing previous values.
+    ///
+    /// Get a [`Response`] from the parameter of
+    /// [`ImplResourceHandler::get_response_headers`] with [`Response::from_impl`].
+    pub fn set_cache_policy(&mut self, policy: CachePolicy) {
+        for (name, value) in policy.headers() {
+            self.set_header_by_name(
+                Some(&CefString::from(&CefStringUtf8::from(name))),
+                Some(&CefString::from(&CefStringUtf8::from(value.as_str()))),
+                1,
+            );
+        }
+    }
+}
+
+/// A [`SchemeHandlerFactory`] which passes each request on to the factory of the first route
+/// whose prefix the URL path starts with, e.g. an API at `/api/`, static files at `/static/` and
+/// the app at `/`. Requests no route matches are not handled.
+///
+/// ```ignore
+/// let router = SchemeHandlerFactoryRouter::new()
+///     .route("/api/", api_factory)
+///     .route("/static/", SchemeHandlerFactory::new(StaticFileFactory::new(assets)))
+///     .route("/", SchemeHandlerFactory::new(StaticFileFactory::new(index)));
+/// register_scheme_handler_factory(Some(&scheme), Some(&host), Some(&SchemeHandlerFactory::new(router)));
+/// ```
+pub struct SchemeHandlerFactoryRouter {
+    object: *mut RcImpl<_cef_scheme_handler_factory_t, Self>,
+    routes: Arc<Vec<(String, SchemeHandlerFactory)>>,
+}
+
+wrap_rc!(
+    SchemeHandlerFactoryRouter,
+    WrapSchemeHandlerFactory,
+    _cef_scheme_handler_factory_t,
+    routes
+);
+
+impl SchemeHandlerFactoryRouter {
+    /// Create a router without routes.
+    pub fn new() -> Self {
+        Self {
+            object: std::ptr::null_mut(),
+            routes: Default::default(),
+        }
+    }
+
+    /// Pass the requests whose path starts with `prefix` to `factory`, unless a route added
+    /// before matches them.
+    pub fn route(mut self, prefix: &str, factory: SchemeHandlerFactory) -> Self {
+        let prefix = format!("/{}", prefix.trim_start_matches('/'));
+        Arc::make_mut(&mut self.routes).push((prefix, factory));
+        self
+    }
+
+    /// The factory of the first route which matches `url`.
+    fn factory(&self, url: &str) -> Option<&SchemeHandlerFactory> {
+        let path = url_path(url);
+        self.routes
+            .iter()
+            .find(|(prefix, _)| path.starts_with(prefix.as_str()))
+            .map(|(_, factory)| factory)
+    }
+}
+
+impl Default for SchemeHandlerFactoryRouter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ImplSchemeHandlerFactory for SchemeHandlerFactoryRouter {
+    fn create(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        frame: Option<&impl ImplFrame>,
+        scheme_name: Option<&CefStringUtf16>,
+        request: Option<&impl ImplRequest>,
+    ) -> Option<ResourceHandler> {
+        let url = request?.get_url()?.to_string();
+        self.factory(&url)?
+            .create(browser, frame, scheme_name, request)
+    }
+
+    fn get_raw(&self) -> *mut _cef_scheme_handler_factory_t {
+        self.object as *mut _
+    }
+}
+
+/// The scheme of the origins created by [`LocalContentBuilder`].
+pub const LOCAL_CONTENT_SCHEME: &str = "app-local";
+
+/// Serves generated HTML and its assets from a unique `app-local://<uuid>/` origin, so relative
+/// references to the assets resolve, and the content gets its own storage.
+///
+/// Register the scheme with [`LocalContentBuilder::register_scheme`] in every process first.
+pub struct LocalContentBuilder {
+    routes: HashMap<String, (String, Vec<u8>)>,
+    request_context: Option<RequestContext>,
+}
+
+impl LocalContentBuilder {
+    /// Serve `html` as `/index.html`.
+    pub fn new(html: &str) -> Self {
+        Self {
+            routes: HashMap::from([(
+                String::from("/index.html"),
+                (String::from("text/html"), html.as_bytes().to_vec()),
+            )]),
+            request_context: None,
+        }
+    }
+
+    /// Serve `body` at `path`, relative to the HTML.
+    pub fn asset(mut self, path: &str, mime_type: &str, body: impl Into<Vec<u8>>) -> Self {
+        let path = format!("/{}", path.trim_start_matches('/'));
+        self.routes
+            .insert(path, (mime_type.to_string(), body.into()));
+        self
+    }
+
+    /// Register the factory with `request_context` instead of the global request context.
+    pub fn request_context(mut self, request_context: RequestContext) -> Self {
+        self.request_context = Some(request_context);
+        self
+    }
+
+    /// Register [`LOCAL_CONTENT_SCHEME`] as a secure standard scheme. Call this from
+    /// [`ImplApp::on_register_custom_schemes`] in every process.
+    pub fn register_scheme(registrar: &impl ImplSchemeRegistrar) -> bool {
+        let options = SchemeOptions::STANDARD
+            | SchemeOptions::SECURE
+            | SchemeOptions::CORS_ENABLED
+            | SchemeOptions::FETCH_ENABLED;
+        registrar.add_custom_scheme(
+            Some(&CefString::from(&CefStringUtf8::from(LOCAL_CONTENT_SCHEME))),
+            options.bits() as c_int,
+        ) != 0
+    }
+
+    /// Register the content on a new origin, or return `None` if CEF rejected the factory.
+    pub fn build(self) -> Option<LocalContent> {
+        let content = LocalContent {
+            host: unique_host(),
+            request_context: self.request_context,
+        };
+        let factory = SchemeHandlerFactory::new(StaticFileFactory::new(self.routes));
+        if content.register(Some(&factory)) == 0 {
+            return None;
+        }
+        context::register_scheme_factories(content.request_context.as_ref());
+        Some(content)
+    }
+}
+
+/// The content registered by [`LocalContentBuilder::build`]. Dropping it removes the factory.
+pub struct LocalContent {
+    host: String,
+    request_context: Option<RequestContext>,
+}
+
+impl LocalContent {
+    /// The `app-local://<uuid>` origin of the content.
+    pub fn origin(&self) -> String {
+        format!("{LOCAL_CONTENT_SCHEME}://{}", self.host)
+    }
+
+    /// The URL of the HTML to load.
+    pub fn url(&self) -> String {
+        format!("{}/index.html", self.origin())
+    }
+
+    fn register(&self, factory: Option<&SchemeHandlerFactory>) -> c_int {
+        let scheme = CefString::from(&CefStringUtf8::from(LOCAL_CONTENT_SCHEME));
+        let host = CefString::from(&CefStringUtf8::from(self.host.as_str()));
+        match &self.request_context {
+            Some(request_context) => {
+                request_context.register_scheme_handler_factory(Some(&scheme), Some(&host), factory)
+            }
+            None => register_scheme_handler_factory(Some(&scheme), Some(&host), factory),
+        }
+    }
+}
+
+impl Drop for LocalContent {
+    fn drop(&mut self) {
+        // The factories were already cleared before CEF shut down.
+        if !context::is_shut_down() {
+            self.register(None);
+        }
+    }
+}
+
+/// A random host in the UUID format, so every [`LocalContent`] has its own origin.
+fn unique_host() -> String {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let random = || {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_usize(COUNTER.fetch_add(1, Ordering::Relaxed));
+        hasher.finish()
+    };
+    let (high, low) = (random(), random());
+    format!(
+        "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+        high >> 32,
+        (high >> 16) & 0xFFFF,
+        high & 0xFFFF,
+        low >> 48,
+        low & 0xFFFF_FFFF_FFFF
+    )
+}
+
+/// Get the decoded path of a URL, without the scheme, authority, query or fragment.
+fn url_path(url: &str) -> String {
+    let url = url.split(['?', '#']).next().unwrap_or_default();
+    let path = match url.split_once("://") {
+        Some((_, rest)) => rest.find('/').map(|index| &rest[index..]).unwrap_or("/"),
+        None => url,
+    };
+
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = (bytes[index] == b'%')
+            .then(|| bytes.get(index + 1..index + 3))
+            .flatten()
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Format `time` as an HTTP date, e.g. `Sun, 06 Nov 1994 08:49:37 GMT`.
+fn http_date(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs())
+        .unwrap_or_default();
+    let (days, seconds) = (seconds / 86400, seconds % 86400);
+    let (year, month, day) = civil_from_days(days as i64);
+    format!(
+        "{}, {day:02} {} {year} {:02}:{:02}:{:02} GMT",
+        // 1970-01-01 was a Thursday.
+        WEEKDAYS[(days % 7) as usize],
+        MONTHS[month as usize - 1],
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// Parse an HTTP date in the format of [`http_date`]. The obsolete RFC 850 and `asctime` formats
+/// aren't supported, so those headers are ignored.
+fn parse_http_date(date: &str) -> Option<SystemTime> {
+    let parts: Vec<_> = date.split_ascii_whitespace().collect();
+    let [_, day, month, year, time, "GMT"] = parts[..] else {
+        return None;
+    };
+    let day = day
+        .parse::<u32>()
+        .ok()
+        .filter(|day| (1..=31).contains(day))?;
+    let month = MONTHS.iter().position(|name| *name == month)? as u32 + 1;
+    let year = year.parse::<i64>().ok()?;
+    let time: Vec<_> = time
+        .split(':')
+        .map(|value| value.parse::<u64>().ok())
+        .collect();
+    let [Some(hours @ 0..=23), Some(minutes @ 0..=59), Some(seconds @ 0..=60)] = time[..] else {
+        return None;
+    };
+    let days = u64::try_from(days_from_civil(year, month, day)).ok()?;
+    Some(UNIX_EPOCH + Duration::from_secs(days * 86400 + hours * 3600 + minutes * 60 + seconds))
+}
+
+/// The days since 1970-01-01 of a date in the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    // Count from March, so the leap day is the last day of the year.
+    let year = if month <= 2 { year - 1 } else { year };
+    let (era, year_of_era) = (year.div_euclid(400), year.rem_euclid(400));
+    let day_of_year = (153 * i64::from((month + 9) % 12) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// The `(year, month, day)` of a number of days since 1970-01-01, see [`days_from_civil`].
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let (era, day_of_era) = (days.div_euclid(146097), days.rem_euclid(146097));
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_from_march + 2) / 5 + 1) as u32;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    } as u32;
+    (year_of_era + era * 400 + i64::from(month <= 2), month, day)
+}
+
+/// Parse a single `bytes=` range against a body of `len` bytes.
+///
+/// Returns `Ok(None)` if the whole body should be sent (unknown units or multiple ranges) and
+/// `Err(())` if the range can't be satisfied.
+fn parse_range(range: &str, len: usize) -> Result<Option<Range<usize>>, ()> {
+    let Some(range) = range.trim().strip_prefix("bytes=") else {
+        return Ok(None);
+    };
+    if range.contains(',') {
+        return Ok(None);
+    }
+    let Some((start, end)) = range.trim().split_once('-') else {
+        return Ok(None);
+    };
+
+    let parse = |value: &str| value.trim().parse::<usize>().map_err(|_| ());
+    match (start.trim(), end.trim()) {
+        ("", "") => Ok(None),
+        ("", suffix) => match parse(suffix)? {
+            0 => Err(()),
+            // An empty body has no last bytes to return.
+            _ if len == 0 => Err(()),
+            suffix => Ok(Some(len.saturating_sub(suffix)..len)),
+        },
+        (start, "") => {
+            let start = parse(start)?;
+            (start < len).then_some(Some(start..len)).ok_or(())
+        }
+        (start, end) => {
+            let (start, end) = (parse(start)?, parse(end)?);
+            (start <= end && start < len)
+                .then_some(Some(start..len.min(end + 1)))
+                .ok_or(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use cef_sys::_cef_response_t;
+
+    use super::*;
+
+    const INDEX_HTML: &[u8] = b"<!DOCTYPE html><html><body><h1>Hello</h1></body></html>";
+
+    fn factory() -> StaticFileFactory {
+        StaticFileFactory::new(HashMap::from([(
+            String::from("/index.html"),
+            (String::from("text/html"), INDEX_HTML.to_vec()),
+        )]))
+    }
+
+    fn range(range: &str) -> RequestHeaders {
+        RequestHeaders {
+            range: Some(range.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn header<'a>(response: &'a StaticResponse, name: &str) -> Option<&'a str> {
+        response
+            .headers
+            .iter()
+            .find(|(header, _)| header == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn read_body(response: StaticResponse) -> Vec<u8> {
+        let handler = StaticResourceHandler::new(response);
+        let mut body = Vec::new();
+        let mut buffer = [0_u8; 7];
+        loop {
+            let mut bytes_read = 0;
+            let more = ImplResourceHandler::read(
+                &handler,
+                buffer.as_mut_ptr(),
+                buffer.len() as _,
+                Some(&mut bytes_read),
+                Option::<&ResourceReadCallback>::None,
+            );
+            if more == 0 {
+                break;
+            }
+            body.extend_from_slice(&buffer[..bytes_read as usize]);
+        }
+        body
+    }
+
+    #[test]
+    fn test_serve_full_body() {
+        let response =
+            factory().respond("myapp://localhost/index.html", &RequestHeaders::default());
+        assert_eq!(response.status, 200);
+        assert_eq!(response.mime_type, "text/html");
+        assert_eq!(read_body(response), INDEX_HTML);
+
+        let response = factory().respond(
+            "myapp://localhost/?query#fragment",
+            &RequestHeaders::default(),
+        );
+        assert_eq!(response.status, 200);
+        assert_eq!(read_body(response), INDEX_HTML);
+    }
+
+    #[test]
+    fn test_serve_range() {
+        let response = factory().respond("myapp://localhost/index.html", &range("bytes=2-9"));
+        assert_eq!(response.status, 206);
+        assert!(response.headers.contains(&(
+            String::from("Content-Range"),
+            format!("bytes 2-9/{}", INDEX_HTML.len())
+        )));
+        assert_eq!(read_body(response), &INDEX_HTML[2..10]);
+
+        let response = factory().respond("myapp://localhost/index.html", &range("bytes=-5"));
+        assert_eq!(read_body(response), &INDEX_HTML[INDEX_HTML.len() - 5..]);
+
+        let response = factory().respond("myapp://localhost/index.html", &range("bytes=1000-"));
+        assert_eq!(response.status, 416);
+    }
+
+    #[test]
+    fn test_serve_range_empty_body() {
+        let factory = StaticFileFactory::new(HashMap::from([(
+            String::from("/empty.txt"),
+            (String::from("text/plain"), vec![]),
+        )]));
+        for value in ["bytes=-5", "bytes=0-", "bytes=0-0"] {
+            let response = factory.respond("myapp://localhost/empty.txt", &range(value));
+            assert_eq!(response.status, 416, "{value}");
+            assert_eq!(header(&response, "Content-Range"), Some("bytes */0"));
+        }
+    }
+
+    #[test]
+    fn test_conditional_request() {
+        let factory = factory();
+        let url = "myapp://localhost/index.html";
+        let response = factory.respond(url, &RequestHeaders::default());
+        assert_eq!(response.status, 200);
+        let etag = header(&response, "ETag").unwrap().to_string();
+        let last_modified = header(&response, "Last-Modified").unwrap().to_string();
+        assert_eq!(etag.len(), 18);
+        assert_eq!(read_body(response), INDEX_HTML);
+
+        let cached = factory.respond(
+            url,
+            &RequestHeaders {
+                if_none_match: Some(etag.clone()),
+                ..Default::default()
+            },
+        );
+        assert_eq!(cached.status, 304);
+        assert_eq!(header(&cached, "ETag"), Some(etag.as_str()));
+        assert!(read_body(cached).is_empty());
+
+        let weak = RequestHeaders {
+            if_none_match: Some(format!("\"other\", W/{etag}")),
+            ..Default::default()
+        };
+        assert_eq!(factory.respond(url, &weak).status, 304);
+
+        // A changed `ETag` wins over an unchanged date.
+        let changed = RequestHeaders {
+            if_none_match: Some(String::from("\"other\"")),
+            if_modified_since: Some(last_modified.clone()),
+            ..Default::default()
+        };
+        assert_eq!(factory.respond(url, &changed).status, 200);
+
+        let since = |date: &str| RequestHeaders {
+            if_modified_since: Some(date.to_string()),
+            ..Default::default()
+        };
+        assert_eq!(factory.respond(url, &since(&last_modified)).status, 304);
+        let before = "Sun, 06 Nov 1994 08:49:37 GMT";
+        assert_eq!(factory.respond(url, &since(before)).status, 200);
+        assert_eq!(factory.respond(url, &since("yesterday")).status, 200);
+    }
+
+    #[test]
+    fn test_http_date() {
+        let time = UNIX_EPOCH + Duration::from_secs(784111777);
+        assert_eq!(http_date(time), "Sun, 06 Nov 1994 08:49:37 GMT");
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"), Some(time));
+        assert_eq!(http_date(UNIX_EPOCH), "Thu, 01 Jan 1970 00:00:00 GMT");
+
+        let leap_day = UNIX_EPOCH + Duration::from_secs(951782400);
+        assert_eq!(http_date(leap_day), "Tue, 29 Feb 2000 00:00:00 GMT");
+        assert_eq!(parse_http_date(&http_date(leap_day)), Some(leap_day));
+
+        assert_eq!(parse_http_date("Sunday, 06-Nov-94 08:49:37 GMT"), None);
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 24:00:00 GMT"), None);
+    }
+
+    #[test]
+    fn test_local_content_origins_are_unique() {
+        let (first, second) = (unique_host(), unique_host());
+        assert_ne!(first, second);
+        assert_eq!(first.len(), 36);
+        assert!(first
+            .chars()
+            .all(|c| c == '-' || c.is_ascii_hexdigit() && !c.is_ascii_uppercase()));
+    }
+
+    #[test]
+    fn test_local_content_routes() {
+        let builder = LocalContentBuilder::new("<script src=\"app.js\"></script>").asset(
+            "app.js",
+            "text/javascript",
+            "run()",
+        );
+        let factory = StaticFileFactory::new(builder.routes);
+        let response = factory.respond("app-local://host/", &RequestHeaders::default());
+        assert_eq!(response.mime_type, "text/html");
+        let response = factory.respond("app-local://host/app.js", &RequestHeaders::default());
+        assert_eq!(response.mime_type, "text/javascript");
+        assert_eq!(read_body(response), b"run()");
+    }
+
+    #[test]
+    fn test_not_found() {
+        let response =
+            factory().respond("myapp://localhost/missing.js", &RequestHeaders::default());
+        assert_eq!(response.status, 404);
+        assert!(read_body(response).is_empty());
+    }
+
+    struct RecordingResponse {
+        object: *mut RcImpl<_cef_response_t, Self>,
+        headers: Arc<std::sync::Mutex<Vec<(String, String)>>>,
+    }
+
+    wrap_rc!(RecordingResponse, WrapResponse, _cef_response_t, headers);
+
+    impl ImplResponse for RecordingResponse {
+        fn set_header_by_name(
+            &self,
+            name: Option<&CefStringUtf16>,
+            value: Option<&CefStringUtf16>,
+            _overwrite: c_int,
+        ) {
+            let header = |value: Option<&CefStringUtf16>| value.map(|value| value.to_string());
+            self.headers.lock().unwrap().push((
+                header(name).unwrap_or_default(),
+                header(value).unwrap_or_default(),
+            ));
+        }
+
+        fn get_raw(&self) -> *mut _cef_response_t {
+            self.object as *mut _
+        }
+    }
+
+    #[test]
+    fn test_resource_handler_from_string() {
+        let handler =
+            ResourceHandlerBuilder::from_string("text/plain", String::from("hello world"));
+
+        let headers = Arc::new(std::sync::Mutex::new(vec![]));
+        let response = Response::new(RecordingResponse {
+            object: std::ptr::null_mut(),
+            headers: headers.clone(),
+        });
+        let mut response_length = 0;
+        handler.get_response_headers(Some(&response), Some(&mut response_length), None);
+        assert_eq!(response_length, 11);
+        assert_eq!(
+            *headers.lock().unwrap(),
+            [(String::from("Content-Length"), String::from("11"))]
+        );
+
+        let mut body = Vec::new();
+        let mut buffer = [0_u8; 16];
+        for (chunk_size, expected) in [(3, 3), (5, 5), (16, 3), (16, 0)] {
+            let mut bytes_read = 0;
+            let more = handler.read(
+                buffer.as_mut_ptr(),
+                chunk_size,
+                Some(&mut bytes_read),
+                Option::<&ResourceReadCallback>::None,
+            );
+            assert_eq!(bytes_read, expected);
+            assert_eq!(more != 0, expected != 0);
+            body.extend_from_slice(&buffer[..bytes_read as usize]);
+        }
+        assert_eq!(body, b"hello world");
+    }
+
+    struct RecordingFactory {
+        object: *mut RcImpl<_cef_scheme_handler_factory_t, Self>,
+        name: &'static str,
+        calls: Arc<std::sync::Mutex<Vec<&'static str>>>,
+    }
+
+    wrap_rc!(
+        RecordingFactory,
+        WrapSchemeHandlerFactory,
+        _cef_scheme_handler_factory_t,
+        name,
+        calls
+    );
+
+    impl ImplSchemeHandlerFactory for RecordingFactory {
+        fn create(
+            &self,
+            _browser: Option<&impl ImplBrowser>,
+            _frame: Option<&impl ImplFrame>,
+            _scheme_name: Option<&CefStringUtf16>,
+            _request: Option<&impl ImplRequest>,
+        ) -> Option<ResourceHandler> {
+            self.calls.lock().unwrap().push(self.name);
+            None
+        }
+
+        fn get_raw(&self) -> *mut _cef_scheme_handler_factory_t {
+            self.object as *mut _
+        }
+    }
+
+    #[test]
+    fn test_router_prefixes() {
+        let calls = Arc::new(std::sync::Mutex::new(vec![]));
+        let factory = |name| {
+            SchemeHandlerFactory::new(RecordingFactory {
+                object: std::ptr::null_mut(),
+                name,
+                calls: calls.clone(),
+            })
+        };
+        let router = SchemeHandlerFactoryRouter::new()
+            .route("/api/", factory("api"))
+            .route("static/", factory("static"))
+            .route("/", factory("index"));
+
+        for url in [
+            "myapp://localhost/api/users?id=1",
+            "myapp://localhost/static/app.js",
+            "myapp://localhost/",
+            "myapp://localhost/apiary",
+        ] {
+            router.factory(url).unwrap().create(
+                Option::<&Browser>::None,
+                Option::<&Frame>::None,
+                None,
+                Option::<&Request>::None,
+            );
+        }
+        assert_eq!(*calls.lock().unwrap(), ["api", "static", "index", "index"]);
+
+        let router = SchemeHandlerFactoryRouter::new().route("/api/", factory("api"));
+        assert!(router.factory("myapp://localhost/index.html").is_none());
+    }
+
+    #[test]
+    fn test_cache_policy_headers() {
+        assert_eq!(
+            CachePolicy::Immutable(Duration::from_secs(31536000)).headers(),
+            [(
+                "Cache-Control",
+                String::from("public, max-age=31536000, immutable")
+            )]
+        );
+        assert_eq!(
+            CachePolicy::Mutable {
+                max_age: Duration::from_secs(60),
+                must_revalidate: true,
+            }
+            .headers(),
+            [(
+                "Cache-Control",
+                String::from("public, max-age=60, must-revalidate")
+            )]
+        );
+        assert_eq!(
+            CachePolicy::Mutable {
+                max_age: Duration::from_millis(1500),
+                must_revalidate: false,
+            }
+            .headers(),
+            [("Cache-Control", String::from("public, max-age=1"))]
+        );
+        assert_eq!(
+            CachePolicy::NoStore.headers(),
+            [
+                ("Cache-Control", String::from("no-store")),
+                ("Pragma", String::from("no-cache")),
+            ]
+        );
+        assert_eq!(
+            CachePolicy::NoCache.headers(),
+            [
+                ("Cache-Control", String::from("no-cache")),
+                ("Pragma", String::from("no-cache")),
+            ]
+        );
+    }
+}
@@ -0,0 +1,95 @@
+//! Shutdown module
+//!
+//! [CleanShutdown] writes the cookies to disk and closes the network connections of a
+//! [RequestContext] before the message loop quits, so nothing is lost when CEF shuts down.
+
+use std::future::Future;
+
+use crate::{quit_message_loop, RequestContext};
+
+/// The steps to run before [crate::shutdown].
+pub struct CleanShutdown;
+
+impl CleanShutdown {
+    /// Flush the cookies of `context`, then close its connections, and then quit the message
+    /// loop, each step once the previous one completed. Use
+    /// [crate::request_context_get_global_context] unless the browsers have their own context.
+    ///
+    /// Start it and poll the future on the UI thread while [crate::run_message_loop] runs. Once
+    /// the message loop returns, drop the [crate::context::Context] or call [crate::shutdown].
+    pub fn run(context: &RequestContext) -> impl Future<Output = ()> {
+        let connections = context.clone();
+        in_order(
+            context.flush_cookies(),
+            move || connections.close_all_connections(),
+            quit_message_loop,
+        )
+    }
+}
+
+/// Wait for `flushed`, then start `close` and wait for it, and then `quit`.
+async fn in_order<F: Future<Output = ()>>(
+    flushed: impl Future<Output = ()>,
+    close: impl FnOnce() -> F,
+    quit: impl FnOnce(),
+) {
+    flushed.await;
+    close().await;
+    quit();
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        pin::pin,
+        sync::{Arc, Mutex},
+        task::{Context, Poll, Waker},
+    };
+
+    use super::*;
+    use crate::future::channel;
+
+    #[test]
+    fn test_steps_in_order() {
+        let steps = Arc::new(Mutex::new(vec![]));
+        let step = |name: &'static str| {
+            let steps = steps.clone();
+            move || steps.lock().unwrap().push(name)
+        };
+        let (flush_sender, flush_completion) = channel::<()>();
+        let (close_sender, close_completion) = channel::<()>();
+
+        let flushed = step("flushed");
+        let close_started = step("close started");
+        let closed = step("closed");
+        let mut shutdown = pin!(in_order(
+            async move {
+                flush_completion.await;
+                flushed();
+            },
+            move || {
+                close_started();
+                async move {
+                    close_completion.await;
+                    closed();
+                }
+            },
+            step("quit"),
+        ));
+        let mut cx = Context::from_waker(Waker::noop());
+
+        assert!(shutdown.as_mut().poll(&mut cx).is_pending());
+        assert!(steps.lock().unwrap().is_empty());
+
+        flush_sender.send(());
+        assert!(shutdown.as_mut().poll(&mut cx).is_pending());
+        assert_eq!(*steps.lock().unwrap(), ["flushed", "close started"]);
+
+        close_sender.send(());
+        assert_eq!(shutdown.as_mut().poll(&mut cx), Poll::Ready(()));
+        assert_eq!(
+            *steps.lock().unwrap(),
+            ["flushed", "close started", "closed", "quit"]
+        );
+    }
+}
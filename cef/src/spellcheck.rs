@@ -0,0 +1,75 @@
+//! Spell checking module
+
+use std::os::raw::c_int;
+
+use crate::{
+    list_value_create, request_context, string_list_alloc, value_create, BrowserHost, CefString,
+    CefStringUtf8, ContextMenuParams, ImplBrowserHost, ImplContextMenuParams, ImplListValue,
+    ImplValue, RequestContext,
+};
+
+/// Preference which turns spell checking on or off.
+pub const ENABLE_SPELLCHECKING: &str = "browser.enable_spellchecking";
+
+/// Preference which holds the list of dictionary languages.
+pub const DICTIONARIES: &str = "spellcheck.dictionaries";
+
+/// Set the spell checking languages of a [RequestContext], e.g. `&["en-US", "de"]`.
+///
+/// Spell checking is enabled when `languages` is not empty and disabled otherwise.
+/// Must be called on the UI thread. Returns the error message from CEF on failure.
+pub fn set_languages(context: &RequestContext, languages: &[&str]) -> Result<(), String> {
+    let list = list_value_create().ok_or_else(|| "Failed to create value".to_string())?;
+    for (index, language) in languages.iter().enumerate() {
+        let language = CefString::from(&CefStringUtf8::from(*language));
+        list.set_string(index, Some(&language));
+    }
+    let mut value = value_create().ok_or_else(|| "Failed to create value".to_string())?;
+    value.set_list(Some(&list));
+    request_context::set_preference(context, DICTIONARIES, &mut value)?;
+
+    set_enabled(context, !languages.is_empty())
+}
+
+/// Turn spell checking on or off for a [RequestContext].
+///
+/// Must be called on the UI thread. Returns the error message from CEF on failure.
+pub fn set_enabled(context: &RequestContext, enabled: bool) -> Result<(), String> {
+    let mut value = value_create().ok_or_else(|| "Failed to create value".to_string())?;
+    value.set_bool(enabled as c_int);
+    request_context::set_preference(context, ENABLE_SPELLCHECKING, &mut value)
+}
+
+impl BrowserHost {
+    /// Add `word` to the spell checking dictionary.
+    pub fn add_word_to_dictionary(&self, word: &str) {
+        let word = CefString::from(&CefStringUtf8::from(word));
+        ImplBrowserHost::add_word_to_dictionary(self, Some(&word));
+    }
+
+    /// Replace the currently selected misspelled word with `word`.
+    pub fn replace_misspelling(&self, word: &str) {
+        let word = CefString::from(&CefStringUtf8::from(word));
+        ImplBrowserHost::replace_misspelling(self, Some(&word));
+    }
+}
+
+impl ContextMenuParams {
+    /// The misspelled word under the context menu, or an empty string if there is none.
+    pub fn misspelled_word(&self) -> String {
+        self.get_misspelled_word()
+            .map(|word| word.to_string())
+            .unwrap_or_default()
+    }
+
+    /// The dictionary suggestions for [ContextMenuParams::misspelled_word].
+    pub fn dictionary_suggestions(&self) -> Vec<String> {
+        let Some(mut suggestions) = string_list_alloc() else {
+            return vec![];
+        };
+        if self.get_dictionary_suggestions(Some(&mut suggestions)) == 0 {
+            return vec![];
+        }
+        suggestions.into_iter().collect()
+    }
+}
@@ -0,0 +1,260 @@
+//! SSL status module
+//!
+//! Typed SSL information of navigation entries, and the [`SecurityState`] summary shown by a
+//! padlock indicator.
+
+use cef_sys::{cef_cert_status_t, cef_ssl_content_status_t, cef_ssl_version_t};
+
+use crate::{
+    Browser, ImplBrowser, ImplBrowserHost, ImplNavigationEntry, ImplSslstatus, NavigationEntry,
+    SslVersion, Sslstatus, X509certificate,
+};
+
+/// Errors in [`CertStatusFlags`], like `CERT_STATUS_ALL_ERRORS` in Chromium.
+const CERT_STATUS_ALL_ERRORS: u32 = 0xFF00_FFFF;
+
+/// A combination of [`cef_cert_status_t`] flags.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct CertStatusFlags(pub u32);
+
+impl CertStatusFlags {
+    /// `true` if `flag` is set.
+    pub fn contains(self, flag: cef_cert_status_t) -> bool {
+        self.0 & flag.0 != 0
+    }
+
+    /// `true` if any of the flags is a certificate error.
+    pub fn is_error(self) -> bool {
+        self.0 & CERT_STATUS_ALL_ERRORS != 0
+    }
+}
+
+/// A combination of [`cef_ssl_content_status_t`] flags.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SslContentStatusFlags(pub u32);
+
+impl SslContentStatusFlags {
+    /// `true` if `flag` is set.
+    pub fn contains(self, flag: cef_ssl_content_status_t) -> bool {
+        self.0 & flag.0 != 0
+    }
+}
+
+/// The SSL information of a [`NavigationEntry`].
+#[derive(Clone)]
+pub struct SslStatus(Sslstatus);
+
+impl SslStatus {
+    /// `true` if the status is for a secure connection.
+    pub fn is_secure_connection(&self) -> bool {
+        self.0.is_secure_connection() != 0
+    }
+
+    /// The certificate status flags.
+    pub fn cert_status(&self) -> CertStatusFlags {
+        CertStatusFlags(self.0.get_cert_status().bits())
+    }
+
+    /// The SSL version of the connection.
+    pub fn ssl_version(&self) -> SslVersion {
+        self.0.get_sslversion()
+    }
+
+    /// The content status flags, e.g. if mixed content was displayed or run.
+    pub fn content_status(&self) -> SslContentStatusFlags {
+        SslContentStatusFlags(self.0.get_content_status().bits())
+    }
+
+    /// The X.509 certificate of the connection.
+    pub fn certificate(&self) -> Option<X509certificate> {
+        self.0.get_x509certificate()
+    }
+
+    /// The [`SecurityState`] of a page loaded from `url` with this status.
+    pub fn security_state(&self, url: &str) -> Option<SecurityState> {
+        security_state(
+            url,
+            self.is_secure_connection(),
+            self.cert_status(),
+            self.ssl_version().into(),
+            self.content_status(),
+        )
+    }
+}
+
+impl From<Sslstatus> for SslStatus {
+    fn from(value: Sslstatus) -> Self {
+        Self(value)
+    }
+}
+
+impl NavigationEntry {
+    /// The SSL information of the entry.
+    pub fn ssl_status(&self) -> Option<SslStatus> {
+        self.get_sslstatus().map(SslStatus)
+    }
+}
+
+/// A reason for [`SecurityState::Broken`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SecurityIssue {
+    /// The certificate has an error, see [`SslStatus::cert_status`].
+    CertificateError(CertStatusFlags),
+    /// The certificate chain has a SHA-1 signature.
+    Sha1Signature,
+    /// The connection uses a protocol older than TLS 1.2.
+    ObsoleteProtocol,
+    /// Scripts or other active content were loaded over an insecure connection.
+    RanInsecureContent,
+    /// Images or other passive content were loaded over an insecure connection.
+    DisplayedInsecureContent,
+}
+
+/// The security summary of a page, following the rules of Chrome's lock icon.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SecurityState {
+    /// Loaded over a valid secure connection without mixed content.
+    Secure,
+    /// Loaded over plain HTTP.
+    Insecure,
+    /// Loaded over HTTPS with issues which break the lock icon.
+    Broken { reasons: Vec<SecurityIssue> },
+}
+
+impl Browser {
+    /// The [`SecurityState`] of the current navigation entry, or `None` if there is no entry or
+    /// its URL isn't an HTTP(S) or WebSocket URL.
+    pub fn security_state(&self) -> Option<SecurityState> {
+        let entry = self.get_host()?.get_visible_navigation_entry()?;
+        let url = entry.get_url()?.to_string();
+        match entry.ssl_status() {
+            Some(status) => status.security_state(&url),
+            None => security_state(
+                &url,
+                false,
+                Default::default(),
+                cef_ssl_version_t::SSL_CONNECTION_VERSION_UNKNOWN,
+                Default::default(),
+            ),
+        }
+    }
+}
+
+fn security_state(
+    url: &str,
+    is_secure_connection: bool,
+    cert_status: CertStatusFlags,
+    ssl_version: cef_ssl_version_t,
+    content_status: SslContentStatusFlags,
+) -> Option<SecurityState> {
+    let scheme = url.split_once(':')?.0.to_ascii_lowercase();
+    match scheme.as_str() {
+        "http" | "ws" => return Some(SecurityState::Insecure),
+        "https" | "wss" => {}
+        _ => return None,
+    }
+    if !is_secure_connection {
+        return Some(SecurityState::Insecure);
+    }
+
+    let mut reasons = vec![];
+    if cert_status.is_error() {
+        reasons.push(SecurityIssue::CertificateError(cert_status));
+    }
+    if cert_status.contains(cef_cert_status_t::CERT_STATUS_SHA1_SIGNATURE_PRESENT) {
+        reasons.push(SecurityIssue::Sha1Signature);
+    }
+    if (ssl_version as u32) < cef_ssl_version_t::SSL_CONNECTION_VERSION_TLS1_2 as u32
+        || ssl_version == cef_ssl_version_t::SSL_CONNECTION_VERSION_UNKNOWN
+    {
+        reasons.push(SecurityIssue::ObsoleteProtocol);
+    }
+    if content_status.contains(cef_ssl_content_status_t::SSL_CONTENT_RAN_INSECURE_CONTENT) {
+        reasons.push(SecurityIssue::RanInsecureContent);
+    }
+    if content_status.contains(cef_ssl_content_status_t::SSL_CONTENT_DISPLAYED_INSECURE_CONTENT) {
+        reasons.push(SecurityIssue::DisplayedInsecureContent);
+    }
+
+    Some(if reasons.is_empty() {
+        SecurityState::Secure
+    } else {
+        SecurityState::Broken { reasons }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TLS1_3: cef_ssl_version_t = cef_ssl_version_t::SSL_CONNECTION_VERSION_TLS1_3;
+
+    #[test]
+    fn test_secure() {
+        assert_eq!(
+            security_state(
+                "https://example.com",
+                true,
+                Default::default(),
+                TLS1_3,
+                Default::default()
+            ),
+            Some(SecurityState::Secure)
+        );
+    }
+
+    #[test]
+    fn test_insecure() {
+        assert_eq!(
+            security_state(
+                "http://example.com",
+                false,
+                Default::default(),
+                TLS1_3,
+                Default::default()
+            ),
+            Some(SecurityState::Insecure)
+        );
+        assert_eq!(
+            security_state(
+                "file:///index.html",
+                false,
+                Default::default(),
+                TLS1_3,
+                Default::default()
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_broken() {
+        let cert_status = CertStatusFlags(
+            cef_cert_status_t::CERT_STATUS_DATE_INVALID.0 | cef_cert_status_t::CERT_STATUS_IS_EV.0,
+        );
+        let content_status = SslContentStatusFlags(
+            cef_ssl_content_status_t::SSL_CONTENT_DISPLAYED_INSECURE_CONTENT.0,
+        );
+        assert_eq!(
+            security_state(
+                "https://example.com",
+                true,
+                cert_status,
+                cef_ssl_version_t::SSL_CONNECTION_VERSION_TLS1,
+                content_status
+            ),
+            Some(SecurityState::Broken {
+                reasons: vec![
+                    SecurityIssue::CertificateError(cert_status),
+                    SecurityIssue::ObsoleteProtocol,
+                    SecurityIssue::DisplayedInsecureContent,
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_ev_is_not_an_error() {
+        assert!(!CertStatusFlags(cef_cert_status_t::CERT_STATUS_IS_EV.0).is_error());
+    }
+}
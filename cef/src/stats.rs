@@ -0,0 +1,813 @@
+//! Statistics module
+//!
+//! [BrowserStatistics] reads the performance counters of a browser's main frame through the
+//! DevTools `Performance` domain. [ResourceStats] counts the network requests of each main frame
+//! navigation.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    future::Future,
+    os::raw::c_int,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use cef_sys::{
+    _cef_load_handler_t, _cef_request_handler_t, _cef_resource_request_handler_t,
+    cef_return_value_t,
+};
+
+use crate::{
+    compat,
+    devtools::{json, DevToolsError},
+    dictionary_value_create,
+    rc::{wrap_rc, RcImpl},
+    BrowserHost, CefString, CefStringUtf16, CefStringUtf8, DictionaryValue, ImplBrowser,
+    ImplCallback, ImplDictionaryValue, ImplFrame, ImplLoadHandler, ImplRequest, ImplRequestHandler,
+    ImplResourceRequestHandler, ImplResponse, LoadHandler, RequestHandler, ResourceRequestHandler,
+    ResourceType, ReturnValue, UrlrequestStatus,
+};
+
+/// The performance counters of a page, see `Performance.getMetrics`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BrowserMetrics {
+    pub js_heap_used_size: u64,
+    pub js_heap_total_size: u64,
+    /// The `performance.memory.jsHeapSizeLimit` of the main frame.
+    pub js_heap_size_limit: u64,
+    pub documents: u32,
+    pub frames: u32,
+    pub layout_count: u32,
+    pub recalc_style_count: u32,
+    /// Seconds spent running tasks on the main thread of the renderer.
+    pub task_duration: f64,
+}
+
+impl BrowserMetrics {
+    /// Read the `metrics` of a `Performance.getMetrics` result. Missing metrics are left at 0.
+    fn from_performance_metrics(result: &[u8]) -> Result<Self, DevToolsError> {
+        let mut metrics = Self::default();
+        let result = json::parse(result)?;
+        for metric in json::elements(&result, &["metrics"])? {
+            let value = json::number(&metric, &["value"])?;
+            match json::string(&metric, &["name"])?.as_str() {
+                "JSHeapUsedSize" => metrics.js_heap_used_size = value as u64,
+                "JSHeapTotalSize" => metrics.js_heap_total_size = value as u64,
+                "Documents" => metrics.documents = value as u32,
+                "Frames" => metrics.frames = value as u32,
+                "LayoutCount" => metrics.layout_count = value as u32,
+                "RecalcStyleCount" => metrics.recalc_style_count = value as u32,
+                "TaskDuration" => metrics.task_duration = value,
+                _ => {}
+            }
+        }
+        Ok(metrics)
+    }
+}
+
+impl fmt::Display for BrowserMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows = [
+            ("JS heap used", format!("{} bytes", self.js_heap_used_size)),
+            (
+                "JS heap total",
+                format!("{} bytes", self.js_heap_total_size),
+            ),
+            (
+                "JS heap limit",
+                format!("{} bytes", self.js_heap_size_limit),
+            ),
+            ("Documents", self.documents.to_string()),
+            ("Frames", self.frames.to_string()),
+            ("Layouts", self.layout_count.to_string()),
+            ("Style recalcs", self.recalc_style_count.to_string()),
+            ("Task duration", format!("{:.3} s", self.task_duration)),
+        ];
+        writeln!(f, "{:<14} {:>20}", "Metric", "Value")?;
+        writeln!(f, "{:-<14} {:->20}", "", "")?;
+        for (name, value) in rows {
+            writeln!(f, "{name:<14} {value:>20}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Collects [BrowserMetrics].
+pub struct BrowserStatistics;
+
+impl BrowserStatistics {
+    /// Collect the metrics of the page loaded in `host`. Must be called on the UI thread after
+    /// the first navigation, otherwise the metrics resolve to 0.
+    pub fn collect(host: &BrowserHost) -> impl Future<Output = BrowserMetrics> {
+        // DevTools runs the methods in order, so the domain is enabled before reading it.
+        let enabled = host.execute_dev_tools("Performance.enable", None);
+        let metrics = host.execute_dev_tools("Performance.getMetrics", None);
+        let heap_size_limit = host.execute_dev_tools("Runtime.evaluate", heap_size_limit_params());
+
+        async move {
+            let _ = enabled.await;
+            let mut metrics = metrics
+                .await
+                .and_then(|result| BrowserMetrics::from_performance_metrics(&result))
+                .unwrap_or_default();
+            if let Ok(limit) = heap_size_limit
+                .await
+                .and_then(|result| json::number(&json::parse(&result)?, &["result", "value"]))
+            {
+                metrics.js_heap_size_limit = limit as u64;
+            }
+            metrics
+        }
+    }
+}
+
+/// The `Runtime.evaluate` parameters reading `performance.memory.jsHeapSizeLimit`.
+fn heap_size_limit_params() -> Option<DictionaryValue> {
+    let key = |value: &str| CefString::from(&CefStringUtf8::from(value));
+    let params = dictionary_value_create()?;
+    params.set_string(
+        Some(&key("expression")),
+        Some(&key("performance.memory.jsHeapSizeLimit")),
+    );
+    params.set_bool(Some(&key("returnByValue")), 1);
+    Some(params)
+}
+
+/// The network requests of a main frame navigation, see [ResourceStats].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NavigationStats {
+    /// See [crate::Browser::identifier].
+    pub browser_id: i32,
+    /// The identifier of the main frame, see [crate::Frame::identifier].
+    pub frame_id: String,
+    /// Counts the navigations seen by the [ResourceStats], starting at 1.
+    pub navigation_id: u64,
+    /// The URL the navigation started with, before redirects.
+    pub url: String,
+    /// Requests started by the page, including the document. A redirect continues its request.
+    pub requests: u32,
+    /// Requests which failed or were canceled.
+    pub failed: u32,
+    pub redirects: u32,
+    /// Response bytes read by the completed requests.
+    pub bytes: u64,
+    /// [NavigationStats::bytes] by the MIME type of the response, `""` if it had none.
+    pub by_mime: HashMap<String, u64>,
+    /// From the start of the navigation to its completion, or to now while it is in progress.
+    pub duration: Duration,
+}
+
+type NavigationCallback = Arc<dyn Fn(&NavigationStats) + Send + Sync>;
+
+/// Counts the requests, failures and bytes of each main frame navigation, without a proxy.
+///
+/// Pass [ResourceStats::request_handler] and [ResourceStats::load_handler] to the
+/// [crate::client::ClientBuilder]. A navigation completes once its document and all requests
+/// started so far are complete, or when the browser stops loading, and
+/// [ResourceStats::on_navigation_complete] is notified. Requests which never complete, e.g. some
+/// answered by a service worker, are dropped then, so they are counted but add no bytes. A
+/// navigation replaced by the next one before it completed is reported as it is.
+#[derive(Clone, Default)]
+pub struct ResourceStats {
+    collector: Arc<Mutex<Collector>>,
+    callbacks: Arc<Mutex<Vec<NavigationCallback>>>,
+}
+
+impl ResourceStats {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// The [RequestHandler] which starts navigations and counts their requests.
+    pub fn request_handler(&self) -> RequestHandler {
+        RequestHandler::new(StatsRequestHandler {
+            object: std::ptr::null_mut(),
+            stats: self.clone(),
+        })
+    }
+
+    /// The [LoadHandler] which completes the navigation when the browser stops loading.
+    pub fn load_handler(&self) -> LoadHandler {
+        LoadHandler::new(StatsLoadHandler {
+            object: std::ptr::null_mut(),
+            stats: self.clone(),
+        })
+    }
+
+    /// Call `callback` with the statistics of every completed navigation, on the thread which
+    /// completed it.
+    pub fn on_navigation_complete(
+        &self,
+        callback: impl Fn(&NavigationStats) + Send + Sync + 'static,
+    ) {
+        if let Ok(mut callbacks) = self.callbacks.lock() {
+            callbacks.push(Arc::new(callback));
+        }
+    }
+
+    /// The statistics of the latest navigation of the browser `browser_id`.
+    pub fn snapshot(&self, browser_id: i32) -> Option<NavigationStats> {
+        self.collector
+            .lock()
+            .ok()?
+            .snapshot(browser_id, Instant::now())
+    }
+
+    /// Drop the statistics of a closed browser, e.g. in
+    /// [crate::ImplLifeSpanHandler::on_before_close].
+    pub fn forget(&self, browser_id: i32) {
+        if let Ok(mut collector) = self.collector.lock() {
+            collector.forget(browser_id);
+        }
+    }
+
+    /// Update the collector, and notify the callbacks of the navigation it completed.
+    fn update(&self, event: impl FnOnce(&mut Collector, Instant) -> Option<NavigationStats>) {
+        let completed = match self.collector.lock() {
+            Ok(mut collector) => event(&mut collector, Instant::now()),
+            Err(_) => None,
+        };
+        let Some(completed) = completed else {
+            return;
+        };
+        // Don't hold the lock in callbacks, which may register other callbacks.
+        let callbacks = self
+            .callbacks
+            .lock()
+            .map(|callbacks| callbacks.clone())
+            .unwrap_or_default();
+        for callback in callbacks {
+            callback(&completed);
+        }
+    }
+}
+
+struct Navigation {
+    stats: NavigationStats,
+    started: Instant,
+    completed: Option<Instant>,
+    document_loaded: bool,
+    pending: usize,
+}
+
+impl Navigation {
+    fn snapshot(&self, now: Instant) -> NavigationStats {
+        let end = self.completed.unwrap_or(now);
+        NavigationStats {
+            duration: end.saturating_duration_since(self.started),
+            ..self.stats.clone()
+        }
+    }
+}
+
+struct PendingRequest {
+    browser_id: i32,
+    navigation_id: u64,
+    is_document: bool,
+    mime: String,
+}
+
+/// The statistics of the latest navigation of each browser, updated from the request events.
+#[derive(Default)]
+struct Collector {
+    navigations: HashMap<i32, Navigation>,
+    requests: HashMap<u64, PendingRequest>,
+    last_navigation_id: u64,
+}
+
+impl Collector {
+    /// A main frame navigation started, which replaces the previous one of the browser.
+    fn begin(
+        &mut self,
+        browser_id: i32,
+        frame_id: &str,
+        url: &str,
+        now: Instant,
+    ) -> Option<NavigationStats> {
+        self.last_navigation_id += 1;
+        let navigation = Navigation {
+            stats: NavigationStats {
+                browser_id,
+                frame_id: frame_id.to_string(),
+                navigation_id: self.last_navigation_id,
+                url: url.to_string(),
+                ..Default::default()
+            },
+            started: now,
+            completed: None,
+            document_loaded: false,
+            pending: 0,
+        };
+        let replaced = self.navigations.insert(browser_id, navigation)?;
+        self.drop_requests(replaced.stats.navigation_id);
+        replaced.completed.is_none().then(|| replaced.snapshot(now))
+    }
+
+    /// The request `request_id` started, or restarted after a redirect.
+    fn start(&mut self, browser_id: i32, request_id: u64, is_document: bool) {
+        if self.requests.contains_key(&request_id) {
+            return;
+        }
+        let Some(navigation) = self.navigations.get_mut(&browser_id) else {
+            return;
+        };
+        navigation.stats.requests += 1;
+        navigation.pending += 1;
+        self.requests.insert(
+            request_id,
+            PendingRequest {
+                browser_id,
+                navigation_id: navigation.stats.navigation_id,
+                is_document,
+                mime: String::new(),
+            },
+        );
+    }
+
+    fn redirect(&mut self, request_id: u64) {
+        if let Some(navigation) = self.navigation_of(request_id) {
+            navigation.stats.redirects += 1;
+        }
+    }
+
+    fn response(&mut self, request_id: u64, mime: &str) {
+        if let Some(request) = self.requests.get_mut(&request_id) {
+            request.mime = mime.to_string();
+        }
+    }
+
+    /// The request `request_id` completed after reading `bytes`.
+    fn complete(
+        &mut self,
+        request_id: u64,
+        success: bool,
+        bytes: u64,
+        now: Instant,
+    ) -> Option<NavigationStats> {
+        let request = self.requests.remove(&request_id)?;
+        let navigation = self
+            .navigations
+            .get_mut(&request.browser_id)
+            .filter(|navigation| navigation.stats.navigation_id == request.navigation_id)?;
+        navigation.pending -= 1;
+        navigation.document_loaded |= request.is_document;
+        let stats = &mut navigation.stats;
+        if !success {
+            stats.failed += 1;
+        }
+        stats.bytes += bytes;
+        *stats.by_mime.entry(request.mime).or_default() += bytes;
+
+        if navigation.document_loaded && navigation.pending == 0 {
+            Self::finish(navigation, now)
+        } else {
+            None
+        }
+    }
+
+    /// The browser stopped loading, so the requests still pending won't complete.
+    fn stop(&mut self, browser_id: i32, now: Instant) -> Option<NavigationStats> {
+        let navigation = self.navigations.get_mut(&browser_id)?;
+        navigation.pending = 0;
+        let navigation_id = navigation.stats.navigation_id;
+        let completed = Self::finish(navigation, now);
+        self.drop_requests(navigation_id);
+        completed
+    }
+
+    fn snapshot(&self, browser_id: i32, now: Instant) -> Option<NavigationStats> {
+        Some(self.navigations.get(&browser_id)?.snapshot(now))
+    }
+
+    fn forget(&mut self, browser_id: i32) {
+        if let Some(navigation) = self.navigations.remove(&browser_id) {
+            self.drop_requests(navigation.stats.navigation_id);
+        }
+    }
+
+    /// Mark `navigation` complete, and return its statistics the first time.
+    fn finish(navigation: &mut Navigation, now: Instant) -> Option<NavigationStats> {
+        if navigation.completed.is_some() {
+            return None;
+        }
+        navigation.completed = Some(now);
+        Some(navigation.snapshot(now))
+    }
+
+    /// The navigation of `request_id`, if it is still the latest one of its browser.
+    fn navigation_of(&mut self, request_id: u64) -> Option<&mut Navigation> {
+        let request = self.requests.get(&request_id)?;
+        let navigation = self.navigations.get_mut(&request.browser_id)?;
+        (navigation.stats.navigation_id == request.navigation_id).then_some(navigation)
+    }
+
+    fn drop_requests(&mut self, navigation_id: u64) {
+        self.requests
+            .retain(|_, request| request.navigation_id != navigation_id);
+    }
+}
+
+fn browser_id(browser: Option<&impl ImplBrowser>) -> Option<i32> {
+    browser.map(|browser| browser.get_identifier())
+}
+
+fn request_id(request: Option<&impl ImplRequest>) -> Option<u64> {
+    request.map(|request| request.get_identifier())
+}
+
+struct StatsRequestHandler {
+    object: *mut RcImpl<_cef_request_handler_t, Self>,
+    stats: ResourceStats,
+}
+
+wrap_rc!(
+    StatsRequestHandler,
+    WrapRequestHandler,
+    _cef_request_handler_t,
+    stats
+);
+
+impl ImplRequestHandler for StatsRequestHandler {
+    fn on_before_browse(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        frame: Option<&impl ImplFrame>,
+        request: Option<&impl ImplRequest>,
+        _user_gesture: c_int,
+        is_redirect: c_int,
+    ) -> c_int {
+        let (Some(browser_id), Some(frame), Some(request)) = (browser_id(browser), frame, request)
+        else {
+            return 0;
+        };
+        if frame.is_main() == 0 || is_redirect != 0 {
+            return 0;
+        }
+        let frame_id = compat::frame_id(frame).to_string();
+        let url = request
+            .get_url()
+            .map(|url| url.to_string())
+            .unwrap_or_default();
+        self.stats
+            .update(|collector, now| collector.begin(browser_id, &frame_id, &url, now));
+        0
+    }
+
+    fn get_resource_request_handler(
+        &self,
+        _browser: Option<&impl ImplBrowser>,
+        _frame: Option<&impl ImplFrame>,
+        _request: Option<&impl ImplRequest>,
+        _is_navigation: c_int,
+        _is_download: c_int,
+        _request_initiator: Option<&CefStringUtf16>,
+        _disable_default_handling: Option<&mut c_int>,
+    ) -> Option<ResourceRequestHandler> {
+        Some(ResourceRequestHandler::new(StatsResourceRequestHandler {
+            object: std::ptr::null_mut(),
+            stats: self.stats.clone(),
+        }))
+    }
+
+    fn get_raw(&self) -> *mut _cef_request_handler_t {
+        self.object as *mut _cef_request_handler_t
+    }
+}
+
+struct StatsResourceRequestHandler {
+    object: *mut RcImpl<_cef_resource_request_handler_t, Self>,
+    stats: ResourceStats,
+}
+
+wrap_rc!(
+    StatsResourceRequestHandler,
+    WrapResourceRequestHandler,
+    _cef_resource_request_handler_t,
+    stats
+);
+
+impl ImplResourceRequestHandler for StatsResourceRequestHandler {
+    fn on_before_resource_load(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        _frame: Option<&impl ImplFrame>,
+        request: Option<&impl ImplRequest>,
+        _callback: Option<&impl ImplCallback>,
+    ) -> ReturnValue {
+        if let (Some(browser_id), Some(request)) = (browser_id(browser), request) {
+            let is_document = request.get_resource_type() == ResourceType::MainFrame;
+            let request_id = request.get_identifier();
+            self.stats.update(|collector, _| {
+                collector.start(browser_id, request_id, is_document);
+                None
+            });
+        }
+        cef_return_value_t::RV_CONTINUE.into()
+    }
+
+    fn on_resource_redirect(
+        &self,
+        _browser: Option<&impl ImplBrowser>,
+        _frame: Option<&impl ImplFrame>,
+        request: Option<&impl ImplRequest>,
+        _response: Option<&impl ImplResponse>,
+        _new_url: Option<&mut CefStringUtf16>,
+    ) {
+        if let Some(request_id) = request_id(request) {
+            self.stats.update(|collector, _| {
+                collector.redirect(request_id);
+                None
+            });
+        }
+    }
+
+    fn on_resource_response(
+        &self,
+        _browser: Option<&impl ImplBrowser>,
+        _frame: Option<&impl ImplFrame>,
+        request: Option<&impl ImplRequest>,
+        response: Option<&impl ImplResponse>,
+    ) -> c_int {
+        if let (Some(request_id), Some(response)) = (request_id(request), response) {
+            let mime = response
+                .get_mime_type()
+                .map(|mime| mime.to_string())
+                .unwrap_or_default();
+            self.stats.update(|collector, _| {
+                collector.response(request_id, &mime);
+                None
+            });
+        }
+        0
+    }
+
+    fn on_resource_load_complete(
+        &self,
+        _browser: Option<&impl ImplBrowser>,
+        _frame: Option<&impl ImplFrame>,
+        request: Option<&impl ImplRequest>,
+        _response: Option<&impl ImplResponse>,
+        status: UrlrequestStatus,
+        received_content_length: i64,
+    ) {
+        let Some(request_id) = request_id(request) else {
+            return;
+        };
+        let success = status == UrlrequestStatus::Success;
+        let bytes = received_content_length.max(0) as u64;
+        self.stats
+            .update(|collector, now| collector.complete(request_id, success, bytes, now));
+    }
+
+    fn get_raw(&self) -> *mut _cef_resource_request_handler_t {
+        self.object as *mut _cef_resource_request_handler_t
+    }
+}
+
+struct StatsLoadHandler {
+    object: *mut RcImpl<_cef_load_handler_t, Self>,
+    stats: ResourceStats,
+}
+
+wrap_rc!(
+    StatsLoadHandler,
+    WrapLoadHandler,
+    _cef_load_handler_t,
+    stats
+);
+
+impl ImplLoadHandler for StatsLoadHandler {
+    fn on_loading_state_change(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        is_loading: c_int,
+        _can_go_back: c_int,
+        _can_go_forward: c_int,
+    ) {
+        if let (Some(browser_id), 0) = (browser_id(browser), is_loading) {
+            self.stats
+                .update(|collector, now| collector.stop(browser_id, now));
+        }
+    }
+
+    fn get_raw(&self) -> *mut _cef_load_handler_t {
+        self.object as *mut _cef_load_handler_t
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// `Performance.getMetrics` of `about:blank`.
+    const BLANK_PAGE_METRICS: &[u8] = br#"{"metrics": [
+        {"name": "Timestamp", "value": 5034.6721},
+        {"name": "Documents", "value": 2},
+        {"name": "Frames", "value": 1},
+        {"name": "JSEventListeners", "value": 0},
+        {"name": "LayoutCount", "value": 1},
+        {"name": "RecalcStyleCount", "value": 2},
+        {"name": "TaskDuration", "value": 0.012345},
+        {"name": "JSHeapUsedSize", "value": 876544},
+        {"name": "JSHeapTotalSize", "value": 1572864}
+    ]}"#;
+
+    #[test]
+    fn test_blank_page_metrics() {
+        let metrics = BrowserMetrics::from_performance_metrics(BLANK_PAGE_METRICS).unwrap();
+        assert_eq!(
+            metrics,
+            BrowserMetrics {
+                js_heap_used_size: 876544,
+                js_heap_total_size: 1572864,
+                js_heap_size_limit: 0,
+                documents: 2,
+                frames: 1,
+                layout_count: 1,
+                recalc_style_count: 2,
+                task_duration: 0.012345,
+            }
+        );
+        assert!(BrowserMetrics::from_performance_metrics(b"{}").is_err());
+    }
+
+    #[test]
+    fn test_display() {
+        let table = BrowserMetrics {
+            frames: 1,
+            task_duration: 0.5,
+            ..Default::default()
+        }
+        .to_string();
+        let lines: Vec<_> = table.lines().collect();
+        assert_eq!(lines.len(), 10);
+        assert!(lines[0].starts_with("Metric"));
+        assert!(lines[6].starts_with("Frames") && lines[6].ends_with(" 1"));
+        assert!(lines[9].ends_with("0.500 s"));
+    }
+
+    /// A request event as the handlers report it to the [Collector].
+    enum Event {
+        Browse(&'static str),
+        Start(u64, bool),
+        Redirect(u64),
+        Response(u64, &'static str),
+        Complete(u64, bool, u64),
+        Stop,
+    }
+
+    const BROWSER: i32 = 1;
+
+    /// Replay `events` one millisecond apart, and return the completed navigations.
+    fn replay(collector: &mut Collector, events: &[Event]) -> Vec<NavigationStats> {
+        let start = Instant::now();
+        let mut completed = vec![];
+        for (index, event) in events.iter().enumerate() {
+            let now = start + Duration::from_millis(index as u64);
+            let navigation = match *event {
+                Event::Browse(url) => collector.begin(BROWSER, "main", url, now),
+                Event::Start(request, is_document) => {
+                    collector.start(BROWSER, request, is_document);
+                    None
+                }
+                Event::Redirect(request) => {
+                    collector.redirect(request);
+                    None
+                }
+                Event::Response(request, mime) => {
+                    collector.response(request, mime);
+                    None
+                }
+                Event::Complete(request, success, bytes) => {
+                    collector.complete(request, success, bytes, now)
+                }
+                Event::Stop => collector.stop(BROWSER, now),
+            };
+            completed.extend(navigation);
+        }
+        completed
+    }
+
+    #[test]
+    fn test_page_load() {
+        let mut collector = Collector::default();
+        let completed = replay(
+            &mut collector,
+            &[
+                Event::Browse("https://example.com/"),
+                Event::Start(1, true),
+                Event::Response(1, "text/html"),
+                Event::Start(2, false),
+                Event::Start(3, false),
+                Event::Response(2, "image/png"),
+                Event::Complete(1, true, 1000),
+                Event::Complete(2, true, 500),
+                Event::Complete(3, false, 0),
+                Event::Stop,
+            ],
+        );
+        assert_eq!(completed.len(), 1);
+        let stats = &completed[0];
+        assert_eq!(
+            (
+                stats.navigation_id,
+                stats.url.as_str(),
+                stats.frame_id.as_str()
+            ),
+            (1, "https://example.com/", "main")
+        );
+        assert_eq!((stats.requests, stats.failed, stats.bytes), (3, 1, 1500));
+        assert_eq!(
+            stats.by_mime,
+            HashMap::from([
+                ("text/html".to_string(), 1000),
+                ("image/png".to_string(), 500),
+                (String::new(), 0)
+            ])
+        );
+        assert_eq!(stats.duration, Duration::from_millis(8));
+        assert_eq!(
+            collector.snapshot(BROWSER, Instant::now()).as_ref(),
+            Some(stats)
+        );
+        assert!(collector.requests.is_empty());
+    }
+
+    #[test]
+    fn test_redirect_chain() {
+        let mut collector = Collector::default();
+        let completed = replay(
+            &mut collector,
+            &[
+                Event::Browse("http://example.com/"),
+                Event::Start(1, true),
+                Event::Redirect(1),
+                Event::Start(1, true),
+                Event::Redirect(1),
+                Event::Start(1, true),
+                Event::Response(1, "text/html"),
+                Event::Complete(1, true, 42),
+            ],
+        );
+        assert_eq!(completed.len(), 1);
+        assert_eq!(
+            (
+                completed[0].requests,
+                completed[0].redirects,
+                completed[0].bytes
+            ),
+            (1, 2, 42)
+        );
+    }
+
+    #[test]
+    fn test_requests_without_completion() {
+        let mut collector = Collector::default();
+        // The service worker answered request 2, which never completes.
+        let completed = replay(
+            &mut collector,
+            &[
+                Event::Browse("https://example.com/"),
+                Event::Start(1, true),
+                Event::Start(2, false),
+                Event::Complete(1, true, 100),
+            ],
+        );
+        assert!(completed.is_empty());
+        assert_eq!(collector.requests.len(), 1);
+
+        let completed = replay(&mut collector, &[Event::Stop, Event::Complete(2, true, 7)]);
+        assert_eq!(completed.len(), 1);
+        assert_eq!((completed[0].requests, completed[0].bytes), (2, 100));
+        assert!(collector.requests.is_empty());
+    }
+
+    #[test]
+    fn test_replaced_navigation() {
+        let mut collector = Collector::default();
+        let completed = replay(
+            &mut collector,
+            &[
+                Event::Browse("https://example.com/"),
+                Event::Start(1, true),
+                Event::Start(2, false),
+                Event::Complete(1, true, 10),
+                Event::Browse("https://example.org/"),
+                Event::Complete(2, true, 20),
+                Event::Start(3, true),
+            ],
+        );
+        assert_eq!(completed.len(), 1);
+        assert_eq!(completed[0].url, "https://example.com/");
+        assert_eq!((completed[0].requests, completed[0].bytes), (2, 10));
+        assert_eq!(collector.requests.len(), 1);
+
+        let current = collector.snapshot(BROWSER, Instant::now()).unwrap();
+        assert_eq!((current.navigation_id, current.requests), (2, 1));
+
+        collector.forget(BROWSER);
+        assert!(collector.requests.is_empty());
+        assert!(collector.snapshot(BROWSER, Instant::now()).is_none());
+    }
+}
@@ -0,0 +1,298 @@
+//! Storage module
+//!
+//! Chrome has no preferences for per-origin storage quotas, so [StoragePersistenceManager] uses
+//! the DevTools `Storage` domain of a browser, and the cookie manager of its [crate::RequestContext].
+
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    devtools::{json, DevToolsError},
+    dictionary_value_create, BrowserHost, CefString, CefStringUtf8, DictionaryValue, ImplBrowser,
+    ImplBrowserHost, ImplDictionaryValue, ImplFrame,
+};
+
+/// The browsing data removed by [StoragePersistenceManager::clear_all_browsing_data].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BrowsingDataTypes {
+    /// Delete all cookies of the request context.
+    pub cookies: bool,
+    /// Clear the HTTP cache.
+    pub cache: bool,
+    pub local_storage: bool,
+    pub indexed_db: bool,
+    pub service_workers: bool,
+    pub cache_storage: bool,
+}
+
+impl BrowsingDataTypes {
+    /// Clear everything.
+    pub fn all() -> Self {
+        Self {
+            cookies: true,
+            cache: true,
+            local_storage: true,
+            indexed_db: true,
+            service_workers: true,
+            cache_storage: true,
+        }
+    }
+
+    /// The `storageTypes` parameter of `Storage.clearDataForOrigin`.
+    fn storage_types(&self) -> String {
+        [
+            (self.local_storage, "local_storage"),
+            (self.indexed_db, "indexeddb"),
+            (self.service_workers, "service_workers"),
+            (self.cache_storage, "cache_storage"),
+        ]
+        .into_iter()
+        .filter_map(|(enabled, name)| enabled.then_some(name))
+        .collect::<Vec<_>>()
+        .join(",")
+    }
+}
+
+/// Storage quotas and clearing of the origins loaded in a browser.
+///
+/// Every method must be called on the UI thread, and fails or does nothing before the first
+/// navigation of the browser, when DevTools is not available yet.
+#[derive(Clone)]
+pub struct StoragePersistenceManager {
+    host: BrowserHost,
+    quotas: Quotas,
+}
+
+impl StoragePersistenceManager {
+    pub fn new(host: BrowserHost) -> Self {
+        Self {
+            host,
+            quotas: Default::default(),
+        }
+    }
+
+    /// Limit the storage of `origin`, which may also be a URL, to `quota_bytes`. The quota is
+    /// shared by IndexedDB and the other quota-managed storage types of the origin.
+    pub fn set_indexed_db_quota(&self, origin: &str, quota_bytes: u64) {
+        let Some(origin) = origin_of(origin) else {
+            return;
+        };
+        let params = dictionary_value_create();
+        if let Some(params) = &params {
+            params.set_string(Some(&key("origin")), Some(&key(&origin)));
+            params.set_double(Some(&key("quotaSize")), quota_bytes as f64);
+        }
+        // The method runs without awaiting its result.
+        drop(
+            self.host
+                .execute_dev_tools("Storage.overrideQuotaForOrigin", params),
+        );
+        self.quotas.set(origin, quota_bytes);
+    }
+
+    /// The quota set with [StoragePersistenceManager::set_indexed_db_quota].
+    pub fn indexed_db_quota(&self, origin: &str) -> Option<u64> {
+        self.quotas.get(&origin_of(origin)?)
+    }
+
+    /// The bytes used by IndexedDB for `origin`, or `None` if DevTools is not available.
+    pub fn get_indexed_db_usage(&self, origin: &str) -> impl Future<Output = Option<u64>> {
+        let usage = origin_of(origin).map(|origin| {
+            self.host
+                .execute_dev_tools("Storage.getUsageAndQuota", origin_params(&origin, None))
+        });
+        async move { indexed_db_usage(&usage?.await.ok()?).ok() }
+    }
+
+    /// Delete the IndexedDB databases of `origin`, and resolve with `true` on success.
+    pub fn clear_indexed_db(&self, origin: &str) -> impl Future<Output = bool> {
+        let cleared = self.clear_data_for_origin(origin, "indexeddb");
+        async move { cleared.await.is_ok() }
+    }
+
+    /// Clear the localStorage of `origin`.
+    pub fn clear_local_storage(&self, origin: &str) {
+        drop(self.clear_data_for_origin(origin, "local_storage"));
+    }
+
+    /// Clear `data_types`. Storage is cleared for the origin of the main frame and the origins
+    /// which have a quota set, since DevTools can only clear storage origin by origin.
+    pub fn clear_all_browsing_data(
+        &self,
+        data_types: BrowsingDataTypes,
+    ) -> impl Future<Output = ()> {
+        let cookies = data_types
+            .cookies
+            .then(|| self.host.get_request_context())
+            .flatten()
+            .map(|context| context.clear_cookies());
+        let cache = data_types.cache.then(|| {
+            self.host
+                .execute_dev_tools("Network.clearBrowserCache", None)
+        });
+
+        let storage_types = data_types.storage_types();
+        let mut origins = self.quotas.origins();
+        if let Some(origin) = self
+            .host
+            .get_browser()
+            .and_then(|browser| browser.get_main_frame())
+            .and_then(|frame| frame.get_url())
+            .and_then(|url| origin_of(&url.to_string()))
+        {
+            origins.push(origin);
+        }
+        origins.sort();
+        origins.dedup();
+        let storage: Vec<_> = if storage_types.is_empty() {
+            vec![]
+        } else {
+            origins
+                .iter()
+                .map(|origin| self.clear_data_for_origin(origin, &storage_types))
+                .collect()
+        };
+
+        async move {
+            if let Some(cookies) = cookies {
+                cookies.await;
+            }
+            if let Some(cache) = cache {
+                let _ = cache.await;
+            }
+            for cleared in storage {
+                let _ = cleared.await;
+            }
+        }
+    }
+
+    fn clear_data_for_origin(
+        &self,
+        origin: &str,
+        storage_types: &str,
+    ) -> impl Future<Output = Result<Vec<u8>, DevToolsError>> {
+        let params = origin_of(origin)
+            .map(|origin| origin_params(&origin, Some(storage_types)))
+            .unwrap_or_default();
+        let cleared = params.map(|params| {
+            self.host
+                .execute_dev_tools("Storage.clearDataForOrigin", Some(params))
+        });
+        async move {
+            match cleared {
+                Some(cleared) => cleared.await,
+                None => Err(DevToolsError::Unavailable),
+            }
+        }
+    }
+}
+
+/// The quotas set per origin.
+#[derive(Clone, Default)]
+struct Quotas(Arc<Mutex<BTreeMap<String, u64>>>);
+
+impl Quotas {
+    fn set(&self, origin: String, quota_bytes: u64) {
+        if let Ok(mut quotas) = self.0.lock() {
+            quotas.insert(origin, quota_bytes);
+        }
+    }
+
+    fn get(&self, origin: &str) -> Option<u64> {
+        self.0.lock().ok()?.get(origin).copied()
+    }
+
+    fn origins(&self) -> Vec<String> {
+        self.0
+            .lock()
+            .map(|quotas| quotas.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+fn key(value: &str) -> CefString {
+    CefString::from(&CefStringUtf8::from(value))
+}
+
+fn origin_params(origin: &str, storage_types: Option<&str>) -> Option<DictionaryValue> {
+    let params = dictionary_value_create()?;
+    params.set_string(Some(&key("origin")), Some(&key(origin)));
+    if let Some(storage_types) = storage_types {
+        params.set_string(Some(&key("storageTypes")), Some(&key(storage_types)));
+    }
+    Some(params)
+}
+
+/// The `scheme://host[:port]` origin of a URL or origin, without a trailing slash.
+pub(crate) fn origin_of(url: &str) -> Option<String> {
+    let url = url.trim();
+    let (scheme, rest) = url.split_once("://")?;
+    let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    if scheme.is_empty() || host.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "{}://{}",
+        scheme.to_ascii_lowercase(),
+        host.to_ascii_lowercase()
+    ))
+}
+
+/// The IndexedDB entry of the `usageBreakdown` of a `Storage.getUsageAndQuota` result.
+fn indexed_db_usage(result: &[u8]) -> Result<u64, DevToolsError> {
+    let breakdown = json::elements(&json::parse(result)?, &["usageBreakdown"])?;
+    for usage in breakdown {
+        if json::string(&usage, &["storageType"])? == "indexeddb" {
+            return Ok(json::number(&usage, &["usage"])? as u64);
+        }
+    }
+    Err(DevToolsError::InvalidResponse)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_set_indexed_db_quota_stores_value() {
+        let quotas = Quotas::default();
+        quotas.set(
+            origin_of("https://Example.com/app/index.html").unwrap(),
+            50 << 20,
+        );
+        assert_eq!(quotas.get("https://example.com"), Some(50 << 20));
+        assert_eq!(quotas.get("https://example.org"), None);
+        assert_eq!(quotas.origins(), vec!["https://example.com".to_string()]);
+    }
+
+    #[test]
+    fn test_origin_of() {
+        assert_eq!(
+            origin_of("http://localhost:8080?query").as_deref(),
+            Some("http://localhost:8080")
+        );
+        assert_eq!(origin_of("about:blank"), None);
+        assert_eq!(origin_of("https:///path"), None);
+    }
+
+    #[test]
+    fn test_indexed_db_usage() {
+        let result = br#"{"usage": 300, "quota": 1000, "overrideActive": false,
+            "usageBreakdown": [{"storageType": "file_systems", "usage": 100},
+            {"storageType": "indexeddb", "usage": 200}]}"#;
+        assert_eq!(indexed_db_usage(result).ok(), Some(200));
+        assert!(indexed_db_usage(br#"{"usageBreakdown": []}"#).is_err());
+    }
+
+    #[test]
+    fn test_storage_types() {
+        assert_eq!(
+            BrowsingDataTypes::all().storage_types(),
+            "local_storage,indexeddb,service_workers,cache_storage"
+        );
+        assert_eq!(BrowsingDataTypes::default().storage_types(), "");
+    }
+}
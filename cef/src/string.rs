@@ -7,9 +7,13 @@ use cef_sys::{
 use std::{
     fmt::{self, Display, Formatter},
     mem, ptr, slice,
+    string::FromUtf16Error,
 };
 
-use crate::CefString;
+use crate::{
+    diagnostics::{self, DiagnosticCategory},
+    CefString,
+};
 
 enum CefStringData<T> {
     Borrowed(Option<T>),
@@ -58,9 +62,9 @@ where
     }
 }
 
-impl<'a, T> Into<Option<&'a T>> for &'a CefStringData<T> {
-    fn into(self) -> Option<&'a T> {
-        match self {
+impl<'a, T> From<&'a CefStringData<T>> for Option<&'a T> {
+    fn from(value: &'a CefStringData<T>) -> Self {
+        match value {
             CefStringData::Borrowed(value) => value.as_ref(),
             CefStringData::Clear(value) => Some(value),
             CefStringData::UserFree(value) => unsafe { value.as_ref() },
@@ -68,9 +72,9 @@ impl<'a, T> Into<Option<&'a T>> for &'a CefStringData<T> {
     }
 }
 
-impl<'a, T> Into<Option<&'a mut T>> for &'a mut CefStringData<T> {
-    fn into(self) -> Option<&'a mut T> {
-        match self {
+impl<'a, T> From<&'a mut CefStringData<T>> for Option<&'a mut T> {
+    fn from(value: &'a mut CefStringData<T>) -> Self {
+        match value {
             CefStringData::Borrowed(value) => value.as_mut(),
             CefStringData::Clear(value) => Some(value),
             CefStringData::UserFree(value) => unsafe { value.as_mut() },
@@ -87,9 +91,9 @@ impl Drop for CefStringUtf8 {
         unsafe {
             match &mut self.0 {
                 CefStringData::UserFree(value) => {
-                    value
-                        .as_mut()
-                        .map(|value| cef_sys::cef_string_userfree_utf8_free(value));
+                    if let Some(value) = value.as_mut() {
+                        cef_sys::cef_string_userfree_utf8_free(value);
+                    }
                 }
                 CefStringData::Clear(value) => {
                     cef_sys::cef_string_utf8_clear(value);
@@ -108,7 +112,7 @@ impl From<&str> for CefStringUtf8 {
                 if !cef_string.is_null() {
                     cef_sys::cef_string_utf8_set(
                         value.as_bytes().as_ptr() as *const _,
-                        value.as_bytes().len(),
+                        value.len(),
                         cef_string,
                         1,
                     );
@@ -138,9 +142,9 @@ impl From<*mut _cef_string_utf8_t> for CefStringUtf8 {
     }
 }
 
-impl Into<*const _cef_string_utf8_t> for &CefStringUtf8 {
-    fn into(self) -> *const _cef_string_utf8_t {
-        let data: Option<&_cef_string_utf8_t> = (&self.0).into();
+impl From<&CefStringUtf8> for *const _cef_string_utf8_t {
+    fn from(value: &CefStringUtf8) -> Self {
+        let data: Option<&_cef_string_utf8_t> = (&value.0).into();
         data.map(ptr::from_ref).unwrap_or(ptr::null())
     }
 }
@@ -149,11 +153,11 @@ impl From<&CefStringUtf16> for CefStringUtf8 {
     fn from(value: &CefStringUtf16) -> Self {
         Self(
             unsafe {
-                let cef_string = ptr::null_mut();
+                let mut cef_string = mem::zeroed();
                 let value: *const _cef_string_utf16_t = value.into();
                 if let Some((str_, length)) = value.as_ref().map(|value| (value.str_, value.length))
                 {
-                    cef_sys::cef_string_utf16_to_utf8(str_, length, cef_string);
+                    cef_sys::cef_string_utf16_to_utf8(str_, length, &mut cef_string);
                 }
                 cef_string
             }
@@ -166,11 +170,11 @@ impl From<&CefStringWide> for CefStringUtf8 {
     fn from(value: &CefStringWide) -> Self {
         Self(
             unsafe {
-                let cef_string = ptr::null_mut();
+                let mut cef_string = mem::zeroed();
                 let value: *const _cef_string_wide_t = value.into();
                 if let Some((str_, length)) = value.as_ref().map(|value| (value.str_, value.length))
                 {
-                    cef_sys::cef_string_wide_to_utf8(str_, length, cef_string);
+                    cef_sys::cef_string_wide_to_utf8(str_, length, &mut cef_string);
                 }
                 cef_string
             }
@@ -179,29 +183,29 @@ impl From<&CefStringWide> for CefStringUtf8 {
     }
 }
 
-impl Into<_cef_string_utf8_t> for CefStringUtf8 {
-    fn into(self) -> _cef_string_utf8_t {
-        match self.0 {
+impl From<CefStringUtf8> for _cef_string_utf8_t {
+    fn from(value: CefStringUtf8) -> Self {
+        match value.0 {
             CefStringData::Borrowed(Some(value)) => value,
             _ => unsafe { mem::zeroed() },
         }
     }
 }
 
-impl Into<*mut _cef_string_utf8_t> for CefStringUtf8 {
-    fn into(self) -> *mut _cef_string_utf8_t {
-        let value = match self.0 {
+impl From<CefStringUtf8> for *mut _cef_string_utf8_t {
+    fn from(string: CefStringUtf8) -> Self {
+        let value = match string.0 {
             CefStringData::UserFree(value) => value,
             _ => ptr::null_mut(),
         };
-        mem::forget(self);
+        mem::forget(string);
         value
     }
 }
 
-impl Into<*mut _cef_string_utf8_t> for &mut CefStringUtf8 {
-    fn into(self) -> *mut _cef_string_utf8_t {
-        mem::take(self).into()
+impl From<&mut CefStringUtf8> for *mut _cef_string_utf8_t {
+    fn from(value: &mut CefStringUtf8) -> Self {
+        mem::take(value).into()
     }
 }
 
@@ -235,9 +239,9 @@ impl Drop for CefStringUtf16 {
         unsafe {
             match &mut self.0 {
                 CefStringData::UserFree(value) => {
-                    value
-                        .as_mut()
-                        .map(|value| cef_sys::cef_string_userfree_utf16_free(value));
+                    if let Some(value) = value.as_mut() {
+                        cef_sys::cef_string_userfree_utf16_free(value);
+                    }
                 }
                 CefStringData::Clear(value) => {
                     cef_sys::cef_string_utf16_clear(value);
@@ -266,13 +270,52 @@ impl From<*mut _cef_string_utf16_t> for CefStringUtf16 {
     }
 }
 
-impl Into<*const _cef_string_utf16_t> for &CefStringUtf16 {
-    fn into(self) -> *const _cef_string_utf16_t {
-        let data: Option<&_cef_string_utf16_t> = (&self.0).into();
+impl From<&CefStringUtf16> for *const _cef_string_utf16_t {
+    fn from(value: &CefStringUtf16) -> Self {
+        let data: Option<&_cef_string_utf16_t> = (&value.0).into();
         data.map(ptr::from_ref).unwrap_or(ptr::null())
     }
 }
 
+impl From<&str> for CefStringUtf16 {
+    /// Copy `value` into a string owned by CEF, which is cleared when this is dropped.
+    fn from(value: &str) -> Self {
+        let value: Vec<u16> = value.encode_utf16().collect();
+        Self(
+            unsafe {
+                let mut cef_string = mem::zeroed();
+                cef_sys::cef_string_utf16_set(value.as_ptr(), value.len(), &mut cef_string, 1);
+                cef_string
+            }
+            .into(),
+        )
+    }
+}
+
+impl From<String> for CefStringUtf16 {
+    fn from(value: String) -> Self {
+        value.as_str().into()
+    }
+}
+
+impl From<&String> for CefStringUtf16 {
+    fn from(value: &String) -> Self {
+        value.as_str().into()
+    }
+}
+
+impl TryFrom<CefStringUtf16> for String {
+    type Error = FromUtf16Error;
+
+    /// Convert `value` without replacing unpaired surrogates, unlike [ToString::to_string],
+    /// which replaces them and reports a [DiagnosticCategory::StringConversion].
+    fn try_from(value: CefStringUtf16) -> Result<Self, Self::Error> {
+        value
+            .as_slice()
+            .map_or(Ok(String::new()), String::from_utf16)
+    }
+}
+
 impl From<&CefStringUtf8> for CefStringUtf16 {
     fn from(value: &CefStringUtf8) -> Self {
         Self(
@@ -307,29 +350,56 @@ impl From<&CefStringWide> for CefStringUtf16 {
     }
 }
 
-impl Into<_cef_string_utf16_t> for CefStringUtf16 {
-    fn into(self) -> _cef_string_utf16_t {
-        match self.0 {
+impl From<CefStringUtf16> for _cef_string_utf16_t {
+    fn from(value: CefStringUtf16) -> Self {
+        match value.0 {
             CefStringData::Borrowed(Some(value)) => value,
             _ => unsafe { mem::zeroed() },
         }
     }
 }
 
-impl Into<*mut _cef_string_utf16_t> for CefStringUtf16 {
-    fn into(self) -> *mut _cef_string_utf16_t {
-        let value = match self.0 {
+impl From<CefStringUtf16> for *mut _cef_string_utf16_t {
+    fn from(string: CefStringUtf16) -> Self {
+        let value = match string.0 {
             CefStringData::UserFree(value) => value,
             _ => ptr::null_mut(),
         };
-        mem::forget(self);
+        mem::forget(string);
         value
     }
 }
 
-impl Into<*mut _cef_string_utf16_t> for &mut CefStringUtf16 {
-    fn into(self) -> *mut _cef_string_utf16_t {
-        mem::take(self).into()
+impl From<&mut CefStringUtf16> for *mut _cef_string_utf16_t {
+    fn from(value: &mut CefStringUtf16) -> Self {
+        mem::take(value).into()
+    }
+}
+
+impl CefStringUtf16 {
+    fn as_slice(&self) -> Option<&[u16]> {
+        let data: Option<&_cef_string_utf16_t> = (&self.0).into();
+        let (str_, length) = data.map(|value| (value.str_, value.length))?;
+        if str_.is_null() {
+            return None;
+        }
+        Some(unsafe { slice::from_raw_parts(str_ as *const _, length) })
+    }
+}
+
+impl Display for CefStringUtf16 {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if let Some(value) = self.as_slice() {
+            match String::from_utf16(value) {
+                Ok(value) => write!(f, "{value}"),
+                Err(err) => {
+                    diagnostics::report(DiagnosticCategory::StringConversion, err);
+                    write!(f, "{}", String::from_utf16_lossy(value))
+                }
+            }
+        } else {
+            Ok(())
+        }
     }
 }
 
@@ -342,9 +412,9 @@ impl Drop for CefStringWide {
         unsafe {
             match &mut self.0 {
                 CefStringData::UserFree(value) => {
-                    value
-                        .as_mut()
-                        .map(|value| cef_sys::cef_string_userfree_wide_free(value));
+                    if let Some(value) = value.as_mut() {
+                        cef_sys::cef_string_userfree_wide_free(value);
+                    }
                 }
                 CefStringData::Clear(value) => {
                     cef_sys::cef_string_wide_clear(value);
@@ -373,9 +443,9 @@ impl From<*mut _cef_string_wide_t> for CefStringWide {
     }
 }
 
-impl Into<*const _cef_string_wide_t> for &CefStringWide {
-    fn into(self) -> *const _cef_string_wide_t {
-        let data: Option<&_cef_string_wide_t> = (&self.0).into();
+impl From<&CefStringWide> for *const _cef_string_wide_t {
+    fn from(value: &CefStringWide) -> Self {
+        let data: Option<&_cef_string_wide_t> = (&value.0).into();
         data.map(ptr::from_ref).unwrap_or(ptr::null())
     }
 }
@@ -414,29 +484,29 @@ impl From<&CefStringUtf16> for CefStringWide {
     }
 }
 
-impl Into<_cef_string_wide_t> for CefStringWide {
-    fn into(self) -> _cef_string_wide_t {
-        match self.0 {
+impl From<CefStringWide> for _cef_string_wide_t {
+    fn from(value: CefStringWide) -> Self {
+        match value.0 {
             CefStringData::Borrowed(Some(value)) => value,
             _ => unsafe { mem::zeroed() },
         }
     }
 }
 
-impl Into<*mut _cef_string_wide_t> for CefStringWide {
-    fn into(self) -> *mut _cef_string_wide_t {
-        let value = match self.0 {
+impl From<CefStringWide> for *mut _cef_string_wide_t {
+    fn from(string: CefStringWide) -> Self {
+        let value = match string.0 {
             CefStringData::UserFree(value) => value,
             _ => ptr::null_mut(),
         };
-        mem::forget(self);
+        mem::forget(string);
         value
     }
 }
 
-impl Into<*mut _cef_string_wide_t> for &mut CefStringWide {
-    fn into(self) -> *mut _cef_string_wide_t {
-        mem::take(self).into()
+impl From<&mut CefStringWide> for *mut _cef_string_wide_t {
+    fn from(value: &mut CefStringWide) -> Self {
+        mem::take(value).into()
     }
 }
 
@@ -446,9 +516,9 @@ pub struct CefStringList(*mut _cef_string_list_t);
 impl Drop for CefStringList {
     fn drop(&mut self) {
         unsafe {
-            self.0
-                .as_mut()
-                .map(|value| cef_sys::cef_string_list_free(value));
+            if let Some(value) = self.0.as_mut() {
+                cef_sys::cef_string_list_free(value);
+            }
         }
     }
 }
@@ -459,17 +529,35 @@ impl From<*mut _cef_string_list_t> for CefStringList {
     }
 }
 
-impl Into<*mut _cef_string_list_t> for &mut CefStringList {
-    fn into(self) -> *mut _cef_string_list_t {
-        self.0
+impl From<&mut CefStringList> for *mut _cef_string_list_t {
+    fn from(value: &mut CefStringList) -> Self {
+        value.0
     }
 }
 
-impl IntoIterator for CefStringList {
-    type Item = String;
-    type IntoIter = std::vec::IntoIter<Self::Item>;
+impl Default for CefStringList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-    fn into_iter(self) -> Self::IntoIter {
+impl CefStringList {
+    /// Allocate an empty list, e.g. for the methods which fill one in.
+    pub fn new() -> Self {
+        Self(unsafe { cef_sys::cef_string_list_alloc() })
+    }
+
+    /// Append a copy of `value`.
+    pub fn push(&mut self, value: &str) {
+        let Some(list) = (unsafe { self.0.as_mut() }) else {
+            return;
+        };
+        let value = CefString::from(&CefStringUtf8::from(value));
+        unsafe { cef_sys::cef_string_list_append(list, (&value).into()) };
+    }
+
+    /// Copy the strings of the list.
+    pub fn to_vec(&self) -> Vec<String> {
         let list = unsafe { self.0.as_mut() };
         list.map(|list| {
             let count = unsafe { cef_sys::cef_string_list_size(list) };
@@ -478,13 +566,19 @@ impl IntoIterator for CefStringList {
                     let mut value = mem::zeroed();
                     (cef_sys::cef_string_list_value(list, i, &mut value) > 0).then_some(value)
                 })
-                .map(|value| {
-                    CefStringUtf8::from(&CefString::from(ptr::from_ref(&value))).to_string()
-                })
+                .map(|value| CefString::from(value).to_string())
                 .collect::<Vec<_>>()
         })
         .unwrap_or_default()
-        .into_iter()
+    }
+}
+
+impl IntoIterator for CefStringList {
+    type Item = String;
+    type IntoIter = std::vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.to_vec().into_iter()
     }
 }
 
@@ -494,9 +588,9 @@ pub struct CefStringMap(*mut _cef_string_map_t);
 impl Drop for CefStringMap {
     fn drop(&mut self) {
         unsafe {
-            self.0
-                .as_mut()
-                .map(|value| cef_sys::cef_string_map_free(value));
+            if let Some(value) = self.0.as_mut() {
+                cef_sys::cef_string_map_free(value);
+            }
         }
     }
 }
@@ -507,9 +601,9 @@ impl From<*mut _cef_string_map_t> for CefStringMap {
     }
 }
 
-impl Into<*mut _cef_string_map_t> for &mut CefStringMap {
-    fn into(self) -> *mut _cef_string_map_t {
-        self.0
+impl From<&mut CefStringMap> for *mut _cef_string_map_t {
+    fn from(value: &mut CefStringMap) -> Self {
+        value.0
     }
 }
 
@@ -531,8 +625,8 @@ impl IntoIterator for CefStringMap {
                 })
                 .map(|(key, value)| {
                     (
-                        CefStringUtf8::from(&CefString::from(ptr::from_ref(&key))).to_string(),
-                        CefStringUtf8::from(&CefString::from(ptr::from_ref(&value))).to_string(),
+                        CefString::from(key).to_string(),
+                        CefString::from(value).to_string(),
                     )
                 })
                 .collect::<Vec<_>>()
@@ -548,9 +642,9 @@ pub struct CefStringMultimap(*mut _cef_string_multimap_t);
 impl Drop for CefStringMultimap {
     fn drop(&mut self) {
         unsafe {
-            self.0
-                .as_mut()
-                .map(|value| cef_sys::cef_string_multimap_free(value));
+            if let Some(value) = self.0.as_mut() {
+                cef_sys::cef_string_multimap_free(value);
+            }
         }
     }
 }
@@ -561,8 +655,107 @@ impl From<*mut _cef_string_multimap_t> for CefStringMultimap {
     }
 }
 
-impl Into<*mut _cef_string_multimap_t> for &mut CefStringMultimap {
-    fn into(self) -> *mut _cef_string_multimap_t {
-        self.0
+impl From<&mut CefStringMultimap> for *mut _cef_string_multimap_t {
+    fn from(value: &mut CefStringMultimap) -> Self {
+        value.0
+    }
+}
+
+impl Default for CefStringMultimap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CefStringMultimap {
+    /// Allocate an empty multimap.
+    pub fn new() -> Self {
+        Self(unsafe { cef_sys::cef_string_multimap_alloc() })
+    }
+
+    /// Append `value` for `key`, after any values the key already has.
+    pub fn append(&mut self, key: &str, value: &str) {
+        let Some(map) = (unsafe { self.0.as_mut() }) else {
+            return;
+        };
+        let key = CefString::from(&CefStringUtf8::from(key));
+        let value = CefString::from(&CefStringUtf8::from(value));
+        unsafe {
+            cef_sys::cef_string_multimap_append(map, (&key).into(), (&value).into());
+        }
+    }
+
+    /// Copy the key value pairs of the multimap, in order.
+    pub fn to_vec(&self) -> Vec<(String, String)> {
+        let map = unsafe { self.0.as_mut() };
+        map.map(|map| {
+            let count = unsafe { cef_sys::cef_string_multimap_size(map) };
+            (0..count)
+                .filter_map(|i| unsafe {
+                    let mut key = mem::zeroed();
+                    let mut value = mem::zeroed();
+                    (cef_sys::cef_string_multimap_key(map, i, &mut key) > 0
+                        && cef_sys::cef_string_multimap_value(map, i, &mut value) > 0)
+                        .then_some((key, value))
+                })
+                .map(|(key, value)| {
+                    (
+                        CefString::from(key).to_string(),
+                        CefString::from(value).to_string(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default()
+    }
+}
+
+impl FromIterator<(String, String)> for CefStringMultimap {
+    fn from_iter<I: IntoIterator<Item = (String, String)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        for (key, value) in iter {
+            map.append(&key, &value);
+        }
+        map
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_utf16_roundtrip() {
+        for value in [
+            "",
+            "plain ASCII",
+            "emoji 😀👩‍👩‍👧",
+            "CJK 漢字かなカナ한글",
+            "combining e\u{301} a\u{308}\u{304}",
+        ] {
+            let cef_string = CefString::from(value);
+            assert_eq!(cef_string.to_string(), value);
+            assert_eq!(
+                cef_string.as_slice().unwrap_or_default(),
+                value.encode_utf16().collect::<Vec<_>>()
+            );
+            assert_eq!(String::try_from(cef_string).unwrap(), value);
+            assert_eq!(
+                String::try_from(CefString::from(value.to_string())).unwrap(),
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn test_utf16_unpaired_surrogate() {
+        let unpaired_surrogate = [0x48, 0xd800, 0x69];
+        let value = _cef_string_utf16_t {
+            str_: unpaired_surrogate.as_ptr() as *mut _,
+            length: unpaired_surrogate.len(),
+            dtor: None,
+        };
+        let cef_string = CefString::from(ptr::from_ref(&value));
+        assert!(String::try_from(cef_string).is_err());
     }
 }
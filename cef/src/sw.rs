@@ -0,0 +1,277 @@
+//! Service worker module
+//!
+//! CEF has no API for service workers, so [ServiceWorkerManager] uses the DevTools
+//! `ServiceWorker` and `Network` domains of the browsers of a [RequestContext].
+
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    future::Future,
+    rc::Rc,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use cef_sys::{_cef_dev_tools_message_observer_t, cef_thread_id_t};
+
+use crate::{
+    devtools::{json, DevToolsError},
+    dictionary_value_create,
+    future::channel,
+    rc::{wrap_rc, RcImpl},
+    storage::origin_of,
+    thread, BrowserHost, CefString, CefStringUtf16, CefStringUtf8, DevToolsMessageObserver,
+    ImplBrowser, ImplBrowserHost, ImplDevToolsMessageObserver, ImplDictionaryValue, ImplFrame,
+    ImplRequestContext, RequestContext,
+};
+
+/// How long to collect the registrations DevTools reports after `ServiceWorker.enable`. It
+/// reports them in events which arrive after the result of the method.
+const SETTLE: Duration = Duration::from_millis(250);
+
+/// Service worker registrations and network bypass for the browsers of a request context.
+///
+/// DevTools only runs on browsers, so add the browsers with [ServiceWorkerManager::add_browser].
+/// A context without a browser has no registrations the manager can see. Clones share the
+/// browsers, and every method must be called and its future polled on the UI thread.
+#[derive(Clone, Default)]
+pub struct ServiceWorkerManager {
+    hosts: Rc<RefCell<Vec<BrowserHost>>>,
+}
+
+impl ServiceWorkerManager {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Use `host` for the service workers of its request context, until its browser closes.
+    pub fn add_browser(&self, host: &BrowserHost) {
+        self.hosts.borrow_mut().push(host.clone());
+    }
+
+    /// Unregister every service worker of `context`, and resolve with `true` if all of them
+    /// were unregistered.
+    pub fn unregister_all(&self, context: &RequestContext) -> impl Future<Output = bool> {
+        let host = self.hosts_of(context).into_iter().next();
+        async move {
+            let Some(host) = host else {
+                return true;
+            };
+            let Ok(scopes) = registered_scopes(&host).await else {
+                return false;
+            };
+            let unregistered: Vec<_> = scopes
+                .iter()
+                .map(|scope| {
+                    let params = dictionary_value_create();
+                    if let Some(params) = &params {
+                        params.set_string(Some(&key("scopeURL")), Some(&key(scope)));
+                    }
+                    host.execute_dev_tools("ServiceWorker.unregister", params)
+                })
+                .collect();
+            let mut success = true;
+            for unregistered in unregistered {
+                success &= unregistered.await.is_ok();
+            }
+            success
+        }
+    }
+
+    /// Resolve with the origins which registered a service worker in `context`, sorted and
+    /// without duplicates.
+    pub fn get_registered_origins(
+        &self,
+        context: &RequestContext,
+    ) -> impl Future<Output = Vec<String>> {
+        let host = self.hosts_of(context).into_iter().next();
+        async move {
+            let Some(host) = host else {
+                return vec![];
+            };
+            registered_scopes(&host)
+                .await
+                .map(|scopes| origins(&scopes))
+                .unwrap_or_default()
+        }
+    }
+
+    /// Let the requests of the browsers of `context` showing `origin` bypass their service
+    /// workers and go to the network. DevTools sets the bypass per browser, not per origin.
+    pub fn bypass_for_network(&self, context: &RequestContext, origin: &str, bypass: bool) {
+        let Some(origin) = origin_of(origin) else {
+            return;
+        };
+        for host in self.hosts_of(context) {
+            let shows_origin = host
+                .get_browser()
+                .and_then(|browser| browser.get_main_frame())
+                .and_then(|frame| frame.get_url())
+                .and_then(|url| origin_of(&url.to_string()))
+                .is_some_and(|url_origin| url_origin == origin);
+            if !shows_origin {
+                continue;
+            }
+            let params = dictionary_value_create();
+            if let Some(params) = &params {
+                params.set_bool(Some(&key("bypass")), bypass.into());
+            }
+            // The method runs without awaiting its result.
+            drop(host.execute_dev_tools("Network.setBypassServiceWorker", params));
+        }
+    }
+
+    /// The hosts of the open browsers of `context`, forgetting closed ones.
+    fn hosts_of(&self, context: &RequestContext) -> Vec<BrowserHost> {
+        let mut hosts = self.hosts.borrow_mut();
+        hosts.retain(|host| host.get_browser().is_some_and(|browser| browser.is_valid()));
+        hosts
+            .iter()
+            .filter(|host| {
+                host.get_request_context()
+                    .is_some_and(|host_context| host_context.is_same(Some(context)) != 0)
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+fn key(value: &str) -> CefString {
+    CefString::from(&CefStringUtf8::from(value))
+}
+
+/// The registrations by id, with their scope URL and whether they were deleted.
+type Registrations = Arc<Mutex<BTreeMap<String, (String, bool)>>>;
+
+/// Resolve with the scope URLs of the service workers registered in the request context of
+/// `host`, enabling the `ServiceWorker` domain for the request.
+async fn registered_scopes(host: &BrowserHost) -> Result<Vec<String>, DevToolsError> {
+    let registrations = Registrations::default();
+    let observer = DevToolsMessageObserver::new(RegistrationObserver {
+        object: std::ptr::null_mut(),
+        registrations: registrations.clone(),
+    });
+    let registration = host
+        .add_dev_tools_message_observer(Some(&observer))
+        .ok_or(DevToolsError::Unavailable)?;
+    let enabled = host.execute_dev_tools("ServiceWorker.enable", None).await;
+    if enabled.is_ok() {
+        let (sender, settled) = channel();
+        if thread::post_delayed(cef_thread_id_t::TID_UI, SETTLE, move || sender.send(())) {
+            settled.await;
+        }
+    }
+    drop(registration);
+    let _ = host.execute_dev_tools("ServiceWorker.disable", None).await;
+    enabled?;
+
+    let registrations = registrations
+        .lock()
+        .map_err(|_| DevToolsError::InvalidResponse)?;
+    Ok(registrations
+        .values()
+        .filter(|(_, is_deleted)| !is_deleted)
+        .map(|(scope, _)| scope.clone())
+        .collect())
+}
+
+/// Update `registrations` with the params of a `ServiceWorker.workerRegistrationUpdated` event.
+fn update_registrations(
+    params: &[u8],
+    registrations: &mut BTreeMap<String, (String, bool)>,
+) -> Result<(), DevToolsError> {
+    let params = json::parse(params)?;
+    for registration in json::elements(&params, &["registrations"])? {
+        registrations.insert(
+            json::string(&registration, &["registrationId"])?,
+            (
+                json::string(&registration, &["scopeURL"])?,
+                json::boolean(&registration, &["isDeleted"])?,
+            ),
+        );
+    }
+    Ok(())
+}
+
+/// The origins of service worker scope URLs, sorted and without duplicates.
+fn origins(scopes: &[String]) -> Vec<String> {
+    let mut origins: Vec<_> = scopes.iter().filter_map(|scope| origin_of(scope)).collect();
+    origins.sort();
+    origins.dedup();
+    origins
+}
+
+struct RegistrationObserver {
+    object: *mut RcImpl<_cef_dev_tools_message_observer_t, Self>,
+    registrations: Registrations,
+}
+
+wrap_rc!(
+    RegistrationObserver,
+    WrapDevToolsMessageObserver,
+    _cef_dev_tools_message_observer_t,
+    registrations
+);
+
+impl ImplDevToolsMessageObserver for RegistrationObserver {
+    fn on_dev_tools_event(
+        &self,
+        _browser: Option<&impl ImplBrowser>,
+        method: Option<&CefStringUtf16>,
+        params: Option<&[u8]>,
+    ) {
+        let is_update = method
+            .is_some_and(|method| method.to_string() == "ServiceWorker.workerRegistrationUpdated");
+        if let (true, Some(params), Ok(mut registrations)) =
+            (is_update, params, self.registrations.lock())
+        {
+            let _ = update_registrations(params, &mut registrations);
+        }
+    }
+
+    fn get_raw(&self) -> *mut _cef_dev_tools_message_observer_t {
+        self.object as *mut _cef_dev_tools_message_observer_t
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn registered_origins(events: &[&[u8]]) -> Vec<String> {
+        let mut registrations = BTreeMap::new();
+        for params in events {
+            update_registrations(params, &mut registrations).unwrap();
+        }
+        let scopes: Vec<_> = registrations
+            .into_values()
+            .filter(|(_, is_deleted)| !is_deleted)
+            .map(|(scope, _)| scope)
+            .collect();
+        origins(&scopes)
+    }
+
+    #[test]
+    fn test_no_service_workers() {
+        assert!(registered_origins(&[br#"{"registrations": []}"#]).is_empty());
+        assert!(registered_origins(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_registered_origins() {
+        let registered: &[u8] = br#"{"registrations": [
+            {"registrationId": "1", "scopeURL": "https://example.com/app/", "isDeleted": false},
+            {"registrationId": "2", "scopeURL": "https://example.com/", "isDeleted": false},
+            {"registrationId": "3", "scopeURL": "https://a.example.org/", "isDeleted": false}]}"#;
+        let deleted: &[u8] = br#"{"registrations": [
+            {"registrationId": "3", "scopeURL": "https://a.example.org/", "isDeleted": true}]}"#;
+        assert_eq!(
+            registered_origins(&[registered]),
+            ["https://a.example.org", "https://example.com"]
+        );
+        assert_eq!(
+            registered_origins(&[registered, deleted]),
+            ["https://example.com"]
+        );
+    }
+}
@@ -0,0 +1,495 @@
+//! Command line switches module
+//!
+//! [SwitchSet] collects the Chromium switches an application needs, and applies them in
+//! [crate::ImplApp::on_before_command_line_processing], e.g. through
+//! [crate::app::AppBuilder::switches].
+
+use crate::{CefString, CefStringUtf8, ImplCommandLine};
+
+const ENABLE_FEATURES: &str = "enable-features";
+const DISABLE_FEATURES: &str = "disable-features";
+const JS_FLAGS: &str = "js-flags";
+
+/// When media may play without a user gesture, see `--autoplay-policy`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AutoplayPolicy {
+    /// Media plays without a user gesture.
+    NoUserGestureRequired,
+    /// Media only plays after a user gesture in the frame.
+    UserGestureRequired,
+    /// Media plays once the document had a user activation, Chrome's default.
+    DocumentUserActivationRequired,
+}
+
+impl AutoplayPolicy {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::NoUserGestureRequired => "no-user-gesture-required",
+            Self::UserGestureRequired => "user-gesture-required",
+            Self::DocumentUserActivationRequired => "document-user-activation-required",
+        }
+    }
+}
+
+/// A set of Chromium command line switches.
+///
+/// Setting a switch again replaces its value. Feature lists and V8 flags are merged instead, and
+/// enabling a feature drops it from the disabled features and the other way around.
+///
+/// ```
+/// use cef::switches::{AutoplayPolicy, SwitchSet};
+///
+/// let switches = SwitchSet::new()
+///     .disable_gpu()
+///     .autoplay_policy(AutoplayPolicy::NoUserGestureRequired)
+///     .enable_features(&["OverlayScrollbar"]);
+/// assert_eq!(
+///     switches.argv(),
+///     [
+///         "--disable-gpu",
+///         "--autoplay-policy=no-user-gesture-required",
+///         "--enable-features=OverlayScrollbar",
+///     ]
+/// );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SwitchSet {
+    switches: Vec<(String, Option<String>)>,
+    js_flags: Vec<String>,
+    enable_features: Vec<String>,
+    disable_features: Vec<String>,
+    all_processes: bool,
+}
+
+macro_rules! flag_switches {
+    ($($(#[$doc:meta])* $method:ident => $name:literal,)*) => {
+        impl SwitchSet {
+            $(
+                $(#[$doc])*
+                #[doc = concat!("\n\nAdds `--", $name, "`.")]
+                pub fn $method(self) -> Self {
+                    self.raw($name, None)
+                }
+            )*
+        }
+    };
+}
+
+flag_switches! {
+    /// Disable GPU acceleration, e.g. on machines with broken drivers.
+    disable_gpu => "disable-gpu",
+    /// Composite on the CPU.
+    disable_gpu_compositing => "disable-gpu-compositing",
+    /// Keep compiled GPU shaders in memory instead of the root cache path, see
+    /// [crate::cache].
+    disable_gpu_shader_disk_cache => "disable-gpu-shader-disk-cache",
+    /// Don't fall back to software WebGL when the GPU is disabled.
+    disable_software_rasterizer => "disable-software-rasterizer",
+    /// Use the GPU even if the driver is on Chromium's blocklist.
+    ignore_gpu_blocklist => "ignore-gpu-blocklist",
+    /// Connect directly instead of using the system proxy settings.
+    no_proxy_server => "no-proxy-server",
+    /// Turn off the same-origin policy. Only for trusted content.
+    disable_web_security => "disable-web-security",
+    /// Let `file://` pages read other `file://` URLs.
+    allow_file_access_from_files => "allow-file-access-from-files",
+    /// Grant camera and microphone requests without prompting.
+    use_fake_ui_for_media_stream => "use-fake-ui-for-media-stream",
+    /// Replace the camera and microphone with test patterns.
+    use_fake_device_for_media_stream => "use-fake-device-for-media-stream",
+    /// Enable `getUserMedia` in Alloy style browsers.
+    enable_media_stream => "enable-media-stream",
+    /// Mute all audio output.
+    mute_audio => "mute-audio",
+    /// Print without showing the print preview.
+    kiosk_printing => "kiosk-printing",
+    /// Use the dark theme for the browser UI and `prefers-color-scheme`.
+    force_dark_mode => "force-dark-mode",
+    /// Don't check for component updates such as Widevine.
+    disable_component_update => "disable-component-update",
+    /// Disable the Chrome extensions system.
+    disable_extensions => "disable-extensions",
+    /// Don't throttle the timers of pages in the background.
+    disable_background_timer_throttling => "disable-background-timer-throttling",
+    /// Don't lower the priority of renderers in the background.
+    disable_renderer_backgrounding => "disable-renderer-backgrounding",
+    /// Don't treat occluded windows as hidden.
+    disable_backgrounding_occluded_windows => "disable-backgrounding-occluded-windows",
+    /// Write the log to stderr instead of a file.
+    enable_logging_to_stderr => "enable-logging=stderr",
+}
+
+impl SwitchSet {
+    /// Create an empty set, applied to the browser process only.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Also apply the switches to the command line of the other processes, which mostly get
+    /// their switches from the browser process already.
+    pub fn all_processes(mut self) -> Self {
+        self.all_processes = true;
+        self
+    }
+
+    /// When media may play without a user gesture.
+    pub fn autoplay_policy(self, policy: AutoplayPolicy) -> Self {
+        self.raw("autoplay-policy", Some(policy.as_str()))
+    }
+
+    /// Override the device scale factor of all displays.
+    pub fn force_device_scale_factor(self, scale_factor: f32) -> Self {
+        self.raw("force-device-scale-factor", Some(&scale_factor.to_string()))
+    }
+
+    /// Pass `flags` to V8, e.g. `--expose-gc`. Flags of repeated calls are kept.
+    pub fn js_flags(mut self, flags: &str) -> Self {
+        self.js_flags
+            .extend(flags.split_whitespace().map(str::to_string));
+        self
+    }
+
+    /// Enable Chromium features. Features of repeated calls are kept.
+    pub fn enable_features(mut self, features: &[&str]) -> Self {
+        merge_features(
+            &mut self.enable_features,
+            &mut self.disable_features,
+            features,
+        );
+        self
+    }
+
+    /// Disable Chromium features. Features of repeated calls are kept.
+    pub fn disable_features(mut self, features: &[&str]) -> Self {
+        merge_features(
+            &mut self.disable_features,
+            &mut self.enable_features,
+            features,
+        );
+        self
+    }
+
+    /// Serve the DevTools protocol on `port`, see also [SwitchSet::remote_allow_origins].
+    pub fn remote_debugging_port(self, port: u16) -> Self {
+        self.raw("remote-debugging-port", Some(&port.to_string()))
+    }
+
+    /// The comma separated origins allowed to connect to the DevTools port, or `*`.
+    pub fn remote_allow_origins(self, origins: &str) -> Self {
+        self.raw("remote-allow-origins", Some(origins))
+    }
+
+    /// Use the proxy `server`, e.g. `http://proxy:8080` or `socks5://proxy:1080`.
+    pub fn proxy_server(self, server: &str) -> Self {
+        self.raw("proxy-server", Some(server))
+    }
+
+    /// The `;` separated hosts which don't use the [SwitchSet::proxy_server].
+    pub fn proxy_bypass_list(self, hosts: &str) -> Self {
+        self.raw("proxy-bypass-list", Some(hosts))
+    }
+
+    /// Replace the default user agent.
+    pub fn user_agent(self, user_agent: &str) -> Self {
+        self.raw("user-agent", Some(user_agent))
+    }
+
+    /// The verbosity of `VLOG` messages in the log.
+    pub fn log_verbosity(self, level: u32) -> Self {
+        self.raw("v", Some(&level.to_string()))
+    }
+
+    /// The Ozone platform on Linux, `x11` or `wayland`.
+    pub fn ozone_platform(self, platform: &str) -> Self {
+        self.raw("ozone-platform", Some(platform))
+    }
+
+    /// Add the switch `name`, with or without leading dashes, and an optional value. Replaces an
+    /// earlier value of the same switch, except for the merged feature lists and V8 flags.
+    pub fn raw(mut self, name: &str, value: Option<&str>) -> Self {
+        let (name, value) = match name.trim_start_matches('-').split_once('=') {
+            Some((name, inline)) if value.is_none() => (name, Some(inline)),
+            _ => (name.trim_start_matches('-'), value),
+        };
+        match (name, value) {
+            (ENABLE_FEATURES, Some(value)) => {
+                return self.enable_features(&value.split(',').collect::<Vec<_>>())
+            }
+            (DISABLE_FEATURES, Some(value)) => {
+                return self.disable_features(&value.split(',').collect::<Vec<_>>())
+            }
+            (JS_FLAGS, Some(value)) => return self.js_flags(value),
+            _ => {}
+        }
+        let value = value.map(str::to_string);
+        match self.switches.iter_mut().find(|(switch, _)| switch == name) {
+            Some(switch) => switch.1 = value,
+            None => self.switches.push((name.to_string(), value)),
+        }
+        self
+    }
+
+    /// The switches as command line arguments, with the merged lists last.
+    pub fn argv(&self) -> Vec<String> {
+        let mut argv: Vec<_> = self
+            .switches
+            .iter()
+            .map(|(name, value)| match value {
+                Some(value) => format!("--{name}={value}"),
+                None => format!("--{name}"),
+            })
+            .collect();
+        argv.extend(
+            self.merged(&[], &[], &[])
+                .into_iter()
+                .map(|(name, value)| format!("--{name}={value}")),
+        );
+        argv
+    }
+
+    /// `true` if the switches apply to the process of `process_type`, which is empty for the
+    /// browser process.
+    pub fn applies_to(&self, process_type: &str) -> bool {
+        process_type.is_empty() || self.all_processes
+    }
+
+    /// Append the switches to `command_line`. The feature lists and V8 flags are merged with
+    /// those already on the command line.
+    pub fn apply(&self, command_line: &impl ImplCommandLine) {
+        for (name, value) in &self.switches {
+            let name = cef_string(name);
+            match value {
+                Some(value) => {
+                    command_line.append_switch_with_value(Some(&name), Some(&cef_string(value)))
+                }
+                None => command_line.append_switch(Some(&name)),
+            }
+        }
+
+        let existing = |name: &str| {
+            command_line
+                .get_switch_value(Some(&cef_string(name)))
+                .map(|value| value.to_string())
+                .unwrap_or_default()
+        };
+        let (enable, disable, js_flags) = (
+            existing(ENABLE_FEATURES),
+            existing(DISABLE_FEATURES),
+            existing(JS_FLAGS),
+        );
+        let split = |value: &str, separator: char| {
+            value
+                .split(separator)
+                .filter(|item| !item.is_empty())
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        };
+        // Chromium uses the last value of a repeated switch, so the merged value replaces it.
+        for (name, value) in self.merged(
+            &split(&enable, ','),
+            &split(&disable, ','),
+            &split(&js_flags, ' '),
+        ) {
+            command_line
+                .append_switch_with_value(Some(&cef_string(name)), Some(&cef_string(&value)));
+        }
+    }
+
+    /// The feature lists and V8 flags merged into `enable`, `disable` and `js_flags`. Lists this
+    /// set doesn't change are skipped, and so are empty ones unless they clear a list.
+    fn merged(
+        &self,
+        enable: &[String],
+        disable: &[String],
+        js_flags: &[String],
+    ) -> Vec<(&'static str, String)> {
+        let mut enabled = enable.to_vec();
+        let mut disabled = disable.to_vec();
+        merge_features(&mut enabled, &mut disabled, &self.enable_features);
+        merge_features(&mut disabled, &mut enabled, &self.disable_features);
+        let changes_features =
+            !self.enable_features.is_empty() || !self.disable_features.is_empty();
+
+        let mut flags = js_flags.to_vec();
+        for flag in &self.js_flags {
+            if !flags.contains(flag) {
+                flags.push(flag.clone());
+            }
+        }
+
+        [
+            (ENABLE_FEATURES, enabled, enable, changes_features),
+            (DISABLE_FEATURES, disabled, disable, changes_features),
+            (JS_FLAGS, flags, js_flags, !self.js_flags.is_empty()),
+        ]
+        .into_iter()
+        .filter(|(_, value, existing, changed)| {
+            *changed && !(value.is_empty() && existing.is_empty())
+        })
+        .map(|(name, value, ..)| {
+            let separator = if name == JS_FLAGS { " " } else { "," };
+            (name, value.join(separator))
+        })
+        .collect()
+    }
+}
+
+/// Add `features` to `list` and remove them from `opposite`.
+fn merge_features(
+    list: &mut Vec<String>,
+    opposite: &mut Vec<String>,
+    features: &[impl AsRef<str>],
+) {
+    for feature in features.iter().map(|feature| feature.as_ref().trim()) {
+        if feature.is_empty() {
+            continue;
+        }
+        opposite.retain(|item| item != feature);
+        if !list.iter().any(|item| item == feature) {
+            list.push(feature.to_string());
+        }
+    }
+}
+
+fn cef_string(value: &str) -> CefString {
+    CefString::from(&CefStringUtf8::from(value))
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use cef_sys::{_cef_command_line_t, cef_base_ref_counted_t};
+
+    use super::*;
+    use crate::CefStringUtf16;
+
+    #[test]
+    fn test_argv() {
+        let switches = SwitchSet::new()
+            .disable_gpu()
+            .force_device_scale_factor(1.5)
+            .remote_debugging_port(9222)
+            .raw("--proxy-server", Some("http://a:1"))
+            .proxy_server("http://b:2")
+            .raw("--lang=de", None)
+            .js_flags("--expose-gc")
+            .js_flags("--max-old-space-size=512 --expose-gc");
+        assert_eq!(
+            switches.argv(),
+            [
+                "--disable-gpu",
+                "--force-device-scale-factor=1.5",
+                "--remote-debugging-port=9222",
+                "--proxy-server=http://b:2",
+                "--lang=de",
+                "--js-flags=--expose-gc --max-old-space-size=512",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_feature_merging() {
+        let switches = SwitchSet::new()
+            .enable_features(&["A", "B"])
+            .disable_features(&["C", "A"])
+            .enable_features(&["B", "D"])
+            .raw("enable-features", Some("E,C"));
+        assert_eq!(
+            switches.argv(),
+            ["--enable-features=B,D,E,C", "--disable-features=A"]
+        );
+    }
+
+    #[test]
+    fn test_applies_to() {
+        let switches = SwitchSet::new().disable_gpu();
+        assert!(switches.applies_to(""));
+        assert!(!switches.applies_to("renderer"));
+        assert!(switches.all_processes().applies_to("renderer"));
+    }
+
+    /// A command line which records the appended switches.
+    #[derive(Clone)]
+    struct Mock {
+        base: Arc<cef_base_ref_counted_t>,
+        existing: Vec<(&'static str, &'static str)>,
+        appended: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl crate::rc::Rc for Mock {
+        fn as_base(&self) -> &cef_base_ref_counted_t {
+            &self.base
+        }
+    }
+
+    impl ImplCommandLine for Mock {
+        fn get_switch_value(&self, name: Option<&CefStringUtf16>) -> Option<CefStringUtf16> {
+            let name = name?.to_string();
+            let (_, value) = self.existing.iter().find(|(switch, _)| *switch == name)?;
+            Some(cef_string(value))
+        }
+
+        fn append_switch(&self, name: Option<&CefStringUtf16>) {
+            let name = name.map(|name| name.to_string()).unwrap_or_default();
+            self.appended.lock().unwrap().push(format!("--{name}"));
+        }
+
+        fn append_switch_with_value(
+            &self,
+            name: Option<&CefStringUtf16>,
+            value: Option<&CefStringUtf16>,
+        ) {
+            let name = name.map(|name| name.to_string()).unwrap_or_default();
+            let value = value.map(|value| value.to_string()).unwrap_or_default();
+            self.appended
+                .lock()
+                .unwrap()
+                .push(format!("--{name}={value}"));
+        }
+
+        fn get_raw(&self) -> *mut _cef_command_line_t {
+            std::ptr::null_mut()
+        }
+    }
+
+    #[test]
+    fn test_apply_merges_existing() {
+        let command_line = Mock {
+            base: Arc::new(unsafe { std::mem::zeroed() }),
+            existing: vec![
+                (ENABLE_FEATURES, "A,B"),
+                (DISABLE_FEATURES, "C"),
+                (JS_FLAGS, "--expose-gc"),
+            ],
+            appended: Default::default(),
+        };
+        SwitchSet::new()
+            .mute_audio()
+            .enable_features(&["C"])
+            .disable_features(&["B"])
+            .apply(&command_line);
+        assert_eq!(
+            *command_line.appended.lock().unwrap(),
+            [
+                "--mute-audio",
+                "--enable-features=A,C",
+                "--disable-features=B",
+            ]
+        );
+
+        command_line.appended.lock().unwrap().clear();
+        SwitchSet::new()
+            .enable_features(&["C"])
+            .js_flags("--jitless")
+            .apply(&command_line);
+        assert_eq!(
+            *command_line.appended.lock().unwrap(),
+            [
+                "--enable-features=A,B,C",
+                "--disable-features=",
+                "--js-flags=--expose-gc --jitless",
+            ]
+        );
+    }
+}
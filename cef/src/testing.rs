@@ -0,0 +1,472 @@
+//! Testing module
+//!
+//! [BrowserEventRecorder] records the input sent to a browser so a test can replay it later
+//! with the same timing. Enable the `serde` feature to save recordings.
+
+use std::{
+    os::raw::c_int,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use cef_sys::{
+    cef_key_event_type_t, cef_mouse_button_type_t, cef_pointer_type_t, cef_touch_event_type_t,
+};
+
+use crate::{diagnostics, BrowserHost, ImplBrowserHost, KeyEvent, MouseEvent, TouchEvent};
+
+macro_rules! event_enum {
+    (
+        $(#[$attr:meta])*
+        $name:ident($raw:ident) {
+            $first:ident = $raw_first:ident,
+            $($variant:ident = $raw_variant:ident,)*
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        pub enum $name {
+            $first,
+            $($variant,)*
+        }
+
+        impl From<$raw> for $name {
+            fn from(value: $raw) -> Self {
+                match value {
+                    $raw::$raw_first => Self::$first,
+                    $($raw::$raw_variant => Self::$variant,)*
+                    // Values added by a newer CEF fall back to the first one.
+                    _ => diagnostics::unknown_enum_value(value, Self::$first),
+                }
+            }
+        }
+
+        impl From<$name> for $raw {
+            fn from(value: $name) -> Self {
+                match value {
+                    $name::$first => Self::$raw_first,
+                    $($name::$variant => Self::$raw_variant,)*
+                }
+            }
+        }
+    };
+}
+
+event_enum! {
+    /// See [cef_mouse_button_type_t].
+    MouseButton(cef_mouse_button_type_t) {
+        Left = MBT_LEFT,
+        Middle = MBT_MIDDLE,
+        Right = MBT_RIGHT,
+    }
+}
+
+event_enum! {
+    /// See [cef_key_event_type_t].
+    KeyEventKind(cef_key_event_type_t) {
+        RawKeyDown = KEYEVENT_RAWKEYDOWN,
+        KeyDown = KEYEVENT_KEYDOWN,
+        KeyUp = KEYEVENT_KEYUP,
+        Char = KEYEVENT_CHAR,
+    }
+}
+
+event_enum! {
+    /// See [cef_touch_event_type_t].
+    TouchEventKind(cef_touch_event_type_t) {
+        Released = CEF_TET_RELEASED,
+        Pressed = CEF_TET_PRESSED,
+        Moved = CEF_TET_MOVED,
+        Cancelled = CEF_TET_CANCELLED,
+    }
+}
+
+event_enum! {
+    /// See [cef_pointer_type_t].
+    PointerKind(cef_pointer_type_t) {
+        Touch = CEF_POINTER_TYPE_TOUCH,
+        Mouse = CEF_POINTER_TYPE_MOUSE,
+        Pen = CEF_POINTER_TYPE_PEN,
+        Eraser = CEF_POINTER_TYPE_ERASER,
+        Unknown = CEF_POINTER_TYPE_UNKNOWN,
+    }
+}
+
+/// An input event sent through a [BrowserEventRecorder], with the time since recording started.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RecordedEvent {
+    MouseClick {
+        at: Duration,
+        x: i32,
+        y: i32,
+        modifiers: u32,
+        button: MouseButton,
+        mouse_up: bool,
+        click_count: i32,
+    },
+    Key {
+        at: Duration,
+        kind: KeyEventKind,
+        modifiers: u32,
+        windows_key_code: i32,
+        native_key_code: i32,
+        is_system_key: bool,
+        character: u16,
+        unmodified_character: u16,
+        focus_on_editable_field: bool,
+    },
+    Touch {
+        at: Duration,
+        id: i32,
+        x: f32,
+        y: f32,
+        radius_x: f32,
+        radius_y: f32,
+        rotation_angle: f32,
+        pressure: f32,
+        kind: TouchEventKind,
+        modifiers: u32,
+        pointer: PointerKind,
+    },
+}
+
+impl RecordedEvent {
+    /// The time of the event since recording started.
+    pub fn at(&self) -> Duration {
+        match self {
+            Self::MouseClick { at, .. } | Self::Key { at, .. } | Self::Touch { at, .. } => *at,
+        }
+    }
+
+    fn mouse_click(
+        at: Duration,
+        event: &MouseEvent,
+        button: MouseButton,
+        mouse_up: bool,
+        click_count: i32,
+    ) -> Self {
+        Self::MouseClick {
+            at,
+            x: event.x,
+            y: event.y,
+            modifiers: event.modifiers,
+            button,
+            mouse_up,
+            click_count,
+        }
+    }
+
+    fn key(at: Duration, event: &KeyEvent) -> Self {
+        let kind: cef_key_event_type_t = event.type_.into();
+        Self::Key {
+            at,
+            kind: kind.into(),
+            modifiers: event.modifiers,
+            windows_key_code: event.windows_key_code,
+            native_key_code: event.native_key_code,
+            is_system_key: event.is_system_key != 0,
+            character: event.character,
+            unmodified_character: event.unmodified_character,
+            focus_on_editable_field: event.focus_on_editable_field != 0,
+        }
+    }
+
+    fn touch(at: Duration, event: &TouchEvent) -> Self {
+        let kind: cef_touch_event_type_t = event.type_.into();
+        let pointer: cef_pointer_type_t = event.pointer_type.into();
+        Self::Touch {
+            at,
+            id: event.id,
+            x: event.x,
+            y: event.y,
+            radius_x: event.radius_x,
+            radius_y: event.radius_y,
+            rotation_angle: event.rotation_angle,
+            pressure: event.pressure,
+            kind: kind.into(),
+            modifiers: event.modifiers,
+            pointer: pointer.into(),
+        }
+    }
+
+    /// Send the event to `browser`.
+    pub fn send_to(&self, browser: &BrowserHost) {
+        match self.clone() {
+            Self::MouseClick {
+                x,
+                y,
+                modifiers,
+                button,
+                mouse_up,
+                click_count,
+                ..
+            } => browser.send_mouse_click_event(
+                Some(&MouseEvent { x, y, modifiers }),
+                cef_mouse_button_type_t::from(button).into(),
+                mouse_up as c_int,
+                click_count,
+            ),
+            Self::Key {
+                kind,
+                modifiers,
+                windows_key_code,
+                native_key_code,
+                is_system_key,
+                character,
+                unmodified_character,
+                focus_on_editable_field,
+                ..
+            } => browser.send_key_event(Some(&KeyEvent {
+                type_: cef_key_event_type_t::from(kind).into(),
+                modifiers,
+                windows_key_code,
+                native_key_code,
+                is_system_key: is_system_key as c_int,
+                character,
+                unmodified_character,
+                focus_on_editable_field: focus_on_editable_field as c_int,
+            })),
+            Self::Touch {
+                id,
+                x,
+                y,
+                radius_x,
+                radius_y,
+                rotation_angle,
+                pressure,
+                kind,
+                modifiers,
+                pointer,
+                ..
+            } => browser.send_touch_event(Some(&TouchEvent {
+                id,
+                x,
+                y,
+                radius_x,
+                radius_y,
+                rotation_angle,
+                pressure,
+                type_: cef_touch_event_type_t::from(kind).into(),
+                modifiers,
+                pointer_type: cef_pointer_type_t::from(pointer).into(),
+            })),
+        }
+    }
+}
+
+/// Sends input events to a browser like [BrowserHost] does, and records them.
+#[derive(Clone)]
+pub struct BrowserEventRecorder {
+    browser: BrowserHost,
+    recording: Recording,
+}
+
+impl BrowserEventRecorder {
+    /// Start recording the events sent to `browser`.
+    pub fn new(browser: BrowserHost) -> Self {
+        Self {
+            browser,
+            recording: Recording::new(),
+        }
+    }
+
+    /// See [ImplBrowserHost::send_mouse_click_event].
+    pub fn send_mouse_click_event(
+        &self,
+        event: &MouseEvent,
+        button: MouseButton,
+        mouse_up: bool,
+        click_count: i32,
+    ) {
+        let event = self
+            .recording
+            .mouse_click(event, button, mouse_up, click_count);
+        event.send_to(&self.browser);
+    }
+
+    /// See [ImplBrowserHost::send_key_event].
+    pub fn send_key_event(&self, event: &KeyEvent) {
+        self.recording.key(event).send_to(&self.browser);
+    }
+
+    /// See [ImplBrowserHost::send_touch_event].
+    pub fn send_touch_event(&self, event: &TouchEvent) {
+        self.recording.touch(event).send_to(&self.browser);
+    }
+
+    /// The events recorded so far.
+    pub fn events(&self) -> Vec<RecordedEvent> {
+        self.recording.events()
+    }
+
+    /// Send `events` to `browser`, waiting between them as long as when they were recorded.
+    ///
+    /// This blocks the calling thread until the last event is sent, so call it from a thread
+    /// other than the UI thread. CEF forwards the events to the UI thread.
+    pub fn replay(browser: &BrowserHost, events: &[RecordedEvent]) {
+        replay_with(events, thread::sleep, |event| event.send_to(browser));
+    }
+}
+
+#[derive(Clone)]
+struct Recording {
+    start: Instant,
+    events: Arc<Mutex<Vec<RecordedEvent>>>,
+}
+
+impl Recording {
+    fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            events: Default::default(),
+        }
+    }
+
+    fn push(&self, event: RecordedEvent) -> RecordedEvent {
+        if let Ok(mut events) = self.events.lock() {
+            events.push(event.clone());
+        }
+        event
+    }
+
+    fn mouse_click(
+        &self,
+        event: &MouseEvent,
+        button: MouseButton,
+        mouse_up: bool,
+        click_count: i32,
+    ) -> RecordedEvent {
+        let at = self.start.elapsed();
+        self.push(RecordedEvent::mouse_click(
+            at,
+            event,
+            button,
+            mouse_up,
+            click_count,
+        ))
+    }
+
+    fn key(&self, event: &KeyEvent) -> RecordedEvent {
+        self.push(RecordedEvent::key(self.start.elapsed(), event))
+    }
+
+    fn touch(&self, event: &TouchEvent) -> RecordedEvent {
+        self.push(RecordedEvent::touch(self.start.elapsed(), event))
+    }
+
+    fn events(&self) -> Vec<RecordedEvent> {
+        self.events
+            .lock()
+            .map(|events| events.clone())
+            .unwrap_or_default()
+    }
+}
+
+fn replay_with(
+    events: &[RecordedEvent],
+    mut sleep: impl FnMut(Duration),
+    mut send: impl FnMut(&RecordedEvent),
+) {
+    let mut previous = events.first().map(RecordedEvent::at).unwrap_or_default();
+    for event in events {
+        let at = event.at();
+        sleep(at.saturating_sub(previous));
+        previous = previous.max(at);
+        send(event);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn record_five_events() -> Vec<RecordedEvent> {
+        let recording = Recording::new();
+        let mouse = MouseEvent {
+            x: 10,
+            y: 20,
+            modifiers: 0,
+        };
+        recording.mouse_click(&mouse, MouseButton::Left, false, 1);
+        thread::sleep(Duration::from_millis(5));
+        recording.mouse_click(&mouse, MouseButton::Left, true, 1);
+        let mut key = KeyEvent {
+            type_: cef_key_event_type_t::KEYEVENT_RAWKEYDOWN.into(),
+            windows_key_code: 0x41,
+            character: 'a' as u16,
+            ..Default::default()
+        };
+        recording.key(&key);
+        key.type_ = cef_key_event_type_t::KEYEVENT_KEYUP.into();
+        recording.key(&key);
+        recording.touch(&TouchEvent {
+            id: 1,
+            x: 1.5,
+            y: 2.5,
+            radius_x: 0.0,
+            radius_y: 0.0,
+            rotation_angle: 0.0,
+            pressure: 0.5,
+            type_: cef_touch_event_type_t::CEF_TET_PRESSED.into(),
+            modifiers: 0,
+            pointer_type: cef_pointer_type_t::CEF_POINTER_TYPE_PEN.into(),
+        });
+        recording.events()
+    }
+
+    fn replay(events: &[RecordedEvent]) -> (Vec<Duration>, Vec<RecordedEvent>) {
+        let (mut waits, mut sent) = (vec![], vec![]);
+        replay_with(
+            events,
+            |wait| waits.push(wait),
+            |event| sent.push(event.clone()),
+        );
+        (waits, sent)
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let events = record_five_events();
+        assert_eq!(events.len(), 5);
+        assert!(matches!(
+            events[2],
+            RecordedEvent::Key {
+                kind: KeyEventKind::RawKeyDown,
+                windows_key_code: 0x41,
+                ..
+            }
+        ));
+        assert!(matches!(
+            events[4],
+            RecordedEvent::Touch {
+                kind: TouchEventKind::Pressed,
+                pointer: PointerKind::Pen,
+                ..
+            }
+        ));
+
+        let (waits, sent) = replay(&events);
+        assert_eq!(sent, events);
+        assert_eq!(waits[0], Duration::ZERO);
+        assert!(waits[1] >= Duration::from_millis(5));
+        assert_eq!(
+            waits.iter().sum::<Duration>(),
+            events[4].at() - events[0].at()
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_record_serialize_and_replay() {
+        let events = record_five_events();
+        let json = serde_json::to_string(&events).unwrap();
+        let events: Vec<RecordedEvent> = serde_json::from_str(&json).unwrap();
+        assert_eq!(events.len(), 5);
+
+        let (_, sent) = replay(&events);
+        assert_eq!(sent, events);
+    }
+}
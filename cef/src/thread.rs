@@ -0,0 +1,423 @@
+//! Thread module
+//!
+//! Most CEF objects may only be used on the browser process UI thread. [UiBound] lets other
+//! threads own such an object without being able to touch it, and [post] and [post_delayed] run
+//! closures on a CEF thread. [assert_on_thread] catches calls from the wrong thread.
+//!
+//! Only the wrappers whose docs say they panic off the UI thread assert it: DevTools methods
+//! ([crate::BrowserHost::execute_dev_tools] and everything built on it), preferences,
+//! [crate::RequestContext::clear_cache] and [crate::profile::ProfileManager::create_or_open].
+//! Creating a browser fails with [crate::browser::CreateBrowserError::WrongThread] instead, and
+//! the other wrappers leave the check to CEF.
+//!
+//! CEF calls each handler method on a fixed thread, see [callback_thread]. Wrap a handler impl
+//! in [check_threads] to check this in debug builds, so e.g. a `BrowserHost` touched from an
+//! IO thread callback panics with the name of the callback instead of crashing CEF later.
+
+use std::{cell::Cell, fmt, mem::ManuallyDrop, time::Duration};
+
+use cef_sys::cef_thread_id_t;
+
+use crate::{
+    callback::task,
+    currently_on,
+    diagnostics::{self, DiagnosticCategory},
+    post_delayed_task, post_task, task_runner_get_for_current_thread, Task, TaskRunner, ThreadId,
+};
+
+thread_local! {
+    /// The CEF thread of the last task [post] ran on this thread.
+    static LAST_THREAD: Cell<Option<ThreadId>> = const { Cell::new(None) };
+    /// The handler method checked by [check_threads] which is running on this thread.
+    static CALLBACK: Cell<Option<Callback>> = const { Cell::new(None) };
+}
+
+/// The threads which [currently_on] can identify.
+const THREADS: [cef_thread_id_t; 7] = [
+    cef_thread_id_t::TID_UI,
+    cef_thread_id_t::TID_IO,
+    cef_thread_id_t::TID_RENDERER,
+    cef_thread_id_t::TID_FILE_BACKGROUND,
+    cef_thread_id_t::TID_FILE_USER_VISIBLE,
+    cef_thread_id_t::TID_FILE_USER_BLOCKING,
+    cef_thread_id_t::TID_PROCESS_LAUNCHER,
+];
+
+/// `true` if called on the browser process UI thread.
+pub fn is_ui_thread() -> bool {
+    currently_on(ThreadId::from(cef_thread_id_t::TID_UI)) != 0
+}
+
+/// The task runner of the current thread, or `None` if it isn't a CEF thread.
+pub fn current_task_runner() -> Option<TaskRunner> {
+    task_runner_get_for_current_thread()
+}
+
+/// Panic unless called on the CEF thread `thread`, naming the thread it was called on instead if
+/// [post] ran a task there before.
+///
+/// Inside a handler method checked by [check_threads], the message names the method too.
+#[track_caller]
+pub fn assert_on_thread(thread: impl Into<ThreadId>) {
+    let thread = thread.into();
+    if currently_on(thread) != 0 {
+        return;
+    }
+    let inside = CALLBACK
+        .get()
+        .map(|callback| format!(" inside {callback}"))
+        .unwrap_or_default();
+    let thread: cef_thread_id_t = thread.into();
+    panic!("must be called on {thread:?}, but was called on {CurrentThread}{inside}");
+}
+
+/// The thread CEF calls the method `method` of the handler trait `handler` on, e.g.
+/// `TID_IO` for `ImplResourceRequestHandler` and `on_before_resource_load`. Generated from the
+/// header docs, so `None` for methods CEF may call on different threads, for methods whose docs
+/// don't name a thread, and for unknown ones.
+pub fn callback_thread(handler: &str, method: &str) -> Option<cef_thread_id_t> {
+    let handler = handler.strip_prefix("Impl").unwrap_or(handler);
+    crate::bindings::callback_thread(handler, method)
+}
+
+/// Check that a handler impl is only called on the threads CEF calls it on, see
+/// [callback_thread], in debug builds. A method called on the wrong thread panics with its name
+/// and the thread, and [assert_on_thread] and [UiBound::get] name the method they are used in.
+///
+/// The trait must be named by its identifier, e.g. `ImplDisplayHandler`. Here `MyHandler` keeps
+/// the host of its browser in a `UiBound<BrowserHost>`:
+///
+/// ```ignore
+/// cef::check_threads! {
+///     impl ImplResourceRequestHandler for MyHandler {
+///         fn on_before_resource_load(
+///             &self,
+///             browser: Option<&impl ImplBrowser>,
+///             frame: Option<&impl ImplFrame>,
+///             request: Option<&impl ImplRequest>,
+///             callback: Option<&impl ImplCallback>,
+///         ) -> ReturnValue {
+///             // `None` on the IO thread, reported as `DiagnosticCategory::WrongThread`.
+///             if let Some(host) = self.host.get() {
+///                 host.was_resized();
+///             }
+///             ReturnValue::RV_CONTINUE
+///         }
+///
+///         fn get_raw(&self) -> *mut _cef_resource_request_handler_t {
+///             self.object as *mut _
+///         }
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! check_threads {
+    (
+        $(#[$impl_attr:meta])*
+        impl $handler:ident for $ty:ty {
+            $(
+                $(#[$attr:meta])*
+                fn $method:ident($($params:tt)*) $(-> $ret:ty)? $body:block
+            )*
+        }
+    ) => {
+        $(#[$impl_attr])*
+        impl $handler for $ty {
+            $(
+                $(#[$attr])*
+                fn $method($($params)*) $(-> $ret)? {
+                    let _callback = ::std::cfg!(debug_assertions).then(|| {
+                        $crate::thread::enter_callback(
+                            ::std::stringify!($handler),
+                            ::std::stringify!($method),
+                        )
+                    });
+                    $body
+                }
+            )*
+        }
+    };
+}
+
+pub use crate::check_threads;
+
+/// A handler method checked by [check_threads].
+#[derive(Clone, Copy)]
+struct Callback {
+    handler: &'static str,
+    method: &'static str,
+}
+
+impl fmt::Display for Callback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let handler = self.handler.strip_prefix("Impl").unwrap_or(self.handler);
+        write!(f, "{handler}::{}", self.method)
+    }
+}
+
+/// Check that `method` of `handler` runs on its [callback_thread], and remember it as the running
+/// callback until the returned guard is dropped. Used by [check_threads].
+#[doc(hidden)]
+#[track_caller]
+pub fn enter_callback(handler: &'static str, method: &'static str) -> Option<CallbackGuard> {
+    let thread = callback_thread(handler, method)?;
+    let callback = Callback { handler, method };
+    if currently_on(thread.into()) == 0 {
+        panic!("{callback} must be called on {thread:?}, but was called on {CurrentThread}");
+    }
+    Some(CallbackGuard {
+        previous: CALLBACK.replace(Some(callback)),
+    })
+}
+
+/// Restores the callback running before [enter_callback], since callbacks may nest.
+#[doc(hidden)]
+pub struct CallbackGuard {
+    previous: Option<Callback>,
+}
+
+impl Drop for CallbackGuard {
+    fn drop(&mut self) {
+        CALLBACK.set(self.previous);
+    }
+}
+
+/// Formats the CEF thread this is called on, as far as it can be told.
+struct CurrentThread;
+
+impl fmt::Display for CurrentThread {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let current = THREADS
+            .into_iter()
+            .find(|&thread| currently_on(thread.into()) != 0)
+            .or_else(|| LAST_THREAD.get().map(Into::into));
+        match current {
+            Some(thread) => write!(f, "{thread:?}"),
+            None => write!(f, "a thread unknown to CEF"),
+        }
+    }
+}
+
+/// A task which records `thread_id` as the thread it runs on before running `callback`.
+fn thread_task(thread_id: ThreadId, callback: impl FnOnce() + Send + 'static) -> Task {
+    task(move || {
+        LAST_THREAD.set(Some(thread_id));
+        callback();
+    })
+}
+
+/// Run `callback` on the CEF thread `thread_id`. Returns `false` if the task could not be posted,
+/// e.g. after shutdown, in which case `callback` is dropped without running.
+pub fn post(thread_id: impl Into<ThreadId>, callback: impl FnOnce() + Send + 'static) -> bool {
+    let thread_id = thread_id.into();
+    post_task(thread_id, Some(&thread_task(thread_id, callback))) != 0
+}
+
+/// Run `callback` on the CEF thread `thread_id` after `delay`, like [post].
+pub fn post_delayed(
+    thread_id: impl Into<ThreadId>,
+    delay: Duration,
+    callback: impl FnOnce() + Send + 'static,
+) -> bool {
+    // Round up, so the callback doesn't run before `delay` has passed.
+    let delay_ms = i64::try_from(delay.as_nanos().div_ceil(1_000_000)).unwrap_or(i64::MAX);
+    let thread_id = thread_id.into();
+    post_delayed_task(thread_id, Some(&thread_task(thread_id, callback)), delay_ms) != 0
+}
+
+/// A value which can be sent to other threads, but only used on the UI thread.
+///
+/// [UiBound::get] returns `None` anywhere else, and [UiBound::post] runs a closure with the value
+/// on the UI thread. If a `UiBound` is dropped on another thread, the value is dropped on the UI
+/// thread, or leaked if CEF has already shut down.
+///
+/// ```no_run
+/// use cef::{thread::UiBound, Browser, ImplBrowser};
+///
+/// fn watch(browser: Browser) {
+///     // On the UI thread.
+///     let browser = UiBound::new(browser).ok().expect("not on the UI thread");
+///     std::thread::spawn(move || {
+///         // Misuse is caught instead of calling into CEF from the wrong thread.
+///         assert!(browser.get().is_none());
+///         browser.post(|browser| {
+///             let _ = browser.get_host();
+///         });
+///     });
+/// }
+/// ```
+pub struct UiBound<T: 'static> {
+    value: Option<T>,
+}
+
+// The value is only accessed and dropped on the UI thread.
+unsafe impl<T: 'static> Send for UiBound<T> {}
+
+impl<T: 'static> UiBound<T> {
+    /// Bind `value` to the UI thread, or give it back if not called on the UI thread.
+    pub fn new(value: T) -> Result<Self, T> {
+        if is_ui_thread() {
+            Ok(Self { value: Some(value) })
+        } else {
+            Err(value)
+        }
+    }
+
+    /// The value, or `None` if not called on the UI thread. Inside a handler method checked by
+    /// [check_threads], that is also reported as a [DiagnosticCategory::WrongThread].
+    #[track_caller]
+    pub fn get(&self) -> Option<&T> {
+        self.value.as_ref().filter(|_| on_ui_thread())
+    }
+
+    /// The value, or `None` if not called on the UI thread, like [UiBound::get].
+    #[track_caller]
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        self.value.as_mut().filter(|_| on_ui_thread())
+    }
+
+    /// Run `callback` with the value on the UI thread. Returns `false` if the task could not be
+    /// posted.
+    pub fn post(self, callback: impl FnOnce(&T) + Send + 'static) -> bool {
+        post(cef_thread_id_t::TID_UI, move || {
+            if let Some(value) = self.get() {
+                callback(value);
+            }
+        })
+    }
+}
+
+impl<T: 'static> Drop for UiBound<T> {
+    fn drop(&mut self) {
+        let Some(value) = self.value.take() else {
+            return;
+        };
+        if is_ui_thread() {
+            drop(value);
+            return;
+        }
+        let value = Unsent(ManuallyDrop::new(value));
+        post(cef_thread_id_t::TID_UI, move || {
+            let mut value = value;
+            unsafe { ManuallyDrop::drop(&mut value.0) };
+        });
+    }
+}
+
+/// [is_ui_thread], reporting the checked callback which is running instead.
+#[track_caller]
+fn on_ui_thread() -> bool {
+    if is_ui_thread() {
+        return true;
+    }
+    if let Some(callback) = CALLBACK.get() {
+        diagnostics::report(
+            DiagnosticCategory::WrongThread,
+            format_args!("a UI thread value was used inside {callback} on {CurrentThread}"),
+        );
+    }
+    false
+}
+
+/// Moves a value to the UI thread to drop it there, and leaks it if the task never runs.
+pub(crate) struct Unsent<T>(pub(crate) ManuallyDrop<T>);
+
+unsafe impl<T> Send for Unsent<T> {}
+
+#[cfg(test)]
+mod test {
+    use cef_sys::_cef_display_handler_t;
+
+    use super::*;
+    use crate::{
+        rc::{wrap_rc, RcImpl},
+        Browser, CefStringUtf16, DisplayHandler, ImplBrowser, ImplDisplayHandler,
+    };
+
+    #[test]
+    fn test_current_task_runner_outside_cef() {
+        let runner = std::thread::spawn(|| current_task_runner().is_none())
+            .join()
+            .unwrap();
+        assert!(runner);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "must be called on TID_UI, but was called on a thread unknown to CEF"
+    )]
+    fn test_assert_on_thread_outside_cef() {
+        assert_on_thread(cef_thread_id_t::TID_UI);
+    }
+
+    #[test]
+    fn test_callback_thread() {
+        use cef_thread_id_t::*;
+
+        let thread = |handler, method| callback_thread(handler, method);
+        assert_eq!(
+            thread("ImplResourceRequestHandler", "on_before_resource_load"),
+            Some(TID_IO)
+        );
+        assert_eq!(
+            thread("RequestHandler", "get_resource_request_handler"),
+            Some(TID_IO)
+        );
+        assert_eq!(thread("RequestHandler", "on_before_browse"), Some(TID_UI));
+        assert_eq!(
+            thread("ImplDisplayHandler", "on_title_change"),
+            Some(TID_UI)
+        );
+        assert_eq!(
+            thread("ImplRenderProcessHandler", "on_context_created"),
+            Some(TID_RENDERER)
+        );
+        // Called on the UI thread for render processes and the IO thread for GPU processes.
+        assert_eq!(
+            thread(
+                "ImplBrowserProcessHandler",
+                "on_before_child_process_launch"
+            ),
+            None
+        );
+        assert_eq!(
+            thread("ImplBrowserProcessHandler", "on_schedule_message_pump_work"),
+            None
+        );
+        assert_eq!(thread("ImplDisplayHandler", "get_raw"), None);
+        assert_eq!(thread("ImplLoadHandler", "on_load_end"), None);
+        assert_eq!(thread("MyTrait", "run"), None);
+    }
+
+    struct TitleHandler {
+        object: *mut RcImpl<_cef_display_handler_t, Self>,
+    }
+
+    check_threads! {
+        impl ImplDisplayHandler for TitleHandler {
+            fn on_title_change(
+                &self,
+                _browser: Option<&impl ImplBrowser>,
+                _title: Option<&CefStringUtf16>,
+            ) {
+            }
+
+            fn get_raw(&self) -> *mut _cef_display_handler_t {
+                self.object as *mut _
+            }
+        }
+    }
+
+    wrap_rc!(TitleHandler, WrapDisplayHandler, _cef_display_handler_t);
+
+    #[test]
+    #[should_panic(
+        expected = "DisplayHandler::on_title_change must be called on TID_UI, but was called on a thread unknown to CEF"
+    )]
+    fn test_check_threads() {
+        let handler = DisplayHandler::new(TitleHandler {
+            object: std::ptr::null_mut(),
+        });
+        handler.on_title_change(Option::<&Browser>::None, None);
+    }
+}
@@ -0,0 +1,541 @@
+//! Request throttle module
+//!
+//! [RequestThrottle] limits the number of requests started per second for each host. Requests
+//! over the limit are delayed in [ImplResourceRequestHandler::on_before_resource_load] until the
+//! host has capacity again, instead of being cancelled.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    os::raw::c_int,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use cef_sys::{
+    _cef_request_handler_t, _cef_resource_request_handler_t, cef_return_value_t, cef_thread_id_t,
+};
+
+use crate::{
+    rc::{from_impl, wrap_rc, RcImpl},
+    thread, Callback, CefStringUtf16, CookieAccessFilter, Errorcode, ImplAuthCallback, ImplBrowser,
+    ImplCallback, ImplFrame, ImplRequest, ImplRequestHandler, ImplResourceRequestHandler,
+    ImplResponse, ImplSelectClientCertificateCallback, ImplSslinfo,
+    ImplUnresponsiveProcessCallback, ImplX509certificate, RequestHandler, ResourceHandler,
+    ResourceRequestHandler, ResponseFilter, ReturnValue, TerminationStatus, ThreadId,
+    UrlrequestStatus, WindowOpenDisposition,
+};
+
+from_impl!(Callback, ImplCallback);
+
+const WINDOW: Duration = Duration::from_secs(1);
+
+/// Limits the requests per second of each host.
+///
+/// Return [RequestThrottle::request_handler] from [crate::ImplClient::get_request_handler], or
+/// wrap your own [RequestHandler] with [RequestThrottle::wrap]. Requests without a host, e.g.
+/// `data:` URLs, are not limited.
+///
+/// ```ignore
+/// let throttle = RequestThrottle::new(10);
+/// let client = ClientBuilder::new()
+///     .request_handler(throttle.wrap(my_request_handler))
+///     .build();
+/// ```
+#[derive(Clone)]
+pub struct RequestThrottle {
+    max_requests_per_second_per_host: u32,
+    buckets: Arc<Mutex<HashMap<String, TokenBucket>>>,
+}
+
+impl RequestThrottle {
+    /// Let at most `max_requests_per_second_per_host` requests to the same host start in any
+    /// second. `0` is treated as `1`.
+    pub fn new(max_requests_per_second_per_host: u32) -> Self {
+        Self {
+            max_requests_per_second_per_host: max_requests_per_second_per_host.max(1),
+            buckets: Default::default(),
+        }
+    }
+
+    pub fn max_requests_per_second_per_host(&self) -> u32 {
+        self.max_requests_per_second_per_host
+    }
+
+    /// A [RequestHandler] which only throttles the requests.
+    pub fn request_handler(&self) -> RequestHandler {
+        self.handler(None)
+    }
+
+    /// A [RequestHandler] which forwards every callback to `inner`, and throttles the requests
+    /// before the [ResourceRequestHandler] of `inner` sees them.
+    ///
+    /// If the [ImplResourceRequestHandler::on_before_resource_load] of `inner` cancels the
+    /// request or continues it asynchronously, the request is not throttled.
+    pub fn wrap(&self, inner: RequestHandler) -> RequestHandler {
+        self.handler(Some(inner))
+    }
+
+    /// Forget the hosts which haven't started a request in the last second.
+    pub fn clear_idle(&self) {
+        let now = Instant::now();
+        if let Ok(mut buckets) = self.buckets.lock() {
+            buckets.retain(|_, bucket| !bucket.is_idle(now));
+        }
+    }
+
+    fn handler(&self, inner: Option<RequestHandler>) -> RequestHandler {
+        RequestHandler::new(ThrottleRequestHandler {
+            object: std::ptr::null_mut(),
+            throttle: self.clone(),
+            inner,
+        })
+    }
+
+    /// Take a token for a request to `host` at `now`, and return how long it must wait.
+    fn reserve(&self, host: &str, now: Instant) -> Duration {
+        let Ok(mut buckets) = self.buckets.lock() else {
+            return Duration::ZERO;
+        };
+        buckets
+            .entry(host.to_string())
+            .or_insert_with(|| TokenBucket::new(self.max_requests_per_second_per_host))
+            .reserve(now)
+    }
+}
+
+/// A bucket of `capacity` tokens, each of which returns to the bucket one second after it was
+/// taken. So at most `capacity` requests start in any second, and a burst of up to `capacity`
+/// requests isn't delayed.
+struct TokenBucket {
+    capacity: usize,
+    /// When the tokens in use were taken, in order.
+    taken: VecDeque<Instant>,
+}
+
+impl TokenBucket {
+    fn new(capacity: u32) -> Self {
+        Self {
+            capacity: capacity as usize,
+            taken: VecDeque::new(),
+        }
+    }
+
+    /// Take the next token, and return how long after `now` it is available.
+    fn reserve(&mut self, now: Instant) -> Duration {
+        while self
+            .taken
+            .front()
+            .is_some_and(|&taken| taken + WINDOW <= now)
+        {
+            self.taken.pop_front();
+        }
+
+        let mut start = now;
+        if let Some(&last) = self.taken.back() {
+            start = start.max(last);
+        }
+        if self.taken.len() >= self.capacity {
+            let returned = self.taken[self.taken.len() - self.capacity] + WINDOW;
+            start = start.max(returned);
+        }
+        self.taken.push_back(start);
+        start - now
+    }
+
+    fn is_idle(&self, now: Instant) -> bool {
+        self.taken.back().is_none_or(|&taken| taken + WINDOW <= now)
+    }
+}
+
+/// The host of `url`, without the user info and port.
+pub(crate) fn host(url: &str) -> Option<&str> {
+    let (_, rest) = url.split_once("://")?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host_port = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host_port)| host_port);
+    let host = match host_port.strip_prefix('[') {
+        Some(ipv6) => ipv6.split(']').next()?,
+        None => host_port.split(':').next()?,
+    };
+    (!host.is_empty()).then_some(host)
+}
+
+struct ThrottleRequestHandler {
+    object: *mut RcImpl<_cef_request_handler_t, Self>,
+    throttle: RequestThrottle,
+    inner: Option<RequestHandler>,
+}
+
+wrap_rc!(
+    ThrottleRequestHandler,
+    WrapRequestHandler,
+    _cef_request_handler_t,
+    throttle,
+    inner
+);
+
+impl ImplRequestHandler for ThrottleRequestHandler {
+    fn on_before_browse(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        frame: Option<&impl ImplFrame>,
+        request: Option<&impl ImplRequest>,
+        user_gesture: c_int,
+        is_redirect: c_int,
+    ) -> c_int {
+        self.inner.as_ref().map_or(0, |inner| {
+            inner.on_before_browse(browser, frame, request, user_gesture, is_redirect)
+        })
+    }
+
+    fn on_open_urlfrom_tab(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        frame: Option<&impl ImplFrame>,
+        target_url: Option<&CefStringUtf16>,
+        target_disposition: WindowOpenDisposition,
+        user_gesture: c_int,
+    ) -> c_int {
+        self.inner.as_ref().map_or(0, |inner| {
+            inner.on_open_urlfrom_tab(browser, frame, target_url, target_disposition, user_gesture)
+        })
+    }
+
+    fn get_resource_request_handler(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        frame: Option<&impl ImplFrame>,
+        request: Option<&impl ImplRequest>,
+        is_navigation: c_int,
+        is_download: c_int,
+        request_initiator: Option<&CefStringUtf16>,
+        disable_default_handling: Option<&mut c_int>,
+    ) -> Option<ResourceRequestHandler> {
+        let inner = self.inner.as_ref().and_then(|inner| {
+            inner.get_resource_request_handler(
+                browser,
+                frame,
+                request,
+                is_navigation,
+                is_download,
+                request_initiator,
+                disable_default_handling,
+            )
+        });
+        Some(ResourceRequestHandler::new(
+            ThrottleResourceRequestHandler {
+                object: std::ptr::null_mut(),
+                throttle: self.throttle.clone(),
+                inner,
+            },
+        ))
+    }
+
+    fn get_auth_credentials(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        origin_url: Option<&CefStringUtf16>,
+        is_proxy: c_int,
+        host: Option<&CefStringUtf16>,
+        port: c_int,
+        realm: Option<&CefStringUtf16>,
+        scheme: Option<&CefStringUtf16>,
+        callback: Option<&impl ImplAuthCallback>,
+    ) -> c_int {
+        self.inner.as_ref().map_or(0, |inner| {
+            inner.get_auth_credentials(
+                browser, origin_url, is_proxy, host, port, realm, scheme, callback,
+            )
+        })
+    }
+
+    fn on_certificate_error(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        cert_error: Errorcode,
+        request_url: Option<&CefStringUtf16>,
+        ssl_info: Option<&impl ImplSslinfo>,
+        callback: Option<&impl ImplCallback>,
+    ) -> c_int {
+        self.inner.as_ref().map_or(0, |inner| {
+            inner.on_certificate_error(browser, cert_error, request_url, ssl_info, callback)
+        })
+    }
+
+    fn on_select_client_certificate(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        is_proxy: c_int,
+        host: Option<&CefStringUtf16>,
+        port: c_int,
+        certificates: Option<&[Option<impl ImplX509certificate>]>,
+        callback: Option<&impl ImplSelectClientCertificateCallback>,
+    ) -> c_int {
+        self.inner.as_ref().map_or(0, |inner| {
+            inner.on_select_client_certificate(
+                browser,
+                is_proxy,
+                host,
+                port,
+                certificates,
+                callback,
+            )
+        })
+    }
+
+    fn on_render_view_ready(&self, browser: Option<&impl ImplBrowser>) {
+        if let Some(inner) = &self.inner {
+            inner.on_render_view_ready(browser);
+        }
+    }
+
+    fn on_render_process_unresponsive(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        callback: Option<&impl ImplUnresponsiveProcessCallback>,
+    ) -> c_int {
+        self.inner.as_ref().map_or(0, |inner| {
+            inner.on_render_process_unresponsive(browser, callback)
+        })
+    }
+
+    fn on_render_process_responsive(&self, browser: Option<&impl ImplBrowser>) {
+        if let Some(inner) = &self.inner {
+            inner.on_render_process_responsive(browser);
+        }
+    }
+
+    fn on_render_process_terminated(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        status: TerminationStatus,
+        error_code: c_int,
+        error_string: Option<&CefStringUtf16>,
+    ) {
+        if let Some(inner) = &self.inner {
+            inner.on_render_process_terminated(browser, status, error_code, error_string);
+        }
+    }
+
+    fn on_document_available_in_main_frame(&self, browser: Option<&impl ImplBrowser>) {
+        if let Some(inner) = &self.inner {
+            inner.on_document_available_in_main_frame(browser);
+        }
+    }
+
+    fn get_raw(&self) -> *mut _cef_request_handler_t {
+        self.object as *mut _cef_request_handler_t
+    }
+}
+
+struct ThrottleResourceRequestHandler {
+    object: *mut RcImpl<_cef_resource_request_handler_t, Self>,
+    throttle: RequestThrottle,
+    inner: Option<ResourceRequestHandler>,
+}
+
+wrap_rc!(
+    ThrottleResourceRequestHandler,
+    WrapResourceRequestHandler,
+    _cef_resource_request_handler_t,
+    throttle,
+    inner
+);
+
+impl ImplResourceRequestHandler for ThrottleResourceRequestHandler {
+    fn get_cookie_access_filter(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        frame: Option<&impl ImplFrame>,
+        request: Option<&impl ImplRequest>,
+    ) -> Option<CookieAccessFilter> {
+        self.inner
+            .as_ref()
+            .and_then(|inner| inner.get_cookie_access_filter(browser, frame, request))
+    }
+
+    fn on_before_resource_load(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        frame: Option<&impl ImplFrame>,
+        request: Option<&impl ImplRequest>,
+        callback: Option<&impl ImplCallback>,
+    ) -> ReturnValue {
+        let continue_now = ReturnValue::from(cef_return_value_t::RV_CONTINUE);
+        let result = self.inner.as_ref().map_or(continue_now, |inner| {
+            inner.on_before_resource_load(browser, frame, request, callback)
+        });
+        if result != continue_now {
+            return result;
+        }
+
+        let (Some(request), Some(callback)) = (request, callback) else {
+            return result;
+        };
+        let url = request
+            .get_url()
+            .map(|url| url.to_string())
+            .unwrap_or_default();
+        let Some(host) = host(&url) else {
+            return result;
+        };
+        let delay = self.throttle.reserve(host, Instant::now());
+        if delay.is_zero() {
+            return result;
+        }
+
+        let callback = Callback::from_impl(callback);
+        let cancel = callback.clone();
+        let io_thread = ThreadId::from(cef_thread_id_t::TID_IO);
+        if thread::post_delayed(io_thread, delay, move || callback.cont()) {
+            cef_return_value_t::RV_CONTINUE_ASYNC.into()
+        } else {
+            cancel.cancel();
+            cef_return_value_t::RV_CANCEL.into()
+        }
+    }
+
+    fn get_resource_handler(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        frame: Option<&impl ImplFrame>,
+        request: Option<&impl ImplRequest>,
+    ) -> Option<ResourceHandler> {
+        self.inner
+            .as_ref()
+            .and_then(|inner| inner.get_resource_handler(browser, frame, request))
+    }
+
+    fn on_resource_redirect(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        frame: Option<&impl ImplFrame>,
+        request: Option<&impl ImplRequest>,
+        response: Option<&impl ImplResponse>,
+        new_url: Option<&mut CefStringUtf16>,
+    ) {
+        if let Some(inner) = &self.inner {
+            inner.on_resource_redirect(browser, frame, request, response, new_url);
+        }
+    }
+
+    fn on_resource_response(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        frame: Option<&impl ImplFrame>,
+        request: Option<&impl ImplRequest>,
+        response: Option<&impl ImplResponse>,
+    ) -> c_int {
+        self.inner.as_ref().map_or(0, |inner| {
+            inner.on_resource_response(browser, frame, request, response)
+        })
+    }
+
+    fn get_resource_response_filter(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        frame: Option<&impl ImplFrame>,
+        request: Option<&impl ImplRequest>,
+        response: Option<&impl ImplResponse>,
+    ) -> Option<ResponseFilter> {
+        self.inner
+            .as_ref()
+            .and_then(|inner| inner.get_resource_response_filter(browser, frame, request, response))
+    }
+
+    fn on_resource_load_complete(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        frame: Option<&impl ImplFrame>,
+        request: Option<&impl ImplRequest>,
+        response: Option<&impl ImplResponse>,
+        status: UrlrequestStatus,
+        received_content_length: i64,
+    ) {
+        if let Some(inner) = &self.inner {
+            inner.on_resource_load_complete(
+                browser,
+                frame,
+                request,
+                response,
+                status,
+                received_content_length,
+            );
+        }
+    }
+
+    fn on_protocol_execution(
+        &self,
+        browser: Option<&impl ImplBrowser>,
+        frame: Option<&impl ImplFrame>,
+        request: Option<&impl ImplRequest>,
+        allow_os_execution: Option<&mut c_int>,
+    ) {
+        if let Some(inner) = &self.inner {
+            inner.on_protocol_execution(browser, frame, request, allow_os_execution);
+        }
+    }
+
+    fn get_raw(&self) -> *mut _cef_resource_request_handler_t {
+        self.object as *mut _cef_resource_request_handler_t
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rapid_requests_to_one_host() {
+        let max_rps = 10;
+        let throttle = RequestThrottle::new(max_rps);
+        let now = Instant::now();
+        let starts: Vec<_> = (0..100)
+            .map(|_| now + throttle.reserve("example.com", now))
+            .collect();
+
+        // A burst up to the limit isn't delayed.
+        assert!(starts[..max_rps as usize].iter().all(|&start| start == now));
+        assert!(starts[max_rps as usize] > now);
+        // At most `max_rps` requests start in any second.
+        for (i, &start) in starts.iter().enumerate() {
+            let in_window = starts[i..]
+                .iter()
+                .take_while(|&&later| later < start + WINDOW)
+                .count();
+            assert!(
+                in_window <= max_rps as usize,
+                "{in_window} requests in a second"
+            );
+        }
+        assert!(starts[99] >= now + Duration::from_secs(9));
+
+        // Other hosts have their own bucket.
+        assert_eq!(throttle.reserve("example.org", now), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_tokens_return_after_a_second() {
+        let mut bucket = TokenBucket::new(2);
+        let now = Instant::now();
+        assert_eq!(bucket.reserve(now), Duration::ZERO);
+        assert_eq!(bucket.reserve(now), Duration::ZERO);
+        assert_eq!(bucket.reserve(now), WINDOW);
+        assert!(!bucket.is_idle(now + WINDOW));
+        assert!(bucket.is_idle(now + WINDOW * 2));
+        assert_eq!(bucket.reserve(now + WINDOW * 2), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_host() {
+        assert_eq!(host("https://example.com/path"), Some("example.com"));
+        assert_eq!(
+            host("https://user:pw@example.com:8443?q"),
+            Some("example.com")
+        );
+        assert_eq!(host("http://[::1]:8080/"), Some("::1"));
+        assert_eq!(host("data:text/plain,hello"), None);
+        assert_eq!(host("file:///tmp/index.html"), None);
+    }
+}
@@ -0,0 +1,596 @@
+//! Touch module
+//!
+//! [TouchZoomController] turns the touches and pinch gestures of a host toolkit into the CEF calls
+//! which zoom a windowless browser, and [visual_viewport] reports the resulting viewport, e.g. to
+//! position overlays over the page.
+//!
+//! ## Pinch zoom
+//!
+//! With [PinchMode::TouchEvents], touches are sent as [TouchEvent]s, so the page sees a standard
+//! pinch: Chromium scales the visual viewport, and pages which handle touch events or set
+//! `touch-action` get the events they expect. Chromium only handles touch events with the
+//! `--touch-events=enabled` switch. Pinch gestures without touches, e.g. of a touchpad, are sent
+//! as two synthetic touches moving apart around the center of the gesture.
+//!
+//! [PinchMode::ZoomLevel] changes the zoom level of the browser instead, which reflows the page
+//! like the zoom of a desktop browser, for pages which aren't made for touch. Single touches are
+//! still sent as touch events.
+//!
+//! [crate::ImplBrowserViewDelegate::on_gesture_command] only reports the back and forward swipes
+//! of Views browsers, so it isn't involved here.
+
+use std::{future::Future, os::raw::c_int};
+
+use cef_sys::{cef_pointer_type_t, cef_touch_event_type_t};
+
+use crate::{
+    devtools::{json, DevToolsError},
+    BrowserHost, ImplBrowserHost, TouchEvent,
+};
+
+/// Chromium zooms by this factor per zoom level.
+const ZOOM_LEVEL_BASE: f64 = 1.2;
+/// The zoom factors Chromium allows, from 25% to 500%.
+const ZOOM_FACTORS: (f64, f64) = (0.25, 5.0);
+/// The distance of the synthetic touches of a pinch gesture at scale 1, in DIP.
+const SYNTHETIC_SPAN: f32 = 200.0;
+/// The touch ids of synthetic pinches, out of the range of toolkit touch ids.
+const SYNTHETIC_IDS: [c_int; 2] = [c_int::MAX - 1, c_int::MAX];
+
+/// The zoom level of [ImplBrowserHost::set_zoom_level] which scales the page by `factor`.
+pub fn zoom_level(factor: f64) -> f64 {
+    factor.ln() / ZOOM_LEVEL_BASE.ln()
+}
+
+/// The factor a page is scaled by at `zoom_level`.
+pub fn zoom_factor(zoom_level: f64) -> f64 {
+    ZOOM_LEVEL_BASE.powf(zoom_level)
+}
+
+/// How [TouchZoomController] zooms for a pinch.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PinchMode {
+    /// Send touch events, so Chromium scales the visual viewport like on a phone.
+    #[default]
+    TouchEvents,
+    /// Change the zoom level of the browser, which reflows the page.
+    ZoomLevel,
+}
+
+/// The phase of a touch reported by the host toolkit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TouchPhase {
+    Started,
+    Moved,
+    Ended,
+    Cancelled,
+}
+
+impl From<TouchPhase> for cef_touch_event_type_t {
+    fn from(value: TouchPhase) -> Self {
+        match value {
+            TouchPhase::Started => Self::CEF_TET_PRESSED,
+            TouchPhase::Moved => Self::CEF_TET_MOVED,
+            TouchPhase::Ended => Self::CEF_TET_RELEASED,
+            TouchPhase::Cancelled => Self::CEF_TET_CANCELLED,
+        }
+    }
+}
+
+/// Converts the touches and pinch gestures of a host toolkit into touch events or zoom level
+/// changes of a windowless browser, see the [module documentation](self).
+///
+/// Coordinates are in DIP of the view, like the other input events of windowless browsers. Call
+/// the methods on the UI thread, since a pinch reads the zoom level of the browser when it starts.
+pub struct TouchZoomController {
+    host: BrowserHost,
+    state: PinchState,
+}
+
+impl TouchZoomController {
+    pub fn new(host: &BrowserHost, mode: PinchMode) -> Self {
+        Self {
+            host: host.clone(),
+            state: PinchState::new(mode),
+        }
+    }
+
+    pub fn mode(&self) -> PinchMode {
+        self.state.mode
+    }
+
+    /// Handle a touch of the toolkit. `id` must stay the same from [TouchPhase::Started] until
+    /// the touch ends.
+    pub fn touch(&mut self, id: i32, phase: TouchPhase, x: f32, y: f32) {
+        let host = &self.host;
+        let actions = self
+            .state
+            .touch(id, phase, (x, y), || host.get_zoom_level());
+        self.apply(actions);
+    }
+
+    /// Start a pinch gesture of the toolkit, e.g. of a touchpad, around `(x, y)`.
+    pub fn pinch_begin(&mut self, x: f32, y: f32) {
+        let host = &self.host;
+        let actions = self.state.pinch_begin((x, y), || host.get_zoom_level());
+        self.apply(actions);
+    }
+
+    /// Scale the pinch gesture by `1 + delta`, e.g. the magnification delta of macOS.
+    pub fn pinch_update(&mut self, delta: f64) {
+        let actions = self.state.pinch_update(delta);
+        self.apply(actions);
+    }
+
+    /// End the pinch gesture.
+    pub fn pinch_end(&mut self) {
+        let actions = self.state.pinch_end();
+        self.apply(actions);
+    }
+
+    fn apply(&self, actions: Vec<ZoomAction>) {
+        for action in actions {
+            match action {
+                ZoomAction::Touch(event) => self.host.send_touch_event(Some(&event)),
+                ZoomAction::SetZoomLevel(zoom_level) => self.host.set_zoom_level(zoom_level),
+            }
+        }
+    }
+}
+
+/// A CEF call of [TouchZoomController].
+#[derive(Clone)]
+enum ZoomAction {
+    Touch(TouchEvent),
+    SetZoomLevel(f64),
+}
+
+/// Two touches pinching in [PinchMode::ZoomLevel].
+struct TouchPinch {
+    ids: [c_int; 2],
+    start_span: f32,
+    start_level: f64,
+    zoom_level: f64,
+}
+
+/// A pinch gesture of the toolkit.
+struct GesturePinch {
+    center: (f32, f32),
+    scale: f64,
+    start_level: f64,
+    zoom_level: f64,
+}
+
+/// The math of [TouchZoomController], without a browser.
+struct PinchState {
+    mode: PinchMode,
+    touches: Vec<(c_int, (f32, f32))>,
+    /// The touch sent as touch events in [PinchMode::ZoomLevel], until a second one starts.
+    forwarded: Option<c_int>,
+    touch_pinch: Option<TouchPinch>,
+    gesture: Option<GesturePinch>,
+}
+
+impl PinchState {
+    fn new(mode: PinchMode) -> Self {
+        Self {
+            mode,
+            touches: vec![],
+            forwarded: None,
+            touch_pinch: None,
+            gesture: None,
+        }
+    }
+
+    fn touch(
+        &mut self,
+        id: c_int,
+        phase: TouchPhase,
+        position: (f32, f32),
+        current_level: impl FnOnce() -> f64,
+    ) -> Vec<ZoomAction> {
+        if self.mode == PinchMode::TouchEvents {
+            return vec![ZoomAction::Touch(touch_event(id, phase, position))];
+        }
+
+        let mut actions = vec![];
+        match phase {
+            TouchPhase::Started => {
+                self.touches.retain(|(touch, _)| *touch != id);
+                self.touches.push((id, position));
+                if self.touches.len() == 1 && self.touch_pinch.is_none() {
+                    self.forwarded = Some(id);
+                    actions.push(ZoomAction::Touch(touch_event(id, phase, position)));
+                } else if self.touches.len() == 2 && self.touch_pinch.is_none() {
+                    // The page saw the first touch start, so it must see it end.
+                    if let Some(forwarded) = self.forwarded.take() {
+                        let at = self.position(forwarded).unwrap_or(position);
+                        actions.push(ZoomAction::Touch(touch_event(
+                            forwarded,
+                            TouchPhase::Cancelled,
+                            at,
+                        )));
+                    }
+                    let (first, second) = (self.touches[0], self.touches[1]);
+                    let start_level = current_level();
+                    self.touch_pinch = Some(TouchPinch {
+                        ids: [first.0, second.0],
+                        start_span: span(first.1, second.1),
+                        start_level,
+                        zoom_level: start_level,
+                    });
+                }
+            }
+            TouchPhase::Moved => {
+                if let Some((_, at)) = self.touches.iter_mut().find(|(touch, _)| *touch == id) {
+                    *at = position;
+                }
+                if self.forwarded == Some(id) {
+                    actions.push(ZoomAction::Touch(touch_event(id, phase, position)));
+                } else if let Some(pinch) = &self.touch_pinch {
+                    if let (true, Some(first), Some(second)) = (
+                        pinch.ids.contains(&id),
+                        self.position(pinch.ids[0]),
+                        self.position(pinch.ids[1]),
+                    ) {
+                        let scale = span(first, second) / pinch.start_span.max(1.0);
+                        let zoom_level = pinched_level(pinch.start_level, scale as f64);
+                        if let Some(pinch) = &mut self.touch_pinch {
+                            if zoom_level != pinch.zoom_level {
+                                pinch.zoom_level = zoom_level;
+                                actions.push(ZoomAction::SetZoomLevel(zoom_level));
+                            }
+                        }
+                    }
+                }
+            }
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                self.touches.retain(|(touch, _)| *touch != id);
+                if self.forwarded == Some(id) {
+                    self.forwarded = None;
+                    actions.push(ZoomAction::Touch(touch_event(id, phase, position)));
+                }
+                if self
+                    .touch_pinch
+                    .as_ref()
+                    .is_some_and(|pinch| pinch.ids.contains(&id))
+                {
+                    self.touch_pinch = None;
+                }
+            }
+        }
+        actions
+    }
+
+    fn pinch_begin(
+        &mut self,
+        center: (f32, f32),
+        current_level: impl FnOnce() -> f64,
+    ) -> Vec<ZoomAction> {
+        let mut actions = self.pinch_end();
+        let start_level = match self.mode {
+            PinchMode::TouchEvents => 0.0,
+            PinchMode::ZoomLevel => current_level(),
+        };
+        let gesture = GesturePinch {
+            center,
+            scale: 1.0,
+            start_level,
+            zoom_level: start_level,
+        };
+        if self.mode == PinchMode::TouchEvents {
+            actions.extend(synthetic_touches(&gesture, TouchPhase::Started));
+        }
+        self.gesture = Some(gesture);
+        actions
+    }
+
+    fn pinch_update(&mut self, delta: f64) -> Vec<ZoomAction> {
+        // Toolkits may report NaN deltas.
+        let (Some(gesture), true) = (&mut self.gesture, delta.is_finite()) else {
+            return vec![];
+        };
+        gesture.scale = (gesture.scale * (1.0 + delta)).clamp(ZOOM_FACTORS.0, ZOOM_FACTORS.1);
+        match self.mode {
+            PinchMode::TouchEvents => synthetic_touches(gesture, TouchPhase::Moved),
+            PinchMode::ZoomLevel => {
+                let zoom_level = pinched_level(gesture.start_level, gesture.scale);
+                if zoom_level == gesture.zoom_level {
+                    return vec![];
+                }
+                gesture.zoom_level = zoom_level;
+                vec![ZoomAction::SetZoomLevel(zoom_level)]
+            }
+        }
+    }
+
+    fn pinch_end(&mut self) -> Vec<ZoomAction> {
+        match (self.gesture.take(), self.mode) {
+            (Some(gesture), PinchMode::TouchEvents) => {
+                synthetic_touches(&gesture, TouchPhase::Ended)
+            }
+            _ => vec![],
+        }
+    }
+
+    fn position(&self, id: c_int) -> Option<(f32, f32)> {
+        self.touches
+            .iter()
+            .find(|(touch, _)| *touch == id)
+            .map(|(_, position)| *position)
+    }
+}
+
+fn touch_event(id: c_int, phase: TouchPhase, (x, y): (f32, f32)) -> TouchEvent {
+    TouchEvent {
+        id,
+        x,
+        y,
+        radius_x: 0.0,
+        radius_y: 0.0,
+        rotation_angle: 0.0,
+        pressure: 0.0,
+        type_: cef_touch_event_type_t::from(phase).into(),
+        modifiers: 0,
+        pointer_type: cef_pointer_type_t::CEF_POINTER_TYPE_TOUCH.into(),
+    }
+}
+
+/// The two touches of a pinch gesture, on a horizontal line through its center.
+fn synthetic_touches(gesture: &GesturePinch, phase: TouchPhase) -> Vec<ZoomAction> {
+    let half_span = SYNTHETIC_SPAN * gesture.scale as f32 / 2.0;
+    let (x, y) = gesture.center;
+    [
+        (SYNTHETIC_IDS[0], x - half_span),
+        (SYNTHETIC_IDS[1], x + half_span),
+    ]
+    .into_iter()
+    .map(|(id, x)| ZoomAction::Touch(touch_event(id, phase, (x, y))))
+    .collect()
+}
+
+fn span(first: (f32, f32), second: (f32, f32)) -> f32 {
+    (second.0 - first.0).hypot(second.1 - first.1)
+}
+
+/// The zoom level after scaling a page at `start_level` by `scale`, within the zoom factors of
+/// Chromium.
+fn pinched_level(start_level: f64, scale: f64) -> f64 {
+    let factor = (zoom_factor(start_level) * scale).clamp(ZOOM_FACTORS.0, ZOOM_FACTORS.1);
+    zoom_level(factor)
+}
+
+/// The visual viewport of a page as `Page.getLayoutMetrics` reports it, in CSS pixels.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct VisualViewport {
+    /// The offset from the layout viewport, which a pinch zoom moves within.
+    pub offset_x: f64,
+    pub offset_y: f64,
+    /// The position in the page.
+    pub page_x: f64,
+    pub page_y: f64,
+    pub client_width: f64,
+    pub client_height: f64,
+    /// The pinch zoom scale, 1 when not zoomed.
+    pub scale: f64,
+    /// The page zoom factor, see [zoom_factor].
+    pub zoom: f64,
+}
+
+impl VisualViewport {
+    /// Convert a position in CSS pixels of the page, e.g. of an element, to DIP of the view.
+    pub fn page_to_view(&self, x: f64, y: f64) -> (f64, f64) {
+        let scale = self.scale * self.zoom;
+        ((x - self.page_x) * scale, (y - self.page_y) * scale)
+    }
+}
+
+/// Resolve with the visual viewport of the page in `host`.
+///
+/// Must be called on the UI thread, like [BrowserHost::execute_dev_tools].
+pub fn visual_viewport(
+    host: &BrowserHost,
+) -> impl Future<Output = Result<VisualViewport, DevToolsError>> {
+    let result = host.execute_dev_tools("Page.getLayoutMetrics", None);
+    async move { layout_visual_viewport(&result.await?) }
+}
+
+/// The `cssVisualViewport` of a `Page.getLayoutMetrics` result.
+fn layout_visual_viewport(result: &[u8]) -> Result<VisualViewport, DevToolsError> {
+    let viewport = json::value(&json::parse(result)?, &["cssVisualViewport"])?;
+    let number = |name| json::number(&viewport, &[name]);
+    Ok(VisualViewport {
+        offset_x: number("offsetX")?,
+        offset_y: number("offsetY")?,
+        page_x: number("pageX")?,
+        page_y: number("pageY")?,
+        client_width: number("clientWidth")?,
+        client_height: number("clientHeight")?,
+        scale: number("scale")?,
+        // Only reported when the page is zoomed.
+        zoom: number("zoom").unwrap_or(1.0),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn touches(actions: &[ZoomAction]) -> Vec<(c_int, cef_touch_event_type_t, f32, f32)> {
+        actions
+            .iter()
+            .filter_map(|action| match action {
+                ZoomAction::Touch(event) => Some((event.id, event.type_.into(), event.x, event.y)),
+                ZoomAction::SetZoomLevel(_) => None,
+            })
+            .collect()
+    }
+
+    fn zoom_levels(actions: &[ZoomAction]) -> Vec<f64> {
+        actions
+            .iter()
+            .filter_map(|action| match action {
+                ZoomAction::SetZoomLevel(zoom_level) => Some(*zoom_level),
+                ZoomAction::Touch(_) => None,
+            })
+            .collect()
+    }
+
+    fn assert_close(found: f64, expected: f64) {
+        assert!((found - expected).abs() < 1e-9, "{found} != {expected}");
+    }
+
+    #[test]
+    fn test_zoom_level_math() {
+        assert_close(zoom_level(1.0), 0.0);
+        assert_close(zoom_level(1.2), 1.0);
+        assert_close(zoom_level(1.44), 2.0);
+        assert_close(zoom_factor(-1.0), 1.0 / 1.2);
+        assert_close(zoom_factor(zoom_level(3.0)), 3.0);
+
+        assert_close(pinched_level(0.0, 1.2), 1.0);
+        assert_close(pinched_level(1.0, 1.2), 2.0);
+        assert_close(pinched_level(0.0, 100.0), zoom_level(5.0));
+        assert_close(pinched_level(0.0, 0.01), zoom_level(0.25));
+    }
+
+    #[test]
+    fn test_touch_events_forwarded() {
+        use cef_touch_event_type_t::*;
+
+        let mut state = PinchState::new(PinchMode::TouchEvents);
+        let level = || unreachable!("touch events don't read the zoom level");
+        let mut actions = state.touch(1, TouchPhase::Started, (10.0, 20.0), level);
+        actions.extend(state.touch(2, TouchPhase::Started, (50.0, 20.0), level));
+        actions.extend(state.touch(2, TouchPhase::Moved, (90.0, 20.0), level));
+        actions.extend(state.touch(1, TouchPhase::Ended, (10.0, 20.0), level));
+        assert_eq!(
+            touches(&actions),
+            [
+                (1, CEF_TET_PRESSED, 10.0, 20.0),
+                (2, CEF_TET_PRESSED, 50.0, 20.0),
+                (2, CEF_TET_MOVED, 90.0, 20.0),
+                (1, CEF_TET_RELEASED, 10.0, 20.0),
+            ]
+        );
+        let ZoomAction::Touch(event) = &actions[0] else {
+            unreachable!()
+        };
+        assert_eq!(event.pointer_type, crate::PointerType::Touch);
+    }
+
+    #[test]
+    fn test_two_finger_zoom_level() {
+        use cef_touch_event_type_t::*;
+
+        let mut state = PinchState::new(PinchMode::ZoomLevel);
+        let level = || 1.0;
+        let actions = state.touch(1, TouchPhase::Started, (100.0, 100.0), level);
+        assert_eq!(touches(&actions), [(1, CEF_TET_PRESSED, 100.0, 100.0)]);
+        let actions = state.touch(1, TouchPhase::Moved, (100.0, 110.0), level);
+        assert_eq!(touches(&actions), [(1, CEF_TET_MOVED, 100.0, 110.0)]);
+
+        // The second finger cancels the first touch and starts the pinch 100 DIP apart.
+        let actions = state.touch(2, TouchPhase::Started, (200.0, 110.0), level);
+        assert_eq!(touches(&actions), [(1, CEF_TET_CANCELLED, 100.0, 110.0)]);
+        assert!(zoom_levels(&actions).is_empty());
+
+        // 120 DIP apart is a factor of 1.2, one zoom level more.
+        let actions = state.touch(2, TouchPhase::Moved, (220.0, 110.0), level);
+        assert!(touches(&actions).is_empty());
+        assert_eq!(zoom_levels(&actions).len(), 1);
+        assert_close(zoom_levels(&actions)[0], 2.0);
+        // The same span doesn't set the zoom level again.
+        let actions = state.touch(1, TouchPhase::Moved, (100.0, 110.0), level);
+        assert!(actions.is_empty());
+
+        // Lifting a finger ends the pinch, and the other one isn't sent until a new touch.
+        assert!(state
+            .touch(1, TouchPhase::Ended, (100.0, 110.0), level)
+            .is_empty());
+        assert!(state
+            .touch(2, TouchPhase::Moved, (300.0, 110.0), level)
+            .is_empty());
+        assert!(state
+            .touch(2, TouchPhase::Ended, (300.0, 110.0), level)
+            .is_empty());
+        let actions = state.touch(3, TouchPhase::Started, (5.0, 5.0), level);
+        assert_eq!(touches(&actions), [(3, CEF_TET_PRESSED, 5.0, 5.0)]);
+    }
+
+    #[test]
+    fn test_pinch_gesture_touches() {
+        use cef_touch_event_type_t::*;
+
+        let mut state = PinchState::new(PinchMode::TouchEvents);
+        let actions = state.pinch_begin((300.0, 200.0), || unreachable!());
+        let [first, second] = SYNTHETIC_IDS;
+        assert_eq!(
+            touches(&actions),
+            [
+                (first, CEF_TET_PRESSED, 200.0, 200.0),
+                (second, CEF_TET_PRESSED, 400.0, 200.0),
+            ]
+        );
+        state.pinch_update(0.25);
+        let actions = state.pinch_update(0.2);
+        // 1.25 * 1.2 = 1.5 times the span.
+        assert_eq!(
+            touches(&actions),
+            [
+                (first, CEF_TET_MOVED, 150.0, 200.0),
+                (second, CEF_TET_MOVED, 450.0, 200.0),
+            ]
+        );
+        let actions = state.pinch_end();
+        assert_eq!(
+            touches(&actions),
+            [
+                (first, CEF_TET_RELEASED, 150.0, 200.0),
+                (second, CEF_TET_RELEASED, 450.0, 200.0),
+            ]
+        );
+        assert!(state.pinch_update(0.5).is_empty());
+    }
+
+    #[test]
+    fn test_pinch_gesture_zoom_level() {
+        let mut state = PinchState::new(PinchMode::ZoomLevel);
+        assert!(state.pinch_begin((300.0, 200.0), || -1.0).is_empty());
+        assert!(state.pinch_update(f64::NAN).is_empty());
+        assert_close(zoom_levels(&state.pinch_update(0.2))[0], 0.0);
+        assert_close(zoom_levels(&state.pinch_update(0.2))[0], 1.0);
+        // Zooming out past 25% stops there.
+        let actions = state.pinch_update(-0.99);
+        assert_close(zoom_levels(&actions)[0], zoom_level(0.25));
+        assert!(state.pinch_update(-0.5).is_empty());
+        assert!(state.pinch_end().is_empty());
+    }
+
+    #[test]
+    fn test_visual_viewport() {
+        let result = br#"{"layoutViewport": {"pageX": 0, "pageY": 40, "clientWidth": 800,
+            "clientHeight": 600}, "visualViewport": {}, "contentSize": {},
+            "cssLayoutViewport": {"pageX": 0, "pageY": 40, "clientWidth": 800,
+            "clientHeight": 600},
+            "cssVisualViewport": {"offsetX": 100, "offsetY": 50, "pageX": 100, "pageY": 90,
+            "clientWidth": 400, "clientHeight": 300, "scale": 2, "zoom": 1},
+            "cssContentSize": {"x": 0, "y": 0, "width": 800, "height": 2000}}"#;
+        let viewport = layout_visual_viewport(result).unwrap();
+        assert_eq!(
+            viewport,
+            VisualViewport {
+                offset_x: 100.0,
+                offset_y: 50.0,
+                page_x: 100.0,
+                page_y: 90.0,
+                client_width: 400.0,
+                client_height: 300.0,
+                scale: 2.0,
+                zoom: 1.0,
+            }
+        );
+        assert_eq!(viewport.page_to_view(150.0, 100.0), (100.0, 20.0));
+        assert!(layout_visual_viewport(br#"{"cssLayoutViewport": {}}"#).is_err());
+    }
+}
@@ -0,0 +1,159 @@
+//! URL module
+//!
+//! Helpers for loading generated HTML. Small documents are loaded from a `data:` URL with
+//! [Frame::load_html]. Documents with assets such as scripts and images are served from a one-off
+//! origin with [crate::scheme::LocalContentBuilder].
+
+use crate::{CefString, CefStringUtf8, Frame, ImplFrame};
+
+/// The URL of an empty document.
+pub const ABOUT_BLANK: &str = "about:blank";
+
+/// Chromium rejects longer URLs, so larger documents need a scheme handler.
+pub const MAX_URL_LENGTH: usize = 2 * 1024 * 1024;
+
+/// `true` if `url` is `about:blank`, ignoring case, a query and a fragment.
+pub fn is_about_blank(url: &str) -> bool {
+    url.trim()
+        .split(['?', '#'])
+        .next()
+        .is_some_and(|url| url.eq_ignore_ascii_case(ABOUT_BLANK))
+}
+
+/// A base64 `data:` URL of `html` with a UTF-8 `text/html` mime type.
+pub fn data_url(html: &str) -> String {
+    format!(
+        "data:text/html;charset=utf-8;base64,{}",
+        encode_base64(html.as_bytes())
+    )
+}
+
+impl Frame {
+    /// Load `html` in the frame from a `data:` URL.
+    ///
+    /// Unless `fake_url` is empty or [ABOUT_BLANK], a `<base>` element pointing at it is inserted,
+    /// so relative links resolve against `fake_url`. The frame still has the opaque origin of a
+    /// `data:` URL, so use [crate::scheme::LocalContentBuilder] for documents which load assets
+    /// or need storage. Returns `false` without loading if the frame is invalid or the URL would
+    /// exceed [MAX_URL_LENGTH].
+    pub fn load_html(&self, html: &str, fake_url: &str) -> bool {
+        if !self.is_valid() {
+            return false;
+        }
+        let url = if fake_url.trim().is_empty() || is_about_blank(fake_url) {
+            data_url(html)
+        } else {
+            data_url(&with_base(html, fake_url))
+        };
+        if url.len() > MAX_URL_LENGTH {
+            return false;
+        }
+        self.load_url(Some(&CefString::from(&CefStringUtf8::from(url.as_str()))));
+        true
+    }
+}
+
+/// Insert `<base href="base_url">` at the start of the `<head>` of `html`, or at the start of the
+/// document if there is no head, after the doctype which keeps it out of quirks mode.
+fn with_base(html: &str, base_url: &str) -> String {
+    let base = format!(r#"<base href="{}">"#, escape_attribute(base_url));
+    let lower = html.to_ascii_lowercase();
+    let head = lower
+        .match_indices("<head")
+        .map(|(start, _)| start)
+        .find(|start| {
+            // Skip `<header>` elements.
+            matches!(
+                lower.as_bytes().get(start + 5),
+                Some(b'>' | b' ' | b'\t' | b'\r' | b'\n')
+            )
+        });
+    let doctype = || {
+        let start = lower.len() - lower.trim_start().len();
+        lower[start..].starts_with("<!doctype").then_some(start)
+    };
+    let end = head
+        .or_else(doctype)
+        .and_then(|start| Some(start + lower[start..].find('>')? + 1))
+        .unwrap_or_default();
+    format!("{}{base}{}", &html[..end], &html[end..])
+}
+
+fn escape_attribute(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn encode_base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or_default(),
+            chunk.get(2).copied().unwrap_or_default(),
+        ];
+        let bits = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(ALPHABET[(bits >> (18 - 6 * index) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_data_url() {
+        assert_eq!(
+            data_url("<p>é</p>"),
+            "data:text/html;charset=utf-8;base64,PHA+w6k8L3A+"
+        );
+        assert_eq!(data_url(""), "data:text/html;charset=utf-8;base64,");
+    }
+
+    #[test]
+    fn test_encode_base64() {
+        assert_eq!(encode_base64(b"f"), "Zg==");
+        assert_eq!(encode_base64(b"fo"), "Zm8=");
+        assert_eq!(encode_base64(b"foo"), "Zm9v");
+        assert_eq!(encode_base64(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn test_is_about_blank() {
+        assert!(is_about_blank("about:blank"));
+        assert!(is_about_blank(" About:Blank#top"));
+        assert!(!is_about_blank("about:blankets"));
+        assert!(!is_about_blank("https://example.com"));
+    }
+
+    #[test]
+    fn test_with_base() {
+        assert_eq!(
+            with_base(
+                "<html><HEAD><title>t</title></HEAD></html>",
+                "https://a.test/x?a&b"
+            ),
+            r#"<html><HEAD><base href="https://a.test/x?a&amp;b"><title>t</title></HEAD></html>"#
+        );
+        assert_eq!(
+            with_base("<header>h</header>", "https://a.test/"),
+            r#"<base href="https://a.test/"><header>h</header>"#
+        );
+        assert_eq!(with_base("<head", "/"), r#"<base href="/"><head"#);
+        assert_eq!(
+            with_base("<!DOCTYPE html><p>p</p>", "/"),
+            r#"<!DOCTYPE html><base href="/"><p>p</p>"#
+        );
+    }
+}
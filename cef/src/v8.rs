@@ -0,0 +1,376 @@
+//! V8 module
+//!
+//! JavaScript promises settled from Rust. [V8context::create_promise] returns a `Promise` for the
+//! page together with the [V8Promise] which resolves or rejects it. V8 objects may only be used
+//! on the render thread of their context, so [V8Promise::into_remote] turns it into a
+//! [RemotePromise] which other threads can settle by posting a task to that thread.
+//!
+//! [V8ArrayBuffer::from_vec] shares a `Vec<u8>` with the page as an `ArrayBuffer` without
+//! copying it.
+
+use std::{
+    cell::{RefCell, UnsafeCell},
+    mem::ManuallyDrop,
+    rc::Rc,
+};
+
+use cef_sys::_cef_v8array_buffer_release_callback_t;
+
+use crate::{
+    callback::task,
+    rc::{wrap_rc, RcImpl},
+    thread::Unsent,
+    v8value_create_array_buffer, v8value_create_promise, CefString, CefStringUtf8, ImplTaskRunner,
+    ImplV8arrayBufferReleaseCallback, ImplV8context, ImplV8value, TaskRunner,
+    V8arrayBufferReleaseCallback, V8context, V8value,
+};
+
+/// The message of the rejection when a [RemotePromise] is dropped without settling it.
+pub const DROPPED_MESSAGE: &str = "The promise was dropped without being settled";
+
+impl V8context {
+    /// Create a pending `Promise` in this context, and the [V8Promise] which settles it. Must be
+    /// called on the render thread of the context.
+    pub fn create_promise(&self) -> Option<(V8value, V8Promise)> {
+        let promise = self.entered(v8value_create_promise)?;
+        Some((
+            promise.clone(),
+            V8Promise {
+                context: self.clone(),
+                promise,
+            },
+        ))
+    }
+
+    /// Run `callback` with this context entered, and `None` if it can't be entered.
+    fn entered<T>(&self, callback: impl FnOnce() -> Option<T>) -> Option<T> {
+        if self.enter() == 0 {
+            return None;
+        }
+        let result = callback();
+        self.exit();
+        result
+    }
+}
+
+/// Settles a `Promise` created by [V8context::create_promise].
+///
+/// Only a pending promise can be settled, later calls return `false`.
+#[derive(Clone)]
+pub struct V8Promise {
+    context: V8context,
+    promise: V8value,
+}
+
+impl V8Promise {
+    /// The `Promise` object.
+    pub fn promise(&self) -> &V8value {
+        &self.promise
+    }
+
+    /// Fulfill the promise with `value`. Must be called on the render thread of the context.
+    pub fn resolve(&self, value: V8value) -> bool {
+        self.context
+            .entered(|| Some(self.promise.resolve_promise(Some(&value)) != 0))
+            .unwrap_or_default()
+    }
+
+    /// Reject the promise with an `Error` of `message`. Must be called on the render thread of the
+    /// context.
+    pub fn reject(&self, message: &str) -> bool {
+        let message = CefString::from(&CefStringUtf8::from(message));
+        self.context
+            .entered(|| Some(self.promise.reject_promise(Some(&message)) != 0))
+            .unwrap_or_default()
+    }
+
+    /// Move the promise to other threads, `None` if the context has no task runner because it is
+    /// already released.
+    pub fn into_remote(self) -> Option<RemotePromise> {
+        let runner = self.context.get_task_runner()?;
+        Some(RemotePromise {
+            runner,
+            promise: Some(ManuallyDrop::new(self)),
+        })
+    }
+}
+
+/// A [V8Promise] which can be sent to other threads, and settled from there.
+///
+/// Each method posts a task to the render thread of the context, and returns `false` if that
+/// failed, e.g. because the context was released. Dropping it without settling it rejects the
+/// promise with [DROPPED_MESSAGE], so the page doesn't wait forever.
+///
+/// ```no_run
+/// use cef::V8context;
+///
+/// fn fetch(context: &V8context) -> Option<cef::V8value> {
+///     let (promise, settle) = context.create_promise()?;
+///     let settle = settle.into_remote()?;
+///     std::thread::spawn(move || {
+///         let text = "done".to_string();
+///         settle.resolve(move || {
+///             let text = cef::CefString::from(&cef::CefStringUtf8::from(text.as_str()));
+///             cef::v8value_create_string(Some(&text))
+///         });
+///     });
+///     Some(promise)
+/// }
+/// ```
+pub struct RemotePromise {
+    runner: TaskRunner,
+    promise: Option<ManuallyDrop<V8Promise>>,
+}
+
+// The task runner is thread safe, and the promise is only used and released on its thread.
+unsafe impl Send for RemotePromise {}
+
+impl RemotePromise {
+    /// Fulfill the promise with the value which `value` creates on the render thread, or reject
+    /// it if `value` returns `None`.
+    pub fn resolve(mut self, value: impl FnOnce() -> Option<V8value> + Send + 'static) -> bool {
+        self.settle(move |promise| {
+            let value = promise.context.entered(value);
+            match value {
+                Some(value) => promise.resolve(value),
+                None => promise.reject("Failed to create the value"),
+            };
+        })
+    }
+
+    /// Reject the promise with an `Error` of `message`.
+    pub fn reject(mut self, message: impl Into<String>) -> bool {
+        let message = message.into();
+        self.settle(move |promise| {
+            promise.reject(&message);
+        })
+    }
+
+    fn settle(&mut self, callback: impl FnOnce(&V8Promise) + Send + 'static) -> bool {
+        let Some(promise) = self.promise.take() else {
+            return false;
+        };
+        let promise = Unsent(promise);
+        let settle = task(move || {
+            let mut promise = promise;
+            callback(&promise.0);
+            unsafe { ManuallyDrop::drop(&mut promise.0) };
+        });
+        self.runner.post_task(Some(&settle)) != 0
+    }
+}
+
+impl Drop for RemotePromise {
+    fn drop(&mut self) {
+        self.settle(|promise| {
+            promise.reject(DROPPED_MESSAGE);
+        });
+    }
+}
+
+/// The memory of an `ArrayBuffer` created by [V8ArrayBuffer::from_vec].
+///
+/// The `Vec` is dropped when both V8 released the `ArrayBuffer` and the [V8ArrayBuffer] was
+/// dropped. Scripts can write to the buffer, so only read it on the render thread of its context,
+/// where no script runs at the same time.
+pub struct V8ArrayBuffer {
+    data: Rc<SharedData>,
+}
+
+impl V8ArrayBuffer {
+    /// Create an `ArrayBuffer` backed by `data` without copying it. Must be called on the render
+    /// thread with a context entered, and returns `None` otherwise.
+    pub fn from_vec(data: Vec<u8>) -> Option<(V8value, V8ArrayBuffer)> {
+        let (buffer, release_callback) = Self::share(data);
+        let value = v8value_create_array_buffer(
+            buffer.data.as_ptr(),
+            buffer.data.len(),
+            Some(&release_callback),
+        )?;
+        Some((value, buffer))
+    }
+
+    /// The buffer, and the callback which releases the reference of V8 to it.
+    fn share(data: Vec<u8>) -> (Self, V8arrayBufferReleaseCallback) {
+        let data = Rc::new(SharedData(UnsafeCell::new(data)));
+        let shared = data.clone();
+        let release_callback = V8arrayBufferReleaseCallback::new(ReleaseBuffer {
+            object: std::ptr::null_mut(),
+            release: Release::new(RefCell::new(Some(Box::new(move || drop(shared))))),
+        });
+        (Self { data }, release_callback)
+    }
+
+    /// The contents of the buffer.
+    pub fn as_slice(&self) -> &[u8] {
+        unsafe { &*self.data.0.get() }
+    }
+
+    /// A copy of the contents of the buffer.
+    pub fn copy_to_vec(&self) -> Vec<u8> {
+        self.as_slice().to_vec()
+    }
+}
+
+/// The `Vec` of a [V8ArrayBuffer], which V8 writes through a pointer.
+struct SharedData(UnsafeCell<Vec<u8>>);
+
+impl SharedData {
+    fn as_ptr(&self) -> *mut u8 {
+        unsafe { (*self.0.get()).as_mut_ptr() }
+    }
+
+    fn len(&self) -> usize {
+        unsafe { (*self.0.get()).len() }
+    }
+}
+
+/// Drops the reference of V8 to the [SharedData], once.
+type Release = Rc<RefCell<Option<Box<dyn FnOnce()>>>>;
+
+struct ReleaseBuffer {
+    object: *mut RcImpl<_cef_v8array_buffer_release_callback_t, Self>,
+    release: Release,
+}
+
+wrap_rc!(
+    ReleaseBuffer,
+    WrapV8arrayBufferReleaseCallback,
+    _cef_v8array_buffer_release_callback_t,
+    release
+);
+
+impl ImplV8arrayBufferReleaseCallback for ReleaseBuffer {
+    fn release_buffer(&self, _buffer: *mut u8) {
+        let release = self.release.borrow_mut().take();
+        if let Some(release) = release {
+            release();
+        }
+    }
+
+    fn get_raw(&self) -> *mut _cef_v8array_buffer_release_callback_t {
+        self.object as *mut _
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        ptr::null_mut,
+        sync::{Arc, Mutex},
+    };
+
+    use cef_sys::{_cef_v8context_t, _cef_v8value_t};
+
+    use super::*;
+    use crate::{
+        rc::{wrap_rc, RcImpl},
+        CefStringUtf16,
+    };
+
+    type Events = Arc<Mutex<Vec<String>>>;
+
+    struct FakeContext {
+        object: *mut RcImpl<_cef_v8context_t, Self>,
+        events: Events,
+    }
+
+    impl ImplV8context for FakeContext {
+        fn enter(&self) -> std::os::raw::c_int {
+            self.events.lock().unwrap().push("enter".into());
+            1
+        }
+
+        fn exit(&self) -> std::os::raw::c_int {
+            self.events.lock().unwrap().push("exit".into());
+            1
+        }
+
+        fn get_raw(&self) -> *mut _cef_v8context_t {
+            self.object as *mut _
+        }
+    }
+
+    wrap_rc!(FakeContext, WrapV8context, _cef_v8context_t, events);
+
+    struct FakePromise {
+        object: *mut RcImpl<_cef_v8value_t, Self>,
+        events: Events,
+    }
+
+    impl ImplV8value for FakePromise {
+        fn resolve_promise(&self, _arg: Option<&impl ImplV8value>) -> std::os::raw::c_int {
+            let mut events = self.events.lock().unwrap();
+            let pending = !events.iter().any(|event| event.starts_with("re"));
+            events.push("resolve".into());
+            pending as _
+        }
+
+        fn reject_promise(&self, error_msg: Option<&CefStringUtf16>) -> std::os::raw::c_int {
+            let message = error_msg.map(|message| message.to_string());
+            let mut events = self.events.lock().unwrap();
+            let pending = !events.iter().any(|event| event.starts_with("re"));
+            events.push(format!("reject {}", message.unwrap_or_default()));
+            pending as _
+        }
+
+        fn get_raw(&self) -> *mut _cef_v8value_t {
+            self.object as *mut _
+        }
+    }
+
+    wrap_rc!(FakePromise, WrapV8value, _cef_v8value_t, events);
+
+    fn promise(events: &Events) -> V8Promise {
+        V8Promise {
+            context: V8context::new(FakeContext {
+                object: null_mut(),
+                events: events.clone(),
+            }),
+            promise: V8value::new(FakePromise {
+                object: null_mut(),
+                events: events.clone(),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_resolve() {
+        let events = Events::default();
+        let promise = promise(&events);
+        assert!(promise.resolve(promise.promise().clone()));
+        assert!(!promise.reject("too late"));
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                "enter",
+                "resolve",
+                "exit",
+                "enter",
+                "reject too late",
+                "exit"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_array_buffer_from_vec() {
+        let mut data = vec![0u8; 1024 * 1024];
+        data[123_456] = 42;
+        let ptr = data.as_ptr();
+        let (buffer, release_callback) = V8ArrayBuffer::share(data);
+
+        // V8 gets the memory of the `Vec` itself.
+        assert_eq!(buffer.data.as_ptr() as *const u8, ptr);
+        assert_eq!(buffer.as_slice().as_ptr(), ptr);
+        assert_eq!(buffer.as_slice().len(), 1024 * 1024);
+        assert_eq!(buffer.as_slice()[123_456], 42);
+        assert_eq!(Rc::strong_count(&buffer.data), 2);
+
+        release_callback.release_buffer(buffer.data.as_ptr());
+        assert_eq!(Rc::strong_count(&buffer.data), 1);
+        let copy = buffer.copy_to_vec();
+        assert_ne!(copy.as_ptr(), ptr);
+        assert_eq!(copy[123_456], 42);
+    }
+}
@@ -0,0 +1,149 @@
+//! Value module
+
+use crate::{list_value_create, ImplListValue, Value};
+
+/// A random-access list of [Value]s, see [crate::ListValue] for the raw interface.
+///
+/// Setting a value which isn't owned by another container transfers its ownership to the list,
+/// so read elements back with [ListValue::get] instead of reusing the [Value] passed in.
+#[derive(Clone)]
+pub struct ListValue(crate::ListValue);
+
+impl ListValue {
+    /// Create an empty list.
+    ///
+    /// # Panics
+    ///
+    /// If CEF fails to allocate the list.
+    pub fn new() -> Self {
+        Self(list_value_create().expect("Failed to create list value"))
+    }
+
+    /// The number of elements in the list.
+    pub fn len(&self) -> usize {
+        self.0.get_size()
+    }
+
+    /// `true` if the list has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the element at `index`, or `None` if it is out of bounds.
+    pub fn get(&self, index: usize) -> Option<Value> {
+        if index < self.len() {
+            self.0.get_value(index)
+        } else {
+            None
+        }
+    }
+
+    /// Set the element at `index`, growing the list with nulls if needed.
+    /// Returns `true` on success.
+    pub fn set(&self, index: usize, value: Value) -> bool {
+        self.0.set_value(index, Some(&value)) != 0
+    }
+
+    /// Append an element to the end of the list. Returns `true` on success.
+    pub fn push(&self, value: Value) -> bool {
+        self.set(self.len(), value)
+    }
+
+    /// Remove the element at `index`. Returns `true` on success.
+    pub fn remove(&self, index: usize) -> bool {
+        self.0.remove(index) != 0
+    }
+
+    /// Remove all elements.
+    pub fn clear(&self) {
+        self.0.clear();
+    }
+
+    /// Get the raw [crate::ListValue].
+    pub fn as_list_value(&self) -> &crate::ListValue {
+        &self.0
+    }
+}
+
+impl Default for ListValue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<crate::ListValue> for ListValue {
+    fn from(value: crate::ListValue) -> Self {
+        Self(value)
+    }
+}
+
+impl From<ListValue> for crate::ListValue {
+    fn from(value: ListValue) -> Self {
+        value.0
+    }
+}
+
+impl From<Vec<Value>> for ListValue {
+    fn from(values: Vec<Value>) -> Self {
+        values.into_iter().collect()
+    }
+}
+
+impl From<ListValue> for Vec<Value> {
+    fn from(list: ListValue) -> Self {
+        (0..list.len())
+            .filter_map(|index| list.get(index))
+            .collect()
+    }
+}
+
+impl FromIterator<Value> for ListValue {
+    fn from_iter<T: IntoIterator<Item = Value>>(iter: T) -> Self {
+        let list = Self::new();
+        for value in iter {
+            list.push(value);
+        }
+        list
+    }
+}
+
+impl IntoIterator for ListValue {
+    type Item = Value;
+    type IntoIter = std::vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Vec::from(self).into_iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{value_create, CefString, CefStringUtf8, ImplValue};
+
+    fn string_value(value: &str) -> Value {
+        let value = CefString::from(&CefStringUtf8::from(value));
+        let result = value_create().unwrap();
+        result.set_string(Some(&value));
+        result
+    }
+
+    #[test]
+    fn test_list_value_roundtrip() {
+        let strings = ["one", "two", "three"];
+        let list = ListValue::from(
+            strings
+                .iter()
+                .map(|value| string_value(value))
+                .collect::<Vec<_>>(),
+        );
+        assert_eq!(list.len(), strings.len());
+
+        let values = Vec::from(list);
+        assert_eq!(values.len(), strings.len());
+        for (value, expected) in values.iter().zip(strings) {
+            let expected = string_value(expected);
+            assert_ne!(value.is_equal(Some(&expected)), 0);
+        }
+    }
+}
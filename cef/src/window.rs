@@ -0,0 +1,912 @@
+//! Native window module
+//!
+//! Helpers for hosts which embed the CEF window as a child of their own native window. The
+//! methods do nothing when the browser has no native window, e.g. in off-screen rendering mode,
+//! where the host changes its own window instead.
+//!
+//! [BrowserHost::native_handle] and the platform accessors, such as `BrowserHost::hwnd` on
+//! Windows, expose the native window to platform APIs. They only exist on their platform.
+//!
+//! [BrowserHost::attach_to_window] creates a browser as the child of an existing native window.
+//!
+//! On Linux, the methods which change or measure the window call Xlib on the display connection
+//! of CEF. They need the `x11` feature, which links `libX11`. The accessors of the native window
+//! don't need it.
+
+#[cfg(any(target_os = "linux", target_os = "windows", target_os = "macos"))]
+use std::ffi::c_void;
+
+#[cfg(any(feature = "x11", not(target_os = "linux")))]
+use crate::Rect;
+#[cfg(any(
+    all(target_os = "linux", feature = "x11"),
+    target_os = "windows",
+    target_os = "macos"
+))]
+use crate::{
+    browser::{create_browser_with, CreateBrowserError},
+    Browser, BrowserSettings, ImplClient, WindowInfo,
+};
+use crate::{BrowserHost, ImplBrowserHost};
+
+/// The native window of a browser, to pass to platform APIs or windowing crates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NativeWindowHandle {
+    /// The X11 window and the display connection of CEF.
+    #[cfg(target_os = "linux")]
+    X11 { display: *mut c_void, window: u64 },
+    /// The `HWND` of the window.
+    #[cfg(target_os = "windows")]
+    Win32 { hwnd: *mut c_void },
+    /// The `NSView` of the browser.
+    #[cfg(target_os = "macos")]
+    AppKit { ns_view: *mut c_void },
+}
+
+#[cfg(any(
+    all(target_os = "linux", feature = "x11"),
+    target_os = "windows",
+    target_os = "macos"
+))]
+impl NativeWindowHandle {
+    #[allow(clippy::unnecessary_cast)]
+    fn raw(&self) -> native::Handle {
+        match *self {
+            #[cfg(target_os = "linux")]
+            Self::X11 { window, .. } => window as native::Handle,
+            #[cfg(target_os = "windows")]
+            Self::Win32 { hwnd } => hwnd,
+            #[cfg(target_os = "macos")]
+            Self::AppKit { ns_view } => ns_view,
+        }
+    }
+}
+
+impl BrowserHost {
+    /// Create a browser which loads `url` as a child of `window`, filling its client area.
+    ///
+    /// On Linux, the X11 window must be mapped and visible before this is called, or the browser
+    /// is created without a size and stays blank. The `display` of the handle is ignored, CEF
+    /// uses its own connection, see [BrowserHost::x11_display].
+    ///
+    /// Must be called on the UI thread.
+    #[cfg(any(
+        all(target_os = "linux", feature = "x11"),
+        target_os = "windows",
+        target_os = "macos"
+    ))]
+    pub fn attach_to_window(
+        window: NativeWindowHandle,
+        client: &impl ImplClient,
+        url: &str,
+        settings: BrowserSettings,
+    ) -> Result<Browser, CreateBrowserError> {
+        let handle = window.raw();
+        let bounds = unsafe { native::get_rect(handle) }.map(|rect| Rect { x: 0, y: 0, ..rect });
+        let window_info = child_window_info(handle, bounds);
+        create_browser_with(&window_info, &settings, client, url, None, None)
+    }
+
+    /// Show or hide the native window of the browser.
+    #[cfg(any(feature = "x11", not(target_os = "linux")))]
+    pub fn set_window_visibility(&self, visible: bool) {
+        if let Some(handle) = self.native_window_handle() {
+            unsafe { native::set_visibility(handle, visible) };
+        }
+    }
+
+    /// Move and resize the native window of the browser, relative to its parent window.
+    #[cfg(any(feature = "x11", not(target_os = "linux")))]
+    pub fn set_window_rect(&self, rect: Rect) {
+        if let Some(handle) = self.native_window_handle() {
+            unsafe { native::set_rect(handle, &rect) };
+        }
+    }
+
+    /// Get the bounds of the native window of the browser, relative to its parent window.
+    ///
+    /// Returns an empty rectangle if the browser has no native window.
+    #[cfg(any(feature = "x11", not(target_os = "linux")))]
+    pub fn get_window_rect(&self) -> Rect {
+        self.native_window_handle()
+            .and_then(|handle| unsafe { native::get_rect(handle) })
+            .unwrap_or(Rect {
+                x: 0,
+                y: 0,
+                width: 0,
+                height: 0,
+            })
+    }
+
+    /// Show or hide the title bar and borders of the top-level window which contains the browser.
+    /// On Linux, the window manager has to support the Motif hints.
+    #[cfg(any(all(target_os = "linux", feature = "x11"), target_os = "windows"))]
+    pub fn set_window_has_frame(&self, has_frame: bool) {
+        if let Some(handle) = self.native_window_handle() {
+            unsafe { native::set_has_frame(native::top_level(handle), has_frame) };
+        }
+    }
+
+    /// Keep the top-level window which contains the browser above all other windows. On Linux,
+    /// the window manager has to support `_NET_WM_STATE_ABOVE`.
+    #[cfg(any(all(target_os = "linux", feature = "x11"), target_os = "windows"))]
+    pub fn set_always_on_top(&self, always_on_top: bool) {
+        if let Some(handle) = self.native_window_handle() {
+            unsafe { native::set_always_on_top(native::top_level(handle), always_on_top) };
+        }
+    }
+
+    /// The native window of the browser, or `None` if it has none, e.g. in off-screen rendering
+    /// mode.
+    #[cfg(any(target_os = "linux", target_os = "windows", target_os = "macos"))]
+    #[allow(clippy::unnecessary_cast)]
+    pub fn native_handle(&self) -> Option<NativeWindowHandle> {
+        let handle = self.native_window_handle()?;
+        #[cfg(target_os = "linux")]
+        let handle = NativeWindowHandle::X11 {
+            display: self.x11_display(),
+            window: handle as u64,
+        };
+        #[cfg(target_os = "windows")]
+        let handle = NativeWindowHandle::Win32 { hwnd: handle };
+        #[cfg(target_os = "macos")]
+        let handle = NativeWindowHandle::AppKit { ns_view: handle };
+        Some(handle)
+    }
+
+    /// The X11 window of the browser, or 0 if it has none. X11 window IDs are `c_ulong`, which is
+    /// 32 bits wide on some targets.
+    #[cfg(target_os = "linux")]
+    #[allow(clippy::unnecessary_cast)]
+    pub fn x11_window(&self) -> u64 {
+        self.native_window_handle().unwrap_or_default() as u64
+    }
+
+    /// The X11 display connection which CEF uses for its windows, or null if there is none, e.g.
+    /// before CEF is initialized. It is the same for every browser.
+    #[cfg(target_os = "linux")]
+    pub fn x11_display(&self) -> *mut c_void {
+        unsafe { cef_sys::cef_get_xdisplay() as *mut c_void }
+    }
+
+    /// The `HWND` of the browser window, or null if it has none.
+    #[cfg(target_os = "windows")]
+    pub fn hwnd(&self) -> *mut c_void {
+        self.native_window_handle().unwrap_or(std::ptr::null_mut())
+    }
+
+    /// The `NSView` of the browser, or null if it has none.
+    #[cfg(target_os = "macos")]
+    pub fn ns_view(&self) -> *mut c_void {
+        self.native_window_handle().unwrap_or(std::ptr::null_mut())
+    }
+
+    // The window handle is an X11 window ID on Linux, and a pointer on Windows and macOS.
+    #[cfg(target_os = "linux")]
+    #[allow(clippy::unnecessary_cast)]
+    fn native_window_handle(&self) -> Option<native::Handle> {
+        let handle = ImplBrowserHost::get_window_handle(self);
+        (handle != 0).then_some(handle as native::Handle)
+    }
+
+    #[cfg(any(target_os = "windows", target_os = "macos"))]
+    fn native_window_handle(&self) -> Option<native::Handle> {
+        let handle = ImplBrowserHost::get_window_handle(self);
+        (!handle.is_null()).then_some(handle as native::Handle)
+    }
+}
+
+/// The [WindowInfo] of a browser window inside `parent`, covering `bounds` if they are known.
+#[cfg(any(
+    all(target_os = "linux", feature = "x11"),
+    target_os = "windows",
+    target_os = "macos"
+))]
+fn child_window_info(parent: native::Handle, bounds: Option<Rect>) -> WindowInfo {
+    let mut window_info = WindowInfo {
+        parent_window: parent as _,
+        ..Default::default()
+    };
+    if let Some(bounds) = bounds {
+        window_info.bounds = bounds;
+    }
+    window_info
+}
+
+#[cfg(target_os = "linux")]
+mod native {
+    use std::os::raw::c_ulong;
+
+    #[cfg(feature = "x11")]
+    pub use xlib::*;
+
+    pub type Handle = c_ulong;
+
+    /// The Xlib calls on the display connection of CEF, which link `libX11`.
+    #[cfg(feature = "x11")]
+    mod xlib {
+        use std::{
+            os::raw::{c_char, c_int, c_long, c_uchar, c_uint, c_ulong, c_void},
+            ptr,
+        };
+
+        use cef_sys::{cef_get_xdisplay, XDisplay};
+
+        use super::Handle;
+        use crate::Rect;
+
+        const PROP_MODE_REPLACE: c_int = 0;
+        const CLIENT_MESSAGE: c_int = 33;
+        const SUBSTRUCTURE_NOTIFY_MASK: c_long = 1 << 19;
+        const SUBSTRUCTURE_REDIRECT_MASK: c_long = 1 << 20;
+        const MWM_HINTS_DECORATIONS: c_ulong = 1 << 1;
+        const NET_WM_STATE_REMOVE: c_long = 0;
+        const NET_WM_STATE_ADD: c_long = 1;
+        /// The request comes from a normal application.
+        const NET_WM_SOURCE_APPLICATION: c_long = 1;
+
+        #[repr(C)]
+        #[derive(Clone, Copy)]
+        struct XClientMessageEvent {
+            type_: c_int,
+            serial: c_ulong,
+            send_event: c_int,
+            display: *mut XDisplay,
+            window: c_ulong,
+            message_type: c_ulong,
+            format: c_int,
+            data: [c_long; 5],
+        }
+
+        /// `XEvent` is a union padded to 24 longs.
+        #[repr(C)]
+        union XEvent {
+            client_message: XClientMessageEvent,
+            pad: [c_long; 24],
+        }
+
+        #[link(name = "X11")]
+        extern "C" {
+            fn XMapWindow(display: *mut XDisplay, window: c_ulong) -> c_int;
+            fn XUnmapWindow(display: *mut XDisplay, window: c_ulong) -> c_int;
+            fn XMoveResizeWindow(
+                display: *mut XDisplay,
+                window: c_ulong,
+                x: c_int,
+                y: c_int,
+                width: c_uint,
+                height: c_uint,
+            ) -> c_int;
+            #[allow(clippy::too_many_arguments)]
+            fn XGetGeometry(
+                display: *mut XDisplay,
+                drawable: c_ulong,
+                root: *mut c_ulong,
+                x: *mut c_int,
+                y: *mut c_int,
+                width: *mut c_uint,
+                height: *mut c_uint,
+                border_width: *mut c_uint,
+                depth: *mut c_uint,
+            ) -> c_int;
+            fn XFlush(display: *mut XDisplay) -> c_int;
+            fn XInternAtom(
+                display: *mut XDisplay,
+                name: *const c_char,
+                only_if_exists: c_int,
+            ) -> c_ulong;
+            #[allow(clippy::too_many_arguments)]
+            fn XChangeProperty(
+                display: *mut XDisplay,
+                window: c_ulong,
+                property: c_ulong,
+                type_: c_ulong,
+                format: c_int,
+                mode: c_int,
+                data: *const c_uchar,
+                count: c_int,
+            ) -> c_int;
+            fn XQueryTree(
+                display: *mut XDisplay,
+                window: c_ulong,
+                root: *mut c_ulong,
+                parent: *mut c_ulong,
+                children: *mut *mut c_ulong,
+                count: *mut c_uint,
+            ) -> c_int;
+            fn XSendEvent(
+                display: *mut XDisplay,
+                window: c_ulong,
+                propagate: c_int,
+                event_mask: c_long,
+                event: *mut XEvent,
+            ) -> c_int;
+            fn XFree(data: *mut c_void) -> c_int;
+        }
+
+        /// The `_MOTIF_WM_HINTS` property which shows or hides the window decorations.
+        pub fn motif_hints(has_frame: bool) -> [c_ulong; 5] {
+            [MWM_HINTS_DECORATIONS, 0, has_frame as c_ulong, 0, 0]
+        }
+
+        /// The `_NET_WM_STATE` message data which adds or removes `state`.
+        pub fn net_wm_state(add: bool, state: c_ulong) -> [c_long; 5] {
+            let action = if add {
+                NET_WM_STATE_ADD
+            } else {
+                NET_WM_STATE_REMOVE
+            };
+            [action, state as c_long, 0, NET_WM_SOURCE_APPLICATION, 0]
+        }
+
+        /// The ancestor of `handle` which is a child of the root window, i.e. the window the window
+        /// manager decorates.
+        pub unsafe fn top_level(handle: Handle) -> Handle {
+            let display = cef_get_xdisplay();
+            if display.is_null() {
+                return handle;
+            }
+            let mut window = handle;
+            loop {
+                let (mut root, mut parent, mut children, mut count) = (0, 0, ptr::null_mut(), 0);
+                if XQueryTree(
+                    display,
+                    window,
+                    &mut root,
+                    &mut parent,
+                    &mut children,
+                    &mut count,
+                ) == 0
+                {
+                    return window;
+                }
+                if !children.is_null() {
+                    XFree(children as *mut c_void);
+                }
+                if parent == 0 || parent == root {
+                    return window;
+                }
+                window = parent;
+            }
+        }
+
+        pub unsafe fn set_has_frame(handle: Handle, has_frame: bool) {
+            let display = cef_get_xdisplay();
+            if display.is_null() {
+                return;
+            }
+            let property = XInternAtom(display, c"_MOTIF_WM_HINTS".as_ptr(), 0);
+            let hints = motif_hints(has_frame);
+            XChangeProperty(
+                display,
+                handle,
+                property,
+                property,
+                32,
+                PROP_MODE_REPLACE,
+                hints.as_ptr() as *const c_uchar,
+                hints.len() as c_int,
+            );
+            XFlush(display);
+        }
+
+        pub unsafe fn set_always_on_top(handle: Handle, always_on_top: bool) {
+            let display = cef_get_xdisplay();
+            if display.is_null() {
+                return;
+            }
+            let mut root = 0;
+            let (mut parent, mut children, mut count) = (0, ptr::null_mut(), 0);
+            if XQueryTree(
+                display,
+                handle,
+                &mut root,
+                &mut parent,
+                &mut children,
+                &mut count,
+            ) == 0
+            {
+                return;
+            }
+            if !children.is_null() {
+                XFree(children as *mut c_void);
+            }
+            // Mapped windows ask the window manager through the root window.
+            let mut event = XEvent {
+                client_message: XClientMessageEvent {
+                    type_: CLIENT_MESSAGE,
+                    serial: 0,
+                    send_event: 1,
+                    display,
+                    window: handle,
+                    message_type: XInternAtom(display, c"_NET_WM_STATE".as_ptr(), 0),
+                    format: 32,
+                    data: net_wm_state(
+                        always_on_top,
+                        XInternAtom(display, c"_NET_WM_STATE_ABOVE".as_ptr(), 0),
+                    ),
+                },
+            };
+            XSendEvent(
+                display,
+                root,
+                0,
+                SUBSTRUCTURE_REDIRECT_MASK | SUBSTRUCTURE_NOTIFY_MASK,
+                &mut event,
+            );
+            XFlush(display);
+        }
+
+        /// X11 geometry of a rectangle. X11 windows must be at least 1x1.
+        pub fn to_geometry(rect: &Rect) -> (c_int, c_int, c_uint, c_uint) {
+            (
+                rect.x,
+                rect.y,
+                rect.width.max(1) as c_uint,
+                rect.height.max(1) as c_uint,
+            )
+        }
+
+        pub fn from_geometry(x: c_int, y: c_int, width: c_uint, height: c_uint) -> Rect {
+            Rect {
+                x,
+                y,
+                width: width as c_int,
+                height: height as c_int,
+            }
+        }
+
+        pub unsafe fn set_visibility(handle: Handle, visible: bool) {
+            let display = cef_get_xdisplay();
+            if display.is_null() {
+                return;
+            }
+            if visible {
+                XMapWindow(display, handle);
+            } else {
+                XUnmapWindow(display, handle);
+            }
+            XFlush(display);
+        }
+
+        pub unsafe fn set_rect(handle: Handle, rect: &Rect) {
+            let display = cef_get_xdisplay();
+            if display.is_null() {
+                return;
+            }
+            let (x, y, width, height) = to_geometry(rect);
+            XMoveResizeWindow(display, handle, x, y, width, height);
+            XFlush(display);
+        }
+
+        pub unsafe fn get_rect(handle: Handle) -> Option<Rect> {
+            let display = cef_get_xdisplay();
+            if display.is_null() {
+                return None;
+            }
+            let (mut root, mut x, mut y, mut width, mut height, mut border_width, mut depth) =
+                (0, 0, 0, 0, 0, 0, 0);
+            (XGetGeometry(
+                display,
+                handle,
+                &mut root,
+                &mut x,
+                &mut y,
+                &mut width,
+                &mut height,
+                &mut border_width,
+                &mut depth,
+            ) != 0)
+                .then(|| from_geometry(x, y, width, height))
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod native {
+    use std::{
+        ffi::c_void,
+        os::raw::{c_int, c_uint},
+        ptr,
+    };
+
+    use crate::Rect;
+
+    pub type Handle = *mut c_void;
+
+    const SW_HIDE: c_int = 0;
+    const SW_SHOW: c_int = 5;
+    const SWP_NOSIZE: c_uint = 0x0001;
+    const SWP_NOMOVE: c_uint = 0x0002;
+    const SWP_NOZORDER: c_uint = 0x0004;
+    const SWP_NOACTIVATE: c_uint = 0x0010;
+    const SWP_FRAMECHANGED: c_uint = 0x0020;
+    const GA_ROOT: c_uint = 2;
+    const GWL_STYLE: c_int = -16;
+    const WS_THICKFRAME: isize = 0x0004_0000;
+    const WS_CAPTION: isize = 0x00C0_0000;
+    const HWND_TOPMOST: isize = -1;
+    const HWND_NOTOPMOST: isize = -2;
+
+    #[repr(C)]
+    #[derive(Default)]
+    pub struct NativeRect {
+        left: c_int,
+        top: c_int,
+        right: c_int,
+        bottom: c_int,
+    }
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn ShowWindow(hwnd: Handle, cmd_show: c_int) -> c_int;
+        fn SetWindowPos(
+            hwnd: Handle,
+            hwnd_insert_after: Handle,
+            x: c_int,
+            y: c_int,
+            cx: c_int,
+            cy: c_int,
+            flags: c_uint,
+        ) -> c_int;
+        fn GetWindowRect(hwnd: Handle, rect: *mut NativeRect) -> c_int;
+        fn GetParent(hwnd: Handle) -> Handle;
+        fn MapWindowPoints(
+            from: Handle,
+            to: Handle,
+            points: *mut NativeRect,
+            count: c_uint,
+        ) -> c_int;
+        fn GetAncestor(hwnd: Handle, flags: c_uint) -> Handle;
+        #[cfg_attr(target_pointer_width = "32", link_name = "GetWindowLongW")]
+        fn GetWindowLongPtrW(hwnd: Handle, index: c_int) -> isize;
+        #[cfg_attr(target_pointer_width = "32", link_name = "SetWindowLongW")]
+        fn SetWindowLongPtrW(hwnd: Handle, index: c_int, value: isize) -> isize;
+    }
+
+    /// The window style `style` with or without the title bar and sizing border.
+    pub fn frame_style(style: isize, has_frame: bool) -> isize {
+        if has_frame {
+            style | WS_CAPTION | WS_THICKFRAME
+        } else {
+            style & !(WS_CAPTION | WS_THICKFRAME)
+        }
+    }
+
+    pub unsafe fn top_level(handle: Handle) -> Handle {
+        let root = GetAncestor(handle, GA_ROOT);
+        if root.is_null() {
+            handle
+        } else {
+            root
+        }
+    }
+
+    pub unsafe fn set_has_frame(handle: Handle, has_frame: bool) {
+        let style = GetWindowLongPtrW(handle, GWL_STYLE);
+        SetWindowLongPtrW(handle, GWL_STYLE, frame_style(style, has_frame));
+        // The new style only applies once the frame is recalculated.
+        SetWindowPos(
+            handle,
+            ptr::null_mut(),
+            0,
+            0,
+            0,
+            0,
+            SWP_NOMOVE | SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE | SWP_FRAMECHANGED,
+        );
+    }
+
+    pub unsafe fn set_always_on_top(handle: Handle, always_on_top: bool) {
+        let insert_after = if always_on_top {
+            HWND_TOPMOST
+        } else {
+            HWND_NOTOPMOST
+        };
+        SetWindowPos(
+            handle,
+            insert_after as Handle,
+            0,
+            0,
+            0,
+            0,
+            SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE,
+        );
+    }
+
+    pub fn from_native(rect: &NativeRect) -> Rect {
+        Rect {
+            x: rect.left,
+            y: rect.top,
+            width: rect.right - rect.left,
+            height: rect.bottom - rect.top,
+        }
+    }
+
+    pub unsafe fn set_visibility(handle: Handle, visible: bool) {
+        ShowWindow(handle, if visible { SW_SHOW } else { SW_HIDE });
+    }
+
+    pub unsafe fn set_rect(handle: Handle, rect: &Rect) {
+        SetWindowPos(
+            handle,
+            ptr::null_mut(),
+            rect.x,
+            rect.y,
+            rect.width,
+            rect.height,
+            SWP_NOZORDER | SWP_NOACTIVATE,
+        );
+    }
+
+    pub unsafe fn get_rect(handle: Handle) -> Option<Rect> {
+        let mut rect = NativeRect::default();
+        if GetWindowRect(handle, &mut rect) == 0 {
+            return None;
+        }
+        // Screen coordinates to the parent's client coordinates, like `SetWindowPos` uses.
+        MapWindowPoints(ptr::null_mut(), GetParent(handle), &mut rect, 2);
+        Some(from_native(&rect))
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod native {
+    use std::{
+        ffi::{c_char, c_void},
+        mem,
+    };
+
+    use crate::Rect;
+
+    pub type Handle = *mut c_void;
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct NSRect {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    }
+
+    #[link(name = "objc")]
+    extern "C" {
+        fn sel_registerName(name: *const c_char) -> *mut c_void;
+        fn objc_msgSend();
+        #[cfg(target_arch = "x86_64")]
+        fn objc_msgSend_stret();
+    }
+
+    pub unsafe fn set_visibility(handle: Handle, visible: bool) {
+        let send: unsafe extern "C" fn(Handle, *mut c_void, bool) =
+            mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        send(handle, sel_registerName(c"setHidden:".as_ptr()), !visible);
+    }
+
+    pub unsafe fn set_rect(handle: Handle, rect: &Rect) {
+        let send: unsafe extern "C" fn(Handle, *mut c_void, NSRect) =
+            mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        let frame = NSRect {
+            x: rect.x as f64,
+            y: rect.y as f64,
+            width: rect.width as f64,
+            height: rect.height as f64,
+        };
+        send(handle, sel_registerName(c"setFrame:".as_ptr()), frame);
+    }
+
+    pub unsafe fn get_rect(handle: Handle) -> Option<Rect> {
+        let selector = sel_registerName(c"frame".as_ptr());
+        #[cfg(target_arch = "x86_64")]
+        let frame = {
+            let mut frame = NSRect::default();
+            let send: unsafe extern "C" fn(*mut NSRect, Handle, *mut c_void) =
+                mem::transmute(objc_msgSend_stret as unsafe extern "C" fn());
+            send(&mut frame, handle, selector);
+            frame
+        };
+        #[cfg(not(target_arch = "x86_64"))]
+        let frame = {
+            let send: unsafe extern "C" fn(Handle, *mut c_void) -> NSRect =
+                mem::transmute(objc_msgSend as unsafe extern "C" fn());
+            send(handle, selector)
+        };
+        Some(Rect {
+            x: frame.x as i32,
+            y: frame.y as i32,
+            width: frame.width as i32,
+            height: frame.height as i32,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_native_window_handle() {
+        use crate::fixtures::FakeHost;
+
+        let host = FakeHost::new().build();
+        assert_eq!(host.native_window_handle(), None);
+        assert_eq!(host.x11_window(), 0);
+
+        let host = FakeHost::new().window_handle(0x0420_0007).build();
+        assert_eq!(host.native_window_handle(), Some(0x0420_0007));
+        assert_eq!(host.x11_window(), 0x0420_0007);
+    }
+
+    #[cfg(all(target_os = "linux", feature = "x11"))]
+    #[test]
+    fn test_window_rect() {
+        use crate::fixtures::FakeHost;
+
+        let rect = Rect {
+            x: 10,
+            y: 20,
+            width: 640,
+            height: 480,
+        };
+        let bounds = |rect: Rect| (rect.x, rect.y, rect.width, rect.height);
+
+        // Off-screen browsers have no native window, so there is nothing to move or measure.
+        let host = FakeHost::new().build();
+        host.set_window_rect(rect.clone());
+        assert_eq!(bounds(host.get_window_rect()), (0, 0, 0, 0));
+
+        // CEF has no display connection before it is initialized, so the window is left alone.
+        let host = FakeHost::new().window_handle(0x0420_0007).build();
+        host.set_window_rect(rect);
+        assert_eq!(bounds(host.get_window_rect()), (0, 0, 0, 0));
+    }
+
+    #[cfg(all(target_os = "linux", feature = "x11"))]
+    #[test]
+    fn test_window_rect_geometry() {
+        let rect = Rect {
+            x: 10,
+            y: 20,
+            width: 640,
+            height: 480,
+        };
+        let (x, y, width, height) = native::to_geometry(&rect);
+        let rect = native::from_geometry(x, y, width, height);
+        assert_eq!((rect.x, rect.y), (10, 20));
+        assert_eq!((rect.width, rect.height), (640, 480));
+    }
+
+    #[cfg(all(target_os = "linux", feature = "x11"))]
+    #[test]
+    fn test_window_rect_geometry_minimum_size() {
+        let rect = Rect {
+            x: 0,
+            y: 0,
+            width: 0,
+            height: -5,
+        };
+        assert_eq!(native::to_geometry(&rect), (0, 0, 1, 1));
+    }
+
+    #[cfg(all(target_os = "linux", feature = "x11"))]
+    #[test]
+    fn test_window_frame_hints() {
+        assert_eq!(native::motif_hints(false), [2, 0, 0, 0, 0]);
+        assert_eq!(native::motif_hints(true), [2, 0, 1, 0, 0]);
+        assert_eq!(native::net_wm_state(true, 42), [1, 42, 0, 1, 0]);
+        assert_eq!(native::net_wm_state(false, 42), [0, 42, 0, 1, 0]);
+    }
+
+    #[cfg(all(target_os = "linux", feature = "x11"))]
+    #[test]
+    fn test_x11_without_display() {
+        // CEF has no display connection before it is initialized, so nothing is sent to X.
+        let window = 0x0420_0007;
+        unsafe {
+            native::set_visibility(window, true);
+            native::set_rect(window, &Rect::default());
+            native::set_has_frame(window, false);
+            native::set_always_on_top(window, true);
+            assert_eq!(native::top_level(window), window);
+            assert!(native::get_rect(window).is_none());
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn test_window_frame_style() {
+        const WS_VISIBLE: isize = 0x1000_0000;
+        let framed = native::frame_style(WS_VISIBLE, true);
+        assert_eq!(framed, WS_VISIBLE | 0x00C0_0000 | 0x0004_0000);
+        assert_eq!(native::frame_style(framed, false), WS_VISIBLE);
+    }
+
+    #[cfg(any(all(target_os = "linux", feature = "x11"), target_os = "windows"))]
+    #[test]
+    fn test_window_methods() {
+        let _: fn(&BrowserHost, bool) = BrowserHost::set_window_has_frame;
+        let _: fn(&BrowserHost, bool) = BrowserHost::set_always_on_top;
+    }
+
+    #[cfg(all(target_os = "linux", feature = "x11"))]
+    #[test]
+    fn test_attach_window_info() {
+        let parent = NativeWindowHandle::X11 {
+            display: std::ptr::null_mut(),
+            window: 0x0420_0007,
+        };
+        let bounds = Rect {
+            x: 0,
+            y: 0,
+            width: 800,
+            height: 600,
+        };
+        let window_info: cef_sys::_cef_window_info_t =
+            child_window_info(parent.raw(), Some(bounds)).into();
+        assert_eq!(window_info.parent_window, 0x0420_0007);
+        assert_eq!(window_info.window, 0);
+        assert_eq!(window_info.windowless_rendering_enabled, 0);
+        assert_eq!(
+            (window_info.bounds.width, window_info.bounds.height),
+            (800, 600)
+        );
+
+        let window_info: cef_sys::_cef_window_info_t = child_window_info(parent.raw(), None).into();
+        assert_eq!(window_info.parent_window, 0x0420_0007);
+        assert_eq!(window_info.bounds.width, 0);
+    }
+
+    #[cfg(any(target_os = "windows", target_os = "macos"))]
+    #[test]
+    fn test_attach_window_info() {
+        #[cfg(target_os = "windows")]
+        let parent = NativeWindowHandle::Win32 {
+            hwnd: 0x1234 as *mut c_void,
+        };
+        #[cfg(target_os = "macos")]
+        let parent = NativeWindowHandle::AppKit {
+            ns_view: 0x1234 as *mut c_void,
+        };
+        let window_info = child_window_info(parent.raw(), None);
+        assert_eq!(window_info.parent_window as usize, 0x1234);
+    }
+
+    #[cfg(any(
+        all(target_os = "linux", feature = "x11"),
+        target_os = "windows",
+        target_os = "macos"
+    ))]
+    #[test]
+    fn test_attach_to_window_signature() {
+        let _: fn(
+            NativeWindowHandle,
+            &crate::Client,
+            &str,
+            BrowserSettings,
+        ) -> Result<Browser, CreateBrowserError> = BrowserHost::attach_to_window;
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_native_handle_methods() {
+        let _: fn(&BrowserHost) -> Option<NativeWindowHandle> = BrowserHost::native_handle;
+        let _: fn(&BrowserHost) -> u64 = BrowserHost::x11_window;
+        let _: fn(&BrowserHost) -> *mut c_void = BrowserHost::x11_display;
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn test_native_handle_methods() {
+        let _: fn(&BrowserHost) -> Option<NativeWindowHandle> = BrowserHost::native_handle;
+        let _: fn(&BrowserHost) -> *mut c_void = BrowserHost::hwnd;
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn test_native_handle_methods() {
+        let _: fn(&BrowserHost) -> Option<NativeWindowHandle> = BrowserHost::native_handle;
+        let _: fn(&BrowserHost) -> *mut c_void = BrowserHost::ns_view;
+    }
+}
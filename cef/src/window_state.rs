@@ -0,0 +1,340 @@
+//! Window state module
+//!
+//! [WindowState] saves the bounds, display and maximized state of a window, so an app can restore
+//! it in the next run. Enable the `serde` feature to save it, e.g. as JSON. Restoring clamps the
+//! bounds to the displays attached then, so a window which was on a monitor that is gone, or whose
+//! work area shrank, still opens on screen.
+//!
+//! The bounds are stored in DIP screen coordinates, which the Views framework uses. Native windows
+//! use screen pixels instead, which [WindowState::capture_native] and [WindowState::to_window_info]
+//! convert with the scale factor of the display the window is on, so a window keeps its size when
+//! it is restored on a display with a different scale factor.
+
+use crate::{
+    display_get_alls, display_get_count, display_get_primary, Display, ImplDisplay, ImplWindow,
+    Rect, WindowInfo,
+};
+
+/// The placement of a window, see the [module documentation](self).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WindowState {
+    /// The left edge in DIP screen coordinates.
+    pub x: i32,
+    /// The top edge in DIP screen coordinates.
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+    /// The [ImplDisplay::get_id] of the display the window was on.
+    pub display_id: i64,
+    pub maximized: bool,
+}
+
+impl WindowState {
+    /// The state of a Views window. The bounds of a maximized window are its maximized bounds, so
+    /// capture them before it is maximized, e.g. in
+    /// [crate::ImplWindowDelegate::on_window_bounds_changed], and set
+    /// [WindowState::maximized] when saving.
+    pub fn capture(window: &impl ImplWindow) -> Self {
+        let display_id = window
+            .get_display()
+            .map(|display| display.get_id())
+            .unwrap_or_default();
+        Self::new(window.get_bounds(), display_id, window.is_maximized() != 0)
+    }
+
+    /// The state of a native window with `bounds` in screen pixels on the display `display_id`.
+    pub fn capture_native(bounds: Rect, display_id: i64, maximized: bool) -> Self {
+        Self::from_pixels(&bounds, display_id, maximized, &DisplayInfo::attached())
+    }
+
+    /// Move a Views window to the saved bounds, clamped to the attached displays, and maximize it
+    /// if it was maximized.
+    pub fn apply(&self, window: &impl ImplWindow) {
+        let state = self.clamp_to(&DisplayInfo::attached());
+        window.set_bounds(Some(&state.bounds()));
+        if state.maximized {
+            window.maximize();
+        }
+    }
+
+    /// The [WindowInfo] of a native window with the saved bounds, clamped to the attached displays
+    /// and converted to screen pixels. [WindowInfo] can't maximize a window, so maximize it
+    /// after it was created if [WindowState::maximized] is set.
+    pub fn to_window_info(&self) -> WindowInfo {
+        WindowInfo {
+            bounds: self.pixel_bounds(&DisplayInfo::attached()),
+            ..Default::default()
+        }
+    }
+
+    fn new(bounds: Rect, display_id: i64, maximized: bool) -> Self {
+        Self {
+            x: bounds.x,
+            y: bounds.y,
+            width: bounds.width,
+            height: bounds.height,
+            display_id,
+            maximized,
+        }
+    }
+
+    fn bounds(&self) -> Rect {
+        Rect {
+            x: self.x,
+            y: self.y,
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    /// The state of a window with `bounds` in screen pixels.
+    fn from_pixels(
+        bounds: &Rect,
+        display_id: i64,
+        maximized: bool,
+        displays: &[DisplayInfo],
+    ) -> Self {
+        let bounds = displays
+            .iter()
+            .find(|display| display.id == display_id)
+            .map(|display| display.from_pixels(bounds))
+            .unwrap_or_else(|| bounds.clone());
+        Self::new(bounds, display_id, maximized)
+    }
+
+    /// The clamped bounds in screen pixels.
+    fn pixel_bounds(&self, displays: &[DisplayInfo]) -> Rect {
+        let state = self.clamp_to(displays);
+        match state.display(displays) {
+            Some(display) => display.to_pixels(&state.bounds()),
+            None => state.bounds(),
+        }
+    }
+
+    /// The state moved onto one of `displays`, and shrunk to fit into its work area if needed.
+    /// The window stays on the display it was on if that is still attached, or moves to the one
+    /// it overlaps most, or to the first one, which [DisplayInfo::attached] returns as the primary
+    /// display.
+    pub fn clamp_to(&self, displays: &[DisplayInfo]) -> Self {
+        let Some(display) = self.display(displays) else {
+            return *self;
+        };
+        let area = &display.work_area;
+        let width = self.width.clamp(1, area.width.max(1));
+        let height = self.height.clamp(1, area.height.max(1));
+        Self {
+            x: self.x.clamp(area.x, area.x + area.width - width),
+            y: self.y.clamp(area.y, area.y + area.height - height),
+            width,
+            height,
+            display_id: display.id,
+            maximized: self.maximized,
+        }
+    }
+
+    /// The display the window belongs on, see [WindowState::clamp_to].
+    fn display<'a>(&self, displays: &'a [DisplayInfo]) -> Option<&'a DisplayInfo> {
+        let bounds = self.bounds();
+        displays
+            .iter()
+            .find(|display| display.id == self.display_id)
+            .or_else(|| {
+                displays
+                    .iter()
+                    .map(|display| (overlap(&display.work_area, &bounds), display))
+                    .filter(|&(overlap, _)| overlap > 0)
+                    .max_by_key(|&(overlap, _)| overlap)
+                    .map(|(_, display)| display)
+            })
+            .or(displays.first())
+    }
+}
+
+/// The area in which two rectangles overlap.
+fn overlap(a: &Rect, b: &Rect) -> i64 {
+    let span = |a_start: i32, a_len: i32, b_start: i32, b_len: i32| {
+        let start = a_start.max(b_start) as i64;
+        let end = (a_start as i64 + a_len as i64).min(b_start as i64 + b_len as i64);
+        (end - start).max(0)
+    };
+    span(a.x, a.width, b.x, b.width) * span(a.y, a.height, b.y, b.height)
+}
+
+/// The layout of a display, to place a [WindowState] with [WindowState::clamp_to].
+#[derive(Clone)]
+pub struct DisplayInfo {
+    /// See [ImplDisplay::get_id].
+    pub id: i64,
+    /// The bounds in DIP screen coordinates.
+    pub bounds: Rect,
+    /// The bounds without the taskbar and docks, in DIP screen coordinates.
+    pub work_area: Rect,
+    /// The bounds in screen pixels.
+    pub pixel_bounds: Rect,
+    pub scale_factor: f32,
+}
+
+impl DisplayInfo {
+    /// The displays attached now, the primary display first.
+    pub fn attached() -> Vec<Self> {
+        let mut displays = vec![None; display_get_count()];
+        display_get_alls(Some(&mut displays));
+        let primary = display_get_primary().map(|display| display.get_id());
+        let mut displays: Vec<_> = displays.iter().flatten().map(Self::from).collect();
+        displays.sort_by_key(|display| Some(display.id) != primary);
+        displays
+    }
+
+    /// Convert `rect` from DIP screen coordinates on this display to screen pixels.
+    pub fn to_pixels(&self, rect: &Rect) -> Rect {
+        let scale = |length: i32| (length as f32 * self.scale_factor).round() as i32;
+        Rect {
+            x: self.pixel_bounds.x + scale(rect.x - self.bounds.x),
+            y: self.pixel_bounds.y + scale(rect.y - self.bounds.y),
+            width: scale(rect.width),
+            height: scale(rect.height),
+        }
+    }
+
+    /// Convert `rect` from screen pixels on this display to DIP screen coordinates.
+    pub fn from_pixels(&self, rect: &Rect) -> Rect {
+        let scale_factor = if self.scale_factor > 0.0 {
+            self.scale_factor
+        } else {
+            1.0
+        };
+        let unscale = |length: i32| (length as f32 / scale_factor).round() as i32;
+        Rect {
+            x: self.bounds.x + unscale(rect.x - self.pixel_bounds.x),
+            y: self.bounds.y + unscale(rect.y - self.pixel_bounds.y),
+            width: unscale(rect.width),
+            height: unscale(rect.height),
+        }
+    }
+}
+
+impl From<&Display> for DisplayInfo {
+    fn from(display: &Display) -> Self {
+        let bounds = display.get_bounds();
+        let scale_factor = display.get_device_scale_factor();
+        // CEF only converts screen coordinates on Windows. Elsewhere, DIP screen coordinates are
+        // the pixel coordinates divided by the scale factor.
+        #[cfg(target_os = "windows")]
+        let pixel_bounds = crate::display_convert_screen_rect_to_pixels(Some(&bounds));
+        #[cfg(not(target_os = "windows"))]
+        let pixel_bounds = {
+            let scale = |length: i32| (length as f32 * scale_factor).round() as i32;
+            Rect {
+                x: scale(bounds.x),
+                y: scale(bounds.y),
+                width: scale(bounds.width),
+                height: scale(bounds.height),
+            }
+        };
+        Self {
+            id: display.get_id(),
+            work_area: display.get_work_area(),
+            bounds,
+            pixel_bounds,
+            scale_factor,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn rect(x: i32, y: i32, width: i32, height: i32) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    fn state(x: i32, y: i32, width: i32, height: i32, display_id: i64) -> WindowState {
+        WindowState::new(rect(x, y, width, height), display_id, false)
+    }
+
+    /// A 1920x1080 primary display with a taskbar, and a 2x display of 1280x720 DIPs to its right.
+    fn layout() -> Vec<DisplayInfo> {
+        vec![
+            DisplayInfo {
+                id: 1,
+                bounds: rect(0, 0, 1920, 1080),
+                work_area: rect(0, 0, 1920, 1040),
+                pixel_bounds: rect(0, 0, 1920, 1080),
+                scale_factor: 1.0,
+            },
+            DisplayInfo {
+                id: 2,
+                bounds: rect(1920, 0, 1280, 720),
+                work_area: rect(1920, 0, 1280, 720),
+                pixel_bounds: rect(1920, 0, 2560, 1440),
+                scale_factor: 2.0,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_clamp_on_same_display() {
+        let displays = layout();
+        let inside = state(100, 100, 800, 600, 1);
+        assert_eq!(inside.clamp_to(&displays), inside);
+        // Moved back above the taskbar.
+        assert_eq!(
+            state(1500, 900, 800, 600, 1).clamp_to(&displays),
+            state(1120, 440, 800, 600, 1)
+        );
+        // Shrunk to the work area.
+        assert_eq!(
+            state(2000, -50, 1600, 900, 2).clamp_to(&displays),
+            state(1920, 0, 1280, 720, 2)
+        );
+    }
+
+    #[test]
+    fn test_clamp_after_display_removed() {
+        let displays = layout();
+        // Display 3 is gone, the window mostly overlaps display 2.
+        assert_eq!(
+            state(2900, 100, 800, 600, 3).clamp_to(&displays),
+            state(2400, 100, 800, 600, 2)
+        );
+        // Far off screen, it moves to the primary display.
+        assert_eq!(
+            state(-5000, -5000, 800, 600, 3).clamp_to(&displays),
+            state(0, 0, 800, 600, 1)
+        );
+        assert_eq!(
+            state(-5000, -5000, 800, 600, 3).clamp_to(&[]),
+            state(-5000, -5000, 800, 600, 3)
+        );
+    }
+
+    #[test]
+    fn test_pixels_per_display() {
+        let displays = layout();
+        let window = state(2020, 50, 600, 400, 2);
+        let pixels = window.pixel_bounds(&displays);
+        assert_eq!(
+            (pixels.x, pixels.y, pixels.width, pixels.height),
+            (2120, 100, 1200, 800)
+        );
+        assert_eq!(
+            WindowState::from_pixels(&pixels, 2, false, &displays),
+            window
+        );
+
+        // The same DIP size on the 1x display.
+        let window = state(100, 50, 600, 400, 1);
+        let pixels = window.pixel_bounds(&displays);
+        assert_eq!(
+            (pixels.x, pixels.y, pixels.width, pixels.height),
+            (100, 50, 600, 400)
+        );
+    }
+}
@@ -3,5 +3,25 @@ bindgen wrapper.h -o src/bindings.rs \
     --default-enum-style=rust_non_exhaustive \
     --allowlist-type cef_.* \
     --allowlist-function cef_.* \
+    --allowlist-var CEF_API_HASH_.* \
     --bitfield-enum .*_mask_t \
+    --bitfield-enum cef_cert_status_t \
+    --bitfield-enum cef_context_menu_edit_state_flags_t \
+    --bitfield-enum cef_context_menu_media_state_flags_t \
+    --bitfield-enum cef_context_menu_type_flags_t \
+    --bitfield-enum cef_dom_event_category_t \
+    --bitfield-enum cef_event_flags_t \
+    --bitfield-enum cef_json_parser_options_t \
+    --bitfield-enum cef_json_writer_options_t \
+    --bitfield-enum cef_log_items_t \
+    --bitfield-enum cef_media_access_permission_types_t \
+    --bitfield-enum cef_permission_request_types_t \
+    --bitfield-enum cef_quick_menu_edit_state_flags_t \
+    --bitfield-enum cef_scheme_options_t \
+    --bitfield-enum cef_ssl_content_status_t \
+    --bitfield-enum cef_touch_handle_state_flags_t \
+    --bitfield-enum cef_transition_type_t \
+    --bitfield-enum cef_uri_unescape_rule_t \
+    --bitfield-enum cef_urlrequest_flags_t \
+    --bitfield-enum cef_v8_propertyattribute_t \
     -- -I $HOME/.local/share/cef/
@@ -1,5 +1,24 @@
-#[cfg(not(feature = "dox"))]
+#[allow(dead_code)]
+mod version {
+    include!("src/version.rs");
+}
+
 fn main() -> Result<(), String> {
+    // Read by the build script of the cef crate as `DEP_CEF_*`.
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=src/version.rs");
+    println!("cargo:version_major={}", version::CEF_VERSION_MAJOR);
+    let milestones: Vec<_> = version::CEF_API_MILESTONES
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    println!("cargo:api_milestones={}", milestones.join(","));
+
+    link()
+}
+
+#[cfg(not(feature = "dox"))]
+fn link() -> Result<(), String> {
     let path = std::env::var("FLATPAK")
         .map(|_| String::from("/usr/lib"))
         .or_else(|_| std::env::var("CEF_PATH"))
@@ -11,10 +30,14 @@ fn main() -> Result<(), String> {
         })
         .map_err(|e| format!("Couldn't get the path of shared library: {e}"))?;
 
+    println!("cargo:rerun-if-env-changed=FLATPAK");
+    println!("cargo:rerun-if-env-changed=CEF_PATH");
     println!("cargo:rustc-link-lib=cef");
     println!("cargo:rustc-link-search={path}");
     Ok(())
 }
 
 #[cfg(feature = "dox")]
-fn main() {}
+fn link() -> Result<(), String> {
+    Ok(())
+}
@@ -1,5 +1,13 @@
 /* automatically generated by rust-bindgen 0.71.1 */
 
+unsafe extern "C" {
+    #[doc = "\n Returns CEF API hashes for the libcef library. The returned string is owned\n by the library and should not be freed. The |entry| parameter describes which\n hash value will be returned:\n 0 - CEF_API_HASH_PLATFORM\n 1 - CEF_API_HASH_UNIVERSAL\n 2 - CEF_COMMIT_HASH (from cef_version.h)\n"]
+    pub fn cef_api_hash(entry: ::std::os::raw::c_int) -> *const ::std::os::raw::c_char;
+}
+unsafe extern "C" {
+    #[doc = "\n Returns CEF version information for the libcef library. The |entry|\n parameter describes which version component will be returned:\n 0 - CEF_VERSION_MAJOR\n 1 - CEF_VERSION_MINOR\n 2 - CEF_VERSION_PATCH\n 3 - CEF_COMMIT_NUMBER\n 4 - CHROME_VERSION_MAJOR\n 5 - CHROME_VERSION_MINOR\n 6 - CHROME_VERSION_BUILD\n 7 - CHROME_VERSION_PATCH\n"]
+    pub fn cef_version_info(entry: ::std::os::raw::c_int) -> ::std::os::raw::c_int;
+}
 pub type __uint16_t = ::std::os::raw::c_ushort;
 pub type __uint_least16_t = __uint16_t;
 pub type __time_t = ::std::os::raw::c_long;
@@ -989,24 +997,60 @@ pub enum cef_log_severity_t {
     #[doc = "\n Disable logging to file for all messages, and to stderr for messages with\n severity less than FATAL.\n"]
     LOGSEVERITY_DISABLE = 99,
 }
-#[repr(u32)]
-#[non_exhaustive]
-#[doc = "\n Log items prepended to each log line.\n"]
-#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
-pub enum cef_log_items_t {
+impl cef_log_items_t {
     #[doc = "\n Prepend the default list of items.\n"]
-    LOG_ITEMS_DEFAULT = 0,
+    pub const LOG_ITEMS_DEFAULT: cef_log_items_t = cef_log_items_t(0);
+}
+impl cef_log_items_t {
     #[doc = "\n Prepend no items.\n"]
-    LOG_ITEMS_NONE = 1,
+    pub const LOG_ITEMS_NONE: cef_log_items_t = cef_log_items_t(1);
+}
+impl cef_log_items_t {
     #[doc = "\n Prepend the process ID.\n"]
-    LOG_ITEMS_FLAG_PROCESS_ID = 2,
+    pub const LOG_ITEMS_FLAG_PROCESS_ID: cef_log_items_t = cef_log_items_t(2);
+}
+impl cef_log_items_t {
     #[doc = "\n Prepend the thread ID.\n"]
-    LOG_ITEMS_FLAG_THREAD_ID = 4,
+    pub const LOG_ITEMS_FLAG_THREAD_ID: cef_log_items_t = cef_log_items_t(4);
+}
+impl cef_log_items_t {
     #[doc = "\n Prepend the timestamp.\n"]
-    LOG_ITEMS_FLAG_TIME_STAMP = 8,
+    pub const LOG_ITEMS_FLAG_TIME_STAMP: cef_log_items_t = cef_log_items_t(8);
+}
+impl cef_log_items_t {
     #[doc = "\n Prepend the tickcount.\n"]
-    LOG_ITEMS_FLAG_TICK_COUNT = 16,
+    pub const LOG_ITEMS_FLAG_TICK_COUNT: cef_log_items_t = cef_log_items_t(16);
 }
+impl ::std::ops::BitOr<cef_log_items_t> for cef_log_items_t {
+    type Output = Self;
+    #[inline]
+    fn bitor(self, other: Self) -> Self {
+        cef_log_items_t(self.0 | other.0)
+    }
+}
+impl ::std::ops::BitOrAssign for cef_log_items_t {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: cef_log_items_t) {
+        self.0 |= rhs.0;
+    }
+}
+impl ::std::ops::BitAnd<cef_log_items_t> for cef_log_items_t {
+    type Output = Self;
+    #[inline]
+    fn bitand(self, other: Self) -> Self {
+        cef_log_items_t(self.0 & other.0)
+    }
+}
+impl ::std::ops::BitAndAssign for cef_log_items_t {
+    #[inline]
+    fn bitand_assign(&mut self, rhs: cef_log_items_t) {
+        self.0 &= rhs.0;
+    }
+}
+#[repr(transparent)]
+#[doc = "\n Log items prepended to each log line.\n"]
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct cef_log_items_t(pub ::std::os::raw::c_uint);
 #[repr(u32)]
 #[non_exhaustive]
 #[doc = "\n Represents the state of a setting.\n"]
@@ -1743,30 +1787,90 @@ pub enum cef_errorcode_t {
     ERR_DNS_NO_MATCHING_SUPPORTED_ALPN = -811,
     ERR_DNS_SECURE_PROBE_RECORD_INVALID = -814,
 }
-#[repr(u32)]
-#[non_exhaustive]
+impl cef_cert_status_t {
+    pub const CERT_STATUS_NONE: cef_cert_status_t = cef_cert_status_t(0);
+}
+impl cef_cert_status_t {
+    pub const CERT_STATUS_COMMON_NAME_INVALID: cef_cert_status_t = cef_cert_status_t(1);
+}
+impl cef_cert_status_t {
+    pub const CERT_STATUS_DATE_INVALID: cef_cert_status_t = cef_cert_status_t(2);
+}
+impl cef_cert_status_t {
+    pub const CERT_STATUS_AUTHORITY_INVALID: cef_cert_status_t = cef_cert_status_t(4);
+}
+impl cef_cert_status_t {
+    pub const CERT_STATUS_NO_REVOCATION_MECHANISM: cef_cert_status_t = cef_cert_status_t(16);
+}
+impl cef_cert_status_t {
+    pub const CERT_STATUS_UNABLE_TO_CHECK_REVOCATION: cef_cert_status_t = cef_cert_status_t(32);
+}
+impl cef_cert_status_t {
+    pub const CERT_STATUS_REVOKED: cef_cert_status_t = cef_cert_status_t(64);
+}
+impl cef_cert_status_t {
+    pub const CERT_STATUS_INVALID: cef_cert_status_t = cef_cert_status_t(128);
+}
+impl cef_cert_status_t {
+    pub const CERT_STATUS_WEAK_SIGNATURE_ALGORITHM: cef_cert_status_t = cef_cert_status_t(256);
+}
+impl cef_cert_status_t {
+    pub const CERT_STATUS_NON_UNIQUE_NAME: cef_cert_status_t = cef_cert_status_t(1024);
+}
+impl cef_cert_status_t {
+    pub const CERT_STATUS_WEAK_KEY: cef_cert_status_t = cef_cert_status_t(2048);
+}
+impl cef_cert_status_t {
+    pub const CERT_STATUS_PINNED_KEY_MISSING: cef_cert_status_t = cef_cert_status_t(8192);
+}
+impl cef_cert_status_t {
+    pub const CERT_STATUS_NAME_CONSTRAINT_VIOLATION: cef_cert_status_t = cef_cert_status_t(16384);
+}
+impl cef_cert_status_t {
+    pub const CERT_STATUS_VALIDITY_TOO_LONG: cef_cert_status_t = cef_cert_status_t(32768);
+}
+impl cef_cert_status_t {
+    pub const CERT_STATUS_IS_EV: cef_cert_status_t = cef_cert_status_t(65536);
+}
+impl cef_cert_status_t {
+    pub const CERT_STATUS_REV_CHECKING_ENABLED: cef_cert_status_t = cef_cert_status_t(131072);
+}
+impl cef_cert_status_t {
+    pub const CERT_STATUS_SHA1_SIGNATURE_PRESENT: cef_cert_status_t = cef_cert_status_t(524288);
+}
+impl cef_cert_status_t {
+    pub const CERT_STATUS_CT_COMPLIANCE_FAILED: cef_cert_status_t = cef_cert_status_t(1048576);
+}
+impl ::std::ops::BitOr<cef_cert_status_t> for cef_cert_status_t {
+    type Output = Self;
+    #[inline]
+    fn bitor(self, other: Self) -> Self {
+        cef_cert_status_t(self.0 | other.0)
+    }
+}
+impl ::std::ops::BitOrAssign for cef_cert_status_t {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: cef_cert_status_t) {
+        self.0 |= rhs.0;
+    }
+}
+impl ::std::ops::BitAnd<cef_cert_status_t> for cef_cert_status_t {
+    type Output = Self;
+    #[inline]
+    fn bitand(self, other: Self) -> Self {
+        cef_cert_status_t(self.0 & other.0)
+    }
+}
+impl ::std::ops::BitAndAssign for cef_cert_status_t {
+    #[inline]
+    fn bitand_assign(&mut self, rhs: cef_cert_status_t) {
+        self.0 &= rhs.0;
+    }
+}
+#[repr(transparent)]
 #[doc = "\n Supported certificate status code values. See net\\cert\\cert_status_flags.h\n for more information. CERT_STATUS_NONE is new in CEF because we use an\n enum while cert_status_flags.h uses a typedef and static const variables.\n"]
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
-pub enum cef_cert_status_t {
-    CERT_STATUS_NONE = 0,
-    CERT_STATUS_COMMON_NAME_INVALID = 1,
-    CERT_STATUS_DATE_INVALID = 2,
-    CERT_STATUS_AUTHORITY_INVALID = 4,
-    CERT_STATUS_NO_REVOCATION_MECHANISM = 16,
-    CERT_STATUS_UNABLE_TO_CHECK_REVOCATION = 32,
-    CERT_STATUS_REVOKED = 64,
-    CERT_STATUS_INVALID = 128,
-    CERT_STATUS_WEAK_SIGNATURE_ALGORITHM = 256,
-    CERT_STATUS_NON_UNIQUE_NAME = 1024,
-    CERT_STATUS_WEAK_KEY = 2048,
-    CERT_STATUS_PINNED_KEY_MISSING = 8192,
-    CERT_STATUS_NAME_CONSTRAINT_VIOLATION = 16384,
-    CERT_STATUS_VALIDITY_TOO_LONG = 32768,
-    CERT_STATUS_IS_EV = 65536,
-    CERT_STATUS_REV_CHECKING_ENABLED = 131072,
-    CERT_STATUS_SHA1_SIGNATURE_PRESENT = 524288,
-    CERT_STATUS_CT_COMPLIANCE_FAILED = 1048576,
-}
+pub struct cef_cert_status_t(pub ::std::os::raw::c_uint);
 impl cef_resultcode_t {
     pub const CEF_RESULT_CODE_SANDBOX_FATAL_INTEGRITY: cef_resultcode_t =
         cef_resultcode_t::CEF_RESULT_CODE_SANDBOX_FATAL_FIRST;
@@ -1937,20 +2041,56 @@ pub enum cef_text_input_mode_t {
     CEF_TEXT_INPUT_MODE_DECIMAL = 7,
     CEF_TEXT_INPUT_MODE_SEARCH = 8,
 }
-#[repr(u32)]
-#[non_exhaustive]
-#[doc = "\n V8 property attribute values.\n"]
-#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
-pub enum cef_v8_propertyattribute_t {
+impl cef_v8_propertyattribute_t {
     #[doc = "\n Writeable, Enumerable, Configurable\n"]
-    V8_PROPERTY_ATTRIBUTE_NONE = 0,
+    pub const V8_PROPERTY_ATTRIBUTE_NONE: cef_v8_propertyattribute_t =
+        cef_v8_propertyattribute_t(0);
+}
+impl cef_v8_propertyattribute_t {
     #[doc = "\n Not writeable\n"]
-    V8_PROPERTY_ATTRIBUTE_READONLY = 1,
+    pub const V8_PROPERTY_ATTRIBUTE_READONLY: cef_v8_propertyattribute_t =
+        cef_v8_propertyattribute_t(1);
+}
+impl cef_v8_propertyattribute_t {
     #[doc = "\n Not enumerable\n"]
-    V8_PROPERTY_ATTRIBUTE_DONTENUM = 2,
+    pub const V8_PROPERTY_ATTRIBUTE_DONTENUM: cef_v8_propertyattribute_t =
+        cef_v8_propertyattribute_t(2);
+}
+impl cef_v8_propertyattribute_t {
     #[doc = "\n Not configurable\n"]
-    V8_PROPERTY_ATTRIBUTE_DONTDELETE = 4,
+    pub const V8_PROPERTY_ATTRIBUTE_DONTDELETE: cef_v8_propertyattribute_t =
+        cef_v8_propertyattribute_t(4);
+}
+impl ::std::ops::BitOr<cef_v8_propertyattribute_t> for cef_v8_propertyattribute_t {
+    type Output = Self;
+    #[inline]
+    fn bitor(self, other: Self) -> Self {
+        cef_v8_propertyattribute_t(self.0 | other.0)
+    }
 }
+impl ::std::ops::BitOrAssign for cef_v8_propertyattribute_t {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: cef_v8_propertyattribute_t) {
+        self.0 |= rhs.0;
+    }
+}
+impl ::std::ops::BitAnd<cef_v8_propertyattribute_t> for cef_v8_propertyattribute_t {
+    type Output = Self;
+    #[inline]
+    fn bitand(self, other: Self) -> Self {
+        cef_v8_propertyattribute_t(self.0 & other.0)
+    }
+}
+impl ::std::ops::BitAndAssign for cef_v8_propertyattribute_t {
+    #[inline]
+    fn bitand_assign(&mut self, rhs: cef_v8_propertyattribute_t) {
+        self.0 &= rhs.0;
+    }
+}
+#[repr(transparent)]
+#[doc = "\n V8 property attribute values.\n"]
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct cef_v8_propertyattribute_t(pub ::std::os::raw::c_uint);
 #[repr(u32)]
 #[non_exhaustive]
 #[doc = "\n Post data elements may represent either bytes or files.\n"]
@@ -2006,82 +2146,194 @@ pub enum cef_resource_type_t {
     #[doc = "\n A sub-frame service worker navigation preload request.\n"]
     RT_NAVIGATION_PRELOAD_SUB_FRAME = 20,
 }
-#[repr(u32)]
-#[non_exhaustive]
-#[doc = "\n Transition type for a request. Made up of one source value and 0 or more\n qualifiers.\n"]
-#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
-pub enum cef_transition_type_t {
+impl cef_transition_type_t {
     #[doc = "\n Source is a link click or the JavaScript window.open function. This is\n also the default value for requests like sub-resource loads that are not\n navigations.\n"]
-    TT_LINK = 0,
+    pub const TT_LINK: cef_transition_type_t = cef_transition_type_t(0);
+}
+impl cef_transition_type_t {
     #[doc = "\n Source is some other \"explicit\" navigation. This is the default value for\n navigations where the actual type is unknown. See also\n TT_DIRECT_LOAD_FLAG.\n"]
-    TT_EXPLICIT = 1,
+    pub const TT_EXPLICIT: cef_transition_type_t = cef_transition_type_t(1);
+}
+impl cef_transition_type_t {
     #[doc = "\n User got to this page through a suggestion in the UI (for example, via the\n destinations page). Chrome style only.\n"]
-    TT_AUTO_BOOKMARK = 2,
+    pub const TT_AUTO_BOOKMARK: cef_transition_type_t = cef_transition_type_t(2);
+}
+impl cef_transition_type_t {
     #[doc = "\n Source is a subframe navigation. This is any content that is automatically\n loaded in a non-toplevel frame. For example, if a page consists of several\n frames containing ads, those ad URLs will have this transition type.\n The user may not even realize the content in these pages is a separate\n frame, so may not care about the URL.\n"]
-    TT_AUTO_SUBFRAME = 3,
+    pub const TT_AUTO_SUBFRAME: cef_transition_type_t = cef_transition_type_t(3);
+}
+impl cef_transition_type_t {
     #[doc = "\n Source is a subframe navigation explicitly requested by the user that will\n generate new navigation entries in the back/forward list. These are\n probably more important than frames that were automatically loaded in\n the background because the user probably cares about the fact that this\n link was loaded.\n"]
-    TT_MANUAL_SUBFRAME = 4,
+    pub const TT_MANUAL_SUBFRAME: cef_transition_type_t = cef_transition_type_t(4);
+}
+impl cef_transition_type_t {
     #[doc = "\n User got to this page by typing in the URL bar and selecting an entry\n that did not look like a URL.  For example, a match might have the URL\n of a Google search result page, but appear like \"Search Google for ...\".\n These are not quite the same as EXPLICIT navigations because the user\n didn't type or see the destination URL. Chrome style only.\n See also TT_KEYWORD.\n"]
-    TT_GENERATED = 5,
+    pub const TT_GENERATED: cef_transition_type_t = cef_transition_type_t(5);
+}
+impl cef_transition_type_t {
     #[doc = "\n This is a toplevel navigation. This is any content that is automatically\n loaded in a toplevel frame.  For example, opening a tab to show the ASH\n screen saver, opening the devtools window, opening the NTP after the safe\n browsing warning, opening web-based dialog boxes are examples of\n AUTO_TOPLEVEL navigations. Chrome style only.\n"]
-    TT_AUTO_TOPLEVEL = 6,
+    pub const TT_AUTO_TOPLEVEL: cef_transition_type_t = cef_transition_type_t(6);
+}
+impl cef_transition_type_t {
     #[doc = "\n Source is a form submission by the user. NOTE: In some situations\n submitting a form does not result in this transition type. This can happen\n if the form uses a script to submit the contents.\n"]
-    TT_FORM_SUBMIT = 7,
+    pub const TT_FORM_SUBMIT: cef_transition_type_t = cef_transition_type_t(7);
+}
+impl cef_transition_type_t {
     #[doc = "\n Source is a \"reload\" of the page via the Reload function or by re-visiting\n the same URL. NOTE: This is distinct from the concept of whether a\n particular load uses \"reload semantics\" (i.e. bypasses cached data).\n"]
-    TT_RELOAD = 8,
+    pub const TT_RELOAD: cef_transition_type_t = cef_transition_type_t(8);
+}
+impl cef_transition_type_t {
     #[doc = "\n The url was generated from a replaceable keyword other than the default\n search provider. If the user types a keyword (which also applies to\n tab-to-search) in the omnibox this qualifier is applied to the transition\n type of the generated url. TemplateURLModel then may generate an\n additional visit with a transition type of TT_KEYWORD_GENERATED against\n the url 'http://' + keyword. For example, if you do a tab-to-search\n against wikipedia the generated url has a transition qualifer of\n TT_KEYWORD, and TemplateURLModel generates a visit for 'wikipedia.org'\n with a transition type of TT_KEYWORD_GENERATED. Chrome style only.\n"]
-    TT_KEYWORD = 9,
+    pub const TT_KEYWORD: cef_transition_type_t = cef_transition_type_t(9);
+}
+impl cef_transition_type_t {
     #[doc = "\n Corresponds to a visit generated for a keyword. See description of\n TT_KEYWORD for more details. Chrome style only.\n"]
-    TT_KEYWORD_GENERATED = 10,
+    pub const TT_KEYWORD_GENERATED: cef_transition_type_t = cef_transition_type_t(10);
+}
+impl cef_transition_type_t {
     #[doc = "\n General mask defining the bits used for the source values.\n"]
-    TT_SOURCE_MASK = 255,
+    pub const TT_SOURCE_MASK: cef_transition_type_t = cef_transition_type_t(255);
+}
+impl cef_transition_type_t {
     #[doc = "\n Attempted to visit a URL but was blocked.\n"]
-    TT_BLOCKED_FLAG = 8388608,
+    pub const TT_BLOCKED_FLAG: cef_transition_type_t = cef_transition_type_t(8388608);
+}
+impl cef_transition_type_t {
     #[doc = "\n Used the Forward or Back function to navigate among browsing history.\n Will be ORed to the transition type for the original load.\n"]
-    TT_FORWARD_BACK_FLAG = 16777216,
+    pub const TT_FORWARD_BACK_FLAG: cef_transition_type_t = cef_transition_type_t(16777216);
+}
+impl cef_transition_type_t {
     #[doc = "\n Loaded a URL directly via CreateBrowser, LoadURL or LoadRequest.\n"]
-    TT_DIRECT_LOAD_FLAG = 33554432,
+    pub const TT_DIRECT_LOAD_FLAG: cef_transition_type_t = cef_transition_type_t(33554432);
+}
+impl cef_transition_type_t {
     #[doc = "\n User is navigating to the home page. Chrome style only.\n"]
-    TT_HOME_PAGE_FLAG = 67108864,
+    pub const TT_HOME_PAGE_FLAG: cef_transition_type_t = cef_transition_type_t(67108864);
+}
+impl cef_transition_type_t {
     #[doc = "\n The transition originated from an external application; the exact\n definition of this is embedder dependent. Chrome style only.\n"]
-    TT_FROM_API_FLAG = 134217728,
+    pub const TT_FROM_API_FLAG: cef_transition_type_t = cef_transition_type_t(134217728);
+}
+impl cef_transition_type_t {
     #[doc = "\n The beginning of a navigation chain.\n"]
-    TT_CHAIN_START_FLAG = 268435456,
+    pub const TT_CHAIN_START_FLAG: cef_transition_type_t = cef_transition_type_t(268435456);
+}
+impl cef_transition_type_t {
     #[doc = "\n The last transition in a redirect chain.\n"]
-    TT_CHAIN_END_FLAG = 536870912,
+    pub const TT_CHAIN_END_FLAG: cef_transition_type_t = cef_transition_type_t(536870912);
+}
+impl cef_transition_type_t {
     #[doc = "\n Redirects caused by JavaScript or a meta refresh tag on the page.\n"]
-    TT_CLIENT_REDIRECT_FLAG = 1073741824,
+    pub const TT_CLIENT_REDIRECT_FLAG: cef_transition_type_t = cef_transition_type_t(1073741824);
+}
+impl cef_transition_type_t {
     #[doc = "\n Redirects sent from the server by HTTP headers.\n"]
-    TT_SERVER_REDIRECT_FLAG = 2147483648,
+    pub const TT_SERVER_REDIRECT_FLAG: cef_transition_type_t = cef_transition_type_t(2147483648);
+}
+impl cef_transition_type_t {
     #[doc = "\n Used to test whether a transition involves a redirect.\n"]
-    TT_IS_REDIRECT_MASK = 3221225472,
+    pub const TT_IS_REDIRECT_MASK: cef_transition_type_t = cef_transition_type_t(3221225472);
+}
+impl cef_transition_type_t {
     #[doc = "\n General mask defining the bits used for the qualifiers.\n"]
-    TT_QUALIFIER_MASK = 4294967040,
+    pub const TT_QUALIFIER_MASK: cef_transition_type_t = cef_transition_type_t(4294967040);
 }
-#[repr(u32)]
-#[non_exhaustive]
-#[doc = "\n Flags used to customize the behavior of CefURLRequest.\n"]
+impl ::std::ops::BitOr<cef_transition_type_t> for cef_transition_type_t {
+    type Output = Self;
+    #[inline]
+    fn bitor(self, other: Self) -> Self {
+        cef_transition_type_t(self.0 | other.0)
+    }
+}
+impl ::std::ops::BitOrAssign for cef_transition_type_t {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: cef_transition_type_t) {
+        self.0 |= rhs.0;
+    }
+}
+impl ::std::ops::BitAnd<cef_transition_type_t> for cef_transition_type_t {
+    type Output = Self;
+    #[inline]
+    fn bitand(self, other: Self) -> Self {
+        cef_transition_type_t(self.0 & other.0)
+    }
+}
+impl ::std::ops::BitAndAssign for cef_transition_type_t {
+    #[inline]
+    fn bitand_assign(&mut self, rhs: cef_transition_type_t) {
+        self.0 &= rhs.0;
+    }
+}
+#[repr(transparent)]
+#[doc = "\n Transition type for a request. Made up of one source value and 0 or more\n qualifiers.\n"]
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
-pub enum cef_urlrequest_flags_t {
+pub struct cef_transition_type_t(pub ::std::os::raw::c_uint);
+impl cef_urlrequest_flags_t {
     #[doc = "\n Default behavior.\n"]
-    UR_FLAG_NONE = 0,
+    pub const UR_FLAG_NONE: cef_urlrequest_flags_t = cef_urlrequest_flags_t(0);
+}
+impl cef_urlrequest_flags_t {
     #[doc = "\n If set the cache will be skipped when handling the request. Setting this\n value is equivalent to specifying the \"Cache-Control: no-cache\" request\n header. Setting this value in combination with UR_FLAG_ONLY_FROM_CACHE\n will cause the request to fail.\n"]
-    UR_FLAG_SKIP_CACHE = 1,
+    pub const UR_FLAG_SKIP_CACHE: cef_urlrequest_flags_t = cef_urlrequest_flags_t(1);
+}
+impl cef_urlrequest_flags_t {
     #[doc = "\n If set the request will fail if it cannot be served from the cache (or\n some equivalent local store). Setting this value is equivalent to\n specifying the \"Cache-Control: only-if-cached\" request header. Setting\n this value in combination with UR_FLAG_SKIP_CACHE or UR_FLAG_DISABLE_CACHE\n will cause the request to fail.\n"]
-    UR_FLAG_ONLY_FROM_CACHE = 2,
+    pub const UR_FLAG_ONLY_FROM_CACHE: cef_urlrequest_flags_t = cef_urlrequest_flags_t(2);
+}
+impl cef_urlrequest_flags_t {
     #[doc = "\n If set the cache will not be used at all. Setting this value is equivalent\n to specifying the \"Cache-Control: no-store\" request header. Setting this\n value in combination with UR_FLAG_ONLY_FROM_CACHE will cause the request\n to fail.\n"]
-    UR_FLAG_DISABLE_CACHE = 4,
+    pub const UR_FLAG_DISABLE_CACHE: cef_urlrequest_flags_t = cef_urlrequest_flags_t(4);
+}
+impl cef_urlrequest_flags_t {
     #[doc = "\n If set user name, password, and cookies may be sent with the request, and\n cookies may be saved from the response.\n"]
-    UR_FLAG_ALLOW_STORED_CREDENTIALS = 8,
+    pub const UR_FLAG_ALLOW_STORED_CREDENTIALS: cef_urlrequest_flags_t = cef_urlrequest_flags_t(8);
+}
+impl cef_urlrequest_flags_t {
     #[doc = "\n If set upload progress events will be generated when a request has a body.\n"]
-    UR_FLAG_REPORT_UPLOAD_PROGRESS = 16,
+    pub const UR_FLAG_REPORT_UPLOAD_PROGRESS: cef_urlrequest_flags_t = cef_urlrequest_flags_t(16);
+}
+impl cef_urlrequest_flags_t {
     #[doc = "\n If set the CefURLRequestClient::OnDownloadData method will not be called.\n"]
-    UR_FLAG_NO_DOWNLOAD_DATA = 32,
+    pub const UR_FLAG_NO_DOWNLOAD_DATA: cef_urlrequest_flags_t = cef_urlrequest_flags_t(32);
+}
+impl cef_urlrequest_flags_t {
     #[doc = "\n If set 5XX redirect errors will be propagated to the observer instead of\n automatically re-tried. This currently only applies for requests\n originated in the browser process.\n"]
-    UR_FLAG_NO_RETRY_ON_5XX = 64,
+    pub const UR_FLAG_NO_RETRY_ON_5XX: cef_urlrequest_flags_t = cef_urlrequest_flags_t(64);
+}
+impl cef_urlrequest_flags_t {
     #[doc = "\n If set 3XX responses will cause the fetch to halt immediately rather than\n continue through the redirect.\n"]
-    UR_FLAG_STOP_ON_REDIRECT = 128,
+    pub const UR_FLAG_STOP_ON_REDIRECT: cef_urlrequest_flags_t = cef_urlrequest_flags_t(128);
+}
+impl ::std::ops::BitOr<cef_urlrequest_flags_t> for cef_urlrequest_flags_t {
+    type Output = Self;
+    #[inline]
+    fn bitor(self, other: Self) -> Self {
+        cef_urlrequest_flags_t(self.0 | other.0)
+    }
+}
+impl ::std::ops::BitOrAssign for cef_urlrequest_flags_t {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: cef_urlrequest_flags_t) {
+        self.0 |= rhs.0;
+    }
+}
+impl ::std::ops::BitAnd<cef_urlrequest_flags_t> for cef_urlrequest_flags_t {
+    type Output = Self;
+    #[inline]
+    fn bitand(self, other: Self) -> Self {
+        cef_urlrequest_flags_t(self.0 & other.0)
+    }
+}
+impl ::std::ops::BitAndAssign for cef_urlrequest_flags_t {
+    #[inline]
+    fn bitand_assign(&mut self, rhs: cef_urlrequest_flags_t) {
+        self.0 &= rhs.0;
+    }
 }
+#[repr(transparent)]
+#[doc = "\n Flags used to customize the behavior of CefURLRequest.\n"]
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct cef_urlrequest_flags_t(pub ::std::os::raw::c_uint);
 #[repr(u32)]
 #[non_exhaustive]
 #[doc = "\n Flags that represent CefURLRequest status.\n"]
@@ -2428,34 +2680,88 @@ pub enum cef_paint_element_type_t {
     PET_VIEW = 0,
     PET_POPUP = 1,
 }
-#[repr(u32)]
-#[non_exhaustive]
-#[doc = "\n Supported event bit flags.\n"]
-#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
-pub enum cef_event_flags_t {
-    EVENTFLAG_NONE = 0,
-    EVENTFLAG_CAPS_LOCK_ON = 1,
-    EVENTFLAG_SHIFT_DOWN = 2,
-    EVENTFLAG_CONTROL_DOWN = 4,
-    EVENTFLAG_ALT_DOWN = 8,
-    EVENTFLAG_LEFT_MOUSE_BUTTON = 16,
-    EVENTFLAG_MIDDLE_MOUSE_BUTTON = 32,
-    EVENTFLAG_RIGHT_MOUSE_BUTTON = 64,
+impl cef_event_flags_t {
+    pub const EVENTFLAG_NONE: cef_event_flags_t = cef_event_flags_t(0);
+}
+impl cef_event_flags_t {
+    pub const EVENTFLAG_CAPS_LOCK_ON: cef_event_flags_t = cef_event_flags_t(1);
+}
+impl cef_event_flags_t {
+    pub const EVENTFLAG_SHIFT_DOWN: cef_event_flags_t = cef_event_flags_t(2);
+}
+impl cef_event_flags_t {
+    pub const EVENTFLAG_CONTROL_DOWN: cef_event_flags_t = cef_event_flags_t(4);
+}
+impl cef_event_flags_t {
+    pub const EVENTFLAG_ALT_DOWN: cef_event_flags_t = cef_event_flags_t(8);
+}
+impl cef_event_flags_t {
+    pub const EVENTFLAG_LEFT_MOUSE_BUTTON: cef_event_flags_t = cef_event_flags_t(16);
+}
+impl cef_event_flags_t {
+    pub const EVENTFLAG_MIDDLE_MOUSE_BUTTON: cef_event_flags_t = cef_event_flags_t(32);
+}
+impl cef_event_flags_t {
+    pub const EVENTFLAG_RIGHT_MOUSE_BUTTON: cef_event_flags_t = cef_event_flags_t(64);
+}
+impl cef_event_flags_t {
     #[doc = " Mac OS-X command key."]
-    EVENTFLAG_COMMAND_DOWN = 128,
+    pub const EVENTFLAG_COMMAND_DOWN: cef_event_flags_t = cef_event_flags_t(128);
+}
+impl cef_event_flags_t {
     #[doc = " Mac OS-X command key."]
-    EVENTFLAG_NUM_LOCK_ON = 256,
+    pub const EVENTFLAG_NUM_LOCK_ON: cef_event_flags_t = cef_event_flags_t(256);
+}
+impl cef_event_flags_t {
     #[doc = " Mac OS-X command key."]
-    EVENTFLAG_IS_KEY_PAD = 512,
+    pub const EVENTFLAG_IS_KEY_PAD: cef_event_flags_t = cef_event_flags_t(512);
+}
+impl cef_event_flags_t {
     #[doc = " Mac OS-X command key."]
-    EVENTFLAG_IS_LEFT = 1024,
+    pub const EVENTFLAG_IS_LEFT: cef_event_flags_t = cef_event_flags_t(1024);
+}
+impl cef_event_flags_t {
     #[doc = " Mac OS-X command key."]
-    EVENTFLAG_IS_RIGHT = 2048,
+    pub const EVENTFLAG_IS_RIGHT: cef_event_flags_t = cef_event_flags_t(2048);
+}
+impl cef_event_flags_t {
     #[doc = " Mac OS-X command key."]
-    EVENTFLAG_ALTGR_DOWN = 4096,
+    pub const EVENTFLAG_ALTGR_DOWN: cef_event_flags_t = cef_event_flags_t(4096);
+}
+impl cef_event_flags_t {
     #[doc = " Mac OS-X command key."]
-    EVENTFLAG_IS_REPEAT = 8192,
+    pub const EVENTFLAG_IS_REPEAT: cef_event_flags_t = cef_event_flags_t(8192);
 }
+impl ::std::ops::BitOr<cef_event_flags_t> for cef_event_flags_t {
+    type Output = Self;
+    #[inline]
+    fn bitor(self, other: Self) -> Self {
+        cef_event_flags_t(self.0 | other.0)
+    }
+}
+impl ::std::ops::BitOrAssign for cef_event_flags_t {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: cef_event_flags_t) {
+        self.0 |= rhs.0;
+    }
+}
+impl ::std::ops::BitAnd<cef_event_flags_t> for cef_event_flags_t {
+    type Output = Self;
+    #[inline]
+    fn bitand(self, other: Self) -> Self {
+        cef_event_flags_t(self.0 & other.0)
+    }
+}
+impl ::std::ops::BitAndAssign for cef_event_flags_t {
+    #[inline]
+    fn bitand_assign(&mut self, rhs: cef_event_flags_t) {
+        self.0 &= rhs.0;
+    }
+}
+#[repr(transparent)]
+#[doc = "\n Supported event bit flags.\n"]
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct cef_event_flags_t(pub ::std::os::raw::c_uint);
 #[repr(u32)]
 #[non_exhaustive]
 #[doc = "\n Supported menu item types.\n"]
@@ -2468,26 +2774,66 @@ pub enum cef_menu_item_type_t {
     MENUITEMTYPE_SEPARATOR = 4,
     MENUITEMTYPE_SUBMENU = 5,
 }
-#[repr(u32)]
-#[non_exhaustive]
-#[doc = "\n Supported context menu type flags.\n"]
-#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
-pub enum cef_context_menu_type_flags_t {
+impl cef_context_menu_type_flags_t {
     #[doc = "\n No node is selected.\n"]
-    CM_TYPEFLAG_NONE = 0,
+    pub const CM_TYPEFLAG_NONE: cef_context_menu_type_flags_t = cef_context_menu_type_flags_t(0);
+}
+impl cef_context_menu_type_flags_t {
     #[doc = "\n The top page is selected.\n"]
-    CM_TYPEFLAG_PAGE = 1,
+    pub const CM_TYPEFLAG_PAGE: cef_context_menu_type_flags_t = cef_context_menu_type_flags_t(1);
+}
+impl cef_context_menu_type_flags_t {
     #[doc = "\n A subframe page is selected.\n"]
-    CM_TYPEFLAG_FRAME = 2,
+    pub const CM_TYPEFLAG_FRAME: cef_context_menu_type_flags_t = cef_context_menu_type_flags_t(2);
+}
+impl cef_context_menu_type_flags_t {
     #[doc = "\n A link is selected.\n"]
-    CM_TYPEFLAG_LINK = 4,
+    pub const CM_TYPEFLAG_LINK: cef_context_menu_type_flags_t = cef_context_menu_type_flags_t(4);
+}
+impl cef_context_menu_type_flags_t {
     #[doc = "\n A media node is selected.\n"]
-    CM_TYPEFLAG_MEDIA = 8,
+    pub const CM_TYPEFLAG_MEDIA: cef_context_menu_type_flags_t = cef_context_menu_type_flags_t(8);
+}
+impl cef_context_menu_type_flags_t {
     #[doc = "\n There is a textual or mixed selection that is selected.\n"]
-    CM_TYPEFLAG_SELECTION = 16,
+    pub const CM_TYPEFLAG_SELECTION: cef_context_menu_type_flags_t =
+        cef_context_menu_type_flags_t(16);
+}
+impl cef_context_menu_type_flags_t {
     #[doc = "\n An editable element is selected.\n"]
-    CM_TYPEFLAG_EDITABLE = 32,
+    pub const CM_TYPEFLAG_EDITABLE: cef_context_menu_type_flags_t =
+        cef_context_menu_type_flags_t(32);
+}
+impl ::std::ops::BitOr<cef_context_menu_type_flags_t> for cef_context_menu_type_flags_t {
+    type Output = Self;
+    #[inline]
+    fn bitor(self, other: Self) -> Self {
+        cef_context_menu_type_flags_t(self.0 | other.0)
+    }
+}
+impl ::std::ops::BitOrAssign for cef_context_menu_type_flags_t {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: cef_context_menu_type_flags_t) {
+        self.0 |= rhs.0;
+    }
 }
+impl ::std::ops::BitAnd<cef_context_menu_type_flags_t> for cef_context_menu_type_flags_t {
+    type Output = Self;
+    #[inline]
+    fn bitand(self, other: Self) -> Self {
+        cef_context_menu_type_flags_t(self.0 & other.0)
+    }
+}
+impl ::std::ops::BitAndAssign for cef_context_menu_type_flags_t {
+    #[inline]
+    fn bitand_assign(&mut self, rhs: cef_context_menu_type_flags_t) {
+        self.0 &= rhs.0;
+    }
+}
+#[repr(transparent)]
+#[doc = "\n Supported context menu type flags.\n"]
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct cef_context_menu_type_flags_t(pub ::std::os::raw::c_uint);
 #[repr(u32)]
 #[non_exhaustive]
 #[doc = "\n Supported context menu media types. These constants match their equivalents\n in Chromium's ContextMenuDataMediaType and should not be renumbered.\n"]
@@ -2508,53 +2854,220 @@ pub enum cef_context_menu_media_type_t {
     #[doc = "\n A plugin node is selected.\n"]
     CM_MEDIATYPE_PLUGIN = 6,
 }
-#[repr(u32)]
-#[non_exhaustive]
+impl cef_context_menu_media_state_flags_t {
+    pub const CM_MEDIAFLAG_NONE: cef_context_menu_media_state_flags_t =
+        cef_context_menu_media_state_flags_t(0);
+}
+impl cef_context_menu_media_state_flags_t {
+    pub const CM_MEDIAFLAG_IN_ERROR: cef_context_menu_media_state_flags_t =
+        cef_context_menu_media_state_flags_t(1);
+}
+impl cef_context_menu_media_state_flags_t {
+    pub const CM_MEDIAFLAG_PAUSED: cef_context_menu_media_state_flags_t =
+        cef_context_menu_media_state_flags_t(2);
+}
+impl cef_context_menu_media_state_flags_t {
+    pub const CM_MEDIAFLAG_MUTED: cef_context_menu_media_state_flags_t =
+        cef_context_menu_media_state_flags_t(4);
+}
+impl cef_context_menu_media_state_flags_t {
+    pub const CM_MEDIAFLAG_LOOP: cef_context_menu_media_state_flags_t =
+        cef_context_menu_media_state_flags_t(8);
+}
+impl cef_context_menu_media_state_flags_t {
+    pub const CM_MEDIAFLAG_CAN_SAVE: cef_context_menu_media_state_flags_t =
+        cef_context_menu_media_state_flags_t(16);
+}
+impl cef_context_menu_media_state_flags_t {
+    pub const CM_MEDIAFLAG_HAS_AUDIO: cef_context_menu_media_state_flags_t =
+        cef_context_menu_media_state_flags_t(32);
+}
+impl cef_context_menu_media_state_flags_t {
+    pub const CM_MEDIAFLAG_CAN_TOGGLE_CONTROLS: cef_context_menu_media_state_flags_t =
+        cef_context_menu_media_state_flags_t(64);
+}
+impl cef_context_menu_media_state_flags_t {
+    pub const CM_MEDIAFLAG_CONTROLS: cef_context_menu_media_state_flags_t =
+        cef_context_menu_media_state_flags_t(128);
+}
+impl cef_context_menu_media_state_flags_t {
+    pub const CM_MEDIAFLAG_CAN_PRINT: cef_context_menu_media_state_flags_t =
+        cef_context_menu_media_state_flags_t(256);
+}
+impl cef_context_menu_media_state_flags_t {
+    pub const CM_MEDIAFLAG_CAN_ROTATE: cef_context_menu_media_state_flags_t =
+        cef_context_menu_media_state_flags_t(512);
+}
+impl cef_context_menu_media_state_flags_t {
+    pub const CM_MEDIAFLAG_CAN_PICTURE_IN_PICTURE: cef_context_menu_media_state_flags_t =
+        cef_context_menu_media_state_flags_t(1024);
+}
+impl cef_context_menu_media_state_flags_t {
+    pub const CM_MEDIAFLAG_PICTURE_IN_PICTURE: cef_context_menu_media_state_flags_t =
+        cef_context_menu_media_state_flags_t(2048);
+}
+impl cef_context_menu_media_state_flags_t {
+    pub const CM_MEDIAFLAG_CAN_LOOP: cef_context_menu_media_state_flags_t =
+        cef_context_menu_media_state_flags_t(4096);
+}
+impl ::std::ops::BitOr<cef_context_menu_media_state_flags_t>
+    for cef_context_menu_media_state_flags_t
+{
+    type Output = Self;
+    #[inline]
+    fn bitor(self, other: Self) -> Self {
+        cef_context_menu_media_state_flags_t(self.0 | other.0)
+    }
+}
+impl ::std::ops::BitOrAssign for cef_context_menu_media_state_flags_t {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: cef_context_menu_media_state_flags_t) {
+        self.0 |= rhs.0;
+    }
+}
+impl ::std::ops::BitAnd<cef_context_menu_media_state_flags_t>
+    for cef_context_menu_media_state_flags_t
+{
+    type Output = Self;
+    #[inline]
+    fn bitand(self, other: Self) -> Self {
+        cef_context_menu_media_state_flags_t(self.0 & other.0)
+    }
+}
+impl ::std::ops::BitAndAssign for cef_context_menu_media_state_flags_t {
+    #[inline]
+    fn bitand_assign(&mut self, rhs: cef_context_menu_media_state_flags_t) {
+        self.0 &= rhs.0;
+    }
+}
+#[repr(transparent)]
 #[doc = "\n Supported context menu media state bit flags. These constants match their\n equivalents in Chromium's ContextMenuData::MediaFlags and should not be\n renumbered.\n"]
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
-pub enum cef_context_menu_media_state_flags_t {
-    CM_MEDIAFLAG_NONE = 0,
-    CM_MEDIAFLAG_IN_ERROR = 1,
-    CM_MEDIAFLAG_PAUSED = 2,
-    CM_MEDIAFLAG_MUTED = 4,
-    CM_MEDIAFLAG_LOOP = 8,
-    CM_MEDIAFLAG_CAN_SAVE = 16,
-    CM_MEDIAFLAG_HAS_AUDIO = 32,
-    CM_MEDIAFLAG_CAN_TOGGLE_CONTROLS = 64,
-    CM_MEDIAFLAG_CONTROLS = 128,
-    CM_MEDIAFLAG_CAN_PRINT = 256,
-    CM_MEDIAFLAG_CAN_ROTATE = 512,
-    CM_MEDIAFLAG_CAN_PICTURE_IN_PICTURE = 1024,
-    CM_MEDIAFLAG_PICTURE_IN_PICTURE = 2048,
-    CM_MEDIAFLAG_CAN_LOOP = 4096,
+pub struct cef_context_menu_media_state_flags_t(pub ::std::os::raw::c_uint);
+impl cef_context_menu_edit_state_flags_t {
+    pub const CM_EDITFLAG_NONE: cef_context_menu_edit_state_flags_t =
+        cef_context_menu_edit_state_flags_t(0);
+}
+impl cef_context_menu_edit_state_flags_t {
+    pub const CM_EDITFLAG_CAN_UNDO: cef_context_menu_edit_state_flags_t =
+        cef_context_menu_edit_state_flags_t(1);
+}
+impl cef_context_menu_edit_state_flags_t {
+    pub const CM_EDITFLAG_CAN_REDO: cef_context_menu_edit_state_flags_t =
+        cef_context_menu_edit_state_flags_t(2);
+}
+impl cef_context_menu_edit_state_flags_t {
+    pub const CM_EDITFLAG_CAN_CUT: cef_context_menu_edit_state_flags_t =
+        cef_context_menu_edit_state_flags_t(4);
+}
+impl cef_context_menu_edit_state_flags_t {
+    pub const CM_EDITFLAG_CAN_COPY: cef_context_menu_edit_state_flags_t =
+        cef_context_menu_edit_state_flags_t(8);
+}
+impl cef_context_menu_edit_state_flags_t {
+    pub const CM_EDITFLAG_CAN_PASTE: cef_context_menu_edit_state_flags_t =
+        cef_context_menu_edit_state_flags_t(16);
+}
+impl cef_context_menu_edit_state_flags_t {
+    pub const CM_EDITFLAG_CAN_DELETE: cef_context_menu_edit_state_flags_t =
+        cef_context_menu_edit_state_flags_t(32);
+}
+impl cef_context_menu_edit_state_flags_t {
+    pub const CM_EDITFLAG_CAN_SELECT_ALL: cef_context_menu_edit_state_flags_t =
+        cef_context_menu_edit_state_flags_t(64);
+}
+impl cef_context_menu_edit_state_flags_t {
+    pub const CM_EDITFLAG_CAN_TRANSLATE: cef_context_menu_edit_state_flags_t =
+        cef_context_menu_edit_state_flags_t(128);
+}
+impl cef_context_menu_edit_state_flags_t {
+    pub const CM_EDITFLAG_CAN_EDIT_RICHLY: cef_context_menu_edit_state_flags_t =
+        cef_context_menu_edit_state_flags_t(256);
+}
+impl ::std::ops::BitOr<cef_context_menu_edit_state_flags_t>
+    for cef_context_menu_edit_state_flags_t
+{
+    type Output = Self;
+    #[inline]
+    fn bitor(self, other: Self) -> Self {
+        cef_context_menu_edit_state_flags_t(self.0 | other.0)
+    }
 }
-#[repr(u32)]
-#[non_exhaustive]
+impl ::std::ops::BitOrAssign for cef_context_menu_edit_state_flags_t {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: cef_context_menu_edit_state_flags_t) {
+        self.0 |= rhs.0;
+    }
+}
+impl ::std::ops::BitAnd<cef_context_menu_edit_state_flags_t>
+    for cef_context_menu_edit_state_flags_t
+{
+    type Output = Self;
+    #[inline]
+    fn bitand(self, other: Self) -> Self {
+        cef_context_menu_edit_state_flags_t(self.0 & other.0)
+    }
+}
+impl ::std::ops::BitAndAssign for cef_context_menu_edit_state_flags_t {
+    #[inline]
+    fn bitand_assign(&mut self, rhs: cef_context_menu_edit_state_flags_t) {
+        self.0 &= rhs.0;
+    }
+}
+#[repr(transparent)]
 #[doc = "\n Supported context menu edit state bit flags. These constants match their\n equivalents in Chromium's ContextMenuDataEditFlags and should not be\n renumbered.\n"]
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
-pub enum cef_context_menu_edit_state_flags_t {
-    CM_EDITFLAG_NONE = 0,
-    CM_EDITFLAG_CAN_UNDO = 1,
-    CM_EDITFLAG_CAN_REDO = 2,
-    CM_EDITFLAG_CAN_CUT = 4,
-    CM_EDITFLAG_CAN_COPY = 8,
-    CM_EDITFLAG_CAN_PASTE = 16,
-    CM_EDITFLAG_CAN_DELETE = 32,
-    CM_EDITFLAG_CAN_SELECT_ALL = 64,
-    CM_EDITFLAG_CAN_TRANSLATE = 128,
-    CM_EDITFLAG_CAN_EDIT_RICHLY = 256,
+pub struct cef_context_menu_edit_state_flags_t(pub ::std::os::raw::c_uint);
+impl cef_quick_menu_edit_state_flags_t {
+    pub const QM_EDITFLAG_NONE: cef_quick_menu_edit_state_flags_t =
+        cef_quick_menu_edit_state_flags_t(0);
 }
-#[repr(u32)]
-#[non_exhaustive]
+impl cef_quick_menu_edit_state_flags_t {
+    pub const QM_EDITFLAG_CAN_ELLIPSIS: cef_quick_menu_edit_state_flags_t =
+        cef_quick_menu_edit_state_flags_t(1);
+}
+impl cef_quick_menu_edit_state_flags_t {
+    pub const QM_EDITFLAG_CAN_CUT: cef_quick_menu_edit_state_flags_t =
+        cef_quick_menu_edit_state_flags_t(2);
+}
+impl cef_quick_menu_edit_state_flags_t {
+    pub const QM_EDITFLAG_CAN_COPY: cef_quick_menu_edit_state_flags_t =
+        cef_quick_menu_edit_state_flags_t(4);
+}
+impl cef_quick_menu_edit_state_flags_t {
+    pub const QM_EDITFLAG_CAN_PASTE: cef_quick_menu_edit_state_flags_t =
+        cef_quick_menu_edit_state_flags_t(8);
+}
+impl ::std::ops::BitOr<cef_quick_menu_edit_state_flags_t> for cef_quick_menu_edit_state_flags_t {
+    type Output = Self;
+    #[inline]
+    fn bitor(self, other: Self) -> Self {
+        cef_quick_menu_edit_state_flags_t(self.0 | other.0)
+    }
+}
+impl ::std::ops::BitOrAssign for cef_quick_menu_edit_state_flags_t {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: cef_quick_menu_edit_state_flags_t) {
+        self.0 |= rhs.0;
+    }
+}
+impl ::std::ops::BitAnd<cef_quick_menu_edit_state_flags_t> for cef_quick_menu_edit_state_flags_t {
+    type Output = Self;
+    #[inline]
+    fn bitand(self, other: Self) -> Self {
+        cef_quick_menu_edit_state_flags_t(self.0 & other.0)
+    }
+}
+impl ::std::ops::BitAndAssign for cef_quick_menu_edit_state_flags_t {
+    #[inline]
+    fn bitand_assign(&mut self, rhs: cef_quick_menu_edit_state_flags_t) {
+        self.0 &= rhs.0;
+    }
+}
+#[repr(transparent)]
 #[doc = "\n Supported quick menu state bit flags.\n"]
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
-pub enum cef_quick_menu_edit_state_flags_t {
-    QM_EDITFLAG_NONE = 0,
-    QM_EDITFLAG_CAN_ELLIPSIS = 1,
-    QM_EDITFLAG_CAN_CUT = 2,
-    QM_EDITFLAG_CAN_COPY = 4,
-    QM_EDITFLAG_CAN_PASTE = 8,
-}
+pub struct cef_quick_menu_edit_state_flags_t(pub ::std::os::raw::c_uint);
 #[repr(u32)]
 #[non_exhaustive]
 #[doc = "\n Key event types.\n"]
@@ -2714,29 +3227,95 @@ pub enum cef_dom_document_type_t {
     DOM_DOCUMENT_TYPE_XHTML = 2,
     DOM_DOCUMENT_TYPE_PLUGIN = 3,
 }
-#[repr(u32)]
-#[non_exhaustive]
+impl cef_dom_event_category_t {
+    pub const DOM_EVENT_CATEGORY_UNKNOWN: cef_dom_event_category_t = cef_dom_event_category_t(0);
+}
+impl cef_dom_event_category_t {
+    pub const DOM_EVENT_CATEGORY_UI: cef_dom_event_category_t = cef_dom_event_category_t(1);
+}
+impl cef_dom_event_category_t {
+    pub const DOM_EVENT_CATEGORY_MOUSE: cef_dom_event_category_t = cef_dom_event_category_t(2);
+}
+impl cef_dom_event_category_t {
+    pub const DOM_EVENT_CATEGORY_MUTATION: cef_dom_event_category_t = cef_dom_event_category_t(4);
+}
+impl cef_dom_event_category_t {
+    pub const DOM_EVENT_CATEGORY_KEYBOARD: cef_dom_event_category_t = cef_dom_event_category_t(8);
+}
+impl cef_dom_event_category_t {
+    pub const DOM_EVENT_CATEGORY_TEXT: cef_dom_event_category_t = cef_dom_event_category_t(16);
+}
+impl cef_dom_event_category_t {
+    pub const DOM_EVENT_CATEGORY_COMPOSITION: cef_dom_event_category_t =
+        cef_dom_event_category_t(32);
+}
+impl cef_dom_event_category_t {
+    pub const DOM_EVENT_CATEGORY_DRAG: cef_dom_event_category_t = cef_dom_event_category_t(64);
+}
+impl cef_dom_event_category_t {
+    pub const DOM_EVENT_CATEGORY_CLIPBOARD: cef_dom_event_category_t =
+        cef_dom_event_category_t(128);
+}
+impl cef_dom_event_category_t {
+    pub const DOM_EVENT_CATEGORY_MESSAGE: cef_dom_event_category_t = cef_dom_event_category_t(256);
+}
+impl cef_dom_event_category_t {
+    pub const DOM_EVENT_CATEGORY_WHEEL: cef_dom_event_category_t = cef_dom_event_category_t(512);
+}
+impl cef_dom_event_category_t {
+    pub const DOM_EVENT_CATEGORY_BEFORE_TEXT_INSERTED: cef_dom_event_category_t =
+        cef_dom_event_category_t(1024);
+}
+impl cef_dom_event_category_t {
+    pub const DOM_EVENT_CATEGORY_OVERFLOW: cef_dom_event_category_t =
+        cef_dom_event_category_t(2048);
+}
+impl cef_dom_event_category_t {
+    pub const DOM_EVENT_CATEGORY_PAGE_TRANSITION: cef_dom_event_category_t =
+        cef_dom_event_category_t(4096);
+}
+impl cef_dom_event_category_t {
+    pub const DOM_EVENT_CATEGORY_POPSTATE: cef_dom_event_category_t =
+        cef_dom_event_category_t(8192);
+}
+impl cef_dom_event_category_t {
+    pub const DOM_EVENT_CATEGORY_PROGRESS: cef_dom_event_category_t =
+        cef_dom_event_category_t(16384);
+}
+impl cef_dom_event_category_t {
+    pub const DOM_EVENT_CATEGORY_XMLHTTPREQUEST_PROGRESS: cef_dom_event_category_t =
+        cef_dom_event_category_t(32768);
+}
+impl ::std::ops::BitOr<cef_dom_event_category_t> for cef_dom_event_category_t {
+    type Output = Self;
+    #[inline]
+    fn bitor(self, other: Self) -> Self {
+        cef_dom_event_category_t(self.0 | other.0)
+    }
+}
+impl ::std::ops::BitOrAssign for cef_dom_event_category_t {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: cef_dom_event_category_t) {
+        self.0 |= rhs.0;
+    }
+}
+impl ::std::ops::BitAnd<cef_dom_event_category_t> for cef_dom_event_category_t {
+    type Output = Self;
+    #[inline]
+    fn bitand(self, other: Self) -> Self {
+        cef_dom_event_category_t(self.0 & other.0)
+    }
+}
+impl ::std::ops::BitAndAssign for cef_dom_event_category_t {
+    #[inline]
+    fn bitand_assign(&mut self, rhs: cef_dom_event_category_t) {
+        self.0 &= rhs.0;
+    }
+}
+#[repr(transparent)]
 #[doc = "\n DOM event category flags.\n"]
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
-pub enum cef_dom_event_category_t {
-    DOM_EVENT_CATEGORY_UNKNOWN = 0,
-    DOM_EVENT_CATEGORY_UI = 1,
-    DOM_EVENT_CATEGORY_MOUSE = 2,
-    DOM_EVENT_CATEGORY_MUTATION = 4,
-    DOM_EVENT_CATEGORY_KEYBOARD = 8,
-    DOM_EVENT_CATEGORY_TEXT = 16,
-    DOM_EVENT_CATEGORY_COMPOSITION = 32,
-    DOM_EVENT_CATEGORY_DRAG = 64,
-    DOM_EVENT_CATEGORY_CLIPBOARD = 128,
-    DOM_EVENT_CATEGORY_MESSAGE = 256,
-    DOM_EVENT_CATEGORY_WHEEL = 512,
-    DOM_EVENT_CATEGORY_BEFORE_TEXT_INSERTED = 1024,
-    DOM_EVENT_CATEGORY_OVERFLOW = 2048,
-    DOM_EVENT_CATEGORY_PAGE_TRANSITION = 4096,
-    DOM_EVENT_CATEGORY_POPSTATE = 8192,
-    DOM_EVENT_CATEGORY_PROGRESS = 16384,
-    DOM_EVENT_CATEGORY_XMLHTTPREQUEST_PROGRESS = 32768,
-}
+pub struct cef_dom_event_category_t(pub ::std::os::raw::c_uint);
 #[repr(u32)]
 #[non_exhaustive]
 #[doc = "\n DOM event processing phases.\n"]
@@ -2932,48 +3511,148 @@ const _: () = {
 };
 #[doc = "\n Structure representing cursor information. |buffer| will be\n |size.width|*|size.height|*4 bytes in size and represents a BGRA image with\n an upper-left origin.\n"]
 pub type cef_cursor_info_t = _cef_cursor_info_t;
-#[repr(u32)]
-#[non_exhaustive]
-#[doc = "\n URI unescape rules passed to CefURIDecode().\n"]
-#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
-pub enum cef_uri_unescape_rule_t {
+impl cef_uri_unescape_rule_t {
     #[doc = "\n Don't unescape anything at all.\n"]
-    UU_NONE = 0,
+    pub const UU_NONE: cef_uri_unescape_rule_t = cef_uri_unescape_rule_t(0);
+}
+impl cef_uri_unescape_rule_t {
     #[doc = "\n Don't unescape anything special, but all normal unescaping will happen.\n This is a placeholder and can't be combined with other flags (since it's\n just the absence of them). All other unescape rules imply \"normal\" in\n addition to their special meaning. Things like escaped letters, digits,\n and most symbols will get unescaped with this mode.\n"]
-    UU_NORMAL = 1,
+    pub const UU_NORMAL: cef_uri_unescape_rule_t = cef_uri_unescape_rule_t(1);
+}
+impl cef_uri_unescape_rule_t {
     #[doc = "\n Convert %20 to spaces. In some places where we're showing URLs, we may\n want this. In places where the URL may be copied and pasted out, then\n you wouldn't want this since it might not be interpreted in one piece\n by other applications.\n"]
-    UU_SPACES = 2,
+    pub const UU_SPACES: cef_uri_unescape_rule_t = cef_uri_unescape_rule_t(2);
+}
+impl cef_uri_unescape_rule_t {
     #[doc = "\n Unescapes '/' and '\\\\'. If these characters were unescaped, the resulting\n URL won't be the same as the source one. Moreover, they are dangerous to\n unescape in strings that will be used as file paths or names. This value\n should only be used when slashes don't have special meaning, like data\n URLs.\n"]
-    UU_PATH_SEPARATORS = 4,
+    pub const UU_PATH_SEPARATORS: cef_uri_unescape_rule_t = cef_uri_unescape_rule_t(4);
+}
+impl cef_uri_unescape_rule_t {
     #[doc = "\n Unescapes various characters that will change the meaning of URLs,\n including '%', '+', '&', '#'. Does not unescape path separators.\n If these characters were unescaped, the resulting URL won't be the same\n as the source one. This flag is used when generating final output like\n filenames for URLs where we won't be interpreting as a URL and want to do\n as much unescaping as possible.\n"]
-    UU_URL_SPECIAL_CHARS_EXCEPT_PATH_SEPARATORS = 8,
+    pub const UU_URL_SPECIAL_CHARS_EXCEPT_PATH_SEPARATORS: cef_uri_unescape_rule_t =
+        cef_uri_unescape_rule_t(8);
+}
+impl cef_uri_unescape_rule_t {
     #[doc = "\n URL queries use \"+\" for space. This flag controls that replacement.\n"]
-    UU_REPLACE_PLUS_WITH_SPACE = 16,
+    pub const UU_REPLACE_PLUS_WITH_SPACE: cef_uri_unescape_rule_t = cef_uri_unescape_rule_t(16);
+}
+impl ::std::ops::BitOr<cef_uri_unescape_rule_t> for cef_uri_unescape_rule_t {
+    type Output = Self;
+    #[inline]
+    fn bitor(self, other: Self) -> Self {
+        cef_uri_unescape_rule_t(self.0 | other.0)
+    }
+}
+impl ::std::ops::BitOrAssign for cef_uri_unescape_rule_t {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: cef_uri_unescape_rule_t) {
+        self.0 |= rhs.0;
+    }
+}
+impl ::std::ops::BitAnd<cef_uri_unescape_rule_t> for cef_uri_unescape_rule_t {
+    type Output = Self;
+    #[inline]
+    fn bitand(self, other: Self) -> Self {
+        cef_uri_unescape_rule_t(self.0 & other.0)
+    }
 }
-#[repr(u32)]
-#[non_exhaustive]
-#[doc = "\n Options that can be passed to CefParseJSON.\n"]
+impl ::std::ops::BitAndAssign for cef_uri_unescape_rule_t {
+    #[inline]
+    fn bitand_assign(&mut self, rhs: cef_uri_unescape_rule_t) {
+        self.0 &= rhs.0;
+    }
+}
+#[repr(transparent)]
+#[doc = "\n URI unescape rules passed to CefURIDecode().\n"]
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
-pub enum cef_json_parser_options_t {
+pub struct cef_uri_unescape_rule_t(pub ::std::os::raw::c_uint);
+impl cef_json_parser_options_t {
     #[doc = "\n Parses the input strictly according to RFC 4627. See comments in\n Chromium's base/json/json_reader.h file for known limitations/\n deviations from the RFC.\n"]
-    JSON_PARSER_RFC = 0,
+    pub const JSON_PARSER_RFC: cef_json_parser_options_t = cef_json_parser_options_t(0);
+}
+impl cef_json_parser_options_t {
     #[doc = "\n Allows commas to exist after the last element in structures.\n"]
-    JSON_PARSER_ALLOW_TRAILING_COMMAS = 1,
+    pub const JSON_PARSER_ALLOW_TRAILING_COMMAS: cef_json_parser_options_t =
+        cef_json_parser_options_t(1);
 }
-#[repr(u32)]
-#[non_exhaustive]
-#[doc = "\n Options that can be passed to CefWriteJSON.\n"]
+impl ::std::ops::BitOr<cef_json_parser_options_t> for cef_json_parser_options_t {
+    type Output = Self;
+    #[inline]
+    fn bitor(self, other: Self) -> Self {
+        cef_json_parser_options_t(self.0 | other.0)
+    }
+}
+impl ::std::ops::BitOrAssign for cef_json_parser_options_t {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: cef_json_parser_options_t) {
+        self.0 |= rhs.0;
+    }
+}
+impl ::std::ops::BitAnd<cef_json_parser_options_t> for cef_json_parser_options_t {
+    type Output = Self;
+    #[inline]
+    fn bitand(self, other: Self) -> Self {
+        cef_json_parser_options_t(self.0 & other.0)
+    }
+}
+impl ::std::ops::BitAndAssign for cef_json_parser_options_t {
+    #[inline]
+    fn bitand_assign(&mut self, rhs: cef_json_parser_options_t) {
+        self.0 &= rhs.0;
+    }
+}
+#[repr(transparent)]
+#[doc = "\n Options that can be passed to CefParseJSON.\n"]
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
-pub enum cef_json_writer_options_t {
+pub struct cef_json_parser_options_t(pub ::std::os::raw::c_uint);
+impl cef_json_writer_options_t {
     #[doc = "\n Default behavior.\n"]
-    JSON_WRITER_DEFAULT = 0,
+    pub const JSON_WRITER_DEFAULT: cef_json_writer_options_t = cef_json_writer_options_t(0);
+}
+impl cef_json_writer_options_t {
     #[doc = "\n This option instructs the writer that if a Binary value is encountered,\n the value (and key if within a dictionary) will be omitted from the\n output, and success will be returned. Otherwise, if a binary value is\n encountered, failure will be returned.\n"]
-    JSON_WRITER_OMIT_BINARY_VALUES = 1,
+    pub const JSON_WRITER_OMIT_BINARY_VALUES: cef_json_writer_options_t =
+        cef_json_writer_options_t(1);
+}
+impl cef_json_writer_options_t {
     #[doc = "\n This option instructs the writer to write doubles that have no fractional\n part as a normal integer (i.e., without using exponential notation\n or appending a '.0') as long as the value is within the range of a\n 64-bit int.\n"]
-    JSON_WRITER_OMIT_DOUBLE_TYPE_PRESERVATION = 2,
+    pub const JSON_WRITER_OMIT_DOUBLE_TYPE_PRESERVATION: cef_json_writer_options_t =
+        cef_json_writer_options_t(2);
+}
+impl cef_json_writer_options_t {
     #[doc = "\n Return a slightly nicer formatted json string (pads with whitespace to\n help with readability).\n"]
-    JSON_WRITER_PRETTY_PRINT = 4,
+    pub const JSON_WRITER_PRETTY_PRINT: cef_json_writer_options_t = cef_json_writer_options_t(4);
+}
+impl ::std::ops::BitOr<cef_json_writer_options_t> for cef_json_writer_options_t {
+    type Output = Self;
+    #[inline]
+    fn bitor(self, other: Self) -> Self {
+        cef_json_writer_options_t(self.0 | other.0)
+    }
+}
+impl ::std::ops::BitOrAssign for cef_json_writer_options_t {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: cef_json_writer_options_t) {
+        self.0 |= rhs.0;
+    }
+}
+impl ::std::ops::BitAnd<cef_json_writer_options_t> for cef_json_writer_options_t {
+    type Output = Self;
+    #[inline]
+    fn bitand(self, other: Self) -> Self {
+        cef_json_writer_options_t(self.0 & other.0)
+    }
+}
+impl ::std::ops::BitAndAssign for cef_json_writer_options_t {
+    #[inline]
+    fn bitand_assign(&mut self, rhs: cef_json_writer_options_t) {
+        self.0 &= rhs.0;
+    }
 }
+#[repr(transparent)]
+#[doc = "\n Options that can be passed to CefWriteJSON.\n"]
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct cef_json_writer_options_t(pub ::std::os::raw::c_uint);
 #[repr(u32)]
 #[non_exhaustive]
 #[doc = "\n Margin type for PDF printing.\n"]
@@ -3271,36 +3950,108 @@ pub enum cef_ssl_version_t {
     SSL_CONNECTION_VERSION_TLS1_3 = 6,
     SSL_CONNECTION_VERSION_QUIC = 7,
 }
-#[repr(u32)]
-#[non_exhaustive]
+impl cef_ssl_content_status_t {
+    pub const SSL_CONTENT_NORMAL_CONTENT: cef_ssl_content_status_t = cef_ssl_content_status_t(0);
+}
+impl cef_ssl_content_status_t {
+    pub const SSL_CONTENT_DISPLAYED_INSECURE_CONTENT: cef_ssl_content_status_t =
+        cef_ssl_content_status_t(1);
+}
+impl cef_ssl_content_status_t {
+    pub const SSL_CONTENT_RAN_INSECURE_CONTENT: cef_ssl_content_status_t =
+        cef_ssl_content_status_t(2);
+}
+impl ::std::ops::BitOr<cef_ssl_content_status_t> for cef_ssl_content_status_t {
+    type Output = Self;
+    #[inline]
+    fn bitor(self, other: Self) -> Self {
+        cef_ssl_content_status_t(self.0 | other.0)
+    }
+}
+impl ::std::ops::BitOrAssign for cef_ssl_content_status_t {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: cef_ssl_content_status_t) {
+        self.0 |= rhs.0;
+    }
+}
+impl ::std::ops::BitAnd<cef_ssl_content_status_t> for cef_ssl_content_status_t {
+    type Output = Self;
+    #[inline]
+    fn bitand(self, other: Self) -> Self {
+        cef_ssl_content_status_t(self.0 & other.0)
+    }
+}
+impl ::std::ops::BitAndAssign for cef_ssl_content_status_t {
+    #[inline]
+    fn bitand_assign(&mut self, rhs: cef_ssl_content_status_t) {
+        self.0 &= rhs.0;
+    }
+}
+#[repr(transparent)]
 #[doc = " Supported SSL content status flags. See content/public/common/ssl_status.h\n for more information."]
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
-pub enum cef_ssl_content_status_t {
-    SSL_CONTENT_NORMAL_CONTENT = 0,
-    SSL_CONTENT_DISPLAYED_INSECURE_CONTENT = 1,
-    SSL_CONTENT_RAN_INSECURE_CONTENT = 2,
+pub struct cef_ssl_content_status_t(pub ::std::os::raw::c_uint);
+impl cef_scheme_options_t {
+    pub const CEF_SCHEME_OPTION_NONE: cef_scheme_options_t = cef_scheme_options_t(0);
 }
-#[repr(u32)]
-#[non_exhaustive]
-#[doc = " Configuration options for registering a custom scheme.\n These values are used when calling AddCustomScheme."]
-#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
-pub enum cef_scheme_options_t {
-    CEF_SCHEME_OPTION_NONE = 0,
+impl cef_scheme_options_t {
     #[doc = " For non-standard scheme URLs only the \"scheme:\" component is parsed and\n canonicalized. The remainder of the URL will be passed to the handler as-\n is. For example, \"scheme:///some%20text\" will remain the same.\n Non-standard scheme URLs cannot be used as a target for form submission.\n"]
-    CEF_SCHEME_OPTION_STANDARD = 1,
+    pub const CEF_SCHEME_OPTION_STANDARD: cef_scheme_options_t = cef_scheme_options_t(1);
+}
+impl cef_scheme_options_t {
     #[doc = "\n If CEF_SCHEME_OPTION_LOCAL is set the scheme will be treated with the same\n security rules as those applied to \"file\" URLs. Normal pages cannot link\n to or access local URLs. Also, by default, local URLs can only perform\n XMLHttpRequest calls to the same URL (origin + path) that originated the\n request. To allow XMLHttpRequest calls from a local URL to other URLs with\n the same origin set the CefSettings.file_access_from_file_urls_allowed\n value to true (1). To allow XMLHttpRequest calls from a local URL to all\n origins set the CefSettings.universal_access_from_file_urls_allowed value\n to true (1).\n"]
-    CEF_SCHEME_OPTION_LOCAL = 2,
+    pub const CEF_SCHEME_OPTION_LOCAL: cef_scheme_options_t = cef_scheme_options_t(2);
+}
+impl cef_scheme_options_t {
     #[doc = "\n If CEF_SCHEME_OPTION_DISPLAY_ISOLATED is set the scheme can only be\n displayed from other content hosted with the same scheme. For example,\n pages in other origins cannot create iframes or hyperlinks to URLs with\n the scheme. For schemes that must be accessible from other schemes don't\n set this, set CEF_SCHEME_OPTION_CORS_ENABLED, and use CORS\n \"Access-Control-Allow-Origin\" headers to further restrict access.\n"]
-    CEF_SCHEME_OPTION_DISPLAY_ISOLATED = 4,
+    pub const CEF_SCHEME_OPTION_DISPLAY_ISOLATED: cef_scheme_options_t = cef_scheme_options_t(4);
+}
+impl cef_scheme_options_t {
     #[doc = "\n If CEF_SCHEME_OPTION_SECURE is set the scheme will be treated with the\n same security rules as those applied to \"https\" URLs. For example, loading\n this scheme from other secure schemes will not trigger mixed content\n warnings.\n"]
-    CEF_SCHEME_OPTION_SECURE = 8,
+    pub const CEF_SCHEME_OPTION_SECURE: cef_scheme_options_t = cef_scheme_options_t(8);
+}
+impl cef_scheme_options_t {
     #[doc = "\n If CEF_SCHEME_OPTION_CORS_ENABLED is set the scheme can be sent CORS\n requests. This value should be set in most cases where\n CEF_SCHEME_OPTION_STANDARD is set.\n"]
-    CEF_SCHEME_OPTION_CORS_ENABLED = 16,
+    pub const CEF_SCHEME_OPTION_CORS_ENABLED: cef_scheme_options_t = cef_scheme_options_t(16);
+}
+impl cef_scheme_options_t {
     #[doc = "\n If CEF_SCHEME_OPTION_CSP_BYPASSING is set the scheme can bypass Content-\n Security-Policy (CSP) checks. This value should not be set in most cases\n where CEF_SCHEME_OPTION_STANDARD is set.\n"]
-    CEF_SCHEME_OPTION_CSP_BYPASSING = 32,
+    pub const CEF_SCHEME_OPTION_CSP_BYPASSING: cef_scheme_options_t = cef_scheme_options_t(32);
+}
+impl cef_scheme_options_t {
     #[doc = "\n If CEF_SCHEME_OPTION_FETCH_ENABLED is set the scheme can perform Fetch API\n requests.\n"]
-    CEF_SCHEME_OPTION_FETCH_ENABLED = 64,
+    pub const CEF_SCHEME_OPTION_FETCH_ENABLED: cef_scheme_options_t = cef_scheme_options_t(64);
+}
+impl ::std::ops::BitOr<cef_scheme_options_t> for cef_scheme_options_t {
+    type Output = Self;
+    #[inline]
+    fn bitor(self, other: Self) -> Self {
+        cef_scheme_options_t(self.0 | other.0)
+    }
+}
+impl ::std::ops::BitOrAssign for cef_scheme_options_t {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: cef_scheme_options_t) {
+        self.0 |= rhs.0;
+    }
+}
+impl ::std::ops::BitAnd<cef_scheme_options_t> for cef_scheme_options_t {
+    type Output = Self;
+    #[inline]
+    fn bitand(self, other: Self) -> Self {
+        cef_scheme_options_t(self.0 & other.0)
+    }
+}
+impl ::std::ops::BitAndAssign for cef_scheme_options_t {
+    #[inline]
+    fn bitand_assign(&mut self, rhs: cef_scheme_options_t) {
+        self.0 &= rhs.0;
+    }
 }
+#[repr(transparent)]
+#[doc = " Configuration options for registering a custom scheme.\n These values are used when calling AddCustomScheme."]
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct cef_scheme_options_t(pub ::std::os::raw::c_uint);
 #[doc = "\n Structure representing a range.\n"]
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
@@ -3629,17 +4380,55 @@ pub enum cef_show_state_t {
     CEF_SHOW_STATE_FULLSCREEN = 4,
     CEF_SHOW_STATE_HIDDEN = 5,
 }
-#[repr(u32)]
-#[non_exhaustive]
+impl cef_touch_handle_state_flags_t {
+    pub const CEF_THS_FLAG_NONE: cef_touch_handle_state_flags_t = cef_touch_handle_state_flags_t(0);
+}
+impl cef_touch_handle_state_flags_t {
+    pub const CEF_THS_FLAG_ENABLED: cef_touch_handle_state_flags_t =
+        cef_touch_handle_state_flags_t(1);
+}
+impl cef_touch_handle_state_flags_t {
+    pub const CEF_THS_FLAG_ORIENTATION: cef_touch_handle_state_flags_t =
+        cef_touch_handle_state_flags_t(2);
+}
+impl cef_touch_handle_state_flags_t {
+    pub const CEF_THS_FLAG_ORIGIN: cef_touch_handle_state_flags_t =
+        cef_touch_handle_state_flags_t(4);
+}
+impl cef_touch_handle_state_flags_t {
+    pub const CEF_THS_FLAG_ALPHA: cef_touch_handle_state_flags_t =
+        cef_touch_handle_state_flags_t(8);
+}
+impl ::std::ops::BitOr<cef_touch_handle_state_flags_t> for cef_touch_handle_state_flags_t {
+    type Output = Self;
+    #[inline]
+    fn bitor(self, other: Self) -> Self {
+        cef_touch_handle_state_flags_t(self.0 | other.0)
+    }
+}
+impl ::std::ops::BitOrAssign for cef_touch_handle_state_flags_t {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: cef_touch_handle_state_flags_t) {
+        self.0 |= rhs.0;
+    }
+}
+impl ::std::ops::BitAnd<cef_touch_handle_state_flags_t> for cef_touch_handle_state_flags_t {
+    type Output = Self;
+    #[inline]
+    fn bitand(self, other: Self) -> Self {
+        cef_touch_handle_state_flags_t(self.0 & other.0)
+    }
+}
+impl ::std::ops::BitAndAssign for cef_touch_handle_state_flags_t {
+    #[inline]
+    fn bitand_assign(&mut self, rhs: cef_touch_handle_state_flags_t) {
+        self.0 &= rhs.0;
+    }
+}
+#[repr(transparent)]
 #[doc = "\n Values indicating what state of the touch handle is set.\n"]
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
-pub enum cef_touch_handle_state_flags_t {
-    CEF_THS_FLAG_NONE = 0,
-    CEF_THS_FLAG_ENABLED = 1,
-    CEF_THS_FLAG_ORIENTATION = 2,
-    CEF_THS_FLAG_ORIGIN = 4,
-    CEF_THS_FLAG_ALPHA = 8,
-}
+pub struct cef_touch_handle_state_flags_t(pub ::std::os::raw::c_uint);
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct _cef_touch_handle_state_t {
@@ -3682,54 +4471,199 @@ const _: () = {
         [::std::mem::offset_of!(_cef_touch_handle_state_t, alpha) - 32usize];
 };
 pub type cef_touch_handle_state_t = _cef_touch_handle_state_t;
-#[repr(u32)]
-#[non_exhaustive]
-#[doc = "\n Media access permissions used by OnRequestMediaAccessPermission.\n"]
-#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
-pub enum cef_media_access_permission_types_t {
+impl cef_media_access_permission_types_t {
     #[doc = "\n No permission.\n"]
-    CEF_MEDIA_PERMISSION_NONE = 0,
+    pub const CEF_MEDIA_PERMISSION_NONE: cef_media_access_permission_types_t =
+        cef_media_access_permission_types_t(0);
+}
+impl cef_media_access_permission_types_t {
     #[doc = "\n Device audio capture permission.\n"]
-    CEF_MEDIA_PERMISSION_DEVICE_AUDIO_CAPTURE = 1,
+    pub const CEF_MEDIA_PERMISSION_DEVICE_AUDIO_CAPTURE: cef_media_access_permission_types_t =
+        cef_media_access_permission_types_t(1);
+}
+impl cef_media_access_permission_types_t {
     #[doc = "\n Device video capture permission.\n"]
-    CEF_MEDIA_PERMISSION_DEVICE_VIDEO_CAPTURE = 2,
+    pub const CEF_MEDIA_PERMISSION_DEVICE_VIDEO_CAPTURE: cef_media_access_permission_types_t =
+        cef_media_access_permission_types_t(2);
+}
+impl cef_media_access_permission_types_t {
     #[doc = "\n Desktop audio capture permission.\n"]
-    CEF_MEDIA_PERMISSION_DESKTOP_AUDIO_CAPTURE = 4,
+    pub const CEF_MEDIA_PERMISSION_DESKTOP_AUDIO_CAPTURE: cef_media_access_permission_types_t =
+        cef_media_access_permission_types_t(4);
+}
+impl cef_media_access_permission_types_t {
     #[doc = "\n Desktop video capture permission.\n"]
-    CEF_MEDIA_PERMISSION_DESKTOP_VIDEO_CAPTURE = 8,
+    pub const CEF_MEDIA_PERMISSION_DESKTOP_VIDEO_CAPTURE: cef_media_access_permission_types_t =
+        cef_media_access_permission_types_t(8);
 }
-#[repr(u32)]
-#[non_exhaustive]
-#[doc = "\n Permission types used with OnShowPermissionPrompt. Some types are\n platform-specific or only supported with Chrome style. Should be kept\n in sync with Chromium's permissions::RequestType type.\n"]
+impl ::std::ops::BitOr<cef_media_access_permission_types_t>
+    for cef_media_access_permission_types_t
+{
+    type Output = Self;
+    #[inline]
+    fn bitor(self, other: Self) -> Self {
+        cef_media_access_permission_types_t(self.0 | other.0)
+    }
+}
+impl ::std::ops::BitOrAssign for cef_media_access_permission_types_t {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: cef_media_access_permission_types_t) {
+        self.0 |= rhs.0;
+    }
+}
+impl ::std::ops::BitAnd<cef_media_access_permission_types_t>
+    for cef_media_access_permission_types_t
+{
+    type Output = Self;
+    #[inline]
+    fn bitand(self, other: Self) -> Self {
+        cef_media_access_permission_types_t(self.0 & other.0)
+    }
+}
+impl ::std::ops::BitAndAssign for cef_media_access_permission_types_t {
+    #[inline]
+    fn bitand_assign(&mut self, rhs: cef_media_access_permission_types_t) {
+        self.0 &= rhs.0;
+    }
+}
+#[repr(transparent)]
+#[doc = "\n Media access permissions used by OnRequestMediaAccessPermission.\n"]
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
-pub enum cef_permission_request_types_t {
-    CEF_PERMISSION_TYPE_NONE = 0,
-    CEF_PERMISSION_TYPE_AR_SESSION = 1,
-    CEF_PERMISSION_TYPE_CAMERA_PAN_TILT_ZOOM = 2,
-    CEF_PERMISSION_TYPE_CAMERA_STREAM = 4,
-    CEF_PERMISSION_TYPE_CAPTURED_SURFACE_CONTROL = 8,
-    CEF_PERMISSION_TYPE_CLIPBOARD = 16,
-    CEF_PERMISSION_TYPE_TOP_LEVEL_STORAGE_ACCESS = 32,
-    CEF_PERMISSION_TYPE_DISK_QUOTA = 64,
-    CEF_PERMISSION_TYPE_LOCAL_FONTS = 128,
-    CEF_PERMISSION_TYPE_GEOLOCATION = 256,
-    CEF_PERMISSION_TYPE_HAND_TRACKING = 512,
-    CEF_PERMISSION_TYPE_IDENTITY_PROVIDER = 1024,
-    CEF_PERMISSION_TYPE_IDLE_DETECTION = 2048,
-    CEF_PERMISSION_TYPE_MIC_STREAM = 4096,
-    CEF_PERMISSION_TYPE_MIDI_SYSEX = 8192,
-    CEF_PERMISSION_TYPE_MULTIPLE_DOWNLOADS = 16384,
-    CEF_PERMISSION_TYPE_NOTIFICATIONS = 32768,
-    CEF_PERMISSION_TYPE_KEYBOARD_LOCK = 65536,
-    CEF_PERMISSION_TYPE_POINTER_LOCK = 131072,
-    CEF_PERMISSION_TYPE_PROTECTED_MEDIA_IDENTIFIER = 262144,
-    CEF_PERMISSION_TYPE_REGISTER_PROTOCOL_HANDLER = 524288,
-    CEF_PERMISSION_TYPE_STORAGE_ACCESS = 1048576,
-    CEF_PERMISSION_TYPE_VR_SESSION = 2097152,
-    CEF_PERMISSION_TYPE_WEB_APP_INSTALLATION = 4194304,
-    CEF_PERMISSION_TYPE_WINDOW_MANAGEMENT = 8388608,
-    CEF_PERMISSION_TYPE_FILE_SYSTEM_ACCESS = 16777216,
+pub struct cef_media_access_permission_types_t(pub ::std::os::raw::c_uint);
+impl cef_permission_request_types_t {
+    pub const CEF_PERMISSION_TYPE_NONE: cef_permission_request_types_t =
+        cef_permission_request_types_t(0);
+}
+impl cef_permission_request_types_t {
+    pub const CEF_PERMISSION_TYPE_AR_SESSION: cef_permission_request_types_t =
+        cef_permission_request_types_t(1);
+}
+impl cef_permission_request_types_t {
+    pub const CEF_PERMISSION_TYPE_CAMERA_PAN_TILT_ZOOM: cef_permission_request_types_t =
+        cef_permission_request_types_t(2);
+}
+impl cef_permission_request_types_t {
+    pub const CEF_PERMISSION_TYPE_CAMERA_STREAM: cef_permission_request_types_t =
+        cef_permission_request_types_t(4);
+}
+impl cef_permission_request_types_t {
+    pub const CEF_PERMISSION_TYPE_CAPTURED_SURFACE_CONTROL: cef_permission_request_types_t =
+        cef_permission_request_types_t(8);
+}
+impl cef_permission_request_types_t {
+    pub const CEF_PERMISSION_TYPE_CLIPBOARD: cef_permission_request_types_t =
+        cef_permission_request_types_t(16);
+}
+impl cef_permission_request_types_t {
+    pub const CEF_PERMISSION_TYPE_TOP_LEVEL_STORAGE_ACCESS: cef_permission_request_types_t =
+        cef_permission_request_types_t(32);
+}
+impl cef_permission_request_types_t {
+    pub const CEF_PERMISSION_TYPE_DISK_QUOTA: cef_permission_request_types_t =
+        cef_permission_request_types_t(64);
+}
+impl cef_permission_request_types_t {
+    pub const CEF_PERMISSION_TYPE_LOCAL_FONTS: cef_permission_request_types_t =
+        cef_permission_request_types_t(128);
+}
+impl cef_permission_request_types_t {
+    pub const CEF_PERMISSION_TYPE_GEOLOCATION: cef_permission_request_types_t =
+        cef_permission_request_types_t(256);
+}
+impl cef_permission_request_types_t {
+    pub const CEF_PERMISSION_TYPE_HAND_TRACKING: cef_permission_request_types_t =
+        cef_permission_request_types_t(512);
+}
+impl cef_permission_request_types_t {
+    pub const CEF_PERMISSION_TYPE_IDENTITY_PROVIDER: cef_permission_request_types_t =
+        cef_permission_request_types_t(1024);
+}
+impl cef_permission_request_types_t {
+    pub const CEF_PERMISSION_TYPE_IDLE_DETECTION: cef_permission_request_types_t =
+        cef_permission_request_types_t(2048);
+}
+impl cef_permission_request_types_t {
+    pub const CEF_PERMISSION_TYPE_MIC_STREAM: cef_permission_request_types_t =
+        cef_permission_request_types_t(4096);
+}
+impl cef_permission_request_types_t {
+    pub const CEF_PERMISSION_TYPE_MIDI_SYSEX: cef_permission_request_types_t =
+        cef_permission_request_types_t(8192);
+}
+impl cef_permission_request_types_t {
+    pub const CEF_PERMISSION_TYPE_MULTIPLE_DOWNLOADS: cef_permission_request_types_t =
+        cef_permission_request_types_t(16384);
+}
+impl cef_permission_request_types_t {
+    pub const CEF_PERMISSION_TYPE_NOTIFICATIONS: cef_permission_request_types_t =
+        cef_permission_request_types_t(32768);
+}
+impl cef_permission_request_types_t {
+    pub const CEF_PERMISSION_TYPE_KEYBOARD_LOCK: cef_permission_request_types_t =
+        cef_permission_request_types_t(65536);
+}
+impl cef_permission_request_types_t {
+    pub const CEF_PERMISSION_TYPE_POINTER_LOCK: cef_permission_request_types_t =
+        cef_permission_request_types_t(131072);
 }
+impl cef_permission_request_types_t {
+    pub const CEF_PERMISSION_TYPE_PROTECTED_MEDIA_IDENTIFIER: cef_permission_request_types_t =
+        cef_permission_request_types_t(262144);
+}
+impl cef_permission_request_types_t {
+    pub const CEF_PERMISSION_TYPE_REGISTER_PROTOCOL_HANDLER: cef_permission_request_types_t =
+        cef_permission_request_types_t(524288);
+}
+impl cef_permission_request_types_t {
+    pub const CEF_PERMISSION_TYPE_STORAGE_ACCESS: cef_permission_request_types_t =
+        cef_permission_request_types_t(1048576);
+}
+impl cef_permission_request_types_t {
+    pub const CEF_PERMISSION_TYPE_VR_SESSION: cef_permission_request_types_t =
+        cef_permission_request_types_t(2097152);
+}
+impl cef_permission_request_types_t {
+    pub const CEF_PERMISSION_TYPE_WEB_APP_INSTALLATION: cef_permission_request_types_t =
+        cef_permission_request_types_t(4194304);
+}
+impl cef_permission_request_types_t {
+    pub const CEF_PERMISSION_TYPE_WINDOW_MANAGEMENT: cef_permission_request_types_t =
+        cef_permission_request_types_t(8388608);
+}
+impl cef_permission_request_types_t {
+    pub const CEF_PERMISSION_TYPE_FILE_SYSTEM_ACCESS: cef_permission_request_types_t =
+        cef_permission_request_types_t(16777216);
+}
+impl ::std::ops::BitOr<cef_permission_request_types_t> for cef_permission_request_types_t {
+    type Output = Self;
+    #[inline]
+    fn bitor(self, other: Self) -> Self {
+        cef_permission_request_types_t(self.0 | other.0)
+    }
+}
+impl ::std::ops::BitOrAssign for cef_permission_request_types_t {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: cef_permission_request_types_t) {
+        self.0 |= rhs.0;
+    }
+}
+impl ::std::ops::BitAnd<cef_permission_request_types_t> for cef_permission_request_types_t {
+    type Output = Self;
+    #[inline]
+    fn bitand(self, other: Self) -> Self {
+        cef_permission_request_types_t(self.0 & other.0)
+    }
+}
+impl ::std::ops::BitAndAssign for cef_permission_request_types_t {
+    #[inline]
+    fn bitand_assign(&mut self, rhs: cef_permission_request_types_t) {
+        self.0 &= rhs.0;
+    }
+}
+#[repr(transparent)]
+#[doc = "\n Permission types used with OnShowPermissionPrompt. Some types are\n platform-specific or only supported with Chrome style. Should be kept\n in sync with Chromium's permissions::RequestType type.\n"]
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct cef_permission_request_types_t(pub ::std::os::raw::c_uint);
 #[repr(u32)]
 #[non_exhaustive]
 #[doc = "\n Permission request results.\n"]
@@ -11747,6 +12681,101 @@ const _: () = {
 };
 #[doc = "\n Implement this structure to provide handler implementations.\n"]
 pub type cef_client_t = _cef_client_t;
+unsafe extern "C" {
+    #[doc = "\n Combines specified |base_url| and |relative_url| into |resolved_url|.\n Returns false (0) if one of the URLs is NULL or invalid.\n"]
+    pub fn cef_resolve_url(
+        base_url: *const cef_string_t,
+        relative_url: *const cef_string_t,
+        resolved_url: *mut cef_string_t,
+    ) -> ::std::os::raw::c_int;
+}
+unsafe extern "C" {
+    #[doc = "\n Parse the specified |url| into its component parts. Returns false (0) if the\n URL is NULL or invalid.\n"]
+    pub fn cef_parse_url(
+        url: *const cef_string_t,
+        parts: *mut _cef_urlparts_t,
+    ) -> ::std::os::raw::c_int;
+}
+unsafe extern "C" {
+    #[doc = "\n Creates a URL from the specified |parts|, which must contain a non-NULL spec\n or a non-NULL host and path (at a minimum), but not both. Returns false (0)\n if |parts| isn't initialized as described.\n"]
+    pub fn cef_create_url(
+        parts: *const _cef_urlparts_t,
+        url: *mut cef_string_t,
+    ) -> ::std::os::raw::c_int;
+}
+unsafe extern "C" {
+    #[doc = "\n This is a convenience function for formatting a URL in a concise and human-\n friendly way to help users make security-related decisions (or in other\n circumstances when people need to distinguish sites, origins, or otherwise-\n simplified URLs from each other). Internationalized domain names (IDN) may be\n presented in Unicode if the conversion is considered safe. The returned\n value will (a) omit the path for standard schemes, excepting file and\n filesystem, and (b) omit the port if it is the default for the scheme. Do\n not use this for URLs which will be parsed or sent to other applications.\n"]
+    pub fn cef_format_url_for_security_display(
+        origin_url: *const cef_string_t,
+    ) -> cef_string_userfree_t;
+}
+unsafe extern "C" {
+    #[doc = "\n Returns the mime type for the specified file extension or an NULL string if\n unknown.\n"]
+    pub fn cef_get_mime_type(extension: *const cef_string_t) -> cef_string_userfree_t;
+}
+unsafe extern "C" {
+    #[doc = "\n Get the extensions associated with the given mime type. This should be passed\n in lower case. There could be multiple extensions for a given mime type,\n like \"html,htm\" for \"text/html\", or \"txt,text,html,...\" for \"text/*\".\n Any existing elements in the provided vector will not be erased.\n"]
+    pub fn cef_get_extensions_for_mime_type(
+        mime_type: *const cef_string_t,
+        extensions: cef_string_list_t,
+    );
+}
+unsafe extern "C" {
+    #[doc = "\n Encodes |data| as a base64 string.\n"]
+    pub fn cef_base64encode(
+        data: *const ::std::os::raw::c_void,
+        data_size: usize,
+    ) -> cef_string_userfree_t;
+}
+unsafe extern "C" {
+    #[doc = "\n Decodes the base64 encoded string |data|. The returned value will be NULL if\n the decoding fails.\n"]
+    pub fn cef_base64decode(data: *const cef_string_t) -> *mut _cef_binary_value_t;
+}
+unsafe extern "C" {
+    #[doc = "\n Escapes characters in |text| which are unsuitable for use as a query\n parameter value. Everything except alphanumerics and -_.!~*'() will be\n converted to \"%XX\". If |use_plus| is true (1) spaces will change to \"+\".\n The result is basically the same as encodeURIComponent in Javacript.\n"]
+    pub fn cef_uriencode(
+        text: *const cef_string_t,
+        use_plus: ::std::os::raw::c_int,
+    ) -> cef_string_userfree_t;
+}
+unsafe extern "C" {
+    #[doc = "\n Unescapes |text| and returns the result. Unescaping consists of looking for\n the exact pattern \"%XX\" where each X is a hex digit and converting to the\n character with the numerical value of those digits (e.g. \"i%20=%203%3b\"\n unescapes to \"i = 3;\"). If |convert_to_utf8| is true (1) this function will\n attempt to interpret the initial decoded result as UTF-8. If the result is\n convertable into UTF-8 it will be returned as converted. Otherwise the\n initial decoded result will be returned.  The |unescape_rule| parameter\n supports further customization the decoding process.\n"]
+    pub fn cef_uridecode(
+        text: *const cef_string_t,
+        convert_to_utf8: ::std::os::raw::c_int,
+        unescape_rule: cef_uri_unescape_rule_t,
+    ) -> cef_string_userfree_t;
+}
+unsafe extern "C" {
+    #[doc = "\n Parses the specified |json_string| and returns a dictionary or list\n representation. If JSON parsing fails this function returns NULL.\n"]
+    pub fn cef_parse_json(
+        json_string: *const cef_string_t,
+        options: cef_json_parser_options_t,
+    ) -> *mut _cef_value_t;
+}
+unsafe extern "C" {
+    #[doc = "\n Parses the specified UTF8-encoded |json| buffer of size |json_size| and\n returns a dictionary or list representation. If JSON parsing fails this\n function returns NULL.\n"]
+    pub fn cef_parse_json_buffer(
+        json: *const ::std::os::raw::c_void,
+        json_size: usize,
+        options: cef_json_parser_options_t,
+    ) -> *mut _cef_value_t;
+}
+unsafe extern "C" {
+    #[doc = "\n Parses the specified |json_string| and returns a dictionary or list\n representation. If JSON parsing fails this function returns NULL and\n populates |error_msg_out| with a formatted error message.\n"]
+    pub fn cef_parse_jsonand_return_error(
+        json_string: *const cef_string_t,
+        options: cef_json_parser_options_t,
+        error_msg_out: *mut cef_string_t,
+    ) -> *mut _cef_value_t;
+}
+unsafe extern "C" {
+    #[doc = "\n Generates a JSON string from the specified root |node| which should be a\n dictionary or list value. Returns an NULL string on failure. This function\n requires exclusive access to |node| including any underlying data.\n"]
+    pub fn cef_write_json(
+        node: *mut _cef_value_t,
+        options: cef_json_writer_options_t,
+    ) -> cef_string_userfree_t;
+}
 #[doc = "\n Structure used to create and/or parse command line arguments. Arguments with\n \"--\", \"-\" and, on Windows, \"/\" prefixes are considered switches. Switches\n will always precede any arguments without switch prefixes. Switches can\n optionally have a value specified using the \"=\" delimiter (e.g.\n \"-switch=value\"). An argument of \"--\" will terminate switch parsing with all\n subsequent tokens, regardless of prefix, being interpreted as non-switch\n arguments. Switch names should be lowercase ASCII and will be converted to\n such if necessary. Switch values will retain the original case and UTF8\n encoding. This structure can be used before cef_initialize() is called.\n"]
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
@@ -6,10 +6,12 @@
     clippy::all
 )]
 mod bindings;
+mod version;
 
 use std::ptr::null_mut;
 
 pub use bindings::*;
+pub use version::*;
 
 impl Default for _cef_string_utf16_t {
     fn default() -> Self {
@@ -0,0 +1,12 @@
+// Generated by update-bindings from include/cef_version.h, don't edit it by hand.
+
+pub const CEF_VERSION_MAJOR: u32 = 131;
+pub const CEF_VERSION_MINOR: u32 = 3;
+pub const CEF_VERSION_PATCH: u32 = 4;
+
+/// The CEF versions which changed the API of the safe wrappers, each one a
+/// `cef_api_at_least` cfg of the cef crate:
+///
+/// - 122: frame identifiers are strings instead of i64
+/// - 125: WindowInfo::runtime_style selects Chrome or Alloy style per browser
+pub const CEF_API_MILESTONES: &[u32] = &[122, 125];
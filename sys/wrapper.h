@@ -1,7 +1,10 @@
+#include "include/cef_api_hash.h"
+#include "include/cef_version.h"
 #include "include/capi/cef_base_capi.h"
 
 #include "include/capi/cef_app_capi.h"
 #include "include/capi/cef_client_capi.h"
+#include "include/capi/cef_parser_capi.h"
 #include "include/capi/cef_urlrequest_capi.h"
 
 #include "include/capi/views/cef_layout_capi.h"
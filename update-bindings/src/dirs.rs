@@ -27,3 +27,18 @@ pub fn get_cef_dir() -> crate::Result<PathBuf> {
     webview2_com_dir.push("cef");
     Ok(webview2_com_dir)
 }
+
+/// The directory of the CEF distribution the sys bindings were generated from, the same one the
+/// sys crate links with.
+pub fn get_cef_path() -> crate::Result<PathBuf> {
+    std::env::var("CEF_PATH")
+        .map(PathBuf::from)
+        .or_else(|_| {
+            std::env::var("HOME").map(|home| {
+                let mut path = PathBuf::from(home);
+                path.push(".local/share/cef");
+                path
+            })
+        })
+        .map_err(|_| crate::Error::MissingPath(PathBuf::from("CEF_PATH")))
+}
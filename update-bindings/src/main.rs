@@ -23,10 +23,11 @@ pub type Result<T> = std::result::Result<T, Error>;
 
 mod dirs;
 mod parse_tree;
+mod version;
 
 fn main() -> Result<()> {
-    let mut sys_bindings = dirs::get_sys_dir()?;
-    sys_bindings.push("src/bindings.rs");
+    let sys_dir = dirs::get_sys_dir()?;
+    let sys_bindings = sys_dir.join("src/bindings.rs");
     let mut cef_bindings = dirs::get_cef_dir()?;
     cef_bindings.push("src/bindings.rs");
 
@@ -39,6 +40,8 @@ fn main() -> Result<()> {
         println!("Updated: {}", cef_bindings.display());
     }
 
+    version::update_version(&dirs::get_cef_path()?, &sys_dir)?;
+
     Ok(())
 }
 
@@ -1,4 +1,4 @@
-use convert_case::{Case, Casing};
+use convert_case::{Boundary, Case, Casing};
 use quote::{format_ident, quote, ToTokens};
 use regex::Regex;
 use std::{
@@ -49,6 +49,14 @@ struct TypeAliasRef<'a> {
 struct EnumRef<'a> {
     name: String,
     ty: Option<&'a syn::ItemEnum>,
+    flags: Option<FlagsRef<'a>>,
+}
+
+/// A bindgen `--bitfield-enum`, a newtype around the integer `bits` with a constant for each
+/// value of the C enum.
+struct FlagsRef<'a> {
+    bits: &'a syn::Type,
+    constants: Vec<&'a syn::ImplItemConst>,
 }
 
 struct FieldRef<'a> {
@@ -83,6 +91,8 @@ struct SignatureRef<'a> {
     name: String,
     inputs: Vec<FnArgRef<'a>>,
     output: Option<&'a syn::Type>,
+    /// The doc comment from the header, which may say which thread the method is called on.
+    doc: String,
     merged_params: OnceCell<Vec<MergedParam>>,
 }
 
@@ -447,31 +457,23 @@ impl SignatureRef<'_> {
                                 })
                             }
                             _ => {
-                                if ty_string.as_str() == BASE_REF_COUNTED {
-                                    Some(quote!{
-                                        let #name = #name.map(|arg| {
-                                            arg.add_ref();
-                                            arg.as_raw()
-                                        }).unwrap_or(std::ptr::null_mut());
-                                    })
+                                let get_raw = if ty_string.as_str() == BASE_REF_COUNTED {
+                                    quote! { |arg| arg.as_raw() }
                                 } else {
-                                    let cast = entry.and_then(|entry| {
+                                    entry.and_then(|entry| {
                                             syn::parse_str::<syn::Type>(&entry.name).ok()
                                         })
                                         .map(|ty| {
                                             let ty = ty.to_token_stream().to_string();
                                             let ty = format_ident!("Impl{ty}");
-                                            quote!{ #ty::get_raw(arg) }
+                                            quote!{ #ty::get_raw }
                                         })
-                                        .unwrap_or_else(|| quote!{ arg.get_raw() });
+                                        .unwrap_or_else(|| quote!{ |arg| arg.get_raw() })
+                                };
 
-                                    Some(quote! {
-                                        let #name = #name.map(|arg| {
-                                            arg.add_ref();
-                                            #cast
-                                        }).unwrap_or(std::ptr::null_mut());
-                                    })
-                                }
+                                Some(quote! {
+                                    let #name = ref_arg(#name, #get_raw);
+                                })
                             },
                         }
                     })
@@ -769,9 +771,7 @@ impl SignatureRef<'_> {
     fn wrap_cef_args(&self, tree: &ParseTree) -> proc_macro2::TokenStream {
         let capture = self.capture_params();
         let args = self.merge_params(tree).filter_map(|arg| match arg {
-            MergedParam::Receiver => Some(quote! {
-                let arg_self_: &RcImpl<_, I> = RcImpl::get(arg_self_);
-            }),
+            MergedParam::Receiver => None,
             MergedParam::Single {
                 name,
                 ty: Some(arg_ty),
@@ -794,16 +794,14 @@ impl SignatureRef<'_> {
 
                                 match modifiers {
                                     [TypeModifier::ConstPtr] => Some(quote! {
-                                        let #arg_name = unsafe { #arg_name.as_ref() }.map(|arg| {
-                                            #name(unsafe { RefGuard::from_raw(arg) })
-                                        });
+                                        let #arg_name = unsafe { wrap_arg::<_, #name>(#arg_name.cast_mut()) };
                                         let #arg_name = #arg_name.as_ref();
                                     }),
+                                    // The guard owns the reference CEF passed in and releases it
+                                    // when the callback returns.
                                     [TypeModifier::MutPtr] => Some(quote! {
-                                        let mut #arg_name = unsafe { #arg_name.as_mut() }.map(|arg| {
-                                            #name(unsafe { RefGuard::from_raw(arg) })
-                                        });
-                                        let #arg_name = #arg_name.as_mut();
+                                        let #arg_name = unsafe { wrap_arg::<_, #name>(#arg_name) };
+                                        let #arg_name = #arg_name.as_ref();
                                     }),
                                     [TypeModifier::MutPtr, TypeModifier::MutPtr] => Some(quote! {
                                         let mut #arg_name = unsafe { #arg_name.as_mut() }.and_then(|ptr| {
@@ -841,9 +839,10 @@ impl SignatureRef<'_> {
                             }
                         } else if CUSTOM_STRING_TYPES.contains(&ty_string.as_str()) {
                             match modifiers {
+                                // The caller owns the string, so don't free it when the wrapper is dropped.
                                 [TypeModifier::MutPtr] => Some(quote! {
-                                    let mut #arg_name = if #arg_name.is_null() { None } else { Some(#arg_name.into()) };
-                                    let #arg_name = #arg_name.as_mut();
+                                    let mut #arg_name = if #arg_name.is_null() { None } else { Some(std::mem::ManuallyDrop::new(#arg_name.into())) };
+                                    let #arg_name = #arg_name.as_mut().map(|arg| &mut **arg);
                                 }),
                                 [TypeModifier::ConstPtr] => Some(quote! {
                                     let #arg_name = if #arg_name.is_null() { None } else { Some(#arg_name.into()) };
@@ -1139,6 +1138,234 @@ impl SignatureRef<'_> {
         let output = self.get_rust_output(tree);
         quote! { fn #name(#args) #output }
     }
+
+    /// The `extern "C"` function which CEF calls for the method of an object implemented in Rust,
+    /// converting the arguments and forwarding them to the `impl_trait` method of the object.
+    fn get_callback_method(
+        &self,
+        tree: &ParseTree,
+        impl_trait: &syn::Ident,
+    ) -> proc_macro2::TokenStream {
+        let name = &self.name;
+        let name = format_ident!("{name}");
+        let args = self.inputs.iter().map(|arg| {
+            let name = make_snake_case_value_name(&arg.name);
+            let name = format_ident!("{name}");
+            let ty = tree.resolve_type_aliases(arg.ty);
+            quote! { #name: #ty }
+        });
+        let wrapped_args = self.wrap_cef_args(tree);
+        let unwrapped_args = self.unwrap_cef_args(tree);
+        let forward_args = self.merge_params(tree).filter_map(|arg| match arg {
+            MergedParam::Single { name, .. } => {
+                let name = format_ident!("arg_{name}");
+                Some(quote! { #name })
+            }
+            MergedParam::Bounded { slice_name, .. } | MergedParam::Buffer { slice_name, .. } => {
+                let name = format_ident!("arg_{slice_name}");
+                Some(quote! { #name })
+            }
+            _ => None,
+        });
+        let original_output = self.output.map(|ty| tree.resolve_type_aliases(ty));
+        let output = original_output.as_ref().map(|output| {
+            quote! { -> #output }
+        });
+        let forward_output = original_output.map(|output| {
+            match syn::parse2::<ModifiedType>(output) {
+                Ok(ModifiedType { ty, modifiers }) => {
+                    tree.cef_name_map
+                        .get(&ty.to_token_stream().to_string())
+                        .and_then(|entry| match entry {
+                            NameMapEntry {
+                                ty: NameMapType::StructDeclaration,
+                                ..
+                            } => match modifiers.as_slice() {
+                                [TypeModifier::ConstPtr] => {
+                                    Some(quote! { result.map(|result| result.into()).unwrap_or(std::ptr::null()) })
+                                }
+                                [TypeModifier::MutPtr] => {
+                                    Some(quote! { result.map(|result| result.into()).unwrap_or(std::ptr::null_mut()) })
+                                }
+                                _ => None,
+                            }
+                            _ => None,
+                        })
+                }
+                _ => None,
+            }.unwrap_or(quote! { result.into() })
+        });
+
+        quote! {
+            extern "C" fn #name<I: #impl_trait>(#(#args),*) #output {
+                #wrapped_args
+                let result = forward_to_impl(arg_self_, |interface: &I| {
+                    #impl_trait::#name(interface, #(#forward_args),*)
+                });
+                #unwrapped_args
+                #forward_output
+            }
+        }
+    }
+
+    /// Call the method through the vtable, reporting a null function pointer and returning a
+    /// default value if CEF left it unset. `function_ptr` is an expression for the `Option` of
+    /// the function pointer, e.g. `self.0.#name`.
+    fn get_forward_method(
+        &self,
+        tree: &ParseTree,
+        rust_name: &syn::Ident,
+        function_ptr: impl FnOnce(&syn::Ident) -> proc_macro2::TokenStream,
+    ) -> proc_macro2::TokenStream {
+        let sig = self.get_signature(tree);
+        let name = &self.name;
+        let name = format_ident!("{name}");
+        let function_ptr = function_ptr(&name);
+        let pre_forward_args = self.unwrap_rust_args(tree);
+        let args = self.inputs.iter().map(|arg| {
+            let name = make_snake_case_value_name(&arg.name);
+            let name = format_ident!("arg_{name}");
+            quote! { #name }
+        });
+        let post_forward_args = self.rewrap_rust_args(tree);
+        let output_type = self.output.and_then(|ty| {
+            let ty = tree.resolve_type_aliases(ty);
+            syn::parse2::<ModifiedType>(ty.to_token_stream()).ok()
+        });
+        let wrap_result = output_type
+            .as_ref()
+            .and_then(|ModifiedType { modifiers, ty, .. }| {
+                let ty = ty.to_token_stream().to_string();
+                match modifiers.as_slice() {
+                    [TypeModifier::ConstPtr | TypeModifier::MutPtr]
+                        if ty != quote! { ::std::os::raw::c_void }.to_string() =>
+                    {
+                        match tree.cef_name_map.get(&ty) {
+                            Some(NameMapEntry {
+                                ty: NameMapType::StructDeclaration,
+                                ..
+                            }) => Some(quote! {
+                                if result.is_null() {
+                                    None
+                                } else {
+                                    Some(result.as_wrapper())
+                                }
+                            }),
+                            _ => None,
+                        }
+                    }
+                    _ => None,
+                }
+            })
+            .unwrap_or(quote! { result.as_wrapper() });
+        // Raw `c_void` pointers don't implement `Default`.
+        let impl_default = output_type
+            .filter(|ty| {
+                ty.ty.to_token_stream().to_string() == quote! { ::std::os::raw::c_void }.to_string()
+            })
+            .map(|_| quote! { std::mem::zeroed() });
+        let function = format!("{rust_name}::{}", self.name);
+        let null_function = match impl_default {
+            Some(impl_default) => quote! {
+                .unwrap_or_else(|| {
+                    crate::diagnostics::null_function::<()>(#function);
+                    #impl_default
+                })
+            },
+            None => quote! {
+                .unwrap_or_else(|| crate::diagnostics::null_function(#function))
+            },
+        };
+        quote! {
+            #sig {
+                unsafe {
+                    #function_ptr.map(|f| {
+                        #pre_forward_args
+                        let result = f(#(#args),*);
+                        #post_forward_args
+                        #wrap_result
+                    })
+                    #null_function
+                }
+            }
+        }
+    }
+
+    /// Forward the method to every handler of a `HandlerChain`, stopping at the first handled
+    /// result if it returns a value, or at the first handled out-parameter if it's a `get_` method
+    /// which doesn't.
+    fn get_chain_method(
+        &self,
+        tree: &ParseTree,
+        impl_trait: &syn::Ident,
+    ) -> proc_macro2::TokenStream {
+        let name = &self.name;
+        let name = format_ident!("{name}");
+        let mut out_params = Vec::new();
+        let (args, forward_args): (Vec<_>, Vec<_>) = self
+            .merge_params(tree)
+            .filter_map(|arg| match arg {
+                MergedParam::Receiver => None,
+                MergedParam::Single { name, ty: Some(ty) } => Some((name, ty)),
+                MergedParam::Bounded {
+                    slice_name,
+                    slice_ty,
+                    ..
+                }
+                | MergedParam::Buffer {
+                    slice_name,
+                    slice_ty,
+                    ..
+                } => Some((slice_name, slice_ty)),
+                _ => None,
+            })
+            .map(|(name, ty)| {
+                let name = format_ident!("{name}");
+                let ty = ty
+                    .get_argument_type(tree)
+                    .unwrap_or_else(|| ty.ty.to_token_stream());
+                // Reborrow the out-parameters for each handler, everything else is `Copy`.
+                if ty.to_string().starts_with("Option < & mut") {
+                    out_params.push(name.clone());
+                    (quote! { mut #name: #ty }, quote! { #name.as_deref_mut() })
+                } else {
+                    (quote! { #name: #ty }, quote! { #name })
+                }
+            })
+            .unzip();
+        let call = quote! { #impl_trait::#name(handler, #(#forward_args),*) };
+
+        match self.get_rust_output(tree) {
+            Some(output) => quote! {
+                fn #name(&self, #(#args),*) #output {
+                    for handler in self.handlers() {
+                        let result = #call;
+                        if ChainResult::is_handled(&result) {
+                            return result;
+                        }
+                    }
+                    Default::default()
+                }
+            },
+            None if out_params.is_empty() || !self.name.starts_with("get_") => quote! {
+                fn #name(&self, #(#args),*) {
+                    for handler in self.handlers() {
+                        #call;
+                    }
+                }
+            },
+            None => quote! {
+                fn #name(&self, #(#args),*) {
+                    for handler in self.handlers() {
+                        #call;
+                        if #(#out_params.as_deref().is_some_and(ChainResult::is_handled))||* {
+                            return;
+                        }
+                    }
+                }
+            },
+        }
+    }
 }
 
 impl<'a> TryFrom<&'a syn::Field> for SignatureRef<'a> {
@@ -1234,6 +1461,7 @@ impl<'a> TryFrom<&'a syn::Field> for SignatureRef<'a> {
             name,
             inputs,
             output,
+            doc: doc_comment(&value.attrs),
             merged_params: Default::default(),
         })
     }
@@ -1243,6 +1471,8 @@ const BASE_REF_COUNTED: &str = "_cef_base_ref_counted_t";
 
 const BASE_SCOPED: &str = "_cef_base_scoped_t";
 
+const CLIENT: &str = "_cef_client_t";
+
 const CUSTOM_STRING_TYPES: &[&str] = &[
     "_cef_string_utf8_t",
     "_cef_string_utf16_t",
@@ -1254,6 +1484,7 @@ const CUSTOM_STRING_TYPES: &[&str] = &[
 
 struct StructDeclarationRef<'a> {
     name: String,
+    doc: String,
     fields: Vec<FieldRef<'a>>,
     methods: Vec<SignatureRef<'a>>,
 }
@@ -1302,8 +1533,11 @@ impl ModifiedType {
                     let name = format_ident!("{name}");
 
                     match self.modifiers.as_slice() {
-                        [TypeModifier::ConstPtr] => Some(quote! { Option<&impl #impl_trait> }),
-                        [TypeModifier::MutPtr] => Some(quote! { Option<&mut impl #impl_trait> }),
+                        // Callbacks only borrow the argument for the duration of the call, clone
+                        // it to keep it.
+                        [TypeModifier::ConstPtr | TypeModifier::MutPtr] => {
+                            Some(quote! { Option<&impl #impl_trait> })
+                        }
                         [TypeModifier::MutPtr, TypeModifier::MutPtr] => {
                             Some(quote! { Option<&mut impl #impl_trait> })
                         }
@@ -1320,6 +1554,9 @@ impl ModifiedType {
 
                     match self.modifiers.as_slice() {
                         [TypeModifier::ConstPtr] => Some(quote! { Option<&#name> }),
+                        [TypeModifier::MutPtr] if BASE_REF_COUNTED == root => {
+                            Some(quote! { Option<&#name> })
+                        }
                         [TypeModifier::MutPtr] => Some(quote! { Option<&mut #name> }),
                         [TypeModifier::MutPtr, TypeModifier::MutPtr] => {
                             Some(quote! { Option<&mut #name> })
@@ -1517,6 +1754,11 @@ struct ParseTree<'a> {
     lookup_global_function_declaration: BTreeMap<String, usize>,
 
     base_types: BTreeMap<String, String>,
+
+    /// The sizes of the CEF structs from the layout tests of bindgen.
+    struct_sizes: Vec<(String, usize)>,
+    /// `CEF_API_HASH_UNIVERSAL`, if bindgen found `cef_api_hash.h`.
+    api_hash: Option<String>,
 }
 
 impl<'a> ParseTree<'a> {
@@ -1528,8 +1770,10 @@ impl<'a> ParseTree<'a> {
                 non_camel_case_types,
                 unused_variables
             )]
+            use crate::chain::{ChainResult, HandlerChain};
             use crate::rc::{
-                ConvertParam, ConvertReturnValue, Rc, RcImpl, RefGuard, WrapParamRef,
+                forward_to_impl, init_vtable, ref_arg, wrap_arg, ConvertParam, ConvertReturnValue,
+                vtable_methods, Rc, RcImpl, RefGuard, VtableMethods, WrapParamRef,
             };
             use cef_sys::*;
         }
@@ -1658,6 +1902,21 @@ impl<'a> ParseTree<'a> {
         self.base_types.get(name).map(String::as_str)
     }
 
+    /// `true` if [CLIENT] returns the handler `name`, which gets a `HandlerChain`.
+    fn is_client_handler(&self, name: &str) -> bool {
+        self.lookup_struct_declaration
+            .get(CLIENT)
+            .and_then(|&i| self.struct_declarations.get(i))
+            .is_some_and(|client| {
+                client.methods.iter().any(|m| {
+                    m.name.ends_with("_handler")
+                        && m.output
+                            .and_then(|ty| self.resolve_modified_type(ty))
+                            .is_some_and(|ty| ty.ty.to_token_stream().to_string() == name)
+                })
+            })
+    }
+
     fn root<'b: 'c, 'c>(&'b self, name: &'c str) -> &'c str {
         self.base(name).map(|base| self.root(base)).unwrap_or(name)
     }
@@ -1737,68 +1996,10 @@ impl<'a> ParseTree<'a> {
         }
 
         let name = s.name.as_str();
-        let methods = s.methods.iter().map(|m| {
-            let sig = m.get_signature(self);
-            let name = &m.name;
-            let name = format_ident!("{name}");
-            let pre_forward_args = m.unwrap_rust_args(self);
-            let args = m.inputs.iter().map(|arg| {
-                let name = make_snake_case_value_name(&arg.name);
-                let name = format_ident!("arg_{name}");
-                quote! { #name }
-            });
-            let post_forward_args = m.rewrap_rust_args(self);
-            let output_type = m.output.and_then(|ty| {
-                let ty = self.resolve_type_aliases(ty);
-                syn::parse2::<ModifiedType>(ty.to_token_stream()).ok()
-            });
-            let wrap_result = output_type
-                .as_ref()
-                .and_then(|ModifiedType { modifiers, ty, .. }| {
-                    let ty = ty.to_token_stream().to_string();
-                    match modifiers.as_slice() {
-                        [TypeModifier::ConstPtr | TypeModifier::MutPtr]
-                            if ty != quote! { ::std::os::raw::c_void }.to_string() =>
-                        {
-                            match self.cef_name_map.get(&ty) {
-                                Some(NameMapEntry {
-                                    ty: NameMapType::StructDeclaration,
-                                    ..
-                                }) => Some(quote! {
-                                    if result.is_null() {
-                                        None
-                                    } else {
-                                        Some(result.as_wrapper())
-                                    }
-                                }),
-                                _ => None,
-                            }
-                        }
-                        _ => None,
-                    }
-                })
-                .unwrap_or(quote! { result.as_wrapper() });
-            let impl_default = output_type
-                .and_then(|ty| {
-                    (ty.ty.to_token_stream().to_string()
-                        != quote! { ::std::os::raw::c_void }.to_string())
-                    .then(|| quote! { .unwrap_or_default() })
-                })
-                .unwrap_or(quote! { .unwrap_or_else(|| std::mem::zeroed()) });
-            quote! {
-                #sig {
-                    unsafe {
-                        self.0.#name.map(|f| {
-                            #pre_forward_args
-                            let result = f(#(#args),*);
-                            #post_forward_args
-                            #wrap_result
-                        })
-                        #impl_default
-                    }
-                }
-            }
-        });
+        let methods = s
+            .methods
+            .iter()
+            .map(|m| m.get_forward_method(self, rust_name, |name| quote! { self.0.#name }));
 
         let base_name = self.base(name);
         let impl_trait = format_ident!("Impl{rust_name}");
@@ -1925,74 +2126,44 @@ impl<'a> ParseTree<'a> {
         let init_methods = s.methods.iter().map(|m| {
             let name = &m.name;
             let name = format_ident!("{name}");
-            quote! {
-                object.#name = Some(#name::<I>);
-            }
+            quote! { #name }
         });
 
-        let wrapped_methods = s.methods.iter().map(|m| {
-                let name = &m.name;
-                let name = format_ident!("{name}");
-                let args = m.inputs.iter().map(|arg| {
-                    let name = make_snake_case_value_name(&arg.name);
-                    let name = format_ident!("{name}");
-                    let ty = self.resolve_type_aliases(arg.ty);
-                    quote! { #name: #ty }
-                });
-                let wrapped_args = m.wrap_cef_args(self);
-                let unwrapped_args = m.unwrap_cef_args(self);
-                let forward_args = m.merge_params(self).filter_map(|arg| match arg {
-                    MergedParam::Single { name, .. } => {
-                        let name = format_ident!("arg_{name}");
-                        Some(quote! { #name })
-                    }
-                    MergedParam::Bounded { slice_name, .. }
-                    | MergedParam::Buffer { slice_name, .. } => {
-                        let name = format_ident!("arg_{slice_name}");
-                        Some(quote! { #name })
+        let wrapped_methods = s
+            .methods
+            .iter()
+            .map(|m| m.get_callback_method(self, &impl_trait));
+
+        let base_ident = format_ident!("{BASE_REF_COUNTED}");
+
+        let handler_chain = self.is_client_handler(name).then(|| {
+            let chain_methods = s
+                .methods
+                .iter()
+                .map(|m| m.get_chain_method(self, &impl_trait));
+
+            quote! {
+                impl #wrap_trait for HandlerChain<#rust_name> {
+                    fn wrap_rc(&mut self, object: *mut RcImpl<#name_ident, Self>) {
+                        self.set_object(object);
                     }
-                    _ => None,
-                });
-                let original_output = m.output.map(|ty| self.resolve_type_aliases(ty));
-                let output = original_output.as_ref().map(|output| {
-                    quote! { -> #output }
-                });
-                let forward_output = original_output.map(|output| {
-                    match syn::parse2::<ModifiedType>(output) {
-                        Ok(ModifiedType { ty, modifiers }) => {
-                            self.cef_name_map
-                                .get(&ty.to_token_stream().to_string())
-                                .and_then(|entry| match entry {
-                                    NameMapEntry {
-                                        ty: NameMapType::StructDeclaration,
-                                        ..
-                                    } => match modifiers.as_slice() {
-                                        [TypeModifier::ConstPtr] => {
-                                            Some(quote! { result.map(|result| result.into()).unwrap_or(std::ptr::null()) })
-                                        }
-                                        [TypeModifier::MutPtr] => {
-                                            Some(quote! { result.map(|result| result.into()).unwrap_or(std::ptr::null_mut()) })
-                                        }
-                                        _ => None,
-                                    }
-                                    _ => None,
-                                })
-                        }
-                        _ => None,
-                    }.unwrap_or(quote! { result.into() })
-                });
+                }
 
-                quote! {
-                    extern "C" fn #name<I: #impl_trait>(#(#args),*) #output {
-                        #wrapped_args
-                        let result = #impl_trait::#name(&arg_self_.interface, #(#forward_args),*);
-                        #unwrapped_args
-                        #forward_output
+                impl #impl_trait for HandlerChain<#rust_name> {
+                    #(#chain_methods)*
+
+                    fn get_raw(&self) -> *mut #name_ident {
+                        self.get_object()
                     }
                 }
-            });
 
-        let base_ident = format_ident!("{BASE_REF_COUNTED}");
+                impl From<HandlerChain<#rust_name>> for #rust_name {
+                    fn from(value: HandlerChain<#rust_name>) -> Self {
+                        Self::new(value)
+                    }
+                }
+            }
+        });
 
         let wrapper = quote! {
             #[derive(Clone)]
@@ -2001,7 +2172,7 @@ impl<'a> ParseTree<'a> {
             impl #rust_name {
                 pub fn new<T>(interface: T) -> Self
                 where
-                    T: #wrap_trait
+                    T: #wrap_trait + 'static
                 {
                     unsafe {
                         let mut cef_object = std::mem::zeroed();
@@ -2012,7 +2183,7 @@ impl<'a> ParseTree<'a> {
                     }
                 }
             }
-            
+
             pub trait #wrap_trait : #impl_trait {
                 fn wrap_rc(&mut self, object: *mut RcImpl<#name_ident, Self>);
             }
@@ -2032,7 +2203,15 @@ impl<'a> ParseTree<'a> {
                 use super::*;
 
                 pub fn init_methods<I: #impl_trait>(object: &mut #name_ident) {
-                    #(#init_methods)*
+                    // The table pairs each field with the callback emitted for it below.
+                    unsafe { init_vtable(object, Methods::<I>::TABLE) };
+                }
+
+                struct Methods<I>(std::marker::PhantomData<I>);
+
+                impl<I: #impl_trait> Methods<I> {
+                    const TABLE: &'static VtableMethods =
+                        vtable_methods!(#name_ident, I, #(#init_methods),*);
                 }
 
                 #(#wrapped_methods)*
@@ -2091,6 +2270,8 @@ impl<'a> ParseTree<'a> {
                     unsafe { std::mem::zeroed() }
                 }
             }
+
+            #handler_chain
         }
         .to_string();
 
@@ -2153,66 +2334,9 @@ impl<'a> ParseTree<'a> {
 
         let name = s.name.as_str();
         let methods = s.methods.iter().map(|m| {
-            let sig = m.get_signature(self);
-            let name = &m.name;
-            let name = format_ident!("{name}");
-            let pre_forward_args = m.unwrap_rust_args(self);
-            let args = m.inputs.iter().map(|arg| {
-                let name = make_snake_case_value_name(&arg.name);
-                let name = format_ident!("arg_{name}");
-                quote! { #name }
-            });
-            let post_forward_args = m.rewrap_rust_args(self);
-            let output_type = m.output.and_then(|ty| {
-                let ty = self.resolve_type_aliases(ty);
-                syn::parse2::<ModifiedType>(ty.to_token_stream()).ok()
-            });
-            let wrap_result = output_type
-                .as_ref()
-                .and_then(|ModifiedType { modifiers, ty, .. }| {
-                    let ty = ty.to_token_stream().to_string();
-                    match modifiers.as_slice() {
-                        [TypeModifier::ConstPtr | TypeModifier::MutPtr]
-                            if ty != quote! { ::std::os::raw::c_void }.to_string() =>
-                        {
-                            match self.cef_name_map.get(&ty) {
-                                Some(NameMapEntry {
-                                    ty: NameMapType::StructDeclaration,
-                                    ..
-                                }) => Some(quote! {
-                                    if result.is_null() {
-                                        None
-                                    } else {
-                                        Some(result.as_wrapper())
-                                    }
-                                }),
-                                _ => None,
-                            }
-                        }
-                        _ => None,
-                    }
-                })
-                .unwrap_or(quote! { result.as_wrapper() });
-            let impl_default = output_type
-                .and_then(|ty| {
-                    (ty.ty.to_token_stream().to_string()
-                        != quote! { ::std::os::raw::c_void }.to_string())
-                    .then(|| quote! { .unwrap_or_default() })
-                })
-                .unwrap_or(quote! { .unwrap_or_else(|| std::mem::zeroed()) });
-            quote! {
-                #sig {
-                    unsafe {
-                        self.0.as_ref().and_then(|this| this.#name).map(|f| {
-                            #pre_forward_args
-                            let result = f(#(#args),*);
-                            #post_forward_args
-                            #wrap_result
-                        })
-                        #impl_default
-                    }
-                }
-            }
+            m.get_forward_method(self, rust_name, |name| {
+                quote! { self.0.as_ref().and_then(|this| this.#name) }
+            })
         });
 
         let base_name = self.base(name);
@@ -2322,72 +2446,13 @@ impl<'a> ParseTree<'a> {
         let init_methods = s.methods.iter().map(|m| {
             let name = &m.name;
             let name = format_ident!("{name}");
-            quote! {
-                object.#name = Some(#name::<I>);
-            }
+            quote! { #name }
         });
 
-        let wrapped_methods = s.methods.iter().map(|m| {
-                let name = &m.name;
-                let name = format_ident!("{name}");
-                let args = m.inputs.iter().map(|arg| {
-                    let name = make_snake_case_value_name(&arg.name);
-                    let name = format_ident!("{name}");
-                    let ty = self.resolve_type_aliases(arg.ty);
-                    quote! { #name: #ty }
-                });
-                let wrapped_args = m.wrap_cef_args(self);
-                let unwrapped_args = m.unwrap_cef_args(self);
-                let forward_args = m.merge_params(self).filter_map(|arg| match arg {
-                    MergedParam::Single { name, .. } => {
-                        let name = format_ident!("arg_{name}");
-                        Some(quote! { #name })
-                    }
-                    MergedParam::Bounded { slice_name, .. }
-                    | MergedParam::Buffer { slice_name, .. } => {
-                        let name = format_ident!("arg_{slice_name}");
-                        Some(quote! { #name })
-                    }
-                    _ => None,
-                });
-                let original_output = m.output.map(|ty| self.resolve_type_aliases(ty));
-                let output = original_output.as_ref().map(|output| {
-                    quote! { -> #output }
-                });
-                let forward_output = original_output.map(|output| {
-                    match syn::parse2::<ModifiedType>(output) {
-                        Ok(ModifiedType { ty, modifiers }) => {
-                            self.cef_name_map
-                                .get(&ty.to_token_stream().to_string())
-                                .and_then(|entry| match entry {
-                                    NameMapEntry {
-                                        ty: NameMapType::StructDeclaration,
-                                        ..
-                                    } => match modifiers.as_slice() {
-                                        [TypeModifier::ConstPtr] => {
-                                            Some(quote! { result.map(|result| result.into()).unwrap_or(std::ptr::null()) })
-                                        }
-                                        [TypeModifier::MutPtr] => {
-                                            Some(quote! { result.map(|result| result.into()).unwrap_or(std::ptr::null_mut()) })
-                                        }
-                                        _ => None,
-                                    }
-                                    _ => None,
-                                })
-                        }
-                        _ => None,
-                    }.unwrap_or(quote! { result.into() })
-                });
-
-                quote! {
-                    extern "C" fn #name<I: #impl_trait>(#(#args),*) #output {
-                        #wrapped_args
-                        let result = #impl_trait::#name(&arg_self_.interface, #(#forward_args),*);
-                        #unwrapped_args
-                        #forward_output
-                    }
-                }
-            });
+        let wrapped_methods = s
+            .methods
+            .iter()
+            .map(|m| m.get_callback_method(self, &impl_trait));
 
         let wrapper = quote! {
             #[derive(Clone, Copy)]
@@ -2408,7 +2473,15 @@ impl<'a> ParseTree<'a> {
                 use super::*;
 
                 pub fn init_methods<I: #impl_trait>(object: &mut #name_ident) {
-                    #(#init_methods)*
+                    // The table pairs each field with the callback emitted for it below.
+                    unsafe { init_vtable(object, Methods::<I>::TABLE) };
+                }
+
+                struct Methods<I>(std::marker::PhantomData<I>);
+
+                impl<I: #impl_trait> Methods<I> {
+                    const TABLE: &'static VtableMethods =
+                        vtable_methods!(#name_ident, I, #(#init_methods),*);
                 }
 
                 #(#wrapped_methods)*
@@ -2658,53 +2731,222 @@ impl<'a> ParseTree<'a> {
         for (rust_name, e) in enum_names {
             let name = &e.name;
             writeln!(f, "\n/// See [{name}] for more documentation.")?;
-            let name = format_ident!("{name}");
-            let rust_name = format_ident!("{rust_name}");
-            let impl_default =
-                e.ty.and_then(|ty| ty.variants.first())
-                    .map(|v| {
-                        let v = &v.ident;
-                        quote! { Self(#name::#v) }
-                    })
-                    .unwrap_or(quote! { unsafe { std::mem::zeroed() } });
-            let wrapper = quote! {
-                #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
-                pub struct #rust_name(#name);
+            let wrapper = match (e.ty, &e.flags) {
+                (Some(ty), _) => Self::make_rust_enum(&rust_name, ty),
+                (None, Some(flags)) => Self::make_flags(&rust_name, name, flags),
+                (None, None) => continue,
+            };
+            writeln!(f, "{wrapper}")?;
+        }
+        Ok(())
+    }
+
+    /// A Rust enum with a variant for each value of `ty`, for enums which hold one value at a time.
+    /// Values which the bindings don't know convert to the first variant and report a diagnostic.
+    fn make_rust_enum(rust_name: &str, ty: &syn::ItemEnum) -> String {
+        let name = &ty.ident;
+        let rust_name = format_ident!("{rust_name}");
+        let repr = ty
+            .attrs
+            .iter()
+            .find(|attr| attr.path().is_ident("repr"))
+            .and_then(|attr| attr.parse_args::<syn::Ident>().ok())
+            .unwrap_or_else(|| format_ident!("u32"));
+        let variant_names = make_variant_names(
+            &ty.variants
+                .iter()
+                .map(|v| v.ident.to_string())
+                .collect::<Vec<_>>(),
+        );
+        let variants: Vec<_> = ty
+            .variants
+            .iter()
+            .zip(variant_names)
+            .filter_map(|(v, rust_variant)| {
+                let (_, discriminant) = v.discriminant.as_ref()?;
+                Some((&v.ident, format_ident!("{rust_variant}"), discriminant))
+            })
+            .collect();
+        // The first variant is the default, like the zeroed C enum usually is.
+        let declarations =
+            variants
+                .iter()
+                .enumerate()
+                .map(|(index, (_, rust_variant, discriminant))| {
+                    let default = (index == 0).then(|| quote! { #[default] });
+                    quote! { #default #rust_variant = #discriminant }
+                });
+        let try_from_arms = variants.iter().map(|(_, rust_variant, discriminant)| {
+            quote! { #discriminant => Ok(Self::#rust_variant) }
+        });
+        let into_arms = variants.iter().map(|(variant, rust_variant, _)| {
+            quote! { Self::#rust_variant => #name::#variant }
+        });
+        quote! {
+            #[repr(#repr)]
+            #[non_exhaustive]
+            #[derive(Debug, Default, Copy, Clone, Hash, PartialEq, Eq)]
+            pub enum #rust_name {
+                #(#declarations,)*
+            }
+
+            impl TryFrom<#repr> for #rust_name {
+                type Error = #repr;
 
-                impl AsRef<#name> for #rust_name {
-                    fn as_ref(&self) -> &#name {
-                        &self.0
+                fn try_from(value: #repr) -> std::result::Result<Self, #repr> {
+                    match value {
+                        #(#try_from_arms,)*
+                        value => Err(value),
                     }
                 }
+            }
 
-                impl AsMut<#name> for #rust_name {
-                    fn as_mut(&mut self) -> &mut #name {
-                        &mut self.0
-                    }
+            impl From<#name> for #rust_name {
+                fn from(value: #name) -> Self {
+                    Self::try_from(value as #repr).unwrap_or_else(|value| {
+                        crate::diagnostics::unknown_enum_value(value, Self::default())
+                    })
                 }
+            }
 
-                impl From<#name> for #rust_name {
-                    fn from(value: #name) -> Self {
-                        Self(value)
+            impl Into<#name> for #rust_name {
+                fn into(self) -> #name {
+                    match self {
+                        #(#into_arms,)*
                     }
                 }
+            }
+        }
+        .to_string()
+    }
 
-                impl Into<#name> for #rust_name {
-                    fn into(self) -> #name {
-                        self.0
+    /// A set of flags for a bindgen `--bitfield-enum`, with a constant for each value of the C enum
+    /// and the bit operations. It keeps bits without a constant, so they survive the round trip
+    /// through the C enum.
+    fn make_flags(rust_name: &str, name: &str, flags: &FlagsRef) -> String {
+        let name = format_ident!("{name}");
+        let rust_name = format_ident!("{rust_name}");
+        let bits = flags.bits;
+        let constant_names = make_constant_names(
+            &flags
+                .constants
+                .iter()
+                .map(|constant| constant.ident.to_string())
+                .collect::<Vec<_>>(),
+        );
+        let constants =
+            flags
+                .constants
+                .iter()
+                .zip(constant_names)
+                .map(|(constant, rust_constant)| {
+                    let docs = constant
+                        .attrs
+                        .iter()
+                        .filter(|attr| attr.path().is_ident("doc"));
+                    let constant = &constant.ident;
+                    let rust_constant = format_ident!("{rust_constant}");
+                    quote! {
+                        #(#docs)*
+                        pub const #rust_constant: Self = Self(#name::#constant);
                     }
+                });
+        quote! {
+            #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+            pub struct #rust_name(#name);
+
+            impl #rust_name {
+                #(#constants)*
+
+                /// No flags set.
+                pub const fn empty() -> Self {
+                    Self(#name(0))
                 }
 
-                impl Default for #rust_name {
-                    fn default() -> Self {
-                        #impl_default
-                    }
+                /// The flags as an integer, including bits without a constant.
+                pub const fn bits(self) -> #bits {
+                    self.0 .0
+                }
+
+                /// The flags of `bits`, including bits without a constant.
+                pub const fn from_bits(bits: #bits) -> Self {
+                    Self(#name(bits))
+                }
+
+                pub const fn is_empty(self) -> bool {
+                    self.bits() == 0
+                }
+
+                /// `true` if every flag of `other` is set.
+                pub const fn contains(self, other: Self) -> bool {
+                    self.bits() & other.bits() == other.bits()
+                }
+
+                /// `true` if any flag of `other` is set.
+                pub const fn intersects(self, other: Self) -> bool {
+                    self.bits() & other.bits() != 0
+                }
+            }
+
+            impl std::ops::BitOr for #rust_name {
+                type Output = Self;
+
+                fn bitor(self, other: Self) -> Self {
+                    Self::from_bits(self.bits() | other.bits())
+                }
+            }
+
+            impl std::ops::BitOrAssign for #rust_name {
+                fn bitor_assign(&mut self, other: Self) {
+                    *self = *self | other;
+                }
+            }
+
+            impl std::ops::BitAnd for #rust_name {
+                type Output = Self;
+
+                fn bitand(self, other: Self) -> Self {
+                    Self::from_bits(self.bits() & other.bits())
+                }
+            }
+
+            impl std::ops::BitAndAssign for #rust_name {
+                fn bitand_assign(&mut self, other: Self) {
+                    *self = *self & other;
+                }
+            }
+
+            impl AsRef<#name> for #rust_name {
+                fn as_ref(&self) -> &#name {
+                    &self.0
+                }
+            }
+
+            impl AsMut<#name> for #rust_name {
+                fn as_mut(&mut self) -> &mut #name {
+                    &mut self.0
+                }
+            }
+
+            impl From<#name> for #rust_name {
+                fn from(value: #name) -> Self {
+                    Self(value)
+                }
+            }
+
+            impl Into<#name> for #rust_name {
+                fn into(self) -> #name {
+                    self.0
+                }
+            }
+
+            impl Default for #rust_name {
+                fn default() -> Self {
+                    Self::empty()
                 }
             }
-            .to_string();
-            writeln!(f, "{wrapper}")?;
         }
-        Ok(())
+        .to_string()
     }
 
     pub fn write_globals(&self, f: &mut Formatter<'_>) -> fmt::Result {
@@ -2781,6 +3023,79 @@ impl<'a> ParseTree<'a> {
         }
         Ok(())
     }
+
+    pub fn write_abi(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let api_hash = match &self.api_hash {
+            Some(api_hash) => quote! { Some(#api_hash) },
+            None => quote! { None },
+        };
+        let api_hash = quote! {
+            pub(crate) const CEF_API_HASH_UNIVERSAL: Option<&str> = #api_hash;
+        }
+        .to_string();
+        writeln!(
+            f,
+            "\n/// `CEF_API_HASH_UNIVERSAL` of the headers the bindings were generated from, if bindgen read\n/// `cef_api_hash.h`."
+        )?;
+        writeln!(f, "{api_hash}")?;
+
+        // `quote` can't write `$crate`, so write the macro as text.
+        writeln!(
+            f,
+            "\n/// Check the size of each CEF struct against the headers the bindings were generated from."
+        )?;
+        writeln!(f, "#[doc(hidden)]\n#[macro_export]")?;
+        writeln!(f, "macro_rules! __assert_cef_struct_sizes {{\n    () => {{")?;
+        for (name, size) in self.struct_sizes.iter() {
+            writeln!(
+                f,
+                "        const _: () = assert!(\n            ::std::mem::size_of::<$crate::sys::{name}>() == {size},\n            \"the size of {name} differs from the CEF headers\"\n        );"
+            )?;
+        }
+        writeln!(f, "    }};\n}}")
+    }
+
+    pub fn write_threads(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let arms = self
+            .struct_declarations
+            .iter()
+            .filter(|s| self.root(&s.name) == BASE_REF_COUNTED)
+            .filter_map(|s| match self.cef_name_map.get(&s.name) {
+                Some(NameMapEntry {
+                    name: rust_name,
+                    ty: NameMapType::StructDeclaration,
+                }) => Some((s, rust_name)),
+                _ => None,
+            })
+            .flat_map(|(s, rust_name)| {
+                let struct_thread = doc_thread(&s.doc);
+                s.methods.iter().filter_map(move |m| {
+                    let Some(DocThread::Thread(thread)) = doc_thread(&m.doc).or(struct_thread)
+                    else {
+                        return None;
+                    };
+                    let method = &m.name;
+                    let thread = format_ident!("{thread}");
+                    Some(quote! {
+                        (#rust_name, #method) => Some(cef_thread_id_t::#thread),
+                    })
+                })
+            });
+        let callback_thread = quote! {
+            pub(crate) fn callback_thread(handler: &str, method: &str) -> Option<cef_thread_id_t> {
+                match (handler, method) {
+                    #(#arms)*
+                    _ => None,
+                }
+            }
+        }
+        .to_string();
+        writeln!(
+            f,
+            "\n/// The thread the header docs say CEF calls `method` of `handler` on, e.g. `TID_IO` for\n/// `ResourceRequestHandler` and `on_before_resource_load`."
+        )?;
+        writeln!(f, "{callback_thread}")
+    }
 }
 
 impl<'a> Display for ParseTree<'a> {
@@ -2789,7 +3104,9 @@ impl<'a> Display for ParseTree<'a> {
         self.write_aliases(f)?;
         self.write_structs(f)?;
         self.write_enums(f)?;
-        self.write_globals(f)
+        self.write_globals(f)?;
+        self.write_abi(f)?;
+        self.write_threads(f)
     }
 }
 
@@ -2816,12 +3133,38 @@ impl<'a> From<&'a syn::File> for ParseTree<'a> {
                 syn::Item::Enum(e) => Some(EnumRef {
                     name: e.ident.to_string(),
                     ty: Some(e),
+                    flags: None,
                 }),
                 syn::Item::Struct(item_struct) => match &item_struct.fields {
-                    syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1 => Some(EnumRef {
-                        name: item_struct.ident.to_string(),
-                        ty: None,
-                    }),
+                    syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                        let name = item_struct.ident.to_string();
+                        let constants = value
+                            .items
+                            .iter()
+                            .filter_map(|item| match item {
+                                syn::Item::Impl(item_impl) if item_impl.trait_.is_none() => {
+                                    Some(item_impl)
+                                }
+                                _ => None,
+                            })
+                            .filter(|item_impl| {
+                                item_impl.self_ty.to_token_stream().to_string() == name
+                            })
+                            .flat_map(|item_impl| item_impl.items.iter())
+                            .filter_map(|item| match item {
+                                syn::ImplItem::Const(constant) => Some(constant),
+                                _ => None,
+                            })
+                            .collect();
+                        Some(EnumRef {
+                            name,
+                            ty: None,
+                            flags: fields.unnamed.first().map(|field| FlagsRef {
+                                bits: &field.ty,
+                                constants,
+                            }),
+                        })
+                    }
                     _ => None,
                 },
                 _ => None,
@@ -2847,6 +3190,7 @@ impl<'a> From<&'a syn::File> for ParseTree<'a> {
 
                         Some(StructDeclarationRef {
                             name: item_struct.ident.to_string(),
+                            doc: doc_comment(&item_struct.attrs),
                             fields,
                             methods,
                         })
@@ -2875,6 +3219,7 @@ impl<'a> From<&'a syn::File> for ParseTree<'a> {
             .flat_map(|items| {
                 items.iter().filter_map(|item| match item {
                     syn::ForeignItem::Fn(syn::ForeignItemFn {
+                        attrs,
                         sig:
                             syn::Signature {
                                 ident,
@@ -2906,6 +3251,7 @@ impl<'a> From<&'a syn::File> for ParseTree<'a> {
                             syn::ReturnType::Default => None,
                             syn::ReturnType::Type(_, ty) => Some(ty.as_ref()),
                         },
+                        doc: doc_comment(attrs),
                         merged_params: Default::default(),
                     }),
                     _ => None,
@@ -2997,6 +3343,42 @@ impl<'a> From<&'a syn::File> for ParseTree<'a> {
             })
             .collect();
 
+        static SIZE_PATTERN: OnceLock<Regex> = OnceLock::new();
+        let size_pattern = SIZE_PATTERN.get_or_init(|| {
+            Regex::new(r#""Size of (_?cef_\w+)"\s*\].*?-\s*(\d+)\s*usize"#).unwrap()
+        });
+        tree.struct_sizes = value
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                syn::Item::Const(item_const) if item_const.ident == "_" => {
+                    Some(item_const.expr.to_token_stream().to_string())
+                }
+                _ => None,
+            })
+            .filter_map(|layout| {
+                let captures = size_pattern.captures(&layout)?;
+                Some((captures[1].to_string(), captures[2].parse().ok()?))
+            })
+            .collect();
+
+        tree.api_hash = value.items.iter().find_map(|item| match item {
+            syn::Item::Const(syn::ItemConst { ident, expr, .. })
+                if ident == "CEF_API_HASH_UNIVERSAL" =>
+            {
+                match expr.as_ref() {
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::ByteStr(value),
+                        ..
+                    }) => String::from_utf8(value.value())
+                        .ok()
+                        .map(|value| value.trim_end_matches('\0').to_string()),
+                    _ => None,
+                }
+            }
+            _ => None,
+        });
+
         tree
     }
 }
@@ -3008,6 +3390,78 @@ fn format_bindings(source_path: &Path) -> crate::Result<()> {
     Ok(())
 }
 
+/// The `#[doc = "..."]` attributes bindgen copied from the header, joined into one line.
+fn doc_comment(attrs: &[syn::Attribute]) -> String {
+    attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                path,
+                value:
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(doc),
+                        ..
+                    }),
+                ..
+            }) if path.is_ident("doc") => Some(doc.value()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The thread a header doc comment says a method is called on, e.g. "Called on the IO thread".
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum DocThread {
+    /// The variant of `cef_thread_id_t`.
+    Thread(&'static str),
+    /// Any or several threads, or one without a `cef_thread_id_t`, e.g. the audio stream thread.
+    Unknown,
+}
+
+/// The thread `doc` says something is called on, if it names any, e.g. "Called on the IO thread"
+/// or "will be called on the browser process UI thread". [DocThread::Unknown] if it names more
+/// than one.
+fn doc_thread(doc: &str) -> Option<DocThread> {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    let pattern = PATTERN.get_or_init(|| {
+        Regex::new(concat!(
+            r"(?i)\b(?:called(?: \w+){0,4}?|and) (?:on|from) ",
+            r"(?:the |a |an )?((?:\w+ ){0,3}?\w+) threads?\b( or)?",
+        ))
+        .unwrap()
+    });
+    pattern
+        .captures_iter(doc)
+        .map(|captures| {
+            let thread = captures.get(1).map_or("", |thread| thread.as_str());
+            let words: Vec<_> = thread.split(' ').collect();
+            if captures.get(2).is_some() || words.contains(&"or") {
+                DocThread::Unknown
+            } else if words.contains(&"UI") {
+                DocThread::Thread("TID_UI")
+            } else if words.contains(&"IO") {
+                DocThread::Thread("TID_IO")
+            } else if thread.ends_with("render process main") {
+                DocThread::Thread("TID_RENDERER")
+            } else if thread.ends_with("process launcher") {
+                DocThread::Thread("TID_PROCESS_LAUNCHER")
+            } else {
+                DocThread::Unknown
+            }
+        })
+        .reduce(|first, next| {
+            if first == next {
+                first
+            } else {
+                DocThread::Unknown
+            }
+        })
+}
+
 fn make_rust_type_name(name: &str) -> Option<String> {
     static PATTERN: OnceLock<Regex> = OnceLock::new();
     let pattern = PATTERN.get_or_init(|| Regex::new(r"^_?cef_(\w+)_t$").unwrap());
@@ -3028,5 +3482,170 @@ fn make_rust_type_name(name: &str) -> Option<String> {
 }
 
 fn make_snake_case_value_name(name: &str) -> String {
-    name.from_case(Case::Camel).to_case(Case::Snake)
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    let pattern = PATTERN.get_or_init(|| Regex::new(r"^[a-z][a-z0-9_]*$").unwrap());
+    if pattern.is_match(name) {
+        return name.to_string();
+    }
+
+    // Keep digits with the preceding word, e.g. `getV8Context` -> `get_v8_context`.
+    name.from_case(Case::Camel)
+        .without_boundaries(&[
+            Boundary::LowerDigit,
+            Boundary::UpperDigit,
+            Boundary::DigitLower,
+        ])
+        .to_case(Case::Snake)
+}
+
+/// Split the C names of enum values into words, without the words they all start with. Names
+/// which would start with a digit keep the last of those words.
+fn strip_common_prefix(variants: &[String]) -> Vec<Vec<&str>> {
+    let words: Vec<Vec<&str>> = variants.iter().map(|v| v.split('_').collect()).collect();
+    let shortest = words.iter().map(Vec::len).min().unwrap_or_default();
+    let prefix = (0..shortest.saturating_sub(1))
+        .take_while(|&i| words.iter().all(|w| w[i] == words[0][i]))
+        .count();
+    words
+        .into_iter()
+        .map(|words| {
+            let start = if words[prefix].starts_with(|c: char| c.is_ascii_digit()) {
+                prefix.saturating_sub(1)
+            } else {
+                prefix
+            };
+            words[start..].to_vec()
+        })
+        .collect()
+}
+
+/// The names of the flag constants, `SCREAMING_SNAKE_CASE` without the common prefix.
+fn make_constant_names(constants: &[String]) -> Vec<String> {
+    strip_common_prefix(constants)
+        .into_iter()
+        .map(|words| words.join("_"))
+        .collect()
+}
+
+/// The Rust names of the `variants` of a C enum, without the prefix they share, e.g.
+/// `LOGSEVERITY_ERROR` -> `Error`. A name which would start with a digit keeps the last word of
+/// the prefix, e.g. `CEF_CHANNEL_LAYOUT_5_1` -> `Layout5_1`.
+fn make_variant_names(variants: &[String]) -> Vec<String> {
+    strip_common_prefix(variants)
+        .into_iter()
+        .map(|words| {
+            let mut name = String::new();
+            for word in words {
+                let ends_with_digit = name.ends_with(|c: char| c.is_ascii_digit());
+                if ends_with_digit && word.starts_with(|c: char| c.is_ascii_digit()) {
+                    // Keep `5_1` apart from `51`.
+                    name.push('_');
+                }
+                let mut chars = word.chars();
+                if let Some(first) = chars.next() {
+                    name.push(first.to_ascii_uppercase());
+                    name.extend(chars.map(|c| c.to_ascii_lowercase()));
+                }
+            }
+            name
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_snake_case_value_name() {
+        assert_eq!(make_snake_case_value_name("getV8Context"), "get_v8_context");
+        assert_eq!(make_snake_case_value_name("self_"), "self_");
+        assert_eq!(
+            make_snake_case_value_name("get_v8_context"),
+            "get_v8_context"
+        );
+        assert_eq!(
+            make_snake_case_value_name("getHTTPStatusCode"),
+            "get_http_status_code"
+        );
+        assert_eq!(make_snake_case_value_name("frameId"), "frame_id");
+    }
+
+    #[test]
+    fn test_variant_names() {
+        let names = |variants: &[&str]| {
+            make_variant_names(&variants.iter().map(|v| v.to_string()).collect::<Vec<_>>())
+        };
+        assert_eq!(
+            names(&["LOGSEVERITY_DEFAULT", "LOGSEVERITY_ERROR"]),
+            ["Default", "Error"]
+        );
+        assert_eq!(
+            names(&["CEF_COLOR_TYPE_RGBA_8888", "CEF_COLOR_TYPE_BGRA_8888"]),
+            ["Rgba8888", "Bgra8888"]
+        );
+        assert_eq!(
+            names(&[
+                "CEF_CHANNEL_LAYOUT_NONE",
+                "CEF_CHANNEL_LAYOUT_5_1",
+                "CEF_CHANNEL_LAYOUT_51"
+            ]),
+            ["None", "Layout5_1", "Layout51"]
+        );
+        assert_eq!(names(&["PID_BROWSER"]), ["Browser"]);
+        assert_eq!(
+            names(&["TID_UI", "TID_FILE_BACKGROUND"]),
+            ["Ui", "FileBackground"]
+        );
+    }
+
+    #[test]
+    fn test_constant_names() {
+        let names = |constants: &[&str]| {
+            make_constant_names(&constants.iter().map(|c| c.to_string()).collect::<Vec<_>>())
+        };
+        assert_eq!(
+            names(&["EVENTFLAG_NONE", "EVENTFLAG_CAPS_LOCK_ON"]),
+            ["NONE", "CAPS_LOCK_ON"]
+        );
+        assert_eq!(
+            names(&["DRAG_OPERATION_NONE", "DRAG_OPERATION_COPY"]),
+            ["NONE", "COPY"]
+        );
+    }
+
+    #[test]
+    fn test_doc_thread() {
+        use DocThread::*;
+
+        assert_eq!(
+            doc_thread("Called on the IO thread before a resource request is loaded."),
+            Some(Thread("TID_IO"))
+        );
+        assert_eq!(
+            doc_thread("This function will be called on the browser process UI thread."),
+            Some(Thread("TID_UI"))
+        );
+        assert_eq!(
+            doc_thread("This function is called on the render process main thread."),
+            Some(Thread("TID_RENDERER"))
+        );
+        assert_eq!(
+            doc_thread(
+                "Will be called on the browser process UI thread when launching a render process \
+                 and on the browser process IO thread when launching a GPU process."
+            ),
+            Some(Unknown)
+        );
+        assert_eq!(
+            doc_thread("Called on the browser process UI thread or render process main thread."),
+            Some(Unknown)
+        );
+        assert_eq!(doc_thread("Called from any thread."), Some(Unknown));
+        assert_eq!(
+            doc_thread("The callback will be executed on the UI thread."),
+            None
+        );
+        assert_eq!(doc_thread("Called when the title changes."), None);
+    }
 }
@@ -0,0 +1,70 @@
+use regex::Regex;
+use std::{fmt::Write, fs, path::Path};
+
+/// The CEF versions which changed signatures that the hand-written wrappers of the `cef` crate
+/// depend on. The build script of `cef` turns each one up to the version of the bindings into a
+/// `cef_api_at_least = "<major>"` cfg, see the `compat` module of `cef`.
+const API_MILESTONES: &[(u32, &str)] = &[
+    (122, "frame identifiers are strings instead of i64"),
+    (
+        125,
+        "WindowInfo::runtime_style selects Chrome or Alloy style per browser",
+    ),
+];
+
+/// The version of the CEF headers in `cef_path`, as `(major, minor, patch)`.
+pub fn read_version(cef_path: &Path) -> crate::Result<(u32, u32, u32)> {
+    let header_path = cef_path.join("include/cef_version.h");
+    let header = crate::read_bindings(&header_path)?;
+    let define = |name: &str| -> crate::Result<u32> {
+        let pattern = Regex::new(&format!(r"#define {name} (\d+)"))?;
+        pattern
+            .captures(&header)
+            .and_then(|captures| captures[1].parse().ok())
+            .ok_or_else(|| crate::Error::MissingPath(header_path.clone()))
+    };
+    Ok((
+        define("CEF_VERSION_MAJOR")?,
+        define("CEF_VERSION_MINOR")?,
+        define("CEF_VERSION_PATCH")?,
+    ))
+}
+
+/// The source of `sys/src/version.rs`, which the build script of the sys crate includes too.
+pub fn generate_version((major, minor, patch): (u32, u32, u32)) -> String {
+    let mut source = String::from(
+        "// Generated by update-bindings from include/cef_version.h, don't edit it by hand.\n\n",
+    );
+    let _ = writeln!(source, "pub const CEF_VERSION_MAJOR: u32 = {major};");
+    let _ = writeln!(source, "pub const CEF_VERSION_MINOR: u32 = {minor};");
+    let _ = writeln!(source, "pub const CEF_VERSION_PATCH: u32 = {patch};");
+    source.push_str(
+        "\n/// The CEF versions which changed the API of the safe wrappers, each one a\n\
+         /// `cef_api_at_least` cfg of the cef crate:\n///\n",
+    );
+    for (version, change) in API_MILESTONES {
+        let _ = writeln!(source, "/// - {version}: {change}");
+    }
+    let milestones: Vec<_> = API_MILESTONES
+        .iter()
+        .map(|(version, _)| version.to_string())
+        .collect();
+    let _ = writeln!(
+        source,
+        "pub const CEF_API_MILESTONES: &[u32] = &[{}];",
+        milestones.join(", ")
+    );
+    source
+}
+
+/// Write `sys/src/version.rs` if the version or the milestones changed.
+pub fn update_version(cef_path: &Path, sys_dir: &Path) -> crate::Result<()> {
+    let source = generate_version(read_version(cef_path)?);
+    let version_path = sys_dir.join("src/version.rs");
+    let dest = crate::read_bindings(&version_path).unwrap_or_default();
+    if source != dest {
+        fs::write(&version_path, source)?;
+        println!("Updated: {}", version_path.display());
+    }
+    Ok(())
+}